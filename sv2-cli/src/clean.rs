@@ -0,0 +1,190 @@
+//! Finds and removes files left behind by running the daemon: component logs,
+//! generated configs and test Bitcoin datadirs, plus (with `--all`) the sv2d
+//! config directory and database.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Locations the daemon writes to
+pub struct CleanRoots {
+    /// Where component logs, generated configs and Bitcoin datadirs go
+    pub tmp_dir: PathBuf,
+    /// `~/.sv2d`
+    pub config_dir: PathBuf,
+    /// Directory the daemon runs from, which holds `sv2d.db`
+    pub work_dir: PathBuf,
+}
+
+impl CleanRoots {
+    pub fn from_env() -> Self {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        Self {
+            tmp_dir: PathBuf::from("/tmp"),
+            config_dir: PathBuf::from(home).join(".sv2d"),
+            work_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanTarget {
+    pub path: PathBuf,
+    pub description: &'static str,
+    /// Mainnet Bitcoin datadir, which needs its own confirmation
+    pub mainnet_datadir: bool,
+}
+
+/// Existing files and directories that `clean` would remove
+pub fn find_targets(roots: &CleanRoots, all: bool) -> Vec<CleanTarget> {
+    let mut targets = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&roots.tmp_dir) {
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+
+        for name in names {
+            let description = if name.starts_with("sv2d-") && name.ends_with(".log") {
+                "component log"
+            } else if name.starts_with("pool_") && name.ends_with(".toml") {
+                "generated pool config"
+            } else if name == "translator_sv2d.toml" {
+                "generated translator config"
+            } else if name.starts_with("bitcoin_") {
+                "Bitcoin datadir"
+            } else {
+                continue;
+            };
+
+            targets.push(CleanTarget {
+                mainnet_datadir: matches!(name.as_str(), "bitcoin_mainnet" | "bitcoin_main"),
+                path: roots.tmp_dir.join(name),
+                description,
+            });
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(&roots.config_dir) {
+        let mut logs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+            .collect();
+        logs.sort();
+        if !all {
+            targets.extend(logs.into_iter().map(|path| CleanTarget {
+                path,
+                description: "daemon log",
+                mainnet_datadir: false,
+            }));
+        }
+    }
+
+    if all {
+        push_if_exists(&mut targets, roots.config_dir.clone(), "sv2d config directory");
+        for suffix in ["", "-wal", "-shm"] {
+            push_if_exists(&mut targets, roots.work_dir.join(format!("sv2d.db{}", suffix)), "database");
+        }
+        push_if_exists(
+            &mut targets,
+            roots.work_dir.join("config").join("translator_config.WORKING.toml"),
+            "generated translator config",
+        );
+    }
+
+    targets
+}
+
+fn push_if_exists(targets: &mut Vec<CleanTarget>, path: PathBuf, description: &'static str) {
+    if path.exists() {
+        targets.push(CleanTarget { path, description, mainnet_datadir: false });
+    }
+}
+
+pub fn remove_target(path: &Path) -> Result<()> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roots(base: &Path) -> CleanRoots {
+        let roots = CleanRoots {
+            tmp_dir: base.join("tmp"),
+            config_dir: base.join("home/.sv2d"),
+            work_dir: base.join("work"),
+        };
+        for dir in [&roots.tmp_dir, &roots.config_dir, &roots.work_dir] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(roots.tmp_dir.join("sv2d-pool.log"), "").unwrap();
+        fs::write(roots.tmp_dir.join("pool_signet.toml"), "").unwrap();
+        fs::write(roots.tmp_dir.join("unrelated.txt"), "").unwrap();
+        fs::create_dir_all(roots.tmp_dir.join("bitcoin_signet/signet")).unwrap();
+        fs::create_dir_all(roots.tmp_dir.join("bitcoin_mainnet")).unwrap();
+        fs::write(roots.config_dir.join("config.toml"), "").unwrap();
+        fs::write(roots.config_dir.join("sv2d.log"), "").unwrap();
+        fs::write(roots.work_dir.join("sv2d.db"), "").unwrap();
+        roots
+    }
+
+    fn paths(targets: &[CleanTarget], base: &Path) -> Vec<String> {
+        targets
+            .iter()
+            .map(|t| t.path.strip_prefix(base).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_default_keeps_config_and_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let targets = find_targets(&roots(dir.path()), false);
+
+        assert_eq!(
+            paths(&targets, dir.path()),
+            vec![
+                "tmp/bitcoin_mainnet",
+                "tmp/bitcoin_signet",
+                "tmp/pool_signet.toml",
+                "tmp/sv2d-pool.log",
+                "home/.sv2d/sv2d.log",
+            ]
+        );
+        let mainnet: Vec<_> = targets.iter().filter(|t| t.mainnet_datadir).collect();
+        assert_eq!(mainnet.len(), 1);
+        assert!(mainnet[0].path.ends_with("bitcoin_mainnet"));
+    }
+
+    #[test]
+    fn test_all_includes_config_and_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let targets = find_targets(&roots(dir.path()), true);
+        let paths = paths(&targets, dir.path());
+
+        assert!(paths.contains(&"home/.sv2d".to_string()));
+        assert!(paths.contains(&"work/sv2d.db".to_string()));
+        // The config directory is removed whole, so its logs aren't listed separately
+        assert!(!paths.contains(&"home/.sv2d/sv2d.log".to_string()));
+    }
+
+    #[test]
+    fn test_remove_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots = roots(dir.path());
+        for target in find_targets(&roots, true) {
+            remove_target(&target.path).unwrap();
+        }
+
+        assert!(find_targets(&roots, true).is_empty());
+        assert!(roots.tmp_dir.join("unrelated.txt").exists());
+    }
+}
@@ -16,6 +16,7 @@ pub struct ApiClientConfig {
     pub base_url: Url,
     pub timeout: Duration,
     pub api_key: Option<String>,
+    pub connect_retry: ConnectRetry,
 }
 
 impl Default for ApiClientConfig {
@@ -24,10 +25,93 @@ impl Default for ApiClientConfig {
             base_url: Url::parse("http://localhost:8080").unwrap(),
             timeout: Duration::from_secs(30),
             api_key: None,
+            connect_retry: ConnectRetry::default(),
         }
     }
 }
 
+/// Retries for requests sent while the daemon may still be binding its port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectRetry {
+    /// Attempts in total, including the first
+    pub attempts: u32,
+    /// Wait before the second attempt, doubled before each further one
+    pub initial_backoff: Duration,
+}
+
+impl ConnectRetry {
+    /// One attempt, for probes that only ask whether the daemon is up
+    pub fn none() -> Self {
+        Self { attempts: 1, initial_backoff: Duration::ZERO }
+    }
+}
+
+impl Default for ConnectRetry {
+    /// About 1.5s of retries, enough for sv2d to bind after `sv2-cli start`
+    fn default() -> Self {
+        Self { attempts: 5, initial_backoff: Duration::from_millis(100) }
+    }
+}
+
+/// Why the daemon couldn't be reached after retrying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaemonUnavailable {
+    /// Nothing accepted a connection on any attempt
+    NotRunning { url: String },
+    /// Something is listening but didn't answer requests yet
+    Starting { url: String },
+}
+
+impl std::fmt::Display for DaemonUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DaemonUnavailable::NotRunning { url } => {
+                write!(f, "sv2d is not running at {} (connection refused). Start it with 'sv2-cli start'", url)
+            }
+            DaemonUnavailable::Starting { url } => {
+                write!(f, "sv2d at {} is still starting up; try again in a moment", url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DaemonUnavailable {}
+
+/// Send `request`, retrying while the daemon isn't accepting connections yet.
+/// Gives up with a [`DaemonUnavailable`] error once `retry` is used up.
+pub async fn send_with_retry(request: reqwest::RequestBuilder, retry: ConnectRetry) -> Result<reqwest::Response> {
+    let mut backoff = retry.initial_backoff;
+    let mut starting = false;
+    let mut attempt = 1;
+    loop {
+        let this_attempt = request.try_clone().context("Request can't be retried")?;
+        match this_attempt.send().await {
+            // Still bringing its API up
+            Ok(response) if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE && attempt < retry.attempts => {
+                starting = true;
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if e.is_connect() || e.is_request() || e.is_timeout() => {
+                // Refused means nothing is bound yet; anything later means it's coming up
+                starting |= !e.is_connect();
+                if attempt >= retry.attempts {
+                    let url = e.url().map(|url| url.origin().ascii_serialization()).unwrap_or_default();
+                    let unavailable = if starting {
+                        DaemonUnavailable::Starting { url }
+                    } else {
+                        DaemonUnavailable::NotRunning { url }
+                    };
+                    return Err(anyhow::Error::new(e).context(unavailable));
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+        attempt += 1;
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
 /// API client for communicating with sv2d daemon
 #[derive(Debug, Clone)]
 pub struct ApiClient {
@@ -110,6 +194,11 @@ impl ApiClient {
             .with_context(|| format!("Failed to build URL for path: {}", path))
     }
 
+    /// Send `request`, retrying while the daemon is still starting
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        send_with_retry(request, self.config.connect_retry).await
+    }
+
     /// Build a request with common headers
     fn build_request(&self, method: reqwest::Method, url: Url) -> reqwest::RequestBuilder {
         let mut request = self.client.request(method, url);
@@ -124,9 +213,7 @@ impl ApiClient {
     /// Check if the daemon is reachable
     pub async fn ping(&self) -> Result<bool> {
         let url = self.build_url("/api/v1/health")?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await;
+        let response = send_with_retry(self.build_request(reqwest::Method::GET, url), ConnectRetry::none()).await;
 
         match response {
             Ok(resp) => Ok(resp.status().is_success()),
@@ -137,10 +224,7 @@ impl ApiClient {
     /// Get daemon status
     pub async fn get_status(&self) -> Result<DaemonStatus> {
         let url = self.build_url("/api/v1/status")?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send status request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -158,10 +242,7 @@ impl ApiClient {
     /// Get active connections
     pub async fn get_connections(&self) -> Result<Vec<ConnectionInfo>> {
         let url = self.build_url("/api/v1/connections")?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send connections request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -179,10 +260,7 @@ impl ApiClient {
     /// Get connection by ID
     pub async fn get_connection(&self, id: Uuid) -> Result<ConnectionInfo> {
         let url = self.build_url(&format!("/api/v1/connections/{}", id))?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send connection request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -211,10 +289,7 @@ impl ApiClient {
         }
         drop(query_pairs);
 
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send shares request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -238,10 +313,7 @@ impl ApiClient {
             query_pairs.append_pair("connection_id", &conn_id.to_string());
         }
 
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send share stats request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -265,10 +337,7 @@ impl ApiClient {
             query_pairs.append_pair("limit", &limit.to_string());
         }
 
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send metrics request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -292,10 +361,7 @@ impl ApiClient {
             query_pairs.append_pair("limit", &limit.to_string());
         }
 
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send templates request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -319,10 +385,7 @@ impl ApiClient {
             query_pairs.append_pair("limit", &limit.to_string());
         }
 
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send alerts request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -340,10 +403,7 @@ impl ApiClient {
     /// Get mining statistics
     pub async fn get_mining_stats(&self) -> Result<MiningStats> {
         let url = self.build_url("/api/v1/mining/stats")?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send mining stats request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -361,10 +421,7 @@ impl ApiClient {
     /// Get current configuration
     pub async fn get_config(&self) -> Result<DaemonConfig> {
         let url = self.build_url("/api/v1/config")?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .context("Failed to send config request")?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -391,11 +448,9 @@ impl ApiClient {
             validate_only: Some(validate_only),
         };
 
-        let response = self.build_request(reqwest::Method::POST, url)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send config update request")?;
+        let response = self.send(self.build_request(reqwest::Method::POST, url)
+            .json(&request))
+            .await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -425,10 +480,7 @@ impl ApiClient {
         };
 
         let url = self.build_url(endpoint)?;
-        let response = self.build_request(reqwest::Method::POST, url)
-            .send()
-            .await
-            .context("Failed to send daemon control request")?;
+        let response = self.send(self.build_request(reqwest::Method::POST, url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow::anyhow!(
@@ -469,10 +521,7 @@ impl ApiClient {
         T: for<'de> Deserialize<'de>,
     {
         let url = self.build_url(path)?;
-        let response = self.build_request(reqwest::Method::GET, url)
-            .send()
-            .await
-            .with_context(|| format!("Failed to send GET request to {}", path))?;
+        let response = self.send(self.build_request(reqwest::Method::GET, url)).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -496,11 +545,9 @@ impl ApiClient {
         R: for<'de> Deserialize<'de>,
     {
         let url = self.build_url(path)?;
-        let response = self.build_request(reqwest::Method::POST, url)
-            .json(body)
-            .send()
-            .await
-            .with_context(|| format!("Failed to send POST request to {}", path))?;
+        let response = self.send(self.build_request(reqwest::Method::POST, url)
+            .json(body))
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -520,10 +567,7 @@ impl ApiClient {
     /// Generic DELETE request
     pub async fn delete(&self, path: &str) -> Result<()> {
         let url = self.build_url(path)?;
-        let response = self.build_request(reqwest::Method::DELETE, url)
-            .send()
-            .await
-            .with_context(|| format!("Failed to send DELETE request to {}", path))?;
+        let response = self.send(self.build_request(reqwest::Method::DELETE, url)).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -593,6 +637,59 @@ mod tests {
         assert_eq!(url.as_str(), "http://localhost:8080/api/v1/status");
     }
 
+    /// Address nothing is listening on right now
+    async fn free_address() -> std::net::SocketAddr {
+        tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap()
+    }
+
+    fn client_for(address: std::net::SocketAddr) -> ApiClient {
+        ApiClient::with_config(ApiClientConfig {
+            base_url: Url::parse(&format!("http://{}", address)).unwrap(),
+            connect_retry: ConnectRetry { attempts: 6, initial_backoff: Duration::from_millis(50) },
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_request_waits_for_daemon_to_bind() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let address = free_address().await;
+        // The daemon binds 200ms after the request is sent
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let body = r#"{"ok":true}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let response: serde_json::Value = client_for(address).get("/api/v1/health").await.unwrap();
+        assert_eq!(response["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_daemon_not_running_after_retries() {
+        let address = free_address().await;
+        let error = client_for(address).get::<serde_json::Value>("/api/v1/health").await.unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<DaemonUnavailable>(),
+            Some(&DaemonUnavailable::NotRunning { url: format!("http://{}", address) })
+        );
+        assert!(error.to_string().contains("not running"));
+
+        // Probes answer straight away
+        assert!(!client_for(address).ping().await.unwrap());
+    }
+
     #[test]
     fn test_invalid_base_url() {
         let result = ApiClient::new().with_base_url("invalid-url");
@@ -7,6 +7,8 @@ pub mod monitor;
 use anyhow::Result;
 use colored::*;
 use std::fmt;
+use sv2_core::connection_quality::ConnectionQuality;
+use sv2_core::difficulty_unit::DifficultyUnit;
 
 /// Output format options
 #[derive(Debug, Clone, Copy)]
@@ -119,6 +121,46 @@ pub fn format_percentage(value: f64) -> String {
     format!("{:.2}%", value)
 }
 
+/// Connection quality score, colored by how worried to be
+pub fn format_quality(quality: Option<&ConnectionQuality>) -> String {
+    match quality {
+        Some(quality) if quality.score >= 80 => quality.score.to_string().green().to_string(),
+        Some(quality) if quality.score >= 50 => quality.score.to_string().yellow().to_string(),
+        Some(quality) => quality.score.to_string().red().to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Unit and network difficulty used to show share and channel difficulty
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DifficultyDisplay {
+    pub unit: DifficultyUnit,
+    pub network_difficulty: Option<f64>,
+}
+
+impl DifficultyDisplay {
+    /// Use `unit` if given, otherwise the daemon's `monitoring.difficulty_unit`
+    pub async fn resolve(client: &crate::client::ApiClient, unit: Option<DifficultyUnit>) -> Self {
+        let unit = match unit {
+            Some(unit) => unit,
+            None => client
+                .get_config()
+                .await
+                .map(|config| config.monitoring.difficulty_unit)
+                .unwrap_or_default(),
+        };
+        let network_difficulty = match unit {
+            DifficultyUnit::Diff1 => None,
+            DifficultyUnit::Network => client.get_mining_stats().await.ok().and_then(|stats| stats.network_difficulty),
+        };
+        Self { unit, network_difficulty }
+    }
+
+    pub fn format(&self, difficulty: f64) -> String {
+        self.unit.format(difficulty, self.network_difficulty)
+    }
+}
+
 /// Check if daemon is reachable and print connection status
 pub async fn check_daemon_connection(client: &crate::client::ApiClient) -> Result<bool> {
     print_info("Checking daemon connection...");
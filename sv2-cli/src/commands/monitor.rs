@@ -5,7 +5,8 @@ use tokio::time::{interval, sleep};
 use tabled::{Table, Tabled};
 
 use crate::client::ApiClient;
-use super::{print_success, print_error, print_info, print_warning, format_hashrate, format_percentage, format_duration, check_daemon_connection};
+use super::{print_success, print_error, print_info, print_warning, format_hashrate, format_percentage, format_duration, check_daemon_connection, format_quality, DifficultyDisplay};
+use sv2_core::difficulty_unit::DifficultyUnit;
 
 /// Real-time monitoring display configuration
 #[derive(Debug, Clone)]
@@ -16,6 +17,8 @@ pub struct MonitorConfig {
     pub show_performance: bool,
     pub show_alerts: bool,
     pub max_entries: usize,
+    /// Overrides the daemon's `monitoring.difficulty_unit`
+    pub difficulty_unit: Option<DifficultyUnit>,
 }
 
 impl Default for MonitorConfig {
@@ -27,6 +30,7 @@ impl Default for MonitorConfig {
             show_performance: true,
             show_alerts: true,
             max_entries: 10,
+            difficulty_unit: None,
         }
     }
 }
@@ -44,6 +48,7 @@ pub async fn handle_monitor(
         return Ok(());
     }
 
+    let difficulty = DifficultyDisplay::resolve(client, config.difficulty_unit).await;
     let mut interval = interval(config.refresh_interval);
     let mut iteration = 0;
 
@@ -80,7 +85,7 @@ pub async fn handle_monitor(
 
         // Display recent shares if enabled
         if config.show_shares {
-            if let Err(e) = display_shares_monitor(client, config.max_entries).await {
+            if let Err(e) = display_shares_monitor(client, config.max_entries, &difficulty).await {
                 print_warning(&format!("Failed to get shares: {}", e));
             }
         }
@@ -189,6 +194,8 @@ async fn display_connections_monitor(client: &ApiClient, max_entries: usize) ->
             shares: String,
             #[tabled(rename = "Rate")]
             rate: String,
+            #[tabled(rename = "Quality")]
+            quality: String,
             #[tabled(rename = "Last Activity")]
             activity: String,
         }
@@ -213,6 +220,7 @@ async fn display_connections_monitor(client: &ApiClient, max_entries: usize) ->
                     state: format!("{} {:?}", state_icon, conn.state),
                     shares: format!("{}/{}", conn.valid_shares, conn.total_shares),
                     rate: format_percentage(conn.acceptance_rate()),
+                    quality: format_quality(conn.quality.as_ref()),
                     activity: conn.last_activity.format("%H:%M:%S").to_string(),
                 }
             })
@@ -227,7 +235,7 @@ async fn display_connections_monitor(client: &ApiClient, max_entries: usize) ->
 }
 
 /// Display recent shares in monitor
-async fn display_shares_monitor(client: &ApiClient, max_entries: usize) -> Result<()> {
+async fn display_shares_monitor(client: &ApiClient, max_entries: usize, difficulty: &DifficultyDisplay) -> Result<()> {
     let shares = client.get_shares(None, Some(max_entries as u32)).await?;
     
     println!("{}", "💎 Recent Shares".bold());
@@ -255,7 +263,7 @@ async fn display_shares_monitor(client: &ApiClient, max_entries: usize) -> Resul
             .map(|share| {
                 ShareDisplay {
                     connection: share.connection_id.to_string()[..8].to_string(),
-                    difficulty: format!("{:.2}", share.difficulty),
+                    difficulty: difficulty.format(share.difficulty),
                     valid: if share.is_valid { "✅" } else { "❌" }.to_string(),
                     block: if share.block_hash.is_some() { "🎉" } else { "-" }.to_string(),
                     time: share.submitted_at.format("%H:%M:%S").to_string(),
@@ -5,10 +5,18 @@ use serde_yaml;
 use tabled::{Table, Tabled};
 
 use crate::client::ApiClient;
-use super::{OutputFormat, print_success, print_error, print_info, format_duration, format_hashrate, format_percentage, check_daemon_connection};
+use super::{OutputFormat, print_success, print_error, print_warning, print_info, format_duration, format_hashrate, format_percentage, check_daemon_connection, format_quality, DifficultyDisplay};
+use sv2_core::difficulty_unit::DifficultyUnit;
+use sv2_core::health::HealthStatus;
 
-/// Handle the status command
-pub async fn handle_status(client: &ApiClient, detailed: bool, format: &str) -> Result<()> {
+/// Handle the status command. `difficulty_unit` overrides the daemon's
+/// `monitoring.difficulty_unit`.
+pub async fn handle_status(
+    client: &ApiClient,
+    detailed: bool,
+    format: &str,
+    difficulty_unit: Option<DifficultyUnit>,
+) -> Result<()> {
     let output_format: OutputFormat = format.parse()
         .context("Invalid output format")?;
 
@@ -21,6 +29,7 @@ pub async fn handle_status(client: &ApiClient, detailed: bool, format: &str) ->
     print_info("Fetching daemon status...");
     let status = client.get_status().await
         .context("Failed to get daemon status")?;
+    let difficulty = DifficultyDisplay::resolve(client, difficulty_unit).await;
 
     match output_format {
         OutputFormat::Json => {
@@ -34,19 +43,23 @@ pub async fn handle_status(client: &ApiClient, detailed: bool, format: &str) ->
             println!("{}", yaml);
         }
         OutputFormat::Table => {
-            print_status_table(&status, detailed).await?;
+            print_status_table(&status, detailed, &difficulty).await?;
         }
     }
 
     if detailed {
-        print_detailed_status(client).await?;
+        print_detailed_status(client, &difficulty).await?;
     }
 
     Ok(())
 }
 
 /// Print status information in table format
-async fn print_status_table(status: &sv2_core::types::DaemonStatus, detailed: bool) -> Result<()> {
+async fn print_status_table(
+    status: &sv2_core::types::DaemonStatus,
+    detailed: bool,
+    difficulty: &DifficultyDisplay,
+) -> Result<()> {
     println!("\n{}", "Daemon Status".bold().underline());
     
     #[derive(Tabled)]
@@ -80,7 +93,7 @@ async fn print_status_table(status: &sv2_core::types::DaemonStatus, detailed: bo
         },
         StatusRow {
             metric: "Current Difficulty".to_string(),
-            value: format!("{:.2}", status.current_difficulty),
+            value: difficulty.format(status.current_difficulty),
         },
         StatusRow {
             metric: "Hashrate".to_string(),
@@ -122,11 +135,21 @@ async fn print_status_table(status: &sv2_core::types::DaemonStatus, detailed: bo
         print_success(&format!("{} blocks found", status.blocks_found));
     }
 
+    // The mode's own health report
+    if let Some(health) = &status.mode_health {
+        let message = format!("Mode health: {}", health.message);
+        match health.status {
+            HealthStatus::Healthy => print_success(&message),
+            HealthStatus::Warning => print_warning(&message),
+            HealthStatus::Critical | HealthStatus::Unknown => print_error(&message),
+        }
+    }
+
     Ok(())
 }
 
 /// Print detailed status information
-async fn print_detailed_status(client: &ApiClient) -> Result<()> {
+async fn print_detailed_status(client: &ApiClient, difficulty: &DifficultyDisplay) -> Result<()> {
     println!("\n{}", "Detailed Information".bold().underline());
 
     // Get connections
@@ -152,6 +175,8 @@ async fn print_detailed_status(client: &ApiClient) -> Result<()> {
                     shares: String,
                     #[tabled(rename = "Acceptance Rate")]
                     acceptance_rate: String,
+                    #[tabled(rename = "Quality")]
+                    quality: String,
                 }
 
                 let connection_rows: Vec<ConnectionRow> = connections.iter().map(|conn| {
@@ -162,6 +187,7 @@ async fn print_detailed_status(client: &ApiClient) -> Result<()> {
                         state: format!("{:?}", conn.state),
                         shares: format!("{}/{}", conn.valid_shares, conn.total_shares),
                         acceptance_rate: format_percentage(conn.acceptance_rate()),
+                        quality: format_quality(conn.quality.as_ref()),
                     }
                 }).collect();
 
@@ -200,7 +226,7 @@ async fn print_detailed_status(client: &ApiClient) -> Result<()> {
                 let share_rows: Vec<ShareRow> = shares.iter().take(10).map(|share| {
                     ShareRow {
                         connection: share.connection_id.to_string()[..8].to_string(),
-                        difficulty: format!("{:.2}", share.difficulty),
+                        difficulty: difficulty.format(share.difficulty),
                         valid: if share.is_valid { "✓".green().to_string() } else { "✗".red().to_string() },
                         block: if share.block_hash.is_some() { "✓".green().to_string() } else { "-".to_string() },
                         submitted: share.submitted_at.format("%H:%M:%S").to_string(),
@@ -0,0 +1,347 @@
+//! `config get` / `config set` on `~/.sv2d/config.toml`. Keys are dotted paths
+//! into sv2d's config (`translator.min_extranonce2_size`). Edits go through
+//! `toml_edit` so comments and layout survive, and the whole file is checked
+//! against the keys and types sv2d accepts before it's written back.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::Path;
+use toml_edit::{Array, DocumentMut, Item, Table, TableLike, Value};
+
+/// What a key accepts
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    String,
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+    Bool,
+    Integer { min: i64, max: i64 },
+    /// Finite and greater than 0
+    PositiveFloat,
+    /// A string or an array of strings
+    Addresses,
+}
+
+const U16: i64 = u16::MAX as i64;
+const U32: i64 = u32::MAX as i64;
+const U64: i64 = i64::MAX;
+
+/// Every key sv2d reads from its config file
+const KEYS: &[(&str, Kind)] = &[
+    ("profile", Kind::Choice(crate::profiles::NAMES)),
+    ("daemon.mode", Kind::String),
+    ("daemon.network", Kind::Choice(&["mainnet", "main", "testnet", "testnet4", "signet", "regtest"])),
+    ("bitcoin.rpc_url", Kind::String),
+    ("bitcoin.rpc_user", Kind::String),
+    ("bitcoin.rpc_password", Kind::String),
+    ("bitcoin.managed", Kind::Bool),
+    ("pool.signature", Kind::String),
+    ("pool.coinbase_address", Kind::String),
+    ("translator.bind_address", Kind::Addresses),
+    ("translator.min_extranonce2_size", Kind::Integer { min: 0, max: U32 }),
+    ("translator.shares_per_minute", Kind::PositiveFloat),
+    ("translator.min_individual_miner_hashrate", Kind::PositiveFloat),
+    ("runtime.worker_threads", Kind::Integer { min: 1, max: U64 }),
+    ("startup.sv2_tp.ready_timeout_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.sv2_tp.poll_interval_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.pool.ready_timeout_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.pool.poll_interval_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.translator.ready_timeout_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.translator.poll_interval_secs", Kind::Integer { min: 1, max: U64 }),
+    ("startup.sv2_tp_ipc_grace_secs", Kind::Integer { min: 0, max: U64 }),
+    ("maintenance.max_duration_secs", Kind::Integer { min: 1, max: U64 }),
+    ("shutdown.timeout_secs", Kind::Integer { min: 1, max: U64 }),
+    ("sv2_tp.external", Kind::Bool),
+    ("sv2_tp.host", Kind::String),
+    ("sv2_tp.port", Kind::Integer { min: 1, max: U16 }),
+    ("sv2_tp.authority_key", Kind::String),
+    ("ports.pool_listen", Kind::Integer { min: 1, max: U16 }),
+    ("ports.pool_plain", Kind::Integer { min: 1, max: U16 }),
+    ("ports.translator_downstream", Kind::Integer { min: 1, max: U16 }),
+    ("ports.tp_port", Kind::Integer { min: 1, max: U16 }),
+    ("ports.json_rpc", Kind::Integer { min: 1, max: U16 }),
+    ("share_probe.enabled", Kind::Bool),
+    ("share_probe.interval_secs", Kind::Integer { min: 1, max: U64 }),
+    ("share_probe.timeout_secs", Kind::Integer { min: 1, max: U64 }),
+    ("share_probe.failure_threshold", Kind::Integer { min: 1, max: U32 }),
+    ("share_probe.difficulty", Kind::PositiveFloat),
+    ("miner_stats.enabled", Kind::Bool),
+    ("miner_stats.poll_interval_secs", Kind::Integer { min: 1, max: U64 }),
+];
+
+fn kind(key: &str) -> Result<Kind> {
+    if let Some((_, kind)) = KEYS.iter().find(|(known, _)| *known == key) {
+        return Ok(*kind);
+    }
+
+    let section = key.rsplit_once('.').map_or("", |(section, _)| section);
+    let siblings: Vec<&str> = KEYS
+        .iter()
+        .map(|(known, _)| *known)
+        .filter(|known| known.rsplit_once('.').map_or("", |(s, _)| s) == section)
+        .collect();
+    if section.is_empty() || siblings.is_empty() {
+        let mut sections: Vec<&str> = KEYS.iter().filter_map(|(known, _)| known.split('.').next()).collect();
+        sections.dedup();
+        Err(anyhow!("Unknown config key '{}'. Sections: {}", key, sections.join(", ")))
+    } else {
+        Err(anyhow!("Unknown config key '{}'. Keys in [{}]: {}", key, section, siblings.join(", ")))
+    }
+}
+
+impl Kind {
+    fn describe(&self) -> String {
+        match self {
+            Kind::String => "a string".to_string(),
+            Kind::Choice(choices) => format!("one of {}", choices.join(", ")),
+            Kind::Bool => "true or false".to_string(),
+            Kind::Integer { min, max } if *max == U64 => format!("an integer of at least {}", min),
+            Kind::Integer { min, max } => format!("an integer from {} to {}", min, max),
+            Kind::PositiveFloat => "a number greater than 0".to_string(),
+            Kind::Addresses => "an address or a comma-separated list of addresses".to_string(),
+        }
+    }
+
+    /// Value for `raw` as typed on the command line
+    fn parse(&self, key: &str, raw: &str) -> Result<Value> {
+        let mismatch = || anyhow!("{} expects {}, got '{}'", key, self.describe(), raw);
+        let value = match self {
+            Kind::String | Kind::Choice(_) => Value::from(raw),
+            Kind::Bool => Value::from(raw.parse::<bool>().map_err(|_| mismatch())?),
+            Kind::Integer { .. } => Value::from(raw.parse::<i64>().map_err(|_| mismatch())?),
+            Kind::PositiveFloat => Value::from(raw.parse::<f64>().map_err(|_| mismatch())?),
+            Kind::Addresses if raw.contains(',') => {
+                Value::Array(raw.split(',').map(str::trim).collect::<Array>())
+            }
+            Kind::Addresses => Value::from(raw.trim()),
+        };
+        self.check(key, &value).map_err(|_| mismatch())?;
+        Ok(value)
+    }
+
+    fn check(&self, key: &str, value: &Value) -> Result<()> {
+        let valid = match (self, value) {
+            (Kind::String, Value::String(_)) => true,
+            (Kind::Choice(choices), Value::String(s)) => choices.contains(&s.value().as_str()),
+            (Kind::Bool, Value::Boolean(_)) => true,
+            (Kind::Integer { min, max }, Value::Integer(i)) => (*min..=*max).contains(i.value()),
+            (Kind::PositiveFloat, Value::Float(f)) => f.value().is_finite() && *f.value() > 0.0,
+            (Kind::PositiveFloat, Value::Integer(i)) => *i.value() > 0,
+            (Kind::Addresses, Value::String(s)) => !s.value().trim().is_empty(),
+            (Kind::Addresses, Value::Array(items)) => {
+                !items.is_empty() && items.iter().all(|item| item.as_str().is_some_and(|s| !s.trim().is_empty()))
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(anyhow!("{} must be {}, found {}", key, self.describe(), display(value)));
+        }
+        Ok(())
+    }
+}
+
+/// `value` as TOML, without surrounding whitespace or comments
+fn display(value: &Value) -> String {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    value.to_string()
+}
+
+/// Check every key in the file is one sv2d knows, with a value of the right type
+fn validate(table: &dyn TableLike, prefix: &str) -> Result<()> {
+    let mut checks = Vec::new();
+    check_table(table, prefix, &mut checks);
+    checks.into_iter().try_for_each(|(_, result)| result)
+}
+
+/// Result for every key in `table`, in file order
+fn check_table(table: &dyn TableLike, prefix: &str, checks: &mut Vec<(String, Result<()>)>) {
+    for (name, item) in table.iter() {
+        let key = if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+        match item {
+            Item::Table(table) => check_table(table, &key, checks),
+            Item::Value(Value::InlineTable(table)) => check_table(table, &key, checks),
+            Item::Value(value) => {
+                let result = kind(&key).and_then(|kind| kind.check(&key, value));
+                checks.push((key, result));
+            }
+            Item::ArrayOfTables(_) => {
+                let error = anyhow!("Unknown config key '{}'", key);
+                checks.push((key, Err(error)));
+            }
+            Item::None => {}
+        }
+    }
+}
+
+/// Whether each key in `contents` is one sv2d knows, with a value of the right type
+pub fn check_keys(contents: &str) -> Result<Vec<(String, Result<()>)>> {
+    let doc = parse(contents)?;
+    let mut checks = Vec::new();
+    check_table(doc.as_table(), "", &mut checks);
+    Ok(checks)
+}
+
+fn parse(contents: &str) -> Result<DocumentMut> {
+    contents.parse::<DocumentMut>().context("Failed to parse config file")
+}
+
+/// Current value of `key` as written in the file, `None` when sv2d falls back to a default
+pub fn get(contents: &str, key: &str) -> Result<Option<String>> {
+    kind(key)?;
+    let doc = parse(contents)?;
+    let mut item = doc.as_item();
+    for part in key.split('.') {
+        match item.get(part) {
+            Some(next) => item = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(item.as_value().map(|value| match value.as_str() {
+        Some(s) => s.to_string(),
+        None => display(value),
+    }))
+}
+
+/// `contents` with `key` set to `raw`. Fails on an unknown key, a value of the
+/// wrong type, or a file that doesn't validate afterwards.
+pub fn set(contents: &str, key: &str, raw: &str) -> Result<String> {
+    let mut value = kind(key)?.parse(key, raw)?;
+    let mut doc = parse(contents)?;
+
+    let (sections, leaf) = key.rsplit_once('.').map_or((None, key), |(s, leaf)| (Some(s), leaf));
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for section in sections.into_iter().flat_map(|s| s.split('.')) {
+        let mut missing = Table::new();
+        missing.set_implicit(true);
+        table = table
+            .entry(section)
+            .or_insert(Item::Table(missing))
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("Cannot set {}: '{}' is not a table", key, section))?;
+    }
+
+    // Keep an inline comment on the line being replaced
+    if let Some(old) = table.get(leaf).and_then(Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table.insert(leaf, Item::Value(value));
+
+    validate(doc.as_table(), "")?;
+    Ok(doc.to_string())
+}
+
+/// Replace `path` without leaving a half-written file behind
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    // The config holds RPC credentials, so keep whatever permissions it had
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(&tmp, metadata.permissions())
+            .with_context(|| format!("Failed to set permissions on {}", tmp.display()))?;
+    }
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Generated by sv2-cli setup
+[daemon]
+mode = "proxy"
+network = "signet"
+
+[translator]
+bind_address = "0.0.0.0:3333"
+min_extranonce2_size = 8 # Bitaxe needs at least 8
+"#;
+
+    #[test]
+    fn test_set_valid_key_keeps_comments() {
+        let updated = set(CONFIG, "translator.min_extranonce2_size", "16").unwrap();
+        assert!(updated.starts_with("# Generated by sv2-cli setup\n"));
+        assert!(updated.contains("min_extranonce2_size = 16 # Bitaxe needs at least 8\n"));
+        assert_eq!(get(&updated, "translator.min_extranonce2_size").unwrap().as_deref(), Some("16"));
+
+        // Missing sections are created
+        let updated = set(&updated, "startup.sv2_tp.ready_timeout_secs", "90").unwrap();
+        assert!(updated.contains("[startup.sv2_tp]\nready_timeout_secs = 90\n"));
+        let updated = set(&updated, "translator.bind_address", "0.0.0.0:3333, 0.0.0.0:3334").unwrap();
+        assert!(updated.contains(r#"bind_address = ["0.0.0.0:3333", "0.0.0.0:3334"]"#));
+        assert_eq!(get(&updated, "daemon.network").unwrap().as_deref(), Some("signet"));
+        assert_eq!(get(&updated, "share_probe.enabled").unwrap(), None);
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        let error = set(CONFIG, "translator.min_extranonce_size", "16").unwrap_err().to_string();
+        assert!(error.contains("Unknown config key 'translator.min_extranonce_size'"), "{}", error);
+        assert!(error.contains("min_extranonce2_size"), "{}", error);
+        assert!(get(CONFIG, "nonsense").unwrap_err().to_string().contains("Sections:"));
+
+        // Unknown keys already in the file fail validation too
+        let stray = format!("{}typo = 1\n", CONFIG);
+        let error = set(&stray, "daemon.network", "regtest").unwrap_err().to_string();
+        assert!(error.contains("Unknown config key 'translator.typo'"), "{}", error);
+    }
+
+    #[test]
+    fn test_type_mismatch_is_rejected() {
+        let error = set(CONFIG, "translator.min_extranonce2_size", "sixteen").unwrap_err().to_string();
+        assert_eq!(error, "translator.min_extranonce2_size expects an integer from 0 to 4294967295, got 'sixteen'");
+        assert!(set(CONFIG, "translator.min_extranonce2_size", "-1").is_err());
+        assert!(set(CONFIG, "bitcoin.managed", "yes").is_err());
+        assert!(set(CONFIG, "translator.shares_per_minute", "0").is_err());
+        assert!(set(CONFIG, "daemon.network", "testnet5").is_err());
+        assert!(set(CONFIG, "daemon.network.name", "signet").is_err());
+    }
+
+    #[test]
+    fn test_check_keys_reports_every_key() {
+        let contents = format!("{}typo = 1\nshares_per_minute = 0\n", CONFIG);
+        let checks = check_keys(&contents).unwrap();
+        let keys: Vec<&str> = checks.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, [
+            "daemon.mode",
+            "daemon.network",
+            "translator.bind_address",
+            "translator.min_extranonce2_size",
+            "translator.typo",
+            "translator.shares_per_minute",
+        ]);
+        let failed: Vec<&str> = checks.iter().filter(|(_, r)| r.is_err()).map(|(key, _)| key.as_str()).collect();
+        assert_eq!(failed, ["translator.typo", "translator.shares_per_minute"]);
+    }
+
+    #[test]
+    fn test_sv2d_sections_are_known() {
+        let contents = format!(
+            "{}\n[ports]\npool_listen = 34254\npool_plain = 34255\ntranslator_downstream = 3333\ntp_port = 38336\njson_rpc = 8332\n\
+             \n[shutdown]\ntimeout_secs = 45\n\n[miner_stats]\nenabled = true\npoll_interval_secs = 60\n",
+            CONFIG
+        );
+        let updated = set(&contents, "daemon.network", "regtest").unwrap();
+        assert_eq!(get(&updated, "ports.tp_port").unwrap().as_deref(), Some("38336"));
+        let updated = set(&updated, "ports.json_rpc", "9332").unwrap();
+        assert_eq!(get(&updated, "ports.json_rpc").unwrap().as_deref(), Some("9332"));
+        assert!(set(&updated, "ports.pool_listen", "70000").is_err());
+        assert!(set(&updated, "ports.pool_plain", "0").is_err());
+        let updated = set(&updated, "shutdown.timeout_secs", "60").unwrap();
+        assert_eq!(get(&updated, "shutdown.timeout_secs").unwrap().as_deref(), Some("60"));
+        assert!(set(&updated, "shutdown.timeout_secs", "0").is_err());
+        let updated = set(&updated, "miner_stats.enabled", "false").unwrap();
+        assert_eq!(get(&updated, "miner_stats.enabled").unwrap().as_deref(), Some("false"));
+        assert!(set(&updated, "miner_stats.poll_interval_secs", "0").is_err());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+        write_atomic(&path, "profile = \"regtest-fast\"\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "profile = \"regtest-fast\"\n");
+        assert!(!dir.path().join("config.toml.tmp").exists());
+    }
+}
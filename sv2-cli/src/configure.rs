@@ -0,0 +1,203 @@
+//! `configure-miners`: repoint detected miners at this pool through their
+//! HTTP API. Only miner types with a known writable API are touched; the rest
+//! are reported so they can be configured by hand.
+
+use sv2_core::scanner::{DetectedMiner, MinerType};
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+/// Whether `sv2-cli` knows how to set the pool on this miner type
+pub fn supports_remote_config(miner_type: &MinerType) -> bool {
+    matches!(miner_type, MinerType::Bitaxe)
+}
+
+/// Stratum endpoint miners are pointed at
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolTarget {
+    pub host: String,
+    pub port: u16,
+}
+
+impl PoolTarget {
+    /// Parse `host:port`, with or without a `stratum+tcp://` prefix
+    pub fn parse(pool: &str) -> Result<Self> {
+        let address = pool.trim().trim_start_matches("stratum+tcp://");
+        let (host, port) = address
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("Pool '{}' must be host:port, e.g. 192.168.1.5:3333", pool))?;
+        if host.is_empty() || host.contains('/') {
+            return Err(anyhow!("Pool '{}' must be host:port, e.g. 192.168.1.5:3333", pool));
+        }
+        let port = port.parse().map_err(|_| anyhow!("Invalid port in pool '{}'", pool))?;
+        Ok(Self { host: host.to_string(), port })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Configured,
+    /// Dry run: the miner would have been configured
+    WouldConfigure,
+    Unsupported,
+    Failed(String),
+}
+
+pub struct MinerConfigurator {
+    client: Client,
+    target: PoolTarget,
+    /// Stratum user to set; the miner keeps its own when `None`
+    worker: Option<String>,
+}
+
+impl MinerConfigurator {
+    pub fn new(target: PoolTarget, worker: Option<String>) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to create HTTP client"),
+            target,
+            worker,
+        }
+    }
+
+    pub async fn configure(&self, miner: &DetectedMiner, dry_run: bool) -> Outcome {
+        if !supports_remote_config(&miner.miner_type) {
+            return Outcome::Unsupported;
+        }
+        if dry_run {
+            return Outcome::WouldConfigure;
+        }
+        match self.configure_bitaxe(miner).await {
+            Ok(()) => Outcome::Configured,
+            Err(e) => Outcome::Failed(format!("{:#}", e)),
+        }
+    }
+
+    /// AxeOS takes settings with `PATCH /api/system` and applies them on restart
+    async fn configure_bitaxe(&self, miner: &DetectedMiner) -> Result<()> {
+        let base = format!("http://{}:{}", miner.ip, miner.api_port.unwrap_or(80));
+
+        let mut settings = json!({
+            "stratumURL": self.target.host,
+            "stratumPort": self.target.port,
+        });
+        if let Some(worker) = &self.worker {
+            settings["stratumUser"] = json!(worker);
+        }
+
+        self.client
+            .patch(format!("{}/api/system", base))
+            .json(&settings)
+            .send()
+            .await
+            .context("miner did not accept the connection")?
+            .error_for_status()
+            .context("miner rejected the new settings")?;
+
+        self.client
+            .post(format!("{}/api/system/restart", base))
+            .send()
+            .await
+            .context("settings saved but the restart request failed; restart the miner to apply them")?
+            .error_for_status()
+            .context("settings saved but the miner refused to restart; restart it to apply them")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sv2_core::scanner::MinerDetails;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Mock AxeOS API recording each request line and body
+    async fn mock_bitaxe(status: &'static str) -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // Read headers, then the body they announce
+                loop {
+                    let n = stream.read(&mut buffer).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            let line = head.lines().next().unwrap_or_default();
+                            recorded.lock().unwrap().push(format!("{} {}", line, body));
+                            break;
+                        }
+                    }
+                }
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (port, requests)
+    }
+
+    fn miner(miner_type: MinerType, port: u16) -> DetectedMiner {
+        DetectedMiner {
+            ip: "127.0.0.1".parse().unwrap(),
+            miner_type,
+            api_port: Some(port),
+            response_time_ms: 1,
+            last_seen: std::time::Instant::now(),
+            details: MinerDetails::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_configure_bitaxe_through_its_api() {
+        let (port, requests) = mock_bitaxe("200 OK").await;
+        let configurator = MinerConfigurator::new(
+            PoolTarget::parse("192.168.1.5:3333").unwrap(),
+            Some("bc1qexample.bitaxe".to_string()),
+        );
+
+        assert_eq!(configurator.configure(&miner(MinerType::Bitaxe, port), true).await, Outcome::WouldConfigure);
+        assert!(requests.lock().unwrap().is_empty());
+
+        assert_eq!(configurator.configure(&miner(MinerType::Bitaxe, port), false).await, Outcome::Configured);
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("PATCH /api/system HTTP/1.1"));
+        assert!(requests[0].contains("\"stratumURL\":\"192.168.1.5\""), "{}", requests[0]);
+        assert!(requests[0].contains("\"stratumPort\":3333"));
+        assert!(requests[0].contains("\"stratumUser\":\"bc1qexample.bitaxe\""));
+        assert!(requests[1].starts_with("POST /api/system/restart"));
+
+        assert_eq!(configurator.configure(&miner(MinerType::AntminerS19, port), false).await, Outcome::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_settings_are_reported() {
+        let (port, _) = mock_bitaxe("400 Bad Request").await;
+        let configurator = MinerConfigurator::new(PoolTarget::parse("stratum+tcp://10.0.0.2:3333").unwrap(), None);
+        match configurator.configure(&miner(MinerType::Bitaxe, port), false).await {
+            Outcome::Failed(reason) => assert!(reason.contains("rejected the new settings")),
+            other => panic!("expected failure, got {:?}", other),
+        }
+
+        assert!(PoolTarget::parse("10.0.0.2").is_err());
+        assert!(PoolTarget::parse("10.0.0.2:99999").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! `test-connection`: check that an upstream pool is reachable and speaks
+//! the protocol its URL says before pointing client or proxy mode at it.
+//!
+//! Stratum V1 pools get a `mining.subscribe`. Stratum V2 pools get the first
+//! Noise NX handshake message; the responder's reply shows the pool speaks
+//! SV2 and which key encoding it uses. SetupConnection, where the protocol
+//! version is agreed, is sent inside the encrypted channel and isn't reached.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+use sv2_core::StratumUrl;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Size of the initiator's ephemeral key. Every 64-byte string is a valid
+/// ElligatorSwift encoding, so random bytes make a well-formed first message.
+const NOISE_INITIATOR_MESSAGE_LEN: usize = 64;
+
+/// Responder reply with ElligatorSwift keys (SRI 1.0 and later): ephemeral
+/// key, encrypted static key and encrypted certificate
+const NOISE_RESPONDER_MESSAGE_LEN: usize = 64 + (64 + 16) + (74 + 16);
+
+/// Responder reply with 32-byte x-only keys, used by earlier SRI releases
+const LEGACY_NOISE_RESPONDER_MESSAGE_LEN: usize = 32 + (32 + 16) + (74 + 16);
+
+#[derive(Debug, Clone)]
+pub struct ConnectionReport {
+    pub address: String,
+    pub protocol: &'static str,
+    /// What the pool's answer says about its protocol version
+    pub version: String,
+    pub connect_latency: Duration,
+    /// From sending the first message to the pool's answer
+    pub response_latency: Duration,
+}
+
+/// Connect to `url`, send the protocol's opening message and wait for the
+/// pool to answer, giving up after `timeout` per step
+pub async fn test_connection(url: &StratumUrl, timeout: Duration) -> Result<ConnectionReport> {
+    let address = url.address();
+
+    let started = Instant::now();
+    let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(&address)).await {
+        Err(_) => bail!(
+            "No answer from {} within {}s. Check the host and port, and that no firewall or VPN is dropping the connection.",
+            address, timeout.as_secs()
+        ),
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => bail!(
+            "Connection refused by {}. Nothing is listening on port {}; check the port and that the pool is running.",
+            address, url.port
+        ),
+        Ok(Err(e)) => bail!("Could not connect to {}: {}. Check the hostname resolves and is reachable.", address, e),
+        Ok(Ok(stream)) => stream,
+    };
+    let connect_latency = started.elapsed();
+
+    let started = Instant::now();
+    let exchange = async {
+        if url.is_sv2() {
+            noise_handshake(&mut stream).await
+        } else {
+            subscribe(&mut stream).await
+        }
+    };
+    let version = tokio::time::timeout(timeout, exchange).await.map_err(|_| {
+        anyhow!(
+            "{} accepted the connection but didn't answer within {}s. It may speak {} instead; try {}.",
+            address,
+            timeout.as_secs(),
+            if url.is_sv2() { "Stratum V1" } else { "Stratum V2" },
+            if url.is_sv2() { "stratum+tcp://" } else { "stratum2+tcp://" }
+        )
+    })??;
+
+    Ok(ConnectionReport {
+        address,
+        protocol: if url.is_sv2() { "Stratum V2" } else { "Stratum V1" },
+        version,
+        connect_latency,
+        response_latency: started.elapsed(),
+    })
+}
+
+async fn noise_handshake(stream: &mut TcpStream) -> Result<String> {
+    let ephemeral_key: Vec<u8> = (0..NOISE_INITIATOR_MESSAGE_LEN / 16)
+        .flat_map(|_| *uuid::Uuid::new_v4().as_bytes())
+        .collect();
+    stream.write_all(&ephemeral_key).await?;
+
+    let mut reply = vec![0u8; NOISE_RESPONDER_MESSAGE_LEN];
+    let mut received = 0;
+    while received < reply.len() {
+        match stream.read(&mut reply[received..]).await? {
+            0 => break,
+            n => received += n,
+        }
+        // Earlier releases send a shorter reply and then wait for ours
+        if received == LEGACY_NOISE_RESPONDER_MESSAGE_LEN {
+            if let Ok(Ok(n)) = tokio::time::timeout(Duration::from_millis(500), stream.read(&mut reply[received..])).await {
+                received += n;
+            }
+            if received == LEGACY_NOISE_RESPONDER_MESSAGE_LEN {
+                break;
+            }
+        }
+    }
+
+    match received {
+        NOISE_RESPONDER_MESSAGE_LEN => Ok("Noise NX with ElligatorSwift keys (SRI 1.0 or later)".to_string()),
+        LEGACY_NOISE_RESPONDER_MESSAGE_LEN => Ok("Noise NX with x-only keys (SRI before 1.0)".to_string()),
+        0 => bail!(
+            "The pool closed the connection during the Noise handshake. It may not speak Stratum V2; try stratum+tcp://."
+        ),
+        _ if reply[0] == b'{' => bail!(
+            "The pool answered with Stratum V1 JSON. Use a stratum+tcp:// URL for this pool."
+        ),
+        n => bail!("Unexpected {}-byte reply to the Noise handshake; this doesn't look like a Stratum V2 pool.", n),
+    }
+}
+
+async fn subscribe(stream: &mut TcpStream) -> Result<String> {
+    let request = json!({"id": 1, "method": "mining.subscribe", "params": ["sv2-cli/test-connection"]});
+    stream.write_all(format!("{}\n", request).as_bytes()).await?;
+
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            bail!("The pool closed the connection without answering mining.subscribe.");
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if buffer[0] != b'{' {
+            bail!("The pool answered with binary data, which looks like Stratum V2. Use a stratum2+tcp:// URL for this pool.");
+        }
+
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(message) = serde_json::from_slice::<Value>(&line) else {
+                bail!("The pool sent a line that isn't JSON; this doesn't look like a Stratum V1 pool.");
+            };
+            if message.get("id").and_then(Value::as_u64) != Some(1) {
+                continue;
+            }
+            if let Some(error) = message.get("error").filter(|e| !e.is_null()) {
+                bail!("The pool rejected mining.subscribe: {}", error);
+            }
+            let extranonce2_size = message.pointer("/result/2").and_then(Value::as_u64);
+            return Ok(match extranonce2_size {
+                Some(size) => format!("Stratum V1 subscription, extranonce2 size {}", size),
+                None => "Stratum V1 subscription".to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    async fn fake_sv1_pool() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(_)) = lines.next_line().await {
+                let notify = json!({"id": null, "method": "mining.set_difficulty", "params": [1]});
+                let reply = json!({"id": 1, "result": [[], "f000000f", 4], "error": null});
+                writer.write_all(format!("{}\n{}\n", notify, reply).as_bytes()).await.unwrap();
+            }
+        });
+        address
+    }
+
+    async fn fake_sv2_pool() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut ephemeral_key = [0u8; NOISE_INITIATOR_MESSAGE_LEN];
+            stream.read_exact(&mut ephemeral_key).await.unwrap();
+            stream.write_all(&[7u8; NOISE_RESPONDER_MESSAGE_LEN]).await.unwrap();
+            // Hold the connection open like a pool waiting for our reply
+            let _ = stream.read(&mut [0u8; 1]).await;
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_connection_to_sv1_and_sv2_pools() {
+        let url: StratumUrl = format!("stratum+tcp://{}", fake_sv1_pool().await).parse().unwrap();
+        let report = test_connection(&url, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(report.protocol, "Stratum V1");
+        assert!(report.version.contains("extranonce2 size 4"));
+
+        let url: StratumUrl = format!("stratum2+tcp://{}", fake_sv2_pool().await).parse().unwrap();
+        let report = test_connection(&url, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(report.protocol, "Stratum V2");
+        assert!(report.version.contains("ElligatorSwift"));
+    }
+
+    #[tokio::test]
+    async fn test_connection_errors_are_actionable() {
+        // Nothing listening
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = listener.local_addr().unwrap();
+        drop(listener);
+        let url: StratumUrl = format!("stratum+tcp://{}", closed).parse().unwrap();
+        let err = test_connection(&url, Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.to_string().contains("Connection refused"));
+
+        // SV1 pool behind an SV2 URL answers the handshake with JSON
+        let url: StratumUrl = format!("stratum2+tcp://{}", fake_sv1_pool().await).parse().unwrap();
+        let err = test_connection(&url, Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.to_string().contains("stratum+tcp://"));
+
+        // A listener that never answers times out
+        let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: StratumUrl = format!("stratum+tcp://{}", silent.local_addr().unwrap()).parse().unwrap();
+        let err = test_connection(&url, Duration::from_millis(300)).await.unwrap_err();
+        assert!(err.to_string().contains("didn't answer"));
+    }
+}
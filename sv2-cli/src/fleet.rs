@@ -0,0 +1,178 @@
+//! Fleet profiles: what `scan --output` learned about the miners, in a form
+//! `setup --from-scan` turns into translator settings.
+
+use sv2_core::scanner::{generate_config_recommendations, DetectedMiner};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Vardiff starting hashrate when no miner reported one, a Bitaxe
+const DEFAULT_MINER_HASHRATE: f64 = 500_000_000_000.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetProfile {
+    pub miner_count: usize,
+    /// Sum of the hashrates miners reported, in H/s
+    pub total_hashrate: f64,
+    /// Miner count per type
+    pub miner_types: BTreeMap<String, usize>,
+    pub min_extranonce2_size: u32,
+    pub shares_per_minute: f64,
+    /// Hashrate vardiff assumes for a new connection: the slowest reporting miner
+    pub min_individual_miner_hashrate: f64,
+}
+
+impl FleetProfile {
+    pub fn from_miners(miners: &[DetectedMiner]) -> Self {
+        let recommendations = generate_config_recommendations(miners);
+
+        let mut miner_types = BTreeMap::new();
+        for miner in miners {
+            *miner_types.entry(miner.miner_type.to_string()).or_insert(0) += 1;
+        }
+
+        let hashrates: Vec<f64> = miners.iter().filter_map(|m| m.details.hashrate).filter(|h| *h > 0.0).collect();
+
+        Self {
+            miner_count: miners.len(),
+            total_hashrate: hashrates.iter().sum(),
+            miner_types,
+            min_extranonce2_size: recommendations
+                .get("extranonce2_size")
+                .and_then(|v| v.as_u64())
+                .map_or(8, |size| size as u32),
+            shares_per_minute: recommendations
+                .get("shares_per_minute")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(5.0),
+            min_individual_miner_hashrate: hashrates
+                .iter()
+                .copied()
+                .reduce(f64::min)
+                .unwrap_or(DEFAULT_MINER_HASHRATE),
+        }
+    }
+
+    /// `[translator]` section for the generated sv2d config
+    pub fn translator_section(&self) -> String {
+        format!(
+            "[translator]\nbind_address = \"0.0.0.0:3333\"\nmin_extranonce2_size = {}\nshares_per_minute = {:.1}\nmin_individual_miner_hashrate = {:.1}\n",
+            self.min_extranonce2_size, self.shares_per_minute, self.min_individual_miner_hashrate
+        )
+    }
+
+    /// One-line description for the setup wizard
+    pub fn summary(&self) -> String {
+        let types: Vec<String> = self.miner_types.iter().map(|(kind, count)| format!("{} x{}", kind, count)).collect();
+        format!(
+            "{} miner(s), {:.2} TH/s total ({})",
+            self.miner_count,
+            self.total_hashrate / 1e12,
+            types.join(", ")
+        )
+    }
+}
+
+/// File written by `scan --output`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanFile {
+    pub scan_time: String,
+    pub miners: Vec<DetectedMiner>,
+    pub recommendations: HashMap<String, serde_json::Value>,
+    /// Missing from scans made before fleet profiles existed
+    #[serde(default)]
+    pub fleet: Option<FleetProfile>,
+}
+
+impl ScanFile {
+    pub fn new(miners: Vec<DetectedMiner>) -> Self {
+        Self {
+            scan_time: chrono::Utc::now().to_rfc3339(),
+            recommendations: generate_config_recommendations(&miners),
+            fleet: Some(FleetProfile::from_miners(&miners)),
+            miners,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write scan results to {}", path.display()))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scan file {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("{} is not a scan file written by 'sv2-cli scan --output'", path.display()))
+    }
+
+    /// The fleet profile, rebuilt from the miners for older scan files
+    pub fn fleet(&self) -> FleetProfile {
+        self.fleet.clone().unwrap_or_else(|| FleetProfile::from_miners(&self.miners))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sv2_core::scanner::{MinerDetails, MinerType};
+
+    fn miner(ip: &str, miner_type: MinerType, hashrate: f64) -> DetectedMiner {
+        DetectedMiner {
+            ip: ip.parse().unwrap(),
+            miner_type,
+            api_port: Some(80),
+            response_time_ms: 20,
+            last_seen: std::time::Instant::now(),
+            details: MinerDetails { hashrate: Some(hashrate), ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_scan_file_round_trips_into_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scan.json");
+
+        ScanFile::new(vec![
+            miner("192.168.1.10", MinerType::Bitaxe, 700e9),
+            miner("192.168.1.11", MinerType::Bitaxe, 650e9),
+            miner("192.168.1.12", MinerType::Apollo, 4.8e12),
+        ])
+        .write(&path)
+        .unwrap();
+
+        let fleet = ScanFile::read(&path).unwrap().fleet();
+        assert_eq!(fleet.miner_count, 3);
+        assert_eq!(fleet.miner_types.get("Bitaxe"), Some(&2));
+        assert!((fleet.total_hashrate - 6.15e12).abs() < 1.0);
+        assert_eq!(fleet.min_extranonce2_size, 6);
+        assert_eq!(fleet.min_individual_miner_hashrate, 650e9);
+
+        let config = format!(
+            "[daemon]\nmode = \"proxy\"\nnetwork = \"signet\"\n\n{}",
+            fleet.translator_section()
+        );
+        let parsed: toml::Value = toml::from_str(&config).unwrap();
+        let translator = &parsed["translator"];
+        assert_eq!(translator["bind_address"].as_str(), Some("0.0.0.0:3333"));
+        assert_eq!(translator["min_extranonce2_size"].as_integer(), Some(6));
+        assert_eq!(translator["shares_per_minute"].as_float(), Some(5.0));
+        assert_eq!(translator["min_individual_miner_hashrate"].as_float(), Some(650e9));
+    }
+
+    #[test]
+    fn test_older_scan_files_rebuild_the_fleet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scan.json");
+
+        let mut scan = ScanFile::new(vec![miner("192.168.1.10", MinerType::Bitaxe, 700e9)]);
+        let expected = scan.fleet.take().unwrap();
+        scan.write(&path).unwrap();
+
+        assert_eq!(ScanFile::read(&path).unwrap().fleet(), expected);
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(ScanFile::read(&path).is_err());
+    }
+}
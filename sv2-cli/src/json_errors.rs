@@ -0,0 +1,92 @@
+//! Machine-readable errors for `--json-errors`
+//!
+//! With the flag set, a failing command prints
+//! `{"error":{"code":"...","message":"..."}}` to stdout instead of text on
+//! stderr, and exits with the code matching `code`:
+//!
+//! | code                 | exit | meaning                                   |
+//! |----------------------|------|-------------------------------------------|
+//! | `error`              | 1    | the command failed                        |
+//! | `usage`              | 2    | bad arguments                             |
+//! | `daemon_not_running` | 3    | nothing is listening where sv2d should be |
+//! | `daemon_starting`    | 4    | sv2d is up but not answering yet          |
+
+use crate::client::DaemonUnavailable;
+use serde::{Deserialize, Serialize};
+
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_DAEMON_NOT_RUNNING: i32 = 3;
+pub const EXIT_DAEMON_STARTING: i32 = 4;
+
+/// `{"error": ...}` body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorOutput {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    pub code: String,
+    pub message: String,
+}
+
+impl ErrorOutput {
+    fn new(code: &str, message: String) -> Self {
+        Self { error: ErrorDetail { code: code.to_string(), message } }
+    }
+
+    /// Output for a failed command, and the exit code to use
+    pub fn from_error(error: &anyhow::Error) -> (Self, i32) {
+        // The full context chain, so the cause isn't lost
+        let message = format!("{:#}", error);
+        match error.downcast_ref::<DaemonUnavailable>() {
+            Some(DaemonUnavailable::NotRunning { .. }) => {
+                (Self::new("daemon_not_running", message), EXIT_DAEMON_NOT_RUNNING)
+            }
+            Some(DaemonUnavailable::Starting { .. }) => (Self::new("daemon_starting", message), EXIT_DAEMON_STARTING),
+            None => (Self::new("error", message), EXIT_FAILURE),
+        }
+    }
+
+    /// Output for arguments that didn't parse, from clap's error text
+    pub fn usage(message: &str) -> (Self, i32) {
+        // The first line names the problem; the rest is usage help for humans
+        let first_line = message.lines().next().unwrap_or_default();
+        let message = first_line.trim().trim_start_matches("error: ").to_string();
+        (Self::new("usage", message), EXIT_USAGE)
+    }
+}
+
+impl std::fmt::Display for ErrorOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_error_codes() {
+        let error = anyhow::anyhow!("Config file not found").context("Failed to load config");
+        let (output, exit) = ErrorOutput::from_error(&error);
+        assert_eq!(exit, EXIT_FAILURE);
+        assert_eq!(output.error.code, "error");
+        assert_eq!(output.error.message, "Failed to load config: Config file not found");
+
+        let error = Err::<(), _>(DaemonUnavailable::NotRunning { url: "http://127.0.0.1:8333".to_string() })
+            .context("Failed to get status")
+            .unwrap_err();
+        let (output, exit) = ErrorOutput::from_error(&error);
+        assert_eq!(exit, EXIT_DAEMON_NOT_RUNNING);
+        assert_eq!(output.error.code, "daemon_not_running");
+
+        let (output, exit) = ErrorOutput::usage("error: unexpected argument '--bogus' found\n");
+        assert_eq!(exit, EXIT_USAGE);
+        assert_eq!(output.to_string(), r#"{"error":{"code":"usage","message":"unexpected argument '--bogus' found"}}"#);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod client;
+pub mod json_errors;
 pub mod commands;
 
-pub use client::{ApiClient, ApiClientConfig};
+pub use client::{ApiClient, ApiClientConfig, ConnectRetry, DaemonUnavailable};
 pub use commands::*;
\ No newline at end of file
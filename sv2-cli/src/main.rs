@@ -7,15 +7,39 @@ use std::fs;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::time::Duration;
+use sv2_cli::client::{send_with_retry, ConnectRetry};
+use sv2_cli::json_errors::ErrorOutput;
+use sv2_core::address;
+use sv2_core::scanner::{NetworkScanner, ProbeTimeout, detect_local_subnets, generate_config_recommendations};
 
-mod scanner;
-use scanner::{NetworkScanner, generate_config_recommendations};
+mod clean;
+use clean::{CleanRoots, find_targets, remove_target};
+
+mod simulate;
+use simulate::SimulationConfig;
+
+mod profiles;
+
+mod connection_test;
+
+mod fleet;
+
+mod configure;
+
+mod config_edit;
+
+mod validate_config;
 
 #[derive(Parser)]
 #[command(name = "sv2-cli")]
 #[command(version = "0.1.0")]
 #[command(about = "Stratum V2 command-line interface")]
 struct Cli {
+    /// Print failures as {"error":{"code":...,"message":...}} on stdout. Exit codes:
+    /// 1 error, 2 usage, 3 daemon not running, 4 daemon starting
+    #[arg(long, global = true)]
+    json_errors: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -23,15 +47,35 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Interactive setup wizard
-    Setup,
+    Setup {
+        /// Start from a built-in profile instead of answering hardware and network questions
+        #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(profiles::NAMES))]
+        profile: Option<String>,
+
+        /// Pre-fill translator settings from a file written by 'scan --output'
+        #[arg(long, conflicts_with = "profile")]
+        from_scan: Option<PathBuf>,
+    },
     
     /// Scan network for miners
     Scan {
-        /// Custom subnet to scan (e.g., 192.168.1.0/24)
+        /// Custom subnet to scan (e.g., 192.168.1.0/24); defaults to the subnets of this host's interfaces
         #[arg(short, long)]
         subnet: Option<Vec<String>>,
+
+        /// Miner API ports to probe, comma-separated (default: 4028,80,8080,4029,3333)
+        #[arg(short, long, value_delimiter = ',')]
+        ports: Option<Vec<u16>>,
+
+        /// Per-host connect timeout in milliseconds
+        #[arg(short, long, default_value_t = 3000)]
+        timeout: u64,
+
+        /// Start at --timeout and widen it to match the latency the scan observes
+        #[arg(long)]
+        adaptive: bool,
         
-        /// Save detected miners to file
+        /// Save detected miners and a fleet profile for 'setup --from-scan'
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
@@ -43,25 +87,269 @@ enum Commands {
     Stop,
     
     /// Get daemon status
-    Status,
+    Status {
+        /// Database to read setup milestones from
+        #[arg(long, default_value = "sqlite://sv2d.db")]
+        database_url: String,
+    },
+
+    /// Pause or resume automatic component restarts
+    Maintenance {
+        /// Turn maintenance mode on or off
+        #[arg(value_enum)]
+        mode: MaintenanceToggle,
+    },
     
-    /// Show daemon logs
+    /// Restart one component without stopping the daemon
+    Restart {
+        /// bitcoin, sv2-tp, pool or translator
+        component: String,
+        /// Also restart the components that depend on it (required for sv2-tp)
+        #[arg(long)]
+        with_dependents: bool,
+    },
+
+    /// Show component logs
     Logs {
+        /// sv2d, sv2-tp, pool or translator
+        #[arg(default_value = "sv2d")]
+        component: String,
+        /// Number of recent lines to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
         /// Follow logs in real-time
         #[arg(short, long)]
         follow: bool,
     },
+
+    /// Decode a protocol message for inspection
+    Decode {
+        /// Protocol of the input (sv1 or sv2)
+        #[arg(short, long, default_value = "sv2")]
+        protocol: String,
+
+        /// SV2 frame as hex (read from --file or stdin when no value is given)
+        #[arg(long, num_args = 0..=1, default_missing_value = "")]
+        hex: Option<String>,
+
+        /// SV1 message as JSON (read from --file or stdin when no value is given)
+        #[arg(long, num_args = 0..=1, default_missing_value = "")]
+        json: Option<String>,
+
+        /// Read input from a file
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Remove runtime files and logs left behind by the daemon
+    Clean {
+        /// Also remove the sv2d config directory and database
+        #[arg(long)]
+        all: bool,
+
+        /// List what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Re-feed a connection capture through the protocol parser
+    Replay {
+        /// Capture file written by the daemon
+        capture: PathBuf,
+    },
+
+    /// Store shares that were dead-lettered while the database was unavailable
+    ReplayShares {
+        /// Dead-letter file written by the daemon
+        #[arg(short, long, default_value = "sv2d_dead_letter.jsonl")]
+        file: PathBuf,
+
+        /// Database to store the shares in
+        #[arg(long, default_value = "sqlite://sv2d.db")]
+        database_url: String,
+    },
+
+    /// Generate load with simulated SV1 miners
+    SimulateMiner {
+        /// Translator address to connect to
+        #[arg(short, long, default_value = "127.0.0.1:3333")]
+        address: String,
+
+        /// Number of concurrent simulated miners
+        #[arg(short, long, default_value_t = 1)]
+        count: usize,
+
+        /// Shares per second submitted by each miner
+        #[arg(short, long, default_value_t = 1.0)]
+        rate: f64,
+
+        /// Difficulty to suggest to the translator
+        #[arg(short, long, default_value_t = 1.0)]
+        difficulty: f64,
+
+        /// Fraction of shares submitted against stale work (0.0 to 1.0)
+        #[arg(long, default_value_t = 0.0)]
+        reject_ratio: f64,
+
+        /// How long to run, in seconds
+        #[arg(long, default_value_t = 60)]
+        duration: u64,
+
+        /// Prefix for simulated worker names
+        #[arg(long, default_value = "sim")]
+        worker_prefix: String,
+    },
+
+    /// Point detected miners at this pool through their HTTP API (Bitaxe only)
+    ConfigureMiners {
+        /// Stratum address miners should connect to, e.g. 192.168.1.5:3333
+        #[arg(long)]
+        pool: String,
+
+        /// Stratum user to set on every miner; each keeps its own when omitted
+        #[arg(long)]
+        worker: Option<String>,
+
+        /// Use the miners in a file written by 'scan --output' instead of scanning
+        #[arg(long)]
+        from_scan: Option<PathBuf>,
+
+        /// Show which miners would be changed without changing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check that an upstream pool is reachable and speaks the expected protocol
+    TestConnection {
+        /// Pool URL (stratum+tcp://host:port or stratum2+tcp://host:port[/authority_pubkey])
+        pool_url: String,
+
+        /// Seconds to wait for the connection and for the pool's answer
+        #[arg(short, long, default_value_t = 10)]
+        timeout: u64,
+    },
+
+    /// Read or change a setting in ~/.sv2d/config.toml
+    ///
+    /// 'config <key>' is short for 'config get <key>', and
+    /// 'config <key> <value>' for 'config set <key> <value>'.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+
+        /// Setting to print, e.g. 'pool.signature'
+        #[arg(required = true)]
+        key: Option<String>,
+
+        /// New value to set instead of printing it
+        value: Option<String>,
+    },
+
+    /// Check ~/.sv2d/config.toml and the Bitcoin RPC it points at, without the daemon running
+    #[command(name = "validate-config")]
+    Validate {
+        /// Config file to check instead of ~/.sv2d/config.toml
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+
+    /// List blocks the daemon has found and whether they confirmed
+    Blocks {
+        /// Database the daemon records found blocks in
+        #[arg(long, default_value = "sqlite://sv2d.db")]
+        database_url: String,
+
+        /// Most recent blocks to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: u32,
+    },
+
+    /// Ban or unban a single worker name without touching its connection
+    Workers {
+        /// sv2-web API to send the change to
+        #[arg(long, default_value = "http://127.0.0.1:8080")]
+        api_url: String,
+
+        #[command(subcommand)]
+        action: WorkerAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorkerAction {
+    /// Reject the worker's shares; other workers on its connection keep mining
+    Ban {
+        name: String,
+        /// Why the worker was banned, shown by 'workers bans'
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Accept the worker's shares again
+    Unban {
+        name: String,
+    },
+
+    /// List banned workers
+    Bans,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a setting, e.g. 'translator.min_extranonce2_size'
+    Get {
+        key: String,
+    },
+
+    /// Change a setting; the whole file is validated before it's saved
+    Set {
+        key: String,
+        /// New value; separate several bind addresses with commas
+        value: String,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum MaintenanceToggle {
+    On,
+    Off,
 }
 
 #[derive(Debug, Serialize)]
 struct JsonRpcRequest {
+    id: u64,
     method: String,
     params: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize)]
 struct JsonRpcResponse {
+    #[serde(default)]
     result: serde_json::Value,
+    /// Set when the daemon rejected the request or the method failed
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// Codes sv2d answers when the request itself was wrong, rather than the
+/// method failing while it ran
+const REQUEST_ERROR_CODES: std::ops::RangeInclusive<i64> = -32700..=-32600;
+
+impl JsonRpcError {
+    fn into_error(self, method: &str) -> anyhow::Error {
+        if REQUEST_ERROR_CODES.contains(&self.code) {
+            anyhow::anyhow!("sv2d rejected '{}' ({}): {}", method, self.code, self.message)
+        } else {
+            anyhow::anyhow!("'{}' failed in sv2d: {}", method, self.message)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -71,6 +359,33 @@ struct StatusResponse {
     miners: ConnectedMinersInfo,
     system_info: SystemInfo,
     uptime_seconds: u64,
+    #[serde(default)]
+    maintenance_expires_in_seconds: Option<u64>,
+    #[serde(default)]
+    listeners: Vec<ListenerStatus>,
+    #[serde(default)]
+    startup: StartupStatus,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StartupStatus {
+    attempts: u32,
+    steps: Vec<StartupStepStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartupStepStatus {
+    name: String,
+    state: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListenerStatus {
+    address: String,
+    relayed: bool,
+    active_connections: u64,
+    total_connections: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,6 +396,8 @@ struct ComponentStatusInfo {
     restart_count: u32,
     health_status: String,
     last_error: Option<String>,
+    #[serde(default)]
+    breaker_state: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -106,34 +423,62 @@ struct SystemInfo {
     bitcoin_synced: Option<bool>,
     sv2_version: String,
     daemon_version: String,
+    #[serde(default)]
+    binaries: std::collections::HashMap<String, BinaryInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinaryInfo {
+    path: String,
+    sha256: String,
+    version: Option<String>,
 }
 
 async fn send_rpc_request(method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+    rpc_request(method, params, ConnectRetry::default()).await
+}
+
+/// Send one JSON-RPC call, retrying per `retry` while sv2d is still binding its port
+async fn rpc_request(method: &str, params: serde_json::Value, retry: ConnectRetry) -> Result<serde_json::Value> {
     let client = Client::new();
     
     let request = JsonRpcRequest {
+        id: 1,
         method: method.to_string(),
         params,
     };
     
-    let response = client
-        .post("http://127.0.0.1:8333")
+    let request = client
+        .post(daemon_rpc_url())
         .json(&request)
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await
-        .context("Failed to connect to sv2d daemon. Is it running?")?;
+        .timeout(Duration::from_secs(30));
+    let response = send_with_retry(request, retry).await?;
     
     if response.status().is_success() {
         let rpc_response: JsonRpcResponse = response.json().await?;
-        Ok(rpc_response.result)
+        match rpc_response.error {
+            Some(error) => Err(error.into_error(method)),
+            None => Ok(rpc_response.result),
+        }
     } else {
         Err(anyhow::anyhow!("RPC request failed: {}", response.status()))
     }
 }
 
+/// sv2d's JSON-RPC endpoint, on the `[ports] json_rpc` port of ~/.sv2d/config.toml
+fn daemon_rpc_url() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let port = fs::read_to_string(format!("{}/.sv2d/config.toml", home))
+        .ok()
+        .and_then(|config| config.parse::<toml::Value>().ok())
+        .and_then(|config| config.get("ports")?.get("json_rpc")?.as_integer())
+        .and_then(|port| u16::try_from(port).ok())
+        .unwrap_or(8333);
+    format!("http://127.0.0.1:{}", port)
+}
+
 async fn check_daemon_running() -> bool {
-    send_rpc_request("status", json!({})).await.is_ok()
+    rpc_request("status", json!({}), ConnectRetry::none()).await.is_ok()
 }
 
 async fn start_daemon() -> Result<()> {
@@ -184,19 +529,27 @@ async fn start_daemon() -> Result<()> {
 }
 
 async fn handle_start() -> Result<()> {
+    // A running daemon whose startup stopped partway retries from the failed step
+    if check_daemon_running().await {
+        let result = send_rpc_request("start", json!({})).await?;
+        match result.get("status").and_then(|v| v.as_str()) {
+            Some("retrying") => {
+                let step = result.get("failed_step").and_then(|v| v.as_str()).unwrap_or("unknown");
+                println!("🔁 Retrying startup from the '{}' step", step);
+                println!("💡 Use 'sv2-cli status' to follow progress");
+            }
+            Some("starting") => println!("⏳ sv2d is still starting components"),
+            _ => println!("✅ sv2d daemon is already running"),
+        }
+        return Ok(());
+    }
+
     // First start the daemon process if needed
     start_daemon().await?;
 
-    // Wait a bit for daemon to be ready
-    for i in 0..10 {
-        if check_daemon_running().await {
-            break;
-        }
-        if i == 9 {
-            return Err(anyhow::anyhow!("Daemon failed to become ready"));
-        }
-        tokio::time::sleep(Duration::from_millis(500)).await;
-    }
+    // Wait for the RPC port to come up, about 6s in all
+    let ready = ConnectRetry { attempts: 6, initial_backoff: Duration::from_millis(200) };
+    rpc_request("status", json!({}), ready).await.context("sv2d started but didn't become ready")?;
 
     // sv2d now auto-starts components, so just confirm they're running
     println!("✅ sv2d daemon started - components will auto-start");
@@ -216,7 +569,48 @@ async fn handle_stop() -> Result<()> {
     Ok(())
 }
 
-async fn handle_status() -> Result<()> {
+async fn handle_maintenance(mode: MaintenanceToggle) -> Result<()> {
+    if !check_daemon_running().await {
+        println!("❌ sv2d daemon is not running");
+        return Ok(());
+    }
+
+    let enabled = matches!(mode, MaintenanceToggle::On);
+    let result = send_rpc_request("maintenance", json!({ "enabled": enabled })).await?;
+
+    match result.get("expires_in_seconds").and_then(|v| v.as_u64()) {
+        Some(expires_in) => {
+            println!("🔧 Maintenance mode on: components that exit will not be restarted");
+            println!("   Expires automatically in {}", format_duration(expires_in));
+            println!("💡 Use 'sv2-cli maintenance off' when you're done");
+        }
+        None => println!("✅ Maintenance mode off: automatic restarts resumed"),
+    }
+    Ok(())
+}
+
+async fn handle_restart(component: &str, with_dependents: bool) -> Result<()> {
+    if !check_daemon_running().await {
+        println!("❌ sv2d daemon is not running");
+        return Ok(());
+    }
+
+    println!("🔄 Restarting {}...", component);
+    let result = send_rpc_request(
+        "restart",
+        json!({ "component": component, "with_dependents": with_dependents }),
+    )
+    .await?;
+
+    let restarted: Vec<&str> = result.get("components")
+        .and_then(|c| c.as_array())
+        .map(|components| components.iter().filter_map(|c| c.as_str()).collect())
+        .unwrap_or_default();
+    println!("✅ Restarted {}", restarted.join(", "));
+    Ok(())
+}
+
+async fn handle_status(database_url: &str) -> Result<()> {
     if !check_daemon_running().await {
         println!("❌ sv2d daemon is not running");
         return Ok(());
@@ -230,7 +624,32 @@ async fn handle_status() -> Result<()> {
     println!("{:=<80}", "");
     println!("Overall Status: {}", if status.running { "✅ Running" } else { "❌ Stopped" });
     println!("Daemon Uptime: {}", format_duration(status.uptime_seconds));
+    if let Some(expires_in) = status.maintenance_expires_in_seconds {
+        println!("🔧 Maintenance mode: restarts paused (expires in {})", format_duration(expires_in));
+    }
     println!();
+
+    // Only shown while startup hasn't finished or stopped partway
+    if status.startup.steps.iter().any(|step| step.state != "completed") {
+        println!("🚀 Startup (attempt {}):", status.startup.attempts);
+        for step in &status.startup.steps {
+            let icon = match step.state.as_str() {
+                "completed" => "✅",
+                "running" => "⏳",
+                "failed" => "❌",
+                "aborted" => "⏹",
+                _ => "⏸",
+            };
+            println!("   {} {} - {}", icon, step.name, step.state);
+            if let Some(error) = &step.error {
+                println!("      Error: {}", error);
+            }
+        }
+        if status.startup.steps.iter().any(|step| step.state == "failed") {
+            println!("💡 Fix the error above and run 'sv2-cli start' to retry from the failed step");
+        }
+        println!();
+    }
     
     // System Information
     println!("🖥  System Information:");
@@ -244,6 +663,15 @@ async fn handle_status() -> Result<()> {
     if let Some(synced) = status.system_info.bitcoin_synced {
         println!("   Bitcoin Synced: {}", if synced { "✅ Yes" } else { "⏳ Syncing" });
     }
+    let mut binaries: Vec<_> = status.system_info.binaries.iter().collect();
+    binaries.sort_by_key(|(name, _)| *name);
+    for (name, binary) in binaries {
+        println!("   {}: {} ({}, sha256 {})",
+                 name,
+                 binary.version.as_deref().unwrap_or("unknown version"),
+                 binary.path,
+                 &binary.sha256[..binary.sha256.len().min(12)]);
+    }
     println!();
     
     // Components
@@ -270,6 +698,12 @@ async fn handle_status() -> Result<()> {
             print!(" - Restarts: {}", component.restart_count);
         }
         
+        match component.breaker_state.as_deref() {
+            Some("Open") => print!(" - Circuit breaker open (restarts paused)"),
+            Some("HalfOpen") => print!(" - Circuit breaker half-open (probing)"),
+            _ => {}
+        }
+        
         println!();
         
         if let Some(error) = &component.last_error {
@@ -297,10 +731,54 @@ async fn handle_status() -> Result<()> {
             println!(" - Connected: {}", miner.connected_at);
         }
     }
+
+    if status.listeners.len() > 1 {
+        println!();
+        println!("🔌 Listeners:");
+        for listener in &status.listeners {
+            print!("   {} - {} connected", listener.address, listener.active_connections);
+            if let Some(total) = listener.total_connections {
+                print!(" ({} total)", total);
+            }
+            println!("{}", if listener.relayed { " - relayed by sv2d" } else { " - translator" });
+        }
+    }
+
+    print_milestones(database_url).await;
     
     Ok(())
 }
 
+/// Celebrate the first share and block, if the database has them
+async fn print_milestones(database_url: &str) {
+    use sv2_core::{milestones::Milestone, DatabaseOps, DatabasePool};
+
+    // Don't create a database just to find it empty
+    if let Some(path) = database_url.strip_prefix("sqlite://") {
+        if !std::path::Path::new(path).exists() {
+            return;
+        }
+    }
+    let Ok(database) = DatabasePool::new(database_url, 1).await else {
+        return;
+    };
+    let milestones = database.get_milestones().await.unwrap_or_default();
+    if milestones.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("🏁 Milestones:");
+    for record in milestones {
+        let icon = match record.milestone {
+            Milestone::FirstShare => "🎉",
+            Milestone::FirstBlock => "🏆",
+        };
+        println!("   {} {} ({})", icon, record.milestone.message(),
+                 record.reached_at.format("%Y-%m-%d %H:%M UTC"));
+    }
+}
+
 fn format_duration(seconds: u64) -> String {
     let days = seconds / 86400;
     let hours = (seconds % 86400) / 3600;
@@ -318,22 +796,379 @@ fn format_duration(seconds: u64) -> String {
     }
 }
 
-async fn handle_logs(follow: bool) -> Result<()> {
-    // For now, just show that logs would be here
-    // In a full implementation, we'd tail the daemon log file
-    println!("📝 Daemon logs:");
-    
+async fn handle_logs(component: &str, lines: usize, follow: bool) -> Result<()> {
+    let params = json!({ "component": component, "lines": lines });
     if follow {
-        println!("Following logs... (Ctrl+C to exit)");
-        // TODO: Implement real log following
-        loop {
-            tokio::time::sleep(Duration::from_secs(1)).await;
+        return follow_logs(params).await;
+    }
+
+    let result = send_rpc_request("logs", params).await?;
+    let path = result.get("path").and_then(|p| p.as_str()).unwrap_or_default();
+    let log_lines = result.get("lines").and_then(|l| l.as_array()).cloned().unwrap_or_default();
+    if log_lines.is_empty() {
+        println!("📝 No {} log output yet ({})", component, path);
+        return Ok(());
+    }
+    for line in log_lines {
+        println!("{}", line.as_str().unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Print a `logs_follow` stream as it arrives, until sv2d ends it or Ctrl+C
+async fn follow_logs(params: serde_json::Value) -> Result<()> {
+    let request = JsonRpcRequest { id: 1, method: "logs_follow".to_string(), params };
+    // No timeout: the response lasts as long as the follow does
+    let request = Client::new().post(daemon_rpc_url()).json(&request);
+    let mut response = send_with_retry(request, ConnectRetry::default()).await?;
+
+    // Anything but the text stream is a JSON-RPC answer, such as invalid params
+    let streamed = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/plain"));
+    if !streamed {
+        let rpc_response: JsonRpcResponse = response.json().await?;
+        return match rpc_response.error {
+            Some(error) => Err(error.into_error("logs_follow")),
+            None => Err(anyhow::anyhow!("sv2d did not stream logs: {}", rpc_response.result)),
+        };
+    }
+
+    let mut stdout = std::io::stdout();
+    while let Some(chunk) = response.chunk().await.context("Log stream interrupted")? {
+        std::io::Write::write_all(&mut stdout, &chunk)?;
+        std::io::Write::flush(&mut stdout)?;
+    }
+    Ok(())
+}
+
+fn read_decode_input(inline: Option<String>, file: Option<PathBuf>) -> Result<String> {
+    if let Some(input) = inline.filter(|i| !i.is_empty()) {
+        return Ok(input);
+    }
+
+    if let Some(path) = file {
+        return fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()));
+    }
+
+    let mut input = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+        .context("Failed to read from stdin")?;
+    Ok(input)
+}
+
+async fn handle_decode(
+    protocol: String,
+    hex_input: Option<String>,
+    json_input: Option<String>,
+    file: Option<PathBuf>,
+) -> Result<()> {
+    use sv2_core::protocol::{parse_sv1_message, parse_sv2_message};
+
+    let parsed = match protocol.to_lowercase().as_str() {
+        "sv2" => {
+            let input = read_decode_input(hex_input, file)?;
+            let cleaned: String = input.split_whitespace().collect();
+            let bytes = hex::decode(cleaned.trim_start_matches("0x"))
+                .context("Input is not valid hex")?;
+            println!("🔎 Decoding {} byte SV2 frame", bytes.len());
+            parse_sv2_message(&bytes)
+        }
+        "sv1" => {
+            let input = read_decode_input(json_input, file)?;
+            println!("🔎 Decoding SV1 message");
+            parse_sv1_message(&input)
+        }
+        other => return Err(anyhow::anyhow!("Unknown protocol: {}. Use sv1 or sv2", other)),
+    };
+
+    match parsed {
+        Ok(message) => {
+            println!("✅ {}", message.message_type());
+            println!("{}", serde_json::to_string_pretty(&message)?);
+            Ok(())
+        }
+        Err(e) => {
+            println!("❌ Parse error at offset {}: {}", e.offset, e.reason);
+            Err(anyhow::anyhow!(e))
         }
-    } else {
-        println!("Log file location: ~/.sv2d/sv2d.log");
-        println!("Use 'sv2-cli logs --follow' to follow in real-time");
     }
-    
+}
+
+async fn handle_replay(capture_path: PathBuf) -> Result<()> {
+    use sv2_core::capture::{read_capture, CaptureDirection};
+
+    let records = read_capture(&capture_path)
+        .with_context(|| format!("Failed to read capture {}", capture_path.display()))?;
+
+    println!("📼 Replaying {} ({} records)", capture_path.display(), records.len());
+    println!("{:-<80}", "");
+
+    let mut pending = String::new();
+    let mut parsed = 0;
+    let mut failed = 0;
+
+    for (index, record) in records.iter().enumerate() {
+        let arrow = match record.direction {
+            CaptureDirection::Inbound => "→",
+            CaptureDirection::Outbound => "←",
+        };
+
+        if record.direction == CaptureDirection::Outbound {
+            let bytes = record.bytes()?;
+            println!("{:>4} {} {}", index + 1, arrow, String::from_utf8_lossy(&bytes).trim());
+            continue;
+        }
+
+        pending.push_str(&String::from_utf8_lossy(&record.bytes()?));
+
+        // Inbound traffic is newline-delimited JSON, possibly split across reads
+        while let Some(newline_pos) = pending.find('\n') {
+            let line = pending[..newline_pos].trim().to_string();
+            pending.drain(..=newline_pos);
+
+            if line.is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<serde_json::Value>(&line) {
+                Ok(message) => {
+                    parsed += 1;
+                    let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("response");
+                    println!("{:>4} {} {} {}", index + 1, arrow, method, message);
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!("{:>4} {} ❌ parse error at column {}: {}", index + 1, arrow, e.column(), line);
+                }
+            }
+        }
+    }
+
+    if !pending.trim().is_empty() {
+        failed += 1;
+        println!("     ❌ trailing partial message: {}", pending.trim());
+    }
+
+    println!("{:-<80}", "");
+    println!("Parsed: {} | Failed: {}", parsed, failed);
+
+    Ok(())
+}
+
+async fn handle_replay_shares(file: PathBuf, database_url: String) -> Result<()> {
+    use sv2_core::{dead_letter, DatabasePool};
+
+    if !file.exists() {
+        println!("✅ No dead-lettered shares at {}", file.display());
+        return Ok(());
+    }
+
+    let pending = dead_letter::read_dead_letters(&file)
+        .with_context(|| format!("Failed to read dead-letter file {}", file.display()))?;
+    println!("📥 Replaying {} dead-lettered shares from {}", pending.len(), file.display());
+
+    let database = DatabasePool::new(&database_url, 1)
+        .await
+        .with_context(|| format!("Failed to open database {}", database_url))?;
+    let report = dead_letter::replay(&file, &database).await?;
+
+    println!("Replayed: {} | Still failing: {}", report.replayed, report.remaining);
+    if report.remaining > 0 {
+        anyhow::bail!(
+            "{} shares could not be stored and were kept in {}",
+            report.remaining,
+            file.display()
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_configure_miners(
+    pool: &str,
+    worker: Option<String>,
+    from_scan: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
+    use configure::{supports_remote_config, MinerConfigurator, Outcome, PoolTarget};
+
+    let target = PoolTarget::parse(pool)?;
+    let miners = match from_scan {
+        Some(path) => fleet::ScanFile::read(&path)?.miners,
+        None => {
+            println!("🔍 Scanning network for miners...");
+            let subnets = Some(detect_local_subnets()).filter(|subnets| !subnets.is_empty());
+            NetworkScanner::new().scan_network(subnets).await?
+        }
+    };
+
+    let (supported, unsupported): (Vec<_>, Vec<_>) =
+        miners.iter().partition(|miner| supports_remote_config(&miner.miner_type));
+    for miner in &unsupported {
+        println!("⏭  {} at {}: remote configuration not supported, set the pool by hand", miner.miner_type, miner.ip);
+    }
+    if supported.is_empty() {
+        println!("❌ No miners that can be configured remotely were found");
+        return Ok(());
+    }
+
+    println!("\n{}", if dry_run { "Would point these miners at (dry run):" } else { "These miners will be pointed at:" });
+    println!("   {}:{}", target.host, target.port);
+    for miner in &supported {
+        let current = miner.details.pool_url.as_deref().unwrap_or("unknown pool");
+        println!("   {} at {} (currently {})", miner.miner_type, miner.ip, current);
+    }
+
+    if !dry_run && !confirm("\nApply and restart these miners? (y/N): ", "y")? {
+        println!("Configuration cancelled.");
+        return Ok(());
+    }
+
+    let configurator = MinerConfigurator::new(target, worker);
+    let mut failed = 0;
+    for miner in supported {
+        match configurator.configure(miner, dry_run).await {
+            Outcome::Configured => println!("✅ {} configured and restarting", miner.ip),
+            Outcome::WouldConfigure => {}
+            Outcome::Unsupported => println!("⏭  {}: remote configuration not supported", miner.ip),
+            Outcome::Failed(reason) => {
+                failed += 1;
+                println!("❌ {}: {}", miner.ip, reason);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} miner(s) could not be configured", failed);
+    }
+    Ok(())
+}
+
+async fn handle_test_connection(pool_url: &str, timeout: u64) -> Result<()> {
+    let url: sv2_core::StratumUrl = pool_url.parse()?;
+    println!("🔌 Testing {} ...", url);
+
+    let report = connection_test::test_connection(&url, Duration::from_secs(timeout.max(1))).await?;
+
+    println!("✅ {} responded", report.address);
+    println!("Protocol: {}", report.protocol);
+    println!("Version: {}", report.version);
+    println!(
+        "Latency: connect {} ms | response {} ms",
+        report.connect_latency.as_millis(),
+        report.response_latency.as_millis()
+    );
+    if url.is_sv2() && url.authority_key.is_none() {
+        println!("⚠️  No authority key in the URL; client mode will need the pool's authority_pubkey to verify it");
+    }
+
+    Ok(())
+}
+
+fn confirm(prompt: &str, expected: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{}", prompt);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case(expected))
+}
+
+async fn handle_simulate_miner(config: SimulationConfig) -> Result<()> {
+    config.validate()?;
+
+    println!(
+        "⛏️  Simulating {} miner(s) against {} at {} share(s)/s each for {}s (Ctrl-C to stop early)",
+        config.count,
+        config.address,
+        config.rate,
+        config.duration.as_secs()
+    );
+
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = stop_tx.send(true);
+        }
+    });
+
+    let target_rate = config.rate * config.count as f64;
+    let report = simulate::run(config, stop_rx).await?;
+
+    println!("{:-<60}", "");
+    println!("Miners connected: {}/{}", report.connected, report.miners);
+    println!(
+        "Shares submitted: {} | accepted: {} | rejected: {}",
+        report.submitted, report.accepted, report.rejected
+    );
+    if report.errors > 0 {
+        println!("Miner errors: {}", report.errors);
+    }
+    println!(
+        "Achieved submit rate: {:.2} shares/s (target {:.2}) over {:.1}s",
+        report.submit_rate(),
+        target_rate,
+        report.elapsed.as_secs_f64()
+    );
+
+    Ok(())
+}
+
+async fn handle_clean(all: bool, dry_run: bool) -> Result<()> {
+    if !dry_run && check_daemon_running().await {
+        println!("❌ sv2d daemon is running. Stop it first with 'sv2-cli stop'");
+        return Ok(());
+    }
+
+    let mut targets = find_targets(&CleanRoots::from_env(), all);
+    if targets.is_empty() {
+        println!("✨ Nothing to clean");
+        return Ok(());
+    }
+
+    println!("{}", if dry_run { "Would remove (dry run):" } else { "The following will be removed:" });
+    for target in &targets {
+        let warning = if target.mainnet_datadir { " ⚠️  MAINNET" } else { "" };
+        println!("   {} ({}){}", target.path.display(), target.description, warning);
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    if !confirm("\nDelete these files? (y/N): ", "y")? {
+        println!("Clean cancelled.");
+        return Ok(());
+    }
+
+    if targets.iter().any(|t| t.mainnet_datadir) {
+        println!("\n⚠️  A mainnet Bitcoin datadir is included. Deleting it means re-downloading the chain.");
+        if !confirm("Type 'delete mainnet' to remove it too, anything else to keep it: ", "delete mainnet")? {
+            println!("Keeping mainnet datadir.");
+            targets.retain(|t| !t.mainnet_datadir);
+        }
+    }
+
+    let mut failed = 0;
+    for target in &targets {
+        match remove_target(&target.path) {
+            Ok(()) => println!("🗑  Removed {}", target.path.display()),
+            Err(e) => {
+                println!("❌ {:#}", e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} of {} targets could not be removed", failed, targets.len()));
+    }
+    println!("✅ Clean complete");
     Ok(())
 }
 
@@ -349,17 +1184,59 @@ fn create_config_dir() -> Result<PathBuf> {
     Ok(config_dir)
 }
 
-async fn handle_scan(subnets: Option<Vec<String>>, output: Option<PathBuf>) -> Result<()> {
+async fn handle_scan(
+    subnets: Option<Vec<String>>,
+    ports: Option<Vec<u16>>,
+    timeout_ms: u64,
+    adaptive: bool,
+    output: Option<PathBuf>,
+) -> Result<()> {
     println!("🔍 Scanning network for miners...");
-    
-    let scanner = NetworkScanner::new();
+
+    if timeout_ms == 0 {
+        anyhow::bail!("--timeout must be greater than 0");
+    }
+    let timeout = Duration::from_millis(timeout_ms);
+    let mut scanner = NetworkScanner::new().with_timeout(if adaptive {
+        ProbeTimeout::adaptive(timeout)
+    } else {
+        ProbeTimeout::fixed(timeout)
+    });
+    if let Some(ports) = ports {
+        scanner = scanner.with_ports(ports)?;
+    }
+
+    let subnets = match subnets {
+        Some(subnets) => Some(subnets),
+        None => {
+            let detected = detect_local_subnets();
+            if detected.is_empty() {
+                println!("⚠️  Could not detect local subnets, scanning common home subnets");
+                None
+            } else {
+                println!("🌐 Auto-selected subnets: {}", detected.join(", "));
+                Some(detected)
+            }
+        }
+    };
     let miners = scanner.scan_network(subnets).await?;
+
+    if adaptive {
+        println!(
+            "⏱  Probe timeout: {} ms (adaptive, started at {} ms)",
+            scanner.effective_timeout().as_millis(),
+            timeout_ms
+        );
+    } else {
+        println!("⏱  Probe timeout: {} ms", scanner.effective_timeout().as_millis());
+    }
     
     if miners.is_empty() {
         println!("❌ No miners detected on the network");
         println!("   • Make sure miners are powered on and connected");
         println!("   • Check that you're on the same network");
         println!("   • Try specifying different subnets with --subnet");
+        println!("   • If your miners use a custom API port, add it with --ports");
         return Ok(());
     }
     
@@ -414,27 +1291,32 @@ async fn handle_scan(subnets: Option<Vec<String>>, output: Option<PathBuf>) -> R
     }
     
     // Save to file if requested
+    let saved = output.is_some();
     if let Some(output_path) = output {
-        let scan_results = serde_json::json!({
-            "scan_time": chrono::Utc::now().to_rfc3339(),
-            "miners": miners,
-            "recommendations": recommendations
-        });
-        
-        fs::write(&output_path, serde_json::to_string_pretty(&scan_results)?)?;
-        println!("\n💾 Scan results saved to: {}", output_path.display());
+        fleet::ScanFile::new(miners).write(&output_path)?;
+        println!("\n💾 Scan results and fleet profile saved to: {}", output_path.display());
     }
     
     println!("\n💡 Next steps:");
-    println!("   1. Update your sv2-cli setup with these recommendations");
+    if saved {
+        println!("   1. Run 'sv2-cli setup --from-scan <file>' to apply these recommendations");
+    } else {
+        println!("   1. Update your sv2-cli setup with these recommendations");
+    }
     println!("   2. Point miners to YOUR_IP:3333 when ready");
     println!("   3. Use 'sv2-cli status' to monitor connections");
     
     Ok(())
 }
 
-async fn handle_setup() -> Result<()> {
+async fn handle_setup(profile: Option<String>, from_scan: Option<PathBuf>) -> Result<()> {
     println!("🎰 SV2 Solo Mining Setup Wizard\n");
+
+    // Read the scan before asking anything so a bad file fails fast
+    let fleet = match &from_scan {
+        Some(path) => Some(fleet::ScanFile::read(path)?.fleet()),
+        None => None,
+    };
     
     // Create config directory
     let config_dir = create_config_dir()?;
@@ -453,29 +1335,44 @@ async fn handle_setup() -> Result<()> {
             return Ok(());
         }
     }
+
+    if let Some(profile) = profile {
+        return setup_from_profile(&config_path, &profile);
+    }
     
-    // 1. Hardware selection
-    println!("What hardware do you have?");
-    println!("1) Bitaxe (~700 GH/s)");
-    println!("2) FutureBit Apollo (~4.8 TH/s)");
-    println!("3) Mixed or unknown");
-    print!("Choice (1-3): ");
-    
+    // 1. Hardware selection, answered by the scan when there is one
     let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    let hardware_choice = input.trim().parse::<u32>().unwrap_or(3);
-    
-    let min_extranonce2_size = match hardware_choice {
-        1 => 4,  // Bitaxe can work with smaller
-        2 => 16, // Apollo needs 16
-        _ => 16, // Universal safe default
+    let translator_section = match &fleet {
+        Some(fleet) => {
+            println!("Using scanned fleet: {}", fleet.summary());
+            fleet.translator_section()
+        }
+        None => {
+            println!("What hardware do you have?");
+            println!("1) Bitaxe (~700 GH/s)");
+            println!("2) FutureBit Apollo (~4.8 TH/s)");
+            println!("3) Mixed or unknown");
+            print!("Choice (1-3): ");
+
+            std::io::stdin().read_line(&mut input)?;
+            let hardware_choice = input.trim().parse::<u32>().unwrap_or(3);
+
+            let min_extranonce2_size = match hardware_choice {
+                1 => 4,  // Bitaxe can work with smaller
+                2 => 16, // Apollo needs 16
+                _ => 16, // Universal safe default
+            };
+            format!("[translator]\nbind_address = \"0.0.0.0:3333\"\nmin_extranonce2_size = {}\n", min_extranonce2_size)
+        }
     };
     
     // 2. Network selection
     println!("\nWhich network?");
     println!("1) Regtest (testing, instant blocks)");
     println!("2) Signet (practice with free coins)");
-    print!("Choice (1-2): ");
+    println!("3) Testnet4 (public test network)");
+    println!("4) Mainnet (real bitcoin)");
+    print!("Choice (1-4): ");
     
     input.clear();
     std::io::stdin().read_line(&mut input)?;
@@ -483,37 +1380,22 @@ async fn handle_setup() -> Result<()> {
     
     let network = match network_choice {
         1 => "regtest",
+        3 => "testnet4",
+        4 => "mainnet",
         _ => "signet",
     };
     
     let rpc_url = match network {
         "regtest" => "http://127.0.0.1:18443",
+        "testnet4" => "http://127.0.0.1:48332",
+        "mainnet" => "http://127.0.0.1:8332",
         _ => "http://127.0.0.1:38332",
     };
     
     // 3. Mining address
     println!("\nWhat's your Bitcoin address?");
     println!("(Where block rewards go if you find a block)");
-    
-    let address_prefix = match network {
-        "regtest" => "bcrt1",
-        _ => "tb1", // signet uses testnet addresses
-    };
-    
-    loop {
-        print!("Address: ");
-        input.clear();
-        std::io::stdin().read_line(&mut input)?;
-        let address = input.trim();
-        
-        if address.starts_with(address_prefix) {
-            break;
-        }
-        
-        println!("❌ Invalid address. Must start with {} for {}", address_prefix, network);
-    }
-    
-    let mining_address = input.trim().to_string();
+    let mining_address = read_coinbase_address(network)?;
     
     // 4. Generate config
     let config = format!(
@@ -530,11 +1412,8 @@ rpc_password = "test"
 signature = "SV2"
 coinbase_address = "{}"
 
-[translator]
-bind_address = "0.0.0.0:3333"
-min_extranonce2_size = {}
-"#,
-        network, rpc_url, mining_address, min_extranonce2_size
+{}"#,
+        network, rpc_url, mining_address, translator_section
     );
     
     // 5. Write config
@@ -555,16 +1434,264 @@ min_extranonce2_size = {}
     Ok(())
 }
 
+/// Ask until the user gives an address that can receive coins on `network`
+fn read_coinbase_address(network: &str) -> Result<String> {
+    let mut input = String::new();
+    loop {
+        print!("Address: ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        input.clear();
+        std::io::stdin().read_line(&mut input)?;
+        let pasted = input.trim();
+
+        match address::check_coinbase_address(pasted, network) {
+            Ok(()) => return Ok(pasted.to_string()),
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+}
+
+/// Write a config that selects `profile`, asking only for per-user settings
+fn setup_from_profile(config_path: &std::path::Path, profile: &str) -> Result<()> {
+    let network = profiles::network(profile)?;
+    println!("Using the '{}' profile ({})\n", profile, network);
+
+    println!("What's your Bitcoin address?");
+    println!("(Where block rewards go if you find a block)");
+    let mining_address = read_coinbase_address(&network)?;
+
+    let credentials = if profiles::needs_rpc_credentials(profile)? {
+        println!("\nBitcoin Core RPC credentials (from your bitcoin.conf)");
+        print!("RPC user: ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut user = String::new();
+        std::io::stdin().read_line(&mut user)?;
+        print!("RPC password: ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut password = String::new();
+        std::io::stdin().read_line(&mut password)?;
+        Some((user.trim().to_string(), password.trim().to_string()))
+    } else {
+        None
+    };
+
+    let config = profiles::render_config(
+        profile,
+        &mining_address,
+        credentials.as_ref().map(|(user, password)| (user.as_str(), password.as_str())),
+    );
+    fs::write(config_path, config).context("Failed to write config file")?;
+
+    println!("\n✅ Setup complete!");
+    println!("Config saved to: {}", config_path.display());
+    println!("Add any setting from the profile to that file to override it.");
+    println!("\nNext steps:");
+    println!("  1. sv2-cli start");
+    println!("  2. Point your miner to: YOUR_IP:3333");
+
+    Ok(())
+}
+
+async fn handle_blocks(database_url: &str, limit: u32) -> Result<()> {
+    use sv2_core::{blocks::BlockStatus, DatabaseOps, DatabasePool};
+
+    if let Some(path) = database_url.strip_prefix("sqlite://") {
+        if !std::path::Path::new(path).exists() {
+            println!("No blocks found yet ({} doesn't exist)", path);
+            return Ok(());
+        }
+    }
+    let database = DatabasePool::new(database_url, 1)
+        .await
+        .with_context(|| format!("Failed to open database {}", database_url))?;
+    let blocks = database.get_blocks(Some(limit)).await?;
+    if blocks.is_empty() {
+        println!("No blocks found yet");
+        return Ok(());
+    }
+
+    let btc = |sats: Option<u64>| sats.map_or("-".to_string(), |sats| format!("{:.8}", sats as f64 / 1e8));
+    println!("🏆 Blocks found");
+    println!("{:<17} {:>8}  {:<16} {:<20} {:>12} {:>11}  Status",
+             "Found (UTC)", "Height", "Hash", "Worker", "Reward BTC", "Fees BTC");
+    for block in blocks {
+        let hash = block.hash.to_string();
+        let status = match block.status {
+            BlockStatus::Submitted => format!("⏳ submitted ({} conf)", block.confirmations),
+            BlockStatus::Confirmed => format!("✅ confirmed ({} conf)", block.confirmations),
+            BlockStatus::Orphaned => "❌ orphaned".to_string(),
+        };
+        println!("{:<17} {:>8}  {:<16} {:<20} {:>12} {:>11}  {}",
+                 block.found_at.format("%Y-%m-%d %H:%M"),
+                 block.height.map_or("-".to_string(), |height| height.to_string()),
+                 format!("{}…{}", &hash[..6], &hash[hash.len() - 6..]),
+                 block.worker.as_deref().unwrap_or("-"),
+                 btc(block.reward),
+                 btc(block.fees),
+                 status);
+    }
+    Ok(())
+}
+
+async fn handle_workers(api_url: &str, action: WorkerAction) -> Result<()> {
+    use sv2_cli::client::ApiClient;
+    use sv2_core::worker_bans::WorkerBan;
+
+    let client = ApiClient::new().with_base_url(api_url)?;
+    let ban_path = |name: &str| format!("/api/v1/workers/{}/ban", name);
+
+    match action {
+        WorkerAction::Ban { name, reason } => {
+            let _: WorkerBan = client.post(&ban_path(&name), &json!({ "reason": reason })).await?;
+            println!("🚫 Banned worker {}; its shares are rejected from now on", name);
+        }
+        WorkerAction::Unban { name } => {
+            client.delete(&ban_path(&name)).await?;
+            println!("✅ Unbanned worker {}", name);
+        }
+        WorkerAction::Bans => {
+            let bans: Vec<WorkerBan> = client.get("/api/v1/workers/bans").await?;
+            if bans.is_empty() {
+                println!("No workers are banned");
+                return Ok(());
+            }
+            println!("{:<17} {:<24} Reason", "Banned (UTC)", "Worker");
+            for ban in bans {
+                println!("{:<17} {:<24} {}",
+                         ban.banned_at.format("%Y-%m-%d %H:%M"),
+                         ban.worker_name,
+                         ban.reason.as_deref().unwrap_or("-"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_config(action: ConfigAction) -> Result<()> {
+    let config_path = create_config_dir()?.join("config.toml");
+    if !config_path.exists() {
+        return Err(anyhow::anyhow!(
+            "Config file not found at {}. Run 'sv2-cli setup' first.",
+            config_path.display()
+        ));
+    }
+    let contents = fs::read_to_string(&config_path).context("Failed to read config file")?;
+
+    match action {
+        ConfigAction::Get { key } => match config_edit::get(&contents, &key)? {
+            Some(value) => println!("{}", value),
+            None => println!("{} is not set; sv2d uses its default or the profile's value", key),
+        },
+        ConfigAction::Set { key, value } => {
+            let updated = config_edit::set(&contents, &key, &value)?;
+            config_edit::write_atomic(&config_path, &updated)?;
+            println!("✅ Set {} = {}", key, value);
+            println!("💡 Restart the daemon to apply: sv2-cli stop && sv2-cli start");
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_validate_config(config: Option<PathBuf>) -> Result<()> {
+    let config_path = match config {
+        Some(path) => path,
+        None => create_config_dir()?.join("config.toml"),
+    };
+    if !config_path.exists() {
+        return Err(anyhow::anyhow!(
+            "Config file not found at {}. Run 'sv2-cli setup' first.",
+            config_path.display()
+        ));
+    }
+    let contents = fs::read_to_string(&config_path).context("Failed to read config file")?;
+
+    println!("Validating {}\n", config_path.display());
+    let checks = validate_config::validate(&contents).await;
+    for check in &checks {
+        match &check.result {
+            Ok(()) => println!("  ✓ {}", check.field),
+            Err(e) => println!("  ✗ {}: {:#}", check.field, e),
+        }
+    }
+
+    let failed = checks.iter().filter(|check| check.result.is_err()).count();
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} of {} checks failed", failed, checks.len()));
+    }
+    println!("\n✅ Config is valid");
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    
-    match cli.command {
-        Commands::Setup => handle_setup().await,
-        Commands::Scan { subnet, output } => handle_scan(subnet, output).await,
+    // Checked by hand too, since arguments that don't parse never produce a `Cli`
+    let json_errors = std::env::args().any(|arg| arg == "--json-errors");
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) if json_errors && e.use_stderr() => {
+            let (output, exit) = ErrorOutput::usage(&e.to_string());
+            println!("{}", output);
+            std::process::exit(exit);
+        }
+        Err(e) => e.exit(),
+    };
+
+    match run(cli.command).await {
+        Err(e) if cli.json_errors => {
+            let (output, exit) = ErrorOutput::from_error(&e);
+            println!("{}", output);
+            std::process::exit(exit);
+        }
+        result => result,
+    }
+}
+
+async fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::Setup { profile, from_scan } => handle_setup(profile, from_scan).await,
+        Commands::Scan { subnet, ports, timeout, adaptive, output } => {
+            handle_scan(subnet, ports, timeout, adaptive, output).await
+        }
         Commands::Start => handle_start().await,
         Commands::Stop => handle_stop().await,
-        Commands::Status => handle_status().await,
-        Commands::Logs { follow } => handle_logs(follow).await,
+        Commands::Status { database_url } => handle_status(&database_url).await,
+        Commands::Maintenance { mode } => handle_maintenance(mode).await,
+        Commands::Restart { component, with_dependents } => handle_restart(&component, with_dependents).await,
+        Commands::Logs { component, lines, follow } => handle_logs(&component, lines, follow).await,
+        Commands::Decode { protocol, hex, json, file } => handle_decode(protocol, hex, json, file).await,
+        Commands::Replay { capture } => handle_replay(capture).await,
+        Commands::ReplayShares { file, database_url } => handle_replay_shares(file, database_url).await,
+        Commands::Clean { all, dry_run } => handle_clean(all, dry_run).await,
+        Commands::SimulateMiner { address, count, rate, difficulty, reject_ratio, duration, worker_prefix } => {
+            handle_simulate_miner(SimulationConfig {
+                address,
+                count,
+                rate,
+                difficulty,
+                reject_ratio,
+                duration: Duration::from_secs(duration),
+                worker_prefix,
+            })
+            .await
+        }
+        Commands::ConfigureMiners { pool, worker, from_scan, dry_run } => {
+            handle_configure_miners(&pool, worker, from_scan, dry_run).await
+        }
+        Commands::TestConnection { pool_url, timeout } => handle_test_connection(&pool_url, timeout).await,
+        Commands::Config { action, key, value } => {
+            let action = match (action, key) {
+                (Some(action), _) => action,
+                (None, Some(key)) => match value {
+                    Some(value) => ConfigAction::Set { key, value },
+                    None => ConfigAction::Get { key },
+                },
+                (None, None) => return Err(anyhow::anyhow!("Name a config key, e.g. 'sv2-cli config pool.signature'")),
+            };
+            handle_config(action)
+        }
+        Commands::Validate { config } => handle_validate_config(config).await,
+        Commands::Blocks { database_url, limit } => handle_blocks(&database_url, limit).await,
+        Commands::Workers { api_url, action } => handle_workers(&api_url, action).await,
     }
 }
\ No newline at end of file
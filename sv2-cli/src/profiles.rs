@@ -0,0 +1,136 @@
+//! Built-in sv2d config profiles for `setup --profile`. sv2d merges the
+//! profile's settings under whatever the generated config sets.
+
+use anyhow::{anyhow, Context, Result};
+
+pub const NAMES: &[&str] = &["regtest-fast", "signet-bitaxe", "mainnet-apollo"];
+
+const SOURCES: &[&str] = &[
+    include_str!("../../config/profiles/regtest-fast.toml"),
+    include_str!("../../config/profiles/signet-bitaxe.toml"),
+    include_str!("../../config/profiles/mainnet-apollo.toml"),
+];
+
+fn profile(name: &str) -> Result<toml::Value> {
+    let index = NAMES
+        .iter()
+        .position(|profile_name| *profile_name == name)
+        .ok_or_else(|| anyhow!("Unknown profile '{}'. Available: {}", name, NAMES.join(", ")))?;
+    toml::from_str(SOURCES[index]).with_context(|| format!("Built-in profile '{}' is invalid", name))
+}
+
+/// Network the profile targets
+pub fn network(name: &str) -> Result<String> {
+    profile(name)?
+        .get("daemon")
+        .and_then(|daemon| daemon.get("network"))
+        .and_then(|network| network.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Profile '{}' does not set [daemon] network", name))
+}
+
+/// Whether the profile leaves Bitcoin RPC credentials for the user to set
+pub fn needs_rpc_credentials(name: &str) -> Result<bool> {
+    let profile = profile(name)?;
+    let bitcoin = profile.get("bitcoin");
+    let has = |key: &str| bitcoin.and_then(|b| b.get(key)).is_some();
+    Ok(!(has("rpc_user") && has("rpc_password")))
+}
+
+/// Config file contents selecting `name`, with only the per-user settings
+pub fn render_config(name: &str, coinbase_address: &str, rpc_credentials: Option<(&str, &str)>) -> String {
+    let mut config = format!(
+        "# Defaults come from the '{}' profile; settings here override it\nprofile = \"{}\"\n",
+        name, name
+    );
+
+    if let Some((user, password)) = rpc_credentials {
+        config.push_str(&format!(
+            "\n[bitcoin]\nrpc_user = {}\nrpc_password = {}\n",
+            toml::Value::String(user.to_string()),
+            toml::Value::String(password.to_string())
+        ));
+    }
+
+    config.push_str(&format!(
+        "\n[pool]\ncoinbase_address = {}\n",
+        toml::Value::String(coinbase_address.to_string())
+    ));
+    config
+}
+
+/// `config` as sv2d reads it: its profile's settings with the file's own on top
+pub fn apply(config: toml::Value) -> Result<toml::Value> {
+    let Some(name) = config.get("profile").and_then(toml::Value::as_str) else {
+        return Ok(config);
+    };
+
+    let mut merged = profile(name)?;
+    merge(&mut merged, config);
+    Ok(merged)
+}
+
+/// Tables merge key by key; anything else in `overrides` replaces `base`
+fn merge(base: &mut toml::Value, overrides: toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_profile_parses() {
+        assert_eq!(NAMES.len(), SOURCES.len());
+        for name in NAMES {
+            let network = network(name).unwrap();
+            assert!(["regtest", "signet", "mainnet"].contains(&network.as_str()), "{}", name);
+        }
+        assert!(network("testnet-s21").is_err());
+    }
+
+    #[test]
+    fn test_mainnet_requires_credentials() {
+        assert!(!needs_rpc_credentials("regtest-fast").unwrap());
+        assert!(needs_rpc_credentials("mainnet-apollo").unwrap());
+    }
+
+    #[test]
+    fn test_rendered_config() {
+        let config = render_config("mainnet-apollo", "bc1qexample", Some(("alice", "p\"ss")));
+        let parsed: toml::Value = toml::from_str(&config).unwrap();
+
+        assert_eq!(parsed["profile"].as_str(), Some("mainnet-apollo"));
+        assert_eq!(parsed["pool"]["coinbase_address"].as_str(), Some("bc1qexample"));
+        assert_eq!(parsed["bitcoin"]["rpc_password"].as_str(), Some("p\"ss"));
+
+        let config = render_config("regtest-fast", "bcrt1qexample", None);
+        let parsed: toml::Value = toml::from_str(&config).unwrap();
+        assert!(parsed.get("bitcoin").is_none());
+    }
+
+    #[test]
+    fn test_apply_layers_config_over_profile() {
+        let config = render_config("regtest-fast", "bcrt1qexample", Some(("alice", "secret")));
+        let merged = apply(toml::from_str(&config).unwrap()).unwrap();
+        assert_eq!(merged["daemon"]["network"].as_str(), Some("regtest"));
+        assert_eq!(merged["bitcoin"]["rpc_url"].as_str(), Some("http://127.0.0.1:18443"));
+        assert_eq!(merged["bitcoin"]["rpc_user"].as_str(), Some("alice"));
+        assert_eq!(merged["pool"]["signature"].as_str(), Some("SV2"));
+
+        let unknown: toml::Value = toml::from_str("profile = \"testnet-s21\"\n").unwrap();
+        assert!(apply(unknown).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Simulated SV1 miners for exercising the translator without hardware.
+//! Each miner subscribes, authorizes and submits shares at a fixed rate,
+//! optionally submitting a share of deliberately stale work to be rejected.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+/// Job id used for shares that are meant to be rejected
+const STALE_JOB_ID: &str = "stale";
+
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Translator address miners connect to
+    pub address: String,
+    /// Number of concurrent miners
+    pub count: usize,
+    /// Shares per second submitted by each miner
+    pub rate: f64,
+    /// Difficulty requested via `mining.suggest_difficulty`
+    pub difficulty: f64,
+    /// Fraction of shares submitted against stale work, 0.0 to 1.0
+    pub reject_ratio: f64,
+    /// How long to run for
+    pub duration: Duration,
+    /// Worker names are `<prefix>.<n>`
+    pub worker_prefix: String,
+}
+
+impl SimulationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.count == 0 {
+            bail!("--count must be at least 1");
+        }
+        if !(self.rate > 0.0 && self.rate.is_finite()) {
+            bail!("--rate must be a positive number of shares per second");
+        }
+        if !(self.difficulty > 0.0 && self.difficulty.is_finite()) {
+            bail!("--difficulty must be positive");
+        }
+        if !(0.0..=1.0).contains(&self.reject_ratio) {
+            bail!("--reject-ratio must be between 0.0 and 1.0");
+        }
+        Ok(())
+    }
+}
+
+/// Share counters shared by all simulated miners
+#[derive(Debug, Default)]
+pub struct SimulationStats {
+    pub connected: AtomicU64,
+    pub submitted: AtomicU64,
+    pub accepted: AtomicU64,
+    pub rejected: AtomicU64,
+    pub errors: AtomicU64,
+}
+
+/// Result of a finished simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub miners: usize,
+    pub connected: u64,
+    pub submitted: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub errors: u64,
+    pub elapsed: Duration,
+}
+
+impl SimulationReport {
+    /// Achieved submit rate across all miners, in shares per second
+    pub fn submit_rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.submitted as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Whether the `n`th share (0-based) should be submitted against stale work.
+/// Spreads rejects evenly instead of relying on randomness.
+pub fn should_reject(n: u64, reject_ratio: f64) -> bool {
+    ((n + 1) as f64 * reject_ratio).floor() > (n as f64 * reject_ratio).floor()
+}
+
+/// Run `config.count` miners until `config.duration` elapses or `stop` fires
+pub async fn run(config: SimulationConfig, stop: watch::Receiver<bool>) -> Result<SimulationReport> {
+    config.validate()?;
+
+    let stats = Arc::new(SimulationStats::default());
+    let started = Instant::now();
+    let deadline = started + config.duration;
+
+    let mut miners = Vec::with_capacity(config.count);
+    for index in 0..config.count {
+        let config = config.clone();
+        let stats = Arc::clone(&stats);
+        let stop = stop.clone();
+        miners.push(tokio::spawn(async move {
+            let worker = format!("{}.{}", config.worker_prefix, index + 1);
+            if let Err(e) = run_miner(&config, &worker, &stats, deadline, stop).await {
+                stats.errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("⚠️  {}: {:#}", worker, e);
+            }
+        }));
+    }
+
+    for miner in miners {
+        let _ = miner.await;
+    }
+
+    Ok(SimulationReport {
+        miners: config.count,
+        connected: stats.connected.load(Ordering::Relaxed),
+        submitted: stats.submitted.load(Ordering::Relaxed),
+        accepted: stats.accepted.load(Ordering::Relaxed),
+        rejected: stats.rejected.load(Ordering::Relaxed),
+        errors: stats.errors.load(Ordering::Relaxed),
+        elapsed: started.elapsed(),
+    })
+}
+
+async fn run_miner(
+    config: &SimulationConfig,
+    worker: &str,
+    stats: &SimulationStats,
+    deadline: Instant,
+    mut stop: watch::Receiver<bool>,
+) -> Result<()> {
+    let stream = TcpStream::connect(&config.address)
+        .await
+        .with_context(|| format!("Failed to connect to {}", config.address))?;
+    stats.connected.fetch_add(1, Ordering::Relaxed);
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    send(&mut writer, json!({"id": 1, "method": "mining.subscribe", "params": ["sv2-cli-simulator/0.1"]})).await?;
+    send(&mut writer, json!({"id": 2, "method": "mining.authorize", "params": [worker, "x"]})).await?;
+    send(&mut writer, json!({"id": 3, "method": "mining.suggest_difficulty", "params": [config.difficulty]})).await?;
+
+    let interval = Duration::from_secs_f64(1.0 / config.rate);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    // Submits use ids from 100 upward so replies can be told apart
+    let mut job_id = String::from("0");
+    let mut share_index = 0u64;
+    let deadline = tokio::time::Instant::from_std(deadline);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => break,
+            _ = stop.changed() => break,
+            _ = ticker.tick() => {
+                let job = if should_reject(share_index, config.reject_ratio) {
+                    STALE_JOB_ID
+                } else {
+                    job_id.as_str()
+                };
+                let submit = json!({
+                    "id": 100 + share_index,
+                    "method": "mining.submit",
+                    "params": [worker, job, "00000000", format!("{:08x}", chrono::Utc::now().timestamp()), format!("{:08x}", share_index)],
+                });
+                send(&mut writer, submit).await?;
+                stats.submitted.fetch_add(1, Ordering::Relaxed);
+                share_index += 1;
+            }
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    bail!("connection closed by {}", config.address);
+                };
+                handle_message(&line, &mut job_id, stats);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Track the current job and count replies to our submits
+fn handle_message(line: &str, job_id: &mut String, stats: &SimulationStats) {
+    let Ok(message) = serde_json::from_str::<Value>(line) else {
+        return;
+    };
+
+    if message.get("method").and_then(Value::as_str) == Some("mining.notify") {
+        if let Some(id) = message.pointer("/params/0").and_then(Value::as_str) {
+            *job_id = id.to_string();
+        }
+        return;
+    }
+
+    let is_submit_reply = message.get("id").and_then(Value::as_u64).map_or(false, |id| id >= 100);
+    if !is_submit_reply {
+        return;
+    }
+
+    if message.get("result").and_then(Value::as_bool) == Some(true) {
+        stats.accepted.fetch_add(1, Ordering::Relaxed);
+    } else {
+        stats.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+async fn send(writer: &mut tokio::net::tcp::OwnedWriteHalf, message: Value) -> Result<()> {
+    writer.write_all(format!("{}\n", message).as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn config(address: String) -> SimulationConfig {
+        SimulationConfig {
+            address,
+            count: 2,
+            rate: 50.0,
+            difficulty: 1.0,
+            reject_ratio: 0.0,
+            duration: Duration::from_millis(300),
+            worker_prefix: "sim".to_string(),
+        }
+    }
+
+    /// Accepts every message except submits against stale work
+    async fn fake_translator() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let request: Value = serde_json::from_str(&line).unwrap();
+                        let stale = request.pointer("/params/1").and_then(Value::as_str) == Some(STALE_JOB_ID);
+                        let reply = json!({"id": request["id"], "result": !stale, "error": null});
+                        if writer.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn test_reject_schedule_matches_ratio() {
+        let rejects = (0..1000).filter(|&n| should_reject(n, 0.1)).count();
+        assert_eq!(rejects, 100);
+        assert!(!(0..1000).any(|n| should_reject(n, 0.0)));
+        assert!((0..1000).all(|n| should_reject(n, 1.0)));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let base = config("127.0.0.1:3333".to_string());
+        assert!(base.validate().is_ok());
+        assert!(SimulationConfig { count: 0, ..base.clone() }.validate().is_err());
+        assert!(SimulationConfig { rate: 0.0, ..base.clone() }.validate().is_err());
+        assert!(SimulationConfig { reject_ratio: 1.5, ..base }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_miners_submit_shares() {
+        let address = fake_translator().await;
+        let (_stop_tx, stop_rx) = watch::channel(false);
+
+        let report = run(SimulationConfig { reject_ratio: 0.5, ..config(address) }, stop_rx)
+            .await
+            .unwrap();
+
+        assert_eq!(report.connected, 2);
+        assert_eq!(report.errors, 0);
+        assert!(report.submitted >= 2);
+        assert!(report.rejected >= 1);
+        assert!(report.accepted + report.rejected <= report.submitted);
+        assert!(report.submit_rate() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_translator_counts_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let (_stop_tx, stop_rx) = watch::channel(false);
+        let report = run(config(address), stop_rx).await.unwrap();
+        assert_eq!(report.connected, 0);
+        assert_eq!(report.errors, 2);
+    }
+}
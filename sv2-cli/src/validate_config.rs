@@ -0,0 +1,194 @@
+//! `validate-config`: check `~/.sv2d/config.toml` without starting sv2d.
+//! Every key gets the type and range check `config set` applies, the file
+//! is merged with its profile the way sv2d loads it, and the result must set
+//! everything sv2d has no default for, pay the coinbase to an address on the
+//! configured network and point at a Bitcoin RPC that answers.
+
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+
+use crate::{config_edit, profiles};
+use sv2_core::address;
+
+/// Keys sv2d fails to load without, once the profile is applied
+const REQUIRED: &[&str] = &[
+    "daemon.mode",
+    "daemon.network",
+    "bitcoin.rpc_url",
+    "bitcoin.rpc_user",
+    "bitcoin.rpc_password",
+    "pool.signature",
+    "pool.coinbase_address",
+    "translator.bind_address",
+    "translator.min_extranonce2_size",
+];
+
+/// Outcome of one check, named after the setting it's about
+#[derive(Debug)]
+pub struct FieldCheck {
+    pub field: String,
+    pub result: Result<()>,
+}
+
+impl FieldCheck {
+    fn new(field: &str, result: Result<()>) -> Self {
+        Self { field: field.to_string(), result }
+    }
+}
+
+/// Every check, ending with whether the Bitcoin RPC answers
+pub async fn validate(contents: &str) -> Vec<FieldCheck> {
+    let (mut checks, merged) = check_offline(contents);
+    let Some(config) = &merged else {
+        return checks;
+    };
+    if let Some(rpc_url) = string(config, "bitcoin.rpc_url") {
+        let user = string(config, "bitcoin.rpc_user").unwrap_or_default();
+        let password = string(config, "bitcoin.rpc_password").unwrap_or_default();
+        checks.push(FieldCheck::new("bitcoin.rpc_url (reachable)", test_bitcoin_rpc(rpc_url, user, password).await));
+    }
+    checks
+}
+
+/// The checks that don't need the network, and the config as sv2d would
+/// see it when the file and its profile could be read
+fn check_offline(contents: &str) -> (Vec<FieldCheck>, Option<toml::Value>) {
+    let keys = match config_edit::check_keys(contents) {
+        Ok(keys) => keys,
+        Err(e) => return (vec![FieldCheck::new("config.toml", Err(e))], None),
+    };
+    let mut checks: Vec<FieldCheck> = keys.into_iter().map(|(field, result)| FieldCheck { field, result }).collect();
+
+    let merged = match toml::from_str::<toml::Value>(contents).map_err(anyhow::Error::from).and_then(profiles::apply) {
+        Ok(merged) => merged,
+        Err(e) => {
+            checks.push(FieldCheck::new("profile", Err(e)));
+            return (checks, None);
+        }
+    };
+
+    for key in REQUIRED {
+        if lookup(&merged, key).is_none() {
+            let error = anyhow!("{} is not set, here or by the profile", key);
+            checks.push(FieldCheck::new(key, Err(error)));
+        }
+    }
+
+    if let (Some(coinbase_address), Some(network)) =
+        (string(&merged, "pool.coinbase_address"), string(&merged, "daemon.network"))
+    {
+        checks.push(FieldCheck::new(
+            "pool.coinbase_address (network)",
+            address::check_coinbase_address(coinbase_address, network).map_err(Into::into),
+        ));
+    }
+
+    (checks, Some(merged))
+}
+
+fn lookup<'a>(config: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(config, |value, part| value.get(part))
+}
+
+fn string<'a>(config: &'a toml::Value, key: &str) -> Option<&'a str> {
+    lookup(config, key).and_then(toml::Value::as_str)
+}
+
+/// Ask the node for `getblockchaininfo`, as sv2d does before starting the pool
+async fn test_bitcoin_rpc(rpc_url: &str, user: &str, password: &str) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(rpc_url)
+        .basic_auth(user, Some(password))
+        .json(&serde_json::json!({
+            "method": "getblockchaininfo",
+            "params": [],
+            "id": 1
+        }))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .map_err(|e| anyhow!("Bitcoin RPC at {} is not reachable: {}", rpc_url, e))?;
+
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        reqwest::StatusCode::UNAUTHORIZED => {
+            Err(anyhow!("Bitcoin RPC at {} rejected bitcoin.rpc_user/rpc_password", rpc_url))
+        }
+        status => Err(anyhow!("Bitcoin RPC at {} answered {}", rpc_url, status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGTEST: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+    const MAINNET: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+    fn failures(checks: &[FieldCheck]) -> Vec<&str> {
+        checks.iter().filter(|c| c.result.is_err()).map(|c| c.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_profile_config_passes_offline_checks() {
+        let config = profiles::render_config("regtest-fast", REGTEST, None);
+        let (checks, merged) = check_offline(&config);
+        assert!(failures(&checks).is_empty(), "{:?}", checks);
+        assert!(checks.iter().any(|c| c.field == "pool.coinbase_address (network)"));
+        assert_eq!(string(&merged.unwrap(), "bitcoin.rpc_url"), Some("http://127.0.0.1:18443"));
+    }
+
+    #[test]
+    fn test_sv2d_sections_pass_offline_checks() {
+        let config = format!(
+            "{}\n[ports]\npool_listen = 34254\npool_plain = 34255\ntranslator_downstream = 3333\ntp_port = 18447\njson_rpc = 8332\n\
+             \n[shutdown]\ntimeout_secs = 45\n\n[miner_stats]\nenabled = true\npoll_interval_secs = 60\n",
+            profiles::render_config("regtest-fast", REGTEST, None)
+        );
+        let (checks, _) = check_offline(&config);
+        assert!(failures(&checks).is_empty(), "{:?}", checks);
+        assert!(checks.iter().any(|c| c.field == "ports.json_rpc"));
+        assert!(checks.iter().any(|c| c.field == "shutdown.timeout_secs"));
+        assert!(checks.iter().any(|c| c.field == "miner_stats.poll_interval_secs"));
+    }
+
+    #[test]
+    fn test_each_problem_is_reported() {
+        let config = format!(
+            "[daemon]\nmode = \"proxy\"\nnetwork = \"regtest\"\n\n[pool]\ncoinbase_address = \"{}\"\n\n\
+             [translator]\nbind_address = \"0.0.0.0:3333\"\nmin_extranonce2_size = -1\n",
+            MAINNET
+        );
+        let (checks, _) = check_offline(&config);
+        assert_eq!(failures(&checks), [
+            "translator.min_extranonce2_size",
+            "bitcoin.rpc_url",
+            "bitcoin.rpc_user",
+            "bitcoin.rpc_password",
+            "pool.signature",
+            "pool.coinbase_address (network)",
+        ]);
+        let mismatch = checks.iter().find(|c| c.field == "pool.coinbase_address (network)").unwrap();
+        assert!(mismatch.result.as_ref().unwrap_err().to_string().contains("is a mainnet address, not a regtest one"));
+    }
+
+    #[test]
+    fn test_unreadable_config_stops_early() {
+        let (checks, merged) = check_offline("[daemon\n");
+        assert_eq!(failures(&checks), ["config.toml"]);
+        assert!(merged.is_none());
+
+        let (checks, merged) = check_offline("profile = \"testnet-s21\"\n");
+        assert_eq!(failures(&checks), ["profile", "profile"]);
+        assert!(merged.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_rpc_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let error = test_bitcoin_rpc(&url, "test", "test").await.unwrap_err().to_string();
+        assert!(error.contains("not reachable"), "{}", error);
+    }
+}
@@ -72,4 +72,78 @@ mod cli_parsing_tests {
         // This is a compile-time test to ensure our CLI structures are valid
         assert!(true);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_json_errors_flag() {
+    use sv2_cli::json_errors::{ErrorOutput, EXIT_FAILURE, EXIT_USAGE};
+
+    // No config file under this HOME, so `config get` fails
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sv2-cli"))
+            .args(args)
+            .env("HOME", home.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["config", "get", "translator.min_extranonce2_size", "--json-errors"]);
+    assert_eq!(output.status.code(), Some(EXIT_FAILURE));
+    let error: ErrorOutput = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error.error.code, "error");
+    assert!(error.error.message.contains("Config file not found"), "{}", error.error.message);
+
+    let output = run(&["--json-errors", "config", "get"]);
+    assert_eq!(output.status.code(), Some(EXIT_USAGE));
+    let error: ErrorOutput = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error.error.code, "usage");
+
+    // Human-readable on stderr without the flag
+    let output = run(&["config", "get", "translator.min_extranonce2_size"]);
+    assert_eq!(output.status.code(), Some(EXIT_FAILURE));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Config file not found"));
+}
+
+#[test]
+fn test_config_key_value_shorthand() {
+    use sv2_cli::json_errors::{EXIT_FAILURE, EXIT_USAGE};
+
+    let home = tempfile::tempdir().unwrap();
+    let config_path = home.path().join(".sv2d").join("config.toml");
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &config_path,
+        "[daemon]\nmode = \"proxy\"\nnetwork = \"signet\"\n\n[translator]\nbind_address = \"0.0.0.0:3333\"\nmin_extranonce2_size = 8\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_sv2-cli"))
+            .args(args)
+            .env("HOME", home.path())
+            .output()
+            .unwrap()
+    };
+
+    // A key alone prints it
+    let output = run(&["config", "translator.min_extranonce2_size"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "8");
+
+    // A key and a value set it, as 'config set' does
+    let output = run(&["config", "translator.min_extranonce2_size", "16"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = run(&["config", "get", "translator.min_extranonce2_size"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "16");
+
+    // Checked like 'config set', leaving the file alone on failure
+    let output = run(&["config", "translator.typo", "1"]);
+    assert_eq!(output.status.code(), Some(EXIT_FAILURE));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown config key 'translator.typo'"));
+    let output = run(&["config", "translator.min_extranonce2_size", "sixteen"]);
+    assert_eq!(output.status.code(), Some(EXIT_FAILURE));
+    assert!(std::fs::read_to_string(&config_path).unwrap().contains("min_extranonce2_size = 16"));
+
+    let output = run(&["config"]);
+    assert_eq!(output.status.code(), Some(EXIT_USAGE));
+}
@@ -16,6 +16,7 @@ async fn test_api_client_with_custom_config() -> Result<()> {
         base_url: Url::parse("http://example.com:9000")?,
         timeout: Duration::from_secs(10),
         api_key: Some("test-key".to_string()),
+        ..Default::default()
     };
     
     let client = ApiClient::with_config(config);
@@ -11,6 +11,7 @@ fn test_monitor_config_creation() {
         show_performance: true,
         show_alerts: false,
         max_entries: 20,
+        difficulty_unit: None,
     };
     
     assert_eq!(config.refresh_interval, Duration::from_secs(10));
@@ -154,6 +155,7 @@ async fn test_monitor_config_validation() -> Result<()> {
         show_performance: true,
         show_alerts: true,
         max_entries: 5,
+        difficulty_unit: None,
     };
     
     // Validate refresh interval is reasonable
@@ -0,0 +1,10 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sv2_core::share_target::{cached_target, difficulty_to_target};
+
+fn target_conversion(c: &mut Criterion) {
+    c.bench_function("difficulty_to_target", |b| b.iter(|| difficulty_to_target(black_box(4096.0))));
+    c.bench_function("cached_target", |b| b.iter(|| cached_target(black_box(4096.0))));
+}
+
+criterion_group!(benches, target_conversion);
+criterion_main!(benches);
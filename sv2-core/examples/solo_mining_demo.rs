@@ -23,6 +23,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         block_template_refresh_interval: 30,
         enable_custom_templates: false,
         max_template_age: 300,
+        template_source: Default::default(),
+        coinbase_tags: Default::default(),
+        block_confirmations: 6,
     };
 
     let bitcoin_config = BitcoinConfig {
@@ -0,0 +1,16 @@
+-- Persistent worker identities for reconnect attribution
+
+CREATE TABLE IF NOT EXISTS worker_records (
+    id UUID PRIMARY KEY,
+    worker_name TEXT NOT NULL,
+    source_ip TEXT NOT NULL,
+    first_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sessions BIGINT NOT NULL DEFAULT 1,
+    total_shares BIGINT NOT NULL DEFAULT 0,
+    valid_shares BIGINT NOT NULL DEFAULT 0,
+    blocks_found BIGINT NOT NULL DEFAULT 0,
+    UNIQUE (worker_name, source_ip)
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_records_last_seen ON worker_records(last_seen);
@@ -0,0 +1,3 @@
+-- Feature flags negotiated during the connection handshake
+
+ALTER TABLE connections ADD COLUMN IF NOT EXISTS flags BIGINT;
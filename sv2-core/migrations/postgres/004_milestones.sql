@@ -0,0 +1,7 @@
+-- One-time milestones such as the first accepted share
+
+CREATE TABLE IF NOT EXISTS milestones (
+    name TEXT PRIMARY KEY,
+    reached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    detail TEXT
+);
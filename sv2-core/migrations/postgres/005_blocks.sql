@@ -0,0 +1,14 @@
+-- Blocks found by this daemon and whether they confirmed or were orphaned
+
+CREATE TABLE IF NOT EXISTS blocks (
+    hash TEXT PRIMARY KEY,
+    height BIGINT,
+    connection_id UUID,
+    status TEXT NOT NULL,
+    confirmations BIGINT NOT NULL DEFAULT 0,
+    alert_id UUID,
+    found_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocks_found_at ON blocks(found_at);
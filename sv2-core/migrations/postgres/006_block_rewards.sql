@@ -0,0 +1,5 @@
+-- Who found each block and what it paid
+
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS worker TEXT;
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS reward BIGINT;
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS fees BIGINT;
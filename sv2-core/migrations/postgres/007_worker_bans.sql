@@ -0,0 +1,7 @@
+-- Worker names whose shares are rejected
+
+CREATE TABLE IF NOT EXISTS worker_bans (
+    worker_name TEXT PRIMARY KEY,
+    reason TEXT,
+    banned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
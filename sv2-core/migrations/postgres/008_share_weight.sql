@@ -0,0 +1,4 @@
+-- How many accepted shares each stored row stands for when accepted shares
+-- are sampled
+
+ALTER TABLE shares ADD COLUMN IF NOT EXISTS weight BIGINT NOT NULL DEFAULT 1;
@@ -0,0 +1,16 @@
+-- Persistent worker identities for reconnect attribution
+
+CREATE TABLE IF NOT EXISTS worker_records (
+    id TEXT PRIMARY KEY,
+    worker_name TEXT NOT NULL,
+    source_ip TEXT NOT NULL,
+    first_seen DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sessions INTEGER NOT NULL DEFAULT 1,
+    total_shares INTEGER NOT NULL DEFAULT 0,
+    valid_shares INTEGER NOT NULL DEFAULT 0,
+    blocks_found INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (worker_name, source_ip)
+);
+
+CREATE INDEX IF NOT EXISTS idx_worker_records_last_seen ON worker_records(last_seen);
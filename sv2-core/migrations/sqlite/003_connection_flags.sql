@@ -0,0 +1,3 @@
+-- Feature flags negotiated during the connection handshake
+
+ALTER TABLE connections ADD COLUMN flags INTEGER;
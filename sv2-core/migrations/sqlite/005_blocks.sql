@@ -0,0 +1,14 @@
+-- Blocks found by this daemon and whether they confirmed or were orphaned
+
+CREATE TABLE IF NOT EXISTS blocks (
+    hash TEXT PRIMARY KEY,
+    height INTEGER,
+    connection_id TEXT,
+    status TEXT NOT NULL,
+    confirmations INTEGER NOT NULL DEFAULT 0,
+    alert_id TEXT,
+    found_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocks_found_at ON blocks(found_at);
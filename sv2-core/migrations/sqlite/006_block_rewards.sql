@@ -0,0 +1,5 @@
+-- Who found each block and what it paid
+
+ALTER TABLE blocks ADD COLUMN worker TEXT;
+ALTER TABLE blocks ADD COLUMN reward INTEGER;
+ALTER TABLE blocks ADD COLUMN fees INTEGER;
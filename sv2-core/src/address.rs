@@ -0,0 +1,145 @@
+//! Coinbase address checks shared by `sv2-cli setup`, `validate-config` and
+//! sv2d's startup. Addresses are parsed rather than matched on prefix, so a
+//! mistyped character is caught by the checksum and an address for another
+//! network is named as such.
+
+use crate::{Error, Result};
+use bitcoin::address::{self, NetworkUnchecked};
+use bitcoin::{base58, bech32, Address, Network};
+
+/// Networks `setup` offers, as written to `[daemon] network`
+pub const NETWORKS: &[&str] = &["regtest", "signet", "testnet4", "mainnet"];
+
+/// The network `[daemon] network` names
+pub fn bitcoin_network(network: &str) -> Result<Network> {
+    match network {
+        "mainnet" | "main" => Ok(Network::Bitcoin),
+        // testnet4 encodes addresses exactly as testnet3 does
+        "testnet" | "testnet4" => Ok(Network::Testnet),
+        "signet" => Ok(Network::Signet),
+        "regtest" => Ok(Network::Regtest),
+        other => Err(Error::Config(format!(
+            "[daemon] network '{}' is not one of {}", other, NETWORKS.join(", ")
+        ))),
+    }
+}
+
+/// Which network an address was encoded for, for the error message
+fn describe_address_network(address: &Address<NetworkUnchecked>) -> &'static str {
+    if address.is_valid_for_network(Network::Bitcoin) {
+        "mainnet"
+    } else if address.is_valid_for_network(Network::Regtest) {
+        "regtest"
+    } else {
+        // Testnet, testnet4 and signet share address encodings
+        "testnet/signet"
+    }
+}
+
+/// Parse `address` and check it can receive coins on `network`
+pub fn check_coinbase_address(address: &str, network: &str) -> Result<()> {
+    parse_for_network(address, bitcoin_network(network)?, network).map(|_| ())
+}
+
+/// `address` parsed and checked as [`check_coinbase_address`] does, for
+/// building the coinbase output that pays it
+pub fn coinbase_address(address: &str, network: Network) -> Result<Address> {
+    let name = match network {
+        Network::Bitcoin => "mainnet".to_string(),
+        other => other.to_string(),
+    };
+    parse_for_network(address, network, &name)
+}
+
+fn parse_for_network(address: &str, expected: Network, network: &str) -> Result<Address> {
+    let address = address.trim();
+
+    let parsed: Address<NetworkUnchecked> = address.parse().map_err(|e| match e {
+        address::Error::Base58(base58::Error::BadChecksum(..)) | address::Error::Bech32(bech32::Error::InvalidChecksum) => {
+            Error::Config(format!("'{}' has a bad checksum; a character was probably mistyped or left out", address))
+        }
+        address::Error::InvalidBech32Variant { expected, found } => Error::Config(format!(
+            "'{}' is encoded as {:?} but its witness version requires {:?} (BIP 350)",
+            address, found, expected
+        )),
+        e => Error::Config(format!("'{}' is not a valid Bitcoin address: {}", address, e)),
+    })?;
+
+    if !parsed.is_valid_for_network(expected) {
+        return Err(Error::Config(format!(
+            "'{}' is a {} address, not a {} one; block rewards would be unspendable",
+            address,
+            describe_address_network(&parsed),
+            network
+        )));
+    }
+    Ok(parsed.assume_checked())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const MAINNET_TAPROOT: &str = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+    const MAINNET_LEGACY: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+    const TESTNET: &str = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+    const TESTNET_LEGACY: &str = "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn";
+    const REGTEST: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+    #[test]
+    fn test_addresses_for_their_network() {
+        for address in [MAINNET, MAINNET_TAPROOT, MAINNET_LEGACY] {
+            assert!(check_coinbase_address(address, "mainnet").is_ok(), "{}", address);
+        }
+        for network in ["testnet4", "signet"] {
+            assert!(check_coinbase_address(TESTNET, network).is_ok());
+            assert!(check_coinbase_address(TESTNET_LEGACY, network).is_ok());
+        }
+        assert!(check_coinbase_address(REGTEST, "regtest").is_ok());
+        assert!(check_coinbase_address(&format!(" {}\n", REGTEST), "regtest").is_ok());
+    }
+
+    #[test]
+    fn test_wrong_network_is_named() {
+        let error = check_coinbase_address(TESTNET, "mainnet").unwrap_err().to_string();
+        assert!(error.contains("is a testnet/signet address, not a mainnet one"), "{}", error);
+        let error = check_coinbase_address(MAINNET_LEGACY, "testnet4").unwrap_err().to_string();
+        assert!(error.contains("is a mainnet address"), "{}", error);
+        let error = check_coinbase_address(REGTEST, "signet").unwrap_err().to_string();
+        assert!(error.contains("is a regtest address"), "{}", error);
+        assert!(check_coinbase_address(REGTEST, "moonnet").is_err());
+    }
+
+    #[test]
+    fn test_coinbase_address_for_network() {
+        let address = coinbase_address(REGTEST, Network::Regtest).unwrap();
+        assert_eq!(address.to_string(), REGTEST);
+        let error = coinbase_address(REGTEST, Network::Bitcoin).unwrap_err().to_string();
+        assert!(error.contains("is a regtest address, not a mainnet one"), "{}", error);
+    }
+
+    #[test]
+    fn test_network_names() {
+        assert_eq!(bitcoin_network("main").unwrap(), Network::Bitcoin);
+        assert_eq!(bitcoin_network("testnet").unwrap(), Network::Testnet);
+        for network in NETWORKS {
+            assert!(bitcoin_network(network).is_ok(), "{}", network);
+        }
+    }
+
+    #[test]
+    fn test_bad_checksum_is_told_apart_from_bad_address() {
+        let error = check_coinbase_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5", "mainnet").unwrap_err();
+        assert!(error.to_string().contains("bad checksum"), "{}", error);
+        let error = check_coinbase_address("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3", "mainnet").unwrap_err();
+        assert!(error.to_string().contains("bad checksum"), "{}", error);
+
+        // A segwit v0 program with a bech32m checksum
+        let error = check_coinbase_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh", "mainnet").unwrap_err();
+        assert!(error.to_string().contains("encoded as Bech32m"), "{}", error);
+
+        let error = check_coinbase_address("not-an-address", "mainnet").unwrap_err();
+        assert!(error.to_string().contains("not a valid Bitcoin address"), "{}", error);
+    }
+}
@@ -2,7 +2,11 @@
 use crate::{
     error::{Error, Result},
     types::{DaemonStatus, ConnectionInfo, MiningStats, WorkTemplate},
+    blocks::BlockRecord,
     database::DatabaseOps,
+    metrics_history::{MetricsHistory, MetricsHistoryConfig, MetricsHistorySnapshot, MetricsSample},
+    connection_quality::{self, ConnectionQualityConfig},
+    worker_bans::{WorkerBan, WorkerBans},
 };
 use axum::{
     extract::{Path, Query, State},
@@ -12,14 +16,14 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::CorsLayer,
     trace::TraceLayer,
 };
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use uuid::Uuid;
 
 /// API response wrapper
@@ -54,6 +58,9 @@ pub struct ApiState {
     pub database: Arc<dyn DatabaseOps>,
     pub daemon_status: Arc<RwLock<DaemonStatus>>,
     pub mining_stats: Arc<RwLock<MiningStats>>,
+    pub metrics_history: Arc<RwLock<MetricsHistory>>,
+    pub connection_quality: ConnectionQualityConfig,
+    pub worker_bans: Arc<WorkerBans>,
 }
 
 /// Query parameters for pagination
@@ -67,6 +74,7 @@ pub struct PaginationQuery {
 pub struct ApiServer {
     bind_address: SocketAddr,
     state: ApiState,
+    history_config: MetricsHistoryConfig,
 }
 
 impl ApiServer {
@@ -76,21 +84,53 @@ impl ApiServer {
         daemon_status: Arc<RwLock<DaemonStatus>>,
         mining_stats: Arc<RwLock<MiningStats>>,
     ) -> Self {
+        let history_config = MetricsHistoryConfig::default();
         let state = ApiState {
             database,
             daemon_status,
             mining_stats,
+            metrics_history: Arc::new(RwLock::new(MetricsHistory::new(&history_config))),
+            connection_quality: ConnectionQualityConfig::default(),
+            worker_bans: Arc::new(WorkerBans::default()),
         };
 
         Self {
             bind_address,
             state,
+            history_config,
         }
     }
 
+    /// Size and sampling rate of the metrics history
+    pub fn with_metrics_history(mut self, config: MetricsHistoryConfig) -> Self {
+        self.state.metrics_history = Arc::new(RwLock::new(MetricsHistory::new(&config)));
+        self.history_config = config;
+        self
+    }
+
+    /// Weights and window for the quality score on connection endpoints
+    pub fn with_connection_quality(mut self, config: ConnectionQualityConfig) -> Self {
+        self.state.connection_quality = config;
+        self
+    }
+
+    /// Worker bans to list and change, shared with the stratum server
+    pub fn with_worker_bans(mut self, worker_bans: Arc<WorkerBans>) -> Self {
+        self.state.worker_bans = worker_bans;
+        self
+    }
+
     /// Start the API server
     pub async fn start(self) -> Result<()> {
         let app = self.create_router();
+
+        if self.history_config.enabled {
+            tokio::spawn(record_metrics_history(
+                self.state.mining_stats.clone(),
+                self.state.metrics_history.clone(),
+                Duration::from_secs(self.history_config.resolution_secs),
+            ));
+        }
         
         info!("Starting API server on {}", self.bind_address);
         
@@ -115,6 +155,12 @@ impl ApiServer {
             // Mining endpoints
             .route("/api/v1/mining/stats", get(get_mining_stats))
             .route("/api/v1/mining/templates", get(get_templates))
+            .route("/api/v1/blocks", get(get_blocks))
+            // Worker endpoints
+            .route("/api/v1/workers/bans", get(get_worker_bans))
+            .route("/api/v1/workers/:name/ban", post(ban_worker).delete(unban_worker))
+            // Metrics endpoints
+            .route("/api/v1/metrics/history", get(get_metrics_history))
             // Control endpoints
             .route("/api/v1/control/shutdown", post(shutdown_daemon))
             .layer(
@@ -146,7 +192,10 @@ async fn get_connections(
     let offset = params.offset.unwrap_or(0);
 
     match state.database.get_connections(Some(limit), Some(offset)).await {
-        Ok(connections) => Ok(Json(ApiResponse::success(connections))),
+        Ok(mut connections) => {
+            add_connection_quality(&state, &mut connections).await;
+            Ok(Json(ApiResponse::success(connections)))
+        }
         Err(e) => {
             error!("Failed to get connections: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -163,7 +212,10 @@ async fn get_connection(
         .map_err(|_| StatusCode::BAD_REQUEST)?;
 
     match state.database.get_connection_info(connection_id).await {
-        Ok(Some(connection)) => Ok(Json(ApiResponse::success(connection))),
+        Ok(Some(mut connection)) => {
+            add_connection_quality(&state, std::slice::from_mut(&mut connection)).await;
+            Ok(Json(ApiResponse::success(connection)))
+        }
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             error!("Failed to get connection {}: {}", id, e);
@@ -172,12 +224,43 @@ async fn get_connection(
     }
 }
 
+/// Score connections; a failure leaves `quality` unset rather than failing the request
+async fn add_connection_quality(state: &ApiState, connections: &mut [ConnectionInfo]) {
+    if let Err(e) = connection_quality::annotate(state.database.as_ref(), &state.connection_quality, connections).await {
+        warn!("Failed to score connection quality: {}", e);
+    }
+}
+
 /// Get mining statistics
 async fn get_mining_stats(State(state): State<ApiState>) -> Json<ApiResponse<MiningStats>> {
     let stats = state.mining_stats.read().await.clone();
     Json(ApiResponse::success(stats))
 }
 
+/// Get recent metrics samples, oldest first
+async fn get_metrics_history(
+    State(state): State<ApiState>,
+    Query(params): Query<PaginationQuery>,
+) -> Json<ApiResponse<MetricsHistorySnapshot>> {
+    let history = state.metrics_history.read().await;
+    Json(ApiResponse::success(history.snapshot(params.limit.map(|limit| limit as usize))))
+}
+
+/// Sample mining stats into the history every `resolution`
+async fn record_metrics_history(
+    mining_stats: Arc<RwLock<MiningStats>>,
+    history: Arc<RwLock<MetricsHistory>>,
+    resolution: Duration,
+) {
+    let mut interval = tokio::time::interval(resolution);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let sample = MetricsSample::new(chrono::Utc::now(), &*mining_stats.read().await, None);
+        history.write().await.push(sample);
+    }
+}
+
 /// Get work templates
 async fn get_templates(
     State(state): State<ApiState>,
@@ -194,6 +277,69 @@ async fn get_templates(
     }
 }
 
+/// Get found blocks and their confirmation status, newest first
+async fn get_blocks(
+    State(state): State<ApiState>,
+    Query(params): Query<PaginationQuery>,
+) -> std::result::Result<Json<ApiResponse<Vec<BlockRecord>>>, StatusCode> {
+    match state.database.get_blocks(params.limit).await {
+        Ok(blocks) => Ok(Json(ApiResponse::success(blocks))),
+        Err(e) => {
+            error!("Failed to get blocks: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Body of a worker ban request
+#[derive(Debug, Default, Deserialize)]
+pub struct BanWorkerRequest {
+    pub reason: Option<String>,
+}
+
+/// Get banned workers
+async fn get_worker_bans(State(state): State<ApiState>) -> Json<ApiResponse<Vec<WorkerBan>>> {
+    Json(ApiResponse::success(state.worker_bans.list()))
+}
+
+/// Reject a worker's shares; other workers on its connection are unaffected
+async fn ban_worker(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    request: Option<Json<BanWorkerRequest>>,
+) -> std::result::Result<Json<ApiResponse<WorkerBan>>, StatusCode> {
+    let reason = request.and_then(|Json(request)| request.reason);
+    match state.worker_bans.ban(state.database.as_ref(), &name, reason).await {
+        Ok(ban) => {
+            info!("Banned worker {}", name);
+            Ok(Json(ApiResponse::success(ban)))
+        }
+        Err(Error::Config(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            error!("Failed to ban worker {}: {}", name, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Accept a banned worker's shares again
+async fn unban_worker(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> std::result::Result<Json<ApiResponse<&'static str>>, StatusCode> {
+    match state.worker_bans.unban(state.database.as_ref(), &name).await {
+        Ok(true) => {
+            info!("Unbanned worker {}", name);
+            Ok(Json(ApiResponse::success("Worker unbanned")))
+        }
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to unban worker {}: {}", name, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// Shutdown daemon
 async fn shutdown_daemon(State(_state): State<ApiState>) -> Json<ApiResponse<&'static str>> {
     // In a real implementation, this would trigger a graceful shutdown
@@ -217,6 +363,9 @@ mod tests {
             database,
             daemon_status,
             mining_stats,
+            metrics_history: Arc::new(RwLock::new(MetricsHistory::new(&MetricsHistoryConfig::default()))),
+            connection_quality: ConnectionQualityConfig::default(),
+            worker_bans: Arc::new(WorkerBans::default()),
         }
     }
 
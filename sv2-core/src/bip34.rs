@@ -0,0 +1,99 @@
+//! BIP 34 block height in the coinbase
+//!
+//! Since BIP 34 a block is only valid if its coinbase scriptSig starts with
+//! the block height, pushed the way Bitcoin Core's `CScript() << height`
+//! encodes it: `OP_0`/`OP_1`..`OP_16` for small heights, otherwise a minimal
+//! little-endian number push. Every coinbase sv2d builds puts this push first
+//! and is checked here before the template is handed out.
+
+use crate::{Error, Result};
+use bitcoin::blockdata::script::Builder;
+use bitcoin::{ScriptBuf, Transaction};
+
+/// The scriptSig prefix BIP 34 requires for `height`
+pub fn height_push(height: u64) -> ScriptBuf {
+    Builder::new().push_int(height as i64).into_script()
+}
+
+/// Height a coinbase commits to, if its scriptSig starts with a valid BIP 34 push
+pub fn coinbase_height(coinbase: &Transaction) -> Option<u64> {
+    let script_sig = coinbase.input.first()?.script_sig.as_bytes();
+    let height = match *script_sig.first()? {
+        0x00 => 0,
+        op @ 0x51..=0x60 => u64::from(op - 0x50),
+        len @ 0x01..=0x08 => {
+            let bytes = script_sig.get(1..=len as usize)?;
+            // Script numbers are little-endian with a sign bit; heights are positive
+            if bytes[bytes.len() - 1] & 0x80 != 0 {
+                return None;
+            }
+            bytes.iter().rev().fold(0u64, |value, byte| value << 8 | u64::from(*byte))
+        }
+        _ => return None,
+    };
+    // Reject non-minimal encodings, which consensus doesn't accept either
+    script_sig.starts_with(height_push(height).as_bytes()).then_some(height)
+}
+
+/// Fail unless `coinbase` is a valid coinbase for a block at `height`
+pub fn check_coinbase(coinbase: &Transaction, height: u64) -> Result<()> {
+    let expected = height_push(height);
+    let valid = coinbase
+        .input
+        .first()
+        .is_some_and(|input| input.script_sig.as_bytes().starts_with(expected.as_bytes()));
+    if !valid {
+        return Err(Error::Template(format!(
+            "coinbase scriptSig must start with the BIP 34 height push {} for block {}",
+            hex::encode(expected.as_bytes()),
+            height
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{TxIn, TxOut};
+
+    fn coinbase(script_sig: ScriptBuf) -> Transaction {
+        Transaction {
+            version: 1,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn { script_sig, ..TxIn::default() }],
+            output: vec![TxOut::default()],
+        }
+    }
+
+    #[test]
+    fn test_height_push_encoding() {
+        assert_eq!(height_push(0).as_bytes(), [0x00]);
+        assert_eq!(height_push(16).as_bytes(), [0x60]);
+        assert_eq!(height_push(17).as_bytes(), [0x01, 0x11]);
+        // 0x80 needs a padding byte to stay positive
+        assert_eq!(height_push(128).as_bytes(), [0x02, 0x80, 0x00]);
+        assert_eq!(height_push(840_000).as_bytes(), [0x03, 0x40, 0xd1, 0x0c]);
+    }
+
+    #[test]
+    fn test_coinbase_height_round_trips() {
+        for height in [0, 1, 16, 17, 127, 128, 255, 256, 840_000, 8_388_608] {
+            let mut script_sig = height_push(height).into_bytes();
+            script_sig.extend_from_slice(&[0x08, 0, 0, 0, 0, 0, 0, 0, 0]);
+            let tx = coinbase(ScriptBuf::from_bytes(script_sig));
+            assert_eq!(coinbase_height(&tx), Some(height));
+            check_coinbase(&tx, height).unwrap();
+        }
+
+        let empty = coinbase(ScriptBuf::new());
+        assert_eq!(coinbase_height(&empty), None);
+        assert!(check_coinbase(&empty, 840_000).is_err());
+
+        // Non-minimal push of 840000
+        let padded = coinbase(ScriptBuf::from_bytes(vec![0x04, 0x40, 0xd1, 0x0c, 0x00]));
+        assert_eq!(coinbase_height(&padded), None);
+        assert!(check_coinbase(&padded, 840_000).is_err());
+        assert!(check_coinbase(&coinbase(height_push(839_999)), 840_000).is_err());
+    }
+}
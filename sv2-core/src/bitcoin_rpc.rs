@@ -1,11 +1,12 @@
 use crate::{Result, Error};
-use crate::types::{WorkTemplate, BlockTemplate};
-use crate::config::BitcoinConfig;
-use bitcoin::{BlockHash, Transaction, Address, Network, ScriptBuf};
-use bitcoin::address::NetworkUnchecked;
+use crate::types::{WorkTemplate, BlockTemplate, NtimeBounds};
+use crate::config::{BitcoinConfig, BitcoinNetwork};
+use crate::coinbase_tag::CoinbaseTagRotator;
+use bitcoin::{BlockHash, Transaction, Network, ScriptBuf};
 use bitcoin::hashes::Hash;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 use uuid::Uuid;
@@ -15,6 +16,8 @@ use uuid::Uuid;
 pub struct BitcoinRpcClient {
     config: BitcoinConfig,
     client: reqwest::Client,
+    /// Signs the coinbase scriptSig of generated work
+    coinbase_tags: Arc<CoinbaseTagRotator>,
 }
 
 /// Bitcoin RPC request structure
@@ -132,6 +135,16 @@ pub struct BlockchainInfoResponse {
     pub warnings: Vec<String>,
 }
 
+/// Block header response (verbose `getblockheader`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockHeaderResponse {
+    pub hash: String,
+    /// -1 when the block is not in the active chain
+    pub confirmations: i64,
+    pub height: u64,
+    pub previousblockhash: Option<String>,
+}
+
 /// Submit block response
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
@@ -140,6 +153,22 @@ pub enum SubmitBlockResponse {
     Error(String),
 }
 
+/// `bitcoin` crate network for a configured network
+pub fn bitcoin_network(network: &BitcoinNetwork) -> Network {
+    match network {
+        BitcoinNetwork::Mainnet => Network::Bitcoin,
+        BitcoinNetwork::Testnet => Network::Testnet,
+        BitcoinNetwork::Signet => Network::Signet,
+        BitcoinNetwork::Regtest => Network::Regtest,
+    }
+}
+
+/// Output script paying `coinbase_address`, which must be an address on
+/// `network`; a reward paid to another network's address is unspendable
+pub fn payout_script(coinbase_address: &str, network: Network) -> Result<ScriptBuf> {
+    Ok(crate::address::coinbase_address(coinbase_address, network)?.script_pubkey())
+}
+
 impl BitcoinRpcClient {
     /// Create a new Bitcoin RPC client
     pub fn new(config: BitcoinConfig) -> Self {
@@ -148,7 +177,13 @@ impl BitcoinRpcClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { config, client }
+        Self { config, client, coinbase_tags: Arc::new(CoinbaseTagRotator::default()) }
+    }
+
+    /// Sign generated coinbases with tags from `coinbase_tags`
+    pub fn with_coinbase_tags(mut self, coinbase_tags: Arc<CoinbaseTagRotator>) -> Self {
+        self.coinbase_tags = coinbase_tags;
+        self
     }
 
     /// Test connection to Bitcoin node
@@ -169,6 +204,16 @@ impl BitcoinRpcClient {
         Ok(response)
     }
 
+    /// Get the header of block `hash`, including how deep it is in the active chain
+    pub async fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeaderResponse> {
+        let params = serde_json::Value::Array(vec![
+            serde_json::Value::String(hash.to_string()),
+            serde_json::Value::Bool(true),
+        ]);
+        let response = self.call_rpc("getblockheader", params).await?;
+        Ok(response)
+    }
+
     /// Get block template for mining
     pub async fn get_block_template(&self, rules: Option<Vec<String>>) -> Result<GetBlockTemplateResponse> {
         let mut params = serde_json::Map::new();
@@ -226,12 +271,14 @@ impl BitcoinRpcClient {
         // Calculate difficulty from target
         let difficulty = self.calculate_difficulty_from_target(&block_template.target)?;
 
-        let template = WorkTemplate::new(
+        let mut template = WorkTemplate::new(
             previous_hash,
             coinbase_tx,
             transactions,
             difficulty,
         );
+        template.ntime_bounds = Some(NtimeBounds::new(block_template.mintime, block_template.curtime));
+        template.fees = Some(block_template.transactions.iter().map(|tx| tx.fee).sum());
 
         Ok(template)
     }
@@ -244,12 +291,7 @@ impl BitcoinRpcClient {
     ) -> Result<Transaction> {
         use bitcoin::{TxIn, TxOut, OutPoint, Witness};
 
-        // Parse the coinbase address
-        let address: Address<NetworkUnchecked> = coinbase_address.parse()
-            .map_err(|e| Error::BitcoinRpc(format!("Invalid coinbase address: {}", e)))?;
-        
-        let address = address.require_network(self.get_bitcoin_network())
-            .map_err(|e| Error::BitcoinRpc(format!("Address network mismatch: {}", e)))?;
+        let script_pubkey = payout_script(coinbase_address, self.get_bitcoin_network())?;
 
         // Create coinbase input (null hash, 0xffffffff index)
         let coinbase_input = TxIn {
@@ -259,10 +301,10 @@ impl BitcoinRpcClient {
             witness: Witness::new(),
         };
 
-        // Create coinbase output
+        // The whole reward, subsidy plus fees, to the configured address
         let coinbase_output = TxOut {
             value: template.coinbasevalue,
-            script_pubkey: address.script_pubkey(),
+            script_pubkey,
         };
 
         // Add witness commitment if required
@@ -285,6 +327,7 @@ impl BitcoinRpcClient {
             input: vec![coinbase_input],
             output: outputs,
         };
+        crate::bip34::check_coinbase(&coinbase_tx, template.height)?;
 
         Ok(coinbase_tx)
     }
@@ -301,8 +344,11 @@ impl BitcoinRpcClient {
         // Add extra nonce space (8 bytes)
         script_builder = script_builder.push_slice(&[0u8; 8]);
         
-        // Add arbitrary data (sv2 identifier) - This proves the block was mined via sv2d
-        script_builder = script_builder.push_slice(b"/sv2-stratum-v2-daemon/");
+        // Add the coinbase tag, rotated per the configured policy
+        let tag = self.coinbase_tags.tag_for_height(height);
+        let tag = bitcoin::script::PushBytesBuf::try_from(tag.into_bytes())
+            .map_err(|e| Error::BitcoinRpc(format!("Coinbase tag too long: {}", e)))?;
+        script_builder = script_builder.push_slice(tag);
 
         Ok(script_builder.into_script())
     }
@@ -348,12 +394,7 @@ impl BitcoinRpcClient {
 
     /// Get Bitcoin network from config
     fn get_bitcoin_network(&self) -> Network {
-        match self.config.network {
-            crate::config::BitcoinNetwork::Mainnet => Network::Bitcoin,
-            crate::config::BitcoinNetwork::Testnet => Network::Testnet,
-            crate::config::BitcoinNetwork::Signet => Network::Signet,
-            crate::config::BitcoinNetwork::Regtest => Network::Regtest,
-        }
+        bitcoin_network(&self.config.network)
     }
 
     /// Make RPC call to Bitcoin node
@@ -472,6 +513,10 @@ impl From<GetBlockTemplateResponse> for BlockTemplate {
             difficulty: 1.0, // Would be calculated from target
             timestamp: response.curtime,
             expires_at: chrono::Utc::now() + chrono::Duration::minutes(5),
+            ntime_bounds: Some(NtimeBounds::new(response.mintime, response.curtime)),
+            fees: Some(response.transactions.iter().map(|tx| tx.fee).sum()),
+            version: Some(response.version),
+            merkle_path: Vec::new(),
         };
 
         BlockTemplate {
@@ -493,7 +538,6 @@ impl From<GetBlockTemplateResponse> for BlockTemplate {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{BitcoinConfig, BitcoinNetwork};
 
     fn create_test_config() -> BitcoinConfig {
         BitcoinConfig {
@@ -540,6 +584,8 @@ mod tests {
         // Script should contain the block height
         let script_bytes = script.as_bytes();
         assert!(script_bytes.len() > 0);
+        assert!(script_bytes.ends_with(crate::coinbase_tag::DEFAULT_COINBASE_TAG.as_bytes()));
+        assert!(script_bytes.len() <= crate::coinbase_tag::MAX_SCRIPT_SIG_LEN);
     }
 
     #[test]
@@ -684,4 +730,72 @@ mod tests {
         assert_eq!(block_template.reward, 5000000000);
         assert!(block_template.capabilities.contains(&"proposal".to_string()));
     }
-}
\ No newline at end of file
+
+    fn block_template(coinbasevalue: u64, default_witness_commitment: Option<String>) -> GetBlockTemplateResponse {
+        GetBlockTemplateResponse {
+            version: 0x2000_0000,
+            rules: vec!["segwit".to_string()],
+            vbavailable: HashMap::new(),
+            vbrequired: 0,
+            previousblockhash: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            transactions: vec![],
+            coinbaseaux: HashMap::new(),
+            coinbasevalue,
+            longpollid: None,
+            target: "00000000ffff0000000000000000000000000000000000000000000000000000".to_string(),
+            mintime: 1000000000,
+            mutable: vec!["time".to_string()],
+            noncerange: "00000000ffffffff".to_string(),
+            sigoplimit: 80000,
+            sizelimit: 4000000,
+            weightlimit: 4000000,
+            curtime: 1000000000,
+            bits: "207fffff".to_string(),
+            height: 840_000,
+            default_witness_commitment,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coinbase_pays_configured_address() {
+        let client = BitcoinRpcClient::new(create_test_config());
+        // Subsidy plus fees, as getblocktemplate reports it
+        let coinbasevalue = 312_500_000 + 48_213;
+        let commitment = "6a24aa21a9ed".to_string() + &"11".repeat(32);
+        let template = block_template(coinbasevalue, Some(commitment.clone()));
+
+        let coinbase = client
+            .create_coinbase_transaction(&template, "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .await
+            .unwrap();
+
+        // P2WPKH for the address's 20-byte witness program
+        assert_eq!(
+            hex::encode(coinbase.output[0].script_pubkey.as_bytes()),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert_eq!(coinbase.output[0].value, coinbasevalue);
+        assert_eq!(hex::encode(coinbase.output[1].script_pubkey.as_bytes()), commitment);
+        assert_eq!(coinbase.output[1].value, 0);
+
+        let script_sig = coinbase.input[0].script_sig.as_bytes();
+        assert!(script_sig.ends_with(crate::coinbase_tag::DEFAULT_COINBASE_TAG.as_bytes()));
+        assert_eq!(crate::bip34::coinbase_height(&coinbase), Some(840_000));
+    }
+
+    #[tokio::test]
+    async fn test_coinbase_address_on_wrong_network_fails() {
+        let client = BitcoinRpcClient::new(create_test_config());
+        let template = block_template(5_000_000_000, None);
+
+        let error = client
+            .create_coinbase_transaction(&template, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("is a mainnet address, not a regtest one"), "{}", error);
+
+        assert!(payout_script("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080", Network::Regtest).is_ok());
+        assert!(payout_script("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080", Network::Bitcoin).is_err());
+        assert!(payout_script("not-an-address", Network::Regtest).is_err());
+    }
+}
@@ -201,5 +201,8 @@ fn create_test_solo_config() -> SoloConfig {
         block_template_refresh_interval: 30,
         enable_custom_templates: false,
         max_template_age: 300,
+        template_source: Default::default(),
+        coinbase_tags: Default::default(),
+        block_confirmations: 6,
     }
 }
\ No newline at end of file
@@ -0,0 +1,380 @@
+//! Found-block history and confirmation tracking
+//!
+//! Every block the daemon finds is stored in the `blocks` table with its
+//! height, finding worker, reward and fees, and served at `GET /api/v1/blocks`
+//! and by `sv2-cli blocks`.
+//!
+//! A block `submitblock` accepted is only `submitted`: it can still lose a
+//! race with another block at the same height. Each found block gets an
+//! informational alert and is watched until it is [`SETTLED_DEPTH`]
+//! deep. Confirmations only change when the chain tip moves, so watched
+//! blocks are looked up again on every tip change; a block becomes
+//! `confirmed` once it has `[solo] block_confirmations` confirmations, or
+//! `orphaned` if it leaves the active chain. Its record and alert are updated
+//! to match.
+
+use crate::bip34;
+use crate::database::DatabaseOps;
+use crate::reorg::ChainView;
+use crate::types::{Alert, AlertSeverity, WorkTemplate};
+use crate::ConnectionId;
+use bitcoin::BlockHash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tokio::sync::Mutex;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+/// Depth at which a found block stops being watched (coinbase maturity)
+pub const SETTLED_DEPTH: u64 = 100;
+
+/// Found blocks watched at once; the oldest is dropped beyond this
+const MAX_WATCHED: usize = 32;
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockStatus {
+    /// Accepted by the node, not yet buried deep enough to count
+    Submitted,
+    /// In the active chain with the required confirmations
+    Confirmed,
+    /// Dropped from the active chain by a reorg
+    Orphaned,
+}
+
+impl BlockStatus {
+    /// Name the status is stored under
+    pub fn key(&self) -> &'static str {
+        match self {
+            BlockStatus::Submitted => "submitted",
+            BlockStatus::Confirmed => "confirmed",
+            BlockStatus::Orphaned => "orphaned",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "submitted" => Some(BlockStatus::Submitted),
+            "confirmed" => Some(BlockStatus::Confirmed),
+            "orphaned" => Some(BlockStatus::Orphaned),
+            _ => None,
+        }
+    }
+}
+
+/// A block this daemon found and what became of it
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockRecord {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub hash: BlockHash,
+    /// BIP 34 height from the coinbase, when the template carried one
+    pub height: Option<u64>,
+    /// Connection whose share solved the block
+    pub connection_id: Option<ConnectionId>,
+    /// Worker that submitted the share
+    pub worker: Option<String>,
+    /// Coinbase outputs in satoshis: subsidy plus fees
+    pub reward: Option<u64>,
+    /// Transaction fees in satoshis, when the template source reported them
+    pub fees: Option<u64>,
+    pub status: BlockStatus,
+    /// Confirmations when last looked up; 0 once orphaned
+    pub confirmations: u64,
+    /// Alert kept in step with the status
+    pub alert_id: Option<Uuid>,
+    pub found_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BlockRecord {
+    /// A block built from `template` that the node just accepted
+    pub fn submitted(hash: BlockHash, template: &WorkTemplate) -> Self {
+        let now = Utc::now();
+        Self {
+            hash,
+            height: bip34::coinbase_height(&template.coinbase_tx),
+            connection_id: None,
+            worker: None,
+            reward: Some(template.coinbase_tx.output.iter().map(|output| output.value).sum()),
+            fees: template.fees,
+            status: BlockStatus::Submitted,
+            confirmations: 0,
+            alert_id: None,
+            found_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Apply a fresh lookup: confirmations in the active chain, or `None` if
+    /// the block isn't in it. Returns whether anything changed.
+    pub fn observe(&mut self, confirmations: Option<u64>, required: u64) -> bool {
+        let (status, confirmations) = match confirmations {
+            None => (BlockStatus::Orphaned, 0),
+            Some(depth) if depth >= required => (BlockStatus::Confirmed, depth),
+            Some(depth) => (BlockStatus::Submitted, depth),
+        };
+        if status == self.status && confirmations == self.confirmations {
+            return false;
+        }
+        self.status = status;
+        self.confirmations = confirmations;
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// Whether the block no longer needs watching
+    pub fn is_settled(&self) -> bool {
+        self.status == BlockStatus::Orphaned || self.confirmations >= SETTLED_DEPTH
+    }
+
+    fn alert(&self, required: u64) -> Alert {
+        let height = self.height.map(|h| h.to_string()).unwrap_or_else(|| "unknown".to_string());
+        let (severity, message) = match self.status {
+            BlockStatus::Submitted => (
+                AlertSeverity::Info,
+                format!(
+                    "Block {} at height {} submitted, {}/{} confirmations",
+                    self.hash, height, self.confirmations, required
+                ),
+            ),
+            BlockStatus::Confirmed => (
+                AlertSeverity::Info,
+                format!("Block {} at height {} confirmed ({} confirmations)", self.hash, height, self.confirmations),
+            ),
+            BlockStatus::Orphaned => (
+                AlertSeverity::Warning,
+                format!("Block {} at height {} was orphaned by a chain reorg", self.hash, height),
+            ),
+        };
+        Alert {
+            id: self.alert_id.unwrap_or_else(Uuid::new_v4),
+            severity,
+            message,
+            timestamp: self.updated_at,
+            acknowledged: false,
+        }
+    }
+}
+
+/// Watches found blocks until they confirm, orphan or settle
+#[derive(Debug)]
+pub struct BlockTracker {
+    /// Confirmations a block needs to count as confirmed
+    required_confirmations: u64,
+    watched: Mutex<VecDeque<BlockRecord>>,
+}
+
+impl BlockTracker {
+    pub fn new(required_confirmations: u64) -> Self {
+        Self { required_confirmations, watched: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Store a block the node accepted, raise its alert and start watching it
+    pub async fn submitted(&self, database: &dyn DatabaseOps, mut record: BlockRecord) -> BlockRecord {
+        let alert = record.alert(self.required_confirmations);
+        match database.create_alert(&alert).await {
+            Ok(()) => record.alert_id = Some(alert.id),
+            Err(e) => error!("Failed to raise alert for block {}: {}", record.hash, e),
+        }
+        self.store(database, &record).await;
+
+        let mut watched = self.watched.lock().await;
+        if watched.len() == MAX_WATCHED {
+            watched.pop_front();
+        }
+        watched.push_back(record.clone());
+        record
+    }
+
+    /// Resume watching the unsettled blocks an earlier run stored
+    pub async fn load(&self, database: &dyn DatabaseOps) -> crate::Result<()> {
+        let stored = database.get_blocks(None).await?;
+        let mut watched = self.watched.lock().await;
+        watched.clear();
+        // Stored newest first; watch oldest first
+        watched.extend(stored.into_iter().rev().filter(|record| !record.is_settled()));
+        while watched.len() > MAX_WATCHED {
+            watched.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Blocks currently watched, oldest first
+    pub async fn watched(&self) -> Vec<BlockRecord> {
+        self.watched.lock().await.iter().cloned().collect()
+    }
+
+    /// Look every watched block up again, storing changes and updating their
+    /// alerts. Returns the blocks whose status changed. Blocks that can't be
+    /// looked up right now stay as they are.
+    pub async fn review(&self, database: &dyn DatabaseOps, chain: &dyn ChainView) -> Vec<BlockRecord> {
+        let mut watched = self.watched.lock().await;
+        let mut transitions = Vec::new();
+        let mut still_watched = VecDeque::with_capacity(watched.len());
+        for mut record in watched.drain(..) {
+            let confirmations = match chain.confirmations(&record.hash).await {
+                Ok(confirmations) => confirmations,
+                Err(e) => {
+                    debug!("Couldn't look up found block {}: {}", record.hash, e);
+                    still_watched.push_back(record);
+                    continue;
+                }
+            };
+
+            let previous = record.status;
+            if record.observe(confirmations, self.required_confirmations) {
+                self.update_alert(database, &mut record).await;
+                self.store(database, &record).await;
+                if record.status != previous {
+                    transitions.push(record.clone());
+                }
+            }
+            if !record.is_settled() {
+                still_watched.push_back(record);
+            }
+        }
+        *watched = still_watched;
+        transitions
+    }
+
+    async fn update_alert(&self, database: &dyn DatabaseOps, record: &mut BlockRecord) {
+        let alert = record.alert(self.required_confirmations);
+        let result = match record.alert_id {
+            Some(_) => database.update_alert(&alert).await,
+            None => database.create_alert(&alert).await,
+        };
+        match result {
+            Ok(()) => record.alert_id = Some(alert.id),
+            Err(e) => error!("Failed to update alert for block {}: {}", record.hash, e),
+        }
+    }
+
+    async fn store(&self, database: &dyn DatabaseOps, record: &BlockRecord) {
+        if let Err(e) = database.store_block(record).await {
+            error!("Failed to store block {}: {}", record.hash, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+    use crate::reorg::MockChainView;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Transaction, TxIn, TxOut};
+
+    fn hash(byte: u8) -> BlockHash {
+        BlockHash::from_byte_array([byte; 32])
+    }
+
+    /// A template for `height` paying 3.125 BTC subsidy plus `fees`
+    fn template(height: u64, fees: u64) -> WorkTemplate {
+        let coinbase = Transaction {
+            version: 1,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn { script_sig: bip34::height_push(height), ..TxIn::default() }],
+            output: vec![TxOut { value: 312_500_000 + fees, ..TxOut::default() }],
+        };
+        let mut template = WorkTemplate::new(hash(0), coinbase, vec![], 1.0);
+        template.fees = Some(fees);
+        template
+    }
+
+    fn found(byte: u8) -> BlockRecord {
+        BlockRecord::submitted(hash(byte), &template(840_000 + byte as u64, 10_000))
+    }
+
+    #[tokio::test]
+    async fn test_found_block_is_recorded_and_listed() {
+        let database = MockDatabaseOps::new();
+        let tracker = BlockTracker::new(6);
+        let first = BlockRecord { worker: Some("rig-a".to_string()), ..found(1) };
+        tracker.submitted(&database, first).await;
+        tracker.submitted(&database, found(2)).await;
+
+        let blocks = database.get_blocks(None).await.unwrap();
+        assert_eq!(blocks.iter().map(|block| block.hash).collect::<Vec<_>>(), vec![hash(2), hash(1)]);
+        let first = &blocks[1];
+        assert_eq!(first.height, Some(840_001));
+        assert_eq!(first.worker.as_deref(), Some("rig-a"));
+        assert_eq!(first.reward, Some(312_510_000));
+        assert_eq!(first.fees, Some(10_000));
+        assert_eq!(first.status, BlockStatus::Submitted);
+        assert!(first.alert_id.is_some());
+
+        assert_eq!(database.get_blocks(Some(1)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_submitted_block_confirms() {
+        let database = MockDatabaseOps::new();
+        let chain = MockChainView::new();
+        let tracker = BlockTracker::new(6);
+        let record = tracker.submitted(&database, found(1)).await;
+        assert_eq!(record.status, BlockStatus::Submitted);
+
+        chain.set(hash(1), Some(3));
+        assert!(tracker.review(&database, &chain).await.is_empty());
+        let stored = database.get_blocks(None).await.unwrap();
+        assert_eq!((stored[0].status, stored[0].confirmations), (BlockStatus::Submitted, 3));
+
+        chain.set(hash(1), Some(6));
+        let transitions = tracker.review(&database, &chain).await;
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].status, BlockStatus::Confirmed);
+        assert_eq!(database.get_blocks(None).await.unwrap()[0].status, BlockStatus::Confirmed);
+
+        // One alert, updated in place
+        let alerts = database.get_alerts(None, None).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(Some(alerts[0].id), record.alert_id);
+        assert!(alerts[0].message.contains("confirmed (6 confirmations)"), "{}", alerts[0].message);
+
+        // Watched until coinbase maturity
+        chain.set(hash(1), Some(SETTLED_DEPTH));
+        tracker.review(&database, &chain).await;
+        assert!(tracker.watched().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_submitted_block_orphans() {
+        let database = MockDatabaseOps::new();
+        let chain = MockChainView::new();
+        let tracker = BlockTracker::new(6);
+        tracker.submitted(&database, found(2)).await;
+        chain.set(hash(2), Some(1));
+        tracker.review(&database, &chain).await;
+
+        // A competing branch at the same height wins
+        chain.set(hash(2), None);
+        let transitions = tracker.review(&database, &chain).await;
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].status, BlockStatus::Orphaned);
+        assert!(tracker.watched().await.is_empty());
+
+        let stored = &database.get_blocks(None).await.unwrap()[0];
+        assert_eq!((stored.status, stored.confirmations), (BlockStatus::Orphaned, 0));
+        let alerts = database.get_alerts(None, None).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+        assert!(alerts[0].message.contains("orphaned"));
+    }
+
+    #[tokio::test]
+    async fn test_unsettled_blocks_reload() {
+        let database = MockDatabaseOps::new();
+        let mut settled = found(3);
+        settled.observe(Some(SETTLED_DEPTH), 6);
+        database.store_block(&settled).await.unwrap();
+        database.store_block(&found(4)).await.unwrap();
+
+        let tracker = BlockTracker::new(6);
+        tracker.load(&database).await.unwrap();
+        let watched: Vec<_> = tracker.watched().await.into_iter().map(|record| record.hash).collect();
+        assert_eq!(watched, vec![hash(4)]);
+    }
+}
@@ -0,0 +1,403 @@
+//! Raw byte capture for individual connections
+//!
+//! Captures are written as newline-delimited JSON records so they can be
+//! inspected by hand and re-fed through the protocol parser with
+//! `sv2-cli replay`.
+
+use crate::{Result, Error, ConnectionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// Placeholder written in place of redacted credentials
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Longest partial line held back waiting for its newline; SV1 messages are
+/// far shorter, so anything longer is written as it arrives
+const MAX_PENDING_LINE: usize = 16 * 1024;
+
+/// Connection capture configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CaptureConfig {
+    /// Capture every connection
+    pub enabled: bool,
+    /// Directory capture files are written to
+    pub directory: PathBuf,
+    /// Maximum bytes recorded per connection before capture stops
+    pub max_bytes: u64,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("captures"),
+            max_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+/// Direction of captured traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A single chunk of captured traffic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    pub timestamp: DateTime<Utc>,
+    pub direction: CaptureDirection,
+    /// Hex-encoded bytes
+    pub data: String,
+}
+
+impl CaptureRecord {
+    /// Decode the captured bytes
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(&self.data)
+            .map_err(|e| Error::Protocol(format!("Invalid capture data: {}", e)))
+    }
+}
+
+/// Writer for a single connection's capture file
+pub struct ConnectionCapture {
+    file: File,
+    path: PathBuf,
+    written: u64,
+    max_bytes: u64,
+    truncated: bool,
+    /// Trailing partial line in each direction, held back until its newline
+    /// arrives so a message split across reads is redacted as a whole
+    pending_inbound: Vec<u8>,
+    pending_outbound: Vec<u8>,
+    /// `Some(redacted)` while the rest of a line too long to hold back is
+    /// still arriving in that direction
+    oversized_inbound: Option<bool>,
+    oversized_outbound: Option<bool>,
+}
+
+impl ConnectionCapture {
+    /// Create a capture file for a connection in the given directory
+    pub fn create(directory: &Path, connection_id: ConnectionId, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join(format!(
+            "{}-{}.capture",
+            Utc::now().format("%Y%m%dT%H%M%S"),
+            connection_id
+        ));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        info!("Capturing connection {} to {}", connection_id, path.display());
+
+        Ok(Self {
+            file,
+            path,
+            written: 0,
+            max_bytes,
+            truncated: false,
+            pending_inbound: Vec::new(),
+            pending_outbound: Vec::new(),
+            oversized_inbound: None,
+            oversized_outbound: None,
+        })
+    }
+
+    /// Record a chunk of traffic, redacting credentials
+    ///
+    /// Text is recorded a complete line at a time, whatever the reads it
+    /// arrived in. Binary (SV2) traffic is recorded as it comes.
+    ///
+    /// A line longer than `MAX_PENDING_LINE` is written in pieces, which
+    /// can't be parsed to redact. Once a piece mentions `mining.authorize`,
+    /// it and the rest of its line are replaced outright.
+    pub fn record(&mut self, direction: CaptureDirection, data: &[u8]) -> Result<()> {
+        if self.truncated {
+            return Ok(());
+        }
+
+        let (pending, oversized) = match direction {
+            CaptureDirection::Inbound => (&mut self.pending_inbound, &mut self.oversized_inbound),
+            CaptureDirection::Outbound => (&mut self.pending_outbound, &mut self.oversized_outbound),
+        };
+        if pending.is_empty() && oversized.is_none() && std::str::from_utf8(data).is_err() {
+            return self.write(direction, data);
+        }
+        pending.extend_from_slice(data);
+
+        let mut pieces = Vec::with_capacity(2);
+        if let Some(redacted) = *oversized {
+            // The rest of the oversized line, up to and including its newline
+            let end = pending.iter().position(|&byte| byte == b'\n').map_or(pending.len(), |end| end + 1);
+            let piece: Vec<u8> = pending.drain(..end).collect();
+            let redact = redacted || mentions_authorize(&piece);
+            *oversized = if piece.ends_with(b"\n") { None } else { Some(redact) };
+            if !redact {
+                pieces.push(piece);
+            } else if !redacted {
+                pieces.push(REDACTED.as_bytes().to_vec());
+            }
+        }
+        match pending.iter().rposition(|&byte| byte == b'\n') {
+            Some(end) => pieces.push(pending.drain(..=end).collect()),
+            None if pending.len() > MAX_PENDING_LINE => {
+                let piece = std::mem::take(pending);
+                let redact = mentions_authorize(&piece);
+                *oversized = Some(redact);
+                pieces.push(if redact { REDACTED.as_bytes().to_vec() } else { piece });
+            }
+            None => {}
+        }
+
+        for piece in pieces {
+            self.write(direction, &piece)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, direction: CaptureDirection, data: &[u8]) -> Result<()> {
+        if self.written + data.len() as u64 > self.max_bytes {
+            warn!("Capture {} reached its {} byte limit", self.path.display(), self.max_bytes);
+            self.truncated = true;
+            return Ok(());
+        }
+
+        let record = CaptureRecord {
+            timestamp: Utc::now(),
+            direction,
+            data: hex::encode(redact_credentials(data)),
+        };
+        serde_json::to_writer(&mut self.file, &record)?;
+        self.file.write_all(b"\n")?;
+        self.written += data.len() as u64;
+
+        Ok(())
+    }
+
+    /// Path of the capture file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the size cap has been reached
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Drop for ConnectionCapture {
+    /// Record what the connection sent before closing without a newline.
+    /// A cut-off `mining.authorize` can't be parsed to redact, so it's
+    /// replaced outright.
+    fn drop(&mut self) {
+        for direction in [CaptureDirection::Inbound, CaptureDirection::Outbound] {
+            let pending = match direction {
+                CaptureDirection::Inbound => std::mem::take(&mut self.pending_inbound),
+                CaptureDirection::Outbound => std::mem::take(&mut self.pending_outbound),
+            };
+            if pending.is_empty() || self.truncated {
+                continue;
+            }
+            let pending = if mentions_authorize(&pending) {
+                REDACTED.as_bytes().to_vec()
+            } else {
+                pending
+            };
+            if let Err(e) = self.write(direction, &pending) {
+                warn!("Failed to capture the end of {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+/// Decides which connections are captured
+///
+/// Capture is enabled for every connection via config, or for a single
+/// connection at runtime through [`CaptureController::enable`].
+#[derive(Debug, Default)]
+pub struct CaptureController {
+    config: CaptureConfig,
+    connections: RwLock<HashSet<ConnectionId>>,
+}
+
+impl CaptureController {
+    pub fn new(config: CaptureConfig) -> Self {
+        Self {
+            config,
+            connections: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Start capturing a specific connection
+    pub fn enable(&self, connection_id: ConnectionId) {
+        self.connections.write().unwrap().insert(connection_id);
+    }
+
+    /// Stop capturing a specific connection
+    pub fn disable(&self, connection_id: ConnectionId) {
+        self.connections.write().unwrap().remove(&connection_id);
+    }
+
+    /// Whether a connection should be captured
+    pub fn is_enabled(&self, connection_id: ConnectionId) -> bool {
+        self.config.enabled || self.connections.read().unwrap().contains(&connection_id)
+    }
+
+    /// Open a capture file for a connection
+    pub fn open(&self, connection_id: ConnectionId) -> Result<ConnectionCapture> {
+        ConnectionCapture::create(&self.config.directory, connection_id, self.config.max_bytes)
+    }
+}
+
+/// Whether text that can't be parsed may hold a `mining.authorize` password
+fn mentions_authorize(data: &[u8]) -> bool {
+    String::from_utf8_lossy(data).contains("mining.authorize")
+}
+
+/// Replace `mining.authorize` passwords in newline-delimited JSON
+pub fn redact_credentials(data: &[u8]) -> Vec<u8> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return data.to_vec(),
+    };
+
+    text.split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim_end();
+            let mut value: serde_json::Value = match serde_json::from_str(trimmed) {
+                Ok(value) => value,
+                Err(_) => return line.to_string(),
+            };
+
+            if value.get("method").and_then(|m| m.as_str()) != Some("mining.authorize") {
+                return line.to_string();
+            }
+
+            if let Some(password) = value.get_mut("params").and_then(|p| p.get_mut(1)) {
+                *password = serde_json::Value::String(REDACTED.to_string());
+            }
+
+            format!("{}{}", value, &line[trimmed.len()..])
+        })
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Read all records from a capture file
+pub fn read_capture(path: &Path) -> Result<Vec<CaptureRecord>> {
+    let file = File::open(path)?;
+    let mut records = Vec::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line)
+            .map_err(|e| Error::Protocol(format!("Invalid capture record on line {}: {}", index + 1, e)))?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_capture_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut capture = ConnectionCapture::create(dir.path(), Uuid::new_v4(), 1024).unwrap();
+
+        let authorize = b"{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"worker1\",\"secret\"]}\n";
+        let response = b"{\"id\":2,\"result\":true,\"error\":null}\n";
+        capture.record(CaptureDirection::Inbound, authorize).unwrap();
+        capture.record(CaptureDirection::Outbound, response).unwrap();
+
+        let records = read_capture(capture.path()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].direction, CaptureDirection::Inbound);
+        assert_eq!(records[1].bytes().unwrap(), response.to_vec());
+
+        let inbound = String::from_utf8(records[0].bytes().unwrap()).unwrap();
+        assert!(!inbound.contains("secret"));
+        assert!(inbound.contains(REDACTED));
+        assert!(inbound.contains("worker1"));
+    }
+
+    #[test]
+    fn test_authorize_split_across_reads_is_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut capture = ConnectionCapture::create(dir.path(), Uuid::new_v4(), 1024).unwrap();
+
+        capture.record(CaptureDirection::Inbound, b"{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"worker1\",\"sec").unwrap();
+        capture.record(CaptureDirection::Inbound, b"ret\"]}\n{\"id\":3,").unwrap();
+        capture.record(CaptureDirection::Inbound, b"\"method\":\"mining.authorize\",\"params\":[\"worker2\",\"hunter2").unwrap();
+        let path = capture.path().to_path_buf();
+        drop(capture);
+
+        let records = read_capture(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        let inbound: String = records
+            .iter()
+            .map(|record| String::from_utf8(record.bytes().unwrap()).unwrap())
+            .collect();
+        assert!(!inbound.contains("secret"));
+        assert!(!inbound.contains("hunter2"));
+        assert!(inbound.contains("worker1"));
+    }
+
+    #[test]
+    fn test_oversized_authorize_is_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut capture = ConnectionCapture::create(dir.path(), Uuid::new_v4(), 1024 * 1024).unwrap();
+
+        // Padded past MAX_PENDING_LINE, then the password in a later read
+        let padding = "x".repeat(MAX_PENDING_LINE);
+        let start = format!("{{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"worker1\",\"{}", padding);
+        capture.record(CaptureDirection::Inbound, start.as_bytes()).unwrap();
+        capture.record(CaptureDirection::Inbound, b"secret").unwrap();
+        capture.record(CaptureDirection::Inbound, b"\"]}\n{\"id\":3,\"method\":\"mining.submit\"}\n").unwrap();
+        let path = capture.path().to_path_buf();
+        drop(capture);
+
+        let records: Vec<String> = read_capture(&path)
+            .unwrap()
+            .iter()
+            .map(|record| String::from_utf8(record.bytes().unwrap()).unwrap())
+            .collect();
+        assert_eq!(records, vec![REDACTED.to_string(), "{\"id\":3,\"method\":\"mining.submit\"}\n".to_string()]);
+    }
+
+    #[test]
+    fn test_capture_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut capture = ConnectionCapture::create(dir.path(), Uuid::new_v4(), 16).unwrap();
+
+        capture.record(CaptureDirection::Inbound, b"012345678\n").unwrap();
+        capture.record(CaptureDirection::Inbound, b"012345678\n").unwrap();
+
+        assert!(capture.is_truncated());
+        assert_eq!(read_capture(capture.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_controller_per_connection() {
+        let controller = CaptureController::new(CaptureConfig::default());
+        let connection_id = Uuid::new_v4();
+
+        assert!(!controller.is_enabled(connection_id));
+        controller.enable(connection_id);
+        assert!(controller.is_enabled(connection_id));
+        controller.disable(connection_id);
+        assert!(!controller.is_enabled(connection_id));
+    }
+}
@@ -0,0 +1,249 @@
+//! Coinbase tag rotation
+//!
+//! Solo mode signs its coinbase scriptSig with a tag. Configuring several tags
+//! under `[mode.config.coinbase_tags]` cycles through them per block, every N
+//! blocks or on a timer, for vanity signatures or to tell blocks apart.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tag used when none are configured
+pub const DEFAULT_COINBASE_TAG: &str = "/sv2-stratum-v2-daemon/";
+
+/// Consensus limit on the coinbase scriptSig
+pub const MAX_SCRIPT_SIG_LEN: usize = 100;
+
+/// scriptSig bytes taken before the tag: the BIP 34 height push (up to 5
+/// bytes) and the 8-byte extranonce push
+const RESERVED_SCRIPT_SIG_LEN: usize = 5 + 9;
+
+/// When to move on to the next tag
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum TagRotation {
+    /// Next tag on every new block
+    #[default]
+    PerBlock,
+    /// Next tag after `blocks` new blocks
+    EveryNBlocks { blocks: u64 },
+    /// Next tag once `interval_secs` have passed, checked on each new template
+    Timed { interval_secs: u64 },
+}
+
+/// `coinbase_tags` configuration
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CoinbaseTagConfig {
+    /// Tags to cycle through; [`DEFAULT_COINBASE_TAG`] when empty
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub rotation: TagRotation,
+}
+
+impl CoinbaseTagConfig {
+    pub fn validate(&self) -> Result<()> {
+        for tag in &self.tags {
+            if tag.is_empty() {
+                return Err(Error::Config("coinbase_tags entries must not be empty".to_string()));
+            }
+            if !fits_script_sig(tag) {
+                return Err(Error::Config(format!(
+                    "coinbase tag '{}' is {} bytes; at most {} fit in the coinbase scriptSig",
+                    tag,
+                    tag.len(),
+                    max_tag_len()
+                )));
+            }
+        }
+
+        match self.rotation {
+            TagRotation::EveryNBlocks { blocks: 0 } => Err(Error::Config(
+                "coinbase_tags rotation every_n_blocks needs blocks > 0".to_string(),
+            )),
+            TagRotation::Timed { interval_secs: 0 } => Err(Error::Config(
+                "coinbase_tags rotation timed needs interval_secs > 0".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Bytes a push of `len` bytes takes in a script
+fn push_len(len: usize) -> usize {
+    match len {
+        0..=75 => 1 + len,
+        76..=255 => 2 + len,
+        _ => 3 + len,
+    }
+}
+
+/// Whether `tag` fits in the scriptSig after the height and extranonce
+pub fn fits_script_sig(tag: &str) -> bool {
+    RESERVED_SCRIPT_SIG_LEN + push_len(tag.len()) <= MAX_SCRIPT_SIG_LEN
+}
+
+/// Longest tag that fits in the scriptSig
+pub fn max_tag_len() -> usize {
+    (0..MAX_SCRIPT_SIG_LEN)
+        .take_while(|&len| RESERVED_SCRIPT_SIG_LEN + push_len(len) <= MAX_SCRIPT_SIG_LEN)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Picks the tag for each coinbase according to the rotation policy
+#[derive(Debug)]
+pub struct CoinbaseTagRotator {
+    tags: Vec<String>,
+    rotation: TagRotation,
+    state: Mutex<RotationState>,
+}
+
+#[derive(Debug)]
+struct RotationState {
+    index: usize,
+    last_height: Option<u64>,
+    /// New blocks seen since the current tag became active
+    blocks_on_tag: u64,
+    activated_at: Instant,
+}
+
+impl CoinbaseTagRotator {
+    pub fn new(config: &CoinbaseTagConfig) -> Self {
+        let tags = if config.tags.is_empty() {
+            vec![DEFAULT_COINBASE_TAG.to_string()]
+        } else {
+            config.tags.clone()
+        };
+        Self {
+            tags,
+            rotation: config.rotation.clone(),
+            state: Mutex::new(RotationState {
+                index: 0,
+                last_height: None,
+                blocks_on_tag: 0,
+                activated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Tag for a coinbase at `height`, advancing the rotation if due
+    pub fn tag_for_height(&self, height: u64) -> String {
+        self.tag_at(height, Instant::now())
+    }
+
+    fn tag_at(&self, height: u64, now: Instant) -> String {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Templates are refreshed several times per block; only a new height counts
+        let new_block = state.last_height.is_some_and(|last| last != height);
+        if state.last_height.is_none() {
+            state.activated_at = now;
+        }
+        state.last_height = Some(height);
+
+        if self.tags.len() > 1 {
+            if new_block {
+                state.blocks_on_tag += 1;
+            }
+            let advance = match self.rotation {
+                TagRotation::PerBlock => new_block,
+                TagRotation::EveryNBlocks { blocks } => state.blocks_on_tag >= blocks,
+                TagRotation::Timed { interval_secs } => {
+                    now.duration_since(state.activated_at) >= Duration::from_secs(interval_secs)
+                }
+            };
+            if advance {
+                state.index = (state.index + 1) % self.tags.len();
+                state.blocks_on_tag = 0;
+                state.activated_at = now;
+            }
+        }
+
+        self.tags[state.index].clone()
+    }
+
+    /// Tag the next coinbase will carry, unless rotation is due
+    pub fn active_tag(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.tags[state.index].clone()
+    }
+}
+
+impl Default for CoinbaseTagRotator {
+    fn default() -> Self {
+        Self::new(&CoinbaseTagConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotator(rotation: TagRotation) -> CoinbaseTagRotator {
+        CoinbaseTagRotator::new(&CoinbaseTagConfig {
+            tags: vec!["/a/".to_string(), "/b/".to_string(), "/c/".to_string()],
+            rotation,
+        })
+    }
+
+    #[test]
+    fn test_per_block_rotation() {
+        let tags = rotator(TagRotation::PerBlock);
+        let now = Instant::now();
+        assert_eq!(tags.tag_at(100, now), "/a/");
+        // Refreshing work on the same block keeps the tag
+        assert_eq!(tags.tag_at(100, now), "/a/");
+        assert_eq!(tags.tag_at(101, now), "/b/");
+        assert_eq!(tags.tag_at(102, now), "/c/");
+        assert_eq!(tags.tag_at(103, now), "/a/");
+        assert_eq!(tags.active_tag(), "/a/");
+    }
+
+    #[test]
+    fn test_every_n_blocks_rotation() {
+        let tags = rotator(TagRotation::EveryNBlocks { blocks: 2 });
+        let now = Instant::now();
+        let seen: Vec<_> = (200..206).map(|height| tags.tag_at(height, now)).collect();
+        assert_eq!(seen, ["/a/", "/a/", "/b/", "/b/", "/c/", "/c/"]);
+    }
+
+    #[test]
+    fn test_timed_rotation() {
+        let tags = rotator(TagRotation::Timed { interval_secs: 3600 });
+        let start = Instant::now();
+        assert_eq!(tags.tag_at(300, start), "/a/");
+        assert_eq!(tags.tag_at(301, start + Duration::from_secs(60)), "/a/");
+        assert_eq!(tags.tag_at(301, start + Duration::from_secs(3600)), "/b/");
+        assert_eq!(tags.tag_at(305, start + Duration::from_secs(3660)), "/b/");
+        assert_eq!(tags.tag_at(305, start + Duration::from_secs(7200)), "/c/");
+    }
+
+    #[test]
+    fn test_default_tag() {
+        let tags = CoinbaseTagRotator::default();
+        assert_eq!(tags.tag_for_height(1), DEFAULT_COINBASE_TAG);
+        assert_eq!(tags.tag_for_height(2), DEFAULT_COINBASE_TAG);
+    }
+
+    #[test]
+    fn test_tags_must_fit_script_sig() {
+        assert_eq!(max_tag_len(), 84);
+
+        let config = CoinbaseTagConfig {
+            tags: vec!["/ok/".to_string(), "x".repeat(max_tag_len())],
+            rotation: TagRotation::PerBlock,
+        };
+        assert!(config.validate().is_ok());
+
+        let config = CoinbaseTagConfig { tags: vec!["x".repeat(85)], ..config };
+        assert!(config.validate().is_err());
+
+        let config = CoinbaseTagConfig {
+            tags: vec!["/ok/".to_string()],
+            rotation: TagRotation::EveryNBlocks { blocks: 0 },
+        };
+        assert!(config.validate().is_err());
+    }
+}
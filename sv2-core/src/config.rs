@@ -1,5 +1,7 @@
 use crate::{Result, Error};
 use crate::mode::OperationMode;
+use crate::difficulty_unit::DifficultyUnit;
+use crate::connection_quality::ConnectionQualityConfig;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -16,6 +18,12 @@ pub struct DaemonConfig {
     pub monitoring: MonitoringConfig,
     pub logging: LoggingConfig,
     pub security: SecurityConfig,
+    /// Outgoing webhooks for found blocks and milestones
+    #[serde(default)]
+    pub webhooks: crate::webhooks::WebhookConfig,
+    /// Slower polling while no miners are connected
+    #[serde(default)]
+    pub idle: crate::idle::IdleConfig,
 }
 
 /// Operation mode with mode-specific configuration
@@ -35,6 +43,19 @@ pub struct SoloConfig {
     pub block_template_refresh_interval: u64,
     pub enable_custom_templates: bool,
     pub max_template_age: u64,
+    /// Where block templates come from (Bitcoin RPC unless configured)
+    #[serde(default)]
+    pub template_source: crate::template_source::TemplateSourceConfig,
+    /// Coinbase signature tags and how they rotate
+    #[serde(default)]
+    pub coinbase_tags: crate::coinbase_tag::CoinbaseTagConfig,
+    /// Confirmations before a found block counts as confirmed
+    #[serde(default = "default_block_confirmations")]
+    pub block_confirmations: u64,
+}
+
+fn default_block_confirmations() -> u64 {
+    6
 }
 
 /// Pool mode configuration
@@ -77,13 +98,37 @@ fn default_upstream_port() -> u16 {
 /// Client mode configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
-    pub upstream_pool: UpstreamPool,
+    /// Pools to mine on, tried by priority (lowest first) then weight
+    /// (highest first). A single `upstream_pool` table is still accepted.
+    #[serde(alias = "upstream_pool", deserialize_with = "one_or_many_pools")]
+    pub upstream_pools: Vec<UpstreamPool>,
     pub enable_job_negotiation: bool,
     pub custom_template_enabled: bool,
+    /// Seconds before the first reconnect attempt, doubling after each
+    /// failure up to five minutes
     pub reconnect_interval: u64,
+    /// Failed reconnects in a row before the client gives up
     pub max_reconnect_attempts: u32,
 }
 
+/// `upstream_pools` as one table or an array of them
+fn one_or_many_pools<'de, D>(deserializer: D) -> std::result::Result<Vec<UpstreamPool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(UpstreamPool),
+        Many(Vec<UpstreamPool>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(pool) => vec![pool],
+        OneOrMany::Many(pools) => pools,
+    })
+}
+
 /// Upstream pool configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpstreamPool {
@@ -108,8 +153,42 @@ pub enum LoadBalancingStrategy {
 pub struct NetworkConfig {
     pub bind_address: SocketAddr,
     pub max_connections: usize,
+    /// Connections the kernel queues before they're accepted, so a burst of
+    /// miners reconnecting after a network blip isn't refused
+    #[serde(default = "default_listen_backlog")]
+    pub listen_backlog: u32,
     pub connection_timeout: u64,
     pub keepalive_interval: u64,
+    /// Raw traffic capture for debugging
+    #[serde(default)]
+    pub capture: crate::capture::CaptureConfig,
+    /// Message shown to SV1 miners after they authorize
+    #[serde(default)]
+    pub welcome: crate::welcome::WelcomeConfig,
+    /// When repeated handshake failures from one IP raise an alert
+    #[serde(default)]
+    pub handshake_failures: crate::handshake_failures::HandshakeFailureConfig,
+    /// New connections accepted per source IP
+    #[serde(default)]
+    pub connection_rate_limit: crate::connection_rate::ConnectionRateLimitConfig,
+    /// Seconds open connections get on shutdown to finish the share they're
+    /// submitting before they're closed
+    #[serde(default = "default_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+}
+
+fn default_listen_backlog() -> u32 {
+    crate::server::DEFAULT_LISTEN_BACKLOG
+}
+
+fn default_drain_timeout_secs() -> u64 {
+    crate::server::DEFAULT_DRAIN_TIMEOUT.as_secs()
+}
+
+impl NetworkConfig {
+    pub fn drain_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.drain_timeout_secs)
+    }
 }
 
 /// Bitcoin node configuration
@@ -139,6 +218,35 @@ pub struct DatabaseConfig {
     pub max_connections: u32,
     pub connection_timeout: u64,
     pub enable_migrations: bool,
+    /// Shares that fail to store are appended here for `sv2-cli replay-shares`
+    #[serde(default = "default_dead_letter_path")]
+    pub dead_letter_path: PathBuf,
+    /// Store one in this many accepted shares; rejected shares and block
+    /// candidates are always stored
+    #[serde(default = "default_accepted_share_sample_rate")]
+    pub accepted_share_sample_rate: u64,
+    /// Shares older than this many days are pruned; 0 keeps them all
+    #[serde(default = "default_share_retention_days")]
+    pub share_retention_days: u32,
+}
+
+fn default_dead_letter_path() -> PathBuf {
+    PathBuf::from("sv2d_dead_letter.jsonl")
+}
+
+fn default_accepted_share_sample_rate() -> u64 {
+    1
+}
+
+fn default_share_retention_days() -> u32 {
+    30
+}
+
+impl DatabaseConfig {
+    /// How long shares are kept, or `None` if they're never pruned
+    pub fn share_retention(&self) -> Option<chrono::Duration> {
+        (self.share_retention_days > 0).then(|| chrono::Duration::days(self.share_retention_days as i64))
+    }
 }
 
 /// Monitoring configuration
@@ -150,6 +258,12 @@ pub struct MonitoringConfig {
     pub health_check_interval: u64,
     pub metrics: MetricsConfig,
     pub health: HealthConfig,
+    /// How status output and the dashboard show difficulty
+    #[serde(default)]
+    pub difficulty_unit: DifficultyUnit,
+    /// Weights and window for the per-connection quality score
+    #[serde(default)]
+    pub connection_quality: ConnectionQualityConfig,
 }
 
 /// Health monitoring configuration
@@ -195,6 +309,12 @@ pub struct MetricsConfig {
     pub system_monitoring: bool,
     /// Custom metrics labels
     pub labels: HashMap<String, String>,
+    /// Push metrics to an external StatsD/InfluxDB endpoint
+    #[serde(default)]
+    pub push: crate::metrics_push::MetricsPushConfig,
+    /// In-memory history served at `/api/v1/metrics/history`
+    #[serde(default)]
+    pub history: crate::metrics_history::MetricsHistoryConfig,
 }
 
 /// Logging configuration
@@ -216,6 +336,47 @@ pub struct LoggingConfig {
     pub max_file_size_mb: Option<u64>,
     /// Number of log files to retain
     pub max_files: Option<u32>,
+    /// Which share submissions get a log line
+    #[serde(default)]
+    pub shares: ShareLoggingConfig,
+}
+
+/// Share logging configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShareLoggingConfig {
+    #[serde(default)]
+    pub mode: ShareLogMode,
+    /// In `sampled` mode, log one in this many accepted shares
+    #[serde(default = "default_share_sample_rate")]
+    pub sample_rate: u64,
+}
+
+/// Share logging verbosity
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShareLogMode {
+    /// Never log individual shares
+    Off,
+    /// Log rejected shares only
+    #[default]
+    RejectedOnly,
+    /// Log every rejected share and one in `sample_rate` accepted shares
+    Sampled,
+    /// Log every share
+    All,
+}
+
+fn default_share_sample_rate() -> u64 {
+    100
+}
+
+impl Default for ShareLoggingConfig {
+    fn default() -> Self {
+        Self {
+            mode: ShareLogMode::default(),
+            sample_rate: default_share_sample_rate(),
+        }
+    }
 }
 
 /// Log format options
@@ -256,6 +417,8 @@ impl Default for DaemonConfig {
             monitoring: MonitoringConfig::default(),
             logging: LoggingConfig::default(),
             security: SecurityConfig::default(),
+            webhooks: crate::webhooks::WebhookConfig::default(),
+            idle: crate::idle::IdleConfig::default(),
         }
     }
 }
@@ -267,6 +430,9 @@ impl Default for SoloConfig {
             block_template_refresh_interval: 30,
             enable_custom_templates: false,
             max_template_age: 300,
+            template_source: Default::default(),
+            coinbase_tags: Default::default(),
+            block_confirmations: default_block_confirmations(),
         }
     }
 }
@@ -300,7 +466,7 @@ impl Default for ProxyConfig {
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
-            upstream_pool: UpstreamPool::default(),
+            upstream_pools: vec![UpstreamPool::default()],
             enable_job_negotiation: false,
             custom_template_enabled: false,
             reconnect_interval: 30,
@@ -326,8 +492,14 @@ impl Default for NetworkConfig {
         Self {
             bind_address: "127.0.0.1:3333".parse().unwrap(),
             max_connections: 1000,
+            listen_backlog: default_listen_backlog(),
             connection_timeout: 30,
             keepalive_interval: 60,
+            capture: crate::capture::CaptureConfig::default(),
+            welcome: crate::welcome::WelcomeConfig::default(),
+            handshake_failures: crate::handshake_failures::HandshakeFailureConfig::default(),
+            connection_rate_limit: crate::connection_rate::ConnectionRateLimitConfig::default(),
+            drain_timeout_secs: default_drain_timeout_secs(),
         }
     }
 }
@@ -352,6 +524,9 @@ impl Default for DatabaseConfig {
             max_connections: 10,
             connection_timeout: 30,
             enable_migrations: true,
+            dead_letter_path: default_dead_letter_path(),
+            accepted_share_sample_rate: default_accepted_share_sample_rate(),
+            share_retention_days: default_share_retention_days(),
         }
     }
 }
@@ -365,6 +540,8 @@ impl Default for MonitoringConfig {
             health_check_interval: 30,
             metrics: MetricsConfig::default(),
             health: HealthConfig::default(),
+            difficulty_unit: DifficultyUnit::default(),
+            connection_quality: ConnectionQualityConfig::default(),
         }
     }
 }
@@ -401,6 +578,8 @@ impl Default for MetricsConfig {
             prometheus_port: 9090,
             system_monitoring: true,
             labels: HashMap::new(),
+            push: crate::metrics_push::MetricsPushConfig::default(),
+            history: crate::metrics_history::MetricsHistoryConfig::default(),
         }
     }
 }
@@ -416,6 +595,7 @@ impl Default for LoggingConfig {
             redact_sensitive_data: true,
             max_file_size_mb: Some(100),
             max_files: Some(10),
+            shares: ShareLoggingConfig::default(),
         }
     }
 }
@@ -477,6 +657,9 @@ impl DaemonConfig {
         
         // Validate security configuration
         self.validate_security()?;
+
+        self.webhooks.validate()?;
+        self.idle.validate()?;
         
         // Mode-specific validation
         self.validate_mode()?;
@@ -552,6 +735,10 @@ impl DaemonConfig {
             return Err(Error::Config("max_connections must be greater than 0".to_string()));
         }
         
+        if self.network.listen_backlog == 0 {
+            return Err(Error::Config("listen_backlog must be greater than 0".to_string()));
+        }
+        
         if self.network.connection_timeout == 0 {
             return Err(Error::Config("connection_timeout must be greater than 0".to_string()));
         }
@@ -559,6 +746,9 @@ impl DaemonConfig {
         if self.network.keepalive_interval == 0 {
             return Err(Error::Config("keepalive_interval must be greater than 0".to_string()));
         }
+
+        self.network.welcome.validate()?;
+        self.network.handshake_failures.validate()?;
         
         Ok(())
     }
@@ -596,6 +786,10 @@ impl DaemonConfig {
             return Err(Error::Config("Database connection_timeout must be greater than 0".to_string()));
         }
         
+        if self.database.accepted_share_sample_rate == 0 {
+            return Err(Error::Config("database.accepted_share_sample_rate must be at least 1".to_string()));
+        }
+        
         Ok(())
     }
 
@@ -603,6 +797,10 @@ impl DaemonConfig {
         if self.monitoring.enable_health_checks && self.monitoring.health_check_interval == 0 {
             return Err(Error::Config("health_check_interval must be greater than 0 when health checks are enabled".to_string()));
         }
+
+        self.monitoring.metrics.push.validate()?;
+        self.monitoring.metrics.history.validate()?;
+        self.monitoring.connection_quality.validate()?;
         
         Ok(())
     }
@@ -613,6 +811,10 @@ impl DaemonConfig {
             return Err(Error::Config(format!("Invalid log level: {}. Must be one of: {:?}", self.logging.level, valid_levels)));
         }
         
+        if self.logging.shares.mode == ShareLogMode::Sampled && self.logging.shares.sample_rate == 0 {
+            return Err(Error::Config("logging.shares.sample_rate must be at least 1".to_string()));
+        }
+        
         Ok(())
     }
 
@@ -733,12 +935,17 @@ impl DaemonConfig {
     }
 
     fn validate_client_config(&self, config: &ClientConfig) -> Result<()> {
-        if config.upstream_pool.url.is_empty() {
-            return Err(Error::Config("Client mode requires upstream pool URL".to_string()));
+        if config.upstream_pools.is_empty() {
+            return Err(Error::Config("Client mode requires at least one upstream pool".to_string()));
         }
         
-        if config.upstream_pool.username.is_empty() {
-            return Err(Error::Config("Client mode requires upstream pool username".to_string()));
+        for (i, pool) in config.upstream_pools.iter().enumerate() {
+            if pool.url.is_empty() {
+                return Err(Error::Config(format!("Client mode requires upstream pool {} URL", i)));
+            }
+            if pool.username.is_empty() {
+                return Err(Error::Config(format!("Client mode requires upstream pool {} username", i)));
+            }
         }
         
         if config.reconnect_interval == 0 {
@@ -820,14 +1027,19 @@ impl DaemonConfig {
                 // For now, we'll handle this in configuration files
             }
             OperationModeConfig::Client(config) => {
+                // These override the primary pool; failover pools come from files
+                if config.upstream_pools.is_empty() {
+                    config.upstream_pools.push(UpstreamPool::default());
+                }
+                let primary = config.primary_pool_index();
                 if let Ok(upstream_url) = std::env::var("SV2D_UPSTREAM_URL") {
-                    config.upstream_pool.url = upstream_url;
+                    config.upstream_pools[primary].url = upstream_url;
                 }
                 if let Ok(upstream_user) = std::env::var("SV2D_UPSTREAM_USERNAME") {
-                    config.upstream_pool.username = upstream_user;
+                    config.upstream_pools[primary].username = upstream_user;
                 }
                 if let Ok(upstream_pass) = std::env::var("SV2D_UPSTREAM_PASSWORD") {
-                    config.upstream_pool.password = upstream_pass;
+                    config.upstream_pools[primary].password = upstream_pass;
                 }
             }
         }
@@ -989,9 +1201,15 @@ impl OperationModeConfig {
             OperationModeConfig::Proxy(config) => {
                 format!("Proxy mode with {} upstream pools", config.upstream_pools.len())
             }
-            OperationModeConfig::Client(config) => {
-                format!("Client mode connecting to: {}", config.upstream_pool.url)
-            }
+            OperationModeConfig::Client(config) => match config.pools_by_priority().first() {
+                Some(primary) if config.upstream_pools.len() > 1 => format!(
+                    "Client mode connecting to: {} ({} failover pools)",
+                    primary.url,
+                    config.upstream_pools.len() - 1
+                ),
+                Some(primary) => format!("Client mode connecting to: {}", primary.url),
+                None => "Client mode without upstream pools".to_string(),
+            },
         }
     }
 }
@@ -1080,6 +1298,19 @@ impl SoloConfig {
             return Err(Error::Config("max_template_age must be greater than 0".to_string()));
         }
         
+        if self.template_source == crate::template_source::TemplateSourceConfig::Upstream {
+            return Err(Error::Config("Solo mode has no upstream pool; use bitcoin_rpc or sv2_tp as template_source".to_string()));
+        }
+        self.template_source.validate()?;
+        self.coinbase_tags.validate()?;
+
+        if self.block_confirmations == 0 || self.block_confirmations > crate::blocks::SETTLED_DEPTH {
+            return Err(Error::Config(format!(
+                "block_confirmations must be between 1 and {}",
+                crate::blocks::SETTLED_DEPTH
+            )));
+        }
+        
         Ok(())
     }
 }
@@ -1161,7 +1392,13 @@ impl ProxyConfig {
 impl ClientConfig {
     /// Validate client configuration
     pub fn validate(&self) -> Result<()> {
-        self.upstream_pool.validate()?;
+        if self.upstream_pools.is_empty() {
+            return Err(Error::Config("Client mode requires at least one upstream pool".to_string()));
+        }
+        
+        for (i, pool) in self.upstream_pools.iter().enumerate() {
+            pool.validate().map_err(|e| Error::Config(format!("Upstream pool {}: {}", i, e)))?;
+        }
         
         if self.reconnect_interval == 0 {
             return Err(Error::Config("reconnect_interval must be greater than 0".to_string()));
@@ -1173,6 +1410,20 @@ impl ClientConfig {
         
         Ok(())
     }
+    
+    /// Pools in failover order: by priority, then the heavier of equal priority first
+    pub fn pools_by_priority(&self) -> Vec<&UpstreamPool> {
+        let mut pools: Vec<&UpstreamPool> = self.upstream_pools.iter().collect();
+        pools.sort_by_key(|pool| (pool.priority, std::cmp::Reverse(pool.weight)));
+        pools
+    }
+    
+    /// Index of the pool tried first
+    fn primary_pool_index(&self) -> usize {
+        (0..self.upstream_pools.len())
+            .min_by_key(|&i| (self.upstream_pools[i].priority, std::cmp::Reverse(self.upstream_pools[i].weight)))
+            .unwrap_or(0)
+    }
 }
 
 impl std::str::FromStr for OperationModeConfig {
@@ -1491,6 +1742,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_client_pools_accept_single_table_and_failover_order() {
+        let single: ClientConfig = toml::from_str(r#"
+            enable_job_negotiation = false
+            custom_template_enabled = false
+            reconnect_interval = 30
+            max_reconnect_attempts = 5
+
+            [upstream_pool]
+            url = "stratum+tcp://pool.example.com:4444"
+            username = "worker"
+            password = "x"
+            priority = 1
+            weight = 1
+        "#).unwrap();
+        assert_eq!(single.upstream_pools.len(), 1);
+
+        let client = ClientConfig {
+            upstream_pools: vec![
+                UpstreamPool { url: "stratum+tcp://backup.example.com:4444".to_string(), priority: 2, weight: 1, ..UpstreamPool::default() },
+                UpstreamPool { url: "stratum+tcp://light.example.com:4444".to_string(), priority: 1, weight: 1, ..UpstreamPool::default() },
+                UpstreamPool { url: "stratum+tcp://heavy.example.com:4444".to_string(), priority: 1, weight: 3, ..UpstreamPool::default() },
+            ],
+            ..ClientConfig::default()
+        };
+        let order: Vec<&str> = client.pools_by_priority().iter().map(|pool| pool.url.as_str()).collect();
+        assert_eq!(order, vec![
+            "stratum+tcp://heavy.example.com:4444",
+            "stratum+tcp://light.example.com:4444",
+            "stratum+tcp://backup.example.com:4444",
+        ]);
+        assert_eq!(client.primary_pool_index(), 2);
+
+        let empty = ClientConfig { upstream_pools: vec![], ..ClientConfig::default() };
+        assert!(empty.validate().is_err());
+    }
+
     #[test]
     fn test_load_from_sources() {
         let dir = tempdir().unwrap();
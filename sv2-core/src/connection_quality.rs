@@ -0,0 +1,286 @@
+//! Per-miner connection quality score
+//!
+//! Folds three signals over a rolling window into one 0-100 number so flaky
+//! miners stand out in connection lists:
+//!
+//! - **acceptance**: accepted shares over submitted shares
+//! - **stability**: how evenly shares arrive. The window is cut into
+//!   `bucket_secs` buckets and shares counted per bucket. Share arrivals are
+//!   Poisson, so counts vary by about √mean even for a perfectly steady miner;
+//!   only variance above that counts against it.
+//! - **reconnects**: new connections in the window, scaled against
+//!   `max_reconnects_per_hour` (at or above it scores 0)
+//!
+//! The score is the weighted mean of the three, times 100. A component with
+//! no data in the window (no shares yet) scores full marks rather than
+//! penalising a miner that just connected. Miners are told apart by their
+//! first authorized worker, or by IP address before they authorize, so
+//! reconnects count against the miner even though each gets a new connection.
+
+use crate::database::DatabaseOps;
+use crate::types::ConnectionInfo;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// `[monitoring.connection_quality]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionQualityConfig {
+    /// Rolling window the score looks at, in seconds
+    pub window_secs: u64,
+    /// Bucket size for share-rate stability, in seconds
+    pub bucket_secs: u64,
+    pub acceptance_weight: f64,
+    pub stability_weight: f64,
+    pub reconnect_weight: f64,
+    /// Reconnect rate that scores 0 on the reconnect component
+    pub max_reconnects_per_hour: f64,
+}
+
+impl Default for ConnectionQualityConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            bucket_secs: 60,
+            acceptance_weight: 0.5,
+            stability_weight: 0.3,
+            reconnect_weight: 0.2,
+            max_reconnects_per_hour: 6.0,
+        }
+    }
+}
+
+/// Score and the components it was built from, each 0.0 to 1.0
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionQuality {
+    /// 0 (unusable) to 100 (steady, no rejects, no reconnects)
+    pub score: u8,
+    pub acceptance: f64,
+    pub stability: f64,
+    pub reconnect: f64,
+    /// Reconnects seen in the window
+    pub reconnects: u32,
+}
+
+/// What a miner did, across all of its connections
+#[derive(Debug, Clone, Default)]
+pub struct MinerHistory {
+    /// Submission time and whether the share was accepted
+    pub shares: Vec<(DateTime<Utc>, bool)>,
+    /// When each of its connections was opened
+    pub connects: Vec<DateTime<Utc>>,
+}
+
+impl ConnectionQualityConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.window_secs == 0 {
+            return Err(Error::Config("connection_quality.window_secs must be greater than 0".to_string()));
+        }
+        if self.bucket_secs == 0 || self.bucket_secs > self.window_secs {
+            return Err(Error::Config(format!(
+                "connection_quality.bucket_secs must be between 1 and window_secs ({}), got {}",
+                self.window_secs, self.bucket_secs
+            )));
+        }
+        let weights = [self.acceptance_weight, self.stability_weight, self.reconnect_weight];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+            return Err(Error::Config(
+                "connection_quality weights must be non-negative and not all 0".to_string(),
+            ));
+        }
+        if !self.max_reconnects_per_hour.is_finite() || self.max_reconnects_per_hour <= 0.0 {
+            return Err(Error::Config(
+                "connection_quality.max_reconnects_per_hour must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.window_secs as i64)
+    }
+
+    /// Score `history` over the window ending at `now`
+    pub fn score(&self, history: &MinerHistory, now: DateTime<Utc>) -> ConnectionQuality {
+        let window_start = now - self.window();
+        let shares: Vec<_> = history.shares.iter().filter(|(at, _)| *at >= window_start && *at <= now).collect();
+
+        let acceptance = if shares.is_empty() {
+            1.0
+        } else {
+            shares.iter().filter(|(_, accepted)| *accepted).count() as f64 / shares.len() as f64
+        };
+
+        // The first connection a miner ever makes isn't a reconnect
+        let first_connect = history.connects.iter().min();
+        let reconnects = history
+            .connects
+            .iter()
+            .filter(|at| **at >= window_start && Some(*at) != first_connect)
+            .count() as u32;
+        let per_hour = reconnects as f64 * 3600.0 / self.window_secs as f64;
+        let reconnect = (1.0 - per_hour / self.max_reconnects_per_hour).clamp(0.0, 1.0);
+
+        // Only measure stability over the part of the window the miner was around for
+        let observed_from = history
+            .connects
+            .iter()
+            .filter(|at| **at >= window_start)
+            .chain(shares.iter().map(|(at, _)| at))
+            .min()
+            .map_or(window_start, |first| (*first).max(window_start));
+        let stability = self.stability(&shares, observed_from, now);
+
+        let weighted = self.acceptance_weight * acceptance
+            + self.stability_weight * stability
+            + self.reconnect_weight * reconnect;
+        let total_weight = self.acceptance_weight + self.stability_weight + self.reconnect_weight;
+        let score = (weighted / total_weight * 100.0).round().clamp(0.0, 100.0) as u8;
+
+        ConnectionQuality { score, acceptance, stability, reconnect, reconnects }
+    }
+
+    fn stability(&self, shares: &[&(DateTime<Utc>, bool)], from: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+        let span = (now - from).num_seconds().max(0) as u64;
+        let buckets = span.div_ceil(self.bucket_secs) as usize;
+        if shares.is_empty() || buckets < 2 {
+            return 1.0;
+        }
+
+        let mut counts = vec![0u64; buckets];
+        for (at, _) in shares {
+            let index = ((*at - from).num_seconds().max(0) as u64 / self.bucket_secs) as usize;
+            counts[index.min(buckets - 1)] += 1;
+        }
+
+        let mean = counts.iter().sum::<u64>() as f64 / buckets as f64;
+        let variance = counts.iter().map(|c| (*c as f64 - mean).powi(2)).sum::<f64>() / buckets as f64;
+        let excess = (variance - mean).max(0.0);
+        (1.0 - excess.sqrt() / mean).clamp(0.0, 1.0)
+    }
+}
+
+/// Identifies a miner across reconnects
+fn miner_key(connection: &ConnectionInfo) -> String {
+    match connection.authorized_workers.first() {
+        Some(worker) => worker.clone(),
+        None => connection.address.ip().to_string(),
+    }
+}
+
+/// Fill in `quality` on each of `connections` from stored connections and shares
+pub async fn annotate(
+    database: &dyn DatabaseOps,
+    config: &ConnectionQualityConfig,
+    connections: &mut [ConnectionInfo],
+) -> Result<()> {
+    let now = Utc::now();
+    let window_start = now - config.window();
+
+    let mut histories: HashMap<String, MinerHistory> = HashMap::new();
+    for known in database.list_connections(None).await? {
+        let history = histories.entry(miner_key(&known)).or_default();
+        history.connects.push(known.connected_at);
+        if known.last_activity >= window_start {
+            let shares = database.get_shares(Some(known.id), None).await?;
+            history.shares.extend(shares.iter().map(|share| (share.submitted_at, share.is_valid)));
+        }
+    }
+
+    for connection in connections.iter_mut() {
+        let quality = match histories.get(&miner_key(connection)) {
+            Some(history) => config.score(history, now),
+            None => config.score(&MinerHistory { shares: Vec::new(), connects: vec![connection.connected_at] }, now),
+        };
+        connection.quality = Some(quality);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 4, 20, 12, 0, 0).unwrap()
+    }
+
+    fn secs_ago(secs: i64) -> DateTime<Utc> {
+        now() - chrono::Duration::seconds(secs)
+    }
+
+    #[test]
+    fn test_stable_miner_scores_high() {
+        // Connected yesterday, a share every 12s for the last hour, all accepted
+        let history = MinerHistory {
+            shares: (0..300).map(|i| (secs_ago(3600 - i * 12), true)).collect(),
+            connects: vec![secs_ago(86_400)],
+        };
+        let quality = ConnectionQualityConfig::default().score(&history, now());
+        assert_eq!(quality.reconnects, 0);
+        assert_eq!(quality.acceptance, 1.0);
+        assert!(quality.stability > 0.95, "{:?}", quality);
+        assert!(quality.score >= 95, "{:?}", quality);
+    }
+
+    #[test]
+    fn test_occasional_rejects_cost_a_little() {
+        let history = MinerHistory {
+            shares: (0..300).map(|i| (secs_ago(3600 - i * 12), i % 10 != 0)).collect(),
+            connects: vec![secs_ago(86_400)],
+        };
+        let quality = ConnectionQualityConfig::default().score(&history, now());
+        assert!((85..=95).contains(&quality.score), "{:?}", quality);
+    }
+
+    #[test]
+    fn test_flaky_miner_scores_low() {
+        // Drops off every other five minutes and reconnects, a third of shares rejected
+        let mut history = MinerHistory { shares: Vec::new(), connects: vec![secs_ago(86_400)] };
+        for stretch in 0..12 {
+            let start = 3600 - stretch * 300;
+            if stretch % 2 == 0 {
+                history.connects.push(secs_ago(start));
+                for i in 0..50 {
+                    history.shares.push((secs_ago(start - i * 6), i % 3 != 0));
+                }
+            }
+        }
+        let quality = ConnectionQualityConfig::default().score(&history, now());
+        assert_eq!(quality.reconnects, 6);
+        assert_eq!(quality.reconnect, 0.0);
+        assert!(quality.stability < 0.3, "{:?}", quality);
+        assert!(quality.score < 45, "{:?}", quality);
+    }
+
+    #[test]
+    fn test_new_miner_without_shares_is_not_penalised() {
+        let history = MinerHistory { shares: Vec::new(), connects: vec![secs_ago(30)] };
+        let quality = ConnectionQualityConfig::default().score(&history, now());
+        assert_eq!(quality.reconnects, 0);
+        assert_eq!(quality.score, 100);
+    }
+
+    #[test]
+    fn test_weights_are_configurable() {
+        let history = MinerHistory {
+            shares: (0..300).map(|i| (secs_ago(3600 - i * 12), i % 2 == 0)).collect(),
+            connects: vec![secs_ago(86_400)],
+        };
+        let acceptance_only = ConnectionQualityConfig {
+            stability_weight: 0.0,
+            reconnect_weight: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(acceptance_only.score(&history, now()).score, 50);
+
+        ConnectionQualityConfig::default().validate().unwrap();
+        assert!(ConnectionQualityConfig { acceptance_weight: 0.0, ..acceptance_only }.validate().is_err());
+        assert!(ConnectionQualityConfig { stability_weight: -1.0, ..Default::default() }.validate().is_err());
+        assert!(ConnectionQualityConfig { bucket_secs: 7200, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Per-IP connection rate limiting
+//!
+//! Each source IP gets a token bucket holding `connections_per_minute`
+//! tokens that refills at that rate. Accepting a connection takes a token;
+//! an IP with none left has its connection closed straight after accept, so
+//! one host reconnecting in a loop can't tie up the accept loop or the
+//! connection tasks. A whole fleet restarting at once stays within the
+//! limit, since each miner has its own IP. Loopback is exempt by default
+//! because sv2d's own translator connects from there. Buckets that have
+//! refilled completely are swept, so IPs seen once aren't kept forever.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often full buckets are swept
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `[network.connection_rate_limit]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionRateLimitConfig {
+    /// New connections one IP may open per minute, in bursts of up to as
+    /// many; 0 disables the limit
+    #[serde(default = "default_connections_per_minute")]
+    pub connections_per_minute: u32,
+    #[serde(default = "default_exempt_loopback")]
+    pub exempt_loopback: bool,
+}
+
+fn default_connections_per_minute() -> u32 {
+    60
+}
+
+fn default_exempt_loopback() -> bool {
+    true
+}
+
+impl Default for ConnectionRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            connections_per_minute: default_connections_per_minute(),
+            exempt_loopback: default_exempt_loopback(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_source: HashMap<IpAddr, Bucket>,
+    last_sweep: Instant,
+}
+
+/// Decides whether a new connection from an IP is accepted
+#[derive(Debug)]
+pub struct ConnectionRateLimiter {
+    config: ConnectionRateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+impl Default for ConnectionRateLimiter {
+    fn default() -> Self {
+        Self::new(ConnectionRateLimitConfig::default())
+    }
+}
+
+impl ConnectionRateLimiter {
+    pub fn new(config: ConnectionRateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets { by_source: HashMap::new(), last_sweep: Instant::now() }),
+        }
+    }
+
+    pub fn config(&self) -> &ConnectionRateLimitConfig {
+        &self.config
+    }
+
+    /// Take a token for a connection from `address`, or `false` if it has
+    /// used up its connections for now
+    pub fn allow(&self, address: IpAddr) -> bool {
+        self.allow_at(address, Instant::now())
+    }
+
+    fn allow_at(&self, address: IpAddr, now: Instant) -> bool {
+        if self.config.connections_per_minute == 0 || (self.config.exempt_loopback && address.is_loopback()) {
+            return true;
+        }
+        let capacity = self.config.connections_per_minute as f64;
+        let per_sec = capacity / 60.0;
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            (bucket.tokens + elapsed * per_sec).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.saturating_duration_since(buckets.last_sweep) >= SWEEP_INTERVAL {
+            // A full bucket is the same as no bucket
+            buckets.by_source.retain(|_, bucket| refill(bucket) < capacity);
+            buckets.last_sweep = now;
+        }
+
+        let bucket = buckets
+            .by_source
+            .entry(address)
+            .or_insert(Bucket { tokens: capacity, updated: now });
+        bucket.tokens = refill(bucket);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// IPs with a bucket still refilling
+    pub fn tracked_sources(&self) -> usize {
+        self.buckets.lock().unwrap().by_source.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(connections_per_minute: u32) -> ConnectionRateLimiter {
+        ConnectionRateLimiter::new(ConnectionRateLimitConfig { connections_per_minute, exempt_loopback: true })
+    }
+
+    #[test]
+    fn test_burst_then_refill() {
+        let limiter = limiter(6);
+        let miner: IpAddr = "192.168.1.20".parse().unwrap();
+        let start = Instant::now();
+
+        assert!((0..6).all(|_| limiter.allow_at(miner, start)));
+        assert!(!limiter.allow_at(miner, start));
+        // Other IPs have their own bucket
+        assert!(limiter.allow_at("192.168.1.21".parse().unwrap(), start));
+
+        // One token every 10 seconds
+        assert!(!limiter.allow_at(miner, start + Duration::from_secs(9)));
+        assert!(limiter.allow_at(miner, start + Duration::from_secs(11)));
+        assert!(!limiter.allow_at(miner, start + Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn test_loopback_exempt_unless_configured() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let start = Instant::now();
+        let exempt = limiter(1);
+        assert!((0..10).all(|_| exempt.allow_at(loopback, start)));
+        assert!(exempt.allow_at("::1".parse().unwrap(), start));
+        assert_eq!(exempt.tracked_sources(), 0);
+
+        let strict = ConnectionRateLimiter::new(ConnectionRateLimitConfig {
+            connections_per_minute: 1,
+            exempt_loopback: false,
+        });
+        assert!(strict.allow_at(loopback, start));
+        assert!(!strict.allow_at(loopback, start));
+
+        assert!((0..10).all(|_| limiter(0).allow_at("10.0.0.1".parse().unwrap(), start)));
+    }
+
+    #[test]
+    fn test_idle_buckets_expire() {
+        let limiter = limiter(60);
+        let start = Instant::now();
+        let idle: IpAddr = "10.0.0.1".parse().unwrap();
+        let busy: IpAddr = "10.0.0.2".parse().unwrap();
+        limiter.allow_at(idle, start);
+        for _ in 0..60 {
+            limiter.allow_at(busy, start + Duration::from_secs(30));
+        }
+        assert_eq!(limiter.tracked_sources(), 2);
+
+        // By the sweep the idle IP's bucket is full again and the busy one's
+        // is half full
+        limiter.allow_at("10.0.0.3".parse().unwrap(), start + SWEEP_INTERVAL);
+        assert_eq!(limiter.tracked_sources(), 2);
+        assert!(!limiter.buckets.lock().unwrap().by_source.contains_key(&idle));
+    }
+}
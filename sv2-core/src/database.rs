@@ -1,4 +1,7 @@
-use crate::{Result, Error, ConnectionInfo, Share, WorkTemplate, PerformanceMetrics};
+use crate::{Result, Error, ConnectionInfo, Share, WorkTemplate, PerformanceMetrics, WorkerRecord};
+use crate::milestones::{Milestone, MilestoneRecord};
+use crate::blocks::{BlockRecord, BlockStatus};
+use crate::worker_bans::WorkerBan;
 use crate::types::Alert;
 use crate::recovery::{DatabaseRecovery, RecoveryConfig};
 use sqlx::{Pool, Sqlite, Postgres, Row};
@@ -25,6 +28,11 @@ pub trait DatabaseOps: Send + Sync {
     async fn create_share(&self, share: &Share) -> Result<()>;
     async fn get_shares(&self, connection_id: Option<Uuid>, limit: Option<u32>) -> Result<Vec<Share>>;
     async fn get_share_stats(&self, connection_id: Option<Uuid>) -> Result<ShareStats>;
+    /// Delete shares submitted before `cutoff`, returning how many were deleted
+    async fn prune_shares_older_than(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64>;
+    /// Accepted and rejected shares per `bucket_seconds` over the last
+    /// `window`, oldest first, with zero counts for buckets without shares
+    async fn get_share_timeseries(&self, bucket_seconds: u32, window: chrono::Duration) -> Result<Vec<ShareBucket>>;
     
     async fn create_work_template(&self, template: &WorkTemplate) -> Result<()>;
     async fn get_work_template(&self, id: Uuid) -> Result<Option<WorkTemplate>>;
@@ -41,9 +49,29 @@ pub trait DatabaseOps: Send + Sync {
     async fn store_config_history(&self, config_data: &str, applied_by: &str) -> Result<()>;
     async fn get_config_history(&self, limit: Option<u32>) -> Result<Vec<ConfigHistoryEntry>>;
     
+    // Worker identities persisted across reconnects
+    async fn get_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<Option<WorkerRecord>>;
+    async fn store_worker_record(&self, record: &WorkerRecord) -> Result<()>;
+    
+    // One-time milestones; recording returns false if it was already reached
+    async fn record_milestone(&self, record: &MilestoneRecord) -> Result<bool>;
+    async fn get_milestones(&self) -> Result<Vec<MilestoneRecord>>;
+    
+    // Found blocks and their confirmation status, newest first
+    async fn store_block(&self, record: &BlockRecord) -> Result<()>;
+    async fn get_blocks(&self, limit: Option<u32>) -> Result<Vec<BlockRecord>>;
+    
+    // Banned worker names; deleting returns false if it wasn't banned
+    async fn store_worker_ban(&self, ban: &WorkerBan) -> Result<()>;
+    async fn delete_worker_ban(&self, worker_name: &str) -> Result<bool>;
+    async fn get_worker_bans(&self) -> Result<Vec<WorkerBan>>;
+    
     // Additional methods needed by solo mode handler
     async fn store_connection(&self, conn: &crate::Connection) -> Result<()>;
     async fn store_share(&self, share: &Share) -> Result<()>;
+    /// Store an accepted share that stands for `weight` accepted shares,
+    /// itself included, when the others were sampled out
+    async fn store_sampled_share(&self, share: &Share, weight: u64) -> Result<()>;
     async fn store_work_template(&self, template: &WorkTemplate) -> Result<()>;
     async fn update_connection_status(&self, connection_id: Uuid, status: crate::types::ConnectionState) -> Result<()>;
     
@@ -54,6 +82,7 @@ pub trait DatabaseOps: Send + Sync {
 }
 
 /// Share statistics
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ShareStats {
     pub total_shares: u64,
@@ -65,6 +94,58 @@ pub struct ShareStats {
     pub last_share: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Shares submitted during one bucket of a share time series
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ShareBucket {
+    /// Start of the bucket, a multiple of the bucket length since the epoch
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+/// Most buckets one share time series may have
+pub const MAX_SHARE_BUCKETS: i64 = 10_000;
+
+/// Zero buckets covering `window` up to and including `now`, oldest first
+fn empty_share_buckets(
+    bucket_seconds: u32,
+    window: chrono::Duration,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<ShareBucket>> {
+    let bucket = bucket_seconds as i64;
+    if bucket == 0 {
+        return Err(Error::Config("Share buckets must be at least one second long".to_string()));
+    }
+    let count = (window.num_seconds() + bucket - 1) / bucket;
+    if !(1..=MAX_SHARE_BUCKETS).contains(&count) {
+        return Err(Error::Config(format!(
+            "A share time series has between 1 and {} buckets, not {}", MAX_SHARE_BUCKETS, count
+        )));
+    }
+    let last = now.timestamp().div_euclid(bucket) * bucket;
+    (0..count)
+        .map(|i| {
+            let start = chrono::DateTime::from_timestamp(last - (count - 1 - i) * bucket, 0)
+                .ok_or_else(|| Error::Config("Share time series is out of range".to_string()))?;
+            Ok(ShareBucket { start, accepted: 0, rejected: 0 })
+        })
+        .collect()
+}
+
+/// Add counts to the bucket starting at `start` (Unix seconds), if the
+/// series has one
+fn add_to_share_bucket(buckets: &mut [ShareBucket], bucket_seconds: u32, start: i64, accepted: u64, rejected: u64) {
+    let Some(first) = buckets.first() else {
+        return;
+    };
+    let index = (start - first.start.timestamp()).div_euclid(bucket_seconds as i64);
+    if let Some(bucket) = usize::try_from(index).ok().and_then(|index| buckets.get_mut(index)) {
+        bucket.accepted += accepted;
+        bucket.rejected += rejected;
+    }
+}
+
 /// Configuration history entry
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ConfigHistoryEntry {
@@ -114,6 +195,14 @@ impl DatabasePool {
         Ok(())
     }
 
+    /// Close the pool, waiting for checked-out connections to be returned
+    pub async fn close(&self) {
+        match self {
+            DatabasePool::Sqlite(pool) => pool.close().await,
+            DatabasePool::Postgres(pool) => pool.close().await,
+        }
+    }
+
     /// Check if database is healthy
     pub async fn health_check(&self) -> Result<()> {
         match self {
@@ -166,6 +255,60 @@ impl DatabasePool {
             }
         }
     }
+
+    /// Share time series ending with the bucket `now` falls in
+    async fn share_timeseries_at(
+        &self,
+        bucket_seconds: u32,
+        window: chrono::Duration,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ShareBucket>> {
+        let mut buckets = empty_share_buckets(bucket_seconds, window, now)?;
+        let since = buckets[0].start;
+        let rows = match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    r#"
+                    SELECT
+                        (CAST(strftime('%s', submitted_at) AS INTEGER) / ?) * ? as bucket,
+                        COALESCE(SUM(CASE WHEN is_valid = 1 THEN weight ELSE 0 END), 0) as accepted,
+                        SUM(CASE WHEN is_valid = 0 THEN 1 ELSE 0 END) as rejected
+                    FROM shares WHERE submitted_at >= ?
+                    GROUP BY bucket
+                    "#
+                )
+                .bind(bucket_seconds as i64)
+                .bind(bucket_seconds as i64)
+                .bind(since)
+                .fetch_all(pool).await?
+                .into_iter()
+                .map(|row| (row.get::<i64, _>("bucket"), row.get::<i64, _>("accepted"), row.get::<i64, _>("rejected")))
+                .collect::<Vec<_>>()
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"
+                    SELECT
+                        (FLOOR(EXTRACT(EPOCH FROM submitted_at))::BIGINT / $1) * $1 as bucket,
+                        COALESCE(SUM(CASE WHEN is_valid = true THEN weight ELSE 0 END), 0)::BIGINT as accepted,
+                        SUM(CASE WHEN is_valid = false THEN 1 ELSE 0 END) as rejected
+                    FROM shares WHERE submitted_at >= $2
+                    GROUP BY bucket
+                    "#
+                )
+                .bind(bucket_seconds as i64)
+                .bind(since)
+                .fetch_all(pool).await?
+                .into_iter()
+                .map(|row| (row.get::<i64, _>("bucket"), row.get::<i64, _>("accepted"), row.get::<i64, _>("rejected")))
+                .collect::<Vec<_>>()
+            }
+        };
+        for (start, accepted, rejected) in rows {
+            add_to_share_bucket(&mut buckets, bucket_seconds, start, accepted as u64, rejected as u64);
+        }
+        Ok(buckets)
+    }
 }
 
 #[async_trait::async_trait]
@@ -177,9 +320,9 @@ impl DatabaseOps for DatabasePool {
                     r#"
                     INSERT INTO connections (
                         id, address, protocol, state, connected_at, last_activity,
-                        user_agent, version, subscribed_difficulty, extranonce1, extranonce2_size,
+                        user_agent, version, flags, subscribed_difficulty, extranonce1, extranonce2_size,
                         total_shares, valid_shares, invalid_shares, blocks_found
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     "#
                 )
                 .bind(conn_info.id.to_string())
@@ -190,6 +333,7 @@ impl DatabaseOps for DatabasePool {
                 .bind(conn_info.last_activity)
                 .bind(&conn_info.user_agent)
                 .bind(&conn_info.version)
+                .bind(conn_info.flags.map(|f| f as i64))
                 .bind(conn_info.subscribed_difficulty)
                 .bind(&conn_info.extranonce1)
                 .bind(conn_info.extranonce2_size.map(|s| s as i32))
@@ -204,9 +348,9 @@ impl DatabaseOps for DatabasePool {
                     r#"
                     INSERT INTO connections (
                         id, address, protocol, state, connected_at, last_activity,
-                        user_agent, version, subscribed_difficulty, extranonce1, extranonce2_size,
+                        user_agent, version, flags, subscribed_difficulty, extranonce1, extranonce2_size,
                         total_shares, valid_shares, invalid_shares, blocks_found
-                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
                     "#
                 )
                 .bind(conn_info.id)
@@ -217,6 +361,7 @@ impl DatabaseOps for DatabasePool {
                 .bind(conn_info.last_activity)
                 .bind(&conn_info.user_agent)
                 .bind(&conn_info.version)
+                .bind(conn_info.flags.map(|f| f as i64))
                 .bind(conn_info.subscribed_difficulty)
                 .bind(&conn_info.extranonce1)
                 .bind(conn_info.extranonce2_size.map(|s| s as i16))
@@ -237,7 +382,7 @@ impl DatabaseOps for DatabasePool {
                     r#"
                     UPDATE connections SET
                         address = ?, protocol = ?, state = ?, last_activity = ?,
-                        user_agent = ?, version = ?, subscribed_difficulty = ?,
+                        user_agent = ?, version = ?, flags = ?, subscribed_difficulty = ?,
                         extranonce1 = ?, extranonce2_size = ?, total_shares = ?,
                         valid_shares = ?, invalid_shares = ?, blocks_found = ?
                     WHERE id = ?
@@ -249,6 +394,7 @@ impl DatabaseOps for DatabasePool {
                 .bind(conn_info.last_activity)
                 .bind(&conn_info.user_agent)
                 .bind(&conn_info.version)
+                .bind(conn_info.flags.map(|f| f as i64))
                 .bind(conn_info.subscribed_difficulty)
                 .bind(&conn_info.extranonce1)
                 .bind(conn_info.extranonce2_size.map(|s| s as i32))
@@ -264,10 +410,10 @@ impl DatabaseOps for DatabasePool {
                     r#"
                     UPDATE connections SET
                         address = $1, protocol = $2, state = $3, last_activity = $4,
-                        user_agent = $5, version = $6, subscribed_difficulty = $7,
-                        extranonce1 = $8, extranonce2_size = $9, total_shares = $10,
-                        valid_shares = $11, invalid_shares = $12, blocks_found = $13
-                    WHERE id = $14
+                        user_agent = $5, version = $6, flags = $7, subscribed_difficulty = $8,
+                        extranonce1 = $9, extranonce2_size = $10, total_shares = $11,
+                        valid_shares = $12, invalid_shares = $13, blocks_found = $14
+                    WHERE id = $15
                     "#
                 )
                 .bind(conn_info.address.to_string())
@@ -276,6 +422,7 @@ impl DatabaseOps for DatabasePool {
                 .bind(conn_info.last_activity)
                 .bind(&conn_info.user_agent)
                 .bind(&conn_info.version)
+                .bind(conn_info.flags.map(|f| f as i64))
                 .bind(conn_info.subscribed_difficulty)
                 .bind(&conn_info.extranonce1)
                 .bind(conn_info.extranonce2_size.map(|s| s as i16))
@@ -313,6 +460,7 @@ impl DatabaseOps for DatabasePool {
                         last_activity: row.get("last_activity"),
                         user_agent: row.get("user_agent"),
                         version: row.get("version"),
+                        flags: row.get::<Option<i64>, _>("flags").map(|f| f as u32),
                         subscribed_difficulty: row.get("subscribed_difficulty"),
                         extranonce1: row.get("extranonce1"),
                         extranonce2_size: row.get::<Option<i32>, _>("extranonce2_size").map(|s| s as u8),
@@ -321,6 +469,7 @@ impl DatabaseOps for DatabasePool {
                         valid_shares: row.get::<i64, _>("valid_shares") as u64,
                         invalid_shares: row.get::<i64, _>("invalid_shares") as u64,
                         blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                        quality: None,
                     }))
                 } else {
                     Ok(None)
@@ -347,6 +496,7 @@ impl DatabaseOps for DatabasePool {
                         last_activity: row.get("last_activity"),
                         user_agent: row.get("user_agent"),
                         version: row.get("version"),
+                        flags: row.get::<Option<i64>, _>("flags").map(|f| f as u32),
                         subscribed_difficulty: row.get("subscribed_difficulty"),
                         extranonce1: row.get("extranonce1"),
                         extranonce2_size: row.get::<Option<i16>, _>("extranonce2_size").map(|s| s as u8),
@@ -355,6 +505,7 @@ impl DatabaseOps for DatabasePool {
                         valid_shares: row.get::<i64, _>("valid_shares") as u64,
                         invalid_shares: row.get::<i64, _>("invalid_shares") as u64,
                         blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                        quality: None,
                     }))
                 } else {
                     Ok(None)
@@ -387,6 +538,7 @@ impl DatabaseOps for DatabasePool {
                             last_activity: row.get("last_activity"),
                             user_agent: row.get("user_agent"),
                             version: row.get("version"),
+                            flags: row.get::<Option<i64>, _>("flags").map(|f| f as u32),
                             subscribed_difficulty: row.get("subscribed_difficulty"),
                             extranonce1: row.get("extranonce1"),
                             extranonce2_size: row.get::<Option<i32>, _>("extranonce2_size").map(|s| s as u8),
@@ -395,6 +547,7 @@ impl DatabaseOps for DatabasePool {
                             valid_shares: row.get::<i64, _>("valid_shares") as u64,
                             invalid_shares: row.get::<i64, _>("invalid_shares") as u64,
                             blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                            quality: None,
                         });
                     }
                 }
@@ -420,6 +573,7 @@ impl DatabaseOps for DatabasePool {
                             last_activity: row.get("last_activity"),
                             user_agent: row.get("user_agent"),
                             version: row.get("version"),
+                            flags: row.get::<Option<i64>, _>("flags").map(|f| f as u32),
                             subscribed_difficulty: row.get("subscribed_difficulty"),
                             extranonce1: row.get("extranonce1"),
                             extranonce2_size: row.get::<Option<i16>, _>("extranonce2_size").map(|s| s as u8),
@@ -428,6 +582,7 @@ impl DatabaseOps for DatabasePool {
                             valid_shares: row.get::<i64, _>("valid_shares") as u64,
                             invalid_shares: row.get::<i64, _>("invalid_shares") as u64,
                             blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                            quality: None,
                         });
                     }
                 }
@@ -453,41 +608,7 @@ impl DatabaseOps for DatabasePool {
     }
 
     async fn create_share(&self, share: &Share) -> Result<()> {
-        match self {
-            DatabasePool::Sqlite(pool) => {
-                sqlx::query(
-                    r#"
-                    INSERT INTO shares (connection_id, nonce, timestamp, difficulty, is_valid, block_hash, submitted_at)
-                    VALUES (?, ?, ?, ?, ?, ?, ?)
-                    "#
-                )
-                .bind(share.connection_id.to_string())
-                .bind(share.nonce as i64)
-                .bind(share.timestamp as i64)
-                .bind(share.difficulty)
-                .bind(share.is_valid)
-                .bind(share.block_hash.map(|h| h.to_string()))
-                .bind(share.submitted_at)
-                .execute(pool).await?;
-            }
-            DatabasePool::Postgres(pool) => {
-                sqlx::query(
-                    r#"
-                    INSERT INTO shares (connection_id, nonce, timestamp, difficulty, is_valid, block_hash, submitted_at)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7)
-                    "#
-                )
-                .bind(share.connection_id)
-                .bind(share.nonce as i64)
-                .bind(share.timestamp as i64)
-                .bind(share.difficulty)
-                .bind(share.is_valid)
-                .bind(share.block_hash.map(|h| h.to_string()))
-                .bind(share.submitted_at)
-                .execute(pool).await?;
-            }
-        }
-        Ok(())
+        self.store_sampled_share(share, 1).await
     }
 
     async fn get_shares(&self, connection_id: Option<Uuid>, limit: Option<u32>) -> Result<Vec<Share>> {
@@ -564,8 +685,8 @@ impl DatabaseOps for DatabasePool {
                     (
                         r#"
                         SELECT 
-                            COUNT(*) as total_shares,
-                            SUM(CASE WHEN is_valid = 1 THEN 1 ELSE 0 END) as valid_shares,
+                            COALESCE(SUM(weight), 0) as total_shares,
+                            COALESCE(SUM(CASE WHEN is_valid = 1 THEN weight ELSE 0 END), 0) as valid_shares,
                             SUM(CASE WHEN is_valid = 0 THEN 1 ELSE 0 END) as invalid_shares,
                             SUM(CASE WHEN block_hash IS NOT NULL THEN 1 ELSE 0 END) as blocks_found,
                             MIN(submitted_at) as first_share,
@@ -578,8 +699,8 @@ impl DatabaseOps for DatabasePool {
                     (
                         r#"
                         SELECT 
-                            COUNT(*) as total_shares,
-                            SUM(CASE WHEN is_valid = 1 THEN 1 ELSE 0 END) as valid_shares,
+                            COALESCE(SUM(weight), 0) as total_shares,
+                            COALESCE(SUM(CASE WHEN is_valid = 1 THEN weight ELSE 0 END), 0) as valid_shares,
                             SUM(CASE WHEN is_valid = 0 THEN 1 ELSE 0 END) as invalid_shares,
                             SUM(CASE WHEN block_hash IS NOT NULL THEN 1 ELSE 0 END) as blocks_found,
                             MIN(submitted_at) as first_share,
@@ -623,8 +744,8 @@ impl DatabaseOps for DatabasePool {
                     (
                         r#"
                         SELECT 
-                            COUNT(*) as total_shares,
-                            SUM(CASE WHEN is_valid = true THEN 1 ELSE 0 END) as valid_shares,
+                            COALESCE(SUM(weight), 0)::BIGINT as total_shares,
+                            COALESCE(SUM(CASE WHEN is_valid = true THEN weight ELSE 0 END), 0)::BIGINT as valid_shares,
                             SUM(CASE WHEN is_valid = false THEN 1 ELSE 0 END) as invalid_shares,
                             SUM(CASE WHEN block_hash IS NOT NULL THEN 1 ELSE 0 END) as blocks_found,
                             MIN(submitted_at) as first_share,
@@ -637,8 +758,8 @@ impl DatabaseOps for DatabasePool {
                     (
                         r#"
                         SELECT 
-                            COUNT(*) as total_shares,
-                            SUM(CASE WHEN is_valid = true THEN 1 ELSE 0 END) as valid_shares,
+                            COALESCE(SUM(weight), 0)::BIGINT as total_shares,
+                            COALESCE(SUM(CASE WHEN is_valid = true THEN weight ELSE 0 END), 0)::BIGINT as valid_shares,
                             SUM(CASE WHEN is_valid = false THEN 1 ELSE 0 END) as invalid_shares,
                             SUM(CASE WHEN block_hash IS NOT NULL THEN 1 ELSE 0 END) as blocks_found,
                             MIN(submitted_at) as first_share,
@@ -680,6 +801,27 @@ impl DatabaseOps for DatabasePool {
         }
     }
 
+    async fn prune_shares_older_than(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                let result = sqlx::query("DELETE FROM shares WHERE submitted_at < ?")
+                    .bind(cutoff)
+                    .execute(pool).await?;
+                Ok(result.rows_affected())
+            }
+            DatabasePool::Postgres(pool) => {
+                let result = sqlx::query("DELETE FROM shares WHERE submitted_at < $1")
+                    .bind(cutoff)
+                    .execute(pool).await?;
+                Ok(result.rows_affected())
+            }
+        }
+    }
+
+    async fn get_share_timeseries(&self, bucket_seconds: u32, window: chrono::Duration) -> Result<Vec<ShareBucket>> {
+        self.share_timeseries_at(bucket_seconds, window, chrono::Utc::now()).await
+    }
+
     async fn create_work_template(&self, template: &WorkTemplate) -> Result<()> {
         let coinbase_bytes = bitcoin::consensus::encode::serialize(&template.coinbase_tx);
         let transactions_bytes = bitcoin::consensus::encode::serialize(&template.transactions);
@@ -740,6 +882,10 @@ impl DatabaseOps for DatabasePool {
                         difficulty: row.get("difficulty"),
                         timestamp: row.get::<i64, _>("timestamp") as u32,
                         expires_at: row.get("expires_at"),
+                        ntime_bounds: None,
+                        fees: None,
+                        version: None,
+                        merkle_path: Vec::new(),
                     }))
                 } else {
                     Ok(None)
@@ -762,6 +908,10 @@ impl DatabaseOps for DatabasePool {
                         difficulty: row.get("difficulty"),
                         timestamp: row.get::<i64, _>("timestamp") as u32,
                         expires_at: row.get("expires_at"),
+                        ntime_bounds: None,
+                        fees: None,
+                        version: None,
+                        merkle_path: Vec::new(),
                     }))
                 } else {
                     Ok(None)
@@ -791,6 +941,10 @@ impl DatabaseOps for DatabasePool {
                         difficulty: row.get("difficulty"),
                         timestamp: row.get::<i64, _>("timestamp") as u32,
                         expires_at: row.get("expires_at"),
+                        ntime_bounds: None,
+                        fees: None,
+                        version: None,
+                        merkle_path: Vec::new(),
                     });
                 }
                 Ok(templates)
@@ -812,6 +966,10 @@ impl DatabaseOps for DatabasePool {
                         difficulty: row.get("difficulty"),
                         timestamp: row.get::<i64, _>("timestamp") as u32,
                         expires_at: row.get("expires_at"),
+                        ntime_bounds: None,
+                        fees: None,
+                        version: None,
+                        merkle_path: Vec::new(),
                     });
                 }
                 Ok(templates)
@@ -1148,6 +1306,329 @@ impl DatabaseOps for DatabasePool {
         }
     }
     
+    async fn get_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<Option<WorkerRecord>> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                let row = sqlx::query(
+                    "SELECT * FROM worker_records WHERE worker_name = ? AND source_ip = ?"
+                )
+                .bind(worker_name)
+                .bind(source_ip)
+                .fetch_optional(pool).await?;
+
+                if let Some(row) = row {
+                    Ok(Some(WorkerRecord {
+                        id: Uuid::parse_str(&row.get::<String, _>("id"))?,
+                        worker_name: row.get("worker_name"),
+                        source_ip: row.get("source_ip"),
+                        first_seen: row.get("first_seen"),
+                        last_seen: row.get("last_seen"),
+                        sessions: row.get::<i64, _>("sessions") as u64,
+                        total_shares: row.get::<i64, _>("total_shares") as u64,
+                        valid_shares: row.get::<i64, _>("valid_shares") as u64,
+                        blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                    }))
+                } else {
+                    Ok(None)
+                }
+            }
+            DatabasePool::Postgres(pool) => {
+                let row = sqlx::query(
+                    "SELECT * FROM worker_records WHERE worker_name = $1 AND source_ip = $2"
+                )
+                .bind(worker_name)
+                .bind(source_ip)
+                .fetch_optional(pool).await?;
+
+                if let Some(row) = row {
+                    Ok(Some(WorkerRecord {
+                        id: row.get("id"),
+                        worker_name: row.get("worker_name"),
+                        source_ip: row.get("source_ip"),
+                        first_seen: row.get("first_seen"),
+                        last_seen: row.get("last_seen"),
+                        sessions: row.get::<i64, _>("sessions") as u64,
+                        total_shares: row.get::<i64, _>("total_shares") as u64,
+                        valid_shares: row.get::<i64, _>("valid_shares") as u64,
+                        blocks_found: row.get::<i64, _>("blocks_found") as u64,
+                    }))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    async fn store_worker_record(&self, record: &WorkerRecord) -> Result<()> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO worker_records (
+                        id, worker_name, source_ip, first_seen, last_seen,
+                        sessions, total_shares, valid_shares, blocks_found
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT (worker_name, source_ip) DO UPDATE SET
+                        last_seen = excluded.last_seen, sessions = excluded.sessions,
+                        total_shares = excluded.total_shares, valid_shares = excluded.valid_shares,
+                        blocks_found = excluded.blocks_found
+                    "#
+                )
+                .bind(record.id.to_string())
+                .bind(&record.worker_name)
+                .bind(&record.source_ip)
+                .bind(record.first_seen)
+                .bind(record.last_seen)
+                .bind(record.sessions as i64)
+                .bind(record.total_shares as i64)
+                .bind(record.valid_shares as i64)
+                .bind(record.blocks_found as i64)
+                .execute(pool).await?;
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO worker_records (
+                        id, worker_name, source_ip, first_seen, last_seen,
+                        sessions, total_shares, valid_shares, blocks_found
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    ON CONFLICT (worker_name, source_ip) DO UPDATE SET
+                        last_seen = EXCLUDED.last_seen, sessions = EXCLUDED.sessions,
+                        total_shares = EXCLUDED.total_shares, valid_shares = EXCLUDED.valid_shares,
+                        blocks_found = EXCLUDED.blocks_found
+                    "#
+                )
+                .bind(record.id)
+                .bind(&record.worker_name)
+                .bind(&record.source_ip)
+                .bind(record.first_seen)
+                .bind(record.last_seen)
+                .bind(record.sessions as i64)
+                .bind(record.total_shares as i64)
+                .bind(record.valid_shares as i64)
+                .bind(record.blocks_found as i64)
+                .execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
+    
+    async fn record_milestone(&self, record: &MilestoneRecord) -> Result<bool> {
+        let result = match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    "INSERT INTO milestones (name, reached_at, detail) VALUES (?, ?, ?) ON CONFLICT (name) DO NOTHING"
+                )
+                .bind(record.milestone.key())
+                .bind(record.reached_at)
+                .bind(&record.detail)
+                .execute(pool).await?
+                .rows_affected()
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    "INSERT INTO milestones (name, reached_at, detail) VALUES ($1, $2, $3) ON CONFLICT (name) DO NOTHING"
+                )
+                .bind(record.milestone.key())
+                .bind(record.reached_at)
+                .bind(&record.detail)
+                .execute(pool).await?
+                .rows_affected()
+            }
+        };
+        Ok(result == 1)
+    }
+    
+    async fn get_milestones(&self) -> Result<Vec<MilestoneRecord>> {
+        let query = "SELECT * FROM milestones ORDER BY reached_at";
+        let records = match self {
+            DatabasePool::Sqlite(pool) => sqlx::query(query).fetch_all(pool).await?
+                .into_iter()
+                .filter_map(|row| Some(MilestoneRecord {
+                    milestone: Milestone::from_key(&row.get::<String, _>("name"))?,
+                    reached_at: row.get("reached_at"),
+                    detail: row.get("detail"),
+                }))
+                .collect(),
+            DatabasePool::Postgres(pool) => sqlx::query(query).fetch_all(pool).await?
+                .into_iter()
+                .filter_map(|row| Some(MilestoneRecord {
+                    milestone: Milestone::from_key(&row.get::<String, _>("name"))?,
+                    reached_at: row.get("reached_at"),
+                    detail: row.get("detail"),
+                }))
+                .collect(),
+        };
+        Ok(records)
+    }
+    
+    async fn store_block(&self, record: &BlockRecord) -> Result<()> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO blocks (hash, height, connection_id, worker, reward, fees, status, confirmations, alert_id, found_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT (hash) DO UPDATE SET
+                        status = excluded.status,
+                        confirmations = excluded.confirmations,
+                        alert_id = excluded.alert_id,
+                        updated_at = excluded.updated_at
+                    "#
+                )
+                .bind(record.hash.to_string())
+                .bind(record.height.map(|h| h as i64))
+                .bind(record.connection_id.map(|id| id.to_string()))
+                .bind(&record.worker)
+                .bind(record.reward.map(|sats| sats as i64))
+                .bind(record.fees.map(|sats| sats as i64))
+                .bind(record.status.key())
+                .bind(record.confirmations as i64)
+                .bind(record.alert_id.map(|id| id.to_string()))
+                .bind(record.found_at)
+                .bind(record.updated_at)
+                .execute(pool).await?;
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO blocks (hash, height, connection_id, worker, reward, fees, status, confirmations, alert_id, found_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    ON CONFLICT (hash) DO UPDATE SET
+                        status = excluded.status,
+                        confirmations = excluded.confirmations,
+                        alert_id = excluded.alert_id,
+                        updated_at = excluded.updated_at
+                    "#
+                )
+                .bind(record.hash.to_string())
+                .bind(record.height.map(|h| h as i64))
+                .bind(record.connection_id)
+                .bind(&record.worker)
+                .bind(record.reward.map(|sats| sats as i64))
+                .bind(record.fees.map(|sats| sats as i64))
+                .bind(record.status.key())
+                .bind(record.confirmations as i64)
+                .bind(record.alert_id)
+                .bind(record.found_at)
+                .bind(record.updated_at)
+                .execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
+    
+    async fn get_blocks(&self, limit: Option<u32>) -> Result<Vec<BlockRecord>> {
+        let limit_clause = limit.map(|l| format!("LIMIT {}", l)).unwrap_or_default();
+        let query = format!("SELECT * FROM blocks ORDER BY found_at DESC {}", limit_clause);
+        let records = match self {
+            DatabasePool::Sqlite(pool) => sqlx::query(&query).fetch_all(pool).await?
+                .into_iter()
+                .filter_map(|row| Some(BlockRecord {
+                    hash: row.get::<String, _>("hash").parse().ok()?,
+                    height: row.get::<Option<i64>, _>("height").map(|h| h as u64),
+                    connection_id: row.get::<Option<String>, _>("connection_id").and_then(|id| Uuid::parse_str(&id).ok()),
+                    worker: row.get("worker"),
+                    reward: row.get::<Option<i64>, _>("reward").map(|sats| sats as u64),
+                    fees: row.get::<Option<i64>, _>("fees").map(|sats| sats as u64),
+                    status: BlockStatus::from_key(&row.get::<String, _>("status"))?,
+                    confirmations: row.get::<i64, _>("confirmations") as u64,
+                    alert_id: row.get::<Option<String>, _>("alert_id").and_then(|id| Uuid::parse_str(&id).ok()),
+                    found_at: row.get("found_at"),
+                    updated_at: row.get("updated_at"),
+                }))
+                .collect(),
+            DatabasePool::Postgres(pool) => sqlx::query(&query).fetch_all(pool).await?
+                .into_iter()
+                .filter_map(|row| Some(BlockRecord {
+                    hash: row.get::<String, _>("hash").parse().ok()?,
+                    height: row.get::<Option<i64>, _>("height").map(|h| h as u64),
+                    connection_id: row.get("connection_id"),
+                    worker: row.get("worker"),
+                    reward: row.get::<Option<i64>, _>("reward").map(|sats| sats as u64),
+                    fees: row.get::<Option<i64>, _>("fees").map(|sats| sats as u64),
+                    status: BlockStatus::from_key(&row.get::<String, _>("status"))?,
+                    confirmations: row.get::<i64, _>("confirmations") as u64,
+                    alert_id: row.get("alert_id"),
+                    found_at: row.get("found_at"),
+                    updated_at: row.get("updated_at"),
+                }))
+                .collect(),
+        };
+        Ok(records)
+    }
+    
+    async fn store_worker_ban(&self, ban: &WorkerBan) -> Result<()> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO worker_bans (worker_name, reason, banned_at) VALUES (?, ?, ?)
+                    ON CONFLICT (worker_name) DO UPDATE SET reason = excluded.reason, banned_at = excluded.banned_at
+                    "#
+                )
+                .bind(&ban.worker_name)
+                .bind(&ban.reason)
+                .bind(ban.banned_at)
+                .execute(pool).await?;
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO worker_bans (worker_name, reason, banned_at) VALUES ($1, $2, $3)
+                    ON CONFLICT (worker_name) DO UPDATE SET reason = excluded.reason, banned_at = excluded.banned_at
+                    "#
+                )
+                .bind(&ban.worker_name)
+                .bind(&ban.reason)
+                .bind(ban.banned_at)
+                .execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
+    
+    async fn delete_worker_ban(&self, worker_name: &str) -> Result<bool> {
+        let deleted = match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query("DELETE FROM worker_bans WHERE worker_name = ?")
+                    .bind(worker_name)
+                    .execute(pool).await?
+                    .rows_affected()
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query("DELETE FROM worker_bans WHERE worker_name = $1")
+                    .bind(worker_name)
+                    .execute(pool).await?
+                    .rows_affected()
+            }
+        };
+        Ok(deleted > 0)
+    }
+    
+    async fn get_worker_bans(&self) -> Result<Vec<WorkerBan>> {
+        let query = "SELECT * FROM worker_bans ORDER BY worker_name";
+        let bans = match self {
+            DatabasePool::Sqlite(pool) => sqlx::query(query).fetch_all(pool).await?
+                .into_iter()
+                .map(|row| WorkerBan {
+                    worker_name: row.get("worker_name"),
+                    reason: row.get("reason"),
+                    banned_at: row.get("banned_at"),
+                })
+                .collect(),
+            DatabasePool::Postgres(pool) => sqlx::query(query).fetch_all(pool).await?
+                .into_iter()
+                .map(|row| WorkerBan {
+                    worker_name: row.get("worker_name"),
+                    reason: row.get("reason"),
+                    banned_at: row.get("banned_at"),
+                })
+                .collect(),
+        };
+        Ok(bans)
+    }
+    
     // Additional methods needed by solo mode handler
     async fn store_connection(&self, conn: &crate::Connection) -> Result<()> {
         let conn_info = ConnectionInfo::from_connection(conn);
@@ -1157,6 +1638,46 @@ impl DatabaseOps for DatabasePool {
     async fn store_share(&self, share: &Share) -> Result<()> {
         self.create_share(share).await
     }
+
+    async fn store_sampled_share(&self, share: &Share, weight: u64) -> Result<()> {
+        match self {
+            DatabasePool::Sqlite(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO shares (connection_id, nonce, timestamp, difficulty, is_valid, block_hash, submitted_at, weight)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(share.connection_id.to_string())
+                .bind(share.nonce as i64)
+                .bind(share.timestamp as i64)
+                .bind(share.difficulty)
+                .bind(share.is_valid)
+                .bind(share.block_hash.map(|h| h.to_string()))
+                .bind(share.submitted_at)
+                .bind(weight as i64)
+                .execute(pool).await?;
+            }
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO shares (connection_id, nonce, timestamp, difficulty, is_valid, block_hash, submitted_at, weight)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    "#
+                )
+                .bind(share.connection_id)
+                .bind(share.nonce as i64)
+                .bind(share.timestamp as i64)
+                .bind(share.difficulty)
+                .bind(share.is_valid)
+                .bind(share.block_hash.map(|h| h.to_string()))
+                .bind(share.submitted_at)
+                .bind(weight as i64)
+                .execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
     
     async fn store_work_template(&self, template: &WorkTemplate) -> Result<()> {
         self.create_work_template(template).await
@@ -1200,7 +1721,15 @@ impl DatabaseOps for DatabasePool {
 pub struct MockDatabaseOps {
     connections: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<Uuid, ConnectionInfo>>>,
     shares: std::sync::Arc<tokio::sync::RwLock<Vec<Share>>>,
+    /// Accepted shares stood for by stored rows beyond the rows themselves
+    sampled_out: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<Uuid, u64>>>,
     templates: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<Uuid, WorkTemplate>>>,
+    worker_records: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<(String, String), WorkerRecord>>>,
+    alerts: std::sync::Arc<tokio::sync::RwLock<Vec<Alert>>>,
+    milestones: std::sync::Arc<tokio::sync::RwLock<Vec<MilestoneRecord>>>,
+    blocks: std::sync::Arc<tokio::sync::RwLock<Vec<BlockRecord>>>,
+    worker_bans: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<String, WorkerBan>>>,
+    fail_share_writes: std::sync::Arc<std::sync::atomic::AtomicBool>,
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -1209,9 +1738,21 @@ impl MockDatabaseOps {
         Self {
             connections: std::sync::Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             shares: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            sampled_out: std::sync::Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             templates: std::sync::Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            worker_records: std::sync::Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            alerts: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            milestones: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            blocks: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            worker_bans: std::sync::Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            fail_share_writes: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
         }
     }
+
+    /// Make share writes fail, as they would with the database down
+    pub fn fail_share_writes(&self, fail: bool) {
+        self.fail_share_writes.store(fail, std::sync::atomic::Ordering::SeqCst);
+    }
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -1250,6 +1791,9 @@ impl DatabaseOps for MockDatabaseOps {
     }
 
     async fn create_share(&self, share: &Share) -> Result<()> {
+        if self.fail_share_writes.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(Error::Database(sqlx::Error::PoolTimedOut));
+        }
         let mut shares = self.shares.write().await;
         shares.push(share.clone());
         Ok(())
@@ -1276,8 +1820,15 @@ impl DatabaseOps for MockDatabaseOps {
             shares.iter().collect()
         };
 
-        let total_shares = filtered_shares.len() as u64;
-        let valid_shares = filtered_shares.iter().filter(|s| s.is_valid).count() as u64;
+        let sampled_out = self.sampled_out.read().await;
+        let sampled_out: u64 = if let Some(conn_id) = connection_id {
+            sampled_out.get(&conn_id).copied().unwrap_or(0)
+        } else {
+            sampled_out.values().sum()
+        };
+
+        let total_shares = filtered_shares.len() as u64 + sampled_out;
+        let valid_shares = filtered_shares.iter().filter(|s| s.is_valid).count() as u64 + sampled_out;
         let invalid_shares = total_shares - valid_shares;
         let blocks_found = filtered_shares.iter().filter(|s| s.block_hash.is_some()).count() as u64;
         let acceptance_rate = if total_shares > 0 {
@@ -1300,6 +1851,24 @@ impl DatabaseOps for MockDatabaseOps {
         })
     }
 
+    async fn prune_shares_older_than(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64> {
+        let mut shares = self.shares.write().await;
+        let initial_count = shares.len();
+        shares.retain(|share| share.submitted_at >= cutoff);
+        Ok((initial_count - shares.len()) as u64)
+    }
+
+    async fn get_share_timeseries(&self, bucket_seconds: u32, window: chrono::Duration) -> Result<Vec<ShareBucket>> {
+        let mut buckets = empty_share_buckets(bucket_seconds, window, chrono::Utc::now())?;
+        let bucket = bucket_seconds as i64;
+        for share in self.shares.read().await.iter() {
+            let start = share.submitted_at.timestamp().div_euclid(bucket) * bucket;
+            let (accepted, rejected) = if share.is_valid { (1, 0) } else { (0, 1) };
+            add_to_share_bucket(&mut buckets, bucket_seconds, start, accepted, rejected);
+        }
+        Ok(buckets)
+    }
+
     async fn create_work_template(&self, template: &WorkTemplate) -> Result<()> {
         let mut templates = self.templates.write().await;
         templates.insert(template.id, template.clone());
@@ -1328,16 +1897,31 @@ impl DatabaseOps for MockDatabaseOps {
         Ok((initial_count - templates.len()) as u64)
     }
 
-    async fn create_alert(&self, _alert: &Alert) -> Result<()> {
+    async fn create_alert(&self, alert: &Alert) -> Result<()> {
+        let mut alerts = self.alerts.write().await;
+        alerts.push(alert.clone());
         Ok(())
     }
 
-    async fn update_alert(&self, _alert: &Alert) -> Result<()> {
+    async fn update_alert(&self, alert: &Alert) -> Result<()> {
+        let mut alerts = self.alerts.write().await;
+        if let Some(existing) = alerts.iter_mut().find(|a| a.id == alert.id) {
+            *existing = alert.clone();
+        }
         Ok(())
     }
 
-    async fn get_alerts(&self, _resolved: Option<bool>, _limit: Option<u32>) -> Result<Vec<Alert>> {
-        Ok(Vec::new())
+    async fn get_alerts(&self, resolved: Option<bool>, limit: Option<u32>) -> Result<Vec<Alert>> {
+        let alerts = self.alerts.read().await;
+        let mut result: Vec<_> = alerts
+            .iter()
+            .filter(|a| resolved.map_or(true, |resolved| a.acknowledged == resolved))
+            .cloned()
+            .collect();
+        if let Some(limit) = limit {
+            result.truncate(limit as usize);
+        }
+        Ok(result)
     }
 
     async fn store_performance_metrics(&self, _metrics: &PerformanceMetrics) -> Result<()> {
@@ -1356,6 +1940,63 @@ impl DatabaseOps for MockDatabaseOps {
         Ok(Vec::new())
     }
 
+    async fn get_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<Option<WorkerRecord>> {
+        let records = self.worker_records.read().await;
+        Ok(records.get(&(worker_name.to_string(), source_ip.to_string())).cloned())
+    }
+
+    async fn store_worker_record(&self, record: &WorkerRecord) -> Result<()> {
+        let mut records = self.worker_records.write().await;
+        records.insert((record.worker_name.clone(), record.source_ip.clone()), record.clone());
+        Ok(())
+    }
+
+    async fn record_milestone(&self, record: &MilestoneRecord) -> Result<bool> {
+        let mut milestones = self.milestones.write().await;
+        if milestones.iter().any(|m| m.milestone == record.milestone) {
+            return Ok(false);
+        }
+        milestones.push(record.clone());
+        Ok(true)
+    }
+
+    async fn get_milestones(&self) -> Result<Vec<MilestoneRecord>> {
+        Ok(self.milestones.read().await.clone())
+    }
+
+    async fn store_block(&self, record: &BlockRecord) -> Result<()> {
+        let mut blocks = self.blocks.write().await;
+        match blocks.iter_mut().find(|b| b.hash == record.hash) {
+            Some(existing) => *existing = record.clone(),
+            None => blocks.push(record.clone()),
+        }
+        Ok(())
+    }
+
+    async fn get_blocks(&self, limit: Option<u32>) -> Result<Vec<BlockRecord>> {
+        let mut blocks = self.blocks.read().await.clone();
+        blocks.sort_by(|a, b| b.found_at.cmp(&a.found_at));
+        if let Some(limit) = limit {
+            blocks.truncate(limit as usize);
+        }
+        Ok(blocks)
+    }
+
+    async fn store_worker_ban(&self, ban: &WorkerBan) -> Result<()> {
+        self.worker_bans.write().await.insert(ban.worker_name.clone(), ban.clone());
+        Ok(())
+    }
+
+    async fn delete_worker_ban(&self, worker_name: &str) -> Result<bool> {
+        Ok(self.worker_bans.write().await.remove(worker_name).is_some())
+    }
+
+    async fn get_worker_bans(&self) -> Result<Vec<WorkerBan>> {
+        let mut bans: Vec<WorkerBan> = self.worker_bans.read().await.values().cloned().collect();
+        bans.sort_by(|a, b| a.worker_name.cmp(&b.worker_name));
+        Ok(bans)
+    }
+
     async fn store_connection(&self, conn: &crate::Connection) -> Result<()> {
         let conn_info = ConnectionInfo::from_connection(conn);
         self.create_connection(&conn_info).await
@@ -1365,6 +2006,12 @@ impl DatabaseOps for MockDatabaseOps {
         self.create_share(share).await
     }
 
+    async fn store_sampled_share(&self, share: &Share, weight: u64) -> Result<()> {
+        self.create_share(share).await?;
+        *self.sampled_out.write().await.entry(share.connection_id).or_insert(0) += weight.saturating_sub(1);
+        Ok(())
+    }
+
     async fn store_work_template(&self, template: &WorkTemplate) -> Result<()> {
         self.create_work_template(template).await
     }
@@ -1425,6 +2072,47 @@ mod tests {
         assert_eq!(stats.total_shares, 0);
     }
     
+    #[tokio::test]
+    async fn test_blocks_round_trip() {
+        use bitcoin::hashes::Hash;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.display());
+        let pool = DatabasePool::new(&db_url, 5).await.unwrap();
+        pool.migrate().await.unwrap();
+
+        let coinbase = bitcoin::Transaction {
+            version: 1,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![bitcoin::TxOut { value: 312_500_000, ..Default::default() }],
+        };
+        let template = WorkTemplate::new(bitcoin::BlockHash::all_zeros(), coinbase, vec![], 1.0);
+        let older = BlockRecord {
+            worker: Some("rig-a".to_string()),
+            connection_id: Some(Uuid::new_v4()),
+            found_at: chrono::Utc::now() - chrono::Duration::hours(1),
+            ..BlockRecord::submitted(bitcoin::BlockHash::from_byte_array([1; 32]), &template)
+        };
+        let newer = BlockRecord::submitted(bitcoin::BlockHash::from_byte_array([2; 32]), &template);
+        pool.store_block(&older).await.unwrap();
+        pool.store_block(&newer).await.unwrap();
+
+        // Storing again updates the status in place
+        let mut confirmed = older.clone();
+        confirmed.observe(Some(6), 6);
+        pool.store_block(&confirmed).await.unwrap();
+
+        let blocks = pool.get_blocks(None).await.unwrap();
+        assert_eq!(blocks.iter().map(|b| b.hash).collect::<Vec<_>>(), vec![newer.hash, older.hash]);
+        assert_eq!((blocks[1].status, blocks[1].confirmations), (BlockStatus::Confirmed, 6));
+        assert_eq!(blocks[1].worker.as_deref(), Some("rig-a"));
+        assert_eq!(blocks[1].connection_id, older.connection_id);
+        assert_eq!(blocks[1].reward, Some(312_500_000));
+        assert_eq!(pool.get_blocks(Some(1)).await.unwrap().len(), 1);
+    }
+    
     #[tokio::test]
     async fn test_database_ops_trait() {
         let dir = tempdir().unwrap();
@@ -1444,6 +2132,81 @@ mod tests {
         let stats = pool.get_share_stats(None).await.unwrap();
         assert_eq!(stats.total_shares, 0);
     }
+
+    #[tokio::test]
+    async fn test_prune_shares_older_than() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.display());
+        let pool = DatabasePool::new(&db_url, 5).await.unwrap();
+        pool.migrate().await.unwrap();
+
+        let connection_id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+        for (nonce, age_days) in [(1, 45), (2, 31), (3, 29), (4, 0)] {
+            let mut share = Share::new(connection_id, nonce, 1_700_000_000, 1.0);
+            share.is_valid = nonce != 2;
+            share.submitted_at = now - chrono::Duration::days(age_days);
+            pool.create_share(&share).await.unwrap();
+        }
+
+        let pruned = pool.prune_shares_older_than(now - chrono::Duration::days(30)).await.unwrap();
+        assert_eq!(pruned, 2);
+
+        let mut nonces: Vec<u32> = pool.get_shares(None, None).await.unwrap().iter().map(|s| s.nonce).collect();
+        nonces.sort();
+        assert_eq!(nonces, vec![3, 4]);
+
+        let stats = pool.get_share_stats(Some(connection_id)).await.unwrap();
+        assert_eq!(stats.total_shares, 2);
+        assert_eq!(stats.invalid_shares, 0);
+        assert!(stats.first_share.unwrap() > now - chrono::Duration::days(30));
+
+        // Nothing left to prune
+        assert_eq!(pool.prune_shares_older_than(now - chrono::Duration::days(30)).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_share_timeseries() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.display());
+        let pool = DatabasePool::new(&db_url, 5).await.unwrap();
+        pool.migrate().await.unwrap();
+
+        // 10:04:30, so the last minute bucket starts at 10:04
+        let now = chrono::DateTime::parse_from_rfc3339("2025-03-01T10:04:30Z").unwrap().with_timezone(&chrono::Utc);
+        let connection_id = Uuid::new_v4();
+        for (nonce, seconds_ago, is_valid) in [
+            (1, 400, true), // before the window
+            (2, 260, true),
+            (3, 250, false),
+            (4, 200, true),
+            (5, 10, true),
+            (6, 0, false),
+        ] {
+            let mut share = Share::new(connection_id, nonce, 1_700_000_000, 1.0);
+            share.is_valid = is_valid;
+            share.submitted_at = now - chrono::Duration::seconds(seconds_ago);
+            pool.create_share(&share).await.unwrap();
+        }
+        let mut sampled = Share::new(connection_id, 7, 1_700_000_000, 1.0);
+        sampled.submitted_at = now - chrono::Duration::seconds(200);
+        pool.store_sampled_share(&sampled, 5).await.unwrap();
+
+        let buckets = pool.share_timeseries_at(60, chrono::Duration::seconds(300), now).await.unwrap();
+        let counts: Vec<_> = buckets.iter().map(|b| (b.start.format("%H:%M").to_string(), b.accepted, b.rejected)).collect();
+        assert_eq!(counts, [
+            ("10:00".to_string(), 1, 1),
+            ("10:01".to_string(), 6, 0),
+            ("10:02".to_string(), 0, 0),
+            ("10:03".to_string(), 0, 0),
+            ("10:04".to_string(), 1, 1),
+        ]);
+
+        assert!(pool.share_timeseries_at(0, chrono::Duration::seconds(300), now).await.is_err());
+        assert!(pool.share_timeseries_at(1, chrono::Duration::days(1), now).await.is_err());
+    }
 }
 
 /// Recovery-enabled database wrapper that provides automatic retry and failover
@@ -1513,6 +2276,14 @@ impl DatabaseOps for RecoveryDatabasePool {
         self.pool.get_share_stats(connection_id).await
     }
 
+    async fn prune_shares_older_than(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64> {
+        self.pool.prune_shares_older_than(cutoff).await
+    }
+
+    async fn get_share_timeseries(&self, bucket_seconds: u32, window: chrono::Duration) -> Result<Vec<ShareBucket>> {
+        self.pool.get_share_timeseries(bucket_seconds, window).await
+    }
+
     async fn create_work_template(&self, template: &WorkTemplate) -> Result<()> {
         self.pool.create_work_template(template).await
     }
@@ -1557,6 +2328,42 @@ impl DatabaseOps for RecoveryDatabasePool {
         self.pool.get_config_history(limit).await
     }
 
+    async fn get_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<Option<WorkerRecord>> {
+        self.pool.get_worker_record(worker_name, source_ip).await
+    }
+
+    async fn store_worker_record(&self, record: &WorkerRecord) -> Result<()> {
+        self.pool.store_worker_record(record).await
+    }
+
+    async fn record_milestone(&self, record: &MilestoneRecord) -> Result<bool> {
+        self.pool.record_milestone(record).await
+    }
+
+    async fn get_milestones(&self) -> Result<Vec<MilestoneRecord>> {
+        self.pool.get_milestones().await
+    }
+
+    async fn store_block(&self, record: &BlockRecord) -> Result<()> {
+        self.pool.store_block(record).await
+    }
+
+    async fn get_blocks(&self, limit: Option<u32>) -> Result<Vec<BlockRecord>> {
+        self.pool.get_blocks(limit).await
+    }
+
+    async fn store_worker_ban(&self, ban: &WorkerBan) -> Result<()> {
+        self.pool.store_worker_ban(ban).await
+    }
+
+    async fn delete_worker_ban(&self, worker_name: &str) -> Result<bool> {
+        self.pool.delete_worker_ban(worker_name).await
+    }
+
+    async fn get_worker_bans(&self) -> Result<Vec<WorkerBan>> {
+        self.pool.get_worker_bans().await
+    }
+
     async fn store_connection(&self, conn: &crate::Connection) -> Result<()> {
         self.pool.store_connection(conn).await
     }
@@ -1565,6 +2372,10 @@ impl DatabaseOps for RecoveryDatabasePool {
         self.pool.store_share(share).await
     }
 
+    async fn store_sampled_share(&self, share: &Share, weight: u64) -> Result<()> {
+        self.pool.store_sampled_share(share, weight).await
+    }
+
     async fn store_work_template(&self, template: &WorkTemplate) -> Result<()> {
         self.pool.store_work_template(template).await
     }
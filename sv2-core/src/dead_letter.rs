@@ -0,0 +1,238 @@
+//! Append-only dead-letter log for shares the database failed to store.
+//!
+//! The miner still gets its share result when persistence fails, so without
+//! this the share would silently drop out of the stats. Dead-lettered shares
+//! are kept as JSON lines and reingested with `sv2-cli replay-shares` once the
+//! database is healthy again.
+
+use crate::{database::DatabaseOps, types::{Alert, AlertSeverity}, Result, Share};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Dead-letter file for one daemon
+pub struct DeadLetterLog {
+    path: PathBuf,
+    /// Set while shares are being dead-lettered, cleared once a store succeeds
+    active: AtomicBool,
+    write_lock: Mutex<()>,
+}
+
+/// One dead-lettered share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    #[serde(flatten)]
+    pub share: Share,
+    /// Accepted shares this one stands for when accepted shares are sampled
+    #[serde(default = "default_weight", skip_serializing_if = "is_unweighted")]
+    pub weight: u64,
+}
+
+fn default_weight() -> u64 {
+    1
+}
+
+fn is_unweighted(weight: &u64) -> bool {
+    *weight == 1
+}
+
+/// Outcome of reingesting a dead-letter file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    /// Shares that failed again and were kept in the file
+    pub remaining: usize,
+}
+
+impl DeadLetterLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            active: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the last persist attempt ended up in the dead-letter file
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Store `share` standing for `weight` shares, dead-lettering it if the
+    /// database rejects it. Only fails if the dead-letter file can't be
+    /// written either.
+    pub async fn persist(&self, database: &dyn DatabaseOps, share: &Share, weight: u64) -> Result<()> {
+        match database.store_sampled_share(share, weight).await {
+            Ok(()) => {
+                if self.active.swap(false, Ordering::SeqCst) {
+                    tracing::info!(
+                        "Share persistence recovered; replay {} with `sv2-cli replay-shares`",
+                        self.path.display()
+                    );
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.append(share, weight).await?;
+                if !self.active.swap(true, Ordering::SeqCst) {
+                    self.raise_alert(database, &e).await;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Append one share to the dead-letter file
+    pub async fn append(&self, share: &Share, weight: u64) -> Result<()> {
+        let mut line = serde_json::to_string(&DeadLetter { share: share.clone(), weight })?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn raise_alert(&self, database: &dyn DatabaseOps, cause: &crate::Error) {
+        let message = format!(
+            "Failed to store shares ({}); dead-lettering them to {}",
+            cause,
+            self.path.display()
+        );
+        tracing::error!("[ALERT] Share persistence failing: {}", message);
+
+        // Best effort: the database may be exactly what is failing
+        let alert = Alert {
+            id: Uuid::new_v4(),
+            severity: AlertSeverity::Critical,
+            message,
+            timestamp: Utc::now(),
+            acknowledged: false,
+        };
+        if let Err(e) = database.create_alert(&alert).await {
+            tracing::debug!("Could not record dead-letter alert: {}", e);
+        }
+    }
+}
+
+/// Store `share` standing for `weight` shares, through `dead_letter` when one
+/// is configured
+pub async fn store_share(
+    database: &dyn DatabaseOps,
+    dead_letter: Option<&DeadLetterLog>,
+    share: &Share,
+    weight: u64,
+) -> Result<()> {
+    match dead_letter {
+        Some(dead_letter) => dead_letter.persist(database, share, weight).await,
+        None => database.store_sampled_share(share, weight).await,
+    }
+}
+
+/// Read every share from a dead-letter file
+pub fn read_dead_letters(path: &Path) -> Result<Vec<DeadLetter>> {
+    let contents = std::fs::read_to_string(path)?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Reingest the shares in `path`. Shares that fail again stay in the file;
+/// the file is removed once everything has been stored.
+pub async fn replay(path: &Path, database: &dyn DatabaseOps) -> Result<ReplayReport> {
+    let dead_letters = read_dead_letters(path)?;
+    let mut report = ReplayReport::default();
+    let mut remaining = String::new();
+
+    for dead_letter in &dead_letters {
+        match database.store_sampled_share(&dead_letter.share, dead_letter.weight).await {
+            Ok(()) => report.replayed += 1,
+            Err(e) => {
+                tracing::warn!("Share from {} still failed to store: {}", dead_letter.share.connection_id, e);
+                remaining.push_str(&serde_json::to_string(dead_letter)?);
+                remaining.push('\n');
+                report.remaining += 1;
+            }
+        }
+    }
+
+    if report.remaining == 0 {
+        std::fs::remove_file(path)?;
+    } else {
+        let tmp = path.with_extension("replay.tmp");
+        std::fs::write(&tmp, remaining)?;
+        std::fs::rename(&tmp, path)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+
+    #[tokio::test]
+    async fn test_persist_failure_writes_dead_letter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead_letter.jsonl");
+        let dead_letter = DeadLetterLog::new(&path);
+
+        let database = MockDatabaseOps::new();
+        let mut share = Share::new(Uuid::new_v4(), 42, 1_700_000_000, 1.0);
+        share.is_valid = true;
+
+        database.fail_share_writes(true);
+        dead_letter.persist(&database, &share, 1).await.unwrap();
+        dead_letter.persist(&database, &share, 1).await.unwrap();
+        assert!(dead_letter.is_active());
+
+        let dead = read_dead_letters(&path).unwrap();
+        assert_eq!(dead.len(), 2);
+        assert_eq!(dead[0].share.nonce, 42);
+        assert!(dead[0].share.is_valid);
+        assert!(database.get_shares(None, None).await.unwrap().is_empty());
+
+        // Dead-lettering begins once, so only one alert is raised
+        assert_eq!(database.get_alerts(None, None).await.unwrap().len(), 1);
+
+        database.fail_share_writes(false);
+        dead_letter.persist(&database, &share, 1).await.unwrap();
+        assert!(!dead_letter.is_active());
+
+        let report = replay(&path, &database).await.unwrap();
+        assert_eq!(report, ReplayReport { replayed: 2, remaining: 0 });
+        assert_eq!(database.get_shares(None, None).await.unwrap().len(), 3);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_shares_that_fail_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead_letter.jsonl");
+        let dead_letter = DeadLetterLog::new(&path);
+        dead_letter.append(&Share::new(Uuid::new_v4(), 1, 1_700_000_000, 1.0), 1).await.unwrap();
+
+        let database = MockDatabaseOps::new();
+        database.fail_share_writes(true);
+
+        let report = replay(&path, &database).await.unwrap();
+        assert_eq!(report, ReplayReport { replayed: 0, remaining: 1 });
+        assert_eq!(read_dead_letters(&path).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! Difficulty display units
+//!
+//! Share and channel difficulty can be shown as raw difficulty-1 multiples
+//! ("difficulty 1000") or as a fraction of the network difficulty ("0.0001%
+//! of network"). `[monitoring] difficulty_unit` picks the unit for status
+//! output and the dashboard; `sv2-cli` can override it per command.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyUnit {
+    /// Multiples of difficulty 1
+    #[default]
+    Diff1,
+    /// Percentage of the current network difficulty
+    Network,
+}
+
+impl DifficultyUnit {
+    /// `difficulty` in this unit. Network-relative values need the network
+    /// difficulty and are `None` without it.
+    pub fn convert(self, difficulty: f64, network_difficulty: Option<f64>) -> Option<f64> {
+        match self {
+            DifficultyUnit::Diff1 => Some(difficulty),
+            DifficultyUnit::Network => network_difficulty
+                .filter(|network| *network > 0.0)
+                .map(|network| difficulty / network * 100.0),
+        }
+    }
+
+    /// Human-readable `difficulty` in this unit. Falls back to diff-1 when
+    /// the network difficulty isn't known yet.
+    pub fn format(self, difficulty: f64, network_difficulty: Option<f64>) -> String {
+        match self.convert(difficulty, network_difficulty) {
+            Some(percent) if self == DifficultyUnit::Network => format!("{}% of network", format_significant(percent)),
+            _ => format_diff1(difficulty),
+        }
+    }
+}
+
+/// Diff-1 difficulty with a K/M/G/T/P suffix
+fn format_diff1(difficulty: f64) -> String {
+    const SUFFIXES: [(f64, &str); 5] = [(1e15, "P"), (1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "K")];
+    SUFFIXES
+        .iter()
+        .find(|(scale, _)| difficulty >= *scale)
+        .map(|(scale, suffix)| format!("{:.2}{}", difficulty / scale, suffix))
+        .unwrap_or_else(|| format!("{:.2}", difficulty))
+}
+
+/// Three significant digits without an exponent, so tiny fractions stay readable
+fn format_significant(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{}", value);
+    }
+    let decimals = (2 - value.abs().log10().floor() as i32).max(0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+impl fmt::Display for DifficultyUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifficultyUnit::Diff1 => write!(f, "diff1"),
+            DifficultyUnit::Network => write!(f, "network"),
+        }
+    }
+}
+
+impl FromStr for DifficultyUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "diff1" => Ok(DifficultyUnit::Diff1),
+            "network" => Ok(DifficultyUnit::Network),
+            _ => Err(Error::Config(format!("Invalid difficulty unit '{}'. Valid units: diff1, network", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Roughly the network difficulty in early 2024
+    const NETWORK: f64 = 1e14;
+
+    #[test]
+    fn test_diff1_display() {
+        let unit = DifficultyUnit::Diff1;
+        assert_eq!(unit.convert(1000.0, Some(NETWORK)), Some(1000.0));
+        assert_eq!(unit.format(512.0, Some(NETWORK)), "512.00");
+        assert_eq!(unit.format(1000.0, None), "1.00K");
+        assert_eq!(unit.format(2.5e6, None), "2.50M");
+        assert_eq!(unit.format(NETWORK, None), "100.00T");
+    }
+
+    #[test]
+    fn test_network_relative_display() {
+        let unit = DifficultyUnit::Network;
+        assert_eq!(unit.convert(1e8, Some(NETWORK)), Some(1e-4));
+        assert_eq!(unit.format(1e8, Some(NETWORK)), "0.000100% of network");
+        assert_eq!(unit.format(5e13, Some(NETWORK)), "50.0% of network");
+        assert_eq!(unit.format(NETWORK, Some(NETWORK)), "100% of network");
+
+        // Unknown network difficulty falls back to diff-1
+        assert_eq!(unit.convert(1000.0, None), None);
+        assert_eq!(unit.convert(1000.0, Some(0.0)), None);
+        assert_eq!(unit.format(1000.0, None), "1.00K");
+    }
+
+    #[test]
+    fn test_unit_parsing() {
+        assert_eq!("diff1".parse::<DifficultyUnit>().unwrap(), DifficultyUnit::Diff1);
+        assert_eq!("Network".parse::<DifficultyUnit>().unwrap(), DifficultyUnit::Network);
+        assert!("percent".parse::<DifficultyUnit>().is_err());
+        assert_eq!(DifficultyUnit::Network.to_string(), "network");
+    }
+}
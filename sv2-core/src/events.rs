@@ -0,0 +1,180 @@
+//! Internal event bus
+//!
+//! Mode handlers and the stratum server publish what happened — connections
+//! opening and closing, failed handshakes, processed shares, found and
+//! orphaned blocks, milestones — to one
+//! [`EventBus`]. Components that react to those events (webhooks, metrics)
+//! subscribe to the bus instead of being called from each handler.
+//!
+//! The bus is a `tokio::sync::broadcast` channel: publishing never waits, and
+//! a subscriber that falls more than the bus capacity behind skips the
+//! oldest events with a warning rather than slow the share path down.
+
+use crate::handshake_failures::HandshakeFailureReason;
+use crate::milestones::MilestoneRecord;
+use crate::types::{ConnectionId, Protocol, ShareResult};
+use bitcoin::BlockHash;
+use chrono::{DateTime, Utc};
+use std::net::SocketAddr;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Events a subscriber can fall behind by before it starts skipping them
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Something that happened in a mode handler
+#[derive(Debug, Clone)]
+pub enum Event {
+    ConnectionOpened {
+        connection_id: ConnectionId,
+        address: SocketAddr,
+        protocol: Protocol,
+    },
+    ConnectionClosed {
+        connection_id: ConnectionId,
+        protocol: Protocol,
+        connected_at: DateTime<Utc>,
+    },
+    /// A share was validated and stored; blocks and milestones follow as their own events
+    ShareProcessed {
+        connection_id: ConnectionId,
+        difficulty: f64,
+        result: ShareResult,
+    },
+    BlockFound {
+        block_hash: BlockHash,
+        connection_id: ConnectionId,
+    },
+    /// A block reported as found is no longer in the active chain
+    BlockOrphaned {
+        block_hash: BlockHash,
+        height: Option<u64>,
+    },
+    MilestoneReached(MilestoneRecord),
+    /// A connection was dropped before its handshake completed
+    HandshakeFailed {
+        address: SocketAddr,
+        reason: HandshakeFailureReason,
+    },
+}
+
+/// Cloneable handle to publish to and subscribe to the bus
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Send `event` to every current subscriber. Never waits.
+    pub fn publish(&self, event: Event) {
+        // No subscribers isn't an error; nobody needed to hear about it
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Run `handle` on each event in a background task until the bus is dropped
+    pub fn spawn_subscriber<F>(&self, name: &'static str, mut handle: F) -> JoinHandle<()>
+    where
+        F: FnMut(Event) + Send + 'static,
+    {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => handle(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("{} fell behind the event bus, skipped {} events", name, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Publish the events a processed share produced
+    pub fn share_processed(
+        &self,
+        connection_id: ConnectionId,
+        difficulty: f64,
+        result: &ShareResult,
+        milestones: Vec<MilestoneRecord>,
+    ) {
+        self.publish(Event::ShareProcessed { connection_id, difficulty, result: result.clone() });
+        if let ShareResult::Block(block_hash) = result {
+            self.publish(Event::BlockFound { block_hash: *block_hash, connection_id });
+        }
+        for milestone in milestones {
+            self.publish(Event::MilestoneReached(milestone));
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_share_event_reaches_every_subscriber() {
+        let bus = EventBus::default();
+        let mut first = bus.subscribe();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&seen);
+        let _task = bus.spawn_subscriber("test", move |event| recorded.lock().unwrap().push(event));
+
+        let connection_id = uuid::Uuid::new_v4();
+        bus.share_processed(connection_id, 512.0, &ShareResult::Valid, Vec::new());
+
+        match first.recv().await.unwrap() {
+            Event::ShareProcessed { connection_id: id, difficulty, result } => {
+                assert_eq!(id, connection_id);
+                assert_eq!(difficulty, 512.0);
+                assert!(matches!(result, ShareResult::Valid));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        for _ in 0..50 {
+            if !seen.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(matches!(seen[0], Event::ShareProcessed { connection_id: id, .. } if id == connection_id));
+    }
+
+    #[tokio::test]
+    async fn test_block_share_publishes_block_found() {
+        use bitcoin::hashes::Hash;
+
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        let block_hash = BlockHash::from_byte_array([7; 32]);
+        bus.share_processed(uuid::Uuid::new_v4(), 1.0, &ShareResult::Block(block_hash), Vec::new());
+
+        assert!(matches!(receiver.recv().await.unwrap(), Event::ShareProcessed { .. }));
+        assert!(matches!(receiver.recv().await.unwrap(), Event::BlockFound { block_hash: hash, .. } if hash == block_hash));
+
+        // Publishing with nobody listening is fine
+        drop(receiver);
+        bus.publish(Event::BlockOrphaned { block_hash, height: None });
+    }
+}
@@ -0,0 +1,392 @@
+//! Handshake failure tracking
+//!
+//! A miner that can't complete the handshake (wrong authority key, a
+//! protocol version the server doesn't speak, garbage on the wire) is just
+//! disconnected, so without counting these one misconfigured rack or a
+//! scanner looks like nothing at all. The stratum server publishes each
+//! failure as [`Event::HandshakeFailed`]; [`HandshakeFailureTracker`] counts
+//! them by source IP and reason and raises a warning alert when one IP fails
+//! `alert_threshold` times within `window_secs`. The alert re-arms once that
+//! IP drops back under the threshold.
+
+use crate::database::DatabaseOps;
+use crate::events::{Event, EventBus};
+use crate::types::{Alert, AlertSeverity};
+use crate::{Error, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Stratum V2 protocol version the server accepts in `SetupConnection`
+pub const SV2_PROTOCOL_VERSION: u64 = 2;
+
+/// `SetupConnection.Error` code for a version range the server can't meet
+pub const PROTOCOL_VERSION_MISMATCH: &str = "protocol-version-mismatch";
+
+/// Reply to a `SetupConnection` the server can't accept. The spec reserves
+/// `flags` for the requested feature flags that caused the error, so the
+/// versions the server does speak are carried alongside it, letting the
+/// miner retry with a range that overlaps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupConnectionError {
+    pub flags: u32,
+    pub error_code: String,
+    pub min_version: u16,
+    pub max_version: u16,
+}
+
+impl SetupConnectionError {
+    pub fn version_mismatch() -> Self {
+        Self {
+            flags: 0,
+            error_code: PROTOCOL_VERSION_MISMATCH.to_string(),
+            min_version: SV2_PROTOCOL_VERSION as u16,
+            max_version: SV2_PROTOCOL_VERSION as u16,
+        }
+    }
+
+    /// As a frame in the JSON encoding SV2 messages use on this server
+    pub fn to_frame(&self) -> serde_json::Value {
+        serde_json::json!({ "msg_type": "SetupConnectionError", "payload": self })
+    }
+}
+
+/// `[network.handshake_failures]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandshakeFailureConfig {
+    /// Failures from one IP within the window that raise an alert
+    #[serde(default = "default_alert_threshold")]
+    pub alert_threshold: u32,
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_alert_threshold() -> u32 {
+    10
+}
+
+fn default_window_secs() -> u64 {
+    300
+}
+
+impl Default for HandshakeFailureConfig {
+    fn default() -> Self {
+        Self {
+            alert_threshold: default_alert_threshold(),
+            window_secs: default_window_secs(),
+        }
+    }
+}
+
+impl HandshakeFailureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.alert_threshold == 0 {
+            return Err(Error::Config("handshake_failures alert_threshold must be at least 1".to_string()));
+        }
+        if self.window_secs == 0 {
+            return Err(Error::Config("handshake_failures window_secs must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Why a handshake didn't complete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandshakeFailureReason {
+    /// The miner expected a different pool authority key
+    AuthorityKeyMismatch,
+    /// No protocol version both sides support
+    UnsupportedVersion,
+    /// The opening frame wasn't a message of either protocol
+    Malformed,
+    /// An HTTP request or TLS handshake, from a browser or scanner
+    NotStratum,
+    Timeout,
+}
+
+impl HandshakeFailureReason {
+    /// Label used in metrics and alerts
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AuthorityKeyMismatch => "authority_key_mismatch",
+            Self::UnsupportedVersion => "unsupported_version",
+            Self::Malformed => "malformed",
+            Self::NotStratum => "not_stratum",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl std::fmt::Display for HandshakeFailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Check the first frame of a connection, before its protocol is known
+pub fn check_opening_frame(message: &str) -> std::result::Result<(), HandshakeFailureReason> {
+    let frame: serde_json::Value =
+        serde_json::from_str(message).map_err(|_| HandshakeFailureReason::Malformed)?;
+    if frame.get("method").is_some() {
+        return Ok(());
+    }
+    match frame.get("msg_type").and_then(|msg_type| msg_type.as_str()) {
+        Some("SetupConnection") => {
+            let payload = &frame["payload"];
+            let min_version = payload.get("min_version").and_then(|v| v.as_u64());
+            let max_version = payload.get("max_version").and_then(|v| v.as_u64());
+            let supported = min_version.map_or(true, |min| min <= SV2_PROTOCOL_VERSION)
+                && max_version.map_or(true, |max| max >= SV2_PROTOCOL_VERSION);
+            if supported {
+                Ok(())
+            } else {
+                Err(HandshakeFailureReason::UnsupportedVersion)
+            }
+        }
+        Some(_) => Ok(()),
+        None => Err(HandshakeFailureReason::Malformed),
+    }
+}
+
+/// Request methods that start an HTTP/1 request line, and the HTTP/2 preface
+const HTTP_METHODS: &[&str] = &["GET ", "HEAD ", "POST ", "PUT ", "DELETE ", "OPTIONS ", "CONNECT ", "PATCH ", "TRACE ", "PRI "];
+
+/// Check the first bytes of a connection for traffic that is plainly not
+/// Stratum: an HTTP request line or a TLS handshake record. These are
+/// turned away as soon as they arrive, since a TLS client never sends the
+/// newline [`check_opening_frame`] waits for. Anything else, including a
+/// prefix too short to tell, is left to that check.
+pub fn check_opening_bytes(data: &[u8]) -> Result<()> {
+    if let [0x16, 0x03, ..] = data {
+        return Err(Error::Protocol("TLS handshake on a Stratum port".to_string()));
+    }
+    if let Some(method) = HTTP_METHODS.iter().find(|method| data.starts_with(method.as_bytes())) {
+        return Err(Error::Protocol(format!("HTTP {} request on a Stratum port", method.trim_end())));
+    }
+    Ok(())
+}
+
+/// Failures from one source IP and reason
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandshakeFailureCount {
+    pub address: IpAddr,
+    pub reason: HandshakeFailureReason,
+    pub count: u64,
+}
+
+#[derive(Debug, Default)]
+struct SourceFailures {
+    by_reason: HashMap<HandshakeFailureReason, u64>,
+    /// Failure times still inside the alert window
+    recent: VecDeque<Instant>,
+    alerting: bool,
+}
+
+/// Counts handshake failures and decides when a source deserves an alert
+#[derive(Debug)]
+pub struct HandshakeFailureTracker {
+    config: HandshakeFailureConfig,
+    sources: Mutex<HashMap<IpAddr, SourceFailures>>,
+}
+
+impl HandshakeFailureTracker {
+    pub fn new(config: HandshakeFailureConfig) -> Self {
+        Self { config, sources: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &HandshakeFailureConfig {
+        &self.config
+    }
+
+    /// Count a failure from `address` at `now`. Returns true when it takes the
+    /// source over the alert threshold.
+    pub fn record(&self, address: IpAddr, reason: HandshakeFailureReason, now: Instant) -> bool {
+        let window = Duration::from_secs(self.config.window_secs);
+        let threshold = self.config.alert_threshold as usize;
+        let mut sources = self.sources.lock().unwrap();
+        let source = sources.entry(address).or_default();
+
+        *source.by_reason.entry(reason).or_default() += 1;
+        source.recent.push_back(now);
+        while source.recent.front().is_some_and(|&at| now.duration_since(at) > window) {
+            source.recent.pop_front();
+        }
+
+        if source.recent.len() < threshold {
+            source.alerting = false;
+            return false;
+        }
+        !std::mem::replace(&mut source.alerting, true)
+    }
+
+    /// Every source and reason seen since startup, most failures first
+    pub fn counts(&self) -> Vec<HandshakeFailureCount> {
+        let sources = self.sources.lock().unwrap();
+        let mut counts: Vec<_> = sources
+            .iter()
+            .flat_map(|(address, source)| {
+                source.by_reason.iter().map(|(reason, count)| HandshakeFailureCount {
+                    address: *address,
+                    reason: *reason,
+                    count: *count,
+                })
+            })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.address.cmp(&b.address)));
+        counts
+    }
+
+    /// Count failures published on `bus`, alerting through `database`
+    pub fn subscribe(
+        self: &Arc<Self>,
+        bus: &EventBus,
+        database: Arc<dyn DatabaseOps>,
+    ) -> tokio::task::JoinHandle<()> {
+        let tracker = Arc::clone(self);
+        bus.spawn_subscriber("Handshake failure tracker", move |event| {
+            if let Event::HandshakeFailed { address, reason } = event {
+                if tracker.record(address.ip(), reason, Instant::now()) {
+                    let message = format!(
+                        "{} failed {} handshakes in {}s, last with {}; check its pool URL and authority key",
+                        address.ip(),
+                        tracker.config.alert_threshold,
+                        tracker.config.window_secs,
+                        reason
+                    );
+                    let database = Arc::clone(&database);
+                    tokio::spawn(async move { raise_alert(database.as_ref(), message).await });
+                }
+            }
+        })
+    }
+}
+
+async fn raise_alert(database: &dyn DatabaseOps, message: String) {
+    tracing::warn!("[ALERT] Repeated handshake failures: {}", message);
+    let alert = Alert {
+        id: Uuid::new_v4(),
+        severity: AlertSeverity::Warning,
+        message,
+        timestamp: Utc::now(),
+        acknowledged: false,
+    };
+    if let Err(e) = database.create_alert(&alert).await {
+        tracing::debug!("Could not record handshake failure alert: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+
+    #[test]
+    fn test_opening_frames() {
+        assert_eq!(check_opening_frame(r#"{"id":1,"method":"mining.subscribe","params":[]}"#), Ok(()));
+        assert_eq!(check_opening_frame(r#"{"msg_type":"SetupConnection","payload":{}}"#), Ok(()));
+        assert_eq!(
+            check_opening_frame(r#"{"msg_type":"SetupConnection","payload":{"min_version":2,"max_version":2}}"#),
+            Ok(())
+        );
+        assert_eq!(
+            check_opening_frame(r#"{"msg_type":"SetupConnection","payload":{"min_version":3,"max_version":4}}"#),
+            Err(HandshakeFailureReason::UnsupportedVersion)
+        );
+        assert_eq!(check_opening_frame("GET / HTTP/1.1"), Err(HandshakeFailureReason::Malformed));
+        assert_eq!(check_opening_frame(r#"{"hello":"world"}"#), Err(HandshakeFailureReason::Malformed));
+    }
+
+    #[test]
+    fn test_opening_bytes() {
+        let error = check_opening_bytes(b"GET / HTTP/1.1\r\nHost: 10.0.0.2:3333\r\n").unwrap_err();
+        assert!(matches!(&error, Error::Protocol(reason) if reason == "HTTP GET request on a Stratum port"));
+        // TLS 1.0 record header carrying a ClientHello, as sent by TLS 1.2 and 1.3 clients
+        let error = check_opening_bytes(&[0x16, 0x03, 0x01, 0x02, 0x00, 0x01]).unwrap_err();
+        assert!(matches!(&error, Error::Protocol(reason) if reason.starts_with("TLS handshake")));
+        assert!(check_opening_bytes(b"PRI * HTTP/2.0\r\n").is_err());
+
+        assert!(check_opening_bytes(br#"{"id":1,"method":"mining.subscribe","params":[]}"#).is_ok());
+        assert!(check_opening_bytes(br#"{"msg_type":"SetupConnection","payload":{}}"#).is_ok());
+        // Not enough to tell yet
+        assert!(check_opening_bytes(b"GE").is_ok());
+        assert!(check_opening_bytes(&[0x16]).is_ok());
+        assert!(check_opening_bytes(b"").is_ok());
+    }
+
+    #[test]
+    fn test_version_mismatch_frame() {
+        let frame = SetupConnectionError::version_mismatch().to_frame();
+        assert_eq!(frame["msg_type"], "SetupConnectionError");
+        assert_eq!(frame["payload"]["error_code"], "protocol-version-mismatch");
+        assert_eq!(frame["payload"]["flags"], 0);
+        assert_eq!(frame["payload"]["min_version"], 2);
+        assert_eq!(frame["payload"]["max_version"], 2);
+    }
+
+    #[test]
+    fn test_alert_trips_once_per_burst() {
+        let tracker = HandshakeFailureTracker::new(HandshakeFailureConfig { alert_threshold: 3, window_secs: 60 });
+        let noisy: IpAddr = "192.168.1.50".parse().unwrap();
+        let quiet: IpAddr = "192.168.1.51".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(!tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, start));
+        assert!(!tracker.record(quiet, HandshakeFailureReason::Malformed, start));
+        assert!(!tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, start));
+        assert!(tracker.record(noisy, HandshakeFailureReason::UnsupportedVersion, start));
+        assert!(!tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, start));
+
+        // Once the window has passed the next burst alerts again
+        let later = start + Duration::from_secs(120);
+        assert!(!tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, later));
+        assert!(!tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, later));
+        assert!(tracker.record(noisy, HandshakeFailureReason::AuthorityKeyMismatch, later));
+
+        let counts = tracker.counts();
+        assert_eq!(counts[0], HandshakeFailureCount {
+            address: noisy,
+            reason: HandshakeFailureReason::AuthorityKeyMismatch,
+            count: 6,
+        });
+        assert_eq!(counts.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_from_one_ip_raise_alert() {
+        let bus = EventBus::default();
+        let database = Arc::new(MockDatabaseOps::new());
+        let tracker = Arc::new(HandshakeFailureTracker::new(HandshakeFailureConfig {
+            alert_threshold: 5,
+            window_secs: 60,
+        }));
+        let _task = tracker.subscribe(&bus, database.clone());
+
+        for port in 0..12 {
+            bus.publish(Event::HandshakeFailed {
+                address: format!("10.0.0.7:{}", 40000 + port).parse().unwrap(),
+                reason: HandshakeFailureReason::AuthorityKeyMismatch,
+            });
+        }
+        bus.publish(Event::HandshakeFailed {
+            address: "10.0.0.8:40000".parse().unwrap(),
+            reason: HandshakeFailureReason::Malformed,
+        });
+
+        let mut alerts = Vec::new();
+        for _ in 0..50 {
+            alerts = database.get_alerts(None, None).await.unwrap();
+            if !alerts.is_empty() && tracker.counts().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+        assert!(alerts[0].message.starts_with("10.0.0.7 failed 5 handshakes"));
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::error::{Error, Result};
 use crate::database::DatabaseOps;
 use crate::config::{HealthConfig, AlertThresholds};
+use crate::mode::ModeHandler;
 use crate::types::{Alert as DbAlert, AlertLevel};
 
 /// Health check status
@@ -28,6 +29,44 @@ pub struct HealthCheck {
     pub metadata: HashMap<String, String>,
 }
 
+impl HealthStatus {
+    fn severity(&self) -> u8 {
+        match self {
+            HealthStatus::Healthy => 0,
+            HealthStatus::Unknown => 1,
+            HealthStatus::Warning => 2,
+            HealthStatus::Critical => 3,
+        }
+    }
+}
+
+impl HealthCheck {
+    pub fn new(name: impl Into<String>, status: HealthStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            message: message.into(),
+            timestamp: chrono::Utc::now(),
+            duration: Duration::ZERO,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: impl ToString) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Report `problem` at `status` unless something worse was already reported
+    pub fn degrade(mut self, status: HealthStatus, problem: impl Into<String>) -> Self {
+        if status.severity() > self.status.severity() {
+            self.status = status;
+            self.message = problem.into();
+        }
+        self
+    }
+}
+
 /// Extended health monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtendedHealthConfig {
@@ -125,6 +164,12 @@ pub struct BitcoinRpcHealthChecker {
     rpc_client: Arc<crate::bitcoin_rpc::BitcoinRpcClient>,
 }
 
+/// Checks the running mode handler's own health: its upstream, template or
+/// miners, depending on the mode
+pub struct ModeHealthChecker {
+    handler: Arc<dyn ModeHandler>,
+}
+
 /// Notification service for sending alerts
 pub struct NotificationService {
     channels: Vec<NotificationChannel>,
@@ -525,6 +570,27 @@ impl HealthChecker for BitcoinRpcHealthChecker {
     }
 }
 
+impl ModeHealthChecker {
+    pub fn new(handler: Arc<dyn ModeHandler>) -> Self {
+        Self { handler }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthChecker for ModeHealthChecker {
+    fn name(&self) -> &str {
+        "mode"
+    }
+
+    async fn check(&self) -> Result<HealthCheck> {
+        let start_time = Instant::now();
+        let mut check = self.handler.health().await;
+        check.name = self.name().to_string();
+        check.duration = start_time.elapsed();
+        Ok(check)
+    }
+}
+
 impl NotificationService {
     pub fn new(channels: Vec<NotificationChannel>) -> Self {
         Self { channels }
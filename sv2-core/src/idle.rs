@@ -0,0 +1,231 @@
+//! Idle power-save
+//!
+//! A rig that is only mined on now and then still polls the node for
+//! templates and runs its periodic monitors at full rate while nothing is
+//! connected. Once no miners have been connected for `after_secs`, loops that
+//! sleep through [`IdleMonitor::sleep`] stretch their interval by `slowdown`
+//! (up to `max_interval_secs`). The first connection wakes them straight away,
+//! so a miner that connects to an idle daemon gets fresh work immediately.
+
+use crate::events::{Event, EventBus};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// `[idle]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdleConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// How long without miners before slowing down
+    #[serde(default = "default_after_secs")]
+    pub after_secs: u64,
+    /// Factor idle intervals are multiplied by
+    #[serde(default = "default_slowdown")]
+    pub slowdown: u32,
+    /// Longest an idle interval is stretched to; never shortens an interval
+    #[serde(default = "default_max_interval_secs")]
+    pub max_interval_secs: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_after_secs() -> u64 {
+    600
+}
+
+fn default_slowdown() -> u32 {
+    4
+}
+
+fn default_max_interval_secs() -> u64 {
+    300
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            after_secs: default_after_secs(),
+            slowdown: default_slowdown(),
+            max_interval_secs: default_max_interval_secs(),
+        }
+    }
+}
+
+impl IdleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.slowdown == 0 {
+            return Err(Error::Config("idle slowdown must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Tracks whether any miners are connected and paces loops accordingly
+#[derive(Debug)]
+pub struct IdleMonitor {
+    config: IdleConfig,
+    connections: AtomicUsize,
+    /// When the last miner left; startup counts as having none
+    idle_since: Mutex<Option<Instant>>,
+    /// Set once the slowdown has been logged, so it's logged once per idle spell
+    slowed: AtomicBool,
+    connected: Notify,
+}
+
+impl IdleMonitor {
+    pub fn new(config: IdleConfig) -> Self {
+        Self {
+            config,
+            connections: AtomicUsize::new(0),
+            idle_since: Mutex::new(Some(Instant::now())),
+            slowed: AtomicBool::new(false),
+            connected: Notify::new(),
+        }
+    }
+
+    pub fn config(&self) -> &IdleConfig {
+        &self.config
+    }
+
+    pub fn connection_opened(&self) {
+        self.connections.fetch_add(1, Ordering::SeqCst);
+        *self.idle_since.lock().unwrap() = None;
+        if self.slowed.swap(false, Ordering::SeqCst) {
+            tracing::info!("Miner connected, back to full polling rate");
+        }
+        self.connected.notify_waiters();
+    }
+
+    pub fn connection_closed(&self) {
+        let previous = self
+            .connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_sub(1)))
+            .unwrap_or_default();
+        if previous <= 1 {
+            *self.idle_since.lock().unwrap() = Some(Instant::now());
+        }
+    }
+
+    /// Whether no miners have been connected for `after_secs` as of `now`
+    pub fn is_idle_at(&self, now: Instant) -> bool {
+        if !self.config.enabled || self.connections.load(Ordering::SeqCst) > 0 {
+            return false;
+        }
+        self.idle_since
+            .lock()
+            .unwrap()
+            .is_some_and(|since| now.duration_since(since) >= Duration::from_secs(self.config.after_secs))
+    }
+
+    /// How long a loop running every `base` should wait as of `now`
+    pub fn interval_at(&self, base: Duration, now: Instant) -> Duration {
+        if !self.is_idle_at(now) {
+            return base;
+        }
+        let ceiling = Duration::from_secs(self.config.max_interval_secs).max(base);
+        base.saturating_mul(self.config.slowdown).min(ceiling)
+    }
+
+    pub fn interval(&self, base: Duration) -> Duration {
+        let interval = self.interval_at(base, Instant::now());
+        if interval > base && !self.slowed.swap(true, Ordering::SeqCst) {
+            tracing::info!(
+                "No miners connected for {}s, polling {}x less often",
+                self.config.after_secs,
+                self.config.slowdown
+            );
+        }
+        interval
+    }
+
+    /// Wait out one interval of a loop running every `base`, returning early
+    /// if a miner connects in the meantime
+    pub async fn sleep(&self, base: Duration) {
+        // Created before the interval is read so a connection in between isn't missed
+        let connected = self.connected.notified();
+        let interval = self.interval(base);
+        if interval == base {
+            tokio::time::sleep(interval).await;
+            return;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = connected => {}
+        }
+    }
+
+    /// Follow connections published on `bus` instead of being told directly
+    pub fn subscribe(self: &Arc<Self>, bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        let monitor = Arc::clone(self);
+        bus.spawn_subscriber("Idle monitor", move |event| match event {
+            Event::ConnectionOpened { .. } => monitor.connection_opened(),
+            Event::ConnectionClosed { .. } => monitor.connection_closed(),
+            _ => {}
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> IdleConfig {
+        IdleConfig { enabled: true, after_secs: 60, slowdown: 4, max_interval_secs: 300 }
+    }
+
+    #[test]
+    fn test_poll_interval_lengthens_when_idle_and_shortens_on_connect() {
+        let monitor = IdleMonitor::new(config());
+        let base = Duration::from_secs(30);
+        let start = Instant::now();
+
+        // Not idle until nobody has been around for the threshold
+        assert_eq!(monitor.interval_at(base, start), base);
+        let idle = start + Duration::from_secs(61);
+        assert_eq!(monitor.interval_at(base, idle), Duration::from_secs(120));
+        // Capped, but a base interval past the cap is left alone
+        assert_eq!(monitor.interval_at(Duration::from_secs(100), idle), Duration::from_secs(300));
+        assert_eq!(monitor.interval_at(Duration::from_secs(600), idle), Duration::from_secs(600));
+
+        monitor.connection_opened();
+        assert_eq!(monitor.interval_at(base, idle), base);
+
+        // The idle clock restarts when the last miner leaves
+        monitor.connection_opened();
+        monitor.connection_closed();
+        assert_eq!(monitor.interval_at(base, Instant::now() + Duration::from_secs(120)), base);
+        monitor.connection_closed();
+        let left = Instant::now();
+        assert_eq!(monitor.interval_at(base, left + Duration::from_secs(30)), base);
+        assert_eq!(monitor.interval_at(base, left + Duration::from_secs(61)), Duration::from_secs(120));
+
+        let disabled = IdleMonitor::new(IdleConfig { enabled: false, ..config() });
+        assert_eq!(disabled.interval_at(base, idle), base);
+    }
+
+    #[tokio::test]
+    async fn test_connection_wakes_idle_sleep() {
+        let monitor = Arc::new(IdleMonitor::new(IdleConfig { after_secs: 0, ..config() }));
+        let bus = EventBus::default();
+        let _subscription = monitor.subscribe(&bus);
+
+        let sleeping = Arc::clone(&monitor);
+        let sleep = tokio::spawn(async move { sleeping.sleep(Duration::from_secs(30)).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        bus.publish(Event::ConnectionOpened {
+            connection_id: uuid::Uuid::new_v4(),
+            address: "127.0.0.1:4000".parse().unwrap(),
+            protocol: crate::types::Protocol::Sv1,
+        });
+
+        tokio::time::timeout(Duration::from_secs(2), sleep).await.unwrap().unwrap();
+        assert!(!monitor.is_idle_at(Instant::now()));
+    }
+}
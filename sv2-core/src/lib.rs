@@ -12,21 +12,51 @@ pub mod share_validator;
 pub mod validation;
 pub mod health;
 pub mod metrics;
+pub mod metrics_push;
+pub mod metrics_history;
 pub mod logging;
 pub mod recovery;
 pub mod mode_factory;
 pub mod connection_auth;
 pub mod daemon;
 pub mod api_server;
+pub mod capture;
+pub mod template_source;
+pub mod dead_letter;
+pub mod coinbase_tag;
+pub mod bip34;
+pub mod milestones;
+pub mod stratum_url;
+pub mod welcome;
+pub mod webhooks;
+pub mod difficulty_unit;
+pub mod connection_quality;
+pub mod reorg;
+pub mod blocks;
+pub mod events;
+pub mod share_target;
+pub mod handshake_failures;
+pub mod connection_rate;
+pub mod idle;
+pub mod sv2_codec;
+pub mod noise;
+pub mod worker_bans;
+pub mod share_sampling;
+pub mod address;
+pub mod scanner;
+pub mod miner_api;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod session_harness;
 
 pub use error::{Error, Result};
 pub use config::DaemonConfig;
 pub use types::{
-    Connection, ConnectionId, ConnectionInfo, ConnectionState,
-    Share, ShareResult, WorkTemplate,
+    Connection, ConnectionId, ConnectionInfo, ConnectionState, HandshakeDetails,
+    Share, ShareResult, WorkTemplate, NtimeBounds,
     MiningStats, PerformanceMetrics, PoolStats,
-    Worker, Job, ShareSubmission, Protocol,
+    Worker, WorkerRecord, Job, ShareSubmission, Protocol,
     Alert, AlertSeverity, AlertLevel,
     DaemonStatus, UpstreamStatus, BlockTemplate,
 };
-pub use database::{DatabasePool, DatabaseOps, ShareStats, ConfigHistoryEntry};
\ No newline at end of file
+pub use database::{DatabasePool, DatabaseOps, ShareStats, ShareBucket, ConfigHistoryEntry};
+pub use stratum_url::StratumUrl;
\ No newline at end of file
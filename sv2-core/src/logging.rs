@@ -1,7 +1,8 @@
-use crate::config::{LoggingConfig, LogFormat, LogOutput};
+use crate::config::{LoggingConfig, LogFormat, LogOutput, ShareLogMode, ShareLoggingConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{Event, Subscriber};
 use tracing_subscriber::{
     fmt::{format::Writer, FormatEvent, FormatFields},
@@ -217,6 +218,38 @@ impl<'a> tracing::field::Visit for JsonFieldVisitor<'a> {
     }
 }
 
+/// Decides which share submissions get a log line, per `logging.shares`
+#[derive(Debug, Default)]
+pub struct ShareLogFilter {
+    config: ShareLoggingConfig,
+    accepted_seen: AtomicU64,
+}
+
+impl ShareLogFilter {
+    pub fn new(config: ShareLoggingConfig) -> Self {
+        Self {
+            config,
+            accepted_seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a share with this outcome should be logged
+    pub fn should_log(&self, accepted: bool) -> bool {
+        match self.config.mode {
+            ShareLogMode::Off => false,
+            ShareLogMode::RejectedOnly => !accepted,
+            ShareLogMode::All => true,
+            ShareLogMode::Sampled => {
+                if !accepted {
+                    return true;
+                }
+                let seen = self.accepted_seen.fetch_add(1, Ordering::Relaxed);
+                seen % self.config.sample_rate.max(1) == 0
+            }
+        }
+    }
+}
+
 /// Initialize the logging system with the given configuration
 pub fn init_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Build the environment filter
@@ -370,6 +403,39 @@ mod unit_tests {
         assert!(!formatter.looks_like_private_key("not_a_private_key"));
     }
 
+    #[test]
+    fn test_share_log_filter_modes() {
+        let filter = |mode| ShareLogFilter::new(ShareLoggingConfig { mode, sample_rate: 10 });
+
+        let off = filter(ShareLogMode::Off);
+        assert!(!off.should_log(true));
+        assert!(!off.should_log(false));
+
+        let rejected_only = filter(ShareLogMode::RejectedOnly);
+        assert!(!rejected_only.should_log(true));
+        assert!(rejected_only.should_log(false));
+
+        let all = filter(ShareLogMode::All);
+        assert!(all.should_log(true));
+        assert!(all.should_log(false));
+    }
+
+    #[test]
+    fn test_share_log_sampling_rate() {
+        let filter = ShareLogFilter::new(ShareLoggingConfig {
+            mode: ShareLogMode::Sampled,
+            sample_rate: 10,
+        });
+
+        let logged = (0..1000).filter(|_| filter.should_log(true)).count();
+        assert_eq!(logged, 100);
+
+        // Rejected shares are always logged and don't advance the sample
+        assert!((0..5).all(|_| filter.should_log(false)));
+        assert!(filter.should_log(true));
+        assert!(!filter.should_log(true));
+    }
+
     #[test]
     fn test_field_redaction() {
         let formatter = JsonFormatter::new(true);
@@ -15,6 +15,7 @@ async fn test_json_logging_configuration() {
         redact_sensitive_data: true,
         max_file_size_mb: Some(100),
         max_files: Some(10),
+        shares: Default::default(),
     };
 
     // Test that configuration is valid
@@ -35,6 +36,7 @@ async fn test_file_logging_configuration() {
         redact_sensitive_data: true,
         max_file_size_mb: Some(100),
         max_files: Some(10),
+        shares: Default::default(),
     };
 
     // Test that file path configuration is valid
@@ -57,6 +59,7 @@ async fn test_component_level_configuration() {
         redact_sensitive_data: true,
         max_file_size_mb: Some(100),
         max_files: Some(10),
+        shares: Default::default(),
     };
 
     let result = init_logging(&config);
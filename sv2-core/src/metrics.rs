@@ -3,11 +3,14 @@ use std::time::{Duration, Instant};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use prometheus::{
-    Counter, Gauge, Histogram, IntCounter, IntGauge, Registry, Encoder, TextEncoder,
-    HistogramOpts, Opts,
+    Counter, Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, Registry, Encoder, TextEncoder,
+    HistogramOpts, Opts, core::Metric,
 };
 use serde::{Deserialize, Serialize};
 use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::idle::IdleMonitor;
+use crate::types::{Protocol, ShareResult};
 
 /// Metrics configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +39,190 @@ impl Default for MetricsConfig {
     }
 }
 
+/// Share validation latency buckets in seconds, from 50µs up to 1s
+const SHARE_VALIDATION_BUCKETS: [f64; 13] = [
+    0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0,
+];
+
+/// Latency percentiles estimated from a histogram, in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyQuantiles {
+    pub count: u64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl LatencyQuantiles {
+    fn from_histogram(histogram: &Histogram) -> Self {
+        let metric = histogram.metric();
+        let proto = metric.get_histogram();
+        let buckets: Vec<(f64, u64)> = proto
+            .get_bucket()
+            .iter()
+            .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+            .collect();
+        let count = proto.get_sample_count();
+
+        Self {
+            count,
+            p50: bucket_quantile(&buckets, count, 0.50),
+            p95: bucket_quantile(&buckets, count, 0.95),
+            p99: bucket_quantile(&buckets, count, 0.99),
+        }
+    }
+}
+
+/// Estimate a quantile from cumulative buckets by linear interpolation
+/// within the bucket that contains it, like Prometheus' `histogram_quantile`
+fn bucket_quantile(buckets: &[(f64, u64)], count: u64, q: f64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+
+    let rank = q * count as f64;
+    let mut lower_bound = 0.0;
+    let mut lower_count = 0u64;
+    for &(upper_bound, cumulative) in buckets {
+        if cumulative as f64 >= rank {
+            let in_bucket = (cumulative - lower_count) as f64;
+            if in_bucket == 0.0 {
+                return upper_bound;
+            }
+            let fraction = (rank - lower_count as f64) / in_bucket;
+            return lower_bound + (upper_bound - lower_bound) * fraction;
+        }
+        lower_bound = upper_bound;
+        lower_count = cumulative;
+    }
+
+    // Above the largest bucket; report its bound rather than guess
+    lower_bound
+}
+
+/// Whether a reading reflects the last collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricStatus {
+    /// Measured on the last collection
+    Ok,
+    /// The source failed on the last collection; the value is the last good one
+    Stale,
+    /// Never measured, or the last good value is too old to show
+    Unavailable,
+}
+
+/// A metric that may not have been measurable. `value` is `None` whenever
+/// `status` is [`MetricStatus::Unavailable`], so a failed source never reads as 0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Reading<T> {
+    pub value: Option<T>,
+    pub status: MetricStatus,
+}
+
+impl<T> Reading<T> {
+    pub fn ok(value: T) -> Self {
+        Self { value: Some(value), status: MetricStatus::Ok }
+    }
+
+    pub fn unavailable() -> Self {
+        Self { value: None, status: MetricStatus::Unavailable }
+    }
+}
+
+/// Last good value of a sampled metric and whether the latest sample worked
+#[derive(Debug)]
+struct Sample<T> {
+    last: Option<(T, Instant)>,
+    failing: bool,
+}
+
+impl<T: Copy> Sample<T> {
+    fn new() -> Self {
+        Self { last: None, failing: false }
+    }
+
+    fn record(&mut self, sample: Result<T>) -> Option<T> {
+        match sample {
+            Ok(value) => {
+                self.last = Some((value, Instant::now()));
+                self.failing = false;
+                Some(value)
+            }
+            Err(_) => {
+                self.failing = true;
+                None
+            }
+        }
+    }
+
+    fn reading(&self, max_age: Duration) -> Reading<T> {
+        match self.last {
+            Some((value, at)) if at.elapsed() <= max_age => Reading {
+                value: Some(value),
+                status: if self.failing { MetricStatus::Stale } else { MetricStatus::Ok },
+            },
+            _ => Reading::unavailable(),
+        }
+    }
+}
+
+/// Where system resource readings come from
+pub trait SystemSource: Send + Sync + std::fmt::Debug {
+    /// CPU time this process has used, in seconds
+    fn cpu_time(&self) -> Result<f64>;
+    /// Resident memory of this process, in bytes
+    fn memory_usage(&self) -> Result<u64>;
+}
+
+/// Reads this process's usage from `/proc/self` (Linux only)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcSource;
+
+impl ProcSource {
+    /// Kernel clock ticks per second `/proc` reports CPU time in
+    const USER_HZ: f64 = 100.0;
+}
+
+impl SystemSource for ProcSource {
+    fn cpu_time(&self) -> Result<f64> {
+        let stat = std::fs::read_to_string("/proc/self/stat")?;
+        // utime and stime are the 12th and 13th fields after the parenthesised command name
+        let fields: Vec<&str> = stat
+            .rsplit_once(')')
+            .map(|(_, rest)| rest.split_whitespace().collect())
+            .unwrap_or_default();
+        let ticks = |index: usize| -> Result<f64> {
+            fields
+                .get(index)
+                .and_then(|field| field.parse::<u64>().ok())
+                .map(|ticks| ticks as f64)
+                .ok_or_else(|| Error::Metrics("Unexpected /proc/self/stat format".to_string()))
+        };
+        Ok((ticks(11)? + ticks(12)?) / Self::USER_HZ)
+    }
+
+    fn memory_usage(&self) -> Result<u64> {
+        let status = std::fs::read_to_string("/proc/self/status")?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|rss| rss.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .ok_or_else(|| Error::Metrics("No VmRSS in /proc/self/status".to_string()))
+    }
+}
+
+/// Sampled values behind the system gauges, with their freshness
+#[derive(Debug)]
+struct SystemSamples {
+    cpu_usage: Sample<f64>,
+    memory_usage: Sample<u64>,
+    /// CPU time at the previous collection, to turn into a usage percentage
+    cpu_time: Option<(f64, Instant)>,
+    hashrate: Option<f64>,
+}
+
 /// Mining-specific metrics
 #[derive(Debug, Clone)]
 pub struct MiningMetrics {
@@ -76,6 +263,9 @@ pub struct ConnectionMetrics {
     /// Messages sent/received
     pub messages_sent: IntCounter,
     pub messages_received: IntCounter,
+    /// Failed handshakes by reason; per-IP counts stay in the
+    /// [`crate::handshake_failures::HandshakeFailureTracker`] to keep label cardinality bounded
+    pub handshake_failures: IntCounterVec,
 }
 
 /// System resource metrics
@@ -110,6 +300,27 @@ pub struct BusinessMetrics {
     pub profitability: Gauge,
 }
 
+/// Protocol translation metrics, labelled by direction and message type
+#[derive(Debug, Clone)]
+pub struct TranslationMetrics {
+    /// Translations attempted
+    pub attempted: IntCounterVec,
+    /// Translations that produced a target-protocol message
+    pub succeeded: IntCounterVec,
+    /// Messages passed through untranslated
+    pub fallback: IntCounterVec,
+    /// Translations that returned an error
+    pub failed: IntCounterVec,
+}
+
+/// Outcome of a single protocol translation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationOutcome {
+    Succeeded,
+    Fallback,
+    Failed,
+}
+
 /// Main metrics collector
 #[derive(Debug)]
 pub struct MetricsCollector {
@@ -119,8 +330,11 @@ pub struct MetricsCollector {
     connections: ConnectionMetrics,
     system: SystemMetrics,
     business: BusinessMetrics,
+    translation: TranslationMetrics,
     start_time: Instant,
     last_collection: Arc<RwLock<Instant>>,
+    system_source: Arc<dyn SystemSource>,
+    samples: std::sync::Mutex<SystemSamples>,
 }
 
 impl MetricsCollector {
@@ -166,7 +380,7 @@ impl MetricsCollector {
             share_validation_time: Histogram::with_opts(
                 HistogramOpts::new("sv2_share_validation_seconds", "Share validation time")
                     .const_labels(config.labels.clone())
-                    .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0])
+                    .buckets(SHARE_VALIDATION_BUCKETS.to_vec())
             )?,
         };
 
@@ -205,6 +419,11 @@ impl MetricsCollector {
                 Opts::new("sv2_messages_received_total", "Messages received")
                     .const_labels(config.labels.clone())
             )?,
+            handshake_failures: IntCounterVec::new(
+                Opts::new("sv2_handshake_failures_total", "Handshakes that failed")
+                    .const_labels(config.labels.clone()),
+                &["reason"],
+            )?,
         };
 
         // Create system metrics
@@ -263,6 +482,31 @@ impl MetricsCollector {
             )?,
         };
 
+        // Create translation metrics
+        let translation_labels = &["direction", "message_type"];
+        let translation = TranslationMetrics {
+            attempted: IntCounterVec::new(
+                Opts::new("sv2_translations_attempted_total", "Protocol translations attempted")
+                    .const_labels(config.labels.clone()),
+                translation_labels,
+            )?,
+            succeeded: IntCounterVec::new(
+                Opts::new("sv2_translations_succeeded_total", "Protocol translations succeeded")
+                    .const_labels(config.labels.clone()),
+                translation_labels,
+            )?,
+            fallback: IntCounterVec::new(
+                Opts::new("sv2_translations_fallback_total", "Messages passed through without translation")
+                    .const_labels(config.labels.clone()),
+                translation_labels,
+            )?,
+            failed: IntCounterVec::new(
+                Opts::new("sv2_translations_failed_total", "Protocol translations failed")
+                    .const_labels(config.labels.clone()),
+                translation_labels,
+            )?,
+        };
+
         // Register all metrics
         registry.register(Box::new(mining.shares_submitted.clone()))?;
         registry.register(Box::new(mining.shares_accepted.clone()))?;
@@ -282,6 +526,7 @@ impl MetricsCollector {
         registry.register(Box::new(connections.connection_duration.clone()))?;
         registry.register(Box::new(connections.messages_sent.clone()))?;
         registry.register(Box::new(connections.messages_received.clone()))?;
+        registry.register(Box::new(connections.handshake_failures.clone()))?;
 
         registry.register(Box::new(system.cpu_usage.clone()))?;
         registry.register(Box::new(system.memory_usage.clone()))?;
@@ -297,6 +542,11 @@ impl MetricsCollector {
         registry.register(Box::new(business.pool_fees.clone()))?;
         registry.register(Box::new(business.profitability.clone()))?;
 
+        registry.register(Box::new(translation.attempted.clone()))?;
+        registry.register(Box::new(translation.succeeded.clone()))?;
+        registry.register(Box::new(translation.fallback.clone()))?;
+        registry.register(Box::new(translation.failed.clone()))?;
+
         let start_time = Instant::now();
         let last_collection = Arc::new(RwLock::new(start_time));
 
@@ -307,11 +557,30 @@ impl MetricsCollector {
             connections,
             system,
             business,
+            translation,
             start_time,
             last_collection,
+            system_source: Arc::new(ProcSource),
+            samples: std::sync::Mutex::new(SystemSamples {
+                cpu_usage: Sample::new(),
+                memory_usage: Sample::new(),
+                cpu_time: None,
+                hashrate: None,
+            }),
         })
     }
 
+    /// Read system resources from `source` instead of `/proc`
+    pub fn with_system_source(mut self, source: Arc<dyn SystemSource>) -> Self {
+        self.system_source = source;
+        self
+    }
+
+    /// How long a sampled value is shown after its source stops answering
+    fn max_sample_age(&self) -> Duration {
+        Duration::from_secs(self.config.collection_interval.max(1) * 3)
+    }
+
     /// Get mining metrics
     pub fn mining(&self) -> &MiningMetrics {
         &self.mining
@@ -332,8 +601,19 @@ impl MetricsCollector {
         &self.business
     }
 
+    /// Get translation metrics
+    pub fn translation(&self) -> &TranslationMetrics {
+        &self.translation
+    }
+
     /// Record a share submission
     pub fn record_share(&self, difficulty: f64, is_valid: bool, is_block: bool, validation_time: Duration) {
+        self.record_share_outcome(difficulty, is_valid, is_block);
+        self.mining.share_validation_time.observe(validation_time.as_secs_f64());
+    }
+
+    /// Count a share and its difficulty, without a validation time
+    fn record_share_outcome(&self, difficulty: f64, is_valid: bool, is_block: bool) {
         self.mining.shares_submitted.inc();
         
         if is_valid {
@@ -347,7 +627,6 @@ impl MetricsCollector {
         }
         
         self.mining.share_difficulty.observe(difficulty);
-        self.mining.share_validation_time.observe(validation_time.as_secs_f64());
         
         // Update acceptance rate
         let total = self.mining.shares_submitted.get() as f64;
@@ -357,6 +636,16 @@ impl MetricsCollector {
         }
     }
 
+    /// Record the time spent validating a single share
+    pub fn record_share_validation(&self, elapsed: Duration) {
+        self.mining.share_validation_time.observe(elapsed.as_secs_f64());
+    }
+
+    /// p50/p95/p99 share validation latency since startup
+    pub fn share_validation_latency(&self) -> LatencyQuantiles {
+        LatencyQuantiles::from_histogram(&self.mining.share_validation_time)
+    }
+
     /// Record connection event
     pub fn record_connection(&self, protocol: &str, is_new: bool) {
         if is_new {
@@ -391,6 +680,28 @@ impl MetricsCollector {
         }
     }
 
+    /// Count shares and connections published on `bus` until the bus is dropped.
+    /// Validation latency isn't on the bus; handlers record it directly.
+    pub fn subscribe(self: &Arc<Self>, bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        let collector = Arc::clone(self);
+        bus.spawn_subscriber("Metrics collector", move |event| match event {
+            Event::ShareProcessed { difficulty, result, .. } => {
+                let is_block = matches!(result, ShareResult::Block(_));
+                let is_valid = is_block || matches!(result, ShareResult::Valid | ShareResult::Accepted);
+                collector.record_share_outcome(difficulty, is_valid, is_block);
+            }
+            Event::ConnectionOpened { protocol, .. } => collector.record_connection(protocol_label(protocol), true),
+            Event::ConnectionClosed { protocol, connected_at, .. } => {
+                let duration = (chrono::Utc::now() - connected_at).to_std().unwrap_or_default();
+                collector.record_connection_close(protocol_label(protocol), duration);
+            }
+            Event::HandshakeFailed { reason, .. } => {
+                collector.connections.handshake_failures.with_label_values(&[reason.as_str()]).inc();
+            }
+            _ => {}
+        })
+    }
+
     /// Record message sent/received
     pub fn record_message(&self, sent: bool) {
         if sent {
@@ -400,9 +711,22 @@ impl MetricsCollector {
         }
     }
 
+    /// Record the outcome of a protocol translation
+    pub fn record_translation(&self, direction: &str, message_type: &str, outcome: TranslationOutcome) {
+        let labels = &[direction, message_type];
+        self.translation.attempted.with_label_values(labels).inc();
+
+        match outcome {
+            TranslationOutcome::Succeeded => self.translation.succeeded.with_label_values(labels).inc(),
+            TranslationOutcome::Fallback => self.translation.fallback.with_label_values(labels).inc(),
+            TranslationOutcome::Failed => self.translation.failed.with_label_values(labels).inc(),
+        }
+    }
+
     /// Update hashrate
     pub fn update_hashrate(&self, hashrate: f64) {
         self.mining.hashrate.set(hashrate);
+        self.samples.lock().unwrap().hashrate = Some(hashrate);
     }
 
     /// Update system metrics
@@ -415,11 +739,42 @@ impl MetricsCollector {
         let uptime = self.start_time.elapsed().as_secs_f64();
         self.system.uptime.set(uptime);
 
-        // TODO: Implement actual system monitoring
-        // For now, we'll use placeholder values
-        // In a real implementation, you would use system monitoring libraries
-        // like sysinfo or procfs to get actual system metrics
-        
+        // A failing source marks its reading stale rather than failing the collection
+        let cpu_time = self.system_source.cpu_time();
+        let memory_usage = self.system_source.memory_usage();
+        if let Err(e) = &cpu_time {
+            tracing::debug!("CPU usage unavailable: {}", e);
+        }
+        if let Err(e) = &memory_usage {
+            tracing::debug!("Memory usage unavailable: {}", e);
+        }
+
+        let mut samples = self.samples.lock().unwrap();
+        if let Some(bytes) = samples.memory_usage.record(memory_usage) {
+            self.system.memory_usage.set(bytes as f64);
+        }
+        let now = Instant::now();
+        let cpu_usage = match (cpu_time, samples.cpu_time) {
+            (Ok(cpu), Some((previous, at))) => {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                samples.cpu_time = Some((cpu, now));
+                if elapsed > 0.0 {
+                    samples.cpu_usage.record(Ok((cpu - previous).max(0.0) / elapsed * 100.0))
+                } else {
+                    None
+                }
+            }
+            // Usage needs two readings; the first only sets the baseline
+            (Ok(cpu), None) => {
+                samples.cpu_time = Some((cpu, now));
+                None
+            }
+            (Err(e), _) => samples.cpu_usage.record(Err(e)),
+        };
+        if let Some(percent) = cpu_usage {
+            self.system.cpu_usage.set(percent);
+        }
+
         Ok(())
     }
 
@@ -444,22 +799,31 @@ impl MetricsCollector {
 
     /// Get metrics summary
     pub fn get_summary(&self) -> MetricsSummary {
+        let samples = self.samples.lock().unwrap();
         MetricsSummary {
             shares_submitted: self.mining.shares_submitted.get(),
             shares_accepted: self.mining.shares_accepted.get(),
             shares_rejected: self.mining.shares_rejected.get(),
             blocks_found: self.mining.blocks_found.get(),
-            hashrate: self.mining.hashrate.get(),
+            hashrate: samples.hashrate.map_or_else(Reading::unavailable, Reading::ok),
+            cpu_usage: samples.cpu_usage.reading(self.max_sample_age()),
+            memory_usage: samples.memory_usage.reading(self.max_sample_age()),
             acceptance_rate: self.mining.acceptance_rate.get(),
             active_connections: self.connections.active_connections.get(),
             total_connections: self.connections.total_connections.get(),
             sv1_connections: self.connections.sv1_connections.get(),
             sv2_connections: self.connections.sv2_connections.get(),
             uptime: self.system.uptime.get(),
+            share_validation: self.share_validation_latency(),
         }
     }
 }
 
+/// Protocol label `record_connection` expects
+fn protocol_label(protocol: Protocol) -> &'static str {
+    if protocol.is_sv2() { "sv2" } else { "sv1" }
+}
+
 /// Metrics summary for API responses
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MetricsSummary {
@@ -467,19 +831,26 @@ pub struct MetricsSummary {
     pub shares_accepted: u64,
     pub shares_rejected: u64,
     pub blocks_found: u64,
-    pub hashrate: f64,
+    /// Unavailable until a mode reports one
+    pub hashrate: Reading<f64>,
+    /// Percent of one core
+    pub cpu_usage: Reading<f64>,
+    /// Resident memory in bytes
+    pub memory_usage: Reading<u64>,
     pub acceptance_rate: f64,
     pub active_connections: i64,
     pub total_connections: u64,
     pub sv1_connections: i64,
     pub sv2_connections: i64,
     pub uptime: f64,
+    pub share_validation: LatencyQuantiles,
 }
 
 /// Metrics service for background collection
 pub struct MetricsService {
     collector: Arc<MetricsCollector>,
     collection_interval: Duration,
+    idle: Option<Arc<IdleMonitor>>,
 }
 
 impl MetricsService {
@@ -487,19 +858,27 @@ impl MetricsService {
         Self {
             collector,
             collection_interval,
+            idle: None,
         }
     }
 
+    /// Collect less often while `idle` sees no miners
+    pub fn with_idle(mut self, idle: Arc<IdleMonitor>) -> Self {
+        self.idle = Some(idle);
+        self
+    }
+
     /// Start the metrics collection service
     pub async fn start(&self) -> Result<()> {
-        let mut interval = tokio::time::interval(self.collection_interval);
-        
         loop {
-            interval.tick().await;
-            
             if let Err(e) = self.collector.collect_metrics().await {
                 tracing::error!("Failed to collect metrics: {}", e);
             }
+
+            match &self.idle {
+                Some(idle) => idle.sleep(self.collection_interval).await,
+                None => tokio::time::sleep(self.collection_interval).await,
+            }
         }
     }
 }
@@ -507,6 +886,7 @@ impl MetricsService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::handshake_failures::HandshakeFailureReason;
     use std::time::Duration;
 
     #[tokio::test]
@@ -541,6 +921,26 @@ mod tests {
         assert_eq!(collector.mining.acceptance_rate.get(), 50.0);
     }
 
+    #[test]
+    fn test_share_validation_latency_quantiles() {
+        let collector = MetricsCollector::new(MetricsConfig::default()).unwrap();
+        assert_eq!(collector.share_validation_latency(), LatencyQuantiles::default());
+
+        // 90 fast validations and 10 slow ones
+        for _ in 0..90 {
+            collector.record_share_validation(Duration::from_micros(200));
+        }
+        for _ in 0..10 {
+            collector.record_share_validation(Duration::from_millis(40));
+        }
+
+        let latency = collector.share_validation_latency();
+        assert_eq!(latency.count, 100);
+        assert!(latency.p50 > 0.0001 && latency.p50 <= 0.00025);
+        assert!(latency.p95 > 0.025 && latency.p95 <= 0.05);
+        assert!(latency.p99 > latency.p95 && latency.p99 <= 0.05);
+    }
+
     #[tokio::test]
     async fn test_connection_recording() {
         let config = MetricsConfig::default();
@@ -570,6 +970,36 @@ mod tests {
         assert_eq!(collector.connections.sv1_connections.get(), 1);
     }
 
+    #[tokio::test]
+    async fn test_failed_translation_recording() {
+        use crate::protocol::{ProtocolMessage, ProtocolTranslator};
+        use crate::types::Protocol;
+
+        let collector = Arc::new(MetricsCollector::new(MetricsConfig::default()).unwrap());
+        let translator = ProtocolTranslator::new(Protocol::Sv1).with_metrics(Arc::clone(&collector));
+
+        let submit = ProtocolMessage::Sv1Submit {
+            worker: "worker1".to_string(),
+            job_id: String::new(),
+            nonce: 0x12345678,
+        };
+        assert!(translator.translate(submit, Protocol::Sv2).is_err());
+
+        let labels = &["sv1_to_sv2", "sv1.submit"];
+        assert_eq!(collector.translation().attempted.with_label_values(labels).get(), 1);
+        assert_eq!(collector.translation().failed.with_label_values(labels).get(), 1);
+        assert_eq!(collector.translation().succeeded.with_label_values(labels).get(), 0);
+
+        // Messages without a translation are counted as fallbacks
+        let authorize = ProtocolMessage::Sv1Authorize {
+            username: "worker1".to_string(),
+            password: "x".to_string(),
+        };
+        assert!(translator.translate(authorize, Protocol::Sv2).is_ok());
+        let labels = &["sv1_to_sv2", "sv1.authorize"];
+        assert_eq!(collector.translation().fallback.with_label_values(labels).get(), 1);
+    }
+
     #[tokio::test]
     async fn test_prometheus_export() {
         let config = MetricsConfig::default();
@@ -600,6 +1030,103 @@ mod tests {
         assert_eq!(summary.shares_submitted, 1);
         assert_eq!(summary.shares_accepted, 1);
         assert_eq!(summary.active_connections, 1);
-        assert_eq!(summary.hashrate, 1000000.0);
+        assert_eq!(summary.hashrate, Reading::ok(1000000.0));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_follow_the_event_bus() {
+        let collector = Arc::new(MetricsCollector::new(MetricsConfig::default()).unwrap());
+        let bus = EventBus::default();
+        let _subscription = collector.subscribe(&bus);
+
+        let connection_id = uuid::Uuid::new_v4();
+        bus.publish(Event::ConnectionOpened {
+            connection_id,
+            address: "127.0.0.1:3333".parse().unwrap(),
+            protocol: Protocol::Sv2,
+        });
+        bus.share_processed(connection_id, 1000.0, &ShareResult::Valid, Vec::new());
+        bus.share_processed(connection_id, 1000.0, &ShareResult::Invalid("stale".to_string()), Vec::new());
+        bus.publish(Event::HandshakeFailed {
+            address: "10.0.0.7:40000".parse().unwrap(),
+            reason: HandshakeFailureReason::UnsupportedVersion,
+        });
+
+        let handshake_failures = collector.connections.handshake_failures.with_label_values(&["unsupported_version"]);
+        for _ in 0..50 {
+            if handshake_failures.get() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(collector.mining.shares_accepted.get(), 1);
+        assert_eq!(collector.mining.shares_rejected.get(), 1);
+        assert_eq!(collector.connections.sv2_connections.get(), 1);
+        assert_eq!(collector.connections.handshake_failures.with_label_values(&["malformed"]).get(), 0);
+        // Validation latency comes from the handlers, not the bus
+        assert_eq!(collector.share_validation_latency().count, 0);
+    }
+
+    /// System source whose readings can be switched off
+    #[derive(Debug, Default)]
+    struct FakeSource {
+        failing: std::sync::atomic::AtomicBool,
+    }
+
+    impl SystemSource for FakeSource {
+        fn cpu_time(&self) -> Result<f64> {
+            self.memory_usage().map(|_| 1.5)
+        }
+
+        fn memory_usage(&self) -> Result<u64> {
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                Err(Error::Metrics("source down".to_string()))
+            } else {
+                Ok(64 * 1024 * 1024)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_source_is_unavailable_not_zero() {
+        let source = Arc::new(FakeSource::default());
+        source.failing.store(true, std::sync::atomic::Ordering::SeqCst);
+        let collector = MetricsCollector::new(MetricsConfig::default())
+            .unwrap()
+            .with_system_source(source.clone());
+
+        // Nothing measured yet, hashrate included
+        let summary = collector.get_summary();
+        assert_eq!(summary.hashrate, Reading::unavailable());
+        assert_eq!(summary.memory_usage, Reading::unavailable());
+
+        // The collection still succeeds
+        collector.collect_metrics().await.unwrap();
+        collector.collect_metrics().await.unwrap();
+        let summary = collector.get_summary();
+        assert_eq!(summary.cpu_usage, Reading::unavailable());
+        assert_eq!(summary.memory_usage, Reading::unavailable());
+    }
+
+    #[tokio::test]
+    async fn test_source_failure_after_success_is_stale() {
+        let source = Arc::new(FakeSource::default());
+        let collector = MetricsCollector::new(MetricsConfig::default())
+            .unwrap()
+            .with_system_source(source.clone());
+
+        collector.collect_metrics().await.unwrap();
+        collector.collect_metrics().await.unwrap();
+        let summary = collector.get_summary();
+        assert_eq!(summary.memory_usage, Reading::ok(64 * 1024 * 1024));
+        // Same CPU time twice: an idle process, which is a real 0
+        assert_eq!(summary.cpu_usage, Reading::ok(0.0));
+
+        source.failing.store(true, std::sync::atomic::Ordering::SeqCst);
+        collector.collect_metrics().await.unwrap();
+        let summary = collector.get_summary();
+        assert_eq!(summary.memory_usage.status, MetricStatus::Stale);
+        assert_eq!(summary.memory_usage.value, Some(64 * 1024 * 1024));
+        assert_eq!(summary.cpu_usage.status, MetricStatus::Stale);
     }
 }
\ No newline at end of file
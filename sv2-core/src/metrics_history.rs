@@ -0,0 +1,239 @@
+//! Short in-memory metrics history for trend charts
+//!
+//! [`MiningStats`] and [`PerformanceMetrics`] only describe the present. The
+//! API server samples them every `resolution_secs` into a fixed-size ring
+//! buffer served at `GET /api/v1/metrics/history`, so the dashboard can draw
+//! graphs without running Prometheus. The oldest sample is dropped once the
+//! buffer holds `capacity` samples, which keeps memory bounded.
+//!
+//! sv2-web has no live stats to sample, so it rebuilds the same history from
+//! the stored share time series with [`MetricsHistory::from_share_buckets`].
+
+use crate::database::ShareBucket;
+use crate::types::{MiningStats, PerformanceMetrics};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Upper bound on `capacity`: a day at 10s resolution
+pub const MAX_HISTORY_SAMPLES: usize = 8640;
+
+/// `[monitoring.metrics.history]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsHistoryConfig {
+    pub enabled: bool,
+    /// Seconds between samples
+    pub resolution_secs: u64,
+    /// Samples kept; the default is one hour at 10s resolution
+    pub capacity: usize,
+}
+
+impl Default for MetricsHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            resolution_secs: 10,
+            capacity: 360,
+        }
+    }
+}
+
+impl MetricsHistoryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.resolution_secs == 0 {
+            return Err(Error::Config("metrics.history.resolution_secs must be greater than 0".to_string()));
+        }
+        if self.capacity == 0 || self.capacity > MAX_HISTORY_SAMPLES {
+            return Err(Error::Config(format!(
+                "metrics.history.capacity must be between 1 and {}, got {}",
+                MAX_HISTORY_SAMPLES, self.capacity
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Key metrics at one point in time
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSample {
+    pub timestamp: DateTime<Utc>,
+    pub hashrate: f64,
+    pub shares_per_minute: f64,
+    pub acceptance_rate: f64,
+    pub shares_accepted: u64,
+    pub shares_rejected: u64,
+    pub blocks_found: u64,
+    /// System metrics, when the daemon collects them
+    pub cpu_usage: Option<f64>,
+    pub memory_usage: Option<u64>,
+    pub open_connections: Option<u64>,
+}
+
+impl MetricsSample {
+    pub fn new(timestamp: DateTime<Utc>, mining: &MiningStats, performance: Option<&PerformanceMetrics>) -> Self {
+        Self {
+            timestamp,
+            hashrate: mining.hashrate,
+            shares_per_minute: mining.shares_per_minute,
+            acceptance_rate: mining.acceptance_rate,
+            shares_accepted: mining.shares_accepted,
+            shares_rejected: mining.shares_rejected,
+            blocks_found: mining.blocks_found,
+            cpu_usage: performance.map(|p| p.cpu_usage),
+            memory_usage: performance.map(|p| p.memory_usage),
+            open_connections: performance.map(|p| p.open_connections),
+        }
+    }
+
+    /// A sample covering one bucket of stored shares
+    ///
+    /// Hashrate, blocks and system metrics are not stored per bucket, so
+    /// they are zero or `None`.
+    pub fn from_share_bucket(bucket: &ShareBucket, bucket_seconds: u32) -> Self {
+        let total = bucket.accepted + bucket.rejected;
+        let acceptance_rate = if total > 0 {
+            (bucket.accepted as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+        Self {
+            timestamp: bucket.start,
+            hashrate: 0.0,
+            shares_per_minute: total as f64 * 60.0 / bucket_seconds.max(1) as f64,
+            acceptance_rate,
+            shares_accepted: bucket.accepted,
+            shares_rejected: bucket.rejected,
+            blocks_found: 0,
+            cpu_usage: None,
+            memory_usage: None,
+            open_connections: None,
+        }
+    }
+}
+
+/// Fixed-size ring buffer of [`MetricsSample`]s, oldest first
+#[derive(Debug, Clone)]
+pub struct MetricsHistory {
+    resolution_secs: u64,
+    capacity: usize,
+    samples: VecDeque<MetricsSample>,
+}
+
+impl MetricsHistory {
+    pub fn new(config: &MetricsHistoryConfig) -> Self {
+        let capacity = config.capacity.clamp(1, MAX_HISTORY_SAMPLES);
+        Self {
+            resolution_secs: config.resolution_secs,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// A history rebuilt from share buckets `resolution_secs` wide, oldest first
+    pub fn from_share_buckets(config: &MetricsHistoryConfig, buckets: &[ShareBucket]) -> Self {
+        let bucket_seconds = u32::try_from(config.resolution_secs).unwrap_or(u32::MAX);
+        let mut history = Self::new(config);
+        for bucket in buckets {
+            history.push(MetricsSample::from_share_bucket(bucket, bucket_seconds));
+        }
+        history
+    }
+
+    /// Append a sample, dropping the oldest when full
+    pub fn push(&mut self, sample: MetricsSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The most recent `limit` samples (all when `None`), oldest first
+    pub fn snapshot(&self, limit: Option<usize>) -> MetricsHistorySnapshot {
+        let skip = limit.map_or(0, |limit| self.samples.len().saturating_sub(limit));
+        MetricsHistorySnapshot {
+            resolution_secs: self.resolution_secs,
+            capacity: self.capacity,
+            samples: self.samples.iter().skip(skip).cloned().collect(),
+        }
+    }
+}
+
+/// Response body of `GET /api/v1/metrics/history`
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsHistorySnapshot {
+    pub resolution_secs: u64,
+    pub capacity: usize,
+    pub samples: Vec<MetricsSample>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(second: i64, hashrate: f64) -> MetricsSample {
+        let mining = MiningStats { hashrate, ..MiningStats::default() };
+        MetricsSample::new(DateTime::from_timestamp(second, 0).unwrap(), &mining, None)
+    }
+
+    #[test]
+    fn test_history_caps_at_capacity_in_order() {
+        let config = MetricsHistoryConfig { capacity: 3, ..Default::default() };
+        let mut history = MetricsHistory::new(&config);
+        assert!(history.is_empty());
+
+        for i in 0..5 {
+            history.push(sample(i * 10, i as f64));
+        }
+        assert_eq!(history.len(), 3);
+
+        let snapshot = history.snapshot(None);
+        assert_eq!(snapshot.capacity, 3);
+        assert_eq!(snapshot.resolution_secs, 10);
+        let hashrates: Vec<f64> = snapshot.samples.iter().map(|s| s.hashrate).collect();
+        assert_eq!(hashrates, [2.0, 3.0, 4.0]);
+        assert!(snapshot.samples.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+
+        let latest: Vec<f64> = history.snapshot(Some(2)).samples.iter().map(|s| s.hashrate).collect();
+        assert_eq!(latest, [3.0, 4.0]);
+        assert_eq!(history.snapshot(Some(10)).samples.len(), 3);
+    }
+
+    #[test]
+    fn test_history_from_share_buckets() {
+        let config = MetricsHistoryConfig { resolution_secs: 60, capacity: 2 };
+        let bucket = |minute: i64, accepted, rejected| ShareBucket {
+            start: DateTime::from_timestamp(minute * 60, 0).unwrap(),
+            accepted,
+            rejected,
+        };
+        let buckets = [bucket(0, 9, 0), bucket(1, 3, 1), bucket(2, 0, 0)];
+
+        let snapshot = MetricsHistory::from_share_buckets(&config, &buckets).snapshot(None);
+        assert_eq!(snapshot.resolution_secs, 60);
+        assert_eq!(snapshot.samples.len(), 2);
+        assert_eq!(snapshot.samples[0].timestamp, buckets[1].start);
+        assert_eq!(snapshot.samples[0].shares_per_minute, 4.0);
+        assert_eq!(snapshot.samples[0].acceptance_rate, 75.0);
+        assert_eq!(snapshot.samples[0].shares_rejected, 1);
+        assert_eq!(snapshot.samples[1].acceptance_rate, 0.0);
+    }
+
+    #[test]
+    fn test_history_config_validation() {
+        MetricsHistoryConfig::default().validate().unwrap();
+        assert!(MetricsHistoryConfig { capacity: 0, ..Default::default() }.validate().is_err());
+        assert!(MetricsHistoryConfig { capacity: MAX_HISTORY_SAMPLES + 1, ..Default::default() }.validate().is_err());
+        assert!(MetricsHistoryConfig { resolution_secs: 0, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,481 @@
+//! Push-based metrics export to StatsD or InfluxDB
+//!
+//! Complements the Prometheus pull endpoint for operators running a push
+//! pipeline. Each flush takes one snapshot of [`MiningStats`] and
+//! [`PerformanceMetrics`] and sends it as a single batch: one or more UDP
+//! datagrams for StatsD, or one HTTP write for InfluxDB line protocol.
+//!
+//! The exporter itself is behind the `metrics-push` feature; configuration
+//! and formatting are always available so config files parse either way,
+//! but a build without the feature refuses `enabled = true` rather than
+//! silently pushing nothing.
+
+use crate::types::{MiningStats, PerformanceMetrics};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Wire format of the push endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushProtocol {
+    /// StatsD gauges over UDP, `endpoint` is `host:port`
+    Statsd,
+    /// InfluxDB line protocol over HTTP, `endpoint` is the full write URL
+    Influx,
+}
+
+/// `[metrics.push]` configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricsPushConfig {
+    /// Enable pushing metrics
+    pub enabled: bool,
+    pub protocol: PushProtocol,
+    /// `host:port` for StatsD, write URL for InfluxDB
+    /// (e.g. `http://localhost:8086/api/v2/write?org=pool&bucket=sv2`)
+    pub endpoint: String,
+    /// Seconds between flushes
+    pub flush_interval: u64,
+    /// Metric name prefix (StatsD) or measurement prefix (InfluxDB)
+    pub prefix: String,
+    /// Per-flush send timeout in milliseconds
+    pub timeout_ms: u64,
+}
+
+impl Default for MetricsPushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: PushProtocol::Statsd,
+            endpoint: "127.0.0.1:8125".to_string(),
+            flush_interval: 10,
+            prefix: "sv2d".to_string(),
+            timeout_ms: 2000,
+        }
+    }
+}
+
+impl MetricsPushConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !cfg!(feature = "metrics-push") {
+            return Err(Error::Config(
+                "metrics.push is enabled but this build lacks the metrics-push feature".to_string(),
+            ));
+        }
+        if self.endpoint.is_empty() {
+            return Err(Error::Config("metrics.push.endpoint is required when push is enabled".to_string()));
+        }
+        if self.flush_interval == 0 {
+            return Err(Error::Config("metrics.push.flush_interval must be greater than 0".to_string()));
+        }
+        if self.timeout_ms == 0 {
+            return Err(Error::Config("metrics.push.timeout_ms must be greater than 0".to_string()));
+        }
+        if self.protocol == PushProtocol::Influx && !self.endpoint.starts_with("http") {
+            return Err(Error::Config(format!(
+                "metrics.push.endpoint must be an http(s) URL for influx, got {}",
+                self.endpoint
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One flush worth of metrics
+#[derive(Debug, Clone, Default)]
+pub struct MetricsBatch {
+    pub mining: Option<MiningStats>,
+    pub performance: Option<PerformanceMetrics>,
+}
+
+impl MetricsBatch {
+    pub fn is_empty(&self) -> bool {
+        self.mining.is_none() && self.performance.is_none()
+    }
+
+    /// Named numeric fields grouped by measurement
+    fn fields(&self) -> Vec<(&'static str, Vec<(&'static str, f64)>)> {
+        let mut groups = Vec::new();
+
+        if let Some(m) = &self.mining {
+            let mut fields = vec![
+                ("hashrate", m.hashrate),
+                ("shares_per_minute", m.shares_per_minute),
+                ("acceptance_rate", m.acceptance_rate),
+                ("efficiency", m.efficiency),
+                ("uptime_seconds", m.uptime.as_secs_f64()),
+                ("shares_accepted", m.shares_accepted as f64),
+                ("shares_rejected", m.shares_rejected as f64),
+                ("blocks_found", m.blocks_found as f64),
+            ];
+            // Skipped rather than sent as 0 when it couldn't be computed
+            if let Some(expected_blocks) = m.expected_blocks {
+                fields.push(("expected_blocks", expected_blocks));
+            }
+            groups.push(("mining", fields));
+        }
+
+        if let Some(p) = &self.performance {
+            groups.push(("performance", vec![
+                ("cpu_usage", p.cpu_usage),
+                ("memory_usage", p.memory_usage as f64),
+                ("memory_total", p.memory_total as f64),
+                ("network_rx_bytes", p.network_rx_bytes as f64),
+                ("network_tx_bytes", p.network_tx_bytes as f64),
+                ("disk_usage", p.disk_usage as f64),
+                ("disk_total", p.disk_total as f64),
+                ("open_connections", p.open_connections as f64),
+                ("database_connections", p.database_connections as f64),
+            ]));
+        }
+
+        groups
+    }
+}
+
+/// Largest StatsD datagram payload, kept under a typical 1500 byte MTU
+pub const STATSD_MAX_PACKET: usize = 1432;
+
+/// Format a batch as StatsD gauges packed into as few datagrams as possible
+pub fn format_statsd(prefix: &str, batch: &MetricsBatch) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut current = String::new();
+
+    for (group, fields) in batch.fields() {
+        for (name, value) in fields {
+            let line = format!("{}.{}.{}:{}|g", prefix, group, name, value);
+            if !current.is_empty() && current.len() + 1 + line.len() > STATSD_MAX_PACKET {
+                packets.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&line);
+        }
+    }
+
+    if !current.is_empty() {
+        packets.push(current);
+    }
+    packets
+}
+
+/// Format a batch as InfluxDB line protocol, one line per measurement
+pub fn format_influx(prefix: &str, batch: &MetricsBatch, timestamp_ns: i64) -> String {
+    batch
+        .fields()
+        .into_iter()
+        .map(|(group, fields)| {
+            let fields = fields
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{}_{} {} {}", prefix, group, fields, timestamp_ns)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "metrics-push")]
+pub use exporter::{MetricsPusher, MetricsSource};
+
+#[cfg(feature = "metrics-push")]
+mod exporter {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+    use tracing::{debug, info, warn};
+
+    /// Supplies the metrics snapshot for each flush
+    #[async_trait]
+    pub trait MetricsSource: Send + Sync {
+        async fn mining_stats(&self) -> Option<MiningStats>;
+        async fn performance_metrics(&self) -> Option<PerformanceMetrics>;
+    }
+
+    /// Periodically pushes metrics from a [`MetricsSource`] to the configured
+    /// endpoint. Sends are bounded by `timeout_ms` and failures are logged and
+    /// dropped, so an unreachable endpoint never stalls the daemon.
+    pub struct MetricsPusher {
+        config: MetricsPushConfig,
+        source: Arc<dyn MetricsSource>,
+        http: reqwest::Client,
+    }
+
+    impl MetricsPusher {
+        pub fn new(config: MetricsPushConfig, source: Arc<dyn MetricsSource>) -> Result<Self> {
+            config.validate()?;
+            let http = reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.timeout_ms))
+                .build()
+                .map_err(|e| Error::Metrics(format!("Failed to build HTTP client: {}", e)))?;
+            Ok(Self { config, source, http })
+        }
+
+        /// Run the flush loop until the task is dropped
+        pub async fn run(&self) {
+            info!(
+                "Pushing metrics to {:?} endpoint {} every {}s",
+                self.config.protocol, self.config.endpoint, self.config.flush_interval
+            );
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.flush_interval));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut failing = false;
+
+            loop {
+                interval.tick().await;
+                match self.flush().await {
+                    Ok(()) if failing => {
+                        info!("Metrics push to {} recovered", self.config.endpoint);
+                        failing = false;
+                    }
+                    Ok(()) => {}
+                    // Only log the transition so a dead endpoint doesn't flood the logs
+                    Err(e) if !failing => {
+                        warn!("Metrics push to {} failed, dropping batch: {}", self.config.endpoint, e);
+                        failing = true;
+                    }
+                    Err(e) => debug!("Metrics push still failing: {}", e),
+                }
+            }
+        }
+
+        /// Collect one batch and send it
+        pub async fn flush(&self) -> Result<()> {
+            let batch = MetricsBatch {
+                mining: self.source.mining_stats().await,
+                performance: self.source.performance_metrics().await,
+            };
+            if batch.is_empty() {
+                return Ok(());
+            }
+
+            let timeout = Duration::from_millis(self.config.timeout_ms);
+            let send = async {
+                match self.config.protocol {
+                    PushProtocol::Statsd => self.send_statsd(&batch).await,
+                    PushProtocol::Influx => self.send_influx(&batch).await,
+                }
+            };
+
+            tokio::time::timeout(timeout, send)
+                .await
+                .map_err(|_| Error::Metrics(format!("Push timed out after {}ms", self.config.timeout_ms)))?
+        }
+
+        async fn send_statsd(&self, batch: &MetricsBatch) -> Result<()> {
+            let socket = UdpSocket::bind("0.0.0.0:0").await?;
+            socket.connect(&self.config.endpoint).await?;
+            for packet in format_statsd(&self.config.prefix, batch) {
+                socket.send(packet.as_bytes()).await?;
+            }
+            Ok(())
+        }
+
+        async fn send_influx(&self, batch: &MetricsBatch) -> Result<()> {
+            let timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+            let body = format_influx(&self.config.prefix, batch, timestamp);
+
+            let response = self
+                .http
+                .post(&self.config.endpoint)
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| Error::Metrics(format!("InfluxDB write failed: {}", e)))?;
+
+            if !response.status().is_success() {
+                return Err(Error::Metrics(format!("InfluxDB write returned {}", response.status())));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    fn sample_batch() -> MetricsBatch {
+        MetricsBatch {
+            mining: Some(MiningStats {
+                hashrate: 1.5e12,
+                shares_per_minute: 6.0,
+                acceptance_rate: 99.5,
+                efficiency: 99.5,
+                uptime: Duration::from_secs(120),
+                shares_accepted: 199,
+                shares_rejected: 1,
+                blocks_found: 0,
+                blocks_orphaned: 0,
+                expected_blocks: Some(0.25),
+                coinbase_tag: None,
+                network_difficulty: None,
+            }),
+            performance: Some(PerformanceMetrics {
+                cpu_usage: 12.5,
+                memory_usage: 1024,
+                memory_total: 4096,
+                network_rx_bytes: 10,
+                network_tx_bytes: 20,
+                disk_usage: 100,
+                disk_total: 1000,
+                open_connections: 3,
+                database_connections: 1,
+                timestamp: Utc::now(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_format_statsd() {
+        let packets = format_statsd("sv2d", &sample_batch());
+        assert_eq!(packets.len(), 1);
+
+        let lines: Vec<&str> = packets[0].lines().collect();
+        assert_eq!(lines.len(), 18);
+        assert!(lines.contains(&"sv2d.mining.hashrate:1500000000000|g"));
+        assert!(lines.contains(&"sv2d.performance.open_connections:3|g"));
+    }
+
+    #[test]
+    fn test_format_statsd_splits_large_batches() {
+        let packets = format_statsd(&"x".repeat(200), &sample_batch());
+        assert!(packets.len() > 1);
+        assert!(packets.iter().all(|p| p.len() <= STATSD_MAX_PACKET));
+        assert_eq!(packets.iter().map(|p| p.lines().count()).sum::<usize>(), 18);
+    }
+
+    #[test]
+    fn test_format_influx() {
+        let body = format_influx("sv2d", &sample_batch(), 42);
+        let lines: Vec<&str> = body.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("sv2d_mining hashrate=1500000000000,"));
+        assert!(lines[0].ends_with(" 42"));
+        assert!(lines[1].starts_with("sv2d_performance cpu_usage=12.5,"));
+    }
+
+    #[test]
+    fn test_push_config_validation() {
+        assert!(MetricsPushConfig::default().validate().is_ok());
+
+        let config = MetricsPushConfig { enabled: true, flush_interval: 0, ..Default::default() };
+        assert!(config.validate().is_err());
+
+        let config = MetricsPushConfig {
+            enabled: true,
+            protocol: PushProtocol::Influx,
+            endpoint: "localhost:8086".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(not(feature = "metrics-push"))]
+    #[test]
+    fn test_push_needs_the_feature() {
+        let config = MetricsPushConfig { enabled: true, ..Default::default() };
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("metrics-push feature"), "{}", error);
+    }
+
+    #[cfg(feature = "metrics-push")]
+    mod exporter {
+        use super::*;
+        use async_trait::async_trait;
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::{TcpListener, UdpSocket};
+
+        struct FixedSource(MetricsBatch);
+
+        #[async_trait]
+        impl MetricsSource for FixedSource {
+            async fn mining_stats(&self) -> Option<MiningStats> {
+                self.0.mining.clone()
+            }
+            async fn performance_metrics(&self) -> Option<PerformanceMetrics> {
+                self.0.performance.clone()
+            }
+        }
+
+        fn pusher(protocol: PushProtocol, endpoint: String) -> MetricsPusher {
+            let config = MetricsPushConfig {
+                enabled: true,
+                protocol,
+                endpoint,
+                timeout_ms: 500,
+                ..Default::default()
+            };
+            MetricsPusher::new(config, Arc::new(FixedSource(sample_batch()))).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_statsd_push_to_udp_sink() {
+            let sink = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let pusher = pusher(PushProtocol::Statsd, sink.local_addr().unwrap().to_string());
+
+            pusher.flush().await.unwrap();
+
+            let mut buf = vec![0u8; STATSD_MAX_PACKET];
+            let len = tokio::time::timeout(Duration::from_secs(2), sink.recv(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            let packet = String::from_utf8_lossy(&buf[..len]);
+            assert!(packet.contains("sv2d.mining.shares_accepted:199|g"));
+        }
+
+        #[tokio::test]
+        async fn test_influx_push_to_http_sink() {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/api/v2/write?bucket=sv2", listener.local_addr().unwrap());
+
+            let sink = tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read until the line-protocol body has arrived
+                while !String::from_utf8_lossy(&request).contains("sv2d_performance") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                stream
+                    .write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+                String::from_utf8_lossy(&request).to_string()
+            });
+
+            pusher(PushProtocol::Influx, url).flush().await.unwrap();
+
+            let request = sink.await.unwrap();
+            assert!(request.starts_with("POST /api/v2/write?bucket=sv2"));
+            assert!(request.contains("sv2d_mining hashrate=1500000000000,"));
+        }
+
+        #[tokio::test]
+        async fn test_unreachable_endpoint_fails_fast() {
+            // Bind then drop to get a port with nothing listening
+            let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+            let pusher = pusher(PushProtocol::Influx, format!("http://127.0.0.1:{}/write", port));
+
+            let started = std::time::Instant::now();
+            assert!(pusher.flush().await.is_err());
+            assert!(started.elapsed() < Duration::from_secs(2));
+        }
+    }
+}
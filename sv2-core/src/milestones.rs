@@ -0,0 +1,185 @@
+//! One-time setup milestones
+//!
+//! The first share accepted and the first block found each raise a single
+//! informational alert, so a new solo miner gets confirmation the setup
+//! works. Reached milestones are stored in the database and don't fire again
+//! after a restart.
+
+use crate::{database::DatabaseOps, types::{Alert, AlertSeverity}, ConnectionId, Result, ShareResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Milestone {
+    FirstShare,
+    FirstBlock,
+}
+
+impl Milestone {
+    /// Name the milestone is stored under
+    pub fn key(&self) -> &'static str {
+        match self {
+            Milestone::FirstShare => "first_share",
+            Milestone::FirstBlock => "first_block",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "first_share" => Some(Milestone::FirstShare),
+            "first_block" => Some(Milestone::FirstBlock),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Milestone::FirstShare => "Your miner is working \u{2014} first share accepted",
+            Milestone::FirstBlock => "You found your first block!",
+        }
+    }
+}
+
+/// A milestone and when it was reached
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MilestoneRecord {
+    pub milestone: Milestone,
+    pub reached_at: DateTime<Utc>,
+    /// Connection, block hash or similar context for the milestone
+    pub detail: Option<String>,
+}
+
+/// Raises each milestone once per database
+#[derive(Debug, Default)]
+pub struct MilestoneTracker {
+    /// Milestones already stored, so shares after the first skip the database
+    reached: Mutex<HashSet<Milestone>>,
+}
+
+impl MilestoneTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the milestones a processed share reaches, returning those it
+    /// reached first
+    pub async fn record_share(
+        &self,
+        database: &dyn DatabaseOps,
+        connection_id: ConnectionId,
+        result: &ShareResult,
+    ) -> Vec<MilestoneRecord> {
+        let reached = match result {
+            ShareResult::Accepted | ShareResult::Valid => {
+                vec![self.reach_record(database, Milestone::FirstShare, connection_id.to_string()).await]
+            }
+            ShareResult::Block(block_hash) => vec![
+                self.reach_record(database, Milestone::FirstShare, connection_id.to_string()).await,
+                self.reach_record(database, Milestone::FirstBlock, block_hash.to_string()).await,
+            ],
+            _ => Vec::new(),
+        };
+        reached.into_iter().flatten().collect()
+    }
+
+    /// Store `milestone` and alert if nothing had reached it before. Returns
+    /// whether this call was the first.
+    pub async fn reach(&self, database: &dyn DatabaseOps, milestone: Milestone, detail: String) -> bool {
+        self.reach_record(database, milestone, detail).await.is_some()
+    }
+
+    async fn reach_record(
+        &self,
+        database: &dyn DatabaseOps,
+        milestone: Milestone,
+        detail: String,
+    ) -> Option<MilestoneRecord> {
+        let mut reached = self.reached.lock().await;
+        if reached.contains(&milestone) {
+            return None;
+        }
+
+        let record = MilestoneRecord {
+            milestone,
+            reached_at: Utc::now(),
+            detail: Some(detail),
+        };
+        let first = match database.record_milestone(&record).await {
+            Ok(first) => first,
+            Err(e) => {
+                // Try again on the next share rather than lose the milestone
+                tracing::debug!("Could not record milestone {}: {}", milestone.key(), e);
+                return None;
+            }
+        };
+        reached.insert(milestone);
+
+        if !first {
+            return None;
+        }
+        tracing::info!("🎉 {}", milestone.message());
+        if let Err(e) = raise_alert(database, &record).await {
+            tracing::debug!("Could not record milestone alert: {}", e);
+        }
+        Some(record)
+    }
+}
+
+async fn raise_alert(database: &dyn DatabaseOps, record: &MilestoneRecord) -> Result<()> {
+    database
+        .create_alert(&Alert {
+            id: Uuid::new_v4(),
+            severity: AlertSeverity::Info,
+            message: record.milestone.message().to_string(),
+            timestamp: record.reached_at,
+            acknowledged: false,
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+    use bitcoin::hashes::Hash;
+
+    #[tokio::test]
+    async fn test_milestones_fire_once_across_restarts() {
+        let database = MockDatabaseOps::new();
+        let connection_id = Uuid::new_v4();
+
+        let tracker = MilestoneTracker::new();
+        tracker.record_share(&database, connection_id, &ShareResult::Invalid("low".to_string())).await;
+        assert!(database.get_milestones().await.unwrap().is_empty());
+
+        let reached = tracker.record_share(&database, connection_id, &ShareResult::Valid).await;
+        assert_eq!(reached.iter().map(|record| record.milestone).collect::<Vec<_>>(), [Milestone::FirstShare]);
+        assert!(tracker.record_share(&database, connection_id, &ShareResult::Valid).await.is_empty());
+        assert_eq!(database.get_alerts(None, None).await.unwrap().len(), 1);
+
+        // A restarted daemon starts with a fresh tracker on the same database
+        let restarted = MilestoneTracker::new();
+        restarted.record_share(&database, connection_id, &ShareResult::Valid).await;
+        let block = ShareResult::Block(bitcoin::BlockHash::all_zeros());
+        restarted.record_share(&database, connection_id, &block).await;
+        restarted.record_share(&database, connection_id, &block).await;
+
+        let alerts = database.get_alerts(None, None).await.unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts.iter().all(|alert| alert.severity == AlertSeverity::Info));
+
+        let milestones: HashSet<_> = database.get_milestones().await.unwrap()
+            .into_iter()
+            .map(|record| record.milestone)
+            .collect();
+        assert_eq!(milestones, HashSet::from([Milestone::FirstShare, Milestone::FirstBlock]));
+
+        assert!(!MilestoneTracker::new().reach(&database, Milestone::FirstBlock, "again".to_string()).await);
+    }
+}
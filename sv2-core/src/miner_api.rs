@@ -0,0 +1,208 @@
+//! Version-tolerant parsing of miner API responses.
+//!
+//! Firmware renames fields and changes hashrate units between releases, so
+//! every detail is looked up under each name it has been seen under, and
+//! hashrates are converted to H/s before they reach [`MinerDetails`].
+
+use crate::scanner::MinerDetails;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HashrateUnit {
+    MegaHash,
+    GigaHash,
+}
+
+impl HashrateUnit {
+    fn to_hashes_per_second(self, value: f64) -> f64 {
+        match self {
+            HashrateUnit::MegaHash => value * 1e6,
+            HashrateUnit::GigaHash => value * 1e9,
+        }
+    }
+}
+
+/// Hashrate fields in lookup order: AxeOS reports GH/s, CGMiner summaries
+/// GH/s or MH/s depending on the model
+const HASHRATE_FIELDS: &[(&str, HashrateUnit)] = &[
+    ("hashRate", HashrateUnit::GigaHash),
+    ("hashrate", HashrateUnit::GigaHash),
+    ("hashRate_1m", HashrateUnit::GigaHash),
+    ("GHS 5s", HashrateUnit::GigaHash),
+    ("GHS av", HashrateUnit::GigaHash),
+    ("MHS 5s", HashrateUnit::MegaHash),
+    ("MHS av", HashrateUnit::MegaHash),
+];
+
+/// A single miner reporting more than this many GH/s is really reporting H/s
+const MAX_PLAUSIBLE_GIGAHASH: f64 = 1e7;
+
+const HOSTNAME_FIELDS: &[&str] = &["hostname", "hostName"];
+const FIRMWARE_FIELDS: &[&str] = &["version", "firmwareVersion", "axeOSVersion"];
+const MAC_FIELDS: &[&str] = &["macAddr", "mac", "MAC"];
+const TEMPERATURE_FIELDS: &[&str] = &["temp", "temperature", "chipTemp", "Temperature"];
+const POWER_FIELDS: &[&str] = &["power", "powerConsumption", "Power"];
+const POOL_URL_FIELDS: &[&str] = &["stratumURL", "stratumUrl", "URL"];
+const WORKER_FIELDS: &[&str] = &["stratumUser", "stratum_user", "User"];
+const SHARES_ACCEPTED_FIELDS: &[&str] = &["sharesAccepted", "Accepted"];
+
+/// How a response matched the known layouts
+#[derive(Debug, Default, PartialEq)]
+pub struct ParseReport {
+    /// Whether any known field was present
+    pub recognized: bool,
+    /// Details found under an older or alternate field name, or converted
+    /// from an unexpected unit
+    pub fallbacks: Vec<String>,
+}
+
+impl ParseReport {
+    /// Whether the response differed from the layout current firmware uses
+    pub fn unexpected(&self) -> bool {
+        !self.recognized || !self.fallbacks.is_empty()
+    }
+
+    fn note(&mut self, fields: &[&str], index: usize) {
+        self.recognized = true;
+        if index > 0 {
+            self.fallbacks.push(format!("{} as {}", fields[0], fields[index]));
+        }
+    }
+}
+
+/// Fill empty fields of `details` from an API response. CGMiner-style
+/// responses are read from their first `SUMMARY` entry.
+pub fn apply_response(details: &mut MinerDetails, json: &Value) -> ParseReport {
+    let json = json.pointer("/SUMMARY/0").unwrap_or(json);
+    let mut report = ParseReport::default();
+
+    let mut text = |target: &mut Option<String>, fields: &[&str]| {
+        if target.is_none() {
+            if let Some((value, index)) = find(json, fields, Value::as_str) {
+                *target = Some(value.to_string());
+                report.note(fields, index);
+            }
+        }
+    };
+    text(&mut details.hostname, HOSTNAME_FIELDS);
+    text(&mut details.firmware_version, FIRMWARE_FIELDS);
+    text(&mut details.mac_address, MAC_FIELDS);
+    text(&mut details.pool_url, POOL_URL_FIELDS);
+    text(&mut details.worker_name, WORKER_FIELDS);
+
+    let mut number = |target: &mut Option<f64>, fields: &[&str]| {
+        if target.is_none() {
+            if let Some((value, index)) = find(json, fields, as_number) {
+                *target = Some(value);
+                report.note(fields, index);
+            }
+        }
+    };
+    number(&mut details.temperature, TEMPERATURE_FIELDS);
+    number(&mut details.power_consumption, POWER_FIELDS);
+
+    if details.shares_accepted.is_none() {
+        if let Some((value, index)) = find(json, SHARES_ACCEPTED_FIELDS, as_number) {
+            details.shares_accepted = Some(value.max(0.0) as u64);
+            report.note(SHARES_ACCEPTED_FIELDS, index);
+        }
+    }
+
+    if details.hashrate.is_none() {
+        let names: Vec<&str> = HASHRATE_FIELDS.iter().map(|(name, _)| *name).collect();
+        if let Some((value, index)) = find(json, &names, as_number) {
+            let (name, unit) = HASHRATE_FIELDS[index];
+            report.note(&names, index);
+            details.hashrate = Some(if unit == HashrateUnit::GigaHash && value > MAX_PLAUSIBLE_GIGAHASH {
+                report.fallbacks.push(format!("{} already in H/s", name));
+                value
+            } else {
+                unit.to_hashes_per_second(value)
+            });
+        }
+    }
+
+    report
+}
+
+/// First field in `fields` with a usable value, and its index
+fn find<'a, T>(json: &'a Value, fields: &[&str], read: impl Fn(&'a Value) -> Option<T>) -> Option<(T, usize)> {
+    fields
+        .iter()
+        .enumerate()
+        .find_map(|(index, field)| json.get(field).and_then(&read).map(|value| (value, index)))
+}
+
+/// Numbers, or numbers sent as strings
+fn as_number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .filter(|n: &f64| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bitaxe_firmware_layouts() {
+        // AxeOS 2.x: one /api/system/info response, hashrate in GH/s
+        let current = json!({
+            "hostname": "bitaxe-01",
+            "version": "v2.4.0",
+            "macAddr": "AA:BB:CC:DD:EE:FF",
+            "hashRate": 712.5,
+            "temp": 58.5,
+            "power": 14.2,
+            "stratumURL": "192.168.1.5",
+            "stratumUser": "bc1qexample.bitaxe-01",
+            "sharesAccepted": 1523
+        });
+        let mut details = MinerDetails::default();
+        let report = apply_response(&mut details, &current);
+        assert!(!report.unexpected());
+        assert_eq!(details.hashrate, Some(712.5e9));
+        assert_eq!(details.temperature, Some(58.5));
+        assert_eq!(details.worker_name.as_deref(), Some("bc1qexample.bitaxe-01"));
+        assert_eq!(details.shares_accepted, Some(1523));
+
+        // Older firmware: different names, numbers sent as strings
+        let older = json!({
+            "hostName": "bitaxe-02",
+            "firmwareVersion": "v1.0.3",
+            "hashrate": "480.0",
+            "temperature": "61",
+            "stratumUrl": "192.168.1.5"
+        });
+        let mut details = MinerDetails::default();
+        let report = apply_response(&mut details, &older);
+        assert!(report.recognized);
+        assert!(report.unexpected());
+        assert!(report.fallbacks.contains(&"hashRate as hashrate".to_string()));
+        assert_eq!(details.hostname.as_deref(), Some("bitaxe-02"));
+        assert_eq!(details.hashrate, Some(480e9));
+        assert_eq!(details.temperature, Some(61.0));
+        assert_eq!(details.pool_url.as_deref(), Some("192.168.1.5"));
+    }
+
+    #[test]
+    fn test_hashrate_units_are_normalized() {
+        // A fork reporting H/s under the GH/s field name
+        let mut details = MinerDetails::default();
+        let report = apply_response(&mut details, &json!({"hashRate": 1.2e12}));
+        assert_eq!(details.hashrate, Some(1.2e12));
+        assert_eq!(report.fallbacks, ["hashRate already in H/s"]);
+
+        // CGMiner summary in MH/s
+        let mut details = MinerDetails::default();
+        apply_response(&mut details, &json!({"STATUS": [], "SUMMARY": [{"MHS 5s": 95_000_000.0, "Accepted": 88}]}));
+        assert_eq!(details.hashrate, Some(95e12));
+        assert_eq!(details.shares_accepted, Some(88));
+
+        let mut details = MinerDetails::default();
+        assert!(!apply_response(&mut details, &json!({"uptime": 10})).recognized);
+        assert_eq!(details.hashrate, None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Result, Connection, Share, ShareResult, WorkTemplate};
+use crate::{Result, Connection, HandshakeDetails, Share, ShareResult, WorkTemplate};
 use async_trait::async_trait;
 
 /// Mode-specific handler interface
@@ -13,6 +13,9 @@ pub trait ModeHandler: Send + Sync {
     /// Handle a new connection
     async fn handle_connection(&self, conn: Connection) -> Result<()>;
 
+    /// Record the details negotiated once a connection's handshake completes
+    async fn handle_handshake(&self, connection_id: crate::ConnectionId, details: HandshakeDetails) -> Result<()>;
+
     /// Process a submitted share
     async fn process_share(&self, share: Share) -> Result<ShareResult>;
 
@@ -25,6 +28,13 @@ pub trait ModeHandler: Send + Sync {
     /// Get mode-specific statistics
     async fn get_statistics(&self) -> Result<crate::MiningStats>;
 
+    /// Mode-specific health: the upstream pool in client mode, template
+    /// freshness and node sync in solo mode, connected miners in pool mode
+    async fn health(&self) -> crate::health::HealthCheck;
+
+    /// Workers currently mining through this mode
+    async fn workers(&self) -> Vec<crate::types::Worker>;
+
     /// Validate mode-specific configuration
     fn validate_config(&self, config: &crate::config::DaemonConfig) -> Result<()>;
 }
@@ -5,35 +5,63 @@ use crate::{
     modes::{SoloModeHandler, PoolModeHandler, ProxyModeHandler, ClientModeHandler},
     database::{DatabasePool, DatabaseOps},
     bitcoin_rpc::BitcoinRpcClient,
+    dead_letter::DeadLetterLog,
+    events::EventBus,
+    handshake_failures::HandshakeFailureTracker,
+    idle::IdleMonitor,
+    protocol::NetworkProtocolMessage,
+    server::StratumServer,
+    webhooks::WebhookNotifier,
+    worker_bans::WorkerBans,
 };
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
 use tracing::{info, warn, error};
 
+/// How often shares past the retention window are pruned
+const SHARE_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 /// Factory for creating mode handlers
 pub struct ModeHandlerFactory;
 
 impl ModeHandlerFactory {
-    /// Create a new mode handler based on configuration
+    /// Create a new mode handler based on configuration, publishing its events on `events`
     pub fn create_handler(
         config: &DaemonConfig,
         database: Arc<DatabasePool>,
+        events: &EventBus,
     ) -> Result<Box<dyn ModeHandler>> {
         info!("Creating mode handler for {} mode", config.mode);
         
+        let dead_letter = Arc::new(DeadLetterLog::new(config.database.dead_letter_path.clone()));
+        let idle = Arc::new(IdleMonitor::new(config.idle.clone()));
         let handler: Box<dyn ModeHandler> = match &config.mode {
             OperationModeConfig::Solo(solo_config) => {
                 let bitcoin_client = BitcoinRpcClient::new(config.bitcoin.clone());
-                Box::new(SoloModeHandler::new(solo_config.clone(), bitcoin_client, database))
+                Box::new(SoloModeHandler::new(solo_config.clone(), bitcoin_client, database)
+                    .with_share_logging(config.logging.shares.clone())
+                    .with_dead_letter(dead_letter)
+                    .with_share_sampling(config.database.accepted_share_sample_rate)
+                    .with_events(events.clone())
+                    .with_idle(idle))
             }
             OperationModeConfig::Pool(pool_config) => {
                 let bitcoin_client = BitcoinRpcClient::new(config.bitcoin.clone());
-                Box::new(PoolModeHandler::new(pool_config.clone(), bitcoin_client, database))
+                Box::new(PoolModeHandler::new(pool_config.clone(), bitcoin_client, database)
+                    .with_share_logging(config.logging.shares.clone())
+                    .with_dead_letter(dead_letter)
+                    .with_share_sampling(config.database.accepted_share_sample_rate)
+                    .with_events(events.clone())
+                    .with_idle(idle))
             }
             OperationModeConfig::Proxy(proxy_config) => {
                 Box::new(ProxyModeHandler::new(proxy_config.clone(), database))
             }
             OperationModeConfig::Client(client_config) => {
-                Box::new(ClientModeHandler::new(client_config.clone(), database))
+                Box::new(ClientModeHandler::new(client_config.clone(), database)
+                    .with_dead_letter(dead_letter)
+                    .with_share_sampling(config.database.accepted_share_sample_rate))
             }
         };
         
@@ -180,6 +208,13 @@ pub struct ModeRouter {
     current_handler: Option<Box<dyn ModeHandler>>,
     database: Arc<DatabasePool>,
     config: Option<DaemonConfig>,
+    /// Shared by every handler the router creates, so subscribers outlive mode switches
+    events: EventBus,
+    webhooks: Option<tokio::task::JoinHandle<()>>,
+    handshake_failures: Option<(Arc<HandshakeFailureTracker>, tokio::task::JoinHandle<()>)>,
+    idle: Option<(Arc<IdleMonitor>, tokio::task::JoinHandle<()>)>,
+    worker_bans: Arc<WorkerBans>,
+    share_pruning: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl ModeRouter {
@@ -189,15 +224,127 @@ impl ModeRouter {
             current_handler: None,
             database,
             config: None,
+            events: EventBus::default(),
+            webhooks: None,
+            handshake_failures: None,
+            idle: None,
+            worker_bans: Arc::new(WorkerBans::default()),
+            share_pruning: None,
+        }
+    }
+
+    /// Bus the current and future mode handlers publish their events on
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// (Re)start the webhook notifier for `config`
+    fn subscribe_webhooks(&mut self, config: &DaemonConfig) -> Result<()> {
+        if let Some(previous) = self.webhooks.take() {
+            previous.abort();
+        }
+        if config.webhooks.enabled {
+            info!("Posting webhook events to {}", config.webhooks.url);
+            let notifier = WebhookNotifier::spawn(config.webhooks.clone())?;
+            self.webhooks = Some(notifier.subscribe(&self.events));
+        }
+        Ok(())
+    }
+
+    /// Handshake failures counted from the bus, once the router is initialized
+    pub fn handshake_failures(&self) -> Option<&Arc<HandshakeFailureTracker>> {
+        self.handshake_failures.as_ref().map(|(tracker, _)| tracker)
+    }
+
+    /// Start counting handshake failures, keeping the counts unless `config` changes how
+    fn track_handshake_failures(&mut self, config: &DaemonConfig) {
+        let wanted = &config.network.handshake_failures;
+        if self.handshake_failures.as_ref().is_some_and(|(tracker, _)| tracker.config() == wanted) {
+            return;
+        }
+        if let Some((_, previous)) = self.handshake_failures.take() {
+            previous.abort();
+        }
+        let tracker = Arc::new(HandshakeFailureTracker::new(wanted.clone()));
+        let database: Arc<dyn DatabaseOps> = self.database.clone();
+        let subscription = tracker.subscribe(&self.events, database);
+        self.handshake_failures = Some((tracker, subscription));
+    }
+
+    /// Miners connected through whichever mode is running, for pacing work
+    /// outside the handlers, once the router is initialized
+    pub fn idle(&self) -> Option<&Arc<IdleMonitor>> {
+        self.idle.as_ref().map(|(idle, _)| idle)
+    }
+
+    /// Start following connections on the bus, keeping the monitor unless `config` changes how
+    fn track_idle(&mut self, config: &DaemonConfig) {
+        if self.idle.as_ref().is_some_and(|(idle, _)| idle.config() == &config.idle) {
+            return;
+        }
+        if let Some((_, previous)) = self.idle.take() {
+            previous.abort();
+        }
+        let idle = Arc::new(IdleMonitor::new(config.idle.clone()));
+        let subscription = idle.subscribe(&self.events);
+        self.idle = Some((idle, subscription));
+    }
+
+    /// (Re)start pruning shares past `config`'s retention window, first
+    /// straight away and then every hour
+    fn prune_shares(&mut self, config: &DaemonConfig) {
+        if let Some(previous) = self.share_pruning.take() {
+            previous.abort();
         }
+        let Some(retention) = config.database.share_retention() else {
+            return;
+        };
+        let database = Arc::clone(&self.database);
+        self.share_pruning = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SHARE_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                match database.prune_shares_older_than(chrono::Utc::now() - retention).await {
+                    Ok(0) => {}
+                    Ok(pruned) => info!("Pruned {} shares older than {} days", pruned, retention.num_days()),
+                    Err(e) => error!("Failed to prune shares: {}", e),
+                }
+            }
+        }));
+    }
+
+    /// Banned workers, loaded from the database when the router is
+    /// initialized; hand this to the API server so bans take effect at once
+    pub fn worker_bans(&self) -> &Arc<WorkerBans> {
+        &self.worker_bans
+    }
+
+    /// Stratum server for the current config, publishing failed handshakes
+    /// on the router's bus so they're counted and rejecting banned workers
+    pub fn stratum_server(
+        &self,
+        message_tx: mpsc::UnboundedSender<NetworkProtocolMessage>,
+    ) -> Result<StratumServer> {
+        let config = self.config.as_ref()
+            .ok_or_else(|| Error::System("Mode router not initialized".to_string()))?;
+        Ok(StratumServer::from_config(&config.network, message_tx)
+            .with_events(self.events.clone())
+            .with_worker_bans(Arc::clone(&self.worker_bans)))
     }
 
     /// Initialize with a configuration
     pub async fn initialize(&mut self, config: DaemonConfig) -> Result<()> {
         info!("Initializing mode router with {} mode", config.mode);
         
-        let handler = ModeHandlerFactory::create_handler(&config, Arc::clone(&self.database))?;
+        // Load worker bans before any miner can submit
+        self.worker_bans = Arc::new(WorkerBans::load(self.database.as_ref()).await?);
+        
+        let handler = ModeHandlerFactory::create_handler(&config, Arc::clone(&self.database), &self.events)?;
         handler.start().await?;
+        self.subscribe_webhooks(&config)?;
+        self.track_handshake_failures(&config);
+        self.track_idle(&config);
+        self.prune_shares(&config);
         
         self.current_handler = Some(handler);
         self.config = Some(config);
@@ -235,8 +382,12 @@ impl ModeRouter {
 
         // Create and start new handler
         info!("Creating new mode handler");
-        let new_handler = ModeHandlerFactory::create_handler(&new_config, Arc::clone(&self.database))?;
+        let new_handler = ModeHandlerFactory::create_handler(&new_config, Arc::clone(&self.database), &self.events)?;
         new_handler.start().await?;
+        self.subscribe_webhooks(&new_config)?;
+        self.track_handshake_failures(&new_config);
+        self.track_idle(&new_config);
+        self.prune_shares(&new_config);
 
         // Restore state if we had any
         if let Some(state) = preserved_state {
@@ -281,6 +432,14 @@ impl ModeRouter {
         self.current_handler.as_ref().map(|h| h.as_ref())
     }
 
+    /// Health of the current mode, for `DaemonStatus::mode_health`
+    pub async fn health(&self) -> Option<crate::health::HealthCheck> {
+        match &self.current_handler {
+            Some(handler) => Some(handler.health().await),
+            None => None,
+        }
+    }
+
     /// Get the current configuration
     pub fn get_config(&self) -> Option<&DaemonConfig> {
         self.config.as_ref()
@@ -293,6 +452,9 @@ impl ModeRouter {
         if let Some(handler) = self.current_handler.take() {
             handler.stop().await?;
         }
+        if let Some(pruning) = self.share_pruning.take() {
+            pruning.abort();
+        }
         
         self.config = None;
         
@@ -315,6 +477,9 @@ mod tests {
             max_connections: 5,
             connection_timeout: 30,
             enable_migrations: true,
+            dead_letter_path: "sv2d_dead_letter.jsonl".into(),
+            accepted_share_sample_rate: 1,
+            share_retention_days: 30,
         }
     }
 
@@ -324,8 +489,14 @@ mod tests {
             network: NetworkConfig {
                 bind_address: "127.0.0.1:0".parse().unwrap(),
                 max_connections: 100,
+                listen_backlog: 1024,
                 connection_timeout: 30,
                 keepalive_interval: 60,
+                capture: Default::default(),
+                welcome: Default::default(),
+                handshake_failures: Default::default(),
+            connection_rate_limit: Default::default(),
+                drain_timeout_secs: 10,
             },
             bitcoin: BitcoinConfig {
                 rpc_url: "http://localhost:18443".to_string(),
@@ -343,10 +514,133 @@ mod tests {
                 health_check_interval: 30,
                 metrics: crate::config::MetricsConfig::default(),
                 health: crate::config::HealthConfig::default(),
+                difficulty_unit: Default::default(),
+                connection_quality: Default::default(),
             },
             logging: crate::config::LoggingConfig::default(),
             security: crate::config::SecurityConfig::default(),
+            webhooks: Default::default(),
+            idle: Default::default(),
+        }
+    }
+
+    /// Router running client mode, which starts without a node or upstream
+    async fn client_router(mut config: DaemonConfig) -> ModeRouter {
+        let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+        database.migrate().await.unwrap();
+        config.mode = OperationModeConfig::Client(crate::config::ClientConfig::default());
+
+        let mut router = ModeRouter::new(Arc::new(database));
+        router.initialize(config).await.unwrap();
+        router
+    }
+
+    /// A free local port for a server to listen on
+    async fn free_address() -> std::net::SocketAddr {
+        tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stratum_server_counts_failed_handshakes() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        assert!(ModeRouter::new(Arc::new(DatabasePool::new("sqlite::memory:", 1).await.unwrap()))
+            .stratum_server(tx.clone())
+            .is_err());
+
+        let mut config = create_test_config(OperationModeConfig::Solo(SoloConfig::default()));
+        config.network.bind_address = free_address().await;
+        let router = client_router(config.clone()).await;
+        let mut server = router.stratum_server(tx).unwrap();
+        let shutdown_tx = server.shutdown_handle();
+        let server_task = tokio::spawn(async move { server.start().await });
+
+        let mut browser = None;
+        for _ in 0..50 {
+            if let Ok(stream) = tokio::net::TcpStream::connect(config.network.bind_address).await {
+                browser = Some(stream);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let mut browser = browser.expect("server never listened");
+        tokio::io::AsyncWriteExt::write_all(&mut browser, b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+
+        let tracker = router.handshake_failures().unwrap();
+        for _ in 0..50 {
+            if !tracker.counts().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(tracker.counts().len(), 1);
+
+        shutdown_tx.send(()).await.unwrap();
+        server_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_follows_connections_in_any_mode() {
+        let mut config = create_test_config(OperationModeConfig::Solo(SoloConfig::default()));
+        config.idle.after_secs = 0;
+        let router = client_router(config).await;
+        let idle = router.idle().unwrap();
+        assert!(idle.is_idle_at(std::time::Instant::now()));
+
+        router.events().publish(crate::events::Event::ConnectionOpened {
+            connection_id: uuid::Uuid::new_v4(),
+            address: "127.0.0.1:3333".parse().unwrap(),
+            protocol: crate::types::Protocol::Sv2,
+        });
+        for _ in 0..50 {
+            if !idle.is_idle_at(std::time::Instant::now()) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!idle.is_idle_at(std::time::Instant::now()));
+    }
+
+    #[tokio::test]
+    async fn test_bans_stored_before_initialize_are_loaded() {
+        let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+        database.migrate().await.unwrap();
+        WorkerBans::new().ban(&database, "rig1", None).await.unwrap();
+
+        let config = create_test_config(OperationModeConfig::Client(crate::config::ClientConfig::default()));
+        let mut router = ModeRouter::new(Arc::new(database));
+        assert!(!router.worker_bans().is_banned("rig1"));
+        router.initialize(config).await.unwrap();
+        assert!(router.worker_bans().is_banned("rig1"));
+        assert!(!router.worker_bans().is_banned("rig2"));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_schedules_share_pruning() {
+        let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+        database.migrate().await.unwrap();
+        let now = chrono::Utc::now();
+        for (nonce, age_days) in [(1, 45), (2, 1)] {
+            let mut share = crate::types::Share::new(uuid::Uuid::new_v4(), nonce, 1_700_000_000, 1.0);
+            share.submitted_at = now - chrono::Duration::days(age_days);
+            database.create_share(&share).await.unwrap();
+        }
+        let database = Arc::new(database);
+
+        let mut config = create_test_config(OperationModeConfig::Client(crate::config::ClientConfig::default()));
+        config.database.share_retention_days = 30;
+        let mut router = ModeRouter::new(Arc::clone(&database));
+        router.initialize(config).await.unwrap();
+
+        // The first prune runs as soon as the router starts
+        let mut nonces = Vec::new();
+        for _ in 0..50 {
+            nonces = database.get_shares(None, None).await.unwrap().iter().map(|s| s.nonce).collect();
+            if nonces.len() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
+        assert_eq!(nonces, vec![2]);
     }
 
     #[test]
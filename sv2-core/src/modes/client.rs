@@ -1,32 +1,56 @@
 use crate::{
     Result, Error, Connection, Share, ShareResult, WorkTemplate, ConnectionId, MiningStats,
-    config::{DaemonConfig, ClientConfig}, database::DatabaseOps,
-    types::{ConnectionInfo, Worker, Job, UpstreamStatus, ConnectionState, BlockTemplate},
+    config::{DaemonConfig, ClientConfig, UpstreamPool}, database::DatabaseOps,
+    dead_letter::DeadLetterLog,
+    health::{HealthCheck, HealthStatus},
+    share_sampling::ShareSampler,
+    types::{ConnectionInfo, HandshakeDetails, Worker, Job, UpstreamStatus, ConnectionState, BlockTemplate},
     mode::ModeHandler,
+    template_source::UpstreamTemplateSource,
+    stratum_url::StratumUrl,
+    protocol::SV2_HEADER_LEN,
+    sv2_codec::Sv2Message,
 };
 use bitcoin::hashes::Hash;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-// URL parsing will be done manually to avoid adding new dependencies
+
+/// Longest wait between reconnect attempts, unless `reconnect_interval` is
+/// longer still
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(300);
 
 /// Client mode handler for connecting to upstream SV2 pools
 pub struct ClientModeHandler {
     config: ClientConfig,
+    /// `config.upstream_pools` in failover order
+    pools: Arc<Vec<UpstreamPool>>,
+    /// Index into `pools` of the active upstream, or the last one used
+    active_pool: Arc<AtomicUsize>,
     database: Arc<dyn DatabaseOps>,
     connections: Arc<RwLock<HashMap<ConnectionId, ConnectionInfo>>>,
     workers: Arc<RwLock<HashMap<ConnectionId, Worker>>>,
     upstream_connection: Arc<RwLock<Option<TcpStream>>>,
+    /// Bytes read from the upstream that don't make up a whole frame yet
+    upstream_buffer: Arc<Mutex<Vec<u8>>>,
     upstream_status: Arc<RwLock<UpstreamStatus>>,
     current_template: Arc<RwLock<Option<WorkTemplate>>>,
+    /// Publishes upstream work to [`UpstreamTemplateSource`] subscribers
+    template_publisher: tokio::sync::watch::Sender<Option<WorkTemplate>>,
+    template_source: UpstreamTemplateSource,
     custom_templates: Arc<RwLock<HashMap<uuid::Uuid, BlockTemplate>>>,
     job_negotiation_token: Arc<RwLock<Option<String>>>,
     reconnect_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     stats: Arc<RwLock<MiningStats>>,
+    /// Where shares go when the database can't store them
+    dead_letter: Option<Arc<DeadLetterLog>>,
+    /// Which accepted shares get a row
+    share_sampler: ShareSampler,
     start_time: Instant,
     job_negotiation_enabled: bool,
 }
@@ -37,27 +61,37 @@ impl ClientModeHandler {
         config: ClientConfig,
         database: Arc<dyn DatabaseOps>,
     ) -> Self {
+        let pools: Vec<UpstreamPool> = config.pools_by_priority().into_iter().cloned().collect();
         let upstream_status = UpstreamStatus {
-            url: config.upstream_pool.url.clone(),
+            url: pools.first().map(|pool| pool.url.clone()).unwrap_or_default(),
+            priority: pools.first().map_or(0, |pool| pool.priority),
             connected: false,
             last_connected: None,
             connection_attempts: 0,
             last_error: None,
+            reconnect_backoff: None,
             latency: None,
             shares_submitted: 0,
             shares_accepted: 0,
             shares_rejected: 0,
+            failovers: 0,
         };
+        let (template_publisher, template_source) = UpstreamTemplateSource::channel();
 
         Self {
             job_negotiation_enabled: config.enable_job_negotiation,
             config,
+            pools: Arc::new(pools),
+            active_pool: Arc::new(AtomicUsize::new(0)),
             database,
             connections: Arc::new(RwLock::new(HashMap::new())),
             workers: Arc::new(RwLock::new(HashMap::new())),
             upstream_connection: Arc::new(RwLock::new(None)),
+            upstream_buffer: Arc::new(Mutex::new(Vec::new())),
             upstream_status: Arc::new(RwLock::new(upstream_status)),
             current_template: Arc::new(RwLock::new(None)),
+            template_publisher,
+            template_source,
             custom_templates: Arc::new(RwLock::new(HashMap::new())),
             job_negotiation_token: Arc::new(RwLock::new(None)),
             reconnect_task: Arc::new(Mutex::new(None)),
@@ -70,11 +104,34 @@ impl ClientModeHandler {
                 shares_accepted: 0,
                 shares_rejected: 0,
                 blocks_found: 0,
+                blocks_orphaned: 0,
+                expected_blocks: None,
+                coinbase_tag: None,
+                network_difficulty: None,
             })),
+            dead_letter: None,
+            share_sampler: ShareSampler::default(),
             start_time: Instant::now(),
         }
     }
 
+    /// Dead-letter shares that fail to store instead of dropping them
+    pub fn with_dead_letter(mut self, dead_letter: Arc<DeadLetterLog>) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// Store one in `accepted_sample_rate` accepted shares
+    pub fn with_share_sampling(mut self, accepted_sample_rate: u64) -> Self {
+        self.share_sampler = ShareSampler::new(accepted_sample_rate);
+        self
+    }
+
+    /// Template source fed by this handler's upstream pool connection
+    pub fn template_source(&self) -> UpstreamTemplateSource {
+        self.template_source.clone()
+    }
+
     /// Start the upstream connection and reconnection management
     pub async fn start_upstream_connection(&self) -> Result<()> {
         // Start initial connection
@@ -88,10 +145,12 @@ impl ClientModeHandler {
             handle.abort();
         }
 
+        let pools = Arc::clone(&self.pools);
+        let active_pool = Arc::clone(&self.active_pool);
         let upstream_status = Arc::clone(&self.upstream_status);
         let upstream_connection = Arc::clone(&self.upstream_connection);
-        let config = self.config.clone();
         let reconnect_interval = Duration::from_secs(self.config.reconnect_interval);
+        let max_attempts = self.config.max_reconnect_attempts;
 
         let handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(reconnect_interval);
@@ -106,33 +165,16 @@ impl ClientModeHandler {
                 };
 
                 if needs_reconnect {
-                    tracing::info!("Attempting to reconnect to upstream pool: {}", config.upstream_pool.url);
-                    
-                    match Self::establish_connection(&config.upstream_pool.url).await {
-                        Ok(stream) => {
-                            {
-                                let mut connection = upstream_connection.write().await;
-                                *connection = Some(stream);
-                            }
-                            
-                            {
-                                let mut status = upstream_status.write().await;
-                                status.connected = true;
-                                status.last_connected = Some(chrono::Utc::now());
-                                status.connection_attempts += 1;
-                                status.last_error = None;
-                            }
-                            
-                            tracing::info!("Successfully reconnected to upstream pool");
-                        }
-                        Err(e) => {
-                            let mut status = upstream_status.write().await;
-                            status.connected = false;
-                            status.connection_attempts += 1;
-                            status.last_error = Some(e.to_string());
-                            
-                            tracing::error!("Failed to reconnect to upstream pool: {}", e);
-                        }
+                    let reconnected = Self::reconnect_with_backoff(
+                        &pools,
+                        &active_pool,
+                        &upstream_status,
+                        &upstream_connection,
+                        reconnect_interval,
+                        max_attempts,
+                    ).await;
+                    if !reconnected {
+                        break;
                     }
                 }
             }
@@ -142,6 +184,117 @@ impl ClientModeHandler {
         Ok(())
     }
 
+    /// Wait before reconnect attempt `attempt` (counting from 1): doubling
+    /// from `reconnect_interval`, up to [`MAX_RECONNECT_BACKOFF`]
+    fn reconnect_backoff(reconnect_interval: Duration, attempt: u64) -> Duration {
+        let cap = MAX_RECONNECT_BACKOFF.max(reconnect_interval);
+        let doublings = attempt.saturating_sub(1).min(31) as u32;
+        reconnect_interval.saturating_mul(1 << doublings).min(cap)
+    }
+
+    /// Reconnect, backing off between attempts, until connected or
+    /// `max_attempts` in a row have failed. Returns whether it connected.
+    async fn reconnect_with_backoff(
+        pools: &[UpstreamPool],
+        active_pool: &AtomicUsize,
+        upstream_status: &RwLock<UpstreamStatus>,
+        upstream_connection: &RwLock<Option<TcpStream>>,
+        reconnect_interval: Duration,
+        max_attempts: u32,
+    ) -> bool {
+        loop {
+            Self::reconnect(pools, active_pool, upstream_status, upstream_connection).await;
+
+            let mut status = upstream_status.write().await;
+            if status.connected {
+                return true;
+            }
+            if status.connection_attempts >= max_attempts as u64 {
+                let last_error = status.last_error.take().unwrap_or_default();
+                status.last_error = Some(format!(
+                    "Gave up reconnecting after {} attempts: {}", status.connection_attempts, last_error
+                ));
+                status.reconnect_backoff = None;
+                tracing::error!("Giving up on upstream pools after {} reconnect attempts", status.connection_attempts);
+                return false;
+            }
+            let backoff = Self::reconnect_backoff(reconnect_interval, status.connection_attempts);
+            status.reconnect_backoff = Some(backoff);
+            drop(status);
+
+            tracing::info!("Retrying upstream pools in {:?}", backoff);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    /// Fail over after losing the upstream: try the pools after the one that
+    /// dropped, in failover order, coming back round to it last
+    async fn reconnect(
+        pools: &[UpstreamPool],
+        active_pool: &AtomicUsize,
+        upstream_status: &RwLock<UpstreamStatus>,
+        upstream_connection: &RwLock<Option<TcpStream>>,
+    ) {
+        let previous = active_pool.load(Ordering::Relaxed);
+        tracing::info!("Attempting to reconnect to upstream pools after losing {}", pools[previous].url);
+
+        match Self::connect_in_order(pools, previous + 1).await {
+            Ok((index, stream)) => {
+                *upstream_connection.write().await = Some(stream);
+                Self::record_connected(pools, active_pool, upstream_status, index).await;
+                tracing::info!("Successfully reconnected to upstream pool {}", pools[index].url);
+            }
+            Err(e) => {
+                let mut status = upstream_status.write().await;
+                status.connected = false;
+                status.connection_attempts += 1;
+                status.last_error = Some(e.to_string());
+                
+                tracing::error!("Failed to reconnect to upstream pool: {}", e);
+            }
+        }
+    }
+
+    /// Connect to the first reachable pool, starting at `from` and wrapping
+    /// round, so every pool gets one try
+    async fn connect_in_order(pools: &[UpstreamPool], from: usize) -> Result<(usize, TcpStream)> {
+        let mut last_error = None;
+        for offset in 0..pools.len() {
+            let index = (from + offset) % pools.len();
+            match Self::establish_connection(&pools[index].url).await {
+                Ok(stream) => return Ok((index, stream)),
+                Err(e) => {
+                    tracing::warn!("Upstream pool {} unreachable: {}", pools[index].url, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::Config("No upstream pools configured".to_string())))
+    }
+
+    /// Make `pools[index]` the active upstream, counting a failover if it
+    /// replaces another
+    async fn record_connected(
+        pools: &[UpstreamPool],
+        active_pool: &AtomicUsize,
+        upstream_status: &RwLock<UpstreamStatus>,
+        index: usize,
+    ) {
+        let previous = active_pool.swap(index, Ordering::Relaxed);
+        let mut status = upstream_status.write().await;
+        if index != previous {
+            status.failovers += 1;
+            tracing::warn!("Failed over from upstream pool {} to {}", pools[previous].url, pools[index].url);
+        }
+        status.url = pools[index].url.clone();
+        status.priority = pools[index].priority;
+        status.connected = true;
+        status.last_connected = Some(chrono::Utc::now());
+        status.connection_attempts = 0;
+        status.last_error = None;
+        status.reconnect_backoff = None;
+    }
+
     /// Stop the reconnection task
     pub async fn stop_upstream_connection(&self) {
         let mut task_handle = self.reconnect_task.lock().await;
@@ -152,14 +305,23 @@ impl ClientModeHandler {
         // Close upstream connection
         let mut connection = self.upstream_connection.write().await;
         *connection = None;
+        self.upstream_buffer.lock().await.clear();
 
         let mut status = self.upstream_status.write().await;
         status.connected = false;
     }
 
-    /// Establish connection to upstream pool
+    /// Establish connection to the highest-priority reachable upstream pool
     async fn connect_to_upstream(&self) -> Result<()> {
-        let stream = Self::establish_connection(&self.config.upstream_pool.url).await?;
+        let (index, stream) = match Self::connect_in_order(&self.pools, 0).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                let mut status = self.upstream_status.write().await;
+                status.connection_attempts += 1;
+                status.last_error = Some(e.to_string());
+                return Err(e);
+            }
+        };
         
         // Perform SV2 handshake
         self.perform_sv2_handshake(&stream).await?;
@@ -168,18 +330,12 @@ impl ClientModeHandler {
         {
             let mut connection = self.upstream_connection.write().await;
             *connection = Some(stream);
+            self.upstream_buffer.lock().await.clear();
         }
 
-        // Update status
-        {
-            let mut status = self.upstream_status.write().await;
-            status.connected = true;
-            status.last_connected = Some(chrono::Utc::now());
-            status.connection_attempts += 1;
-            status.last_error = None;
-        }
+        Self::record_connected(&self.pools, &self.active_pool, &self.upstream_status, index).await;
 
-        tracing::info!("Connected to upstream pool: {}", self.config.upstream_pool.url);
+        tracing::info!("Connected to upstream pool: {}", self.pools[index].url);
         Ok(())
     }
 
@@ -198,32 +354,8 @@ impl ClientModeHandler {
 
     /// Parse Stratum URL to extract host and port
     fn parse_stratum_url(url: &str) -> Result<(String, u16)> {
-        // Remove protocol prefix if present
-        let url = url.strip_prefix("stratum+tcp://")
-            .or_else(|| url.strip_prefix("stratum://"))
-            .or_else(|| url.strip_prefix("tcp://"))
-            .unwrap_or(url);
-        
-        // Split host and port
-        if let Some(colon_pos) = url.rfind(':') {
-            let host = url[..colon_pos].to_string();
-            let port_str = &url[colon_pos + 1..];
-            
-            if host.is_empty() {
-                return Err(Error::Config("Empty host in upstream URL".to_string()));
-            }
-            
-            let port = port_str.parse::<u16>()
-                .map_err(|_| Error::Config(format!("Invalid port in upstream URL: {}", port_str)))?;
-            
-            Ok((host, port))
-        } else {
-            // No port specified, use default
-            if url.is_empty() {
-                return Err(Error::Config("Empty upstream URL".to_string()));
-            }
-            Ok((url.to_string(), 4444)) // Default Stratum port
-        }
+        let url: StratumUrl = url.parse()?;
+        Ok((url.host, url.port))
     }
 
     /// Perform SV2 protocol handshake
@@ -332,7 +464,8 @@ impl ClientModeHandler {
         message.extend_from_slice(&[0x00, 0x10]); // Message length: 16 bytes
         
         // User identifier (simplified)
-        let user_id = self.config.upstream_pool.username.as_bytes();
+        let active_pool = self.pools.get(self.active_pool.load(Ordering::Relaxed));
+        let user_id = active_pool.map_or(&[][..], |pool| pool.username.as_bytes());
         message.extend_from_slice(&(user_id.len() as u16).to_le_bytes());
         message.extend_from_slice(user_id);
         
@@ -462,61 +595,66 @@ impl ClientModeHandler {
         }
     }
 
-    /// Receive work from upstream pool
+    /// Read what the upstream has sent since the last call, without
+    /// waiting for more, and return the newest template among the complete
+    /// frames. Other messages are skipped.
     async fn receive_work_from_upstream(&self) -> Result<Option<WorkTemplate>> {
         let connection = self.upstream_connection.read().await;
-        
-        if connection.is_some() {
-            // In a real implementation, this would continuously listen for new work
-            // For now, we'll simulate receiving work from upstream
-            
-            // Simulate work message
-            let simulated_message = vec![0x71, 0x00, 0x00, 0x40]; // NewTemplate message
-            
-            // Parse received message
-            if let Ok(template) = self.parse_work_message(&simulated_message) {
-                return Ok(Some(template));
+        let Some(stream) = connection.as_ref() else {
+            return Ok(None);
+        };
+
+        let mut buffer = self.upstream_buffer.lock().await;
+        let mut chunk = [0u8; 4096];
+        loop {
+            match stream.try_read(&mut chunk) {
+                Ok(0) => {
+                    // Whatever's buffered belongs to a stream that's gone
+                    buffer.clear();
+                    self.upstream_status.write().await.connected = false;
+                    return Err(Error::Connection("Upstream pool closed the connection".to_string()));
+                }
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(Error::Connection(format!("Failed to read from upstream: {}", e))),
             }
         }
-        
-        Ok(None)
+
+        let previous_hash = self.current_template.read().await
+            .as_ref()
+            .map_or_else(bitcoin::BlockHash::all_zeros, |template| template.previous_hash);
+        let mut template = None;
+        while let Some(frame) = Self::take_frame(&mut buffer) {
+            match Self::parse_work_message(&frame, previous_hash) {
+                Ok(parsed) => template = Some(parsed),
+                Err(e) => tracing::debug!("Skipping upstream frame: {}", e),
+            }
+        }
+        Ok(template)
     }
 
-    /// Parse work message from upstream
-    fn parse_work_message(&self, message: &[u8]) -> Result<WorkTemplate> {
-        // Simplified work message parsing
-        // In a real implementation, this would properly parse SV2 NewTemplate messages
-        
-        if message.len() < 16 {
-            return Err(Error::Protocol("Invalid work message format".to_string()));
+    /// Split the first complete frame off the front of `buffer`
+    fn take_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+        if buffer.len() < SV2_HEADER_LEN {
+            return None;
         }
-        
-        let message_type = u16::from_le_bytes([message[0], message[1]]);
-        
-        if message_type != 0x71 { // NewTemplate
-            return Err(Error::Protocol("Not a work template message".to_string()));
+        let length = u32::from_le_bytes([buffer[3], buffer[4], buffer[5], 0]) as usize;
+        if buffer.len() < SV2_HEADER_LEN + length {
+            return None;
+        }
+        Some(buffer.drain(..SV2_HEADER_LEN + length).collect())
+    }
+
+    /// Parse an SV2 `NewTemplate` frame from upstream. NewTemplate doesn't
+    /// carry the previous block hash, so the template builds on
+    /// `previous_hash`.
+    fn parse_work_message(message: &[u8], previous_hash: bitcoin::BlockHash) -> Result<WorkTemplate> {
+        match Sv2Message::decode(message)? {
+            Sv2Message::NewTemplate(new_template) => new_template.to_work_template(previous_hash),
+            other => Err(Error::Protocol(format!(
+                "Not a work template message: 0x{:02x}", other.msg_type()
+            ))),
         }
-        
-        // Create a simplified work template
-        // In a real implementation, this would parse the actual template data
-        use bitcoin::{BlockHash, Transaction, TxIn, TxOut, hashes::Hash};
-        
-        let previous_hash = BlockHash::all_zeros(); // Would be parsed from message
-        let coinbase_tx = Transaction {
-            version: 1,
-            lock_time: bitcoin::absolute::LockTime::ZERO,
-            input: vec![TxIn::default()],
-            output: vec![TxOut::default()],
-        };
-        
-        let template = WorkTemplate::new(
-            previous_hash,
-            coinbase_tx,
-            vec![], // Transactions would be parsed from message
-            1.0,    // Difficulty would be parsed from message
-        );
-        
-        Ok(template)
     }
 
     /// Update mining statistics
@@ -782,8 +920,12 @@ impl ClientModeHandler {
         let base_template = current_template.as_ref()
             .ok_or_else(|| Error::Template("No base template available".to_string()))?;
 
+        // The custom coinbase commits to the same height as the base template's
+        let height = crate::bip34::coinbase_height(&base_template.coinbase_tx)
+            .ok_or_else(|| Error::Template("Base template coinbase has no BIP 34 height".to_string()))?;
+
         // Create custom coinbase transaction
-        let coinbase_tx = self.create_custom_coinbase_transaction()?;
+        let coinbase_tx = self.create_custom_coinbase_transaction(height)?;
 
         // Combine preferred transactions with base template transactions
         let mut transactions = vec![coinbase_tx.clone()];
@@ -815,7 +957,7 @@ impl ClientModeHandler {
 
         let block_template = BlockTemplate {
             template,
-            height: 0, // Would be obtained from Bitcoin node
+            height,
             reward: 625_000_000, // Current block reward in satoshis
             fees,
             weight: total_weight,
@@ -830,14 +972,19 @@ impl ClientModeHandler {
         Ok(block_template)
     }
 
-    /// Create custom coinbase transaction
-    fn create_custom_coinbase_transaction(&self) -> Result<bitcoin::Transaction> {
+    /// Create custom coinbase transaction for a block at `height`
+    fn create_custom_coinbase_transaction(&self, height: u64) -> Result<bitcoin::Transaction> {
         use bitcoin::{Transaction, TxIn, TxOut, OutPoint, ScriptBuf, Amount};
-        
+
+        // BIP 34 height first, then 8 bytes of extranonce space
+        let script_sig = bitcoin::blockdata::script::Builder::from(crate::bip34::height_push(height).into_bytes())
+            .push_slice(&[0u8; 8])
+            .into_script();
+
         // Create coinbase input
         let coinbase_input = TxIn {
             previous_output: OutPoint::null(),
-            script_sig: ScriptBuf::new(), // Simplified coinbase script
+            script_sig,
             sequence: bitcoin::Sequence::MAX,
             witness: bitcoin::Witness::new(),
         };
@@ -854,6 +1001,7 @@ impl ClientModeHandler {
             input: vec![coinbase_input],
             output: vec![coinbase_output],
         };
+        crate::bip34::check_coinbase(&coinbase_tx, height)?;
 
         Ok(coinbase_tx)
     }
@@ -943,6 +1091,28 @@ impl crate::mode::ModeHandler for ClientModeHandler {
         Ok(())
     }
 
+    /// Record handshake details on a connection
+    async fn handle_handshake(&self, connection_id: ConnectionId, details: HandshakeDetails) -> Result<()> {
+        let connection_info = {
+            let mut connections = self.connections.write().await;
+            let connection_info = connections.get_mut(&connection_id)
+                .ok_or_else(|| Error::Connection(format!("Unknown connection: {}", connection_id)))?;
+            connection_info.apply_handshake(&details);
+            connection_info.clone()
+        };
+
+        self.database.update_connection(&connection_info).await?;
+
+        tracing::debug!(
+            "Handshake completed in client mode: {} (version {:?}, flags {:?})",
+            connection_id,
+            connection_info.version,
+            connection_info.flags
+        );
+
+        Ok(())
+    }
+
     /// Process a submitted share
     async fn process_share(&self, share: Share) -> Result<ShareResult> {
         // Submit share to upstream pool
@@ -975,7 +1145,7 @@ impl crate::mode::ModeHandler for ClientModeHandler {
             share_with_result.block_hash = Some(*block_hash);
         }
         
-        self.database.store_share(&share_with_result).await?;
+        self.share_sampler.store(self.database.as_ref(), self.dead_letter.as_deref(), &share_with_result).await?;
         
         // Update statistics
         self.update_statistics().await;
@@ -995,6 +1165,7 @@ impl crate::mode::ModeHandler for ClientModeHandler {
         if let Ok(Some(new_template)) = self.receive_work_from_upstream().await {
             let mut current = self.current_template.write().await;
             *current = Some(new_template.clone());
+            self.template_publisher.send_replace(Some(new_template.clone()));
             return Ok(new_template);
         }
         
@@ -1020,6 +1191,7 @@ impl crate::mode::ModeHandler for ClientModeHandler {
         }
 
         // Update database
+        self.share_sampler.flush(self.database.as_ref(), self.dead_letter.as_deref(), connection_id).await?;
         self.database.update_connection_status(connection_id, ConnectionState::Disconnected).await?;
         
         tracing::info!("Connection disconnected from client mode: {}", connection_id);
@@ -1034,15 +1206,53 @@ impl crate::mode::ModeHandler for ClientModeHandler {
         Ok(stats.clone())
     }
 
+    async fn health(&self) -> HealthCheck {
+        let status = self.upstream_status.read().await.clone();
+        let mut check = HealthCheck::new("client_mode", HealthStatus::Healthy, format!("Connected to upstream {}", status.url))
+            .with_metadata("mode", "client")
+            .with_metadata("upstream_url", &status.url)
+            .with_metadata("upstream_priority", status.priority)
+            .with_metadata("upstream_connected", status.connected)
+            .with_metadata("connection_attempts", status.connection_attempts)
+            .with_metadata("failovers", status.failovers);
+        if let Some(at) = status.last_connected {
+            check = check.with_metadata("last_connected", at.to_rfc3339());
+        }
+        if let Some(primary) = self.pools.first().filter(|primary| primary.url != status.url) {
+            check = check.degrade(
+                HealthStatus::Warning,
+                format!("Failed over to upstream {}; primary {} unavailable", status.url, primary.url),
+            );
+        }
+        if !status.connected {
+            let problem = match &status.last_error {
+                Some(error) => format!("Upstream {} disconnected: {}", status.url, error),
+                None => format!("Upstream {} not connected", status.url),
+            };
+            check = check.degrade(HealthStatus::Critical, problem);
+        }
+        check
+    }
+
+    async fn workers(&self) -> Vec<Worker> {
+        self.workers.read().await.values().cloned().collect()
+    }
+
     /// Validate mode-specific configuration
     fn validate_config(&self, config: &DaemonConfig) -> Result<()> {
         if let crate::config::OperationModeConfig::Client(client_config) = &config.mode {
-            if client_config.upstream_pool.url.is_empty() {
-                return Err(Error::Config("Client mode requires upstream pool URL".to_string()));
+            if client_config.upstream_pools.is_empty() {
+                return Err(Error::Config("Client mode requires at least one upstream pool".to_string()));
             }
             
-            if client_config.upstream_pool.username.is_empty() {
-                return Err(Error::Config("Client mode requires upstream pool username".to_string()));
+            for pool in &client_config.upstream_pools {
+                if pool.url.is_empty() {
+                    return Err(Error::Config("Client mode requires upstream pool URL".to_string()));
+                }
+                
+                if pool.username.is_empty() {
+                    return Err(Error::Config("Client mode requires upstream pool username".to_string()));
+                }
             }
             
             if client_config.reconnect_interval == 0 {
@@ -1084,13 +1294,13 @@ mod tests {
 
     fn create_test_client_config() -> ClientConfig {
         ClientConfig {
-            upstream_pool: UpstreamPool {
+            upstream_pools: vec![UpstreamPool {
                 url: "stratum+tcp://pool.example.com:4444".to_string(),
                 username: "test_worker".to_string(),
                 password: "test_password".to_string(),
                 priority: 1,
                 weight: 1,
-            },
+            }],
             enable_job_negotiation: false,
             custom_template_enabled: false,
             reconnect_interval: 30,
@@ -1105,8 +1315,8 @@ mod tests {
 
         let handler = ClientModeHandler::new(client_config.clone(), database);
         
-        assert_eq!(handler.config.upstream_pool.url, "stratum+tcp://pool.example.com:4444");
-        assert_eq!(handler.config.upstream_pool.username, "test_worker");
+        assert_eq!(handler.config.upstream_pools[0].url, "stratum+tcp://pool.example.com:4444");
+        assert_eq!(handler.config.upstream_pools[0].username, "test_worker");
         assert!(!handler.job_negotiation_enabled);
     }
 
@@ -1141,6 +1351,227 @@ mod tests {
         assert_eq!(connections[&connection_id].address, addr);
     }
 
+    #[tokio::test]
+    async fn test_health_reports_upstream_status() {
+        let handler = ClientModeHandler::new(create_test_client_config(), Arc::new(MockDatabaseOps::new()));
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Critical);
+        assert_eq!(health.metadata["upstream_connected"], "false");
+
+        handler.upstream_status.write().await.last_error = Some("Connection refused".to_string());
+        assert!(handler.health().await.message.contains("Connection refused"));
+
+        {
+            let mut status = handler.upstream_status.write().await;
+            status.connected = true;
+            status.last_connected = Some(chrono::Utc::now());
+            status.last_error = None;
+        }
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+        assert!(health.metadata.contains_key("last_connected"));
+    }
+
+    /// A pool at `port` with `priority`
+    fn local_pool(port: u16, priority: u32) -> UpstreamPool {
+        UpstreamPool {
+            url: format!("stratum+tcp://127.0.0.1:{}", port),
+            priority,
+            ..create_test_client_config().upstream_pools[0].clone()
+        }
+    }
+
+    /// A port nothing listens on
+    async fn closed_port() -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_secondary_when_primary_unreachable() {
+        let secondary = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let secondary_port = secondary.local_addr().unwrap().port();
+        let client_config = ClientConfig {
+            // Listed out of order; priority decides
+            upstream_pools: vec![local_pool(secondary_port, 2), local_pool(closed_port().await, 1)],
+            ..create_test_client_config()
+        };
+        let handler = ClientModeHandler::new(client_config, Arc::new(MockDatabaseOps::new()));
+        assert_eq!(handler.get_upstream_status().await.priority, 1);
+
+        handler.connect_to_upstream().await.unwrap();
+
+        let status = handler.get_upstream_status().await;
+        assert!(status.connected);
+        assert_eq!(status.url, format!("stratum+tcp://127.0.0.1:{}", secondary_port));
+        assert_eq!(status.priority, 2);
+        assert_eq!(status.failovers, 1);
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert_eq!(health.metadata["upstream_priority"], "2");
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_moves_to_next_pool_after_disconnect() {
+        let primary = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let secondary = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary_port = primary.local_addr().unwrap().port();
+        let secondary_port = secondary.local_addr().unwrap().port();
+        let client_config = ClientConfig {
+            upstream_pools: vec![local_pool(primary_port, 1), local_pool(secondary_port, 2)],
+            ..create_test_client_config()
+        };
+        let handler = ClientModeHandler::new(client_config, Arc::new(MockDatabaseOps::new()));
+
+        handler.connect_to_upstream().await.unwrap();
+        assert_eq!(handler.get_upstream_status().await.priority, 1);
+        assert_eq!(handler.health().await.status, HealthStatus::Healthy);
+
+        // The primary goes away
+        drop(primary);
+        handler.upstream_status.write().await.connected = false;
+        ClientModeHandler::reconnect(
+            &handler.pools,
+            &handler.active_pool,
+            &handler.upstream_status,
+            &handler.upstream_connection,
+        ).await;
+
+        let status = handler.get_upstream_status().await;
+        assert!(status.connected);
+        assert_eq!(status.url, format!("stratum+tcp://127.0.0.1:{}", secondary_port));
+        assert_eq!(status.failovers, 1);
+    }
+
+    #[tokio::test]
+    async fn test_new_template_from_upstream_becomes_work() {
+        use crate::sv2_codec::NewTemplate;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client_config = ClientConfig {
+            upstream_pools: vec![local_pool(listener.local_addr().unwrap().port(), 1)],
+            ..create_test_client_config()
+        };
+        let handler = ClientModeHandler::new(client_config, Arc::new(MockDatabaseOps::new()));
+        handler.connect_to_upstream().await.unwrap();
+        let (mut upstream, _) = listener.accept().await.unwrap();
+
+        let commitment = bitcoin::TxOut {
+            value: 0,
+            script_pubkey: bitcoin::ScriptBuf::from(vec![0x6a, 0x01, 0xaa]),
+        };
+        let frame = Sv2Message::NewTemplate(NewTemplate {
+            template_id: 7,
+            future_template: false,
+            version: 0x2000_0000,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![0x03, 0x40, 0x0d, 0x03],
+            coinbase_tx_input_sequence: u32::MAX,
+            coinbase_tx_value_remaining: 312_500_000,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: bitcoin::consensus::encode::serialize(&commitment),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![[0x11; 32]],
+        }).encode().unwrap();
+
+        // Half a frame isn't work yet
+        upstream.write_all(&frame[..10]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(handler.receive_work_from_upstream().await.unwrap().is_none());
+
+        upstream.write_all(&frame[10..]).await.unwrap();
+        let template = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(template) = handler.get_work_template().await {
+                    break template;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        assert_eq!(template.id, Uuid::from_u64_pair(0, 7));
+        assert_eq!(template.version, Some(0x2000_0000));
+        assert_eq!(template.merkle_path.len(), 1);
+        assert_eq!(template.coinbase_tx.input[0].script_sig.as_bytes(), &[0x03, 0x40, 0x0d, 0x03]);
+        assert_eq!(template.coinbase_tx.output[0].value, 312_500_000);
+        assert_eq!(template.coinbase_tx.output[1], commitment);
+
+        // The upstream hanging up is noticed on the next read
+        drop(upstream);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handler.receive_work_from_upstream().await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        assert!(!handler.get_upstream_status().await.connected);
+    }
+
+    #[test]
+    fn test_reconnect_backoff_doubles_up_to_cap() {
+        let interval = Duration::from_secs(30);
+        let backoffs: Vec<u64> = (1..=6)
+            .map(|attempt| ClientModeHandler::reconnect_backoff(interval, attempt).as_secs())
+            .collect();
+        assert_eq!(backoffs, vec![30, 60, 120, 240, 300, 300]);
+        assert_eq!(ClientModeHandler::reconnect_backoff(interval, u64::MAX), MAX_RECONNECT_BACKOFF);
+
+        // An interval over the cap is used as it is
+        let long = Duration::from_secs(900);
+        assert_eq!(ClientModeHandler::reconnect_backoff(long, 3), long);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_gives_up_after_max_attempts() {
+        let client_config = ClientConfig {
+            upstream_pools: vec![local_pool(closed_port().await, 1), local_pool(closed_port().await, 2)],
+            ..create_test_client_config()
+        };
+        let handler = ClientModeHandler::new(client_config, Arc::new(MockDatabaseOps::new()));
+
+        let reconnected = ClientModeHandler::reconnect_with_backoff(
+            &handler.pools,
+            &handler.active_pool,
+            &handler.upstream_status,
+            &handler.upstream_connection,
+            Duration::from_millis(1),
+            3,
+        ).await;
+
+        assert!(!reconnected);
+        let status = handler.get_upstream_status().await;
+        assert!(!status.connected);
+        assert_eq!(status.connection_attempts, 3);
+        assert_eq!(status.reconnect_backoff, None);
+        assert!(status.last_error.unwrap().starts_with("Gave up reconnecting after 3 attempts"));
+
+        // A pool coming back resets the count
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let pools = vec![local_pool(listener.local_addr().unwrap().port(), 1)];
+        assert!(ClientModeHandler::reconnect_with_backoff(
+            &pools,
+            &AtomicUsize::new(0),
+            &handler.upstream_status,
+            &handler.upstream_connection,
+            Duration::from_millis(1),
+            3,
+        ).await);
+        assert_eq!(handler.get_upstream_status().await.connection_attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_when_no_pool_reachable() {
+        let client_config = ClientConfig {
+            upstream_pools: vec![local_pool(closed_port().await, 1), local_pool(closed_port().await, 2)],
+            ..create_test_client_config()
+        };
+        let handler = ClientModeHandler::new(client_config, Arc::new(MockDatabaseOps::new()));
+
+        assert!(handler.connect_to_upstream().await.is_err());
+        let status = handler.get_upstream_status().await;
+        assert!(!status.connected);
+        assert!(status.last_error.is_some());
+        assert_eq!(status.failovers, 0);
+    }
+
     #[tokio::test]
     async fn test_share_processing_without_upstream() {
         let client_config = create_test_client_config();
@@ -1183,7 +1614,8 @@ mod tests {
         let handler = ClientModeHandler::new(client_config.clone(), database);
         
         let status = handler.get_upstream_status().await;
-        assert_eq!(status.url, client_config.upstream_pool.url);
+        assert_eq!(status.url, client_config.upstream_pools[0].url);
+        assert_eq!(status.failovers, 0);
         assert!(!status.connected);
         assert_eq!(status.shares_submitted, 0);
         assert_eq!(status.shares_accepted, 0);
@@ -1284,10 +1716,10 @@ mod tests {
         // Test invalid config - empty URL
         let invalid_config = DaemonConfig {
             mode: crate::config::OperationModeConfig::Client(ClientConfig {
-                upstream_pool: UpstreamPool {
+                upstream_pools: vec![UpstreamPool {
                     url: "".to_string(),
-                    ..create_test_client_config().upstream_pool
-                },
+                    ..create_test_client_config().upstream_pools[0].clone()
+                }],
                 ..create_test_client_config()
             }),
             ..Default::default()
@@ -1298,10 +1730,10 @@ mod tests {
         // Test invalid config - empty username
         let invalid_config2 = DaemonConfig {
             mode: crate::config::OperationModeConfig::Client(ClientConfig {
-                upstream_pool: UpstreamPool {
+                upstream_pools: vec![UpstreamPool {
                     username: "".to_string(),
-                    ..create_test_client_config().upstream_pool
-                },
+                    ..create_test_client_config().upstream_pools[0].clone()
+                }],
                 ..create_test_client_config()
             }),
             ..Default::default()
@@ -1512,14 +1944,25 @@ mod tests {
         
         // Set up a base template first
         use bitcoin::{BlockHash, Transaction, TxIn, TxOut, hashes::Hash};
+        let base_coinbase = |script_sig| Transaction {
+            version: 1,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn { script_sig, ..TxIn::default() }],
+            output: vec![TxOut::default()],
+        };
+
+        // Without a BIP 34 height there's no valid coinbase to build
+        *handler.current_template.write().await = Some(WorkTemplate::new(
+            BlockHash::all_zeros(),
+            base_coinbase(bitcoin::ScriptBuf::new()),
+            vec![],
+            1.0,
+        ));
+        assert!(handler.create_custom_template(vec![]).await.is_err());
+
         let base_template = WorkTemplate::new(
             BlockHash::all_zeros(),
-            Transaction {
-                version: 1,
-                lock_time: bitcoin::absolute::LockTime::ZERO,
-                input: vec![TxIn::default()],
-                output: vec![TxOut::default()],
-            },
+            base_coinbase(crate::bip34::height_push(840_000)),
             vec![],
             1.0,
         );
@@ -1538,6 +1981,12 @@ mod tests {
         assert!(custom_template.weight > 0);
         assert!(custom_template.sigops >= 0);
         assert_eq!(custom_template.reward, 625_000_000);
+        assert_eq!(custom_template.height, 840_000);
+
+        // scriptSig: push of 840000 (0x0cd140, little-endian), then the extranonce push
+        let script_sig = custom_template.template.coinbase_tx.input[0].script_sig.as_bytes();
+        assert_eq!(script_sig[..4], [0x03, 0x40, 0xd1, 0x0c]);
+        assert_eq!(script_sig[4], 0x08);
     }
 
     #[tokio::test]
@@ -1,8 +1,16 @@
 use crate::{
-    Result, Error, Connection, Share, ShareResult, WorkTemplate, MiningStats,
-    config::{DaemonConfig, PoolConfig},
+    Result, Error, Connection, Share, ShareResult, WorkTemplate, MiningStats, NtimeBounds,
+    config::{DaemonConfig, PoolConfig, ShareLoggingConfig},
     database::DatabaseOps,
-    types::{ConnectionId, ConnectionInfo, ConnectionState, Worker, Job, ShareSubmission, PoolStats},
+    dead_letter::DeadLetterLog,
+    milestones::MilestoneTracker,
+    events::{Event, EventBus},
+    health::{HealthCheck, HealthStatus},
+    idle::{IdleConfig, IdleMonitor},
+    logging::ShareLogFilter,
+    metrics::MetricsCollector,
+    share_sampling::ShareSampler,
+    types::{ConnectionId, ConnectionInfo, ConnectionState, HandshakeDetails, Worker, WorkerRecord, Job, ShareSubmission, PoolStats},
     bitcoin_rpc::{BitcoinRpcClient, GetBlockTemplateResponse},
 };
 use async_trait::async_trait;
@@ -21,6 +29,8 @@ pub struct PoolModeHandler {
     // Connection management
     connections: Arc<RwLock<HashMap<ConnectionId, ConnectionInfo>>>,
     workers: Arc<RwLock<HashMap<String, Worker>>>,
+    /// Persistent identities of authorized workers, keyed by worker name
+    worker_records: Arc<RwLock<HashMap<String, WorkerRecord>>>,
     
     // Work distribution
     current_template: Arc<RwLock<Option<WorkTemplate>>>,
@@ -29,6 +39,16 @@ pub struct PoolModeHandler {
     // Statistics and monitoring
     pool_stats: Arc<RwLock<PoolStats>>,
     last_difficulty_adjustment: Arc<Mutex<Instant>>,
+    share_log: Arc<ShareLogFilter>,
+    metrics: Option<Arc<MetricsCollector>>,
+    dead_letter: Option<Arc<DeadLetterLog>>,
+    /// Which accepted shares get a row
+    share_sampler: Arc<ShareSampler>,
+    milestones: Arc<MilestoneTracker>,
+    /// Where connection, share and block events are published
+    events: EventBus,
+    /// Slows template polling and stats while no miners are connected
+    idle: Arc<IdleMonitor>,
     
     // Communication channels
     share_tx: mpsc::UnboundedSender<ShareSubmission>,
@@ -53,16 +73,60 @@ impl PoolModeHandler {
             database,
             connections: Arc::new(RwLock::new(HashMap::new())),
             workers: Arc::new(RwLock::new(HashMap::new())),
+            worker_records: Arc::new(RwLock::new(HashMap::new())),
             current_template: Arc::new(RwLock::new(None)),
             active_jobs: Arc::new(RwLock::new(HashMap::new())),
             pool_stats: Arc::new(RwLock::new(PoolStats::default())),
             last_difficulty_adjustment: Arc::new(Mutex::new(Instant::now())),
+            share_log: Arc::new(ShareLogFilter::default()),
+            metrics: None,
+            dead_letter: None,
+            share_sampler: Arc::new(ShareSampler::default()),
+            milestones: Arc::new(MilestoneTracker::new()),
+            events: EventBus::default(),
+            idle: Arc::new(IdleMonitor::new(IdleConfig { enabled: false, ..Default::default() })),
             share_tx,
             share_rx: Arc::new(Mutex::new(Some(share_rx))),
             task_handles: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Apply the configured share logging verbosity
+    pub fn with_share_logging(mut self, config: ShareLoggingConfig) -> Self {
+        self.share_log = Arc::new(ShareLogFilter::new(config));
+        self
+    }
+
+    /// Record share validation latency in the given metrics collector
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Dead-letter shares that fail to store instead of dropping them
+    pub fn with_dead_letter(mut self, dead_letter: Arc<DeadLetterLog>) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// Store one in `accepted_sample_rate` accepted shares
+    pub fn with_share_sampling(mut self, accepted_sample_rate: u64) -> Self {
+        self.share_sampler = Arc::new(ShareSampler::new(accepted_sample_rate));
+        self
+    }
+
+    /// Publish connection, share and block events on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Poll less often while `idle` sees no miners
+    pub fn with_idle(mut self, idle: Arc<IdleMonitor>) -> Self {
+        self.idle = idle;
+        self
+    }
+
     /// Start background tasks for pool management
     pub async fn start(&self) -> Result<()> {
         let mut handles = self.task_handles.lock().await;
@@ -113,6 +177,12 @@ impl PoolModeHandler {
             let mut connections = self.connections.write().await;
             connections.insert(conn.id, conn_info);
         }
+        self.idle.connection_opened();
+        self.events.publish(Event::ConnectionOpened {
+            connection_id: conn.id,
+            address: conn.address,
+            protocol: conn.protocol,
+        });
         
         // Update pool statistics
         {
@@ -132,14 +202,29 @@ impl PoolModeHandler {
             connections.remove(&connection_id)
         };
         
-        if removed.is_some() {
+        if let Some(info) = removed {
+            self.idle.connection_closed();
+
             // Update database
+            self.share_sampler.flush(self.database.as_ref(), self.dead_letter.as_deref(), connection_id).await?;
             self.database.delete_connection(connection_id).await?;
+            self.events.publish(Event::ConnectionClosed {
+                connection_id,
+                protocol: info.protocol,
+                connected_at: info.connected_at,
+            });
             
-            // Remove associated workers
+            // Remove associated workers; their persistent records stay in the database
             {
                 let mut workers = self.workers.write().await;
-                workers.retain(|_, worker| worker.connection_id != connection_id);
+                let mut records = self.worker_records.write().await;
+                workers.retain(|name, worker| {
+                    let keep = worker.connection_id != connection_id;
+                    if !keep {
+                        records.remove(name);
+                    }
+                    keep
+                });
             }
             
             // Update pool statistics
@@ -158,6 +243,15 @@ impl PoolModeHandler {
     async fn authorize_worker(&self, connection_id: ConnectionId, worker_name: String, difficulty: f64) -> Result<()> {
         let worker = Worker::new(worker_name.clone(), connection_id, difficulty);
         
+        // Tie this session to the worker's persistent record
+        let source_ip = {
+            let connections = self.connections.read().await;
+            connections.get(&connection_id).map(|conn| conn.address.ip().to_string())
+        };
+        if let Some(source_ip) = source_ip {
+            self.attach_worker_record(&worker_name, &source_ip).await?;
+        }
+        
         // Add to workers tracking
         {
             let mut workers = self.workers.write().await;
@@ -187,6 +281,53 @@ impl PoolModeHandler {
         Ok(())
     }
 
+    /// Load or create the persistent record for a worker session
+    async fn attach_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<WorkerRecord> {
+        let record = match self.database.get_worker_record(worker_name, source_ip).await? {
+            Some(mut record) => {
+                record.reconnect();
+                tracing::info!(
+                    "Worker {} reconnected from {} (session {}, {} shares so far)",
+                    worker_name, source_ip, record.sessions, record.total_shares
+                );
+                record
+            }
+            None => WorkerRecord::new(worker_name.to_string(), source_ip.to_string()),
+        };
+        
+        self.database.store_worker_record(&record).await?;
+        self.worker_records.write().await.insert(worker_name.to_string(), record.clone());
+        Ok(record)
+    }
+
+    /// Credit a share to the worker's persistent record
+    async fn record_worker_share(&self, worker_name: &str, is_valid: bool, is_block: bool) -> Result<()> {
+        let record = {
+            let mut records = self.worker_records.write().await;
+            match records.get_mut(worker_name) {
+                Some(record) => {
+                    record.add_share(is_valid, is_block);
+                    record.clone()
+                }
+                None => return Ok(()),
+            }
+        };
+        
+        self.database.store_worker_record(&record).await
+    }
+
+    /// Cumulative statistics for a worker across all of its sessions
+    pub async fn get_worker_record(&self, worker_name: &str) -> Option<WorkerRecord> {
+        self.worker_records.read().await.get(worker_name).cloned()
+    }
+
+    /// Name of the first worker authorized on a connection
+    async fn worker_for_connection(&self, connection_id: ConnectionId) -> Option<String> {
+        let connections = self.connections.read().await;
+        connections.get(&connection_id)
+            .and_then(|conn| conn.authorized_workers.first().cloned())
+    }
+
     /// Get work for a specific connection/worker
     async fn get_work_for_connection(&self, connection_id: ConnectionId) -> Result<Job> {
         let template = {
@@ -229,7 +370,11 @@ impl PoolModeHandler {
             .ok_or_else(|| Error::Protocol("Work template not found".to_string()))?;
         
         // Validate the share
+        let started = Instant::now();
         let result = submission.validate(&template);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_share_validation(started.elapsed());
+        }
         
         // Update worker statistics
         {
@@ -239,6 +384,12 @@ impl PoolModeHandler {
             }
         }
         
+        self.record_worker_share(
+            &submission.worker_name,
+            submission.share.is_valid,
+            submission.share.block_hash.is_some(),
+        ).await?;
+        
         // Update connection statistics
         {
             let mut connections = self.connections.write().await;
@@ -249,7 +400,9 @@ impl PoolModeHandler {
         }
         
         // Store share in database
-        self.database.create_share(&submission.share).await?;
+        self.share_sampler.store(self.database.as_ref(), self.dead_letter.as_deref(), &submission.share).await?;
+        let milestones = self.milestones.record_share(self.database.as_ref(), submission.share.connection_id, &result).await;
+        self.events.share_processed(submission.share.connection_id, submission.share.difficulty, &result, milestones);
         
         // Update pool statistics
         {
@@ -263,7 +416,9 @@ impl PoolModeHandler {
             }
         }
         
-        println!("Processed share from {}: {:?}", submission.worker_name, result);
+        if self.share_log.should_log(submission.share.is_valid) {
+            tracing::info!("Processed share from {}: {:?}", submission.worker_name, result);
+        }
         Ok(result)
     }
 
@@ -332,14 +487,13 @@ impl PoolModeHandler {
         let handler = Arc::new(self.clone());
         
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30)); // Refresh every 30 seconds
-            
             loop {
-                interval.tick().await;
-                
                 if let Err(e) = handler.refresh_work_template().await {
                     eprintln!("Error refreshing work template: {}", e);
                 }
+
+                // Every 30 seconds, less often while no miners are connected
+                handler.idle.sleep(Duration::from_secs(30)).await;
             }
         })
     }
@@ -383,14 +537,13 @@ impl PoolModeHandler {
         let handler = Arc::new(self.clone());
         
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(10)); // Update every 10 seconds
-            
             loop {
-                interval.tick().await;
-                
                 if let Err(e) = handler.update_pool_statistics().await {
                     eprintln!("Error updating pool statistics: {}", e);
                 }
+
+                // Every 10 seconds, less often while no miners are connected
+                handler.idle.sleep(Duration::from_secs(10)).await;
             }
         })
     }
@@ -446,7 +599,9 @@ impl PoolModeHandler {
             .map_err(|e| Error::Protocol(format!("Invalid bits format: {}", e)))?;
         let difficulty = self.bits_to_difficulty(bits_value);
         
-        Ok(WorkTemplate::new(previous_hash, coinbase_tx, transactions, difficulty))
+        let mut template = WorkTemplate::new(previous_hash, coinbase_tx, transactions, difficulty);
+        template.ntime_bounds = Some(NtimeBounds::new(response.mintime, response.curtime));
+        Ok(template)
     }
     
     /// Convert bits to difficulty (simplified)
@@ -543,10 +698,18 @@ impl Clone for PoolModeHandler {
             database: Arc::clone(&self.database),
             connections: Arc::clone(&self.connections),
             workers: Arc::clone(&self.workers),
+            worker_records: Arc::clone(&self.worker_records),
             current_template: Arc::clone(&self.current_template),
             active_jobs: Arc::clone(&self.active_jobs),
             pool_stats: Arc::clone(&self.pool_stats),
             last_difficulty_adjustment: Arc::clone(&self.last_difficulty_adjustment),
+            share_log: Arc::clone(&self.share_log),
+            metrics: self.metrics.clone(),
+            dead_letter: self.dead_letter.clone(),
+            share_sampler: Arc::clone(&self.share_sampler),
+            milestones: Arc::clone(&self.milestones),
+            events: self.events.clone(),
+            idle: Arc::clone(&self.idle),
             share_tx,
             share_rx: Arc::new(Mutex::new(Some(share_rx))),
             task_handles: Arc::new(Mutex::new(Vec::new())),
@@ -579,14 +742,38 @@ impl crate::mode::ModeHandler for PoolModeHandler {
         self.add_connection(conn).await
     }
 
+    async fn handle_handshake(&self, connection_id: ConnectionId, details: HandshakeDetails) -> Result<()> {
+        let connection_info = {
+            let mut connections = self.connections.write().await;
+            let connection_info = connections.get_mut(&connection_id)
+                .ok_or_else(|| Error::Connection(format!("Unknown connection: {}", connection_id)))?;
+            connection_info.apply_handshake(&details);
+            connection_info.clone()
+        };
+
+        self.database.update_connection(&connection_info).await?;
+
+        tracing::debug!(
+            "Handshake completed in pool mode: {} (version {:?}, flags {:?})",
+            connection_id,
+            connection_info.version,
+            connection_info.flags
+        );
+
+        Ok(())
+    }
+
     async fn process_share(&self, share: Share) -> Result<ShareResult> {
+        let worker_name = self.worker_for_connection(share.connection_id).await
+            .unwrap_or_else(|| "unknown".to_string());
+        
         // Create a share submission for processing
         let submission = ShareSubmission {
             share,
             job_id: "unknown".to_string(), // Would be provided by the protocol layer
             extranonce2: "".to_string(),
             ntime: chrono::Utc::now().timestamp() as u32,
-            worker_name: "unknown".to_string(), // Would be provided by the protocol layer
+            worker_name,
             user_agent: None,
             validation_result: None,
         };
@@ -615,9 +802,35 @@ impl crate::mode::ModeHandler for PoolModeHandler {
             shares_accepted: 0, // TODO: implement share tracking
             shares_rejected: 0, // TODO: implement share tracking
             blocks_found: 0, // TODO: implement block tracking
+            blocks_orphaned: 0,
+            expected_blocks: None,
+            coinbase_tag: None,
+            network_difficulty: None,
         })
     }
 
+    async fn health(&self) -> HealthCheck {
+        let miners = self.get_connection_count().await;
+        let workers = self.workers.read().await.len();
+        let check = HealthCheck::new(
+            "pool_mode",
+            HealthStatus::Healthy,
+            format!("{} miners connected with {} workers", miners, workers),
+        )
+        .with_metadata("mode", "pool")
+        .with_metadata("connected_miners", miners)
+        .with_metadata("workers", workers);
+        if miners == 0 {
+            check.degrade(HealthStatus::Warning, "No miners connected")
+        } else {
+            check
+        }
+    }
+
+    async fn workers(&self) -> Vec<Worker> {
+        self.workers.read().await.values().cloned().collect()
+    }
+
     fn validate_config(&self, config: &DaemonConfig) -> Result<()> {
         if let crate::config::OperationModeConfig::Pool(pool_config) = &config.mode {
             if pool_config.share_difficulty <= 0.0 {
@@ -705,6 +918,70 @@ mod tests {
         assert_eq!(handler.get_connection_count().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_health_degrades_without_miners() {
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let handler = PoolModeHandler::new(PoolConfig::default(), bitcoin_client, Arc::new(MockDatabaseOps::new()));
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert_eq!(health.metadata["connected_miners"], "0");
+
+        let conn = Connection::new("127.0.0.1:3333".parse().unwrap(), Protocol::Sv2);
+        let conn_id = conn.id;
+        handler.handle_connection(conn).await.unwrap();
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+        assert_eq!(health.metadata["connected_miners"], "1");
+
+        handler.handle_disconnection(conn_id).await.unwrap();
+        assert_eq!(handler.health().await.status, HealthStatus::Warning);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_details_update_connection_info() {
+        let config = PoolConfig::default();
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let database = Arc::new(MockDatabaseOps::new());
+        
+        let handler = PoolModeHandler::new(config, bitcoin_client, database.clone());
+        
+        let addr: SocketAddr = "127.0.0.1:3333".parse().unwrap();
+        let conn = Connection::new(addr, Protocol::Sv2);
+        let conn_id = conn.id;
+        handler.handle_connection(conn).await.unwrap();
+        
+        let stored = database.get_connection_info(conn_id).await.unwrap().unwrap();
+        assert_eq!(stored.version, None);
+        assert_eq!(stored.flags, None);
+        
+        handler.handle_handshake(conn_id, HandshakeDetails {
+            user_agent: Some("bitaxe/2.1".to_string()),
+            version: Some("2".to_string()),
+            flags: Some(0b101),
+            extranonce1: Some("f000000f".to_string()),
+            extranonce2_size: Some(4),
+        }).await.unwrap();
+        
+        // A later partial update keeps what the first one recorded
+        handler.handle_handshake(conn_id, HandshakeDetails {
+            extranonce2_size: Some(8),
+            ..Default::default()
+        }).await.unwrap();
+        
+        let stored = database.get_connection_info(conn_id).await.unwrap().unwrap();
+        assert_eq!(stored.user_agent.as_deref(), Some("bitaxe/2.1"));
+        assert_eq!(stored.version.as_deref(), Some("2"));
+        assert_eq!(stored.flags, Some(0b101));
+        assert_eq!(stored.extranonce1.as_deref(), Some("f000000f"));
+        assert_eq!(stored.extranonce2_size, Some(8));
+        
+        let in_memory = handler.connections.read().await.get(&conn_id).cloned().unwrap();
+        assert_eq!(in_memory.flags, stored.flags);
+        assert_eq!(in_memory.extranonce2_size, stored.extranonce2_size);
+        
+        assert!(handler.handle_handshake(uuid::Uuid::new_v4(), HandshakeDetails::default()).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_worker_authorization() {
         let config = PoolConfig::default();
@@ -725,6 +1002,53 @@ mod tests {
         assert_eq!(workers.get("worker1").unwrap().connection_id, conn_id);
     }
 
+    #[tokio::test]
+    async fn test_worker_stats_carry_over_reconnect() {
+        let config = PoolConfig::default();
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let database = Arc::new(MockDatabaseOps::new());
+        
+        let handler = PoolModeHandler::new(config, bitcoin_client, database.clone());
+        let addr: SocketAddr = "192.168.1.20:40001".parse().unwrap();
+        
+        // First session submits two shares, then drops
+        let conn = Connection::new(addr, Protocol::Sv1);
+        let first_id = conn.id;
+        handler.handle_connection(conn).await.unwrap();
+        handler.authorize_worker(first_id, "rig1".to_string(), 1.0).await.unwrap();
+        handler.record_worker_share("rig1", true, false).await.unwrap();
+        handler.record_worker_share("rig1", false, false).await.unwrap();
+        handler.handle_disconnection(first_id).await.unwrap();
+        assert!(handler.get_worker_record("rig1").await.is_none());
+        
+        // Same worker comes back from a new source port
+        let addr: SocketAddr = "192.168.1.20:40002".parse().unwrap();
+        let conn = Connection::new(addr, Protocol::Sv1);
+        let second_id = conn.id;
+        handler.handle_connection(conn).await.unwrap();
+        handler.authorize_worker(second_id, "rig1".to_string(), 1.0).await.unwrap();
+        handler.record_worker_share("rig1", true, false).await.unwrap();
+        
+        let record = handler.get_worker_record("rig1").await.unwrap();
+        assert_eq!(record.sessions, 2);
+        assert_eq!(record.total_shares, 3);
+        assert_eq!(record.valid_shares, 2);
+        
+        let stored = database.get_worker_record("rig1", "192.168.1.20").await.unwrap().unwrap();
+        assert_eq!(stored, record);
+        
+        // The same name from a different source IP is a different worker
+        handler.handle_disconnection(second_id).await.unwrap();
+        let addr: SocketAddr = "192.168.1.21:40003".parse().unwrap();
+        let conn = Connection::new(addr, Protocol::Sv1);
+        let other_id = conn.id;
+        handler.handle_connection(conn).await.unwrap();
+        handler.authorize_worker(other_id, "rig1".to_string(), 1.0).await.unwrap();
+        let record = handler.get_worker_record("rig1").await.unwrap();
+        assert_eq!(record.sessions, 1);
+        assert_eq!(record.total_shares, 0);
+    }
+
     #[tokio::test]
     async fn test_pool_statistics() {
         let config = PoolConfig::default();
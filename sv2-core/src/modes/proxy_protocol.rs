@@ -5,10 +5,12 @@
 
 use crate::{
     Result, Error, Connection, Share, WorkTemplate, ConnectionId,
+    metrics::MetricsCollector,
     protocol::{ProtocolMessage, ProtocolTranslator},
     types::{Protocol, Job, ShareSubmission},
 };
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, warn, error};
@@ -23,6 +25,8 @@ pub struct ProxyProtocolService {
     job_mappings: Arc<RwLock<HashMap<String, WorkTemplate>>>,
     /// Maps SV2 template IDs to SV1 job IDs
     reverse_job_mappings: Arc<RwLock<HashMap<Uuid, String>>>,
+    /// Next translator channel ID to hand a downstream connection
+    next_channel_id: AtomicU32,
 }
 
 /// Protocol state for a downstream connection
@@ -30,6 +34,8 @@ pub struct ProxyProtocolService {
 pub struct ConnectionProtocolState {
     pub connection_id: ConnectionId,
     pub protocol: Protocol,
+    /// The connection's channel in the shared translator
+    pub channel_id: u32,
     pub subscribed: bool,
     pub authorized: bool,
     pub difficulty: f64,
@@ -44,6 +50,7 @@ impl Default for ConnectionProtocolState {
         Self {
             connection_id: Uuid::new_v4(),
             protocol: Protocol::Sv1,
+            channel_id: 0,
             subscribed: false,
             authorized: false,
             difficulty: 1.0,
@@ -58,21 +65,30 @@ impl Default for ConnectionProtocolState {
 impl ProxyProtocolService {
     pub fn new() -> Self {
         Self {
-            translator: Arc::new(ProtocolTranslator::new()),
+            translator: Arc::new(ProtocolTranslator::new(Protocol::Sv1)),
             connection_states: Arc::new(RwLock::new(HashMap::new())),
             job_mappings: Arc::new(RwLock::new(HashMap::new())),
             reverse_job_mappings: Arc::new(RwLock::new(HashMap::new())),
+            next_channel_id: AtomicU32::new(1),
         }
     }
 
+    /// Record the outcome of every translation in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.translator = Arc::new(ProtocolTranslator::new(Protocol::Sv1).with_metrics(metrics));
+        self
+    }
+
     /// Initialize protocol state for a new downstream connection
     pub async fn initialize_connection(&self, connection: &Connection) -> Result<()> {
         let mut states = self.connection_states.write().await;
         let state = ConnectionProtocolState {
             connection_id: connection.id,
             protocol: connection.protocol,
+            channel_id: self.next_channel_id.fetch_add(1, Ordering::Relaxed),
             ..Default::default()
         };
+        self.translator.open_channel(state.channel_id, state.difficulty);
         states.insert(connection.id, state);
         debug!("Initialized protocol state for connection: {}", connection.id);
         Ok(())
@@ -255,15 +271,32 @@ impl ProxyProtocolService {
             reverse_mappings.insert(template.id, job_id.clone());
         }
 
-        let states = self.connection_states.read().await;
+        let mut states = self.connection_states.write().await;
         
         for &connection_id in target_connections {
-            if let Some(state) = states.get(&connection_id) {
+            if let Some(state) = states.get_mut(&connection_id) {
                 if state.subscribed && state.authorized {
                     match state.protocol {
                         Protocol::Sv1 | Protocol::StratumV1 => {
-                            let notify_message = self.create_sv1_notify_message(template, &job_id, state)?;
-                            responses.push((connection_id, notify_message));
+                            // The translator puts a mining.set_difficulty ahead
+                            // of the job when this channel's target has changed
+                            let translated = self.translator.translate_sv2_to_sv1(
+                                state.channel_id,
+                                ProtocolMessage::Sv2NewTemplate { template: template.clone() },
+                            )?;
+                            for message in translated {
+                                match message {
+                                    ProtocolMessage::Sv1SetDifficulty { difficulty } => {
+                                        state.difficulty = difficulty;
+                                        responses.push((connection_id, message));
+                                    }
+                                    ProtocolMessage::Sv1Notify { .. } => {
+                                        let notify_message = self.create_sv1_notify_message(template, &job_id, state)?;
+                                        responses.push((connection_id, notify_message));
+                                    }
+                                    _ => {}
+                                }
+                            }
                         }
                         Protocol::Sv2 | Protocol::StratumV2 => {
                             // For SV2 connections, we would create appropriate SV2 messages
@@ -279,6 +312,19 @@ impl ProxyProtocolService {
         Ok(responses)
     }
 
+    /// Apply a `SetTarget` from upstream to a downstream connection's
+    /// channel; the miner is told on its next job
+    pub async fn forward_set_target(&self, connection_id: ConnectionId, target: [u8; 32]) -> Result<()> {
+        let states = self.connection_states.read().await;
+        let state = states.get(&connection_id)
+            .ok_or_else(|| Error::Protocol("Connection state not found".to_string()))?;
+        self.translator.translate_sv2_to_sv1(
+            state.channel_id,
+            ProtocolMessage::Sv2SetTarget { channel_id: state.channel_id, target },
+        )?;
+        Ok(())
+    }
+
     /// Create SV1 notify message from work template
     fn create_sv1_notify_message(
         &self,
@@ -346,7 +392,9 @@ impl ProxyProtocolService {
     /// Remove connection state when connection is closed
     pub async fn cleanup_connection(&self, connection_id: ConnectionId) -> Result<()> {
         let mut states = self.connection_states.write().await;
-        states.remove(&connection_id);
+        if let Some(state) = states.remove(&connection_id) {
+            self.translator.close_channel(state.channel_id);
+        }
         debug!("Cleaned up protocol state for connection: {}", connection_id);
         Ok(())
     }
@@ -520,6 +568,46 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_work_template_forwarding_per_channel_difficulty() {
+        let service = ProxyProtocolService::new();
+        let connections = [create_test_connection(Protocol::Sv1), create_test_connection(Protocol::Sv1)];
+        for connection in &connections {
+            service.initialize_connection(connection).await.unwrap();
+            service.handle_downstream_message(connection.id, ProtocolMessage::Subscribe {
+                user_agent: "test_miner".to_string(),
+                session_id: None,
+            }).await.unwrap();
+            service.handle_downstream_message(connection.id, ProtocolMessage::Authorize {
+                username: "test_worker".to_string(),
+                password: "password".to_string(),
+            }).await.unwrap();
+        }
+
+        for (connection, difficulty) in connections.iter().zip([1024.0, 65536.0]) {
+            let target = crate::share_target::difficulty_to_target(difficulty).unwrap();
+            service.forward_set_target(connection.id, target).await.unwrap();
+        }
+
+        let ids = [connections[0].id, connections[1].id];
+        let responses = service.forward_work_template(&create_test_template(), &ids).await.unwrap();
+        assert_eq!(responses.len(), 4);
+        for (pair, (id, difficulty)) in responses.chunks(2).zip([(ids[0], 1024.0), (ids[1], 65536.0)]) {
+            assert!(pair.iter().all(|(connection_id, _)| *connection_id == id));
+            match &pair[0].1 {
+                ProtocolMessage::Sv1SetDifficulty { difficulty: sent } => assert!((sent - difficulty).abs() < 1e-6),
+                other => panic!("Expected SetDifficulty, got {:?}", other),
+            }
+            assert!(matches!(pair[1].1, ProtocolMessage::Notify { .. }));
+            let state = service.get_connection_state(id).await.unwrap();
+            assert!((state.difficulty - difficulty).abs() < 1e-6);
+        }
+
+        // Unchanged targets, so the next job is a notify per connection
+        let responses = service.forward_work_template(&create_test_template(), &ids).await.unwrap();
+        assert_eq!(responses.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_share_creation() {
         let service = ProxyProtocolService::new();
@@ -1,25 +1,69 @@
 use crate::{
     Result, Error, Connection, Share, ShareResult, WorkTemplate, ConnectionId, MiningStats,
-    bitcoin_rpc::BitcoinRpcClient, config::{DaemonConfig, SoloConfig}, database::DatabaseOps,
-    types::{ConnectionInfo, Worker, Job, ShareSubmission},
+    bitcoin_rpc::BitcoinRpcClient, config::{DaemonConfig, ShareLoggingConfig, SoloConfig}, database::DatabaseOps,
+    dead_letter::DeadLetterLog,
+    milestones::MilestoneTracker,
+    blocks::{BlockRecord, BlockStatus, BlockTracker},
+    reorg::{classify_tip_change, ChainView, TipChange},
+    events::{Event, EventBus},
+    health::{HealthCheck, HealthStatus},
+    idle::{IdleConfig, IdleMonitor},
+    coinbase_tag::CoinbaseTagRotator,
+    logging::ShareLogFilter, metrics::MetricsCollector, share_sampling::ShareSampler,
+    share_validator::block_probability,
+    template_source::{build_template_source, BitcoinRpcTemplateSource, TemplateSource},
+    types::{ConnectionInfo, HandshakeDetails, Worker, Job, ShareSubmission},
 };
 use async_trait::async_trait;
 use std::sync::Arc;
-use tokio::sync::{RwLock, Mutex};
+use tokio::sync::{broadcast, RwLock, Mutex};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use bitcoin::{BlockHash, hashes::Hash};
 
+/// New work for connected miners
+#[derive(Debug, Clone)]
+pub struct WorkUpdate {
+    pub template: WorkTemplate,
+    /// Set when the chain tip moved and earlier jobs can no longer make a block
+    pub clean_jobs: bool,
+}
+
 /// Solo mining mode handler
 pub struct SoloModeHandler {
     config: SoloConfig,
     bitcoin_client: BitcoinRpcClient,
+    /// Where work templates come from, selected by `config.template_source`
+    template_source: Arc<dyn TemplateSource>,
     database: Arc<dyn DatabaseOps>,
     connections: Arc<RwLock<HashMap<ConnectionId, ConnectionInfo>>>,
     workers: Arc<RwLock<HashMap<ConnectionId, Worker>>>,
     current_template: Arc<RwLock<Option<WorkTemplate>>>,
+    /// Where prevhash changes are checked against to tell reorgs from new blocks
+    chain: Arc<dyn ChainView>,
+    /// Blocks found here, watched until they confirm or are orphaned
+    blocks: Arc<BlockTracker>,
+    work_updates: broadcast::Sender<WorkUpdate>,
     template_refresh_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     stats: Arc<RwLock<MiningStats>>,
+    /// Latest network difficulty reported by the Bitcoin node
+    network_difficulty: Arc<RwLock<Option<f64>>>,
+    /// Latest sync state reported by the Bitcoin node
+    node_sync: Arc<RwLock<Option<NodeSync>>>,
+    share_log: ShareLogFilter,
+    /// Optional metrics sink for share validation latency
+    metrics: Option<Arc<MetricsCollector>>,
+    /// Where shares go when the database can't store them
+    dead_letter: Option<Arc<DeadLetterLog>>,
+    /// Which accepted shares get a row
+    share_sampler: ShareSampler,
+    /// Rotates the tag signed into coinbases built from Bitcoin RPC templates
+    coinbase_tags: Arc<CoinbaseTagRotator>,
+    milestones: MilestoneTracker,
+    /// Where connection, share and block events are published
+    events: EventBus,
+    /// Slows template polling while no miners are connected
+    idle: Arc<IdleMonitor>,
     start_time: Instant,
 }
 
@@ -30,13 +74,38 @@ impl SoloModeHandler {
         bitcoin_client: BitcoinRpcClient,
         database: Arc<dyn DatabaseOps>,
     ) -> Self {
+        let coinbase_tags = Arc::new(CoinbaseTagRotator::new(&config.coinbase_tags));
+        let bitcoin_client = bitcoin_client.with_coinbase_tags(Arc::clone(&coinbase_tags));
+        let poll_interval = Duration::from_secs(config.block_template_refresh_interval);
+        let template_source = build_template_source(
+            &config.template_source,
+            &bitcoin_client,
+            &config.coinbase_address,
+            poll_interval,
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!("{}; falling back to Bitcoin RPC templates", e);
+            Arc::new(BitcoinRpcTemplateSource::new(
+                bitcoin_client.clone(),
+                config.coinbase_address.clone(),
+                poll_interval,
+            ))
+        });
+
+        let (work_updates, _) = broadcast::channel(16);
+        let blocks = Arc::new(BlockTracker::new(config.block_confirmations));
+
         Self {
             config,
+            chain: Arc::new(bitcoin_client.clone()),
             bitcoin_client,
+            template_source,
             database,
             connections: Arc::new(RwLock::new(HashMap::new())),
             workers: Arc::new(RwLock::new(HashMap::new())),
             current_template: Arc::new(RwLock::new(None)),
+            blocks,
+            work_updates,
             template_refresh_task: Arc::new(Mutex::new(None)),
             stats: Arc::new(RwLock::new(MiningStats {
                 hashrate: 0.0,
@@ -47,11 +116,91 @@ impl SoloModeHandler {
                 shares_accepted: 0,
                 shares_rejected: 0,
                 blocks_found: 0,
+                blocks_orphaned: 0,
+                expected_blocks: Some(0.0),
+                coinbase_tag: None,
+                network_difficulty: None,
             })),
+            network_difficulty: Arc::new(RwLock::new(None)),
+            node_sync: Arc::new(RwLock::new(None)),
+            share_log: ShareLogFilter::default(),
+            metrics: None,
+            dead_letter: None,
+            share_sampler: ShareSampler::default(),
+            coinbase_tags,
+            milestones: MilestoneTracker::new(),
+            events: EventBus::default(),
+            idle: Arc::new(IdleMonitor::new(IdleConfig { enabled: false, ..Default::default() })),
             start_time: Instant::now(),
         }
     }
 
+    /// Use `source` for work templates instead of the configured one
+    pub fn with_template_source(mut self, source: Arc<dyn TemplateSource>) -> Self {
+        self.template_source = source;
+        self
+    }
+
+    /// Apply the configured share logging verbosity
+    pub fn with_share_logging(mut self, config: ShareLoggingConfig) -> Self {
+        self.share_log = ShareLogFilter::new(config);
+        self
+    }
+
+    /// Record share validation latency in the given metrics collector
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Dead-letter shares that fail to store instead of dropping them
+    pub fn with_dead_letter(mut self, dead_letter: Arc<DeadLetterLog>) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// Store one in `accepted_sample_rate` accepted shares
+    pub fn with_share_sampling(mut self, accepted_sample_rate: u64) -> Self {
+        self.share_sampler = ShareSampler::new(accepted_sample_rate);
+        self
+    }
+
+    /// Publish connection, share and block events on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Poll for templates less often while `idle` sees no miners
+    pub fn with_idle(mut self, idle: Arc<IdleMonitor>) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Check tip changes against `chain` instead of the Bitcoin node
+    pub fn with_chain_view(mut self, chain: Arc<dyn ChainView>) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Receive each new template as it replaces the current one
+    pub fn subscribe_work(&self) -> broadcast::Receiver<WorkUpdate> {
+        self.work_updates.subscribe()
+    }
+
+    fn template_updater(&self) -> TemplateUpdater {
+        TemplateUpdater {
+            current_template: Arc::clone(&self.current_template),
+            chain: Arc::clone(&self.chain),
+            blocks: Arc::clone(&self.blocks),
+            work_updates: self.work_updates.clone(),
+            stats: Arc::clone(&self.stats),
+            database: Arc::clone(&self.database),
+            events: self.events.clone(),
+            max_template_age: Duration::from_secs(self.config.max_template_age),
+        }
+    }
+
     /// Start the template refresh background task
     pub async fn start_template_refresh(&self) -> Result<()> {
         let mut task_handle = self.template_refresh_task.lock().await;
@@ -70,40 +219,35 @@ impl SoloModeHandler {
 
         // Start new template refresh task
         let bitcoin_client = self.bitcoin_client.clone();
-        let current_template = Arc::clone(&self.current_template);
+        let template_source = Arc::clone(&self.template_source);
+        let updater = self.template_updater();
+        let network_difficulty = Arc::clone(&self.network_difficulty);
+        let node_sync = Arc::clone(&self.node_sync);
         let refresh_interval = Duration::from_secs(self.config.block_template_refresh_interval);
-        let coinbase_address = self.config.coinbase_address.clone();
-        let max_template_age = Duration::from_secs(self.config.max_template_age);
+        let idle = Arc::clone(&self.idle);
 
         let handle = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(refresh_interval);
             let mut consecutive_failures = 0u32;
             
             loop {
-                interval.tick().await;
+                let mut median_time_past = None;
+                if let Ok(info) = bitcoin_client.get_blockchain_info().await {
+                    *network_difficulty.write().await = Some(info.difficulty);
+                    *node_sync.write().await = Some(NodeSync {
+                        initial_block_download: info.initialblockdownload,
+                        blocks: info.blocks,
+                        headers: info.headers,
+                    });
+                    median_time_past = Some(info.mediantime);
+                }
                 
-                match bitcoin_client.generate_work_template(&coinbase_address).await {
-                    Ok(template) => {
-                        consecutive_failures = 0; // Reset failure count on success
-                        
-                        let mut current = current_template.write().await;
-                        
-                        // Check if we need to update the template
-                        let should_update = match current.as_ref() {
-                            None => true,
-                            Some(existing) => {
-                                // Update if template is expired or if we have a new block
-                                existing.is_expired() ||
-                                existing.previous_hash != template.previous_hash ||
-                                existing.expires_at.signed_duration_since(chrono::Utc::now()) < chrono::Duration::from_std(max_template_age).unwrap_or_default()
-                            }
-                        };
-
-                        if should_update {
-                            *current = Some(template.clone());
-                            tracing::info!("Updated work template for solo mining: height={}, difficulty={:.2}", 
-                                         template.timestamp, template.difficulty);
+                match template_source.fetch_template().await {
+                    Ok(mut template) => {
+                        if let Some(mtp) = median_time_past {
+                            template.ntime_bounds = Some(template.ntime_bounds().with_median_time_past(mtp));
                         }
+                        consecutive_failures = 0; // Reset failure count on success
+                        updater.update(template).await;
                     }
                     Err(e) => {
                         consecutive_failures += 1;
@@ -120,6 +264,9 @@ impl SoloModeHandler {
                         }
                     }
                 }
+
+                // A connecting miner cuts an idle wait short
+                idle.sleep(refresh_interval).await;
             }
         });
 
@@ -149,14 +296,9 @@ impl SoloModeHandler {
         // Drop the read lock before acquiring write lock
         drop(current);
         
-        // Generate new template
-        let new_template = self.bitcoin_client
-            .generate_work_template(&self.config.coinbase_address)
-            .await?;
-        
-        // Update current template
-        let mut current = self.current_template.write().await;
-        *current = Some(new_template.clone());
+        // Fetch a new template
+        let new_template = self.template_source.fetch_template().await?;
+        self.template_updater().update(new_template.clone()).await;
         
         Ok(new_template)
     }
@@ -171,6 +313,11 @@ impl SoloModeHandler {
             return Ok(ShareResult::Invalid("Invalid difficulty".to_string()));
         }
 
+        if let Some(reason) = template.ntime_bounds().violation(submission.share.timestamp) {
+            tracing::debug!("Rejecting share from {}: {}", submission.share.connection_id, reason);
+            return Ok(ShareResult::Invalid(reason));
+        }
+
         // Check if share meets minimum difficulty
         let min_difficulty = 1.0; // Configurable minimum difficulty for solo mining
         if submission.share.difficulty < min_difficulty {
@@ -189,6 +336,16 @@ impl SoloModeHandler {
                 match self.submit_block(&submission.share, &template).await {
                     Ok(block_hash) => {
                         tracing::info!("Block found and submitted: {}", block_hash);
+                        let worker = self.connections.read().await
+                            .get(&submission.connection_id)
+                            .and_then(|connection| connection.authorized_workers.first().cloned())
+                            .unwrap_or_else(|| submission.worker_name.clone());
+                        let record = BlockRecord {
+                            connection_id: Some(submission.connection_id),
+                            worker: Some(worker),
+                            ..BlockRecord::submitted(block_hash, &template)
+                        };
+                        self.blocks.submitted(self.database.as_ref(), record).await;
                         return Ok(ShareResult::Block(block_hash));
                     }
                     Err(e) => {
@@ -305,6 +462,24 @@ impl SoloModeHandler {
         Ok(block_hex)
     }
 
+    /// Network difficulty, fetched from the Bitcoin node if not yet known
+    async fn network_difficulty(&self) -> Option<f64> {
+        if let Some(difficulty) = *self.network_difficulty.read().await {
+            return Some(difficulty);
+        }
+
+        match self.bitcoin_client.get_blockchain_info().await {
+            Ok(info) => {
+                *self.network_difficulty.write().await = Some(info.difficulty);
+                Some(info.difficulty)
+            }
+            Err(e) => {
+                tracing::debug!("Network difficulty unavailable: {}", e);
+                None
+            }
+        }
+    }
+
     /// Update mining statistics
     async fn update_statistics(&self) {
         let connections = self.connections.read().await;
@@ -465,6 +640,7 @@ impl crate::mode::ModeHandler for SoloModeHandler {
                 if let Ok(info) = self.bitcoin_client.get_blockchain_info().await {
                     tracing::info!("Bitcoin node info: chain={}, blocks={}, difficulty={:.2}", 
                                  info.chain, info.blocks, info.difficulty);
+                    *self.network_difficulty.write().await = Some(info.difficulty);
                 }
             }
             Err(e) => {
@@ -473,6 +649,11 @@ impl crate::mode::ModeHandler for SoloModeHandler {
             }
         }
         
+        // Pick up found blocks that hadn't settled before a restart
+        if let Err(e) = self.blocks.load(self.database.as_ref()).await {
+            tracing::warn!("Failed to load found blocks: {}", e);
+        }
+
         // Start template refresh background task
         self.start_template_refresh().await?;
         
@@ -496,9 +677,15 @@ impl crate::mode::ModeHandler for SoloModeHandler {
             let mut connections = self.connections.write().await;
             connections.insert(conn.id, connection_info);
         }
+        self.idle.connection_opened();
 
         // Store connection in database
         self.database.store_connection(&conn).await?;
+        self.events.publish(Event::ConnectionOpened {
+            connection_id: conn.id,
+            address: conn.address,
+            protocol: conn.protocol,
+        });
         
         tracing::info!("New connection in solo mode: {} ({})", conn.id, conn.address);
         
@@ -510,6 +697,28 @@ impl crate::mode::ModeHandler for SoloModeHandler {
         Ok(())
     }
 
+    /// Record handshake details on a connection
+    async fn handle_handshake(&self, connection_id: ConnectionId, details: HandshakeDetails) -> Result<()> {
+        let connection_info = {
+            let mut connections = self.connections.write().await;
+            let connection_info = connections.get_mut(&connection_id)
+                .ok_or_else(|| Error::Connection(format!("Unknown connection: {}", connection_id)))?;
+            connection_info.apply_handshake(&details);
+            connection_info.clone()
+        };
+
+        self.database.update_connection(&connection_info).await?;
+
+        tracing::debug!(
+            "Handshake completed in solo mode: {} (version {:?}, flags {:?})",
+            connection_id,
+            connection_info.version,
+            connection_info.flags
+        );
+
+        Ok(())
+    }
+
     /// Process a submitted share
     async fn process_share(&self, share: Share) -> Result<ShareResult> {
         // Create share submission for validation
@@ -524,7 +733,11 @@ impl crate::mode::ModeHandler for SoloModeHandler {
         );
 
         // Validate the share
+        let started = Instant::now();
         let result = self.validate_share(&submission).await?;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_share_validation(started.elapsed());
+        }
         
         // Update connection and worker statistics
         {
@@ -553,16 +766,32 @@ impl crate::mode::ModeHandler for SoloModeHandler {
             share_with_result.block_hash = Some(*block_hash);
         }
         
-        self.database.store_share(&share_with_result).await?;
+        self.share_sampler.store(self.database.as_ref(), self.dead_letter.as_deref(), &share_with_result).await?;
+        let milestones = self.milestones.record_share(self.database.as_ref(), share_with_result.connection_id, &result).await;
+        self.events.share_processed(share_with_result.connection_id, share_with_result.difficulty, &result, milestones);
+
+        if share_with_result.block_hash.is_some() {
+            self.stats.write().await.blocks_found += 1;
+        }
+
+        if share_with_result.is_valid {
+            if let Some(network_difficulty) = self.network_difficulty().await {
+                let probability = block_probability(share_with_result.difficulty, network_difficulty);
+                *self.stats.write().await.expected_blocks.get_or_insert(0.0) += probability;
+                tracing::debug!("Share block probability: {:.3e}", probability);
+            }
+        }
         
         // Update statistics
         self.update_statistics().await;
         
-        tracing::debug!(
-            "Processed share from {}: {:?}",
-            share_with_result.connection_id,
-            result
-        );
+        if self.share_log.should_log(share_with_result.is_valid) {
+            tracing::info!(
+                "Processed share from {}: {:?}",
+                share_with_result.connection_id,
+                result
+            );
+        }
         
         Ok(result)
     }
@@ -575,16 +804,27 @@ impl crate::mode::ModeHandler for SoloModeHandler {
     /// Handle connection disconnection
     async fn handle_disconnection(&self, connection_id: ConnectionId) -> Result<()> {
         // Remove from active connections
-        {
+        let removed = {
             let mut connections = self.connections.write().await;
             let mut workers = self.workers.write().await;
             
-            connections.remove(&connection_id);
             workers.remove(&connection_id);
+            connections.remove(&connection_id)
+        };
+        if removed.is_some() {
+            self.idle.connection_closed();
         }
 
         // Update database
+        self.share_sampler.flush(self.database.as_ref(), self.dead_letter.as_deref(), connection_id).await?;
         self.database.update_connection_status(connection_id, crate::types::ConnectionState::Disconnected).await?;
+        if let Some(info) = removed {
+            self.events.publish(Event::ConnectionClosed {
+                connection_id,
+                protocol: info.protocol,
+                connected_at: info.connected_at,
+            });
+        }
         
         tracing::info!("Connection disconnected from solo mode: {}", connection_id);
         
@@ -594,8 +834,47 @@ impl crate::mode::ModeHandler for SoloModeHandler {
     /// Get mode-specific statistics
     async fn get_statistics(&self) -> Result<MiningStats> {
         self.update_statistics().await;
-        let stats = self.stats.read().await;
-        Ok(stats.clone())
+        let mut stats = self.stats.read().await.clone();
+        stats.network_difficulty = *self.network_difficulty.read().await;
+        // A template provider builds its own coinbase, so only RPC work is tagged
+        if self.template_source.name() == "bitcoin_rpc" {
+            stats.coinbase_tag = Some(self.coinbase_tags.active_tag());
+        }
+        Ok(stats)
+    }
+
+    async fn health(&self) -> HealthCheck {
+        let mut check = match self.current_template.read().await.as_ref() {
+            None => HealthCheck::new("solo_mode", HealthStatus::Critical, "No work template yet"),
+            Some(template) => {
+                let age = (chrono::Utc::now().timestamp() - template.timestamp as i64).max(0);
+                let check = HealthCheck::new("solo_mode", HealthStatus::Healthy, format!("Work template is {}s old", age))
+                    .with_metadata("template_age_secs", age);
+                if template.is_expired() {
+                    check.degrade(HealthStatus::Warning, format!("Work template is stale ({}s old, expired)", age))
+                } else {
+                    check
+                }
+            }
+        };
+        check = check.with_metadata("mode", "solo");
+
+        if let Some(sync) = *self.node_sync.read().await {
+            check = check
+                .with_metadata("node_blocks", sync.blocks)
+                .with_metadata("node_headers", sync.headers);
+            if sync.initial_block_download || sync.blocks < sync.headers {
+                check = check.degrade(
+                    HealthStatus::Warning,
+                    format!("Bitcoin node is syncing ({}/{} blocks)", sync.blocks, sync.headers),
+                );
+            }
+        }
+        check
+    }
+
+    async fn workers(&self) -> Vec<Worker> {
+        self.workers.read().await.values().cloned().collect()
     }
 
     /// Validate mode-specific configuration
@@ -604,6 +883,11 @@ impl crate::mode::ModeHandler for SoloModeHandler {
             if solo_config.coinbase_address.is_empty() {
                 return Err(Error::Config("Solo mode requires coinbase address".to_string()));
             }
+
+            // Caught here rather than when the first template fails to build
+            let network = crate::bitcoin_rpc::bitcoin_network(&config.bitcoin.network);
+            crate::bitcoin_rpc::payout_script(&solo_config.coinbase_address, network)
+                .map_err(|e| Error::Config(format!("Solo mode coinbase_address: {}", e)))?;
             
             if solo_config.block_template_refresh_interval == 0 {
                 return Err(Error::Config("Block template refresh interval must be greater than 0".to_string()));
@@ -620,6 +904,91 @@ impl crate::mode::ModeHandler for SoloModeHandler {
     }
 }
 
+/// Sync state from the node's `getblockchaininfo`
+#[derive(Debug, Clone, Copy)]
+struct NodeSync {
+    initial_block_download: bool,
+    blocks: u64,
+    headers: u64,
+}
+
+/// Installs new templates and reacts to the chain tip moving under them
+#[derive(Clone)]
+struct TemplateUpdater {
+    current_template: Arc<RwLock<Option<WorkTemplate>>>,
+    chain: Arc<dyn ChainView>,
+    blocks: Arc<BlockTracker>,
+    work_updates: broadcast::Sender<WorkUpdate>,
+    stats: Arc<RwLock<MiningStats>>,
+    database: Arc<dyn DatabaseOps>,
+    events: EventBus,
+    max_template_age: Duration,
+}
+
+impl TemplateUpdater {
+    /// Replace the current template with `template` if it builds on a new tip
+    /// or the current one is about to expire, and push it to miners
+    async fn update(&self, template: WorkTemplate) {
+        let current = self.current_template.read().await.clone();
+        let (tip_change, aging) = match &current {
+            None => (TipChange::Extended, true),
+            Some(existing) => {
+                let remaining = existing.expires_at.signed_duration_since(chrono::Utc::now());
+                let aging = existing.is_expired()
+                    || remaining < chrono::Duration::from_std(self.max_template_age).unwrap_or_default();
+                let change = classify_tip_change(self.chain.as_ref(), &existing.previous_hash, &template.previous_hash).await;
+                (change, aging)
+            }
+        };
+        if tip_change == TipChange::Unchanged && !aging {
+            return;
+        }
+
+        if tip_change == TipChange::Reorg {
+            tracing::warn!(
+                "Chain reorg: previous tip {} left the active chain, new work builds on {}",
+                current.as_ref().map(|t| t.previous_hash.to_string()).unwrap_or_default(),
+                template.previous_hash
+            );
+        }
+
+        *self.current_template.write().await = Some(template.clone());
+        tracing::info!("Updated work template for solo mining: height={}, difficulty={:.2}",
+                     template.timestamp, template.difficulty);
+
+        // Jobs on the old tip can no longer produce a block, so miners must drop them
+        let clean_jobs = tip_change != TipChange::Unchanged;
+        // No receivers just means no miner is listening yet
+        let _ = self.work_updates.send(WorkUpdate { template, clean_jobs });
+
+        // Found blocks only gain or lose confirmations when the tip moves
+        if clean_jobs {
+            self.review_found_blocks().await;
+        }
+    }
+
+    /// Update found blocks' confirmations; orphaned ones stop counting as found
+    async fn review_found_blocks(&self) {
+        for block in self.blocks.review(self.database.as_ref(), self.chain.as_ref()).await {
+            match block.status {
+                BlockStatus::Confirmed => {
+                    tracing::info!("Block {} confirmed with {} confirmations", block.hash, block.confirmations);
+                }
+                BlockStatus::Orphaned => {
+                    tracing::warn!("Block {} was orphaned by a chain reorg", block.hash);
+                    {
+                        let mut stats = self.stats.write().await;
+                        stats.blocks_found = stats.blocks_found.saturating_sub(1);
+                        stats.blocks_orphaned += 1;
+                    }
+                    self.events.publish(Event::BlockOrphaned { block_hash: block.hash, height: block.height });
+                }
+                BlockStatus::Submitted => {}
+            }
+        }
+    }
+}
+
 impl Drop for SoloModeHandler {
     fn drop(&mut self) {
         // Clean shutdown of background tasks
@@ -648,10 +1017,13 @@ mod tests {
 
     fn create_test_solo_config() -> SoloConfig {
         SoloConfig {
-            coinbase_address: "bcrt1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh".to_string(),
+            coinbase_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
             block_template_refresh_interval: 30,
             enable_custom_templates: false,
             max_template_age: 300,
+            template_source: Default::default(),
+            coinbase_tags: Default::default(),
+            block_confirmations: 6,
         }
     }
 
@@ -661,7 +1033,7 @@ mod tests {
             rpc_user: "test".to_string(),
             rpc_password: "test".to_string(),
             network: BitcoinNetwork::Regtest,
-            coinbase_address: Some("bcrt1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh".to_string()),
+            coinbase_address: Some("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string()),
             block_template_timeout: 30,
         }
     }
@@ -675,10 +1047,141 @@ mod tests {
 
         let handler = SoloModeHandler::new(solo_config, bitcoin_client, database);
         
-        assert_eq!(handler.config.coinbase_address, "bcrt1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh");
+        assert_eq!(handler.config.coinbase_address, "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080");
         assert_eq!(handler.config.block_template_refresh_interval, 30);
     }
 
+    #[tokio::test]
+    async fn test_template_source_selection() {
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let database = Arc::new(MockDatabaseOps::new());
+
+        let handler = SoloModeHandler::new(create_test_solo_config(), bitcoin_client.clone(), database.clone());
+        assert_eq!(handler.template_source.name(), "bitcoin_rpc");
+
+        let sv2_tp_config = SoloConfig {
+            template_source: crate::template_source::TemplateSourceConfig::Sv2Tp {
+                address: "127.0.0.1:8442".to_string(),
+            },
+            ..create_test_solo_config()
+        };
+        let handler = SoloModeHandler::new(sv2_tp_config, bitcoin_client.clone(), database.clone());
+        assert_eq!(handler.template_source.name(), "sv2_tp");
+
+        // Work comes from whichever source is plugged in
+        let template = crate::template_source::MockTemplateSource::template([9; 32], 2.0);
+        let mock = crate::template_source::MockTemplateSource::new(vec![template.clone()]);
+        let handler = SoloModeHandler::new(create_test_solo_config(), bitcoin_client, database)
+            .with_template_source(Arc::new(mock));
+        assert_eq!(handler.ensure_work_template().await.unwrap().id, template.id);
+    }
+
+    #[tokio::test]
+    async fn test_reorg_replaces_template_and_orphans_found_block() {
+        use crate::reorg::MockChainView;
+        use crate::template_source::MockTemplateSource;
+
+        let original = MockTemplateSource::template([1; 32], 2.0);
+        let competing = MockTemplateSource::template([2; 32], 2.0);
+        let chain = Arc::new(MockChainView::new());
+        chain.set(original.previous_hash, Some(1));
+        let database = Arc::new(MockDatabaseOps::new());
+        let handler = SoloModeHandler::new(
+            create_test_solo_config(),
+            BitcoinRpcClient::new(create_test_bitcoin_config()),
+            database.clone(),
+        )
+        .with_template_source(Arc::new(MockTemplateSource::new(vec![original.clone()])))
+        .with_chain_view(chain.clone());
+        let mut work = handler.subscribe_work();
+
+        assert_eq!(handler.ensure_work_template().await.unwrap().id, original.id);
+        assert_eq!(work.recv().await.unwrap().template.id, original.id);
+
+        // A block found on the original tip, then a competing branch wins
+        let found = BlockHash::from_byte_array([7; 32]);
+        chain.set(found, Some(1));
+        handler.blocks.submitted(database.as_ref(), BlockRecord::submitted(found, &original)).await;
+        handler.stats.write().await.blocks_found = 1;
+        chain.set(original.previous_hash, None);
+        chain.set(found, None);
+
+        handler.template_updater().update(competing.clone()).await;
+
+        let current = handler.current_template.read().await.clone().unwrap();
+        assert_eq!(current.id, competing.id);
+        let update = work.recv().await.unwrap();
+        assert_eq!(update.template.id, competing.id);
+        assert!(update.clean_jobs);
+
+        let stats = handler.get_statistics().await.unwrap();
+        assert_eq!(stats.blocks_found, 0);
+        assert_eq!(stats.blocks_orphaned, 1);
+        assert!(handler.blocks.watched().await.is_empty());
+        let alerts = database.get_alerts(None, None).await.unwrap();
+        assert!(alerts.iter().any(|alert| alert.message.contains(&found.to_string())));
+
+        // A refresh on the same tip doesn't make miners drop their jobs
+        handler.template_updater().update(competing).await;
+        assert!(!work.recv().await.unwrap().clean_jobs);
+    }
+
+    #[tokio::test]
+    async fn test_health_degrades_on_stale_template_and_node_sync() {
+        use crate::template_source::MockTemplateSource;
+
+        let handler = SoloModeHandler::new(
+            create_test_solo_config(),
+            BitcoinRpcClient::new(create_test_bitcoin_config()),
+            Arc::new(MockDatabaseOps::new()),
+        );
+        assert_eq!(handler.health().await.status, HealthStatus::Critical);
+
+        let template = MockTemplateSource::template([1; 32], 2.0);
+        *handler.current_template.write().await = Some(template.clone());
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+        assert!(health.metadata.contains_key("template_age_secs"));
+
+        *handler.current_template.write().await = Some(WorkTemplate {
+            expires_at: chrono::Utc::now() - chrono::Duration::seconds(1),
+            ..template.clone()
+        });
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert!(health.message.contains("stale"));
+
+        *handler.current_template.write().await = Some(template);
+        *handler.node_sync.write().await = Some(NodeSync { initial_block_download: true, blocks: 100, headers: 800_000 });
+        let health = handler.health().await;
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert!(health.message.contains("syncing"));
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_active_coinbase_tag() {
+        use crate::coinbase_tag::{CoinbaseTagConfig, TagRotation};
+
+        let solo_config = SoloConfig {
+            coinbase_tags: CoinbaseTagConfig {
+                tags: vec!["/rig-a/".to_string(), "/rig-b/".to_string()],
+                rotation: TagRotation::PerBlock,
+            },
+            ..create_test_solo_config()
+        };
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let handler = SoloModeHandler::new(solo_config, bitcoin_client, Arc::new(MockDatabaseOps::new()));
+
+        let stats = handler.get_statistics().await.unwrap();
+        assert_eq!(stats.coinbase_tag.as_deref(), Some("/rig-a/"));
+
+        // The RPC client shares the rotator, so each new block's coinbase moves it on
+        assert_eq!(handler.coinbase_tags.tag_for_height(500), "/rig-a/");
+        assert_eq!(handler.coinbase_tags.tag_for_height(501), "/rig-b/");
+        let stats = handler.get_statistics().await.unwrap();
+        assert_eq!(stats.coinbase_tag.as_deref(), Some("/rig-b/"));
+    }
+
     #[tokio::test]
     async fn test_connection_handling() {
         let solo_config = create_test_solo_config();
@@ -720,6 +1223,25 @@ mod tests {
         assert!(result.is_err() || matches!(result.unwrap(), ShareResult::Valid | ShareResult::Invalid(_)));
     }
 
+    #[tokio::test]
+    async fn test_share_validation_latency_recorded() {
+        let bitcoin_client = BitcoinRpcClient::new(create_test_bitcoin_config());
+        let database = Arc::new(MockDatabaseOps::new());
+        let metrics = Arc::new(MetricsCollector::new(Default::default()).unwrap());
+
+        let template = crate::template_source::MockTemplateSource::template([3; 32], 1e12);
+        let mock = crate::template_source::MockTemplateSource::new(vec![template]);
+        let handler = SoloModeHandler::new(create_test_solo_config(), bitcoin_client, database)
+            .with_template_source(Arc::new(mock))
+            .with_metrics(Arc::clone(&metrics));
+        assert_eq!(metrics.share_validation_latency().count, 0);
+
+        let share = Share::new(Uuid::new_v4(), 12345, chrono::Utc::now().timestamp() as u32, 1.0);
+        handler.process_share(share).await.unwrap();
+
+        assert!(metrics.share_validation_latency().count >= 1);
+    }
+
     #[tokio::test]
     async fn test_statistics_update() {
         let solo_config = create_test_solo_config();
@@ -802,6 +1324,17 @@ mod tests {
         };
         
         assert!(handler.validate_config(&invalid_config).is_err());
+
+        // A mainnet address while mining on regtest
+        let wrong_network = DaemonConfig {
+            mode: crate::config::OperationModeConfig::Solo(SoloConfig {
+                coinbase_address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_string(),
+                ..create_test_solo_config()
+            }),
+            ..Default::default()
+        };
+        let error = handler.validate_config(&wrong_network).unwrap_err().to_string();
+        assert!(error.contains("is a mainnet address, not a regtest one"), "{}", error);
     }
 
     #[tokio::test]
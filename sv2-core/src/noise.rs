@@ -0,0 +1,297 @@
+//! Noise NX sessions for Stratum V2 connections
+//!
+//! SV2 connections are encrypted: before any frame is exchanged the client
+//! (initiator) and the pool (responder) run the Noise NX handshake, in which
+//! the responder sends its static key together with a certificate signed by
+//! the pool's authority key. The initiator checks that signature against the
+//! authority key it was configured with, so a man in the middle can't stand
+//! in for the pool. The handshake and cipher states come from SRI's
+//! `noise_sv2`; this module drives them without doing any I/O, so callers
+//! decide how handshake messages and frames travel.
+//!
+//! Once established, every frame is encrypted as SRI does it: the 6 byte
+//! header on its own, then the payload in chunks of at most 65535 encrypted
+//! bytes, each carrying its own 16 byte MAC.
+
+use crate::protocol::{parse_sv2_message, ProtocolMessage, SV2_HEADER_LEN};
+use crate::sv2_codec::Sv2Message;
+use crate::{Error, Result};
+use noise_sv2::{Initiator, NoiseCodec, Responder};
+use std::time::Duration;
+
+/// The initiator's ephemeral key, ElligatorSwift encoded
+pub const NOISE_INITIATOR_MESSAGE_LEN: usize = 64;
+
+/// The responder's ephemeral key, encrypted static key and encrypted
+/// certificate
+pub const NOISE_RESPONDER_MESSAGE_LEN: usize = 64 + (64 + 16) + (74 + 16);
+
+const MAC_LEN: usize = 16;
+
+/// Largest ciphertext chunk of an encrypted payload, MAC included
+const MAX_CHUNK_LEN: usize = 65535;
+
+const ENCRYPTED_HEADER_LEN: usize = SV2_HEADER_LEN + MAC_LEN;
+
+enum State {
+    Initiator(Box<Initiator>),
+    Responder(Box<Responder>),
+    Established(Box<NoiseCodec>),
+    /// A handshake step failed; the connection has to be dropped
+    Failed,
+}
+
+/// One side of an encrypted SV2 connection
+pub struct Sv2NoiseSession {
+    state: State,
+}
+
+impl std::fmt::Debug for Sv2NoiseSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self.state {
+            State::Initiator(_) => "initiator handshake",
+            State::Responder(_) => "responder handshake",
+            State::Established(_) => "established",
+            State::Failed => "failed",
+        };
+        f.debug_struct("Sv2NoiseSession").field("state", &state).finish()
+    }
+}
+
+impl Sv2NoiseSession {
+    /// Client side, accepting only a responder whose certificate is signed by
+    /// `authority_public_key` (x-only, as in SRI configs)
+    pub fn initiator(authority_public_key: [u8; 32]) -> Result<Self> {
+        let initiator = Initiator::from_raw_k(authority_public_key)
+            .map_err(|e| Error::Protocol(format!("Invalid authority public key: {:?}", e)))?;
+        Ok(Self { state: State::Initiator(initiator) })
+    }
+
+    /// Pool side, issuing certificates valid for `cert_validity` signed with
+    /// the authority keypair
+    pub fn responder(
+        authority_public_key: [u8; 32],
+        authority_private_key: [u8; 32],
+        cert_validity: Duration,
+    ) -> Result<Self> {
+        let responder = Responder::from_authority_kp(&authority_public_key, &authority_private_key, cert_validity)
+            .map_err(|e| Error::Protocol(format!("Invalid authority keypair: {:?}", e)))?;
+        Ok(Self { state: State::Responder(responder) })
+    }
+
+    /// Whether the handshake has completed and frames can be exchanged
+    pub fn is_established(&self) -> bool {
+        matches!(self.state, State::Established(_))
+    }
+
+    /// The initiator's first handshake message
+    pub fn start_handshake(&mut self) -> Result<Vec<u8>> {
+        match &mut self.state {
+            State::Initiator(initiator) => {
+                let message = initiator.step_0()
+                    .map_err(|e| Error::Protocol(format!("Noise handshake failed: {:?}", e)))?;
+                Ok(message.to_vec())
+            }
+            _ => Err(Error::Protocol("Only an initiator that hasn't started can start the Noise handshake".to_string())),
+        }
+    }
+
+    /// Take the peer's handshake message, returning the reply to send back
+    /// if there is one. The session is established once this succeeds.
+    pub fn read_handshake(&mut self, message: &[u8]) -> Result<Option<Vec<u8>>> {
+        match std::mem::replace(&mut self.state, State::Failed) {
+            State::Responder(mut responder) => {
+                let message: [u8; NOISE_INITIATOR_MESSAGE_LEN] = message.try_into().map_err(|_| {
+                    Error::Protocol(format!(
+                        "Noise handshake message is {} bytes, expected {}", message.len(), NOISE_INITIATOR_MESSAGE_LEN
+                    ))
+                })?;
+                let (reply, codec) = responder.step_1(message)
+                    .map_err(|e| Error::Protocol(format!("Noise handshake failed: {:?}", e)))?;
+                self.state = State::Established(Box::new(codec));
+                Ok(Some(reply.to_vec()))
+            }
+            State::Initiator(mut initiator) => {
+                let message: [u8; NOISE_RESPONDER_MESSAGE_LEN] = message.try_into().map_err(|_| {
+                    Error::Protocol(format!(
+                        "Noise handshake message is {} bytes, expected {}", message.len(), NOISE_RESPONDER_MESSAGE_LEN
+                    ))
+                })?;
+                let codec = initiator.step_2(message).map_err(|e| match e {
+                    noise_sv2::Error::InvalidCertificate(_) => Error::Protocol(
+                        "Noise handshake failed: the pool's certificate isn't signed by the configured authority key".to_string()
+                    ),
+                    e => Error::Protocol(format!("Noise handshake failed: {:?}", e)),
+                })?;
+                self.state = State::Established(Box::new(codec));
+                Ok(None)
+            }
+            state => {
+                self.state = state;
+                Err(Error::Protocol("No Noise handshake in progress".to_string()))
+            }
+        }
+    }
+
+    /// Encrypt one plaintext frame
+    pub fn encrypt_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        let codec = self.codec()?;
+        if frame.len() < SV2_HEADER_LEN {
+            return Err(Error::Protocol(format!("Frame shorter than the {} byte header", SV2_HEADER_LEN)));
+        }
+        let (header, payload) = frame.split_at(SV2_HEADER_LEN);
+
+        let mut encrypted = Vec::with_capacity(frame.len() + MAC_LEN * (2 + payload.len() / MAX_CHUNK_LEN));
+        for chunk in std::iter::once(header).chain(payload.chunks(MAX_CHUNK_LEN - MAC_LEN)) {
+            let mut buffer = chunk.to_vec();
+            codec.encrypt(&mut buffer)
+                .map_err(|e| Error::Protocol(format!("Failed to encrypt frame: {:?}", e)))?;
+            encrypted.extend_from_slice(&buffer);
+        }
+        Ok(encrypted)
+    }
+
+    /// Decrypt one encrypted frame back to its plaintext header and payload
+    pub fn decrypt_frame(&mut self, encrypted: &[u8]) -> Result<Vec<u8>> {
+        let codec = self.codec()?;
+        if encrypted.len() < ENCRYPTED_HEADER_LEN {
+            return Err(Error::Protocol(format!(
+                "Encrypted frame shorter than the {} byte header", ENCRYPTED_HEADER_LEN
+            )));
+        }
+        let (header, payload) = encrypted.split_at(ENCRYPTED_HEADER_LEN);
+
+        let mut frame = header.to_vec();
+        codec.decrypt(&mut frame)
+            .map_err(|e| Error::Protocol(format!("Failed to decrypt frame header: {:?}", e)))?;
+        let length = u32::from_le_bytes([frame[3], frame[4], frame[5], 0]) as usize;
+        if payload.len() != encrypted_payload_len(length) {
+            return Err(Error::Protocol(format!(
+                "Encrypted payload is {} bytes, header declares {} plaintext bytes", payload.len(), length
+            )));
+        }
+
+        for chunk in payload.chunks(MAX_CHUNK_LEN) {
+            let mut buffer = chunk.to_vec();
+            codec.decrypt(&mut buffer)
+                .map_err(|e| Error::Protocol(format!("Failed to decrypt frame payload: {:?}", e)))?;
+            frame.extend_from_slice(&buffer);
+        }
+        Ok(frame)
+    }
+
+    /// Encode and encrypt `message`
+    pub fn write_message(&mut self, message: &Sv2Message) -> Result<Vec<u8>> {
+        self.encrypt_frame(&message.encode()?)
+    }
+
+    /// Decrypt and parse one frame
+    pub fn read_message(&mut self, encrypted: &[u8]) -> Result<ProtocolMessage> {
+        let frame = self.decrypt_frame(encrypted)?;
+        Ok(parse_sv2_message(&frame)?)
+    }
+
+    fn codec(&mut self) -> Result<&mut NoiseCodec> {
+        match &mut self.state {
+            State::Established(codec) => Ok(codec),
+            State::Failed => Err(Error::Protocol("Noise handshake failed; the session can't carry frames".to_string())),
+            _ => Err(Error::Protocol("Noise handshake hasn't completed".to_string())),
+        }
+    }
+}
+
+/// Size on the wire of a payload of `length` plaintext bytes
+fn encrypted_payload_len(length: usize) -> usize {
+    let chunks = length.div_ceil(MAX_CHUNK_LEN - MAC_LEN);
+    length + chunks * MAC_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sv2_codec::SetupConnection;
+    use bitcoin::secp256k1::{KeyPair, Secp256k1, SecretKey};
+
+    fn authority(secret: u8) -> ([u8; 32], [u8; 32]) {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[secret; 32]).unwrap();
+        let keypair = KeyPair::from_secret_key(&secp, &secret_key);
+        (keypair.x_only_public_key().0.serialize(), secret_key.secret_bytes())
+    }
+
+    fn handshake(initiator_authority: [u8; 32]) -> Result<(Sv2NoiseSession, Sv2NoiseSession)> {
+        let (public_key, private_key) = authority(0x11);
+        let mut responder = Sv2NoiseSession::responder(public_key, private_key, Duration::from_secs(3600))?;
+        let mut initiator = Sv2NoiseSession::initiator(initiator_authority)?;
+
+        let first = initiator.start_handshake()?;
+        let reply = responder.read_handshake(&first)?.expect("responder replies to the ephemeral key");
+        assert_eq!(reply.len(), NOISE_RESPONDER_MESSAGE_LEN);
+        assert!(initiator.read_handshake(&reply)?.is_none());
+        Ok((initiator, responder))
+    }
+
+    fn setup_connection() -> Sv2Message {
+        Sv2Message::SetupConnection(SetupConnection {
+            protocol: 0,
+            min_version: 2,
+            max_version: 2,
+            flags: 1,
+            endpoint_host: "0.0.0.0".to_string(),
+            endpoint_port: 34254,
+            vendor: "Bitmain".to_string(),
+            hardware_version: "S9i 13.5".to_string(),
+            firmware: "braiins-os-2018-09-22-1-hash".to_string(),
+            device_id: "some-device-uuid".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_handshake_then_setup_connection() {
+        let (mut initiator, mut responder) = handshake(authority(0x11).0).unwrap();
+        assert!(initiator.is_established() && responder.is_established());
+
+        let plaintext = setup_connection().encode().unwrap();
+        let encrypted = initiator.write_message(&setup_connection()).unwrap();
+        assert_eq!(encrypted.len(), plaintext.len() + 2 * MAC_LEN);
+        assert!(!encrypted.windows(7).any(|w| w == b"Bitmain"));
+
+        let frame = responder.decrypt_frame(&encrypted).unwrap();
+        assert_eq!(Sv2Message::decode(&frame).unwrap(), setup_connection());
+
+        // And back the other way, through the parser
+        let encrypted = responder.write_message(&setup_connection()).unwrap();
+        assert!(matches!(initiator.read_message(&encrypted).unwrap(), ProtocolMessage::Sv2SetupConnection));
+
+        // A tampered frame fails its MAC
+        let mut tampered = initiator.write_message(&setup_connection()).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(responder.decrypt_frame(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_wrong_authority_key_is_rejected() {
+        let err = handshake(authority(0x22).0).unwrap_err();
+        assert!(matches!(&err, Error::Protocol(msg) if msg.contains("authority key")), "{}", err);
+    }
+
+    #[test]
+    fn test_frames_need_an_established_session() {
+        let mut initiator = Sv2NoiseSession::initiator(authority(0x11).0).unwrap();
+        assert!(initiator.encrypt_frame(&setup_connection().encode().unwrap()).is_err());
+
+        // A malformed handshake message ends the session
+        initiator.start_handshake().unwrap();
+        assert!(initiator.read_handshake(&[0u8; 10]).is_err());
+        assert!(initiator.read_handshake(&[0u8; NOISE_RESPONDER_MESSAGE_LEN]).is_err());
+        assert!(!initiator.is_established());
+    }
+
+    #[test]
+    fn test_large_payloads_are_chunked() {
+        assert_eq!(encrypted_payload_len(0), 0);
+        assert_eq!(encrypted_payload_len(65519), 65535);
+        assert_eq!(encrypted_payload_len(65520), 65520 + 2 * MAC_LEN);
+    }
+}
@@ -1,6 +1,12 @@
 use crate::{Result, Error};
+use crate::metrics::{MetricsCollector, TranslationOutcome};
+use crate::share_target::target_to_difficulty;
+use crate::sv2_codec::Sv2Message;
 use crate::types::{Protocol, Share, WorkTemplate, Job, ShareSubmission};
+use bitcoin::hashes::Hash;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 /// Protocol message types for translation between SV1 and SV2
@@ -11,11 +17,17 @@ pub enum ProtocolMessage {
     Sv1Authorize { username: String, password: String },
     Sv1Submit { worker: String, job_id: String, nonce: u32 },
     Sv1Notify { job_id: String, difficulty: f64 },
+    Sv1SetDifficulty { difficulty: f64 },
 
     // SV2 Messages
     Sv2SetupConnection,
+    Sv2OpenChannelRequest { request_id: u32, user_identity: String },
     Sv2OpenChannel { channel_id: u32 },
+    Sv2NewMiningJob { channel_id: u32, job_id: u32 },
+    /// `target` is big-endian
+    Sv2SetTarget { channel_id: u32, target: [u8; 32] },
     Sv2SubmitShares { channel_id: u32, shares: Vec<Share> },
+    Sv2SubmitSharesExtended { channel_id: u32, shares: Vec<Share>, extranonce: Vec<u8> },
     Sv2NewTemplate { template: WorkTemplate },
 
     // Generic
@@ -33,9 +45,14 @@ impl ProtocolMessage {
             ProtocolMessage::Sv1Authorize { .. } => "sv1.authorize",
             ProtocolMessage::Sv1Submit { .. } => "sv1.submit",
             ProtocolMessage::Sv1Notify { .. } => "sv1.notify",
+            ProtocolMessage::Sv1SetDifficulty { .. } => "sv1.set_difficulty",
             ProtocolMessage::Sv2SetupConnection => "sv2.setup_connection",
+            ProtocolMessage::Sv2OpenChannelRequest { .. } => "sv2.open_channel_request",
             ProtocolMessage::Sv2OpenChannel { .. } => "sv2.open_channel",
+            ProtocolMessage::Sv2NewMiningJob { .. } => "sv2.new_mining_job",
+            ProtocolMessage::Sv2SetTarget { .. } => "sv2.set_target",
             ProtocolMessage::Sv2SubmitShares { .. } => "sv2.submit_shares",
+            ProtocolMessage::Sv2SubmitSharesExtended { .. } => "sv2.submit_shares_extended",
             ProtocolMessage::Sv2NewTemplate { .. } => "sv2.new_template",
             ProtocolMessage::Subscribe { .. } => "subscribe",
             ProtocolMessage::Authorize { .. } => "authorize",
@@ -46,23 +63,249 @@ impl ProtocolMessage {
     }
 }
 
+/// SV2 frame header length: extension type (u16), message type (u8), length (u24)
+pub const SV2_HEADER_LEN: usize = 6;
+
+/// SV2 message types understood by the parser
+pub mod sv2_message_types {
+    pub const SETUP_CONNECTION: u8 = 0x00;
+    pub const OPEN_STANDARD_MINING_CHANNEL: u8 = 0x10;
+    pub const OPEN_STANDARD_MINING_CHANNEL_SUCCESS: u8 = 0x11;
+    pub const NEW_MINING_JOB: u8 = 0x15;
+    pub const SUBMIT_SHARES_STANDARD: u8 = 0x1a;
+    pub const SUBMIT_SHARES_EXTENDED: u8 = 0x1b;
+    pub const SET_TARGET: u8 = 0x21;
+    /// Template distribution protocol
+    pub const NEW_TEMPLATE: u8 = 0x71;
+}
+
+/// Parse failure with the byte (SV2) or column (SV1) offset it occurred at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolParseError {
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl ProtocolParseError {
+    pub(crate) fn new(offset: usize, reason: impl Into<String>) -> Self {
+        Self { offset, reason: reason.into() }
+    }
+}
+
+impl std::fmt::Display for ProtocolParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.reason, self.offset)
+    }
+}
+
+impl std::error::Error for ProtocolParseError {}
+
+impl From<ProtocolParseError> for Error {
+    fn from(err: ProtocolParseError) -> Self {
+        Error::Protocol(err.to_string())
+    }
+}
+
+/// Parse a single newline-free SV1 JSON-RPC message
+pub fn parse_sv1_message(input: &str) -> std::result::Result<ProtocolMessage, ProtocolParseError> {
+    let value: serde_json::Value = serde_json::from_str(input.trim())
+        .map_err(|e| ProtocolParseError::new(e.column(), format!("Invalid JSON: {}", e)))?;
+
+    let method = value.get("method").and_then(|m| m.as_str())
+        .ok_or_else(|| ProtocolParseError::new(0, "Missing method"))?;
+    let params = value.get("params").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    let param = |index: usize| -> std::result::Result<String, ProtocolParseError> {
+        params.get(index)
+            .and_then(|p| p.as_str())
+            .map(String::from)
+            .ok_or_else(|| ProtocolParseError::new(0, format!("{} is missing string param {}", method, index)))
+    };
+
+    match method {
+        "mining.subscribe" => Ok(ProtocolMessage::Sv1Subscribe {
+            id: value.get("id").map(|id| id.to_string()).unwrap_or_default(),
+            version: param(0).unwrap_or_default(),
+        }),
+        "mining.authorize" => Ok(ProtocolMessage::Sv1Authorize {
+            username: param(0)?,
+            password: param(1).unwrap_or_default(),
+        }),
+        "mining.submit" => {
+            let nonce = param(4)?;
+            Ok(ProtocolMessage::Sv1Submit {
+                worker: param(0)?,
+                job_id: param(1)?,
+                nonce: u32::from_str_radix(&nonce, 16)
+                    .map_err(|e| ProtocolParseError::new(0, format!("Invalid nonce {}: {}", nonce, e)))?,
+            })
+        }
+        other => Err(ProtocolParseError::new(0, format!("Unsupported method {}", other))),
+    }
+}
+
+/// Parse a single SV2 frame (header and payload)
+pub fn parse_sv2_message(frame: &[u8]) -> std::result::Result<ProtocolMessage, ProtocolParseError> {
+    Ok(match Sv2Message::decode(frame)? {
+        Sv2Message::SetupConnection(_) => ProtocolMessage::Sv2SetupConnection,
+        Sv2Message::OpenStandardMiningChannel(m) => ProtocolMessage::Sv2OpenChannelRequest {
+            request_id: m.request_id,
+            user_identity: m.user_identity,
+        },
+        Sv2Message::OpenStandardMiningChannelSuccess(m) => ProtocolMessage::Sv2OpenChannel {
+            channel_id: m.channel_id,
+        },
+        Sv2Message::NewMiningJob(m) => ProtocolMessage::Sv2NewMiningJob {
+            channel_id: m.channel_id,
+            job_id: m.job_id,
+        },
+        Sv2Message::SubmitSharesStandard(m) => ProtocolMessage::Sv2SubmitShares {
+            channel_id: m.channel_id,
+            shares: vec![Share::new(Uuid::nil(), m.nonce, m.ntime, 1.0)],
+        },
+        Sv2Message::SubmitSharesExtended(m) => ProtocolMessage::Sv2SubmitSharesExtended {
+            channel_id: m.channel_id,
+            shares: vec![Share::new(Uuid::nil(), m.nonce, m.ntime, 1.0)],
+            extranonce: m.extranonce,
+        },
+        Sv2Message::SetTarget(m) => {
+            let mut target = m.maximum_target;
+            target.reverse();
+            ProtocolMessage::Sv2SetTarget { channel_id: m.channel_id, target }
+        }
+        // The previous block hash comes in a separate SetNewPrevHash
+        Sv2Message::NewTemplate(m) => ProtocolMessage::Sv2NewTemplate {
+            template: m.to_work_template(bitcoin::BlockHash::all_zeros())
+                .map_err(|e| ProtocolParseError::new(SV2_HEADER_LEN, e.to_string()))?,
+        },
+    })
+}
+
+/// What the translator has seen of one SV2 channel and told its SV1 miner
+#[derive(Debug, Default)]
+struct TranslationState {
+    /// Latest share target the upstream set, big-endian
+    target: Option<[u8; 32]>,
+    /// Difficulty last sent in a `mining.set_difficulty`
+    sent_difficulty: Option<f64>,
+}
+
 /// Protocol translator for converting between SV1 and SV2
 pub struct ProtocolTranslator {
     /// Current protocol mode
     mode: Protocol,
+    /// Optional metrics sink for translation outcomes
+    metrics: Option<Arc<MetricsCollector>>,
+    /// Per-channel state; one translator serves every downstream connection
+    channels: Mutex<HashMap<u32, TranslationState>>,
 }
 
 impl ProtocolTranslator {
     pub fn new(mode: Protocol) -> Self {
-        Self { mode }
+        Self { mode, metrics: None, channels: Mutex::new(HashMap::new()) }
+    }
+
+    /// Record translation outcomes in the given metrics collector
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     /// Translate a message from one protocol to another
     pub fn translate(&self, message: ProtocolMessage, target: Protocol) -> Result<ProtocolMessage> {
+        let direction = match (self.mode, target) {
+            (Protocol::Sv1, Protocol::Sv2) => "sv1_to_sv2",
+            (Protocol::Sv2, Protocol::Sv1) => "sv2_to_sv1",
+            _ => return Ok(message), // Same protocol, no translation needed
+        };
+        let message_type = message.message_type();
+
+        if !self.can_translate(&message, target) {
+            self.record(direction, message_type, TranslationOutcome::Fallback);
+            return self.handle_translation_fallback(message);
+        }
+
+        let result = match target {
+            Protocol::Sv2 => self.sv1_to_sv2(message),
+            _ => self.sv2_to_sv1(message, None),
+        };
+
+        let outcome = if result.is_ok() {
+            TranslationOutcome::Succeeded
+        } else {
+            TranslationOutcome::Failed
+        };
+        self.record(direction, message_type, outcome);
+
+        result
+    }
+
+    /// Start tracking a channel whose miner already works at `difficulty`,
+    /// so no `mining.set_difficulty` is sent until it changes
+    pub fn open_channel(&self, channel_id: u32, difficulty: f64) {
+        self.channels.lock().unwrap().insert(
+            channel_id,
+            TranslationState { target: None, sent_difficulty: Some(difficulty) },
+        );
+    }
+
+    /// Forget a closed channel's target and difficulty
+    pub fn close_channel(&self, channel_id: u32) {
+        self.channels.lock().unwrap().remove(&channel_id);
+    }
+
+    /// Translate an SV2 message into what the SV1 miner on `channel_id`
+    /// should be sent, in order. A `SetTarget` only updates the difficulty of
+    /// the channel it names, which goes out in a `mining.set_difficulty`
+    /// ahead of the next `mining.notify` on that channel that changes it.
+    pub fn translate_sv2_to_sv1(&self, channel_id: u32, message: ProtocolMessage) -> Result<Vec<ProtocolMessage>> {
+        let mut channels = self.channels.lock().unwrap();
+        if let ProtocolMessage::Sv2SetTarget { channel_id, target } = message {
+            channels.entry(channel_id).or_default().target = Some(target);
+            return Ok(Vec::new());
+        }
+
+        let message_type = message.message_type();
+        if !matches!(message, ProtocolMessage::Sv2NewTemplate { .. }) {
+            self.record("sv2_to_sv1", message_type, TranslationOutcome::Fallback);
+            return Ok(vec![self.handle_translation_fallback(message)?]);
+        }
+
+        let state = channels.entry(channel_id).or_default();
+        let translated = self.sv2_to_sv1(message, state.target.as_ref())?;
+        self.record("sv2_to_sv1", message_type, TranslationOutcome::Succeeded);
+
+        let mut messages = Vec::with_capacity(2);
+        if let ProtocolMessage::Sv1Notify { difficulty, .. } = translated {
+            if state.sent_difficulty != Some(difficulty) {
+                state.sent_difficulty = Some(difficulty);
+                messages.push(ProtocolMessage::Sv1SetDifficulty { difficulty });
+            }
+        }
+        messages.push(translated);
+        Ok(messages)
+    }
+
+    /// Check whether a message has a translation into the target protocol
+    pub fn can_translate(&self, message: &ProtocolMessage, target: Protocol) -> bool {
         match (self.mode, target) {
-            (Protocol::Sv1, Protocol::Sv2) => self.sv1_to_sv2(message),
-            (Protocol::Sv2, Protocol::Sv1) => self.sv2_to_sv1(message),
-            _ => Ok(message), // Same protocol, no translation needed
+            (Protocol::Sv1, Protocol::Sv2) => matches!(
+                message,
+                ProtocolMessage::Sv1Subscribe { .. } | ProtocolMessage::Sv1Submit { .. }
+            ),
+            (Protocol::Sv2, Protocol::Sv1) => matches!(message, ProtocolMessage::Sv2NewTemplate { .. }),
+            _ => true,
+        }
+    }
+
+    /// Pass a message through unchanged when no translation exists
+    pub fn handle_translation_fallback(&self, message: ProtocolMessage) -> Result<ProtocolMessage> {
+        tracing::debug!("No translation for {}, passing through", message.message_type());
+        Ok(message)
+    }
+
+    fn record(&self, direction: &str, message_type: &str, outcome: TranslationOutcome) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_translation(direction, message_type, outcome);
         }
     }
 
@@ -73,6 +316,14 @@ impl ProtocolTranslator {
                 Ok(ProtocolMessage::Sv2SetupConnection)
             }
             ProtocolMessage::Sv1Submit { job_id, nonce, .. } => {
+                // mining.submit echoes the job_id of the mining.notify being
+                // worked on, and SubmitSharesStandard has to name the job the
+                // share solves. A submit without one can't belong to any job
+                // upstream, so it fails here rather than going out as a share
+                // for whichever job happens to be first.
+                if job_id.is_empty() {
+                    return Err(Error::Protocol("Share submission is missing a job ID".to_string()));
+                }
                 // In a real implementation, we would look up the job and create proper shares
                 Ok(ProtocolMessage::Sv2SubmitShares {
                     channel_id: 0,
@@ -83,13 +334,14 @@ impl ProtocolTranslator {
         }
     }
 
-    /// Convert SV2 message to SV1
-    fn sv2_to_sv1(&self, message: ProtocolMessage) -> Result<ProtocolMessage> {
+    /// Convert SV2 message to SV1, at the channel's share target once the
+    /// upstream has set one
+    fn sv2_to_sv1(&self, message: ProtocolMessage, target: Option<&[u8; 32]>) -> Result<ProtocolMessage> {
         match message {
             ProtocolMessage::Sv2NewTemplate { template } => {
                 Ok(ProtocolMessage::Sv1Notify {
                     job_id: template.id.to_string(),
-                    difficulty: template.difficulty,
+                    difficulty: target.and_then(target_to_difficulty).unwrap_or(template.difficulty),
                 })
             }
             _ => Ok(message),
@@ -118,3 +370,163 @@ pub type NetworkProtocolMessage = ProtocolMessage;
 
 /// Stratum message (alias)
 pub type StratumMessage = ProtocolMessage;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submit_shares_frame() -> Vec<u8> {
+        let mut frame = vec![0x00, 0x00, sv2_message_types::SUBMIT_SHARES_STANDARD, 24, 0, 0];
+        for field in [1u32, 2, 3, 0x12345678, 0x5f5e1000, 0x20000000] {
+            frame.extend_from_slice(&field.to_le_bytes());
+        }
+        frame
+    }
+
+    #[test]
+    fn test_parse_sv2_submit_shares() {
+        match parse_sv2_message(&submit_shares_frame()).unwrap() {
+            ProtocolMessage::Sv2SubmitShares { channel_id, shares } => {
+                assert_eq!(channel_id, 1);
+                assert_eq!(shares[0].nonce, 0x12345678);
+                assert_eq!(shares[0].timestamp, 0x5f5e1000);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_sv2_submit_shares_extended() {
+        let mut frame = submit_shares_frame();
+        frame[2] = sv2_message_types::SUBMIT_SHARES_EXTENDED;
+        frame[3] += 5;
+        frame.extend_from_slice(&[4, 0xde, 0xad, 0xbe, 0xef]);
+        match parse_sv2_message(&frame).unwrap() {
+            ProtocolMessage::Sv2SubmitSharesExtended { channel_id, shares, extranonce } => {
+                assert_eq!(channel_id, 1);
+                assert_eq!(shares[0].nonce, 0x12345678);
+                assert_eq!(extranonce, [0xde, 0xad, 0xbe, 0xef]);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_sv2_errors_report_offset() {
+        let frame = submit_shares_frame();
+        let err = parse_sv2_message(&frame[..4]).unwrap_err();
+        assert_eq!(err.offset, 4);
+
+        let mut unknown = frame.clone();
+        unknown[2] = 0xff;
+        assert_eq!(parse_sv2_message(&unknown).unwrap_err().offset, 2);
+    }
+
+    fn new_template() -> ProtocolMessage {
+        let coinbase = bitcoin::Transaction {
+            version: 1,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        ProtocolMessage::Sv2NewTemplate {
+            template: WorkTemplate::new(bitcoin::BlockHash::all_zeros(), coinbase, vec![], 1.0),
+        }
+    }
+
+    fn set_target(channel_id: u32, difficulty: f64) -> ProtocolMessage {
+        let target = crate::share_target::difficulty_to_target(difficulty).unwrap();
+        ProtocolMessage::Sv2SetTarget { channel_id, target }
+    }
+
+    fn difficulties(messages: &[ProtocolMessage]) -> Vec<(&'static str, f64)> {
+        messages.iter().map(|message| match message {
+            ProtocolMessage::Sv1SetDifficulty { difficulty } | ProtocolMessage::Sv1Notify { difficulty, .. } => {
+                (message.message_type(), (difficulty * 1e6).round() / 1e6)
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }).collect()
+    }
+
+    #[test]
+    fn test_target_changes_send_set_difficulty_before_notify() {
+        let translator = ProtocolTranslator::new(Protocol::Sv2);
+
+        // No target from upstream yet, so the template's own difficulty
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.set_difficulty", 1.0), ("sv1.notify", 1.0)]);
+
+        assert!(translator.translate_sv2_to_sv1(1, set_target(1, 1024.0)).unwrap().is_empty());
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.set_difficulty", 1024.0), ("sv1.notify", 1024.0)]);
+
+        // Unchanged difficulty, so notify alone
+        translator.translate_sv2_to_sv1(1, set_target(1, 1024.0)).unwrap();
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.notify", 1024.0)]);
+
+        translator.translate_sv2_to_sv1(1, set_target(1, 65536.0)).unwrap();
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.set_difficulty", 65536.0), ("sv1.notify", 65536.0)]);
+    }
+
+    #[test]
+    fn test_channels_keep_their_own_difficulty() {
+        let translator = ProtocolTranslator::new(Protocol::Sv2);
+        translator.open_channel(1, 1.0);
+        translator.open_channel(2, 1.0);
+
+        translator.translate_sv2_to_sv1(1, set_target(1, 1024.0)).unwrap();
+        translator.translate_sv2_to_sv1(2, set_target(2, 65536.0)).unwrap();
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.set_difficulty", 1024.0), ("sv1.notify", 1024.0)]);
+        let messages = translator.translate_sv2_to_sv1(2, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.set_difficulty", 65536.0), ("sv1.notify", 65536.0)]);
+
+        // Channel 2's next job doesn't resend its difficulty or disturb channel 1
+        let messages = translator.translate_sv2_to_sv1(2, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.notify", 65536.0)]);
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.notify", 1024.0)]);
+
+        // Closing forgets the target, so a reused channel ID is back at the template's difficulty
+        translator.close_channel(1);
+        translator.open_channel(1, 1.0);
+        let messages = translator.translate_sv2_to_sv1(1, new_template()).unwrap();
+        assert_eq!(difficulties(&messages), vec![("sv1.notify", 1.0)]);
+    }
+
+    #[test]
+    fn test_parse_sv2_set_target_is_big_endian() {
+        let mut wire_target = crate::share_target::difficulty_to_target(1.0).unwrap();
+        wire_target.reverse();
+        let frame = crate::sv2_codec::Sv2Message::SetTarget(crate::sv2_codec::SetTarget {
+            channel_id: 3,
+            maximum_target: wire_target,
+        }).encode().unwrap();
+
+        match parse_sv2_message(&frame).unwrap() {
+            ProtocolMessage::Sv2SetTarget { channel_id, target } => {
+                assert_eq!(channel_id, 3);
+                assert_eq!(target_to_difficulty(&target), Some(1.0));
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_sv1_submit() {
+        let input = r#"{"id":4,"method":"mining.submit","params":["worker1","job1","00000000","5f5e1000","12345678"]}"#;
+        match parse_sv1_message(input).unwrap() {
+            ProtocolMessage::Sv1Submit { worker, job_id, nonce } => {
+                assert_eq!(worker, "worker1");
+                assert_eq!(job_id, "job1");
+                assert_eq!(nonce, 0x12345678);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+
+        let err = parse_sv1_message(r#"{"id":1,"method":"#).unwrap_err();
+        assert!(err.offset > 0);
+    }
+}
@@ -388,6 +388,30 @@ impl crate::DatabaseOps for RecoveryDatabasePool {
         self.pool.get_config_history(limit).await
     }
 
+    async fn get_worker_record(&self, worker_name: &str, source_ip: &str) -> Result<Option<crate::WorkerRecord>> {
+        self.pool.get_worker_record(worker_name, source_ip).await
+    }
+
+    async fn store_worker_record(&self, record: &crate::WorkerRecord) -> Result<()> {
+        self.pool.store_worker_record(record).await
+    }
+
+    async fn record_milestone(&self, record: &crate::milestones::MilestoneRecord) -> Result<bool> {
+        self.pool.record_milestone(record).await
+    }
+
+    async fn get_milestones(&self) -> Result<Vec<crate::milestones::MilestoneRecord>> {
+        self.pool.get_milestones().await
+    }
+
+    async fn store_block(&self, record: &crate::blocks::BlockRecord) -> Result<()> {
+        self.pool.store_block(record).await
+    }
+
+    async fn get_blocks(&self, limit: Option<u32>) -> Result<Vec<crate::blocks::BlockRecord>> {
+        self.pool.get_blocks(limit).await
+    }
+
     async fn store_connection(&self, conn: &crate::Connection) -> Result<()> {
         self.pool.store_connection(conn).await
     }
@@ -396,6 +420,10 @@ impl crate::DatabaseOps for RecoveryDatabasePool {
         self.pool.store_share(share).await
     }
 
+    async fn store_sampled_share(&self, share: &crate::Share, weight: u64) -> Result<()> {
+        self.pool.store_sampled_share(share, weight).await
+    }
+
     async fn store_work_template(&self, template: &crate::WorkTemplate) -> Result<()> {
         self.pool.store_work_template(template).await
     }
@@ -0,0 +1,112 @@
+//! Chain tip changes
+//!
+//! Every template names the tip it builds on. When a new template's prevhash
+//! differs from the current one, outstanding jobs are stale and miners get
+//! the new work with `clean_jobs` set. If the old tip is still in the node's
+//! active chain the chain just grew; if it has dropped out, the node
+//! reorganised onto another branch.
+//!
+//! Either way, blocks this daemon found are looked up again through the same
+//! [`ChainView`] (see [`crate::blocks`]).
+
+use crate::bitcoin_rpc::BitcoinRpcClient;
+use crate::Result;
+use async_trait::async_trait;
+use bitcoin::BlockHash;
+use tracing::debug;
+
+/// What the node knows about its active chain
+#[async_trait]
+pub trait ChainView: Send + Sync {
+    /// Confirmations of `hash` in the active chain, `None` if it isn't in it
+    async fn confirmations(&self, hash: &BlockHash) -> Result<Option<u64>>;
+}
+
+#[async_trait]
+impl ChainView for BitcoinRpcClient {
+    async fn confirmations(&self, hash: &BlockHash) -> Result<Option<u64>> {
+        // Bitcoin Core reports -1 for blocks outside the active chain
+        let header = self.get_block_header(hash).await?;
+        Ok(u64::try_from(header.confirmations).ok())
+    }
+}
+
+/// How the tip moved between two templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipChange {
+    Unchanged,
+    /// New blocks on top of the previous tip
+    Extended,
+    /// The previous tip left the active chain
+    Reorg,
+}
+
+/// Classify the move from a template building on `current` to one building on `next`
+pub async fn classify_tip_change(chain: &dyn ChainView, current: &BlockHash, next: &BlockHash) -> TipChange {
+    if current == next {
+        return TipChange::Unchanged;
+    }
+    match chain.confirmations(current).await {
+        Ok(Some(_)) => TipChange::Extended,
+        Ok(None) => TipChange::Reorg,
+        Err(e) => {
+            // Jobs get replaced either way; only the log line differs
+            debug!("Couldn't look up previous tip {}: {}", current, e);
+            TipChange::Extended
+        }
+    }
+}
+
+/// Chain view with hand-set confirmations; unknown blocks are not in the chain
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Default)]
+pub struct MockChainView {
+    confirmations: std::sync::Mutex<std::collections::HashMap<BlockHash, u64>>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockChainView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `hash` in the active chain at `depth`, or take it out with `None`
+    pub fn set(&self, hash: BlockHash, depth: Option<u64>) {
+        let mut confirmations = self.confirmations.lock().unwrap();
+        match depth {
+            Some(depth) => confirmations.insert(hash, depth),
+            None => confirmations.remove(&hash),
+        };
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+#[async_trait]
+impl ChainView for MockChainView {
+    async fn confirmations(&self, hash: &BlockHash) -> Result<Option<u64>> {
+        Ok(self.confirmations.lock().unwrap().get(hash).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    fn hash(byte: u8) -> BlockHash {
+        BlockHash::from_byte_array([byte; 32])
+    }
+
+    #[tokio::test]
+    async fn test_classify_tip_change() {
+        let chain = MockChainView::new();
+        chain.set(hash(1), Some(2));
+
+        assert_eq!(classify_tip_change(&chain, &hash(1), &hash(1)).await, TipChange::Unchanged);
+        assert_eq!(classify_tip_change(&chain, &hash(1), &hash(2)).await, TipChange::Extended);
+
+        // The old tip was replaced by a competing branch
+        chain.set(hash(1), None);
+        assert_eq!(classify_tip_change(&chain, &hash(1), &hash(3)).await, TipChange::Reorg);
+    }
+}
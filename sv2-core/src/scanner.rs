@@ -0,0 +1,843 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tracing::{info, warn, debug};
+
+use crate::miner_api;
+
+/// Miner API ports probed unless `scan --ports` says otherwise
+pub const DEFAULT_PORTS: &[u16] = &[
+    4028, // CGMiner API (many ASICs)
+    80,   // Web interface
+    8080, // Alternative web interface
+    4029, // Backup API port
+    3333, // Stratum port (sometimes has web interface)
+];
+
+/// Well-known services no miner exposes its API on
+const NON_MINER_PORTS: &[(u16, &str)] = &[
+    (21, "FTP"),
+    (22, "SSH"),
+    (25, "SMTP"),
+    (53, "DNS"),
+    (139, "NetBIOS"),
+    (445, "SMB"),
+    (3389, "RDP"),
+    (5900, "VNC"),
+    (8332, "Bitcoin RPC"),
+    (8333, "Bitcoin P2P"),
+];
+
+/// Per-host connect timeout unless `scan --timeout` says otherwise
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Adaptive timeouts never widen past this
+const MAX_ADAPTIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answered probes needed before the adaptive timeout moves
+const ADAPTIVE_MIN_SAMPLES: usize = 3;
+
+/// Adaptive timeout as a multiple of the median observed latency
+const ADAPTIVE_LATENCY_MULTIPLIER: u32 = 4;
+
+/// Connect timeout for probes. In adaptive mode it starts at the configured
+/// value and widens to a multiple of the latency answered probes show, so a
+/// congested network doesn't make slow miners look absent.
+#[derive(Debug)]
+pub struct ProbeTimeout {
+    base: Duration,
+    adaptive: bool,
+    latencies: Mutex<Vec<Duration>>,
+}
+
+impl ProbeTimeout {
+    pub fn fixed(timeout: Duration) -> Self {
+        Self { base: timeout, adaptive: false, latencies: Mutex::new(Vec::new()) }
+    }
+
+    pub fn adaptive(initial: Duration) -> Self {
+        Self { base: initial, adaptive: true, latencies: Mutex::new(Vec::new()) }
+    }
+
+    /// Timeout for the next probe
+    pub fn current(&self) -> Duration {
+        if !self.adaptive {
+            return self.base;
+        }
+        let mut latencies = self.latencies.lock().unwrap().clone();
+        if latencies.len() < ADAPTIVE_MIN_SAMPLES {
+            return self.base;
+        }
+        latencies.sort();
+        let typical = latencies[latencies.len() / 2] * ADAPTIVE_LATENCY_MULTIPLIER;
+        typical.clamp(self.base, MAX_ADAPTIVE_TIMEOUT.max(self.base))
+    }
+
+    /// Note how long a host took to answer a connection attempt
+    pub fn record(&self, latency: Duration) {
+        if self.adaptive {
+            self.latencies.lock().unwrap().push(latency);
+        }
+    }
+}
+
+/// Attempts per miner API request; cheap firmware web servers often drop
+/// the first request while busy
+const API_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedMiner {
+    pub ip: IpAddr,
+    pub miner_type: MinerType,
+    pub api_port: Option<u16>,
+    pub response_time_ms: u64,
+    #[serde(skip, default = "std::time::Instant::now")]
+    pub last_seen: Instant,
+    pub details: MinerDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MinerType {
+    Bitaxe,
+    Apollo,
+    AntminerS19,
+    AntminerS21,
+    Whatsminer,
+    Unknown,
+}
+
+impl fmt::Display for MinerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinerType::Bitaxe => write!(f, "Bitaxe"),
+            MinerType::Apollo => write!(f, "Apollo BTC"),
+            MinerType::AntminerS19 => write!(f, "Antminer S19"),
+            MinerType::AntminerS21 => write!(f, "Antminer S21"),
+            MinerType::Whatsminer => write!(f, "Whatsminer"),
+            MinerType::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinerDetails {
+    pub hostname: Option<String>,
+    pub mac_address: Option<String>,
+    pub firmware_version: Option<String>,
+    pub hashrate: Option<f64>,
+    pub temperature: Option<f64>,
+    pub power_consumption: Option<f64>,
+    pub pool_url: Option<String>,
+    pub worker_name: Option<String>,
+    /// Shares the pool accepted since the miner booted
+    pub shares_accepted: Option<u64>,
+}
+
+impl Default for MinerDetails {
+    fn default() -> Self {
+        Self {
+            hostname: None,
+            mac_address: None,
+            firmware_version: None,
+            hashrate: None,
+            temperature: None,
+            power_consumption: None,
+            pool_url: None,
+            worker_name: None,
+            shares_accepted: None,
+        }
+    }
+}
+
+pub struct NetworkScanner {
+    client: Client,
+    probe_timeout: Arc<ProbeTimeout>,
+    ports: Vec<u16>,
+}
+
+impl NetworkScanner {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .expect("Failed to create HTTP client"),
+            probe_timeout: Arc::new(ProbeTimeout::fixed(DEFAULT_PROBE_TIMEOUT)),
+            ports: DEFAULT_PORTS.to_vec(),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: ProbeTimeout) -> Self {
+        self.probe_timeout = Arc::new(timeout);
+        self
+    }
+
+    /// Timeout probes use now; after a scan, the one it finished with
+    pub fn effective_timeout(&self) -> Duration {
+        self.probe_timeout.current()
+    }
+
+    /// Probe `ports` instead of [`DEFAULT_PORTS`]
+    pub fn with_ports(mut self, ports: Vec<u16>) -> Result<Self> {
+        self.ports = validate_ports(ports)?;
+        Ok(self)
+    }
+
+    /// Scan the local network for miners
+    pub async fn scan_network(&self, subnets: Option<Vec<String>>) -> Result<Vec<DetectedMiner>> {
+        let subnets = subnets.unwrap_or_else(|| vec![
+            "192.168.1.0/24".to_string(),
+            "192.168.0.0/24".to_string(), 
+            "10.0.0.0/24".to_string(),
+        ]);
+
+        info!("🔍 Scanning network for miners...");
+        info!("   Subnets: {:?}", subnets);
+
+        let mut detected_miners = Vec::new();
+
+        for subnet in subnets {
+            let subnet_miners = self.scan_subnet(&subnet).await?;
+            detected_miners.extend(subnet_miners);
+        }
+
+        info!("✅ Network scan complete. Found {} miners", detected_miners.len());
+        Ok(detected_miners)
+    }
+
+    /// Scan a specific subnet for miners
+    async fn scan_subnet(&self, subnet: &str) -> Result<Vec<DetectedMiner>> {
+        let (base_ip, _mask) = subnet.split_once('/').unwrap_or((subnet, "24"));
+        let base: Vec<u8> = base_ip.split('.').map(|s| s.parse().unwrap_or(0)).collect();
+        
+        if base.len() != 4 {
+            return Err(anyhow::anyhow!("Invalid subnet format: {}", subnet));
+        }
+
+        let mut miners = Vec::new();
+        let mut scan_tasks = Vec::new();
+
+        // Scan 192.168.x.1-254 range (skip .0 and .255)
+        for i in 1..255 {
+            let ip = Ipv4Addr::new(base[0], base[1], base[2], i);
+            let scanner = self.clone();
+            
+            let task = tokio::spawn(async move {
+                scanner.probe_host(IpAddr::V4(ip)).await
+            });
+            scan_tasks.push(task);
+        }
+
+        // Wait for all scans to complete
+        for task in scan_tasks {
+            if let Ok(Some(miner)) = task.await? {
+                miners.push(miner);
+            }
+        }
+
+        debug!("Subnet {} scan complete: {} miners found", subnet, miners.len());
+        Ok(miners)
+    }
+
+    /// Probe a specific host for miner services
+    pub async fn probe_host(&self, ip: IpAddr) -> Result<Option<DetectedMiner>> {
+        for &port in &self.ports {
+            if let Ok(Some(miner)) = self.check_miner_api(ip, port).await {
+                return Ok(Some(miner));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Check if a specific IP:port responds to miner API calls
+    async fn check_miner_api(&self, ip: IpAddr, port: u16) -> Result<Option<DetectedMiner>> {
+        let start_time = Instant::now();
+
+        // First check if port is open
+        if !self.is_port_open(ip, port).await {
+            return Ok(None);
+        }
+
+        let response_time = start_time.elapsed().as_millis() as u64;
+        debug!("Port {} open on {}", port, ip);
+
+        // Try different miner API endpoints
+        let miner_type = if let Some(miner_type) = self.detect_bitaxe(ip, port).await? {
+            miner_type
+        } else if let Some(miner_type) = self.detect_apollo(ip, port).await? {
+            miner_type
+        } else if let Some(miner_type) = self.detect_antminer(ip, port).await? {
+            miner_type
+        } else if let Some(miner_type) = self.detect_whatsminer(ip, port).await? {
+            miner_type
+        } else {
+            return Ok(None);
+        };
+
+        // Get detailed information
+        let details = self.get_miner_details(ip, port, &miner_type).await?;
+
+        Ok(Some(DetectedMiner {
+            ip,
+            miner_type,
+            api_port: Some(port),
+            response_time_ms: response_time,
+            last_seen: Instant::now(),
+            details,
+        }))
+    }
+
+    /// Check if a TCP port is open
+    async fn is_port_open(&self, ip: IpAddr, port: u16) -> bool {
+        let started = Instant::now();
+        match timeout(self.probe_timeout.current(), TcpStream::connect((ip, port))).await {
+            // A refusal is an answer too, so it still measures the network
+            Ok(result) => {
+                self.probe_timeout.record(started.elapsed());
+                result.is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Detect Bitaxe miner
+    async fn detect_bitaxe(&self, ip: IpAddr, port: u16) -> Result<Option<MinerType>> {
+        let url = format!("http://{}:{}/api/system/info", ip, port);
+        
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                if text.contains("bitaxe") || text.contains("\"ASICModel\"") {
+                    debug!("Detected Bitaxe at {}:{}", ip, port);
+                    return Ok(Some(MinerType::Bitaxe));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Detect Apollo BTC miner
+    async fn detect_apollo(&self, ip: IpAddr, port: u16) -> Result<Option<MinerType>> {
+        // Apollo typically uses CGMiner API on port 4028
+        if port == 4028 {
+            // Try CGMiner version command
+            // Note: This would require implementing CGMiner JSON API over TCP
+            // For now, we'll check HTTP endpoints
+        }
+
+        let url = format!("http://{}:{}/", ip, port);
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                if text.contains("Apollo") || text.contains("FutureBit") {
+                    debug!("Detected Apollo at {}:{}", ip, port);
+                    return Ok(Some(MinerType::Apollo));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Detect Antminer
+    async fn detect_antminer(&self, ip: IpAddr, port: u16) -> Result<Option<MinerType>> {
+        let url = format!("http://{}:{}/", ip, port);
+        
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                if text.contains("Antminer") {
+                    if text.contains("S19") {
+                        debug!("Detected Antminer S19 at {}:{}", ip, port);
+                        return Ok(Some(MinerType::AntminerS19));
+                    } else if text.contains("S21") {
+                        debug!("Detected Antminer S21 at {}:{}", ip, port);
+                        return Ok(Some(MinerType::AntminerS21));
+                    } else {
+                        debug!("Detected unknown Antminer at {}:{}", ip, port);
+                        return Ok(Some(MinerType::Unknown));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Detect Whatsminer
+    async fn detect_whatsminer(&self, ip: IpAddr, port: u16) -> Result<Option<MinerType>> {
+        let url = format!("http://{}:{}/", ip, port);
+        
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                if text.contains("Whatsminer") || text.contains("MicroBT") {
+                    debug!("Detected Whatsminer at {}:{}", ip, port);
+                    return Ok(Some(MinerType::Whatsminer));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Query a miner found by an earlier probe for its current details,
+    /// skipping detection
+    pub async fn refresh_details(&self, miner: &DetectedMiner) -> Result<MinerDetails> {
+        let port = miner.api_port.ok_or_else(|| anyhow::anyhow!("{} has no API port", miner.ip))?;
+        self.get_miner_details(miner.ip, port, &miner.miner_type).await
+    }
+
+    /// Get detailed information about a detected miner
+    async fn get_miner_details(&self, ip: IpAddr, port: u16, miner_type: &MinerType) -> Result<MinerDetails> {
+        let mut details = MinerDetails::default();
+
+        match miner_type {
+            MinerType::Bitaxe => {
+                details = self.get_bitaxe_details(ip, port).await.unwrap_or_default();
+            }
+            MinerType::Apollo => {
+                details = self.get_apollo_details(ip, port).await.unwrap_or_default();
+            }
+            _ => {
+                // Try generic approaches for other miners
+                details = self.get_generic_details(ip, port).await.unwrap_or_default();
+            }
+        }
+
+        Ok(details)
+    }
+
+    /// Get Bitaxe-specific details
+    async fn get_bitaxe_details(&self, ip: IpAddr, port: u16) -> Result<MinerDetails> {
+        let mut details = MinerDetails::default();
+
+        // Depending on firmware version the stats are in the system info or
+        // a separate endpoint
+        for endpoint in ["/api/system/info", "/api/system/stats"] {
+            let Some(json) = self.fetch_json(&format!("http://{}:{}{}", ip, port, endpoint)).await else {
+                continue;
+            };
+            let report = miner_api::apply_response(&mut details, &json);
+            if report.unexpected() {
+                warn!(
+                    "Bitaxe at {}:{} answered {} with an unexpected layout (fallbacks: {}); check its details",
+                    ip,
+                    port,
+                    endpoint,
+                    if report.fallbacks.is_empty() { "none matched".to_string() } else { report.fallbacks.join(", ") }
+                );
+            }
+        }
+
+        Ok(details)
+    }
+
+    /// GET `url` as JSON, retrying once on failure
+    async fn fetch_json(&self, url: &str) -> Option<serde_json::Value> {
+        for attempt in 1..=API_ATTEMPTS {
+            match self.client.get(url).send().await {
+                Ok(response) => match response.json::<serde_json::Value>().await {
+                    Ok(json) => return Some(json),
+                    // A body that isn't JSON won't become JSON on retry
+                    Err(e) => {
+                        debug!("{} did not return JSON: {}", url, e);
+                        return None;
+                    }
+                },
+                Err(e) if attempt < API_ATTEMPTS => {
+                    debug!("{} failed ({}), retrying", url, e);
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                }
+                Err(e) => debug!("{} failed: {}", url, e),
+            }
+        }
+        None
+    }
+
+    /// Get Apollo-specific details  
+    async fn get_apollo_details(&self, ip: IpAddr, port: u16) -> Result<MinerDetails> {
+        let mut details = MinerDetails::default();
+
+        // Apollo uses CGMiner API - would need TCP JSON-RPC implementation
+        // For now, try HTTP endpoints
+        let url = format!("http://{}:{}/", ip, port);
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                // Parse HTML or JSON for Apollo details
+                // This is a simplified implementation
+                if text.contains("Apollo") {
+                    details.hostname = Some("Apollo BTC".to_string());
+                }
+            }
+        }
+
+        Ok(details)
+    }
+
+    /// Get generic miner details
+    async fn get_generic_details(&self, ip: IpAddr, port: u16) -> Result<MinerDetails> {
+        let mut details = MinerDetails::default();
+
+        let url = format!("http://{}:{}/", ip, port);
+        if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(text) = response.text().await {
+                // Some firmware serves JSON stats at the root
+                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
+                    let report = miner_api::apply_response(&mut details, &json);
+                    if !report.recognized {
+                        debug!("No known fields in the API response from {}:{}", ip, port);
+                    }
+                }
+                if details.hostname.is_none() {
+                    details.hostname = Some(format!("Miner-{}", ip));
+                }
+            }
+        }
+
+        Ok(details)
+    }
+}
+
+impl Clone for NetworkScanner {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            probe_timeout: Arc::clone(&self.probe_timeout),
+            ports: self.ports.clone(),
+        }
+    }
+}
+
+/// An IPv4 address assigned to a network interface
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceAddr {
+    pub name: String,
+    pub addr: Ipv4Addr,
+    pub prefix_len: u8,
+}
+
+/// Subnets of the host's own network interfaces, for scanning when no
+/// `--subnet` is given. Empty if the interfaces can't be listed.
+pub fn detect_local_subnets() -> Vec<String> {
+    subnets_for(&list_interfaces())
+}
+
+/// One subnet per distinct network: the interface's own prefix, narrowed to
+/// a /24 for larger networks so a scan stays at 254 hosts
+pub fn subnets_for(interfaces: &[InterfaceAddr]) -> Vec<String> {
+    let mut subnets = Vec::new();
+    for interface in interfaces {
+        if interface.addr.is_loopback() || interface.addr.is_link_local() || interface.addr.is_unspecified() {
+            continue;
+        }
+        let prefix_len = interface.prefix_len.clamp(24, 32);
+        let mask = u32::MAX << (32 - prefix_len as u32);
+        let network = Ipv4Addr::from(u32::from(interface.addr) & mask);
+        let subnet = format!("{}/{}", network, prefix_len);
+        if !subnets.contains(&subnet) {
+            debug!("Interface {} ({}/{}) -> {}", interface.name, interface.addr, interface.prefix_len, subnet);
+            subnets.push(subnet);
+        }
+    }
+    subnets
+}
+
+/// IPv4 interface addresses from `ip` on Linux, or `ifconfig` elsewhere
+fn list_interfaces() -> Vec<InterfaceAddr> {
+    let run = |program: &str, args: &[&str]| {
+        std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    if let Some(output) = run("ip", &["-o", "-4", "addr", "show"]) {
+        return parse_ip_addr(&output);
+    }
+    if let Some(output) = run("ifconfig", &[]) {
+        return parse_ifconfig(&output);
+    }
+    warn!("Could not list network interfaces with 'ip' or 'ifconfig'");
+    Vec::new()
+}
+
+/// Parse `ip -o -4 addr show`: `2: eth0    inet 192.168.1.23/24 brd ...`
+fn parse_ip_addr(output: &str) -> Vec<InterfaceAddr> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let name = fields.next()?.to_string();
+            let (addr, prefix_len) = fields.skip_while(|f| *f != "inet").nth(1)?.split_once('/')?;
+            Some(InterfaceAddr {
+                name,
+                addr: addr.parse().ok()?,
+                prefix_len: prefix_len.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Parse `ifconfig`, whose netmask is dotted on Linux and hex on macOS/BSD
+fn parse_ifconfig(output: &str) -> Vec<InterfaceAddr> {
+    let mut interfaces = Vec::new();
+    let mut name = String::new();
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            name = line.split(':').next().unwrap_or_default().trim().to_string();
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let value_after = |key: &str| fields.iter().position(|f| *f == key).and_then(|i| fields.get(i + 1)).copied();
+        let (Some(addr), Some(netmask)) = (value_after("inet"), value_after("netmask")) else {
+            continue;
+        };
+        let mask = match netmask.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => netmask.parse::<Ipv4Addr>().ok().map(u32::from),
+        };
+        if let (Ok(addr), Some(mask)) = (addr.trim_start_matches("addr:").parse(), mask) {
+            interfaces.push(InterfaceAddr { name: name.clone(), addr, prefix_len: mask.count_ones() as u8 });
+        }
+    }
+    interfaces
+}
+
+/// Drop duplicates and ports of well-known non-miner services, keeping order
+pub fn validate_ports(ports: Vec<u16>) -> Result<Vec<u16>> {
+    let mut validated = Vec::with_capacity(ports.len());
+    for port in ports {
+        if port == 0 {
+            return Err(anyhow::anyhow!("Port 0 cannot be scanned"));
+        }
+        if let Some((_, service)) = NON_MINER_PORTS.iter().find(|(p, _)| *p == port) {
+            warn!("Skipping port {}: it is used by {}, not a miner API", port, service);
+            continue;
+        }
+        if !validated.contains(&port) {
+            validated.push(port);
+        }
+    }
+
+    if validated.is_empty() {
+        return Err(anyhow::anyhow!("No miner API ports left to scan"));
+    }
+    Ok(validated)
+}
+
+/// Generate configuration recommendations based on detected miners
+pub fn generate_config_recommendations(miners: &[DetectedMiner]) -> HashMap<String, serde_json::Value> {
+    let mut recommendations = HashMap::new();
+
+    if miners.is_empty() {
+        recommendations.insert(
+            "message".to_string(), 
+            serde_json::json!("No miners detected. Manual configuration required.")
+        );
+        return recommendations;
+    }
+
+    // Count miner types
+    let mut miner_counts = HashMap::new();
+    for miner in miners {
+        *miner_counts.entry(miner.miner_type.to_string()).or_insert(0) += 1;
+    }
+
+    recommendations.insert("detected_miners".to_string(), serde_json::json!(miner_counts));
+
+    // Determine optimal extranonce2_size
+    let has_bitaxe = miners.iter().any(|m| matches!(m.miner_type, MinerType::Bitaxe));
+    let has_apollo = miners.iter().any(|m| matches!(m.miner_type, MinerType::Apollo));
+
+    let extranonce2_size = if has_bitaxe && !has_apollo {
+        4 // Bitaxe optimal
+    } else if has_apollo && !has_bitaxe {
+        6 // Apollo optimal  
+    } else if has_bitaxe && has_apollo {
+        6 // Compatible with both
+    } else {
+        8 // Conservative default
+    };
+
+    recommendations.insert("extranonce2_size".to_string(), serde_json::json!(extranonce2_size));
+
+    // Network-specific recommendations
+    let total_estimated_hashrate: f64 = miners.iter()
+        .filter_map(|m| m.details.hashrate)
+        .sum();
+
+    if total_estimated_hashrate > 0.0 {
+        recommendations.insert("total_hashrate".to_string(), serde_json::json!(total_estimated_hashrate));
+        recommendations.insert(
+            "shares_per_minute".to_string(),
+            serde_json::json!(recommended_shares_per_minute(total_estimated_hashrate)),
+        );
+    }
+
+    recommendations
+}
+
+/// Vardiff target share rate for a fleet with `total_hashrate` H/s
+pub fn recommended_shares_per_minute(total_hashrate: f64) -> f64 {
+    if total_hashrate < 1e12 { // < 1 TH/s
+        3.0
+    } else if total_hashrate < 10e12 { // < 10 TH/s
+        5.0
+    } else {
+        10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miner_type_display() {
+        assert_eq!(MinerType::Bitaxe.to_string(), "Bitaxe");
+        assert_eq!(MinerType::Apollo.to_string(), "Apollo BTC");
+    }
+
+    #[test]
+    fn test_subnets_from_interfaces() {
+        let interface = |name: &str, addr: &str, prefix_len| InterfaceAddr {
+            name: name.to_string(),
+            addr: addr.parse().unwrap(),
+            prefix_len,
+        };
+        let interfaces = [
+            interface("lo", "127.0.0.1", 8),
+            interface("eth0", "192.168.1.23", 24),
+            interface("wlan0", "192.168.1.40", 24),
+            interface("tun0", "10.8.0.6", 16),
+            interface("eth1", "172.16.5.130", 26),
+            interface("eth2", "169.254.10.1", 16),
+        ];
+        assert_eq!(
+            subnets_for(&interfaces),
+            ["192.168.1.0/24", "10.8.0.0/24", "172.16.5.128/26"]
+        );
+        assert!(subnets_for(&[interface("lo", "127.0.0.1", 8)]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_interface_listings() {
+        let ip = "1: lo    inet 127.0.0.1/8 scope host lo\\       valid_lft forever preferred_lft forever\n\
+                  2: eth0    inet 192.168.1.23/24 brd 192.168.1.255 scope global dynamic eth0\\       valid_lft 86000sec\n";
+        let parsed = parse_ip_addr(ip);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].name, "eth0");
+        assert_eq!(parsed[1].addr, Ipv4Addr::new(192, 168, 1, 23));
+        assert_eq!(parsed[1].prefix_len, 24);
+
+        let macos = "en0: flags=8863<UP,BROADCAST,RUNNING> mtu 1500\n\
+                     \tinet6 fe80::1%en0 prefixlen 64 scopeid 0x4\n\
+                     \tinet 10.0.0.12 netmask 0xffffff00 broadcast 10.0.0.255\n";
+        assert_eq!(
+            parse_ifconfig(macos),
+            [InterfaceAddr { name: "en0".to_string(), addr: Ipv4Addr::new(10, 0, 0, 12), prefix_len: 24 }]
+        );
+
+        let linux = "eth0: flags=4163<UP,BROADCAST,RUNNING,MULTICAST>  mtu 1500\n        inet 192.168.0.7  netmask 255.255.255.0  broadcast 192.168.0.255\n";
+        assert_eq!(parse_ifconfig(linux)[0].prefix_len, 24);
+    }
+
+    #[test]
+    fn test_adaptive_timeout_widens_for_slow_networks() {
+        let timeout = ProbeTimeout::adaptive(Duration::from_millis(200));
+
+        // Too few samples to judge the network yet
+        timeout.record(Duration::from_millis(150));
+        assert_eq!(timeout.current(), Duration::from_millis(200));
+
+        timeout.record(Duration::from_millis(180));
+        timeout.record(Duration::from_millis(160));
+        assert_eq!(timeout.current(), Duration::from_millis(640));
+
+        // Never past the cap, however slow
+        timeout.record(Duration::from_secs(5));
+        timeout.record(Duration::from_secs(5));
+        timeout.record(Duration::from_secs(5));
+        assert_eq!(timeout.current(), MAX_ADAPTIVE_TIMEOUT);
+
+        // Fast networks keep the initial timeout
+        let fast = ProbeTimeout::adaptive(Duration::from_millis(200));
+        for _ in 0..5 {
+            fast.record(Duration::from_millis(2));
+        }
+        assert_eq!(fast.current(), Duration::from_millis(200));
+
+        let fixed = ProbeTimeout::fixed(Duration::from_millis(200));
+        fixed.record(Duration::from_secs(1));
+        fixed.record(Duration::from_secs(1));
+        fixed.record(Duration::from_secs(1));
+        assert_eq!(fixed.current(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_validate_ports() {
+        assert_eq!(validate_ports(vec![8081, 4028, 8081, 22]).unwrap(), vec![8081, 4028]);
+        assert!(validate_ports(vec![0]).is_err());
+        assert!(validate_ports(vec![22, 445]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_port_is_probed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Stand-in Bitaxe answering every request with its system info
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    if matches!(stream.read(&mut request).await, Ok(n) if n > 0) {
+                        let body = r#"{"ASICModel":"BM1366","hostname":"bitaxe","hashRate":700.0}"#;
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                });
+            }
+        });
+
+        let localhost: IpAddr = "127.0.0.1".parse().unwrap();
+        let scanner = NetworkScanner::new().with_ports(vec![port]).unwrap();
+        let miner = scanner.probe_host(localhost).await.unwrap().expect("miner on custom port");
+        assert!(matches!(miner.miner_type, MinerType::Bitaxe));
+        assert_eq!(miner.api_port, Some(port));
+        assert_eq!(miner.details.hashrate, Some(700e9));
+    }
+
+    #[test]
+    fn test_generate_config_recommendations() {
+        let miners = vec![
+            DetectedMiner {
+                ip: "192.168.1.100".parse().unwrap(),
+                miner_type: MinerType::Bitaxe,
+                api_port: Some(80),
+                response_time_ms: 50,
+                last_seen: Instant::now(),
+                details: MinerDetails {
+                    hashrate: Some(700e9), // 700 GH/s
+                    ..Default::default()
+                },
+            }
+        ];
+
+        let recommendations = generate_config_recommendations(&miners);
+        assert_eq!(recommendations.get("extranonce2_size").unwrap(), &serde_json::json!(4));
+    }
+}
\ No newline at end of file
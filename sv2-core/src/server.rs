@@ -1,8 +1,15 @@
 // TCP server implementation for Stratum connections
 use crate::{
+    capture::{CaptureController, CaptureDirection, ConnectionCapture},
+    config::NetworkConfig,
+    connection_rate::{ConnectionRateLimitConfig, ConnectionRateLimiter},
     error::{Error, Result},
+    events::{Event, EventBus},
+    handshake_failures::{check_opening_bytes, check_opening_frame, HandshakeFailureReason, SetupConnectionError},
     protocol::{NetworkProtocolMessage, StratumMessage},
-    types::{Connection, ConnectionId, Protocol},
+    types::{Connection, ConnectionId, ConnectionState, Protocol},
+    welcome::WelcomeConfig,
+    worker_bans::WorkerBans,
 };
 use std::{
     collections::HashMap,
@@ -10,53 +17,107 @@ use std::{
     sync::{Arc, atomic::{AtomicU64, Ordering}},
 };
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::{TcpListener, TcpStream},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpSocket},
     sync::{mpsc, RwLock},
-    time::{timeout, Duration},
+    task::JoinSet,
+    time::{timeout, Duration, Instant},
 };
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
+/// Byte stream a miner is connected over: a TCP socket, or an in-memory
+/// pipe in tests (see `session_harness`)
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Transport for T {}
+
 /// Connection handler for individual client connections
 pub struct ConnectionHandler {
     connection_id: ConnectionId,
-    stream: TcpStream,
+    stream: Box<dyn Transport>,
     peer_addr: SocketAddr,
-    protocol: Protocol,
+    connection: Connection,
     message_tx: mpsc::UnboundedSender<NetworkProtocolMessage>,
     shutdown_rx: mpsc::Receiver<()>,
+    capture_controller: Option<Arc<CaptureController>>,
+    welcome: Arc<WelcomeConfig>,
+    events: Option<EventBus>,
+    worker_bans: Arc<WorkerBans>,
+}
+
+/// What the server remembers about an SV1 miner for its own replies
+#[derive(Debug, Default)]
+struct Sv1Session {
+    user_agent: Option<String>,
+    welcomed: bool,
 }
 
 impl ConnectionHandler {
     pub fn new(
         connection_id: ConnectionId,
-        stream: TcpStream,
+        stream: impl Transport + 'static,
         peer_addr: SocketAddr,
         message_tx: mpsc::UnboundedSender<NetworkProtocolMessage>,
         shutdown_rx: mpsc::Receiver<()>,
     ) -> Self {
         Self {
             connection_id,
-            stream,
+            stream: Box::new(stream),
             peer_addr,
-            protocol: Protocol::StratumV1, // Default to V1, detect later
+            // Pinned to the protocol of the first message
+            connection: Connection { id: connection_id, ..Connection::new(peer_addr, Protocol::StratumV1) },
             message_tx,
             shutdown_rx,
+            capture_controller: None,
+            welcome: Arc::new(WelcomeConfig::default()),
+            events: None,
+            worker_bans: Arc::new(WorkerBans::default()),
         }
     }
 
+    /// Record this connection's traffic when the controller enables it
+    pub fn with_capture(mut self, controller: Arc<CaptureController>) -> Self {
+        self.capture_controller = Some(controller);
+        self
+    }
+
+    /// Greet the miner with `welcome` after it first authorizes
+    pub fn with_welcome(mut self, welcome: Arc<WelcomeConfig>) -> Self {
+        self.welcome = welcome;
+        self
+    }
+
+    /// Publish failed handshakes on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Reject shares from the workers banned in `worker_bans`
+    pub fn with_worker_bans(mut self, worker_bans: Arc<WorkerBans>) -> Self {
+        self.worker_bans = worker_bans;
+        self
+    }
+
     /// Handle the connection lifecycle
     pub async fn handle(self) -> Result<()> {
         info!("Handling connection from {}: {}", self.peer_addr, self.connection_id);
         
-        let (mut reader, mut writer) = self.stream.into_split();
+        let (mut reader, mut writer) = tokio::io::split(self.stream);
         let mut buffer = vec![0u8; 4096];
         let mut message_buffer = String::new();
         let mut shutdown_rx = self.shutdown_rx;
         let connection_id = self.connection_id;
         let message_tx = self.message_tx;
-        let mut protocol = self.protocol;
+        let mut connection = self.connection;
+        let capture_controller = self.capture_controller;
+        let mut capture: Option<ConnectionCapture> = None;
+        let welcome = self.welcome;
+        let events = self.events;
+        let peer_addr = self.peer_addr;
+        let worker_bans = self.worker_bans;
+        let mut session = Sv1Session::default();
 
         loop {
             tokio::select! {
@@ -68,8 +129,31 @@ impl ConnectionHandler {
                             break;
                         }
                         Ok(n) => {
+                            if let Some(controller) = &capture_controller {
+                                Self::update_capture(controller, connection_id, &mut capture);
+                            }
+                            if let Some(capture) = capture.as_mut() {
+                                if let Err(e) = capture.record(CaptureDirection::Inbound, &buffer[..n]) {
+                                    warn!("Failed to capture traffic for {}: {}", connection_id, e);
+                                }
+                            }
+
                             let data = String::from_utf8_lossy(&buffer[..n]);
                             message_buffer.push_str(&data);
+
+                            // A browser or TLS client is turned away on its first bytes
+                            if !connection.protocol_pinned {
+                                if let Err(e) = check_opening_bytes(message_buffer.as_bytes()) {
+                                    if let Some(events) = &events {
+                                        events.publish(Event::HandshakeFailed {
+                                            address: peer_addr,
+                                            reason: HandshakeFailureReason::NotStratum,
+                                        });
+                                    }
+                                    warn!("Disconnecting {} from {}: {}", connection_id, peer_addr, e);
+                                    return Err(e);
+                                }
+                            }
                             
                             // Process complete messages (newline-delimited JSON)
                             while let Some(newline_pos) = message_buffer.find('\n') {
@@ -77,16 +161,43 @@ impl ConnectionHandler {
                                 message_buffer.drain(..=newline_pos);
                                 
                                 if !message_str.is_empty() {
+                                    // Until a frame pins the protocol the handshake is still going
+                                    if !connection.protocol_pinned {
+                                        if let Err(reason) = check_opening_frame(&message_str) {
+                                            if let Some(events) = &events {
+                                                events.publish(Event::HandshakeFailed { address: peer_addr, reason });
+                                            }
+                                            // Tell a miner on another protocol version what
+                                            // this server speaks, so it can retry with that
+                                            if reason == HandshakeFailureReason::UnsupportedVersion {
+                                                let reply = SetupConnectionError::version_mismatch().to_frame();
+                                                if let Err(e) = Self::send_response(&mut writer, &reply.to_string(), &mut capture).await {
+                                                    debug!("Failed to send SetupConnectionError to {}: {}", connection_id, e);
+                                                }
+                                            }
+                                            warn!("Disconnecting {} from {}: handshake failed ({})", connection_id, peer_addr, reason);
+                                            return Err(Error::Protocol(format!("handshake failed: {}", reason)));
+                                        }
+                                    }
+
                                     match Self::process_message(
                                         &message_str, 
                                         &mut writer, 
                                         connection_id, 
                                         &message_tx, 
-                                        &mut protocol
+                                        &mut connection,
+                                        &mut capture,
+                                        &welcome,
+                                        &mut session,
+                                        &worker_bans,
                                     ).await {
                                         Ok(()) => {
                                             debug!("Successfully processed message from {}", connection_id);
                                         }
+                                        Err(e) if connection.state == ConnectionState::Error => {
+                                            warn!("Disconnecting {}: {}", connection_id, e);
+                                            return Err(e);
+                                        }
                                         Err(e) => {
                                             error!("Error processing message from {}: {}", connection_id, e);
                                             // Send error response but continue handling connection
@@ -95,7 +206,7 @@ impl ConnectionHandler {
                                                 "result": null,
                                                 "error": {"code": -32700, "message": "Parse error"}
                                             });
-                                            if let Err(send_err) = Self::send_response(&mut writer, &error_response.to_string()).await {
+                                            if let Err(send_err) = Self::send_response(&mut writer, &error_response.to_string(), &mut capture).await {
                                                 error!("Failed to send error response: {}", send_err);
                                                 break; // Break if we can't send responses
                                             }
@@ -121,13 +232,33 @@ impl ConnectionHandler {
         Ok(())
     }
 
+    /// Open or close the capture file to match the controller's current state
+    fn update_capture(
+        controller: &CaptureController,
+        connection_id: ConnectionId,
+        capture: &mut Option<ConnectionCapture>,
+    ) {
+        match (controller.is_enabled(connection_id), capture.is_some()) {
+            (true, false) => match controller.open(connection_id) {
+                Ok(opened) => *capture = Some(opened),
+                Err(e) => warn!("Failed to open capture for {}: {}", connection_id, e),
+            },
+            (false, true) => *capture = None,
+            _ => {}
+        }
+    }
+
     /// Process a single message from the client
     async fn process_message(
         message_str: &str, 
-        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        writer: &mut (impl AsyncWrite + Unpin),
         connection_id: ConnectionId,
         message_tx: &mpsc::UnboundedSender<NetworkProtocolMessage>,
-        protocol: &mut Protocol,
+        connection: &mut Connection,
+        capture: &mut Option<ConnectionCapture>,
+        welcome: &WelcomeConfig,
+        session: &mut Sv1Session,
+        worker_bans: &WorkerBans,
     ) -> Result<()> {
         debug!("Received message from {}: {}", connection_id, message_str);
         
@@ -136,10 +267,15 @@ impl ConnectionHandler {
             .map_err(|e| Error::Protocol(format!("Invalid JSON: {}", e)))?;
 
         // Detect protocol version based on message structure
-        if json_value.get("method").is_some() {
-            *protocol = Protocol::StratumV1;
+        let detected = if json_value.get("method").is_some() {
+            Some(Protocol::StratumV1)
         } else if json_value.get("msg_type").is_some() {
-            *protocol = Protocol::StratumV2;
+            Some(Protocol::StratumV2)
+        } else {
+            None
+        };
+        if let Some(detected) = detected {
+            connection.pin_protocol(detected)?;
         }
 
         // Handle immediate responses for some messages
@@ -148,6 +284,10 @@ impl ConnectionHandler {
             
             match method {
                 "mining.subscribe" => {
+                    session.user_agent = json_value.get("params")
+                        .and_then(|params| params.get(0))
+                        .and_then(|agent| agent.as_str())
+                        .map(String::from);
                     let response = serde_json::json!({
                         "id": id,
                         "result": [
@@ -157,7 +297,7 @@ impl ConnectionHandler {
                         ],
                         "error": null
                     });
-                    Self::send_response(writer, &response.to_string()).await?;
+                    Self::send_response(writer, &response.to_string(), capture).await?;
                 }
                 "mining.authorize" => {
                     let response = serde_json::json!({
@@ -165,16 +305,39 @@ impl ConnectionHandler {
                         "result": true,
                         "error": null
                     });
-                    Self::send_response(writer, &response.to_string()).await?;
+                    Self::send_response(writer, &response.to_string(), capture).await?;
+
+                    // Once per connection, even when several workers authorize
+                    if !session.welcomed {
+                        session.welcomed = true;
+                        if let Some(notification) = welcome.notification(session.user_agent.as_deref()) {
+                            Self::send_response(writer, &notification.to_string(), capture).await?;
+                        }
+                    }
                 }
                 "mining.submit" => {
+                    let worker = json_value.get("params")
+                        .and_then(|params| params.get(0))
+                        .and_then(|worker| worker.as_str());
+                    if let Some(worker) = worker.filter(|worker| worker_bans.is_banned(worker)) {
+                        // Other workers on this connection keep submitting
+                        debug!("Rejecting share from banned worker {} on {}", worker, connection_id);
+                        let response = serde_json::json!({
+                            "id": id,
+                            "result": null,
+                            "error": {"code": 24, "message": "Worker is banned"}
+                        });
+                        Self::send_response(writer, &response.to_string(), capture).await?;
+                        return Ok(());
+                    }
+
                     // For now, accept all shares - the mode handler will do proper validation
                     let response = serde_json::json!({
                         "id": id,
                         "result": true,
                         "error": null
                     });
-                    Self::send_response(writer, &response.to_string()).await?;
+                    Self::send_response(writer, &response.to_string(), capture).await?;
                 }
                 _ => {
                     // Unknown method
@@ -183,13 +346,13 @@ impl ConnectionHandler {
                         "result": null,
                         "error": {"code": -1, "message": "Unknown method"}
                     });
-                    Self::send_response(writer, &response.to_string()).await?;
+                    Self::send_response(writer, &response.to_string(), capture).await?;
                 }
             }
         }
 
         // Create protocol message for forwarding to mode handler
-        let protocol_msg = match *protocol {
+        let protocol_msg = match connection.protocol {
             Protocol::StratumV1 | Protocol::Sv1 => {
                 let stratum_msg = StratumMessage::from_json(&json_value)?;
                 NetworkProtocolMessage::StratumV1 {
@@ -214,8 +377,17 @@ impl ConnectionHandler {
     }
 
     /// Send a response back to the client
-    async fn send_response(writer: &mut tokio::net::tcp::OwnedWriteHalf, response: &str) -> Result<()> {
+    async fn send_response(
+        writer: &mut (impl AsyncWrite + Unpin),
+        response: &str,
+        capture: &mut Option<ConnectionCapture>,
+    ) -> Result<()> {
         let response_with_newline = format!("{}\n", response);
+        if let Some(capture) = capture.as_mut() {
+            if let Err(e) = capture.record(CaptureDirection::Outbound, response_with_newline.as_bytes()) {
+                warn!("Failed to capture outbound traffic: {}", e);
+            }
+        }
         writer.write_all(response_with_newline.as_bytes()).await
             .map_err(|e| Error::Network(format!("Failed to send response: {}", e)))?;
         writer.flush().await
@@ -232,8 +404,21 @@ pub struct StratumServer {
     message_tx: mpsc::UnboundedSender<NetworkProtocolMessage>,
     shutdown_tx: mpsc::Sender<()>,
     shutdown_rx: Option<mpsc::Receiver<()>>,
+    capture_controller: Arc<CaptureController>,
+    welcome: Arc<WelcomeConfig>,
+    events: Option<EventBus>,
+    worker_bans: Arc<WorkerBans>,
+    listen_backlog: u32,
+    rate_limiter: ConnectionRateLimiter,
+    drain_timeout: Duration,
 }
 
+/// Connections the kernel queues while the accept loop catches up
+pub const DEFAULT_LISTEN_BACKLOG: u32 = 1024;
+
+/// How long open connections get to finish on shutdown before they're closed
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl StratumServer {
     pub fn new(
         bind_address: SocketAddr,
@@ -248,47 +433,158 @@ impl StratumServer {
             message_tx,
             shutdown_tx,
             shutdown_rx: Some(shutdown_rx),
+            capture_controller: Arc::new(CaptureController::default()),
+            welcome: Arc::new(WelcomeConfig::default()),
+            events: None,
+            worker_bans: Arc::new(WorkerBans::default()),
+            listen_backlog: DEFAULT_LISTEN_BACKLOG,
+            rate_limiter: ConnectionRateLimiter::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
         }
     }
 
+    /// Server for the `[network]` section of the daemon config
+    pub fn from_config(
+        config: &NetworkConfig,
+        message_tx: mpsc::UnboundedSender<NetworkProtocolMessage>,
+    ) -> Self {
+        Self::new(config.bind_address, message_tx)
+            .with_listen_backlog(config.listen_backlog)
+            .with_connection_rate_limit(config.connection_rate_limit.clone())
+            .with_drain_timeout(config.drain_timeout())
+            .with_capture(Arc::new(CaptureController::new(config.capture.clone())))
+            .with_welcome(config.welcome.clone())
+    }
+
+    /// On shutdown, give open connections up to `drain_timeout` to finish
+    /// the message they're handling before they're closed
+    pub fn with_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// Queue up to `backlog` connections not yet accepted, so a burst of
+    /// miners reconnecting at once isn't refused. The kernel may cap it
+    /// (`net.core.somaxconn` on Linux).
+    pub fn with_listen_backlog(mut self, backlog: u32) -> Self {
+        self.listen_backlog = backlog;
+        self
+    }
+
+    /// Close new connections from an IP past its per-minute allowance
+    pub fn with_connection_rate_limit(mut self, config: ConnectionRateLimitConfig) -> Self {
+        self.rate_limiter = ConnectionRateLimiter::new(config);
+        self
+    }
+
+    /// Use the given capture controller for new connections
+    pub fn with_capture(mut self, controller: Arc<CaptureController>) -> Self {
+        self.capture_controller = controller;
+        self
+    }
+
+    /// Message shown to SV1 miners after they authorize
+    pub fn with_welcome(mut self, welcome: WelcomeConfig) -> Self {
+        self.welcome = Arc::new(welcome);
+        self
+    }
+
+    /// Publish connections' failed handshakes on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Reject shares from the workers banned in `worker_bans`
+    pub fn with_worker_bans(mut self, worker_bans: Arc<WorkerBans>) -> Self {
+        self.worker_bans = worker_bans;
+        self
+    }
+
+    /// Start capturing raw traffic for a specific connection
+    pub fn enable_capture(&self, connection_id: ConnectionId) {
+        self.capture_controller.enable(connection_id);
+    }
+
+    /// Stop capturing raw traffic for a specific connection
+    pub fn disable_capture(&self, connection_id: ConnectionId) {
+        self.capture_controller.disable(connection_id);
+    }
+
+    /// Bind the listening socket with the configured backlog
+    fn bind(&self) -> std::io::Result<TcpListener> {
+        let socket = if self.bind_address.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        #[cfg(unix)]
+        socket.set_reuseaddr(true)?;
+        socket.bind(self.bind_address)?;
+        socket.listen(self.listen_backlog)
+    }
+
     /// Start the server
     pub async fn start(&mut self) -> Result<()> {
-        let listener = TcpListener::bind(self.bind_address).await
+        let listener = self.bind()
             .map_err(|e| Error::Network(format!("Failed to bind to {}: {}", self.bind_address, e)))?;
         
-        info!("Stratum server listening on {}", self.bind_address);
+        info!("Stratum server listening on {} (backlog {})", self.bind_address, self.listen_backlog);
 
         let mut shutdown_rx = self.shutdown_rx.take()
             .ok_or_else(|| Error::Internal("Server already started".to_string()))?;
 
+        // Each connection's task, and the sender that asks it to finish up
+        let mut tasks = JoinSet::new();
+        let mut draining: HashMap<ConnectionId, mpsc::Sender<()>> = HashMap::new();
+
         loop {
             tokio::select! {
+                // Forget connections that have closed
+                Some(finished) = tasks.join_next(), if !tasks.is_empty() => {
+                    if let Ok(connection_id) = finished {
+                        draining.remove(&connection_id);
+                    }
+                }
                 // Accept new connections
                 result = listener.accept() => {
                     match result {
                         Ok((stream, peer_addr)) => {
+                            if !self.rate_limiter.allow(peer_addr.ip()) {
+                                warn!(
+                                    "Refusing connection from {}: more than {} connections per minute",
+                                    peer_addr, self.rate_limiter.config().connections_per_minute
+                                );
+                                drop(stream);
+                                continue;
+                            }
                             let connection_id = Uuid::new_v4();
                             
                             info!("Accepted connection from {}: {}", peer_addr, connection_id);
 
                             // Create connection handler
-                            let (_conn_shutdown_tx, conn_shutdown_rx) = mpsc::channel(1);
-                            let handler = ConnectionHandler::new(
+                            let (conn_shutdown_tx, conn_shutdown_rx) = mpsc::channel(1);
+                            draining.insert(connection_id, conn_shutdown_tx);
+                            let mut handler = ConnectionHandler::new(
                                 connection_id,
                                 stream,
                                 peer_addr,
                                 self.message_tx.clone(),
                                 conn_shutdown_rx,
-                            );
-
-                            // Store connection for later communication
-                            let (response_tx, _response_rx) = mpsc::unbounded_channel();
-                            self.connections.write().await.insert(connection_id, response_tx);
+                            )
+                            .with_capture(Arc::clone(&self.capture_controller))
+                            .with_welcome(Arc::clone(&self.welcome))
+                            .with_worker_bans(Arc::clone(&self.worker_bans));
+                            if let Some(events) = &self.events {
+                                handler = handler.with_events(events.clone());
+                            }
 
-                            // Spawn connection handler
+                            // Everything past accept runs on the connection's
+                            // own task, so neither a slow handshake nor the
+                            // connections lock holds up the next accept
                             let connections = Arc::clone(&self.connections);
                             let message_tx = self.message_tx.clone();
-                            tokio::spawn(async move {
+                            tasks.spawn(async move {
+                                // Store connection for later communication
+                                let (response_tx, _response_rx) = mpsc::unbounded_channel();
+                                connections.write().await.insert(connection_id, response_tx);
+
                                 // Send connection established message
                                 let connect_msg = NetworkProtocolMessage::Connect {
                                     connection_id,
@@ -314,6 +610,7 @@ impl StratumServer {
                                 // Clean up connection
                                 connections.write().await.remove(&connection_id);
                                 info!("Connection {} cleaned up", connection_id);
+                                connection_id
                             });
                         }
                         Err(e) => {
@@ -329,13 +626,50 @@ impl StratumServer {
             }
         }
 
-        // Close all connections
-        let connections = self.connections.read().await;
-        for (connection_id, _) in connections.iter() {
-            info!("Closing connection: {}", connection_id);
+        // No new connections while the open ones drain
+        drop(listener);
+        self.drain(tasks, draining).await;
+        Ok(())
+    }
+
+    /// Ask every open connection to close once it has finished the message
+    /// it's handling, so a share being submitted still gets its answer, and
+    /// close the ones still open after the drain timeout
+    async fn drain(
+        &self,
+        mut tasks: JoinSet<ConnectionId>,
+        mut draining: HashMap<ConnectionId, mpsc::Sender<()>>,
+    ) {
+        if draining.is_empty() {
+            return;
+        }
+        info!("Draining {} connections for up to {}s", draining.len(), self.drain_timeout.as_secs());
+        for shutdown_tx in draining.values() {
+            let _ = shutdown_tx.try_send(());
         }
 
-        Ok(())
+        let deadline = Instant::now() + self.drain_timeout;
+        let mut drained = 0;
+        while let Ok(Some(finished)) = tokio::time::timeout_at(deadline, tasks.join_next()).await {
+            // A connection whose task panicked is counted as force-closed
+            if let Ok(connection_id) = finished {
+                draining.remove(&connection_id);
+                drained += 1;
+            }
+        }
+
+        // Aborted tasks never get to announce their disconnect themselves
+        tasks.shutdown().await;
+        let mut connections = self.connections.write().await;
+        for connection_id in draining.keys() {
+            warn!("Force-closing connection {}: still busy after the drain timeout", connection_id);
+            connections.remove(connection_id);
+            let _ = self.message_tx.send(NetworkProtocolMessage::Disconnect {
+                connection_id: *connection_id,
+                reason: "Server shutting down".to_string(),
+            });
+        }
+        info!("Drained {} connections, force-closed {}", drained, draining.len());
     }
 
     /// Send a message to a specific connection
@@ -372,6 +706,12 @@ impl StratumServer {
         self.connections.read().await.len()
     }
 
+    /// Sender that shuts the server down, for stopping it once `start` is
+    /// running on another task
+    pub fn shutdown_handle(&self) -> mpsc::Sender<()> {
+        self.shutdown_tx.clone()
+    }
+
     /// Shutdown the server
     pub async fn shutdown(&self) -> Result<()> {
         self.shutdown_tx.send(()).await
@@ -398,4 +738,357 @@ mod tests {
         let server = StratumServer::new("127.0.0.1:0".parse().unwrap(), tx);
         assert_eq!(server.connection_count().await, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_burst_of_connects_all_accepted() {
+        // A free port to start the server on
+        let bind_address = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut server = StratumServer::new(bind_address, tx).with_listen_backlog(16);
+        let shutdown_tx = server.shutdown_tx.clone();
+        let server_task = tokio::spawn(async move { server.start().await });
+        for _ in 0..50 {
+            if TcpStream::connect(bind_address).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // The probe connection above
+        assert!(matches!(rx.recv().await, Some(NetworkProtocolMessage::Connect { .. })));
+
+        // Far more miners than the backlog, none of which ever sends a
+        // byte, so a handshake blocking accept would stall the rest
+        let miners: Vec<_> = (0..200).map(|_| tokio::spawn(TcpStream::connect(bind_address))).collect();
+        let mut streams = Vec::new();
+        for miner in miners {
+            streams.push(miner.await.unwrap().expect("connection refused"));
+        }
+
+        let mut connected = 0;
+        while connected < streams.len() {
+            match timeout(Duration::from_secs(5), rx.recv()).await.expect("connections dropped") {
+                Some(NetworkProtocolMessage::Connect { .. }) => connected += 1,
+                Some(_) => {}
+                None => panic!("server stopped"),
+            }
+        }
+        assert_eq!(connected, 200);
+
+        shutdown_tx.send(()).await.unwrap();
+        server_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connections_past_rate_limit_refused() {
+        let bind_address = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut server = StratumServer::new(bind_address, tx).with_connection_rate_limit(ConnectionRateLimitConfig {
+            connections_per_minute: 3,
+            exempt_loopback: false,
+        });
+        let shutdown_tx = server.shutdown_tx.clone();
+        let server_task = tokio::spawn(async move { server.start().await });
+        let mut first = None;
+        for _ in 0..50 {
+            if let Ok(stream) = TcpStream::connect(bind_address).await {
+                first = Some(stream);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut streams = vec![first.expect("server never listened")];
+        for _ in 0..4 {
+            streams.push(TcpStream::connect(bind_address).await.unwrap());
+        }
+
+        // Accepted connections wait for the miner to speak; refused ones
+        // are closed at once
+        let mut refused = 0;
+        for stream in &mut streams {
+            let mut buf = [0u8; 1];
+            if let Ok(read) = timeout(Duration::from_millis(500), stream.read(&mut buf)).await {
+                assert!(matches!(read, Ok(0) | Err(_)), "server sent data");
+                refused += 1;
+            }
+        }
+        assert_eq!(refused, 2);
+
+        let mut connected = 0;
+        while let Ok(Some(message)) = timeout(Duration::from_millis(200), rx.recv()).await {
+            if matches!(message, NetworkProtocolMessage::Connect { .. }) {
+                connected += 1;
+            }
+        }
+        assert_eq!(connected, 3);
+
+        shutdown_tx.send(()).await.unwrap();
+        server_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_open_connections() {
+        let bind_address = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut server = StratumServer::new(bind_address, tx).with_drain_timeout(Duration::from_secs(5));
+        let shutdown_tx = server.shutdown_tx.clone();
+        let server_task = tokio::spawn(async move { server.start().await });
+        let mut miner = None;
+        for _ in 0..50 {
+            if let Ok(stream) = TcpStream::connect(bind_address).await {
+                miner = Some(stream);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut miner = miner.expect("server never listened");
+
+        // The connection stays open for shares until shutdown
+        miner.write_all(concat!(
+            r#"{"id":1,"method":"mining.submit","params":["worker1","1","00000000","5f5e1000","00000001"]}"#, "\n",
+        ).as_bytes()).await.unwrap();
+        let mut buffer = [0u8; 4096];
+        let n = timeout(Duration::from_secs(5), miner.read(&mut buffer)).await.unwrap().unwrap();
+        assert!(String::from_utf8_lossy(&buffer[..n]).contains(r#""result":true"#));
+
+        let started = Instant::now();
+        shutdown_tx.send(()).await.unwrap();
+        timeout(Duration::from_secs(5), server_task).await.expect("connections never drained").unwrap().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Closed by the connection itself rather than force-closed
+        let mut rest = Vec::new();
+        timeout(Duration::from_secs(1), miner.read_to_end(&mut rest)).await.unwrap().unwrap();
+        let mut disconnects = Vec::new();
+        while let Some(message) = rx.recv().await {
+            if let NetworkProtocolMessage::Disconnect { reason, .. } = message {
+                disconnects.push(reason);
+            }
+        }
+        assert_eq!(disconnects, ["Connection closed"]);
+    }
+
+    #[test]
+    fn test_server_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = NetworkConfig::default();
+        config.capture.enabled = true;
+        config.capture.directory = dir.path().to_path_buf();
+        config.listen_backlog = 16;
+        config.connection_rate_limit.connections_per_minute = 5;
+        config.drain_timeout_secs = 3;
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let server = StratumServer::from_config(&config, tx);
+        assert_eq!(server.bind_address, config.bind_address);
+        assert_eq!(server.listen_backlog, 16);
+        assert_eq!(server.rate_limiter.config().connections_per_minute, 5);
+        assert_eq!(server.drain_timeout, Duration::from_secs(3));
+        // Capture enabled in config applies to every connection
+        assert!(server.capture_controller.is_enabled(Uuid::new_v4()));
+    }
+
+    /// Subscribe and authorize two workers, returning every line the server sent
+    async fn messages_after_authorize(welcome: WelcomeConfig) -> Vec<serde_json::Value> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut miner = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, peer_addr) = listener.accept().await.unwrap();
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let handler = ConnectionHandler::new(Uuid::new_v4(), stream, peer_addr, tx, shutdown_rx)
+            .with_welcome(Arc::new(welcome));
+        tokio::spawn(handler.handle());
+
+        miner.write_all(concat!(
+            r#"{"id":1,"method":"mining.subscribe","params":["bitaxe/BM1366/v2.4.0"]}"#, "\n",
+            r#"{"id":2,"method":"mining.authorize","params":["worker1","x"]}"#, "\n",
+            r#"{"id":3,"method":"mining.authorize","params":["worker2","x"]}"#, "\n",
+        ).as_bytes()).await.unwrap();
+
+        let mut received = String::new();
+        let mut buffer = [0u8; 4096];
+        while let Ok(Ok(n)) = timeout(Duration::from_millis(300), miner.read(&mut buffer)).await {
+            if n == 0 {
+                break;
+            }
+            received.push_str(&String::from_utf8_lossy(&buffer[..n]));
+        }
+        received.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sv1_frame_on_pinned_sv2_connection_disconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut peer = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, peer_addr) = listener.accept().await.unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let handler = ConnectionHandler::new(Uuid::new_v4(), stream, peer_addr, tx, shutdown_rx);
+        let handle = tokio::spawn(handler.handle());
+
+        peer.write_all(concat!(
+            r#"{"msg_type":"SetupConnection","payload":{}}"#, "\n",
+            r#"{"id":1,"method":"mining.subscribe","params":[]}"#, "\n",
+        ).as_bytes()).await.unwrap();
+
+        let result = timeout(Duration::from_secs(2), handle).await.unwrap().unwrap();
+        assert!(matches!(result, Err(Error::Protocol(_))));
+
+        // Only the SV2 frame reached the mode handler, and nothing was answered
+        assert!(matches!(rx.recv().await, Some(NetworkProtocolMessage::StratumV2 { .. })));
+        assert!(rx.recv().await.is_none());
+        let mut buffer = [0u8; 64];
+        assert_eq!(peer.read(&mut buffer).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_version_fails_handshake() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut peer = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, peer_addr) = listener.accept().await.unwrap();
+
+        let events = EventBus::default();
+        let mut published = events.subscribe();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let handler = ConnectionHandler::new(Uuid::new_v4(), stream, peer_addr, tx, shutdown_rx)
+            .with_events(events);
+        let handle = tokio::spawn(handler.handle());
+
+        peer.write_all(concat!(
+            r#"{"msg_type":"SetupConnection","payload":{"min_version":3,"max_version":3}}"#, "\n",
+        ).as_bytes()).await.unwrap();
+
+        let result = timeout(Duration::from_secs(2), handle).await.unwrap().unwrap();
+        assert!(matches!(result, Err(Error::Protocol(_))));
+        assert!(rx.recv().await.is_none());
+
+        // A structured error naming the versions this server speaks, then EOF
+        let mut reply = String::new();
+        peer.read_to_string(&mut reply).await.unwrap();
+        let frame: serde_json::Value = serde_json::from_str(reply.trim()).unwrap();
+        assert_eq!(frame["msg_type"], "SetupConnectionError");
+        assert_eq!(frame["payload"]["error_code"], "protocol-version-mismatch");
+        assert_eq!(frame["payload"]["min_version"], 2);
+        assert_eq!(frame["payload"]["max_version"], 2);
+
+        match published.recv().await.unwrap() {
+            Event::HandshakeFailed { address, reason } => {
+                assert_eq!(address, peer_addr);
+                assert_eq!(reason, crate::handshake_failures::HandshakeFailureReason::UnsupportedVersion);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_and_tls_clients_disconnected() {
+        // Neither ends in the newline a Stratum frame would
+        let openings: [&[u8]; 2] = [
+            b"GET / HTTP/1.1\r\nHost: 10.0.0.2:3333\r\n\r",
+            &[0x16, 0x03, 0x01, 0x00, 0xc8, 0x01],
+        ];
+        for opening in openings {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let mut peer = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+            let (stream, peer_addr) = listener.accept().await.unwrap();
+
+            let events = EventBus::default();
+            let mut published = events.subscribe();
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+            let handler = ConnectionHandler::new(Uuid::new_v4(), stream, peer_addr, tx, shutdown_rx)
+                .with_events(events);
+            let handle = tokio::spawn(handler.handle());
+
+            peer.write_all(opening).await.unwrap();
+            let result = timeout(Duration::from_secs(2), handle).await.unwrap().unwrap();
+            assert!(matches!(result, Err(Error::Protocol(_))));
+            assert!(rx.recv().await.is_none());
+
+            // Closed without a JSON-RPC parse error reply
+            let mut buffer = [0u8; 64];
+            assert_eq!(peer.read(&mut buffer).await.unwrap(), 0);
+            match published.recv().await.unwrap() {
+                Event::HandshakeFailed { reason, .. } => {
+                    assert_eq!(reason, crate::handshake_failures::HandshakeFailureReason::NotStratum);
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_welcome_message_after_authorize() {
+        let messages = messages_after_authorize(WelcomeConfig::default()).await;
+        assert_eq!(messages.len(), 3);
+        assert!(messages.iter().all(|m| m["method"] != "client.show_message"));
+
+        let welcome = WelcomeConfig {
+            message: Some("Welcome to the home pool".to_string()),
+            status_url: Some("http://10.0.0.2:8080".to_string()),
+            ..Default::default()
+        };
+        let messages = messages_after_authorize(welcome.clone()).await;
+        let shown: Vec<_> = messages.iter().filter(|m| m["method"] == "client.show_message").collect();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0]["params"][0], "Welcome to the home pool | Status: http://10.0.0.2:8080");
+        // Sent right after the first authorize response
+        assert_eq!(messages[1]["id"], 2);
+        assert_eq!(messages[2]["method"], "client.show_message");
+
+        let skipped = WelcomeConfig { skip_user_agents: vec!["bitaxe".to_string()], ..welcome };
+        let messages = messages_after_authorize(skipped).await;
+        assert!(messages.iter().all(|m| m["method"] != "client.show_message"));
+    }
+
+    #[tokio::test]
+    async fn test_banned_worker_rejected_while_sibling_accepted() {
+        let database = crate::database::MockDatabaseOps::new();
+        let worker_bans = Arc::new(WorkerBans::new());
+        worker_bans.ban(&database, "rig1", Some("duplicate shares".to_string())).await.unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut miner = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, peer_addr) = listener.accept().await.unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let handler = ConnectionHandler::new(Uuid::new_v4(), stream, peer_addr, tx, shutdown_rx)
+            .with_worker_bans(worker_bans);
+        tokio::spawn(handler.handle());
+
+        miner.write_all(concat!(
+            r#"{"id":1,"method":"mining.authorize","params":["rig1","x"]}"#, "\n",
+            r#"{"id":2,"method":"mining.authorize","params":["rig2","x"]}"#, "\n",
+            r#"{"id":3,"method":"mining.submit","params":["rig1","1","00000000","5f5e1000","0000abcd"]}"#, "\n",
+            r#"{"id":4,"method":"mining.submit","params":["rig2","1","00000000","5f5e1000","0000abce"]}"#, "\n",
+        ).as_bytes()).await.unwrap();
+
+        let mut received = String::new();
+        let mut buffer = [0u8; 4096];
+        while received.lines().count() < 4 {
+            let n = timeout(Duration::from_secs(2), miner.read(&mut buffer)).await.unwrap().unwrap();
+            received.push_str(&String::from_utf8_lossy(&buffer[..n]));
+        }
+        let responses: Vec<serde_json::Value> = received.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], 24);
+        assert_eq!(responses[3]["id"], 4);
+        assert_eq!(responses[3]["result"], true);
+
+        // Both authorizes and only rig2's share reached the mode handler
+        let mut forwarded = Vec::new();
+        while let Ok(Some(message)) = timeout(Duration::from_millis(200), rx.recv()).await {
+            forwarded.push(message);
+        }
+        assert_eq!(forwarded.len(), 3);
+        match &forwarded[2] {
+            NetworkProtocolMessage::StratumV1 { message, .. } => {
+                assert_eq!(message.params.as_ref().unwrap()[0], "rig2");
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+}
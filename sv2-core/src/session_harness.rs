@@ -0,0 +1,271 @@
+//! In-memory miner sessions for tests
+//!
+//! Mode handlers are mostly tested by calling them directly, which skips the
+//! stratum server in front of them. [`MinerSession`] puts a real
+//! [`ConnectionHandler`] between a test and a mode handler, joined by a
+//! `tokio::io::duplex` pipe instead of a socket, and plays the daemon's part:
+//! the connection is opened on the mode handler when the miner is accepted,
+//! each frame the server forwards is dispatched to it, and the connection is
+//! closed on it when the stream ends. SV2 frames, which the daemon only logs
+//! so far, are dispatched as handshakes and shares too. A test writes what a
+//! miner would write, reads what a miner would read, and checks the mode
+//! handler in between.
+
+use crate::mode::ModeHandler;
+use crate::protocol::NetworkProtocolMessage;
+use crate::server::ConnectionHandler;
+use crate::types::{Connection, ConnectionId, HandshakeDetails, Protocol, Share, ShareResult};
+use crate::{Error, Result};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, ReadHalf, WriteHalf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+/// How long to wait for the server to answer or forward a frame
+const STEP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Share difficulty the daemon assigns to submissions
+const SHARE_DIFFICULTY: f64 = 1.0;
+
+/// What the mode handler was asked to do with a forwarded frame
+#[derive(Debug, Clone)]
+pub enum Dispatched {
+    Handshake(HandshakeDetails),
+    Share(ShareResult),
+    /// Frames the daemon only logs, such as `mining.subscribe`
+    Ignored,
+}
+
+/// One miner connected to a mode handler through the stratum server
+pub struct MinerSession {
+    connection_id: ConnectionId,
+    handler: Arc<dyn ModeHandler>,
+    lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+    writer: WriteHalf<DuplexStream>,
+    forwarded: mpsc::UnboundedReceiver<NetworkProtocolMessage>,
+    server: JoinHandle<Result<()>>,
+    _shutdown: mpsc::Sender<()>,
+}
+
+impl MinerSession {
+    /// Accept a miner from `address` and open its connection on `handler`
+    pub async fn open(handler: Arc<dyn ModeHandler>, address: SocketAddr) -> Result<Self> {
+        let connection_id = Uuid::new_v4();
+        let (miner, server_side) = tokio::io::duplex(64 * 1024);
+        let (forward_tx, forwarded) = mpsc::unbounded_channel();
+        let (shutdown, shutdown_rx) = mpsc::channel(1);
+
+        // Like the daemon, the protocol is only known once the first frame arrives
+        let connection = Connection { id: connection_id, ..Connection::new(address, Protocol::StratumV1) };
+        handler.handle_connection(connection).await?;
+
+        let server = tokio::spawn(
+            ConnectionHandler::new(connection_id, server_side, address, forward_tx, shutdown_rx).handle(),
+        );
+        let (reader, writer) = tokio::io::split(miner);
+
+        Ok(Self {
+            connection_id,
+            handler,
+            lines: BufReader::new(reader).lines(),
+            writer,
+            forwarded,
+            server,
+            _shutdown: shutdown,
+        })
+    }
+
+    pub fn connection_id(&self) -> ConnectionId {
+        self.connection_id
+    }
+
+    /// Write one frame as the miner
+    pub async fn send(&mut self, frame: serde_json::Value) -> Result<()> {
+        let line = format!("{}\n", frame);
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Next frame the server wrote back to the miner
+    pub async fn recv(&mut self) -> Result<serde_json::Value> {
+        let line = timeout(STEP_TIMEOUT, self.lines.next_line())
+            .await
+            .map_err(|_| Error::Network("server sent nothing".to_string()))??
+            .ok_or_else(|| Error::Network("server closed the connection".to_string()))?;
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Hand the next frame the server forwarded to the mode handler, the way
+    /// the daemon does
+    pub async fn dispatch(&mut self) -> Result<Dispatched> {
+        let message = timeout(STEP_TIMEOUT, self.forwarded.recv())
+            .await
+            .map_err(|_| Error::Network("server forwarded nothing".to_string()))?
+            .ok_or_else(|| Error::Network("server stopped forwarding".to_string()))?;
+
+        match message {
+            NetworkProtocolMessage::StratumV1 { message, .. } => {
+                if message.method.as_deref() != Some("mining.submit") {
+                    return Ok(Dispatched::Ignored);
+                }
+                // [worker, job_id, extranonce2, ntime, nonce]
+                let params = message.params.unwrap_or_default();
+                let ntime = hex_param(&params[3])?;
+                let nonce = hex_param(&params[4])?;
+                self.submit(ntime, nonce).await
+            }
+            NetworkProtocolMessage::StratumV2 { data, .. } => {
+                let frame: serde_json::Value = serde_json::from_slice(&data)?;
+                let payload = &frame["payload"];
+                match frame["msg_type"].as_str() {
+                    Some("SetupConnection") => {
+                        let details = HandshakeDetails {
+                            user_agent: payload["device_id"].as_str().map(String::from),
+                            version: payload["max_version"].as_u64().map(|version| version.to_string()),
+                            flags: payload["flags"].as_u64().map(|flags| flags as u32),
+                            ..Default::default()
+                        };
+                        self.handler.handle_handshake(self.connection_id, details.clone()).await?;
+                        Ok(Dispatched::Handshake(details))
+                    }
+                    Some("SubmitSharesStandard") => {
+                        let field = |name: &str| {
+                            payload[name].as_u64().map(|value| value as u32).ok_or_else(|| {
+                                Error::Protocol(format!("SubmitSharesStandard without {}", name))
+                            })
+                        };
+                        let (ntime, nonce) = (field("ntime")?, field("nonce")?);
+                        self.submit(ntime, nonce).await
+                    }
+                    _ => Ok(Dispatched::Ignored),
+                }
+            }
+            _ => Ok(Dispatched::Ignored),
+        }
+    }
+
+    async fn submit(&self, ntime: u32, nonce: u32) -> Result<Dispatched> {
+        let share = Share::new(self.connection_id, nonce, ntime, SHARE_DIFFICULTY);
+        Ok(Dispatched::Share(self.handler.process_share(share).await?))
+    }
+
+    /// Hang up as the miner and close the connection on the mode handler.
+    /// Returns how the server's side of the connection ended.
+    pub async fn close(self) -> Result<Result<()>> {
+        let Self { connection_id, handler, lines, writer, server, .. } = self;
+        drop((lines, writer));
+        let ended = timeout(STEP_TIMEOUT, server)
+            .await
+            .map_err(|_| Error::Network("server kept the connection open".to_string()))?
+            .map_err(|e| Error::Internal(format!("connection task failed: {}", e)))?;
+        handler.handle_disconnection(connection_id).await?;
+        Ok(ended)
+    }
+}
+
+fn hex_param(param: &serde_json::Value) -> Result<u32> {
+    let hex = param.as_str().ok_or_else(|| Error::Protocol(format!("expected a hex string, got {}", param)))?;
+    u32::from_str_radix(hex, 16).map_err(|e| Error::Protocol(format!("invalid hex '{}': {}", hex, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin_rpc::BitcoinRpcClient;
+    use crate::config::{BitcoinConfig, SoloConfig};
+    use crate::database::{DatabaseOps, MockDatabaseOps};
+    use crate::modes::SoloModeHandler;
+    use crate::template_source::MockTemplateSource;
+    use crate::types::ConnectionState;
+    use serde_json::json;
+
+    fn solo_handler(database: Arc<MockDatabaseOps>) -> Arc<dyn ModeHandler> {
+        let template = MockTemplateSource::template([5; 32], 1e12);
+        let handler = SoloModeHandler::new(
+            SoloConfig::default(),
+            BitcoinRpcClient::new(BitcoinConfig::default()),
+            database,
+        )
+        .with_template_source(Arc::new(MockTemplateSource::new(vec![template])));
+        Arc::new(handler)
+    }
+
+    async fn state(database: &MockDatabaseOps, connection_id: ConnectionId) -> ConnectionState {
+        database.get_connection(connection_id).await.unwrap().unwrap().state
+    }
+
+    #[tokio::test]
+    async fn test_sv1_session() {
+        let database = Arc::new(MockDatabaseOps::new());
+        let handler = solo_handler(Arc::clone(&database));
+        let ntime = handler.get_work_template().await.unwrap().timestamp;
+
+        let mut miner = MinerSession::open(Arc::clone(&handler), "10.0.0.20:51000".parse().unwrap()).await.unwrap();
+        let connection_id = miner.connection_id();
+        assert_eq!(state(&database, connection_id).await, ConnectionState::Connected);
+
+        miner.send(json!({"id": 1, "method": "mining.subscribe", "params": ["cpuminer/2.5"]})).await.unwrap();
+        assert_eq!(miner.recv().await.unwrap()["id"], 1);
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Ignored));
+
+        miner.send(json!({"id": 2, "method": "mining.authorize", "params": ["rig1", "x"]})).await.unwrap();
+        assert_eq!(miner.recv().await.unwrap()["result"], true);
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Ignored));
+
+        let submit = |id: u64, ntime: u32| {
+            json!({"id": id, "method": "mining.submit", "params": ["rig1", "1", "00000000", format!("{:08x}", ntime), "0000abcd"]})
+        };
+        miner.send(submit(3, ntime)).await.unwrap();
+        assert_eq!(miner.recv().await.unwrap()["id"], 3);
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Share(ShareResult::Valid)));
+
+        // Outside the template's ntime window
+        miner.send(submit(4, ntime + 7200)).await.unwrap();
+        miner.recv().await.unwrap();
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Share(ShareResult::Invalid(_))));
+
+        let shares = database.get_shares(Some(connection_id), None).await.unwrap();
+        assert_eq!(shares.iter().filter(|share| share.is_valid).count(), 1);
+        assert_eq!(shares.len(), 2);
+
+        assert!(miner.close().await.unwrap().is_ok());
+        assert_eq!(state(&database, connection_id).await, ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_sv2_session() {
+        let database = Arc::new(MockDatabaseOps::new());
+        let handler = solo_handler(Arc::clone(&database));
+        let ntime = handler.get_work_template().await.unwrap().timestamp;
+
+        let mut miner = MinerSession::open(Arc::clone(&handler), "10.0.0.21:51000".parse().unwrap()).await.unwrap();
+        let connection_id = miner.connection_id();
+
+        miner.send(json!({
+            "msg_type": "SetupConnection",
+            "payload": {"min_version": 2, "max_version": 2, "flags": 1, "device_id": "bitaxe-ultra"}
+        })).await.unwrap();
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Handshake(_)));
+        let connection = database.get_connection(connection_id).await.unwrap().unwrap();
+        assert_eq!(connection.version.as_deref(), Some("2"));
+        assert_eq!(connection.flags, Some(1));
+        assert_eq!(connection.user_agent.as_deref(), Some("bitaxe-ultra"));
+
+        miner.send(json!({
+            "msg_type": "SubmitSharesStandard",
+            "payload": {"channel_id": 1, "sequence_number": 0, "job_id": 1, "nonce": 0xabcd, "ntime": ntime, "version": 0x20000000}
+        })).await.unwrap();
+        assert!(matches!(miner.dispatch().await.unwrap(), Dispatched::Share(ShareResult::Valid)));
+
+        // An SV1 frame on the SV2 session ends it
+        miner.send(json!({"id": 1, "method": "mining.subscribe", "params": []})).await.unwrap();
+        assert!(matches!(miner.close().await.unwrap(), Err(Error::Protocol(_))));
+        assert_eq!(state(&database, connection_id).await, ConnectionState::Disconnected);
+        assert_eq!(database.get_shares(Some(connection_id), None).await.unwrap().len(), 1);
+    }
+}
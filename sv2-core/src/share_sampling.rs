@@ -0,0 +1,144 @@
+//! Sampling of accepted shares for persistence
+//!
+//! At high share rates most stored rows are accepted shares that say nothing
+//! the stats don't already. With `database.accepted_share_sample_rate` above
+//! one, only one in that many accepted shares per connection is stored, with
+//! a weight covering the ones skipped since the last stored row, so the
+//! aggregate stats still count every share. Rejected shares and block
+//! candidates are always stored. Shares skipped since the last stored row
+//! are written out when their connection closes.
+
+use crate::{
+    database::DatabaseOps,
+    dead_letter::{self, DeadLetterLog},
+    Result, Share,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Accepted shares skipped on one connection since its last stored row
+#[derive(Debug)]
+struct Pending {
+    last: Share,
+    count: u64,
+}
+
+/// Decides which shares get a row, per `database.accepted_share_sample_rate`
+#[derive(Debug)]
+pub struct ShareSampler {
+    accepted_sample_rate: u64,
+    pending: Mutex<HashMap<Uuid, Pending>>,
+}
+
+impl Default for ShareSampler {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl ShareSampler {
+    pub fn new(accepted_sample_rate: u64) -> Self {
+        Self {
+            accepted_sample_rate: accepted_sample_rate.max(1),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How many shares a row for `share` stands for, or `None` if it's
+    /// skipped and counted towards a later row
+    pub fn weight(&self, share: &Share) -> Option<u64> {
+        if self.accepted_sample_rate == 1 || !share.is_valid || share.block_hash.is_some() {
+            return Some(1);
+        }
+        let mut pending = self.pending.lock().unwrap();
+        let skipped = pending.remove(&share.connection_id).map_or(0, |p| p.count);
+        if skipped + 1 >= self.accepted_sample_rate {
+            return Some(skipped + 1);
+        }
+        pending.insert(share.connection_id, Pending { last: share.clone(), count: skipped + 1 });
+        None
+    }
+
+    /// Store `share` if it's sampled, through `dead_letter` when one is configured
+    pub async fn store(
+        &self,
+        database: &dyn DatabaseOps,
+        dead_letter: Option<&DeadLetterLog>,
+        share: &Share,
+    ) -> Result<()> {
+        match self.weight(share) {
+            Some(weight) => dead_letter::store_share(database, dead_letter, share, weight).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Store the shares skipped on `connection_id`, as a row for the last of
+    /// them, so its closing counts are exact
+    pub async fn flush(
+        &self,
+        database: &dyn DatabaseOps,
+        dead_letter: Option<&DeadLetterLog>,
+        connection_id: Uuid,
+    ) -> Result<()> {
+        let pending = self.pending.lock().unwrap().remove(&connection_id);
+        match pending {
+            Some(pending) => dead_letter::store_share(database, dead_letter, &pending.last, pending.count).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+    use bitcoin::hashes::Hash;
+
+    fn share(connection_id: Uuid, nonce: u32, is_valid: bool) -> Share {
+        let mut share = Share::new(connection_id, nonce, 1_700_000_000, 1.0);
+        share.is_valid = is_valid;
+        share
+    }
+
+    #[tokio::test]
+    async fn test_stats_stay_exact_while_rows_are_sampled() {
+        let database = MockDatabaseOps::new();
+        let sampler = ShareSampler::new(100);
+        let connection_id = Uuid::new_v4();
+
+        for nonce in 0..250 {
+            sampler.store(&database, None, &share(connection_id, nonce, true)).await.unwrap();
+        }
+        for nonce in 250..253 {
+            sampler.store(&database, None, &share(connection_id, nonce, false)).await.unwrap();
+        }
+        let mut candidate = share(connection_id, 253, true);
+        candidate.block_hash = Some(bitcoin::BlockHash::from_byte_array([0xab; 32]));
+        sampler.store(&database, None, &candidate).await.unwrap();
+
+        // Two sampled rows for 200 accepted shares, every rejected share and the block
+        let stored = database.get_shares(Some(connection_id), None).await.unwrap();
+        assert_eq!(stored.len(), 6);
+        assert_eq!(stored.iter().filter(|s| !s.is_valid).count(), 3);
+        assert!(stored.iter().any(|s| s.block_hash.is_some()));
+        let stats = database.get_share_stats(Some(connection_id)).await.unwrap();
+        assert_eq!(stats.total_shares, 204);
+        assert_eq!(stats.valid_shares, 201);
+
+        sampler.flush(&database, None, connection_id).await.unwrap();
+        assert_eq!(database.get_shares(Some(connection_id), None).await.unwrap().len(), 7);
+        let stats = database.get_share_stats(Some(connection_id)).await.unwrap();
+        assert_eq!(stats.total_shares, 254);
+        assert_eq!(stats.valid_shares, 251);
+        assert_eq!(stats.invalid_shares, 3);
+        assert_eq!(stats.blocks_found, 1);
+    }
+
+    #[test]
+    fn test_unsampled_rate_stores_every_share() {
+        let sampler = ShareSampler::default();
+        let connection_id = Uuid::new_v4();
+        assert!((0..10).all(|nonce| sampler.weight(&share(connection_id, nonce, true)) == Some(1)));
+    }
+}
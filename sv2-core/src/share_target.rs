@@ -0,0 +1,206 @@
+//! Difficulty to share target conversion
+//!
+//! A share of difficulty `d` needs a hash at or below `diff1 / d`, where
+//! `diff1` is the difficulty-1 target `0xffff << 208` (compact `0x1d00ffff`).
+//! Validation and channel setup convert the same handful of difficulties over
+//! and over, since most connections run at the same one, so [`cached_target`]
+//! keeps recent results. The conversion is pure: entries are only ever
+//! evicted to bound the cache, never invalidated.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Difficulties [`cached_target`] remembers
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Largest target, returned for difficulties too small to have one of their own
+const MAX_TARGET: [u8; 32] = [0xff; 32];
+
+/// Big-endian 256-bit target for `difficulty`, `None` unless it's a positive number
+pub fn difficulty_to_target(difficulty: f64) -> Option<[u8; 32]> {
+    if !difficulty.is_finite() || difficulty <= 0.0 {
+        return None;
+    }
+
+    // difficulty = mantissa * 2^exponent, exactly
+    let bits = difficulty.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut mantissa, mut exponent) = match biased {
+        0 => (fraction, -1074),
+        _ => (fraction | (1 << 52), biased - 1075),
+    };
+    let zeros = mantissa.trailing_zeros();
+    mantissa >>= zeros;
+    exponent += zeros as i32;
+
+    // target = 0xffff * 2^(208 - exponent) / mantissa, in 384 bits so the
+    // numerator fits before dividing. Little-endian limbs.
+    let shift = 208 - exponent;
+    if shift < 0 {
+        // Only difficulties from 2^208 up get here, with targets of 0xffff and below
+        let quotient = match u32::try_from(-shift) {
+            Ok(bits) if bits < 16 => 0xffff / (mantissa << bits).max(1),
+            _ => 0,
+        };
+        let mut target = [0u8; 32];
+        target[24..].copy_from_slice(&quotient.to_be_bytes());
+        return Some(target);
+    }
+    // A numerator past 384 bits leaves a quotient far past 256 after dividing by a 53-bit mantissa
+    if shift + 16 > 384 {
+        return Some(MAX_TARGET);
+    }
+    let mut limbs = [0u64; 6];
+    let (limb, offset) = ((shift / 64) as usize, shift % 64);
+    limbs[limb] |= 0xffff << offset;
+    if offset > 48 {
+        limbs[limb + 1] |= 0xffff >> (64 - offset);
+    }
+
+    let mut remainder = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let current = (remainder << 64) | *limb as u128;
+        *limb = (current / mantissa as u128) as u64;
+        remainder = current % mantissa as u128;
+    }
+    if limbs[4] != 0 || limbs[5] != 0 {
+        return Some(MAX_TARGET);
+    }
+
+    let mut target = [0u8; 32];
+    for (i, limb) in limbs[..4].iter().enumerate() {
+        target[(3 - i) * 8..(4 - i) * 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    Some(target)
+}
+
+/// Difficulty of a big-endian 256-bit target, `diff1 / target`; `None` for
+/// the zero target
+pub fn target_to_difficulty(target: &[u8; 32]) -> Option<f64> {
+    let target = target.iter().fold(0.0, |value, byte| value * 256.0 + *byte as f64);
+    if target == 0.0 {
+        return None;
+    }
+    Some(0xffff as f64 * 2f64.powi(208) / target)
+}
+
+/// Bounded difficulty → target map; the oldest entry goes first when full
+#[derive(Debug)]
+pub struct TargetCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Keyed by the difficulty's bits, so every distinct f64 is its own entry
+    targets: HashMap<u64, [u8; 32]>,
+    inserted: VecDeque<u64>,
+}
+
+impl TargetCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: Mutex::new(Entries::default()) }
+    }
+
+    /// Target for `difficulty`, computed at most once while it stays cached
+    pub fn target(&self, difficulty: f64) -> Option<[u8; 32]> {
+        let key = difficulty.to_bits();
+        if let Some(target) = self.entries.lock().unwrap().targets.get(&key) {
+            return Some(*target);
+        }
+
+        let target = difficulty_to_target(difficulty)?;
+        let mut entries = self.entries.lock().unwrap();
+        if entries.targets.insert(key, target).is_none() {
+            entries.inserted.push_back(key);
+            while entries.inserted.len() > self.capacity {
+                if let Some(oldest) = entries.inserted.pop_front() {
+                    entries.targets.remove(&oldest);
+                }
+            }
+        }
+        Some(target)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// [`difficulty_to_target`] through a process-wide [`TargetCache`]
+pub fn cached_target(difficulty: f64) -> Option<[u8; 32]> {
+    static CACHE: OnceLock<TargetCache> = OnceLock::new();
+    CACHE.get_or_init(|| TargetCache::new(DEFAULT_CAPACITY)).target(difficulty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{CompactTarget, Target};
+
+    fn hex(difficulty: f64) -> String {
+        hex::encode(difficulty_to_target(difficulty).unwrap())
+    }
+
+    #[test]
+    fn test_known_targets() {
+        assert_eq!(hex(1.0), "00000000ffff0000000000000000000000000000000000000000000000000000");
+        assert_eq!(hex(2.0), "000000007fff8000000000000000000000000000000000000000000000000000");
+        assert_eq!(hex(3.0), "0000000055550000000000000000000000000000000000000000000000000000");
+        assert_eq!(hex(0.5), "00000001fffe0000000000000000000000000000000000000000000000000000");
+        assert_eq!(hex(65536.0), "000000000000ffff000000000000000000000000000000000000000000000000");
+        assert_eq!(hex(1024.0), "00000000003fffc0000000000000000000000000000000000000000000000000");
+
+        // Difficulty 1 is the genesis block's compact target
+        let genesis = Target::from_compact(CompactTarget::from_consensus(0x1d00ffff));
+        assert_eq!(Target::from_be_bytes(difficulty_to_target(1.0).unwrap()), genesis);
+
+        // Too small a difficulty for its own target, and too large for a non-zero one
+        assert_eq!(difficulty_to_target(1e-12), Some(MAX_TARGET));
+        assert_eq!(difficulty_to_target(1e80), Some([0; 32]));
+
+        for invalid in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(difficulty_to_target(invalid), None);
+        }
+    }
+
+    #[test]
+    fn test_target_to_difficulty_inverts_difficulty_to_target() {
+        for difficulty in [0.5, 1.0, 3.0, 1024.0, 65536.0, 1e6, 1.23e12] {
+            let target = difficulty_to_target(difficulty).unwrap();
+            let back = target_to_difficulty(&target).unwrap();
+            assert!((back - difficulty).abs() / difficulty < 1e-9, "{} -> {}", difficulty, back);
+        }
+        assert_eq!(target_to_difficulty(&[0; 32]), None);
+    }
+
+    #[test]
+    fn test_targets_shrink_with_difficulty() {
+        let difficulties = [0.001, 0.75, 1.0, 1.5, 512.0, 1000.0, 1e6, 1.23e12];
+        for pair in difficulties.windows(2) {
+            assert!(difficulty_to_target(pair[0]) > difficulty_to_target(pair[1]), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let cache = TargetCache::new(2);
+        assert_eq!(cache.target(1.0), difficulty_to_target(1.0));
+        assert_eq!(cache.target(1.0), difficulty_to_target(1.0));
+        assert_eq!(cache.len(), 1);
+
+        cache.target(2.0);
+        cache.target(4.0);
+        assert_eq!(cache.len(), 2);
+        // Invalid difficulties aren't cached
+        assert_eq!(cache.target(0.0), None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.target(1.0), difficulty_to_target(1.0));
+    }
+}
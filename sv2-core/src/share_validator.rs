@@ -1,5 +1,5 @@
 use crate::{Result, Error, Share, ShareResult, WorkTemplate, types::ShareSubmission};
-use bitcoin::{BlockHash, Target, CompactTarget};
+use bitcoin::{BlockHash, Target};
 use std::str::FromStr;
 use sha2::{Sha256, Digest};
 use std::collections::HashMap;
@@ -197,12 +197,9 @@ impl ShareValidator {
             )));
         }
         
-        // Validate timestamp is not before template creation
-        if share.timestamp < template.timestamp {
-            return Err(Error::ShareValidation(ShareValidationError::InvalidTimestamp(
-                format!("Share timestamp {} before template timestamp {}", 
-                        share.timestamp, template.timestamp)
-            )));
+        // ntime must be one the node would accept in a block on this template
+        if let Some(reason) = template.ntime_bounds().violation(share.timestamp) {
+            return Err(Error::ShareValidation(ShareValidationError::InvalidTimestamp(reason)));
         }
         
         Ok(())
@@ -289,14 +286,12 @@ impl ShareValidator {
             )));
         }
         
-        // Simplified target calculation
-        // In reality, this would use proper Bitcoin target calculation
-        let max_target_value = 0x1d00ffff_u32; // Bitcoin's max target in compact form
-        let target_value = (max_target_value as f64 / difficulty) as u32;
-        
-        // Create a simplified target (this is not the real Bitcoin target calculation)
-        let compact_target = CompactTarget::from_consensus(target_value);
-        Ok(Target::from_compact(compact_target))
+        // Connections mostly share a difficulty, so the conversion is cached
+        crate::share_target::cached_target(difficulty)
+            .map(Target::from_be_bytes)
+            .ok_or_else(|| Error::ShareValidation(ShareValidationError::InvalidDifficulty(
+                format!("No target for difficulty {}", difficulty)
+            )))
     }
 
     /// Build block header for hashing
@@ -367,10 +362,7 @@ impl ShareValidator {
         let mut hash_be = *hash;
         hash_be.reverse();
         
-        let compact_value = u32::from_be_bytes([hash_be[0], hash_be[1], hash_be[2], hash_be[3]]);
-        let compact_target = CompactTarget::from_consensus(compact_value);
-        let hash_target = Target::from_compact(compact_target);
-        hash_target <= *target
+        Target::from_be_bytes(hash_be) <= *target
     }
 
     /// Get validation statistics
@@ -398,6 +390,25 @@ pub struct ShareValidatorStats {
     pub max_difficulty: f64,
 }
 
+/// Probability that a share of the given difficulty is also a block
+///
+/// Each hash meeting the share target meets the network target with
+/// probability `share_difficulty / network_difficulty`, capped at 1.
+pub fn block_probability(share_difficulty: f64, network_difficulty: f64) -> f64 {
+    if share_difficulty <= 0.0 || network_difficulty <= 0.0 {
+        return 0.0;
+    }
+    (share_difficulty / network_difficulty).min(1.0)
+}
+
+/// Expected number of blocks from a set of accepted share difficulties
+pub fn expected_blocks<I: IntoIterator<Item = f64>>(share_difficulties: I, network_difficulty: f64) -> f64 {
+    share_difficulties
+        .into_iter()
+        .map(|difficulty| block_probability(difficulty, network_difficulty))
+        .sum()
+}
+
 // Add ShareValidation error to the main Error enum
 impl From<ShareValidationError> for Error {
     fn from(err: ShareValidationError) -> Self {
@@ -472,6 +483,48 @@ mod tests {
         assert!(invalid_result.is_err());
     }
 
+    #[test]
+    fn test_ntime_bounds() {
+        let curtime = 1_700_000_000;
+        let bounds = crate::NtimeBounds::new(curtime - 1800, curtime).with_median_time_past(curtime - 1200);
+        assert_eq!(bounds.earliest(), curtime - 1199);
+
+        let share = |ntime| Share::new(uuid::Uuid::new_v4(), 1, ntime, 1.0);
+
+        // In bounds
+        assert!(share(curtime).check_ntime(&bounds).is_ok());
+        assert!(share(curtime + 7200).check_ntime(&bounds).is_ok());
+
+        // Too old: after mintime but not after median time past
+        let old = share(curtime - 1200);
+        assert!(old.is_stale(&bounds));
+        assert!(old.check_ntime(&bounds).unwrap_err().to_string().contains("earliest allowed"));
+
+        // Too far in the future
+        let future = share(curtime + 7201);
+        assert!(!future.is_stale(&bounds));
+        assert!(future.check_ntime(&bounds).unwrap_err().to_string().contains("latest allowed"));
+    }
+
+    #[test]
+    fn test_validate_against_template_rejects_ntime_out_of_bounds() {
+        let validator = ShareValidator::new(ShareValidatorConfig::default());
+        let mut template = create_test_template();
+        let now = chrono::Utc::now().timestamp() as u32;
+        template.ntime_bounds = Some(crate::NtimeBounds::new(now - 600, now));
+
+        let share = |ntime| Share::new(uuid::Uuid::new_v4(), 1, ntime, 1.0);
+        assert!(validator.validate_against_template(&share(now - 60), &template).is_ok());
+        assert!(matches!(
+            validator.validate_against_template(&share(now - 601), &template),
+            Err(Error::ShareValidation(ShareValidationError::InvalidTimestamp(_)))
+        ));
+        assert!(matches!(
+            validator.validate_against_template(&share(now + 7201), &template),
+            Err(Error::ShareValidation(ShareValidationError::InvalidTimestamp(_)))
+        ));
+    }
+
     #[tokio::test]
     async fn test_duplicate_detection() {
         let config = ShareValidatorConfig::default();
@@ -568,6 +621,18 @@ mod tests {
         assert_eq!(stats.recent_shares_tracked, 0);
     }
 
+    #[test]
+    fn test_block_probability() {
+        assert_eq!(block_probability(1000.0, 1_000_000.0), 0.001);
+        assert_eq!(block_probability(2.0e12, 1.0e12), 1.0);
+        assert_eq!(block_probability(1000.0, 0.0), 0.0);
+        assert_eq!(block_probability(0.0, 1_000_000.0), 0.0);
+
+        let expected = expected_blocks(vec![500.0; 4], 1_000_000.0);
+        assert!((expected - 0.002).abs() < 1e-12);
+        assert_eq!(expected_blocks(Vec::new(), 1_000_000.0), 0.0);
+    }
+
     #[tokio::test]
     async fn test_validator_stats() {
         let config = ShareValidatorConfig::default();
@@ -0,0 +1,137 @@
+//! Stratum pool URLs
+//!
+//! `stratum+tcp://host:port` is a Stratum V1 pool and
+//! `stratum2+tcp://host:port[/authority_pubkey]` a Stratum V2 one. A bare
+//! `host[:port]` is taken as Stratum V1.
+
+use crate::{types::Protocol, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Port assumed for Stratum V1 URLs without one
+pub const DEFAULT_SV1_PORT: u16 = 4444;
+
+/// Port assumed for Stratum V2 URLs without one, the SRI pool default
+pub const DEFAULT_SV2_PORT: u16 = 34254;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StratumUrl {
+    pub protocol: Protocol,
+    pub host: String,
+    pub port: u16,
+    /// Pool authority key from the URL path, SV2 only
+    pub authority_key: Option<String>,
+}
+
+impl StratumUrl {
+    /// `host:port` to connect to
+    pub fn address(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    pub fn is_sv2(&self) -> bool {
+        matches!(self.protocol, Protocol::Sv2 | Protocol::StratumV2)
+    }
+}
+
+impl FromStr for StratumUrl {
+    type Err = Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        let url = url.trim();
+        let (protocol, rest) = match url.split_once("://") {
+            Some(("stratum+tcp" | "stratum" | "tcp", rest)) => (Protocol::Sv1, rest),
+            Some(("stratum2+tcp" | "stratum2", rest)) => (Protocol::Sv2, rest),
+            Some((scheme, _)) => {
+                return Err(Error::Config(format!(
+                    "Unsupported pool URL scheme '{}://'; use stratum+tcp:// or stratum2+tcp://",
+                    scheme
+                )))
+            }
+            None => (Protocol::Sv1, url),
+        };
+
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let authority_key = match path.trim_matches('/') {
+            "" => None,
+            key if protocol == Protocol::Sv2 => Some(key.to_string()),
+            _ => return Err(Error::Config(format!("Unexpected path in Stratum V1 URL '{}'", url))),
+        };
+
+        let default_port = if protocol == Protocol::Sv2 { DEFAULT_SV2_PORT } else { DEFAULT_SV1_PORT };
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            // [ipv6]:port
+            let (host, after) = bracketed.split_once(']')
+                .ok_or_else(|| Error::Config(format!("Unclosed '[' in pool URL '{}'", url)))?;
+            match after.strip_prefix(':') {
+                Some(port) => (host, parse_port(port)?),
+                None => (host, default_port),
+            }
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, parse_port(port)?),
+                None => (authority, default_port),
+            }
+        };
+
+        if host.is_empty() {
+            return Err(Error::Config(format!("Pool URL '{}' has no host", url)));
+        }
+
+        Ok(Self {
+            protocol,
+            host: host.to_string(),
+            port,
+            authority_key,
+        })
+    }
+}
+
+fn parse_port(port: &str) -> Result<u16> {
+    port.parse::<u16>()
+        .map_err(|_| Error::Config(format!("Invalid port in pool URL: {}", port)))
+}
+
+impl fmt::Display for StratumUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scheme = if self.is_sv2() { "stratum2+tcp" } else { "stratum+tcp" };
+        write!(f, "{}://{}", scheme, self.address())?;
+        if let Some(key) = &self.authority_key {
+            write!(f, "/{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stratum_urls() {
+        let url: StratumUrl = "stratum+tcp://pool.example.com:3333".parse().unwrap();
+        assert_eq!(url.protocol, Protocol::Sv1);
+        assert_eq!(url.address(), "pool.example.com:3333");
+
+        let url: StratumUrl = "stratum2+tcp://10.0.0.5:34254/9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
+            .parse()
+            .unwrap();
+        assert!(url.is_sv2());
+        assert_eq!(url.authority_key.as_deref(), Some("9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"));
+        assert_eq!(url.to_string(), "stratum2+tcp://10.0.0.5:34254/9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72");
+
+        let url: StratumUrl = "stratum2+tcp://[::1]".parse().unwrap();
+        assert_eq!(url.address(), "[::1]:34254");
+
+        let url: StratumUrl = "pool.example.com".parse().unwrap();
+        assert_eq!((url.protocol, url.port), (Protocol::Sv1, DEFAULT_SV1_PORT));
+
+        for invalid in ["", "stratum+tcp://", ":4444", "pool.example.com:99999", "http://pool:80", "stratum+tcp://pool:3333/key"] {
+            assert!(invalid.parse::<StratumUrl>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,742 @@
+//! Stratum V2 mining message codec
+//!
+//! The daemon sits between miners and SRI's pool_sv2/translator_sv2, so the
+//! frames it reads and writes have to match SRI's encoding byte for byte.
+//! A frame is a 6 byte header (extension type u16, message type u8, payload
+//! length u24, all little-endian) and a payload of the spec's primitive
+//! types: integers and F32 little-endian, BOOL as one byte, U256 as 32 raw
+//! bytes, STR0_255, B0_32 and B0_255 with a one byte length prefix, B0_64K
+//! with a two byte one, and OPTION and SEQ0_255 as a sequence of items behind
+//! a one byte count. Messages addressed to a channel set the top bit of the
+//! extension type, as SRI does. `NewTemplate`, from the template
+//! distribution protocol, is here too, for work sent down by an upstream.
+
+use crate::protocol::{sv2_message_types, ProtocolParseError, SV2_HEADER_LEN};
+use crate::types::WorkTemplate;
+use crate::{Error, Result};
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::Hash;
+use serde::{Deserialize, Serialize};
+
+/// Extension type bit SRI sets on messages addressed to a channel
+pub const CHANNEL_MSG_BIT: u16 = 0x8000;
+
+/// Largest payload the u24 length field can describe
+const MAX_PAYLOAD_LEN: usize = 0xff_ffff;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupConnection {
+    /// 0 for the mining protocol
+    pub protocol: u8,
+    pub min_version: u16,
+    pub max_version: u16,
+    pub flags: u32,
+    pub endpoint_host: String,
+    pub endpoint_port: u16,
+    pub vendor: String,
+    pub hardware_version: String,
+    pub firmware: String,
+    pub device_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenStandardMiningChannel {
+    pub request_id: u32,
+    pub user_identity: String,
+    /// Expected hashrate in H/s
+    pub nominal_hash_rate: f32,
+    /// Little-endian, as on the wire
+    pub max_target: [u8; 32],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenStandardMiningChannelSuccess {
+    pub request_id: u32,
+    pub channel_id: u32,
+    /// Little-endian, as on the wire
+    pub target: [u8; 32],
+    /// Up to 32 bytes
+    pub extranonce_prefix: Vec<u8>,
+    pub group_channel_id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewMiningJob {
+    pub channel_id: u32,
+    pub job_id: u32,
+    /// None for a future job, to be activated by a later SetNewPrevHash
+    pub min_ntime: Option<u32>,
+    pub version: u32,
+    /// Up to 32 bytes
+    pub merkle_root: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmitSharesStandard {
+    pub channel_id: u32,
+    pub sequence_number: u32,
+    pub job_id: u32,
+    pub nonce: u32,
+    pub ntime: u32,
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmitSharesExtended {
+    pub channel_id: u32,
+    pub sequence_number: u32,
+    pub job_id: u32,
+    pub nonce: u32,
+    pub ntime: u32,
+    pub version: u32,
+    /// The miner's part of the extranonce, up to 32 bytes
+    pub extranonce: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetTarget {
+    pub channel_id: u32,
+    /// Little-endian, as on the wire
+    pub maximum_target: [u8; 32],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewTemplate {
+    pub template_id: u64,
+    /// Work for the next block, to be activated by a later SetNewPrevHash
+    pub future_template: bool,
+    pub version: u32,
+    pub coinbase_tx_version: u32,
+    /// Start of the coinbase scriptSig, up to 255 bytes
+    pub coinbase_prefix: Vec<u8>,
+    pub coinbase_tx_input_sequence: u32,
+    /// Satoshis left for the receiver's own coinbase outputs
+    pub coinbase_tx_value_remaining: u64,
+    pub coinbase_tx_outputs_count: u32,
+    /// Consensus-encoded outputs the coinbase must include, up to 64KiB
+    pub coinbase_tx_outputs: Vec<u8>,
+    pub coinbase_tx_locktime: u32,
+    /// Up to 255 hashes, internal byte order
+    pub merkle_path: Vec<[u8; 32]>,
+}
+
+impl NewTemplate {
+    /// The coinbase this template asks for: its required outputs after one
+    /// paying out the remaining value, whose script the receiver fills in
+    pub fn coinbase_tx(&self) -> Result<bitcoin::Transaction> {
+        let mut outputs = &self.coinbase_tx_outputs[..];
+        let mut output = vec![bitcoin::TxOut {
+            value: self.coinbase_tx_value_remaining,
+            script_pubkey: bitcoin::ScriptBuf::new(),
+        }];
+        for index in 0..self.coinbase_tx_outputs_count {
+            let tx_out = bitcoin::TxOut::consensus_decode(&mut outputs).map_err(|e| {
+                Error::Protocol(format!("Invalid coinbase output {}: {}", index, e))
+            })?;
+            output.push(tx_out);
+        }
+        if !outputs.is_empty() {
+            return Err(Error::Protocol(format!(
+                "{} bytes after the last coinbase output", outputs.len()
+            )));
+        }
+
+        Ok(bitcoin::Transaction {
+            version: self.coinbase_tx_version as i32,
+            lock_time: bitcoin::absolute::LockTime::from_consensus(self.coinbase_tx_locktime),
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint::null(),
+                script_sig: bitcoin::ScriptBuf::from(self.coinbase_prefix.clone()),
+                sequence: bitcoin::Sequence(self.coinbase_tx_input_sequence),
+                witness: bitcoin::Witness::new(),
+            }],
+            output,
+        })
+    }
+
+    /// Work on top of `previous_hash`, which NewTemplate itself doesn't
+    /// carry. The ID is derived from `template_id`, so it stays the same if
+    /// the template is sent again.
+    pub fn to_work_template(&self, previous_hash: bitcoin::BlockHash) -> Result<WorkTemplate> {
+        let mut template = WorkTemplate::new(previous_hash, self.coinbase_tx()?, vec![], 1.0);
+        template.id = uuid::Uuid::from_u64_pair(0, self.template_id);
+        template.version = Some(self.version);
+        template.merkle_path = self.merkle_path.iter()
+            .map(|hash| bitcoin::TxMerkleNode::from_byte_array(*hash))
+            .collect();
+        Ok(template)
+    }
+}
+
+/// A message the codec reads and writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Sv2Message {
+    SetupConnection(SetupConnection),
+    OpenStandardMiningChannel(OpenStandardMiningChannel),
+    OpenStandardMiningChannelSuccess(OpenStandardMiningChannelSuccess),
+    NewMiningJob(NewMiningJob),
+    SubmitSharesStandard(SubmitSharesStandard),
+    SubmitSharesExtended(SubmitSharesExtended),
+    SetTarget(SetTarget),
+    NewTemplate(NewTemplate),
+}
+
+impl Sv2Message {
+    pub fn msg_type(&self) -> u8 {
+        match self {
+            Sv2Message::SetupConnection(_) => sv2_message_types::SETUP_CONNECTION,
+            Sv2Message::OpenStandardMiningChannel(_) => sv2_message_types::OPEN_STANDARD_MINING_CHANNEL,
+            Sv2Message::OpenStandardMiningChannelSuccess(_) => {
+                sv2_message_types::OPEN_STANDARD_MINING_CHANNEL_SUCCESS
+            }
+            Sv2Message::NewMiningJob(_) => sv2_message_types::NEW_MINING_JOB,
+            Sv2Message::SubmitSharesStandard(_) => sv2_message_types::SUBMIT_SHARES_STANDARD,
+            Sv2Message::SubmitSharesExtended(_) => sv2_message_types::SUBMIT_SHARES_EXTENDED,
+            Sv2Message::SetTarget(_) => sv2_message_types::SET_TARGET,
+            Sv2Message::NewTemplate(_) => sv2_message_types::NEW_TEMPLATE,
+        }
+    }
+
+    /// Whether SRI marks this message as addressed to a channel
+    pub fn is_channel_message(&self) -> bool {
+        matches!(
+            self,
+            Sv2Message::NewMiningJob(_)
+                | Sv2Message::SubmitSharesStandard(_)
+                | Sv2Message::SubmitSharesExtended(_)
+                | Sv2Message::SetTarget(_)
+        )
+    }
+
+    /// Encode as a complete frame, header included
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut payload = Writer::default();
+        match self {
+            Sv2Message::SetupConnection(m) => {
+                payload.u8(m.protocol);
+                payload.u16(m.min_version);
+                payload.u16(m.max_version);
+                payload.u32(m.flags);
+                payload.str0_255("endpoint_host", &m.endpoint_host)?;
+                payload.u16(m.endpoint_port);
+                payload.str0_255("vendor", &m.vendor)?;
+                payload.str0_255("hardware_version", &m.hardware_version)?;
+                payload.str0_255("firmware", &m.firmware)?;
+                payload.str0_255("device_id", &m.device_id)?;
+            }
+            Sv2Message::OpenStandardMiningChannel(m) => {
+                payload.u32(m.request_id);
+                payload.str0_255("user_identity", &m.user_identity)?;
+                payload.bytes(&m.nominal_hash_rate.to_le_bytes());
+                payload.bytes(&m.max_target);
+            }
+            Sv2Message::OpenStandardMiningChannelSuccess(m) => {
+                payload.u32(m.request_id);
+                payload.u32(m.channel_id);
+                payload.bytes(&m.target);
+                payload.b0_32("extranonce_prefix", &m.extranonce_prefix)?;
+                payload.u32(m.group_channel_id);
+            }
+            Sv2Message::NewMiningJob(m) => {
+                payload.u32(m.channel_id);
+                payload.u32(m.job_id);
+                match m.min_ntime {
+                    Some(min_ntime) => {
+                        payload.u8(1);
+                        payload.u32(min_ntime);
+                    }
+                    None => payload.u8(0),
+                }
+                payload.u32(m.version);
+                payload.b0_32("merkle_root", &m.merkle_root)?;
+            }
+            Sv2Message::SubmitSharesStandard(m) => {
+                for field in [m.channel_id, m.sequence_number, m.job_id, m.nonce, m.ntime, m.version] {
+                    payload.u32(field);
+                }
+            }
+            Sv2Message::SubmitSharesExtended(m) => {
+                for field in [m.channel_id, m.sequence_number, m.job_id, m.nonce, m.ntime, m.version] {
+                    payload.u32(field);
+                }
+                payload.b0_32("extranonce", &m.extranonce)?;
+            }
+            Sv2Message::SetTarget(m) => {
+                payload.u32(m.channel_id);
+                payload.bytes(&m.maximum_target);
+            }
+            Sv2Message::NewTemplate(m) => {
+                payload.u64(m.template_id);
+                payload.u8(m.future_template as u8);
+                payload.u32(m.version);
+                payload.u32(m.coinbase_tx_version);
+                payload.b0_255("coinbase_prefix", &m.coinbase_prefix)?;
+                payload.u32(m.coinbase_tx_input_sequence);
+                payload.u64(m.coinbase_tx_value_remaining);
+                payload.u32(m.coinbase_tx_outputs_count);
+                payload.b0_64k("coinbase_tx_outputs", &m.coinbase_tx_outputs)?;
+                payload.u32(m.coinbase_tx_locktime);
+                if m.merkle_path.len() > 255 {
+                    return Err(Error::Protocol(format!(
+                        "merkle_path has {} hashes, the limit is 255", m.merkle_path.len()
+                    )));
+                }
+                payload.u8(m.merkle_path.len() as u8);
+                for hash in &m.merkle_path {
+                    payload.bytes(hash);
+                }
+            }
+        }
+
+        let payload = payload.0;
+        if payload.len() > MAX_PAYLOAD_LEN {
+            return Err(Error::Protocol(format!("{} byte payload doesn't fit an SV2 frame", payload.len())));
+        }
+        let extension_type = if self.is_channel_message() { CHANNEL_MSG_BIT } else { 0 };
+        let mut frame = Vec::with_capacity(SV2_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&extension_type.to_le_bytes());
+        frame.push(self.msg_type());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes()[..3]);
+        frame.extend_from_slice(&payload);
+        Ok(frame)
+    }
+
+    /// Decode one complete frame. Errors carry the offset into `frame` where
+    /// decoding stopped.
+    pub fn decode(frame: &[u8]) -> std::result::Result<Self, ProtocolParseError> {
+        if frame.len() < SV2_HEADER_LEN {
+            return Err(ProtocolParseError::new(frame.len(), format!(
+                "Frame shorter than the {} byte header", SV2_HEADER_LEN
+            )));
+        }
+
+        let msg_type = frame[2];
+        let length = u32::from_le_bytes([frame[3], frame[4], frame[5], 0]) as usize;
+        if frame.len() - SV2_HEADER_LEN < length {
+            return Err(ProtocolParseError::new(frame.len(), format!(
+                "Payload truncated: header declares {} bytes, got {}", length, frame.len() - SV2_HEADER_LEN
+            )));
+        }
+
+        let mut r = Reader { frame, pos: SV2_HEADER_LEN, end: SV2_HEADER_LEN + length };
+        let message = match msg_type {
+            sv2_message_types::SETUP_CONNECTION => Sv2Message::SetupConnection(SetupConnection {
+                protocol: r.u8()?,
+                min_version: r.u16()?,
+                max_version: r.u16()?,
+                flags: r.u32()?,
+                endpoint_host: r.str0_255()?,
+                endpoint_port: r.u16()?,
+                vendor: r.str0_255()?,
+                hardware_version: r.str0_255()?,
+                firmware: r.str0_255()?,
+                device_id: r.str0_255()?,
+            }),
+            sv2_message_types::OPEN_STANDARD_MINING_CHANNEL => {
+                Sv2Message::OpenStandardMiningChannel(OpenStandardMiningChannel {
+                    request_id: r.u32()?,
+                    user_identity: r.str0_255()?,
+                    nominal_hash_rate: f32::from_le_bytes(r.array()?),
+                    max_target: r.array()?,
+                })
+            }
+            sv2_message_types::OPEN_STANDARD_MINING_CHANNEL_SUCCESS => {
+                Sv2Message::OpenStandardMiningChannelSuccess(OpenStandardMiningChannelSuccess {
+                    request_id: r.u32()?,
+                    channel_id: r.u32()?,
+                    target: r.array()?,
+                    extranonce_prefix: r.b0_32()?,
+                    group_channel_id: r.u32()?,
+                })
+            }
+            sv2_message_types::NEW_MINING_JOB => Sv2Message::NewMiningJob(NewMiningJob {
+                channel_id: r.u32()?,
+                job_id: r.u32()?,
+                min_ntime: r.option_u32()?,
+                version: r.u32()?,
+                merkle_root: r.b0_32()?,
+            }),
+            sv2_message_types::SUBMIT_SHARES_STANDARD => Sv2Message::SubmitSharesStandard(SubmitSharesStandard {
+                channel_id: r.u32()?,
+                sequence_number: r.u32()?,
+                job_id: r.u32()?,
+                nonce: r.u32()?,
+                ntime: r.u32()?,
+                version: r.u32()?,
+            }),
+            sv2_message_types::SUBMIT_SHARES_EXTENDED => Sv2Message::SubmitSharesExtended(SubmitSharesExtended {
+                channel_id: r.u32()?,
+                sequence_number: r.u32()?,
+                job_id: r.u32()?,
+                nonce: r.u32()?,
+                ntime: r.u32()?,
+                version: r.u32()?,
+                extranonce: r.b0_32()?,
+            }),
+            sv2_message_types::SET_TARGET => Sv2Message::SetTarget(SetTarget {
+                channel_id: r.u32()?,
+                maximum_target: r.array()?,
+            }),
+            sv2_message_types::NEW_TEMPLATE => Sv2Message::NewTemplate(NewTemplate {
+                template_id: r.u64()?,
+                future_template: r.bool()?,
+                version: r.u32()?,
+                coinbase_tx_version: r.u32()?,
+                coinbase_prefix: r.b0_255()?,
+                coinbase_tx_input_sequence: r.u32()?,
+                coinbase_tx_value_remaining: r.u64()?,
+                coinbase_tx_outputs_count: r.u32()?,
+                coinbase_tx_outputs: r.b0_64k()?,
+                coinbase_tx_locktime: r.u32()?,
+                merkle_path: r.seq0_255_u256()?,
+            }),
+            other => return Err(ProtocolParseError::new(2, format!("Unsupported message type 0x{:02x}", other))),
+        };
+
+        if r.pos != r.end {
+            return Err(ProtocolParseError::new(r.pos, format!(
+                "{} unexpected bytes after the last field", r.end - r.pos
+            )));
+        }
+        Ok(message)
+    }
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn prefixed(&mut self, field: &str, bytes: &[u8], max: usize) -> Result<()> {
+        if bytes.len() > max {
+            return Err(Error::Protocol(format!(
+                "{} is {} bytes, the limit is {}", field, bytes.len(), max
+            )));
+        }
+        self.u8(bytes.len() as u8);
+        self.bytes(bytes);
+        Ok(())
+    }
+
+    fn str0_255(&mut self, field: &str, value: &str) -> Result<()> {
+        self.prefixed(field, value.as_bytes(), 255)
+    }
+
+    fn b0_32(&mut self, field: &str, value: &[u8]) -> Result<()> {
+        self.prefixed(field, value, 32)
+    }
+
+    fn b0_255(&mut self, field: &str, value: &[u8]) -> Result<()> {
+        self.prefixed(field, value, 255)
+    }
+
+    fn b0_64k(&mut self, field: &str, value: &[u8]) -> Result<()> {
+        if value.len() > u16::MAX as usize {
+            return Err(Error::Protocol(format!(
+                "{} is {} bytes, the limit is {}", field, value.len(), u16::MAX
+            )));
+        }
+        self.u16(value.len() as u16);
+        self.bytes(value);
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    frame: &'a [u8],
+    pos: usize,
+    /// End of the payload the header declared
+    end: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> std::result::Result<&'a [u8], ProtocolParseError> {
+        if self.end - self.pos < len {
+            return Err(ProtocolParseError::new(self.pos, "Unexpected end of payload"));
+        }
+        let bytes = &self.frame[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> std::result::Result<[u8; N], ProtocolParseError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> std::result::Result<u8, ProtocolParseError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> std::result::Result<u16, ProtocolParseError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> std::result::Result<u32, ProtocolParseError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> std::result::Result<u64, ProtocolParseError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn bool(&mut self) -> std::result::Result<bool, ProtocolParseError> {
+        let at = self.pos;
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(ProtocolParseError::new(at, format!("Invalid BOOL {}", other))),
+        }
+    }
+
+    fn prefixed(&mut self, max: usize) -> std::result::Result<&'a [u8], ProtocolParseError> {
+        let at = self.pos;
+        let len = self.u8()? as usize;
+        if len > max {
+            return Err(ProtocolParseError::new(at, format!("Length {} is over the limit of {}", len, max)));
+        }
+        self.take(len)
+    }
+
+    fn str0_255(&mut self) -> std::result::Result<String, ProtocolParseError> {
+        let at = self.pos;
+        let bytes = self.prefixed(255)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ProtocolParseError::new(at, "String is not valid UTF-8"))
+    }
+
+    fn b0_32(&mut self) -> std::result::Result<Vec<u8>, ProtocolParseError> {
+        Ok(self.prefixed(32)?.to_vec())
+    }
+
+    fn b0_255(&mut self) -> std::result::Result<Vec<u8>, ProtocolParseError> {
+        Ok(self.prefixed(255)?.to_vec())
+    }
+
+    fn b0_64k(&mut self) -> std::result::Result<Vec<u8>, ProtocolParseError> {
+        let len = self.u16()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn seq0_255_u256(&mut self) -> std::result::Result<Vec<[u8; 32]>, ProtocolParseError> {
+        let count = self.u8()?;
+        (0..count).map(|_| self.array()).collect()
+    }
+
+    fn option_u32(&mut self) -> std::result::Result<Option<u32>, ProtocolParseError> {
+        let at = self.pos;
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u32()?)),
+            count => Err(ProtocolParseError::new(at, format!("Option holds {} items", count))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: Sv2Message) {
+        let frame = message.encode().unwrap();
+        assert_eq!(Sv2Message::decode(&frame).unwrap(), message);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(Sv2Message::SetupConnection(SetupConnection {
+            protocol: 0,
+            min_version: 2,
+            max_version: 2,
+            flags: 0b101,
+            endpoint_host: String::new(),
+            endpoint_port: 0,
+            vendor: "v".repeat(255),
+            hardware_version: "BM1370".to_string(),
+            firmware: "2.4.2".to_string(),
+            device_id: "bitaxe-gamma-ünïcode".to_string(),
+        }));
+        round_trip(Sv2Message::OpenStandardMiningChannel(OpenStandardMiningChannel {
+            request_id: u32::MAX,
+            user_identity: "worker.1".to_string(),
+            nominal_hash_rate: 1.2e12,
+            max_target: [0xff; 32],
+        }));
+        round_trip(Sv2Message::OpenStandardMiningChannelSuccess(OpenStandardMiningChannelSuccess {
+            request_id: 3,
+            channel_id: 9,
+            target: [0x42; 32],
+            extranonce_prefix: Vec::new(),
+            group_channel_id: 1,
+        }));
+        for min_ntime in [None, Some(0), Some(u32::MAX)] {
+            round_trip(Sv2Message::NewMiningJob(NewMiningJob {
+                channel_id: 9,
+                job_id: 1,
+                min_ntime,
+                version: 0x2000_0000,
+                merkle_root: vec![0xab; 32],
+            }));
+        }
+        round_trip(Sv2Message::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 9,
+            sequence_number: 12,
+            job_id: 1,
+            nonce: 0xdead_beef,
+            ntime: 0x66a0_b1c2,
+            version: 0x2000_0000,
+        }));
+        for extranonce in [Vec::new(), vec![0x5a; 32]] {
+            round_trip(Sv2Message::SubmitSharesExtended(SubmitSharesExtended {
+                channel_id: 9,
+                sequence_number: 13,
+                job_id: 1,
+                nonce: 0xdead_beef,
+                ntime: 0x66a0_b1c2,
+                version: 0x2000_0000,
+                extranonce,
+            }));
+        }
+        round_trip(Sv2Message::SetTarget(SetTarget { channel_id: 9, maximum_target: [0x42; 32] }));
+        for future_template in [false, true] {
+            round_trip(Sv2Message::NewTemplate(NewTemplate {
+                future_template,
+                merkle_path: vec![[0x11; 32]; 255],
+                ..new_template()
+            }));
+        }
+    }
+
+    fn new_template() -> NewTemplate {
+        let commitment = bitcoin::TxOut {
+            value: 0,
+            script_pubkey: bitcoin::ScriptBuf::from(vec![0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed]),
+        };
+        NewTemplate {
+            template_id: u64::MAX - 1,
+            future_template: false,
+            version: 0x2000_0000,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![0x03, 0x40, 0x0d, 0x03],
+            coinbase_tx_input_sequence: u32::MAX,
+            coinbase_tx_value_remaining: 312_500_000,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: bitcoin::consensus::encode::serialize(&commitment),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![[0x11; 32], [0x22; 32]],
+        }
+    }
+
+    #[test]
+    fn test_new_template_to_work_template() {
+        let message = new_template();
+        let template = message.to_work_template(bitcoin::BlockHash::from_byte_array([7; 32])).unwrap();
+        assert_eq!(template.id, uuid::Uuid::from_u64_pair(0, message.template_id));
+        assert_eq!(template.previous_hash, bitcoin::BlockHash::from_byte_array([7; 32]));
+        assert_eq!(template.version, Some(0x2000_0000));
+        assert_eq!(template.merkle_path[1], bitcoin::TxMerkleNode::from_byte_array([0x22; 32]));
+
+        let coinbase = &template.coinbase_tx;
+        assert_eq!(coinbase.version, 2);
+        assert_eq!(coinbase.input[0].script_sig.as_bytes(), &message.coinbase_prefix[..]);
+        assert_eq!(coinbase.input[0].sequence, bitcoin::Sequence::MAX);
+        assert_eq!(coinbase.output.len(), 2);
+        assert_eq!(coinbase.output[0].value, 312_500_000);
+        assert_eq!(bitcoin::consensus::encode::serialize(&coinbase.output[1]), message.coinbase_tx_outputs);
+
+        // Outputs that don't add up to the count
+        let short = NewTemplate { coinbase_tx_outputs_count: 2, ..new_template() };
+        assert!(short.coinbase_tx().unwrap_err().to_string().contains("coinbase output 1"));
+        let extra = NewTemplate { coinbase_tx_outputs_count: 0, ..new_template() };
+        assert!(extra.coinbase_tx().is_err());
+    }
+
+    #[test]
+    fn test_encode_rejects_oversized_fields() {
+        let job = NewMiningJob { channel_id: 1, job_id: 1, min_ntime: None, version: 0, merkle_root: vec![0; 33] };
+        let err = Sv2Message::NewMiningJob(job).encode().unwrap_err();
+        assert!(err.to_string().contains("merkle_root is 33 bytes"));
+    }
+
+    #[test]
+    fn test_decode_errors_report_offset() {
+        let job = Sv2Message::NewMiningJob(NewMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: Some(3),
+            version: 4,
+            merkle_root: vec![5; 32],
+        });
+        let frame = job.encode().unwrap();
+
+        // Header declares more than is there
+        assert_eq!(Sv2Message::decode(&frame[..20]).unwrap_err().offset, 20);
+
+        // Option count at the start of min_ntime
+        let mut bad_option = frame.clone();
+        bad_option[SV2_HEADER_LEN + 8] = 2;
+        assert_eq!(Sv2Message::decode(&bad_option).unwrap_err().offset, SV2_HEADER_LEN + 8);
+
+        // Payload longer than its fields
+        let mut padded = frame.clone();
+        padded.push(0);
+        padded[3] += 1;
+        let err = Sv2Message::decode(&padded).unwrap_err();
+        assert_eq!(err.offset, frame.len());
+        assert!(err.reason.contains("1 unexpected bytes"));
+    }
+
+    #[test]
+    fn test_submit_shares_extended_extranonce_bounds() {
+        let share = SubmitSharesExtended {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: 5,
+            version: 6,
+            extranonce: vec![7; 8],
+        };
+        let frame = Sv2Message::SubmitSharesExtended(share.clone()).encode().unwrap();
+        let prefix_at = SV2_HEADER_LEN + 24;
+        assert_eq!(frame[prefix_at], 8);
+
+        // Extranonce cut short, with the header length agreeing
+        let mut truncated = frame[..frame.len() - 3].to_vec();
+        truncated[3] -= 3;
+        let err = Sv2Message::decode(&truncated).unwrap_err();
+        assert_eq!(err.offset, prefix_at + 1);
+        assert!(err.reason.contains("Unexpected end of payload"));
+
+        // Length prefix over B0_32's limit
+        let mut oversized = frame.clone();
+        oversized[prefix_at] = 33;
+        let err = Sv2Message::decode(&oversized).unwrap_err();
+        assert_eq!(err.offset, prefix_at);
+        assert!(err.reason.contains("Length 33 is over the limit of 32"));
+
+        let err = Sv2Message::SubmitSharesExtended(SubmitSharesExtended { extranonce: vec![0; 33], ..share })
+            .encode()
+            .unwrap_err();
+        assert!(err.to_string().contains("extranonce is 33 bytes"));
+    }
+}
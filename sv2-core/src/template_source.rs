@@ -0,0 +1,476 @@
+//! Where mining work comes from
+//!
+//! A [`TemplateSource`] hands out [`WorkTemplate`]s independently of the mode
+//! consuming them. Solo mode picks its source from `[mode.config.template_source]`:
+//! raw `getblocktemplate` over Bitcoin RPC (the default) or an SV2 Template
+//! Provider. Client mode publishes the work it receives from its upstream pool
+//! through an [`UpstreamTemplateSource`].
+
+use crate::bitcoin_rpc::BitcoinRpcClient;
+use crate::{Error, Result, WorkTemplate};
+use async_trait::async_trait;
+use bitcoin::hashes::Hash;
+use bitcoin::BlockHash;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::sync::{watch, Mutex};
+
+/// A provider of block templates
+#[async_trait]
+pub trait TemplateSource: Send + Sync {
+    /// Short name for logs and status output
+    fn name(&self) -> &'static str;
+
+    /// The best template available right now
+    async fn fetch_template(&self) -> Result<WorkTemplate>;
+
+    /// Wait for a template that builds on a different chain tip than `current`
+    /// (or for any template when `current` is `None`)
+    async fn await_new_template(&self, current: Option<&WorkTemplate>) -> Result<WorkTemplate>;
+}
+
+/// `template_source` configuration for modes that build their own work
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TemplateSourceConfig {
+    /// `getblocktemplate` against the configured Bitcoin node
+    #[default]
+    BitcoinRpc,
+    /// An SV2 Template Provider (sv2-tp) speaking Template Distribution
+    Sv2Tp {
+        /// `host:port` of the template provider
+        address: String,
+    },
+    /// Work pushed by an upstream pool connection (client mode only)
+    Upstream,
+}
+
+impl TemplateSourceConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let TemplateSourceConfig::Sv2Tp { address } = self {
+            let valid = address
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+            if !valid {
+                return Err(Error::Config(format!(
+                    "template_source address '{}' must be host:port", address
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Build the template source a mode asked for
+///
+/// `poll_interval` is how often pull-based sources check for a new chain tip.
+pub fn build_template_source(
+    config: &TemplateSourceConfig,
+    bitcoin_client: &BitcoinRpcClient,
+    coinbase_address: &str,
+    poll_interval: Duration,
+) -> Result<Arc<dyn TemplateSource>> {
+    config.validate()?;
+    match config {
+        TemplateSourceConfig::BitcoinRpc => Ok(Arc::new(BitcoinRpcTemplateSource::new(
+            bitcoin_client.clone(),
+            coinbase_address.to_string(),
+            poll_interval,
+        ))),
+        TemplateSourceConfig::Sv2Tp { address } => Ok(Arc::new(Sv2TpTemplateSource::new(address.clone()))),
+        TemplateSourceConfig::Upstream => Err(Error::Config(
+            "template_source 'upstream' needs an upstream pool connection and is only available in client mode"
+                .to_string(),
+        )),
+    }
+}
+
+/// Templates from `getblocktemplate`, polled for new chain tips
+pub struct BitcoinRpcTemplateSource {
+    client: BitcoinRpcClient,
+    coinbase_address: String,
+    poll_interval: Duration,
+}
+
+impl BitcoinRpcTemplateSource {
+    pub fn new(client: BitcoinRpcClient, coinbase_address: String, poll_interval: Duration) -> Self {
+        Self { client, coinbase_address, poll_interval }
+    }
+}
+
+#[async_trait]
+impl TemplateSource for BitcoinRpcTemplateSource {
+    fn name(&self) -> &'static str {
+        "bitcoin_rpc"
+    }
+
+    async fn fetch_template(&self) -> Result<WorkTemplate> {
+        self.client.generate_work_template(&self.coinbase_address).await
+    }
+
+    async fn await_new_template(&self, current: Option<&WorkTemplate>) -> Result<WorkTemplate> {
+        loop {
+            let template = self.fetch_template().await?;
+            if current.map_or(true, |c| c.previous_hash != template.previous_hash) {
+                return Ok(template);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// Template Distribution message types handled by [`Sv2TpTemplateSource`]
+pub mod tdp_message_types {
+    pub const NEW_TEMPLATE: u8 = 0x71;
+    pub const SET_NEW_PREV_HASH: u8 = 0x72;
+}
+
+/// Templates pushed by an SV2 Template Provider
+///
+/// Reads unencrypted Template Distribution frames: `SetNewPrevHash` moves to
+/// a new chain tip and `NewTemplate` refreshes work on the current one. The
+/// connection is opened on first use and re-opened after errors.
+pub struct Sv2TpTemplateSource {
+    address: String,
+    state: Mutex<Sv2TpState>,
+}
+
+#[derive(Default)]
+struct Sv2TpState {
+    stream: Option<TcpStream>,
+    prev_hash: Option<(BlockHash, f64)>,
+    latest: Option<WorkTemplate>,
+}
+
+impl Sv2TpTemplateSource {
+    pub fn new(address: String) -> Self {
+        Self { address, state: Mutex::new(Sv2TpState::default()) }
+    }
+
+    /// Read frames until the provider announces new work
+    async fn next_template(&self, state: &mut Sv2TpState) -> Result<WorkTemplate> {
+        if state.stream.is_none() {
+            let stream = tokio::time::timeout(Duration::from_secs(10), TcpStream::connect(&self.address))
+                .await
+                .map_err(|_| Error::Connection(format!("Timed out connecting to sv2-tp at {}", self.address)))?
+                .map_err(|e| Error::Connection(format!("Failed to connect to sv2-tp at {}: {}", self.address, e)))?;
+            state.stream = Some(stream);
+        }
+
+        loop {
+            let frame = match read_frame(state.stream.as_mut().expect("connected above")).await {
+                Ok(frame) => frame,
+                Err(e) => {
+                    state.stream = None;
+                    return Err(e);
+                }
+            };
+
+            match frame {
+                (tdp_message_types::SET_NEW_PREV_HASH, payload) => {
+                    let (prev_hash, difficulty) = parse_set_new_prev_hash(&payload)?;
+                    state.prev_hash = Some((prev_hash, difficulty));
+                    return Ok(template_for(prev_hash, difficulty));
+                }
+                // Non-future templates refresh work on the current tip
+                (tdp_message_types::NEW_TEMPLATE, payload) if payload.get(8) == Some(&0) => {
+                    if let Some((prev_hash, difficulty)) = state.prev_hash {
+                        return Ok(template_for(prev_hash, difficulty));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TemplateSource for Sv2TpTemplateSource {
+    fn name(&self) -> &'static str {
+        "sv2_tp"
+    }
+
+    async fn fetch_template(&self) -> Result<WorkTemplate> {
+        let mut state = self.state.lock().await;
+        if let Some(template) = state.latest.as_ref().filter(|t| !t.is_expired()) {
+            return Ok(template.clone());
+        }
+        let template = self.next_template(&mut state).await?;
+        state.latest = Some(template.clone());
+        Ok(template)
+    }
+
+    async fn await_new_template(&self, current: Option<&WorkTemplate>) -> Result<WorkTemplate> {
+        let mut state = self.state.lock().await;
+        loop {
+            let template = self.next_template(&mut state).await?;
+            state.latest = Some(template.clone());
+            if current.map_or(true, |c| c.previous_hash != template.previous_hash) {
+                return Ok(template);
+            }
+        }
+    }
+}
+
+/// Read one SV2 frame, returning its message type and payload
+async fn read_frame(stream: &mut TcpStream) -> Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; crate::protocol::SV2_HEADER_LEN];
+    stream.read_exact(&mut header).await
+        .map_err(|e| Error::Connection(format!("sv2-tp connection lost: {}", e)))?;
+    let length = u32::from_le_bytes([header[3], header[4], header[5], 0]) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await
+        .map_err(|e| Error::Connection(format!("sv2-tp connection lost: {}", e)))?;
+    Ok((header[2], payload))
+}
+
+/// `SetNewPrevHash`: template_id (u64), prev_hash (32), header_timestamp (u32),
+/// n_bits (u32), target (32)
+fn parse_set_new_prev_hash(payload: &[u8]) -> Result<(BlockHash, f64)> {
+    if payload.len() < 48 {
+        return Err(Error::Protocol(format!(
+            "SetNewPrevHash payload too short: {} bytes", payload.len()
+        )));
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&payload[8..40]);
+    let n_bits = u32::from_le_bytes([payload[44], payload[45], payload[46], payload[47]]);
+    Ok((BlockHash::from_byte_array(hash), difficulty_from_bits(n_bits)))
+}
+
+/// Network difficulty for a compact target, relative to the difficulty-1 target
+pub fn difficulty_from_bits(n_bits: u32) -> f64 {
+    let exponent = (n_bits >> 24) as i32;
+    let mantissa = (n_bits & 0x00ff_ffff) as f64;
+    if mantissa == 0.0 {
+        return 0.0;
+    }
+    (0xffff as f64 / mantissa) * 256f64.powi(0x1d - exponent)
+}
+
+fn template_for(prev_hash: BlockHash, difficulty: f64) -> WorkTemplate {
+    use bitcoin::{Transaction, TxIn, TxOut};
+
+    // The provider builds the real coinbase; miners only need the tip and target here
+    let coinbase_tx = Transaction {
+        version: 1,
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![TxIn::default()],
+        output: vec![TxOut::default()],
+    };
+    WorkTemplate::new(prev_hash, coinbase_tx, vec![], difficulty)
+}
+
+/// Templates received from an upstream pool
+///
+/// The upstream connection publishes each new template through the sender
+/// returned by [`UpstreamTemplateSource::channel`].
+#[derive(Clone)]
+pub struct UpstreamTemplateSource {
+    receiver: watch::Receiver<Option<WorkTemplate>>,
+}
+
+impl UpstreamTemplateSource {
+    pub fn channel() -> (watch::Sender<Option<WorkTemplate>>, Self) {
+        let (sender, receiver) = watch::channel(None);
+        (sender, Self { receiver })
+    }
+}
+
+#[async_trait]
+impl TemplateSource for UpstreamTemplateSource {
+    fn name(&self) -> &'static str {
+        "upstream"
+    }
+
+    async fn fetch_template(&self) -> Result<WorkTemplate> {
+        self.receiver.borrow().clone()
+            .ok_or_else(|| Error::Protocol("No work template received from upstream yet".to_string()))
+    }
+
+    async fn await_new_template(&self, current: Option<&WorkTemplate>) -> Result<WorkTemplate> {
+        let mut receiver = self.receiver.clone();
+        let template = receiver
+            .wait_for(|t| match (t, current) {
+                (Some(t), Some(c)) => t.previous_hash != c.previous_hash,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .await
+            .map_err(|_| Error::Connection("Upstream connection closed".to_string()))?;
+        Ok(template.clone().expect("wait_for only returns Some"))
+    }
+}
+
+/// Scripted template source for tests
+#[cfg(any(test, feature = "test-utils"))]
+pub struct MockTemplateSource {
+    templates: Mutex<std::collections::VecDeque<WorkTemplate>>,
+    current: Mutex<Option<WorkTemplate>>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockTemplateSource {
+    /// Serve `templates` in order; the last one keeps being returned
+    pub fn new(templates: Vec<WorkTemplate>) -> Self {
+        Self {
+            templates: Mutex::new(templates.into()),
+            current: Mutex::new(None),
+        }
+    }
+
+    /// A template building on `prev_hash`
+    pub fn template(prev_hash: [u8; 32], difficulty: f64) -> WorkTemplate {
+        template_for(BlockHash::from_byte_array(prev_hash), difficulty)
+    }
+
+    async fn advance(&self) -> Option<WorkTemplate> {
+        let next = self.templates.lock().await.pop_front();
+        let mut current = self.current.lock().await;
+        if next.is_some() {
+            *current = next;
+        }
+        current.clone()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+#[async_trait]
+impl TemplateSource for MockTemplateSource {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn fetch_template(&self) -> Result<WorkTemplate> {
+        let current = self.current.lock().await.clone();
+        match current {
+            Some(template) => Ok(template),
+            None => self.advance().await
+                .ok_or_else(|| Error::Protocol("Mock template source is empty".to_string())),
+        }
+    }
+
+    async fn await_new_template(&self, current: Option<&WorkTemplate>) -> Result<WorkTemplate> {
+        loop {
+            let remaining = self.templates.lock().await.len();
+            let template = self.advance().await
+                .ok_or_else(|| Error::Protocol("Mock template source is empty".to_string()))?;
+            if current.map_or(true, |c| c.previous_hash != template.previous_hash) {
+                return Ok(template);
+            }
+            if remaining == 0 {
+                return Err(Error::Protocol("Mock template source has no new tip".to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BitcoinConfig;
+    use tokio::io::AsyncWriteExt;
+
+    fn bitcoin_client() -> BitcoinRpcClient {
+        BitcoinRpcClient::new(BitcoinConfig::default())
+    }
+
+    #[test]
+    fn test_config_defaults_to_bitcoin_rpc() {
+        assert_eq!(TemplateSourceConfig::default(), TemplateSourceConfig::BitcoinRpc);
+
+        let config: TemplateSourceConfig =
+            toml::from_str("type = \"sv2_tp\"\naddress = \"127.0.0.1:8442\"\n").unwrap();
+        assert_eq!(config, TemplateSourceConfig::Sv2Tp { address: "127.0.0.1:8442".to_string() });
+    }
+
+    #[test]
+    fn test_build_selects_source_by_config() {
+        let client = bitcoin_client();
+        let interval = Duration::from_secs(1);
+
+        let source = build_template_source(&TemplateSourceConfig::BitcoinRpc, &client, "bcrt1q", interval).unwrap();
+        assert_eq!(source.name(), "bitcoin_rpc");
+
+        let sv2_tp = TemplateSourceConfig::Sv2Tp { address: "127.0.0.1:8442".to_string() };
+        let source = build_template_source(&sv2_tp, &client, "bcrt1q", interval).unwrap();
+        assert_eq!(source.name(), "sv2_tp");
+
+        assert!(build_template_source(&TemplateSourceConfig::Upstream, &client, "bcrt1q", interval).is_err());
+        let bad = TemplateSourceConfig::Sv2Tp { address: "localhost".to_string() };
+        assert!(build_template_source(&bad, &client, "bcrt1q", interval).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_source_new_tip() {
+        let first = MockTemplateSource::template([1; 32], 1.0);
+        let same_tip = MockTemplateSource::template([1; 32], 1.0);
+        let next_tip = MockTemplateSource::template([2; 32], 2.0);
+        let source = MockTemplateSource::new(vec![first.clone(), same_tip, next_tip.clone()]);
+
+        assert_eq!(source.fetch_template().await.unwrap().id, first.id);
+        // Skips the refresh on the same tip
+        let new = source.await_new_template(Some(&first)).await.unwrap();
+        assert_eq!(new.id, next_tip.id);
+        assert_eq!(source.fetch_template().await.unwrap().id, next_tip.id);
+    }
+
+    #[tokio::test]
+    async fn test_upstream_source() {
+        let (sender, source) = UpstreamTemplateSource::channel();
+        assert!(source.fetch_template().await.is_err());
+
+        let template = MockTemplateSource::template([3; 32], 4.0);
+        let waiter = {
+            let source = source.clone();
+            tokio::spawn(async move { source.await_new_template(None).await })
+        };
+        sender.send_replace(Some(template.clone()));
+
+        assert_eq!(waiter.await.unwrap().unwrap().id, template.id);
+        assert_eq!(source.fetch_template().await.unwrap().id, template.id);
+    }
+
+    fn frame(msg_type: u8, payload: &[u8]) -> Vec<u8> {
+        let len = (payload.len() as u32).to_le_bytes();
+        let mut frame = vec![0x00, 0x00, msg_type, len[0], len[1], len[2]];
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[tokio::test]
+    async fn test_sv2_tp_source_reads_new_prev_hash() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut payload = Vec::new();
+            payload.extend_from_slice(&7u64.to_le_bytes()); // template_id
+            payload.extend_from_slice(&[0xab; 32]); // prev_hash
+            payload.extend_from_slice(&1_700_000_000u32.to_le_bytes()); // header_timestamp
+            payload.extend_from_slice(&0x1d00ffffu32.to_le_bytes()); // n_bits
+            payload.extend_from_slice(&[0xff; 32]); // target
+            socket.write_all(&frame(tdp_message_types::SET_NEW_PREV_HASH, &payload)).await.unwrap();
+            // Keep the connection open until the client is done
+            let _ = socket.read(&mut [0u8; 1]).await;
+        });
+
+        let source = Sv2TpTemplateSource::new(address);
+        let template = source.fetch_template().await.unwrap();
+        assert_eq!(template.previous_hash, BlockHash::from_byte_array([0xab; 32]));
+        assert!((template.difficulty - 1.0).abs() < 1e-9);
+        // Cached until expiry
+        assert_eq!(source.fetch_template().await.unwrap().id, template.id);
+    }
+
+    #[test]
+    fn test_difficulty_from_bits() {
+        assert!((difficulty_from_bits(0x1d00ffff) - 1.0).abs() < 1e-9);
+        assert!(difficulty_from_bits(0x1b0404cb) > 16_000.0);
+        assert_eq!(difficulty_from_bits(0x1d000000), 0.0);
+    }
+}
@@ -3,13 +3,15 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use bitcoin::{BlockHash, Transaction};
+use bitcoin::{BlockHash, Transaction, TxMerkleNode};
 use std::time::Duration;
+use crate::connection_quality::ConnectionQuality;
 
 /// Type alias for connection IDs
 pub type ConnectionId = Uuid;
 
 /// Mining protocol type
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Protocol {
     Sv1,
@@ -18,7 +20,15 @@ pub enum Protocol {
     StratumV2,
 }
 
+impl Protocol {
+    /// `Sv2` and `StratumV2` name the same protocol
+    pub fn is_sv2(self) -> bool {
+        matches!(self, Protocol::Sv2 | Protocol::StratumV2)
+    }
+}
+
 /// Connection state
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectionState {
     Connected,
@@ -29,21 +39,48 @@ pub enum ConnectionState {
 }
 
 /// Connection information
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionInfo {
     pub id: Uuid,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub address: SocketAddr,
     pub protocol: Protocol,
     pub state: ConnectionState,
     pub connected_at: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    /// Protocol version negotiated during the handshake
+    pub version: Option<String>,
+    /// Feature flags agreed during the handshake (SV2 `SetupConnection.flags`)
+    #[serde(default)]
+    pub flags: Option<u32>,
     pub subscribed_difficulty: Option<f64>,
+    pub extranonce1: Option<String>,
+    pub extranonce2_size: Option<u8>,
     pub authorized_workers: Vec<String>,
     pub total_shares: u64,
     pub valid_shares: u64,
+    pub invalid_shares: u64,
+    pub blocks_found: u64,
+    /// Filled in by the API from recent history; see [`crate::connection_quality`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<ConnectionQuality>,
+}
+
+/// Connection details that are only known once the handshake completes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HandshakeDetails {
+    pub user_agent: Option<String>,
+    pub version: Option<String>,
+    pub flags: Option<u32>,
+    pub extranonce1: Option<String>,
+    pub extranonce2_size: Option<u8>,
 }
 
 impl ConnectionInfo {
+    /// Snapshot of a freshly accepted connection; handshake details are
+    /// filled in later by [`ConnectionInfo::apply_handshake`]
     pub fn from_connection(conn: &Connection) -> Self {
         Self {
             id: conn.id,
@@ -52,23 +89,58 @@ impl ConnectionInfo {
             state: conn.state,
             connected_at: conn.connected_at,
             last_activity: conn.last_activity,
+            user_agent: None,
+            version: None,
+            flags: None,
             subscribed_difficulty: None,
+            extranonce1: None,
+            extranonce2_size: None,
             authorized_workers: Vec::new(),
             total_shares: 0,
             valid_shares: 0,
+            invalid_shares: 0,
+            blocks_found: 0,
+            quality: None,
+        }
+    }
+
+    /// Merge post-handshake details, keeping anything already known that
+    /// the handshake didn't report
+    pub fn apply_handshake(&mut self, details: &HandshakeDetails) {
+        if let Some(user_agent) = &details.user_agent {
+            self.user_agent = Some(user_agent.clone());
+        }
+        if let Some(version) = &details.version {
+            self.version = Some(version.clone());
+        }
+        if let Some(flags) = details.flags {
+            self.flags = Some(flags);
         }
+        if let Some(extranonce1) = &details.extranonce1 {
+            self.extranonce1 = Some(extranonce1.clone());
+        }
+        if let Some(extranonce2_size) = details.extranonce2_size {
+            self.extranonce2_size = Some(extranonce2_size);
+        }
+        self.last_activity = Utc::now();
     }
 
-    pub fn add_share(&mut self, is_valid: bool) {
+    pub fn add_share(&mut self, is_valid: bool, is_block: bool) {
         self.total_shares += 1;
         if is_valid {
             self.valid_shares += 1;
+        } else {
+            self.invalid_shares += 1;
         }
+        if is_block {
+            self.blocks_found += 1;
+        }
+        self.last_activity = Utc::now();
     }
 
-    pub fn is_stale(&self) -> bool {
+    pub fn is_stale(&self, timeout_secs: u64) -> bool {
         let now = Utc::now();
-        (now - self.last_activity).num_seconds() > 300 // 5 minutes
+        (now - self.last_activity).num_seconds() > timeout_secs as i64
     }
 }
 
@@ -81,6 +153,42 @@ pub struct Connection {
     pub state: ConnectionState,
     pub connected_at: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
+    /// Set once the first frame negotiates `protocol`; later frames must match
+    pub protocol_pinned: bool,
+}
+
+impl Connection {
+    pub fn new(address: SocketAddr, protocol: Protocol) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            address,
+            protocol,
+            state: ConnectionState::Connected,
+            connected_at: now,
+            last_activity: now,
+            protocol_pinned: false,
+        }
+    }
+
+    /// Pin the protocol on the first detected frame and reject frames of the
+    /// other protocol afterwards. Re-detecting mid-session would let a peer
+    /// switch parsers under the mode handler, so a switch puts the connection
+    /// in the `Error` state for the caller to disconnect.
+    pub fn pin_protocol(&mut self, detected: Protocol) -> Result<()> {
+        if !self.protocol_pinned {
+            self.protocol = detected;
+            self.protocol_pinned = true;
+        } else if detected.is_sv2() != self.protocol.is_sv2() {
+            self.state = ConnectionState::Error;
+            return Err(Error::Protocol(format!(
+                "connection {} negotiated {:?} but sent a {:?} frame",
+                self.id, self.protocol, detected
+            )));
+        }
+        self.last_activity = Utc::now();
+        Ok(())
+    }
 }
 
 /// Worker information
@@ -127,6 +235,56 @@ impl Worker {
     }
 }
 
+/// Persistent worker identity, keyed by authorized worker name and source IP,
+/// so cumulative statistics survive reconnects
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerRecord {
+    pub id: Uuid,
+    pub worker_name: String,
+    pub source_ip: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub sessions: u64,
+    pub total_shares: u64,
+    pub valid_shares: u64,
+    pub blocks_found: u64,
+}
+
+impl WorkerRecord {
+    pub fn new(worker_name: String, source_ip: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            worker_name,
+            source_ip,
+            first_seen: now,
+            last_seen: now,
+            sessions: 1,
+            total_shares: 0,
+            valid_shares: 0,
+            blocks_found: 0,
+        }
+    }
+
+    /// Mark the start of another session for this worker
+    pub fn reconnect(&mut self) {
+        self.sessions += 1;
+        self.last_seen = Utc::now();
+    }
+
+    pub fn add_share(&mut self, is_valid: bool, is_block: bool) {
+        self.total_shares += 1;
+        if is_valid {
+            self.valid_shares += 1;
+        }
+        if is_block {
+            self.blocks_found += 1;
+        }
+        self.last_seen = Utc::now();
+    }
+}
+
 /// Mining job
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Job {
@@ -187,6 +345,7 @@ impl ShareSubmission {
 }
 
 /// Share data
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Share {
     pub connection_id: Uuid,
@@ -194,6 +353,7 @@ pub struct Share {
     pub timestamp: u32,
     pub difficulty: f64,
     pub is_valid: bool,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
     pub block_hash: Option<BlockHash>,
     pub submitted_at: DateTime<Utc>,
 }
@@ -228,6 +388,77 @@ impl Share {
 
         Ok(())
     }
+
+    /// Whether the share's ntime is earlier than the job allows
+    pub fn is_stale(&self, bounds: &NtimeBounds) -> bool {
+        self.timestamp < bounds.earliest()
+    }
+
+    /// Reject an ntime that would make the block invalid, which usually means
+    /// the miner's clock is off
+    pub fn check_ntime(&self, bounds: &NtimeBounds) -> Result<()> {
+        match bounds.violation(self.timestamp) {
+            Some(reason) => Err(Error::InvalidShare(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Range a share's ntime must fall in for its block to be valid
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NtimeBounds {
+    /// Earliest ntime the node accepts for the job (`mintime`)
+    pub min_time: u32,
+    /// Latest ntime before the block is too far in the future
+    pub max_time: u32,
+    /// Median time past of the chain tip; ntime must be strictly later
+    pub median_time_past: Option<u32>,
+}
+
+impl NtimeBounds {
+    /// Nodes reject blocks more than two hours ahead of their clock
+    pub const MAX_FUTURE_SECS: u32 = 7200;
+
+    /// Bounds for a template with `mintime` and `curtime`
+    pub fn new(min_time: u32, curtime: u32) -> Self {
+        Self {
+            min_time,
+            max_time: curtime.saturating_add(Self::MAX_FUTURE_SECS),
+            median_time_past: None,
+        }
+    }
+
+    pub fn with_median_time_past(mut self, median_time_past: u32) -> Self {
+        self.median_time_past = Some(median_time_past);
+        self
+    }
+
+    /// Earliest valid ntime
+    pub fn earliest(&self) -> u32 {
+        match self.median_time_past {
+            Some(mtp) => self.min_time.max(mtp.saturating_add(1)),
+            None => self.min_time,
+        }
+    }
+
+    /// Why `ntime` is out of bounds, if it is
+    pub fn violation(&self, ntime: u32) -> Option<String> {
+        let earliest = self.earliest();
+        if ntime < earliest {
+            Some(format!(
+                "ntime {} is {}s before the earliest allowed {}",
+                ntime, earliest - ntime, earliest
+            ))
+        } else if ntime > self.max_time {
+            Some(format!(
+                "ntime {} is {}s after the latest allowed {}",
+                ntime, ntime - self.max_time, self.max_time
+            ))
+        } else {
+            None
+        }
+    }
 }
 
 /// Share validation result
@@ -242,15 +473,33 @@ pub enum ShareResult {
 }
 
 /// Work template for mining
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkTemplate {
     pub id: Uuid,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub previous_hash: BlockHash,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
     pub coinbase_tx: Transaction,
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<Object>))]
     pub transactions: Vec<Transaction>,
     pub difficulty: f64,
     pub timestamp: u32,
     pub expires_at: DateTime<Utc>,
+    /// ntime limits reported by the node, when the template came from one
+    #[serde(default)]
+    pub ntime_bounds: Option<NtimeBounds>,
+    /// Total fees the block's transactions pay, in satoshis, when the source reported them
+    #[serde(default)]
+    pub fees: Option<u64>,
+    /// Block version the source asked for, when it set one
+    #[serde(default)]
+    pub version: Option<u32>,
+    /// Merkle path from the coinbase to the root, when the source sent that
+    /// instead of `transactions`
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
+    pub merkle_path: Vec<TxMerkleNode>,
 }
 
 impl WorkTemplate {
@@ -268,25 +517,51 @@ impl WorkTemplate {
             difficulty,
             timestamp: Utc::now().timestamp() as u32,
             expires_at: Utc::now() + chrono::Duration::seconds(300), // 5 minutes
+            ntime_bounds: None,
+            fees: None,
+            version: None,
+            merkle_path: Vec::new(),
         }
     }
 
     pub fn is_expired(&self) -> bool {
         Utc::now() > self.expires_at
     }
+
+    /// ntime limits for shares on this template. Without node-reported
+    /// bounds, ntime may only roll forward from the template's time.
+    pub fn ntime_bounds(&self) -> NtimeBounds {
+        self.ntime_bounds
+            .unwrap_or_else(|| NtimeBounds::new(self.timestamp, self.timestamp))
+    }
 }
 
 /// Mining statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MiningStats {
     pub hashrate: f64,
     pub shares_per_minute: f64,
     pub acceptance_rate: f64,
     pub efficiency: f64,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
     pub uptime: Duration,
     pub shares_accepted: u64,
     pub shares_rejected: u64,
     pub blocks_found: u64,
+    /// Found blocks a chain reorg later dropped; not counted in `blocks_found`
+    #[serde(default)]
+    pub blocks_orphaned: u64,
+    /// Cumulative probability-weighted blocks from accepted shares since start,
+    /// `None` when the network difficulty couldn't be fetched
+    #[serde(default)]
+    pub expected_blocks: Option<f64>,
+    /// Tag the next coinbase will carry, for modes that build their own coinbase
+    #[serde(default)]
+    pub coinbase_tag: Option<String>,
+    /// Network difficulty the daemon last saw, for network-relative display
+    #[serde(default)]
+    pub network_difficulty: Option<f64>,
 }
 
 /// Pool statistics
@@ -305,6 +580,7 @@ pub struct PoolStats {
 }
 
 /// Performance metrics
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceMetrics {
     pub cpu_usage: f64,
@@ -320,6 +596,7 @@ pub struct PerformanceMetrics {
 }
 
 /// Alert information
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
     pub id: Uuid,
@@ -330,6 +607,7 @@ pub struct Alert {
 }
 
 /// Alert severity levels
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AlertSeverity {
     Info,
@@ -342,23 +620,42 @@ pub enum AlertSeverity {
 pub type AlertLevel = AlertSeverity;
 
 /// Daemon status
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DaemonStatus {
     pub running: bool,
     pub mode: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
     pub uptime: Duration,
     pub connections: u64,
     pub hashrate: f64,
+    /// The running mode's own view of its health, from `ModeHandler::health`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub mode_health: Option<crate::health::HealthCheck>,
 }
 
 /// Upstream pool status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpstreamStatus {
-    pub connected: bool,
+    /// The active upstream, or the one being tried next while disconnected
     pub url: String,
-    pub last_update: DateTime<Utc>,
+    /// Priority of `url` in the client's failover order
+    pub priority: u32,
+    pub connected: bool,
     pub last_connected: Option<DateTime<Utc>>,
-    pub hashrate: f64,
+    /// Failed attempts since the last successful connect
+    pub connection_attempts: u64,
+    pub last_error: Option<String>,
+    /// Wait before the next reconnect attempt, while backing off
+    #[serde(default)]
+    pub reconnect_backoff: Option<Duration>,
+    pub latency: Option<Duration>,
+    pub shares_submitted: u64,
+    pub shares_accepted: u64,
+    pub shares_rejected: u64,
+    /// Times the client switched to another upstream after losing one
+    pub failovers: u64,
 }
 
 /// Block template for mining
@@ -0,0 +1,343 @@
+//! Outgoing webhooks
+//!
+//! With `[webhooks]` enabled, sv2d POSTs a JSON event to `url` when a block is
+//! found or later orphaned by a reorg and, if `milestones` is set, when a
+//! setup milestone is reached. When
+//! a `secret` is configured each request carries an
+//! `X-Sv2d-Signature: sha256=<hex>` header, the HMAC-SHA256 of the body, so
+//! the receiver can check it came from this daemon.
+//!
+//! The notifier listens on the [`EventBus`]. Events are queued and delivered
+//! by a background task with retries and exponential backoff. The share path
+//! only enqueues: a full queue drops the event with a warning rather than
+//! wait on a slow receiver.
+
+use crate::events::{Event, EventBus};
+use crate::milestones::MilestoneRecord;
+use crate::{ConnectionId, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Header carrying the body signature
+pub const SIGNATURE_HEADER: &str = "X-Sv2d-Signature";
+
+/// `[webhooks]` configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    /// Receiver URL events are POSTed to
+    pub url: String,
+    /// Key for the `X-Sv2d-Signature` HMAC; unsigned when unset
+    pub secret: Option<String>,
+    /// Send `block_found` and `block_orphaned` events
+    pub block_found: bool,
+    /// Send `milestone` events (first share, first block)
+    pub milestones: bool,
+    /// Retries after a failed delivery
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further one
+    pub retry_backoff_ms: u64,
+    /// Per-request timeout in milliseconds
+    pub timeout_ms: u64,
+    /// Events waiting for delivery before new ones are dropped
+    pub queue_size: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            secret: None,
+            block_found: true,
+            milestones: false,
+            max_retries: 3,
+            retry_backoff_ms: 1000,
+            timeout_ms: 5000,
+            queue_size: 64,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(Error::Config(format!(
+                "webhooks.url must be an http(s) URL, got '{}'",
+                self.url
+            )));
+        }
+        if self.secret.as_deref().is_some_and(str::is_empty) {
+            return Err(Error::Config("webhooks.secret must not be empty".to_string()));
+        }
+        if self.timeout_ms == 0 {
+            return Err(Error::Config("webhooks.timeout_ms must be greater than 0".to_string()));
+        }
+        if self.queue_size == 0 {
+            return Err(Error::Config("webhooks.queue_size must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Something a receiver can be told about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    BlockFound {
+        block_hash: String,
+        connection_id: ConnectionId,
+    },
+    /// A block reported as found is no longer in the active chain
+    BlockOrphaned {
+        block_hash: String,
+        height: Option<u64>,
+    },
+    Milestone(MilestoneRecord),
+}
+
+/// Request body: the event plus when it was sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` under `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac_sha256(secret.as_bytes(), body)))
+}
+
+/// HMAC (RFC 2104) over SHA-256
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_LEN: usize = 64;
+    let mut block_key = [0u8; BLOCK_LEN];
+    if key.len() > BLOCK_LEN {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Queues events for the background delivery task
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    queue: mpsc::Sender<WebhookPayload>,
+}
+
+impl WebhookNotifier {
+    /// Start the delivery task. Must be called within a Tokio runtime.
+    pub fn spawn(config: WebhookConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|e| Error::Network(format!("Failed to build webhook client: {}", e)))?;
+        let (queue, receiver) = mpsc::channel(config.queue_size);
+        tokio::spawn(deliver_all(config.clone(), client, receiver));
+        Ok(Self { config, queue })
+    }
+
+    /// Queue `event` if the configuration asks for it. Never waits.
+    pub fn notify(&self, event: WebhookEvent) {
+        let wanted = match event {
+            WebhookEvent::BlockFound { .. } | WebhookEvent::BlockOrphaned { .. } => self.config.block_found,
+            WebhookEvent::Milestone(_) => self.config.milestones,
+        };
+        if !wanted {
+            return;
+        }
+        let payload = WebhookPayload { event, timestamp: Utc::now() };
+        if let Err(e) = self.queue.try_send(payload) {
+            warn!("Dropping webhook event, delivery queue unavailable: {}", e);
+        }
+    }
+
+    /// Queue block and milestone events published on `bus` until the bus is dropped
+    pub fn subscribe(self, bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        bus.spawn_subscriber("Webhook notifier", move |event| match event {
+            Event::BlockFound { block_hash, connection_id } => {
+                self.notify(WebhookEvent::BlockFound { block_hash: block_hash.to_string(), connection_id })
+            }
+            Event::BlockOrphaned { block_hash, height } => {
+                self.notify(WebhookEvent::BlockOrphaned { block_hash: block_hash.to_string(), height })
+            }
+            Event::MilestoneReached(milestone) => self.notify(WebhookEvent::Milestone(milestone)),
+            _ => {}
+        })
+    }
+}
+
+async fn deliver_all(config: WebhookConfig, client: reqwest::Client, mut receiver: mpsc::Receiver<WebhookPayload>) {
+    while let Some(payload) = receiver.recv().await {
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook event: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = deliver(&config, &client, body).await {
+            warn!("Webhook delivery to {} failed after {} retries: {}", config.url, config.max_retries, e);
+        }
+    }
+}
+
+/// POST one body, retrying with exponential backoff
+async fn deliver(config: &WebhookConfig, client: &reqwest::Client, body: Vec<u8>) -> Result<()> {
+    let signature = config.secret.as_deref().map(|secret| sign(secret, &body));
+    let mut backoff = Duration::from_millis(config.retry_backoff_ms);
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(&config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let error = match request.send().await.and_then(|response| response.error_for_status()) {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+        if attempt >= config.max_retries {
+            return Err(Error::Network(error.to_string()));
+        }
+        attempt += 1;
+        debug!("Webhook delivery failed ({}), retry {} in {:?}", error, attempt, backoff);
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Receiver that fails the first request, then records headers and bodies
+    async fn mock_receiver() -> (String, Arc<Mutex<Vec<(String, String)>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks/sv2d", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&received);
+        tokio::spawn(async move {
+            let mut requests = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = stream.read(&mut buffer).await.unwrap_or(0);
+                    if n == 0 {
+                        break (String::new(), String::new());
+                    }
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break (head.to_string(), body.to_string());
+                        }
+                    }
+                };
+                requests += 1;
+                let status = if requests == 1 { "500 Internal Server Error" } else { "200 OK" };
+                if requests > 1 {
+                    recorded.lock().unwrap().push((head, body));
+                }
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, received)
+    }
+
+    #[test]
+    fn test_hmac_sha256_matches_rfc_4231() {
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_found_is_signed_and_retried() {
+        let (url, received) = mock_receiver().await;
+        let notifier = WebhookNotifier::spawn(WebhookConfig {
+            enabled: true,
+            url,
+            secret: Some("s3cret".to_string()),
+            retry_backoff_ms: 10,
+            ..Default::default()
+        })
+        .unwrap();
+        let bus = EventBus::default();
+        let _subscription = notifier.subscribe(&bus);
+
+        let connection_id = uuid::Uuid::new_v4();
+        // Milestones are off by default
+        bus.publish(Event::MilestoneReached(MilestoneRecord {
+            milestone: crate::milestones::Milestone::FirstShare,
+            reached_at: Utc::now(),
+            detail: None,
+        }));
+        bus.publish(Event::BlockFound { block_hash: bitcoin::BlockHash::all_zeros(), connection_id });
+
+        let mut delivered = None;
+        for _ in 0..100 {
+            if let Some(request) = received.lock().unwrap().first().cloned() {
+                delivered = Some(request);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let (head, body) = delivered.expect("webhook was not retried");
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        let signature = head
+            .lines()
+            .find_map(|line| line.strip_prefix("x-sv2d-signature: ").or_else(|| line.strip_prefix("X-Sv2d-Signature: ")))
+            .expect("missing signature header");
+        assert_eq!(signature, sign("s3cret", body.as_bytes()));
+
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["event"], "block_found");
+        assert_eq!(payload["block_hash"], "00".repeat(32));
+        assert_eq!(payload["connection_id"], connection_id.to_string());
+        assert!(payload["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_webhook_config_validation() {
+        WebhookConfig::default().validate().unwrap();
+        let enabled = WebhookConfig { enabled: true, url: "https://example.com/hook".to_string(), ..Default::default() };
+        enabled.validate().unwrap();
+        assert!(WebhookConfig { url: "example.com".to_string(), ..enabled.clone() }.validate().is_err());
+        assert!(WebhookConfig { secret: Some(String::new()), ..enabled }.validate().is_err());
+    }
+}
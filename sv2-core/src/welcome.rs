@@ -0,0 +1,110 @@
+//! Welcome message for SV1 miners
+//!
+//! Some pool frontends greet miners with `client.show_message` once they
+//! authorize. Setting `[network.welcome] message` makes the stratum server do
+//! the same, adding the pool signature and a status URL when configured.
+//! `client.show_message` is optional in Stratum V1 and a few firmwares drop
+//! the connection on notifications they don't know, so miners whose
+//! `mining.subscribe` user agent matches `skip_user_agents` never get it.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// `welcome` configuration; nothing is sent unless `message` is set
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct WelcomeConfig {
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Pool signature shown after the message
+    #[serde(default)]
+    pub pool_signature: Option<String>,
+    /// Where miners can check pool status
+    #[serde(default)]
+    pub status_url: Option<String>,
+    /// User-agent prefixes, matched case-insensitively, of miners that
+    /// don't tolerate `client.show_message`
+    #[serde(default)]
+    pub skip_user_agents: Vec<String>,
+}
+
+impl WelcomeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.message.as_deref().is_some_and(|message| message.trim().is_empty()) {
+            return Err(Error::Config("welcome message must not be empty".to_string()));
+        }
+        if let Some(url) = &self.status_url {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(Error::Config(format!(
+                    "welcome status_url '{}' must start with http:// or https://",
+                    url
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Text shown to miners, or `None` when no message is configured
+    pub fn text(&self) -> Option<String> {
+        let mut parts = vec![self.message.clone()?];
+        if let Some(signature) = &self.pool_signature {
+            parts.push(signature.clone());
+        }
+        if let Some(url) = &self.status_url {
+            parts.push(format!("Status: {}", url));
+        }
+        Some(parts.join(" | "))
+    }
+
+    /// Whether a miner that subscribed with `user_agent` should get the message
+    pub fn tolerated_by(&self, user_agent: Option<&str>) -> bool {
+        let user_agent = user_agent.unwrap_or_default().to_ascii_lowercase();
+        !self
+            .skip_user_agents
+            .iter()
+            .any(|prefix| user_agent.starts_with(&prefix.to_ascii_lowercase()))
+    }
+
+    /// The `client.show_message` notification for a miner, if it gets one
+    pub fn notification(&self, user_agent: Option<&str>) -> Option<serde_json::Value> {
+        if !self.tolerated_by(user_agent) {
+            return None;
+        }
+        let text = self.text()?;
+        Some(serde_json::json!({
+            "id": null,
+            "method": "client.show_message",
+            "params": [text]
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welcome_notification() {
+        assert!(WelcomeConfig::default().notification(Some("cgminer/4.12")).is_none());
+
+        let config = WelcomeConfig {
+            message: Some("Welcome to the home pool".to_string()),
+            pool_signature: Some("/home-pool/".to_string()),
+            status_url: Some("http://10.0.0.2:8080".to_string()),
+            skip_user_agents: vec!["LegacyMiner".to_string()],
+        };
+        config.validate().unwrap();
+
+        let notification = config.notification(Some("bitaxe/BM1366/v2.4.0")).unwrap();
+        assert_eq!(notification["method"], "client.show_message");
+        assert_eq!(
+            notification["params"][0],
+            "Welcome to the home pool | /home-pool/ | Status: http://10.0.0.2:8080"
+        );
+
+        assert!(config.notification(Some("legacyminer/1.0")).is_none());
+        assert!(config.notification(None).is_some());
+
+        let invalid = WelcomeConfig { status_url: Some("10.0.0.2:8080".to_string()), ..config };
+        assert!(invalid.validate().is_err());
+    }
+}
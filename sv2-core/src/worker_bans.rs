@@ -0,0 +1,108 @@
+//! Per-worker bans
+//!
+//! Several workers often share one connection or one IP (a proxy, or a farm
+//! behind NAT), so a single misbehaving worker can't be dealt with by
+//! dropping its connection. A banned worker name has its `mining.submit`s
+//! rejected by the stratum server before they reach the mode handler, while
+//! other workers on the same connection carry on. Bans are stored in the
+//! database and outlive restarts; the in-memory set keeps the database off
+//! the submit path.
+
+use crate::{database::DatabaseOps, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// A banned worker name
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerBan {
+    /// Name the worker authorized with
+    pub worker_name: String,
+    pub reason: Option<String>,
+    pub banned_at: DateTime<Utc>,
+}
+
+impl WorkerBan {
+    pub fn new(worker_name: String, reason: Option<String>) -> Self {
+        Self { worker_name, reason, banned_at: Utc::now() }
+    }
+}
+
+/// Banned worker names, shared by the stratum server and the API
+#[derive(Debug, Default)]
+pub struct WorkerBans {
+    banned: RwLock<HashMap<String, WorkerBan>>,
+}
+
+impl WorkerBans {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bans stored in `database`
+    pub async fn load(database: &dyn DatabaseOps) -> Result<Self> {
+        let bans = database.get_worker_bans().await?;
+        let banned = bans.into_iter().map(|ban| (ban.worker_name.clone(), ban)).collect();
+        Ok(Self { banned: RwLock::new(banned) })
+    }
+
+    pub fn is_banned(&self, worker_name: &str) -> bool {
+        self.banned.read().unwrap().contains_key(worker_name)
+    }
+
+    /// Ban `worker_name`, replacing the reason if it already was
+    pub async fn ban(
+        &self,
+        database: &dyn DatabaseOps,
+        worker_name: &str,
+        reason: Option<String>,
+    ) -> Result<WorkerBan> {
+        if worker_name.trim().is_empty() {
+            return Err(Error::Config("Worker name must not be empty".to_string()));
+        }
+        let ban = WorkerBan::new(worker_name.to_string(), reason);
+        database.store_worker_ban(&ban).await?;
+        self.banned.write().unwrap().insert(ban.worker_name.clone(), ban.clone());
+        Ok(ban)
+    }
+
+    /// Lift the ban on `worker_name`, returning false if it wasn't banned
+    pub async fn unban(&self, database: &dyn DatabaseOps, worker_name: &str) -> Result<bool> {
+        database.delete_worker_ban(worker_name).await?;
+        Ok(self.banned.write().unwrap().remove(worker_name).is_some())
+    }
+
+    /// Current bans, by worker name
+    pub fn list(&self) -> Vec<WorkerBan> {
+        let mut bans: Vec<WorkerBan> = self.banned.read().unwrap().values().cloned().collect();
+        bans.sort_by(|a, b| a.worker_name.cmp(&b.worker_name));
+        bans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MockDatabaseOps;
+
+    #[tokio::test]
+    async fn test_bans_are_stored_and_reloaded() {
+        let database = MockDatabaseOps::new();
+        let bans = WorkerBans::new();
+        bans.ban(&database, "rig2", Some("stale flood".to_string())).await.unwrap();
+        bans.ban(&database, "rig1", None).await.unwrap();
+        assert!(bans.is_banned("rig1"));
+        assert!(!bans.is_banned("rig3"));
+        assert!(bans.ban(&database, " ", None).await.is_err());
+
+        let reloaded = WorkerBans::load(&database).await.unwrap();
+        let names: Vec<String> = reloaded.list().into_iter().map(|ban| ban.worker_name).collect();
+        assert_eq!(names, ["rig1", "rig2"]);
+
+        assert!(reloaded.unban(&database, "rig2").await.unwrap());
+        assert!(!reloaded.unban(&database, "rig2").await.unwrap());
+        assert!(!WorkerBans::load(&database).await.unwrap().is_banned("rig2"));
+    }
+}
@@ -17,12 +17,21 @@ fn create_test_config() -> DaemonConfig {
             block_template_refresh_interval: 30,
             enable_custom_templates: false,
             max_template_age: 300,
+            template_source: Default::default(),
+            coinbase_tags: Default::default(),
+            block_confirmations: 6,
         }),
         network: NetworkConfig {
             bind_address: "127.0.0.1:0".parse().unwrap(), // Use random port
             max_connections: 100,
+            listen_backlog: 1024,
             connection_timeout: 30,
             keepalive_interval: 60,
+            capture: Default::default(),
+            welcome: Default::default(),
+            handshake_failures: Default::default(),
+            connection_rate_limit: Default::default(),
+            drain_timeout_secs: 10,
         },
         bitcoin: BitcoinConfig {
             rpc_url: "http://localhost:18443".to_string(),
@@ -37,6 +46,9 @@ fn create_test_config() -> DaemonConfig {
             max_connections: 5,
             connection_timeout: 30,
             enable_migrations: true,
+            dead_letter_path: "sv2d_dead_letter.jsonl".into(),
+            accepted_share_sample_rate: 1,
+            share_retention_days: 30,
         },
         monitoring: MonitoringConfig {
             enable_metrics: true,
@@ -49,6 +61,8 @@ fn create_test_config() -> DaemonConfig {
                 prometheus_port: 9090,
                 system_monitoring: true,
                 labels: HashMap::new(),
+                push: Default::default(),
+                history: Default::default(),
             },
             health: HealthConfig {
                 enabled: true,
@@ -63,6 +77,8 @@ fn create_test_config() -> DaemonConfig {
                     database_connections: 10,
                 },
             },
+            difficulty_unit: Default::default(),
+            connection_quality: Default::default(),
         },
         logging: LoggingConfig {
             level: "info".to_string(),
@@ -73,6 +89,7 @@ fn create_test_config() -> DaemonConfig {
             redact_sensitive_data: true,
             max_file_size_mb: None,
             max_files: None,
+            shares: Default::default(),
         },
         security: SecurityConfig {
             enable_authentication: false,
@@ -83,6 +100,8 @@ fn create_test_config() -> DaemonConfig {
             tls_key_path: None,
             auth: sv2_core::auth::AuthConfig::default(),
         },
+        webhooks: Default::default(),
+        idle: Default::default(),
     }
 }
 
@@ -132,6 +151,7 @@ impl Daemon for MockDaemon {
             blocks_found: 0,
             current_difficulty: 1.0,
             hashrate: 0.0,
+            mode_health: None,
         }
     }
 
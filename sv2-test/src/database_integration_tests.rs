@@ -28,6 +28,7 @@ fn create_test_connection_info() -> ConnectionInfo {
         last_activity: chrono::Utc::now(),
         user_agent: Some("test-miner/1.0".to_string()),
         version: Some("1.0.0".to_string()),
+        flags: None,
         subscribed_difficulty: Some(1.0),
         extranonce1: Some("abcd1234".to_string()),
         extranonce2_size: Some(4),
@@ -36,6 +37,7 @@ fn create_test_connection_info() -> ConnectionInfo {
         valid_shares: 9,
         invalid_shares: 1,
         blocks_found: 0,
+        quality: None,
     }
 }
 
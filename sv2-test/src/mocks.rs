@@ -1,4 +1,5 @@
-use sv2_core::{Result, Connection, Share, ShareResult, WorkTemplate, ModeHandler, MiningStats};
+use sv2_core::{Result, Connection, HandshakeDetails, Share, ShareResult, WorkTemplate, ModeHandler, MiningStats};
+use sv2_core::health::{HealthCheck, HealthStatus};
 use sv2_core::bitcoin_rpc::{GetBlockTemplateResponse, NetworkInfoResponse, BlockchainInfoResponse, SubmitBlockResponse, BlockTemplateTransaction};
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -45,6 +46,10 @@ impl ModeHandler for MockModeHandler {
         }
     }
 
+    async fn handle_handshake(&self, _connection_id: sv2_core::ConnectionId, _details: HandshakeDetails) -> Result<()> {
+        Ok(())
+    }
+
     async fn process_share(&self, _share: Share) -> Result<ShareResult> {
         if self.should_fail {
             Ok(ShareResult::Invalid("Mock invalid share".to_string()))
@@ -88,9 +93,25 @@ impl ModeHandler for MockModeHandler {
             shares_accepted: 95,
             shares_rejected: 5,
             blocks_found: 0,
+            blocks_orphaned: 0,
+            expected_blocks: None,
+            coinbase_tag: None,
+            network_difficulty: None,
         })
     }
 
+    async fn health(&self) -> HealthCheck {
+        if self.should_fail {
+            HealthCheck::new("mock_mode", HealthStatus::Critical, "Mock health failure")
+        } else {
+            HealthCheck::new("mock_mode", HealthStatus::Healthy, "Mock mode healthy")
+        }
+    }
+
+    async fn workers(&self) -> Vec<sv2_core::types::Worker> {
+        Vec::new()
+    }
+
     fn validate_config(&self, _config: &sv2_core::config::DaemonConfig) -> Result<()> {
         Ok(())
     }
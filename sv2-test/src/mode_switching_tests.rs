@@ -3,6 +3,7 @@ use sv2_core::{
     mode_factory::{ModeHandlerFactory, ModeRouter, ModeState},
     config::{DaemonConfig, OperationModeConfig, SoloConfig, PoolConfig, NetworkConfig, DatabaseConfig, BitcoinConfig, MonitoringConfig, LoggingConfig, SecurityConfig, MetricsConfig, HealthConfig, AlertThresholds, LogFormat, LogOutput},
     database::DatabasePool,
+    events::EventBus,
 };
 use std::collections::HashMap;
 use tempfile::tempdir;
@@ -16,6 +17,9 @@ fn create_test_database_config() -> DatabaseConfig {
         max_connections: 5,
         connection_timeout: 30,
         enable_migrations: true,
+        dead_letter_path: "sv2d_dead_letter.jsonl".into(),
+        accepted_share_sample_rate: 1,
+        share_retention_days: 30,
     }
 }
 
@@ -25,8 +29,14 @@ fn create_test_config(mode: OperationModeConfig) -> DaemonConfig {
         network: NetworkConfig {
             bind_address: "127.0.0.1:0".parse().unwrap(),
             max_connections: 100,
+            listen_backlog: 1024,
             connection_timeout: 30,
             keepalive_interval: 60,
+            capture: Default::default(),
+            welcome: Default::default(),
+            handshake_failures: Default::default(),
+            connection_rate_limit: Default::default(),
+            drain_timeout_secs: 10,
         },
         bitcoin: BitcoinConfig {
             rpc_url: "http://localhost:18443".to_string(),
@@ -48,6 +58,8 @@ fn create_test_config(mode: OperationModeConfig) -> DaemonConfig {
                 prometheus_port: 9090,
                 system_monitoring: true,
                 labels: HashMap::new(),
+                push: Default::default(),
+                history: Default::default(),
             },
             health: HealthConfig {
                 enabled: true,
@@ -62,6 +74,8 @@ fn create_test_config(mode: OperationModeConfig) -> DaemonConfig {
                     database_connections: 10,
                 },
             },
+            difficulty_unit: Default::default(),
+            connection_quality: Default::default(),
         },
         logging: LoggingConfig {
             level: "info".to_string(),
@@ -72,6 +86,7 @@ fn create_test_config(mode: OperationModeConfig) -> DaemonConfig {
             redact_sensitive_data: true,
             max_file_size_mb: None,
             max_files: None,
+            shares: Default::default(),
         },
         security: SecurityConfig {
             enable_authentication: false,
@@ -82,6 +97,8 @@ fn create_test_config(mode: OperationModeConfig) -> DaemonConfig {
             tls_key_path: None,
             auth: sv2_core::auth::AuthConfig::default(),
         },
+        webhooks: Default::default(),
+        idle: Default::default(),
     }
 }
 
@@ -100,7 +117,7 @@ async fn test_mode_handler_factory_create_solo() {
     let config = create_test_config(OperationModeConfig::Solo(SoloConfig::default()));
     let database = create_test_database().await.unwrap();
     
-    let handler = ModeHandlerFactory::create_handler(&config, database);
+    let handler = ModeHandlerFactory::create_handler(&config, database, &EventBus::default());
     assert!(handler.is_ok());
 }
 
@@ -109,7 +126,7 @@ async fn test_mode_handler_factory_create_pool() {
     let config = create_test_config(OperationModeConfig::Pool(PoolConfig::default()));
     let database = create_test_database().await.unwrap();
     
-    let handler = ModeHandlerFactory::create_handler(&config, database);
+    let handler = ModeHandlerFactory::create_handler(&config, database, &EventBus::default());
     assert!(handler.is_ok());
 }
 
@@ -328,7 +345,7 @@ async fn test_mode_state_preservation() {
     let database = create_test_database().await.unwrap();
     let config = create_test_config(OperationModeConfig::Solo(SoloConfig::default()));
     
-    let handler = ModeHandlerFactory::create_handler(&config, Arc::clone(&database)).unwrap();
+    let handler = ModeHandlerFactory::create_handler(&config, Arc::clone(&database), &EventBus::default()).unwrap();
     
     // Test state preservation (should not panic)
     let result = ModeHandlerFactory::preserve_mode_state(handler.as_ref(), Arc::clone(&database)).await;
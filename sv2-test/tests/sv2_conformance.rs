@@ -0,0 +1,209 @@
+// SV2 wire format conformance
+//
+// sv2d spawns SRI's pool_sv2 and translator_sv2, so the in-crate codec has to
+// read and write exactly what they do. Each reference frame here is built by
+// SRI itself: the message types from common_messages_sv2 and mining_sv2,
+// framed by codec_sv2. The codec must decode those bytes to the expected
+// message and encode that message back to the same bytes.
+use codec_sv2::binary_sv2::{GetSize, Serialize, Str0255, Sv2Option, U256};
+use codec_sv2::Sv2Frame;
+use sv2_core::protocol::{parse_sv2_message, ProtocolMessage};
+use sv2_core::sv2_codec::{
+    NewMiningJob, OpenStandardMiningChannel, OpenStandardMiningChannelSuccess, SetupConnection,
+    SubmitSharesStandard, Sv2Message, CHANNEL_MSG_BIT,
+};
+
+/// A whole frame, header included, as SRI's codec writes it
+fn sri_frame<T: Serialize + GetSize>(message: T, message_type: u8, channel_msg: bool) -> Vec<u8> {
+    let frame = Sv2Frame::<T, Vec<u8>>::from_message(message, message_type, 0, channel_msg).unwrap();
+    let mut bytes = vec![0u8; frame.encoded_length()];
+    frame.serialize(&mut bytes).unwrap();
+    bytes
+}
+
+fn str0255(value: &str) -> Str0255<'static> {
+    value.to_string().try_into().unwrap()
+}
+
+fn assert_conforms(bytes: Vec<u8>, expected: Sv2Message) -> ProtocolMessage {
+    assert_eq!(Sv2Message::decode(&bytes).unwrap(), expected);
+    assert_eq!(expected.encode().unwrap(), bytes);
+
+    let extension_type = u16::from_le_bytes([bytes[0], bytes[1]]);
+    assert_eq!(extension_type & CHANNEL_MSG_BIT != 0, expected.is_channel_message());
+    parse_sv2_message(&bytes).unwrap()
+}
+
+#[test]
+fn test_setup_connection() {
+    let sri = common_messages_sv2::SetupConnection {
+        protocol: common_messages_sv2::Protocol::MiningProtocol,
+        min_version: 2,
+        max_version: 2,
+        flags: 1,
+        endpoint_host: str0255("0.0.0.0"),
+        endpoint_port: 34254,
+        vendor: str0255("Bitmain"),
+        hardware_version: str0255("S9i 13.5"),
+        firmware: str0255("braiins-os-2018-09-22-1-hash"),
+        device_id: str0255("some-device-uuid"),
+    };
+    let parsed = assert_conforms(
+        sri_frame(
+            sri,
+            common_messages_sv2::MESSAGE_TYPE_SETUP_CONNECTION,
+            common_messages_sv2::CHANNEL_BIT_SETUP_CONNECTION,
+        ),
+        Sv2Message::SetupConnection(SetupConnection {
+            protocol: 0,
+            min_version: 2,
+            max_version: 2,
+            flags: 1,
+            endpoint_host: "0.0.0.0".to_string(),
+            endpoint_port: 34254,
+            vendor: "Bitmain".to_string(),
+            hardware_version: "S9i 13.5".to_string(),
+            firmware: "braiins-os-2018-09-22-1-hash".to_string(),
+            device_id: "some-device-uuid".to_string(),
+        }),
+    );
+    assert!(matches!(parsed, ProtocolMessage::Sv2SetupConnection));
+}
+
+#[test]
+fn test_open_standard_mining_channel() {
+    let sri = mining_sv2::OpenStandardMiningChannel {
+        request_id: 1u32.into(),
+        user_identity: str0255("sv2d_miner"),
+        nominal_hash_rate: 5e11,
+        max_target: U256::from([0xff; 32]),
+    };
+    let parsed = assert_conforms(
+        sri_frame(
+            sri,
+            mining_sv2::MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL,
+            mining_sv2::CHANNEL_BIT_OPEN_STANDARD_MINING_CHANNEL,
+        ),
+        Sv2Message::OpenStandardMiningChannel(OpenStandardMiningChannel {
+            request_id: 1,
+            user_identity: "sv2d_miner".to_string(),
+            nominal_hash_rate: 5e11,
+            max_target: [0xff; 32],
+        }),
+    );
+    match parsed {
+        ProtocolMessage::Sv2OpenChannelRequest { request_id, user_identity } => {
+            assert_eq!(request_id, 1);
+            assert_eq!(user_identity, "sv2d_miner");
+        }
+        other => panic!("Unexpected message: {:?}", other),
+    }
+}
+
+#[test]
+fn test_open_standard_mining_channel_success() {
+    // Difficulty 1, little-endian
+    let mut target = [0u8; 32];
+    target[28..30].copy_from_slice(&[0xff, 0xff]);
+    let sri = mining_sv2::OpenStandardMiningChannelSuccess {
+        request_id: 1u32.into(),
+        channel_id: 1,
+        target: U256::from(target),
+        extranonce_prefix: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        group_channel_id: 0,
+    };
+    let parsed = assert_conforms(
+        sri_frame(
+            sri,
+            mining_sv2::MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL_SUCCESS,
+            mining_sv2::CHANNEL_BIT_OPEN_STANDARD_MINING_CHANNEL_SUCCESS,
+        ),
+        Sv2Message::OpenStandardMiningChannelSuccess(OpenStandardMiningChannelSuccess {
+            request_id: 1,
+            channel_id: 1,
+            target,
+            extranonce_prefix: vec![1, 0, 0, 0, 0, 0, 0, 0],
+            group_channel_id: 0,
+        }),
+    );
+    assert!(matches!(parsed, ProtocolMessage::Sv2OpenChannel { channel_id: 1 }));
+}
+
+#[test]
+fn test_new_mining_job() {
+    let merkle_root: Vec<u8> = (0..32).collect();
+    let sri = mining_sv2::NewMiningJob {
+        channel_id: 1,
+        job_id: 7,
+        min_ntime: Sv2Option::new(Some(0x66a0b1c2)),
+        version: 0x20000000,
+        merkle_root: merkle_root.clone().try_into().unwrap(),
+    };
+    let parsed = assert_conforms(
+        sri_frame(sri, mining_sv2::MESSAGE_TYPE_NEW_MINING_JOB, mining_sv2::CHANNEL_BIT_NEW_MINING_JOB),
+        Sv2Message::NewMiningJob(NewMiningJob {
+            channel_id: 1,
+            job_id: 7,
+            min_ntime: Some(0x66a0b1c2),
+            version: 0x20000000,
+            merkle_root,
+        }),
+    );
+    assert!(matches!(parsed, ProtocolMessage::Sv2NewMiningJob { channel_id: 1, job_id: 7 }));
+}
+
+#[test]
+fn test_future_new_mining_job() {
+    let sri = mining_sv2::NewMiningJob {
+        channel_id: 1,
+        job_id: 8,
+        min_ntime: Sv2Option::new(None),
+        version: 0x20000000,
+        merkle_root: vec![0u8; 32].try_into().unwrap(),
+    };
+    assert_conforms(
+        sri_frame(sri, mining_sv2::MESSAGE_TYPE_NEW_MINING_JOB, mining_sv2::CHANNEL_BIT_NEW_MINING_JOB),
+        Sv2Message::NewMiningJob(NewMiningJob {
+            channel_id: 1,
+            job_id: 8,
+            min_ntime: None,
+            version: 0x20000000,
+            merkle_root: vec![0u8; 32],
+        }),
+    );
+}
+
+#[test]
+fn test_submit_shares_standard() {
+    let sri = mining_sv2::SubmitSharesStandard {
+        channel_id: 1,
+        sequence_number: 0,
+        job_id: 7,
+        nonce: 0x12345678,
+        ntime: 0x66a0b1c2,
+        version: 0x20000000,
+    };
+    let parsed = assert_conforms(
+        sri_frame(
+            sri,
+            mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            mining_sv2::CHANNEL_BIT_SUBMIT_SHARES_STANDARD,
+        ),
+        Sv2Message::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number: 0,
+            job_id: 7,
+            nonce: 0x12345678,
+            ntime: 0x66a0b1c2,
+            version: 0x20000000,
+        }),
+    );
+    match parsed {
+        ProtocolMessage::Sv2SubmitShares { channel_id, shares } => {
+            assert_eq!(channel_id, 1);
+            assert_eq!(shares[0].nonce, 0x12345678);
+            assert_eq!(shares[0].timestamp, 0x66a0b1c2);
+        }
+        other => panic!("Unexpected message: {:?}", other),
+    }
+}
@@ -0,0 +1,65 @@
+//! Bakes build metadata into the binary for the `/api/v1/version` endpoint.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let workspace_root = Path::new(&manifest_dir).parent().unwrap();
+
+    println!("cargo:rustc-env=SV2_WEB_GIT_COMMIT={}", git_commit());
+    println!("cargo:rustc-env=SV2_WEB_BUILD_TIMESTAMP={}", build_timestamp());
+    println!(
+        "cargo:rustc-env=SV2_WEB_SRI_VERSION={}",
+        locked_version(&workspace_root.join("Cargo.lock"), "roles_logic_sv2")
+            .unwrap_or_else(|| "unknown".to_string())
+    );
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", workspace_root.join("Cargo.lock").display());
+    println!("cargo:rerun-if-changed={}", workspace_root.join(".git/HEAD").display());
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+fn git_commit() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Seconds since the Unix epoch, honouring `SOURCE_DATE_EPOCH` for
+/// reproducible builds
+fn build_timestamp() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        })
+}
+
+/// Version of `package` recorded in the lockfile
+fn locked_version(lockfile: &Path, package: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(lockfile).ok()?;
+    let name_line = format!("name = \"{}\"", package);
+    let mut lines = contents.lines();
+
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line {
+            let version = lines.next()?.trim().strip_prefix("version = \"")?;
+            return version.strip_suffix('"').map(str::to_string);
+        }
+    }
+
+    None
+}
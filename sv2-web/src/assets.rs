@@ -0,0 +1,47 @@
+//! Dashboard assets compiled into the binary (`embed-assets` feature).
+//!
+//! Serves the contents of `sv2-web/static` from memory so the binary can be
+//! deployed without a `static/` directory next to it.
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use rust_embed::RustEmbed;
+
+#[derive(RustEmbed)]
+#[folder = "static/"]
+struct Assets;
+
+/// Routes serving the embedded dashboard: `index.html` at `/` and every
+/// asset under `/static/`
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/", get(index))
+        .route("/static/*path", get(static_asset))
+}
+
+async fn index() -> Response {
+    serve_asset("index.html")
+}
+
+async fn static_asset(Path(path): Path<String>) -> Response {
+    serve_asset(&path)
+}
+
+fn serve_asset(path: &str) -> Response {
+    match Assets::get(path) {
+        Some(file) => (
+            [(header::CONTENT_TYPE, file.metadata.mimetype().to_string())],
+            file.data.into_owned(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
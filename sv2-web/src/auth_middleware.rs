@@ -78,6 +78,7 @@ fn get_required_permission(path: &str, method: &str) -> Option<Permission> {
         ("GET", path) if path.starts_with("/api/v1/alerts") => Some(Permission::ViewHealth),
         ("GET", path) if path.starts_with("/api/v1/config") => Some(Permission::ViewConfig),
         ("GET", path) if path.starts_with("/api/v1/mining-stats") => Some(Permission::ViewMiningStats),
+        ("GET", path) if path.starts_with("/api/v1/blocks") => Some(Permission::ViewMiningStats),
         
         // Write operations
         ("POST", path) if path.starts_with("/api/v1/templates") => Some(Permission::CreateTemplates),
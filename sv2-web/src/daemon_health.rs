@@ -0,0 +1,187 @@
+//! Health of the sv2d daemon the web server runs beside, for
+//! `GET /api/v1/health`.
+//!
+//! The daemon's `status` JSON-RPC call reports a health status for each
+//! component it supervises and whether bitcoind has finished its initial
+//! block download. Any Critical component, or a daemon that doesn't answer,
+//! makes the service unhealthy. A Warning component or a node still syncing
+//! makes it degraded, which load balancers should keep routing to.
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long to wait for the daemon before calling it unreachable
+const RPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Overall health, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    Unhealthy,
+    Degraded,
+    Healthy,
+}
+
+/// A component that isn't healthy, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentProblem {
+    pub component: String,
+    pub health_status: String,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DaemonHealth {
+    pub status: Health,
+    /// Components reported Critical, or `sv2d` itself when it didn't answer
+    pub unhealthy: Vec<ComponentProblem>,
+    /// Components reported Warning
+    pub degraded: Vec<ComponentProblem>,
+    pub bitcoin_synced: Option<bool>,
+    pub daemon_uptime_seconds: Option<u64>,
+}
+
+impl DaemonHealth {
+    fn unreachable(error: String) -> Self {
+        Self {
+            status: Health::Unhealthy,
+            unhealthy: vec![ComponentProblem {
+                component: "sv2d".to_string(),
+                health_status: "Unreachable".to_string(),
+                last_error: Some(error),
+            }],
+            degraded: Vec::new(),
+            bitcoin_synced: None,
+            daemon_uptime_seconds: None,
+        }
+    }
+}
+
+/// Ask the daemon at `rpc_url` for its status and assess it
+pub async fn check(rpc_url: &str) -> DaemonHealth {
+    match fetch_status(rpc_url).await {
+        Ok(status) => assess(&status),
+        Err(error) => DaemonHealth::unreachable(error),
+    }
+}
+
+async fn fetch_status(rpc_url: &str) -> Result<serde_json::Value, String> {
+    let response = reqwest::Client::new()
+        .post(rpc_url)
+        .json(&serde_json::json!({ "id": 1, "method": "status", "params": {} }))
+        .timeout(RPC_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("sv2d at {} is not reachable: {}", rpc_url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("sv2d at {} answered {}", rpc_url, response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("sv2d at {} sent an unreadable status: {}", rpc_url, e))?;
+
+    if let Some(message) = body.get("error").and_then(|e| e.get("message")).and_then(|m| m.as_str()) {
+        return Err(format!("sv2d could not report its status: {}", message));
+    }
+    let result = body.get("result").cloned().unwrap_or_default();
+    if !result.is_object() {
+        return Err(format!("sv2d at {} sent a status without a result", rpc_url));
+    }
+    Ok(result)
+}
+
+/// Health from the result of the daemon's `status` call
+pub fn assess(status: &serde_json::Value) -> DaemonHealth {
+    let mut unhealthy = Vec::new();
+    let mut degraded = Vec::new();
+
+    if let Some(components) = status.get("components").and_then(|c| c.as_object()) {
+        for (name, component) in components {
+            let health_status = component.get("health_status").and_then(|s| s.as_str()).unwrap_or("Unknown");
+            let problem = ComponentProblem {
+                component: name.clone(),
+                health_status: health_status.to_string(),
+                last_error: component.get("last_error").and_then(|e| e.as_str()).map(str::to_string),
+            };
+            // Unknown means not checked yet, which says nothing either way
+            match health_status {
+                "Critical" => unhealthy.push(problem),
+                "Warning" => degraded.push(problem),
+                _ => {}
+            }
+        }
+    }
+    // Components come out of a map, so sort for a stable response
+    unhealthy.sort_by(|a, b| a.component.cmp(&b.component));
+    degraded.sort_by(|a, b| a.component.cmp(&b.component));
+
+    let bitcoin_synced = status
+        .get("system_info")
+        .and_then(|info| info.get("bitcoin_synced"))
+        .and_then(|synced| synced.as_bool());
+
+    let status_value = if !unhealthy.is_empty() {
+        Health::Unhealthy
+    } else if !degraded.is_empty() || bitcoin_synced == Some(false) {
+        Health::Degraded
+    } else {
+        Health::Healthy
+    };
+
+    DaemonHealth {
+        status: status_value,
+        unhealthy,
+        degraded,
+        bitcoin_synced,
+        daemon_uptime_seconds: status.get("uptime_seconds").and_then(|u| u.as_u64()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn status(bitcoin: &str, pool: &str, synced: bool) -> serde_json::Value {
+        json!({
+            "running": true,
+            "uptime_seconds": 120,
+            "components": {
+                "bitcoin": { "running": true, "health_status": bitcoin, "last_error": null },
+                "pool": { "running": true, "health_status": pool, "last_error": "stratum port closed" },
+            },
+            "system_info": { "bitcoin_synced": synced },
+        })
+    }
+
+    #[test]
+    fn test_assess() {
+        let health = assess(&status("Healthy", "Unknown", true));
+        assert_eq!(health.status, Health::Healthy);
+        assert_eq!(health.daemon_uptime_seconds, Some(120));
+
+        let health = assess(&status("Healthy", "Critical", true));
+        assert_eq!(health.status, Health::Unhealthy);
+        assert_eq!(health.unhealthy, [ComponentProblem {
+            component: "pool".to_string(),
+            health_status: "Critical".to_string(),
+            last_error: Some("stratum port closed".to_string()),
+        }]);
+
+        let health = assess(&status("Warning", "Healthy", true));
+        assert_eq!(health.status, Health::Degraded);
+        assert_eq!(health.degraded[0].component, "bitcoin");
+    }
+
+    #[test]
+    fn test_syncing_node_is_degraded() {
+        let health = assess(&status("Healthy", "Healthy", false));
+        assert_eq!(health.status, Health::Degraded);
+        assert!(health.degraded.is_empty());
+        assert_eq!(health.bitcoin_synced, Some(false));
+
+        // A critical component outweighs the sync
+        assert_eq!(assess(&status("Critical", "Healthy", false)).status, Health::Unhealthy);
+    }
+}
@@ -1,7 +1,8 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::{Html, Json},
+    http::{header, HeaderValue, Request, StatusCode, Uri},
+    middleware::Next,
+    response::{Html, IntoResponse, Json, Redirect, Response},
 
 };
 use serde::{Deserialize, Serialize};
@@ -9,17 +10,36 @@ use std::str::FromStr;
 use std::sync::Arc;
 use sv2_core::{
     DaemonStatus, ConnectionInfo, Share, WorkTemplate, PerformanceMetrics, Alert,
-    database::{DatabaseOps, ShareStats},
+    blocks::BlockRecord,
+    database::{DatabaseOps, ShareBucket, ShareStats, MAX_SHARE_BUCKETS},
     config::DaemonConfig,
-    types::MiningStats,
+    metrics_history::{MetricsHistory, MetricsHistorySnapshot, MAX_HISTORY_SAMPLES},
+    mode::ModeHandler,
+    types::{MiningStats, Worker},
+    worker_bans::{WorkerBan, WorkerBans},
 };
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::daemon_health::{self, Health};
+use crate::websocket::WebSocketBroadcaster;
+
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub database: Arc<dyn DatabaseOps>,
     pub config: Arc<tokio::sync::RwLock<DaemonConfig>>,
+    /// Cancelled when the server begins shutting down
+    pub shutdown: CancellationToken,
+    /// The running mode, when the API is served alongside one
+    pub mode_handler: Option<Arc<dyn ModeHandler>>,
+    /// Events pushed to every websocket client
+    pub websocket: Arc<WebSocketBroadcaster>,
+    /// sv2d's JSON-RPC endpoint; `None` when the API runs without a daemon
+    pub daemon_rpc_url: Option<String>,
+    /// Banned worker names, shared with the stratum server when served alongside one
+    pub worker_bans: Arc<WorkerBans>,
 }
 
 /// Query parameters for pagination
@@ -47,15 +67,33 @@ pub struct ShareQuery {
     pub pagination: PaginationQuery,
 }
 
-/// Configuration update request
+/// Query parameters for the share time series, in seconds
 #[derive(Debug, Deserialize)]
+pub struct ShareTimeseriesQuery {
+    #[serde(default = "default_timeseries_bucket")]
+    pub bucket: u32,
+    #[serde(default = "default_timeseries_window")]
+    pub window: u32,
+}
+
+fn default_timeseries_bucket() -> u32 {
+    60
+}
+
+fn default_timeseries_window() -> u32 {
+    3600
+}
+
+/// Configuration update request
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ConfigUpdateRequest {
+    #[schema(value_type = Object)]
     pub config: serde_json::Value,
     pub validate_only: Option<bool>,
 }
 
 /// Configuration update response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ConfigUpdateResponse {
     pub success: bool,
     pub message: String,
@@ -63,7 +101,7 @@ pub struct ConfigUpdateResponse {
 }
 
 /// Custom work template request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CustomTemplateRequest {
     pub transactions: Vec<String>, // Hex-encoded transactions
     pub coinbase_data: Option<String>,
@@ -71,10 +109,11 @@ pub struct CustomTemplateRequest {
 }
 
 /// API error response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiError {
     pub error: String,
     pub code: u16,
+    #[schema(value_type = Option<Object>)]
     pub details: Option<serde_json::Value>,
 }
 
@@ -96,6 +135,76 @@ impl ApiError {
     }
 }
 
+/// Build and version information
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VersionInfo {
+    /// sv2-web crate version
+    pub version: String,
+    /// Short git commit the binary was built from, or "unknown"
+    pub git_commit: String,
+    /// When the binary was built
+    pub build_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Version of the Stratum V2 reference implementation (SRI) crates linked in
+    pub sv2_implementation: String,
+}
+
+/// Response header carrying the server version on every API call
+pub const VERSION_HEADER: &str = "x-sv2-version";
+
+/// Version information baked in at compile time
+pub fn version_info() -> VersionInfo {
+    let build_time = env!("SV2_WEB_BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
+
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("SV2_WEB_GIT_COMMIT").to_string(),
+        build_time,
+        sv2_implementation: format!("SRI roles_logic_sv2 {}", env!("SV2_WEB_SRI_VERSION")),
+    }
+}
+
+/// Stamp the server version onto every response
+pub async fn version_header_middleware<B>(request: Request<B>, next: Next<B>) -> Response {
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(
+        VERSION_HEADER,
+        HeaderValue::from_static(env!("CARGO_PKG_VERSION")),
+    );
+    response
+}
+
+/// Get build and version information
+#[utoipa::path(
+    get,
+    path = "/api/v1/version",
+    tag = "status",
+    responses(
+        (status = 200, description = "Build and version information", body = VersionInfo),
+    )
+)]
+pub async fn get_version() -> Json<VersionInfo> {
+    Json(version_info())
+}
+
+/// Fallback for unmatched routes.
+///
+/// Unknown `/api/*` paths get a JSON 404 so API clients never receive HTML.
+/// Anything else is treated as an SPA route and sent to `/`, which serves the
+/// dashboard however it is configured. The redirect is temporary so a
+/// transient misconfiguration doesn't get cached by browsers.
+pub async fn static_file_fallback(uri: Uri) -> Response {
+    let path = uri.path();
+    if path == "/api" || path.starts_with("/api/") {
+        let error = ApiError::new(404, &format!("No API endpoint at {}", path));
+        return (StatusCode::NOT_FOUND, Json(error)).into_response();
+    }
+
+    Redirect::temporary("/").into_response()
+}
+
 /// Serve the main dashboard page
 pub async fn index() -> Html<&'static str> {
     Html(r#"
@@ -182,6 +291,15 @@ pub async fn index() -> Html<&'static str> {
 }
 
 /// Get daemon status via API
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "status",
+    responses(
+        (status = 200, description = "Daemon status", body = DaemonStatus),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_status(State(state): State<AppState>) -> Result<Json<DaemonStatus>, (StatusCode, Json<ApiError>)> {
     // In a real implementation, this would query the actual daemon
     // For now, we'll return mock data with some database stats
@@ -200,6 +318,7 @@ pub async fn get_status(State(state): State<AppState>) -> Result<Json<DaemonStat
                 blocks_found: share_stats.blocks_found,
                 current_difficulty: 1.0, // TODO: Get from config
                 hashrate: share_stats.total_shares as f64 * 1e9, // Mock calculation
+                mode_health: None,
             };
             Ok(Json(status))
         }
@@ -211,6 +330,21 @@ pub async fn get_status(State(state): State<AppState>) -> Result<Json<DaemonStat
 }
 
 /// Get active connections
+#[utoipa::path(
+    get,
+    path = "/api/v1/connections",
+    tag = "connections",
+    params(
+        ("protocol" = Option<String>, Query, description = "Filter by protocol (e.g. sv1, sv2)"),
+        ("state" = Option<String>, Query, description = "Filter by connection state"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+        ("offset" = Option<u32>, Query, description = "Number of items to skip"),
+    ),
+    responses(
+        (status = 200, description = "Active connections", body = Vec<ConnectionInfo>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_connections(
     State(state): State<AppState>,
     Query(query): Query<ConnectionQuery>,
@@ -225,7 +359,8 @@ pub async fn get_connections(
             if let Some(state_filter) = &query.state {
                 connections.retain(|conn| format!("{:?}", conn.state).to_lowercase().contains(&state_filter.to_lowercase()));
             }
-            
+
+            add_connection_quality(&state, &mut connections).await;
             Ok(Json(connections))
         }
         Err(e) => {
@@ -236,12 +371,28 @@ pub async fn get_connections(
 }
 
 /// Get connection by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/connections/{id}",
+    tag = "connections",
+    params(
+        ("id" = Uuid, Path, description = "Connection ID"),
+    ),
+    responses(
+        (status = 200, description = "Connection details", body = ConnectionInfo),
+        (status = 404, description = "Connection not found", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_connection(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ConnectionInfo>, (StatusCode, Json<ApiError>)> {
     match state.database.get_connection(id).await {
-        Ok(Some(connection)) => Ok(Json(connection)),
+        Ok(Some(mut connection)) => {
+            add_connection_quality(&state, std::slice::from_mut(&mut connection)).await;
+            Ok(Json(connection))
+        }
         Ok(None) => {
             let error = ApiError::new(404, "Connection not found");
             Err((StatusCode::NOT_FOUND, Json(error)))
@@ -253,7 +404,30 @@ pub async fn get_connection(
     }
 }
 
+/// Score connections; a failure leaves `quality` unset rather than failing the request
+async fn add_connection_quality(state: &AppState, connections: &mut [ConnectionInfo]) {
+    let config = state.config.read().await.monitoring.connection_quality.clone();
+    if let Err(e) = sv2_core::connection_quality::annotate(state.database.as_ref(), &config, connections).await {
+        tracing::warn!("Failed to score connection quality: {}", e);
+    }
+}
+
 /// Get shares with optional filtering
+#[utoipa::path(
+    get,
+    path = "/api/v1/shares",
+    tag = "shares",
+    params(
+        ("connection_id" = Option<Uuid>, Query, description = "Only include shares from this connection"),
+        ("valid_only" = Option<bool>, Query, description = "Only include valid shares"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+        ("offset" = Option<u32>, Query, description = "Number of items to skip"),
+    ),
+    responses(
+        (status = 200, description = "Recent shares", body = Vec<Share>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_shares(
     State(state): State<AppState>,
     Query(query): Query<ShareQuery>,
@@ -275,6 +449,18 @@ pub async fn get_shares(
 }
 
 /// Get share statistics
+#[utoipa::path(
+    get,
+    path = "/api/v1/shares/stats",
+    tag = "shares",
+    params(
+        ("connection_id" = Option<Uuid>, Query, description = "Only include shares from this connection"),
+    ),
+    responses(
+        (status = 200, description = "Share statistics", body = ShareStats),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_share_stats(
     State(state): State<AppState>,
     Query(query): Query<ShareQuery>,
@@ -288,7 +474,57 @@ pub async fn get_share_stats(
     }
 }
 
+/// Get accepted and rejected shares per time bucket
+#[utoipa::path(
+    get,
+    path = "/api/v1/shares/timeseries",
+    tag = "shares",
+    params(
+        ("bucket" = Option<u32>, Query, description = "Bucket length in seconds (default 60)"),
+        ("window" = Option<u32>, Query, description = "Seconds of history to cover, ending now (default 3600)"),
+    ),
+    responses(
+        (status = 200, description = "Share counts per bucket, oldest first, including empty buckets", body = Vec<ShareBucket>),
+        (status = 400, description = "Bucket or window out of range", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
+pub async fn get_share_timeseries(
+    State(state): State<AppState>,
+    Query(query): Query<ShareTimeseriesQuery>,
+) -> Result<Json<Vec<ShareBucket>>, (StatusCode, Json<ApiError>)> {
+    if query.bucket == 0 || query.window == 0 || query.window.div_ceil(query.bucket) as i64 > MAX_SHARE_BUCKETS {
+        let error = ApiError::new(
+            400,
+            &format!("bucket and window must be positive, with at most {} buckets in the window", MAX_SHARE_BUCKETS),
+        );
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    let window = chrono::Duration::seconds(query.window as i64);
+    match state.database.get_share_timeseries(query.bucket, window).await {
+        Ok(buckets) => Ok(Json(buckets)),
+        Err(e) => {
+            let error = ApiError::new(500, &format!("Failed to get share time series: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        }
+    }
+}
+
 /// Get performance metrics
+#[utoipa::path(
+    get,
+    path = "/api/v1/metrics",
+    tag = "metrics",
+    params(
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+        ("offset" = Option<u32>, Query, description = "Number of items to skip"),
+    ),
+    responses(
+        (status = 200, description = "Performance metrics", body = Vec<PerformanceMetrics>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_metrics(
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
@@ -302,7 +538,62 @@ pub async fn get_metrics(
     }
 }
 
+/// Get recent metrics samples, oldest first
+///
+/// Samples are rebuilt from stored shares at the `[monitoring.metrics.history]`
+/// resolution and capacity, so hashrate and system metrics are not included.
+/// The history is empty when it is disabled.
+#[utoipa::path(
+    get,
+    path = "/api/v1/metrics/history",
+    tag = "metrics",
+    params(
+        ("limit" = Option<u32>, Query, description = "Most recent samples to return (default: all)"),
+    ),
+    responses(
+        (status = 200, description = "Metrics samples, oldest first", body = MetricsHistorySnapshot),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
+pub async fn get_metrics_history(
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<MetricsHistorySnapshot>, (StatusCode, Json<ApiError>)> {
+    let config = state.config.read().await.monitoring.metrics.history.clone();
+    if !config.enabled {
+        return Ok(Json(MetricsHistory::new(&config).snapshot(None)));
+    }
+
+    let bucket_seconds = u32::try_from(config.resolution_secs.max(1)).unwrap_or(u32::MAX);
+    let samples = config.capacity.clamp(1, MAX_HISTORY_SAMPLES) as i64;
+    let window = chrono::Duration::seconds(bucket_seconds as i64 * samples);
+
+    match state.database.get_share_timeseries(bucket_seconds, window).await {
+        Ok(buckets) => {
+            let history = MetricsHistory::from_share_buckets(&config, &buckets);
+            Ok(Json(history.snapshot(query.limit.map(|limit| limit as usize))))
+        }
+        Err(e) => {
+            let error = ApiError::new(500, &format!("Failed to get metrics history: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        }
+    }
+}
+
 /// Get work templates
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates",
+    tag = "templates",
+    params(
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+        ("offset" = Option<u32>, Query, description = "Number of items to skip"),
+    ),
+    responses(
+        (status = 200, description = "Work templates", body = Vec<WorkTemplate>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_templates(
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
@@ -317,6 +608,19 @@ pub async fn get_templates(
 }
 
 /// Get work template by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates/{id}",
+    tag = "templates",
+    params(
+        ("id" = Uuid, Path, description = "Template ID"),
+    ),
+    responses(
+        (status = 200, description = "Work template", body = WorkTemplate),
+        (status = 404, description = "Template not found", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_template(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -335,6 +639,17 @@ pub async fn get_template(
 }
 
 /// Submit custom work template
+#[utoipa::path(
+    post,
+    path = "/api/v1/templates/custom",
+    tag = "templates",
+    request_body = CustomTemplateRequest,
+    responses(
+        (status = 200, description = "Stored work template", body = WorkTemplate),
+        (status = 400, description = "Invalid transaction data", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn submit_custom_template(
     State(state): State<AppState>,
     Json(request): Json<CustomTemplateRequest>,
@@ -390,6 +705,19 @@ pub async fn submit_custom_template(
 }
 
 /// Get system alerts
+#[utoipa::path(
+    get,
+    path = "/api/v1/alerts",
+    tag = "alerts",
+    params(
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+        ("offset" = Option<u32>, Query, description = "Number of items to skip"),
+    ),
+    responses(
+        (status = 200, description = "System alerts", body = Vec<Alert>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_alerts(
     State(state): State<AppState>,
     Query(query): Query<PaginationQuery>,
@@ -403,7 +731,210 @@ pub async fn get_alerts(
     }
 }
 
+/// Get found blocks and their confirmation status, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/blocks",
+    tag = "blocks",
+    params(
+        ("limit" = Option<u32>, Query, description = "Maximum number of items to return"),
+    ),
+    responses(
+        (status = 200, description = "Found blocks", body = Vec<BlockRecord>),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
+pub async fn get_blocks(
+    State(state): State<AppState>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<Vec<BlockRecord>>, (StatusCode, Json<ApiError>)> {
+    match state.database.get_blocks(query.limit).await {
+        Ok(blocks) => Ok(Json(blocks)),
+        Err(e) => {
+            let error = ApiError::new(500, &format!("Failed to get blocks: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        }
+    }
+}
+
+/// A worker's live statistics, as tracked by the running mode
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WorkerStats {
+    pub id: String,
+    pub username: String,
+    pub connection_id: Uuid,
+    /// Estimated hashrate in H/s
+    pub hashrate: f64,
+    pub difficulty: f64,
+    pub valid_shares: u64,
+    pub invalid_shares: u64,
+    /// Last share or other activity on the worker
+    pub last_share_time: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Worker> for WorkerStats {
+    fn from(worker: Worker) -> Self {
+        Self {
+            invalid_shares: worker.shares_submitted.saturating_sub(worker.shares_accepted),
+            valid_shares: worker.shares_accepted,
+            id: worker.id,
+            username: worker.username,
+            connection_id: worker.connection_id,
+            hashrate: worker.hashrate,
+            difficulty: worker.difficulty,
+            last_share_time: worker.last_activity,
+        }
+    }
+}
+
+/// Workers of the running mode, or 503 when no mode is attached
+async fn mode_workers(state: &AppState) -> Result<Vec<Worker>, (StatusCode, Json<ApiError>)> {
+    match &state.mode_handler {
+        Some(handler) => Ok(handler.workers().await),
+        None => {
+            let error = ApiError::new(503, "No mining mode is running");
+            Err((StatusCode::SERVICE_UNAVAILABLE, Json(error)))
+        }
+    }
+}
+
+/// Get per-worker statistics from the running mode
+#[utoipa::path(
+    get,
+    path = "/api/v1/workers",
+    tag = "workers",
+    responses(
+        (status = 200, description = "Workers and their statistics", body = Vec<WorkerStats>),
+        (status = 503, description = "No mining mode is running", body = ApiError),
+    )
+)]
+pub async fn get_workers(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<WorkerStats>>, (StatusCode, Json<ApiError>)> {
+    let mut workers: Vec<WorkerStats> = mode_workers(&state)
+        .await?
+        .into_iter()
+        .map(WorkerStats::from)
+        .collect();
+    workers.sort_by(|a, b| a.username.cmp(&b.username));
+    Ok(Json(workers))
+}
+
+/// Get one worker's statistics by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/workers/{id}",
+    tag = "workers",
+    params(
+        ("id" = String, Path, description = "Worker ID"),
+    ),
+    responses(
+        (status = 200, description = "Worker statistics", body = WorkerStats),
+        (status = 404, description = "Worker not found", body = ApiError),
+        (status = 503, description = "No mining mode is running", body = ApiError),
+    )
+)]
+pub async fn get_worker(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<WorkerStats>, (StatusCode, Json<ApiError>)> {
+    match mode_workers(&state).await?.into_iter().find(|worker| worker.id == id) {
+        Some(worker) => Ok(Json(worker.into())),
+        None => {
+            let error = ApiError::new(404, "Worker not found");
+            Err((StatusCode::NOT_FOUND, Json(error)))
+        }
+    }
+}
+
+/// Body of a worker ban request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct BanWorkerRequest {
+    /// Shown next to the ban by `sv2-cli workers bans`
+    pub reason: Option<String>,
+}
+
+/// List banned workers
+#[utoipa::path(
+    get,
+    path = "/api/v1/workers/bans",
+    tag = "workers",
+    responses(
+        (status = 200, description = "Banned workers, by name", body = Vec<WorkerBan>),
+    )
+)]
+pub async fn get_worker_bans(State(state): State<AppState>) -> Json<Vec<WorkerBan>> {
+    Json(state.worker_bans.list())
+}
+
+/// Reject a worker's shares; other workers on its connection are unaffected
+#[utoipa::path(
+    post,
+    path = "/api/v1/workers/{id}/ban",
+    tag = "workers",
+    params(
+        ("id" = String, Path, description = "Name the worker authorized with"),
+    ),
+    request_body = Option<BanWorkerRequest>,
+    responses(
+        (status = 200, description = "The ban, replacing any earlier reason", body = WorkerBan),
+        (status = 400, description = "Empty worker name", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
+pub async fn ban_worker(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    request: Option<Json<BanWorkerRequest>>,
+) -> Result<Json<WorkerBan>, (StatusCode, Json<ApiError>)> {
+    let reason = request.and_then(|Json(request)| request.reason);
+    match state.worker_bans.ban(state.database.as_ref(), &name, reason).await {
+        Ok(ban) => Ok(Json(ban)),
+        Err(sv2_core::Error::Config(message)) => Err((StatusCode::BAD_REQUEST, Json(ApiError::new(400, &message)))),
+        Err(e) => {
+            let error = ApiError::new(500, &format!("Failed to ban worker: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        }
+    }
+}
+
+/// Accept a banned worker's shares again
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workers/{id}/ban",
+    tag = "workers",
+    params(
+        ("id" = String, Path, description = "Name the worker authorized with"),
+    ),
+    responses(
+        (status = 204, description = "Ban lifted"),
+        (status = 404, description = "Worker is not banned", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
+pub async fn unban_worker(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
+    match state.worker_bans.unban(state.database.as_ref(), &name).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, Json(ApiError::new(404, "Worker is not banned")))),
+        Err(e) => {
+            let error = ApiError::new(500, &format!("Failed to unban worker: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        }
+    }
+}
+
 /// Get current configuration
+#[utoipa::path(
+    get,
+    path = "/api/v1/config",
+    tag = "config",
+    responses(
+        (status = 200, description = "Current daemon configuration", body = Object),
+    )
+)]
 pub async fn get_config(
     State(state): State<AppState>,
 ) -> Result<Json<DaemonConfig>, (StatusCode, Json<ApiError>)> {
@@ -412,6 +943,16 @@ pub async fn get_config(
 }
 
 /// Update configuration
+#[utoipa::path(
+    put,
+    path = "/api/v1/config",
+    tag = "config",
+    request_body = ConfigUpdateRequest,
+    responses(
+        (status = 200, description = "Update or validation result", body = ConfigUpdateResponse),
+        (status = 400, description = "Malformed configuration", body = ApiError),
+    )
+)]
 pub async fn update_config(
     State(state): State<AppState>,
     Json(request): Json<ConfigUpdateRequest>,
@@ -466,12 +1007,52 @@ pub async fn update_config(
     Ok(Json(response))
 }
 
+/// Mining statistics of the running mode for Prometheus to scrape
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Prometheus text exposition, one series per metric labelled with the mode",
+            body = String, content_type = "text/plain; version=0.0.4"),
+        (status = 500, description = "The mode couldn't report statistics", body = ApiError),
+        (status = 503, description = "No mining mode is running", body = ApiError),
+    )
+)]
+pub async fn get_prometheus_metrics(
+    State(state): State<AppState>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), (StatusCode, Json<ApiError>)> {
+    let Some(handler) = &state.mode_handler else {
+        let error = ApiError::new(503, "No mining mode is running");
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error)));
+    };
+    let stats = handler.get_statistics().await.map_err(|e| {
+        let error = ApiError::new(500, &format!("Failed to get mining statistics: {}", e));
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error))
+    })?;
+    let mode = state.config.read().await.get_mode_type().to_string();
+    let connections = state.database.list_connections(None).await.unwrap_or_default().len();
+
+    let body = crate::metrics_exposition::render(&mode, &stats, connections);
+    Ok(([(header::CONTENT_TYPE, crate::metrics_exposition::CONTENT_TYPE)], body))
+}
+
 /// Get mining statistics (aggregated data)
+#[utoipa::path(
+    get,
+    path = "/api/v1/mining/stats",
+    tag = "status",
+    responses(
+        (status = 200, description = "Aggregated mining statistics", body = MiningStats),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn get_mining_stats(
     State(state): State<AppState>,
 ) -> Result<Json<MiningStats>, (StatusCode, Json<ApiError>)> {
     match state.database.get_share_stats(None).await {
         Ok(share_stats) => {
+            let (network_difficulty, expected_blocks) = running_block_odds(&state).await;
             let stats = MiningStats {
                 hashrate: share_stats.total_shares as f64 * 1e12, // Mock calculation
                 shares_per_minute: if let Some(first) = share_stats.first_share {
@@ -490,6 +1071,10 @@ pub async fn get_mining_stats(
                 shares_accepted: share_stats.valid_shares,
                 shares_rejected: share_stats.invalid_shares,
                 blocks_found: 0, // TODO: Get from database
+                blocks_orphaned: 0,
+                expected_blocks,
+                coinbase_tag: None,
+                network_difficulty,
             };
             Ok(Json(stats))
         }
@@ -500,7 +1085,57 @@ pub async fn get_mining_stats(
     }
 }
 
+/// Longest a stats request waits on the node before reporting its values unavailable
+const NODE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Current network difficulty from the configured node, if it answers in time
+async fn fetch_network_difficulty(state: &AppState) -> Option<f64> {
+    let bitcoin_config = state.config.read().await.bitcoin.clone();
+    let client = sv2_core::bitcoin_rpc::BitcoinRpcClient::new(bitcoin_config);
+
+    match tokio::time::timeout(NODE_TIMEOUT, client.get_blockchain_info()).await {
+        Ok(Ok(info)) => Some(info.difficulty),
+        Ok(Err(e)) => {
+            tracing::debug!("Network difficulty unavailable: {}", e);
+            None
+        }
+        Err(_) => {
+            tracing::debug!("Network difficulty unavailable: node didn't answer within {:?}", NODE_TIMEOUT);
+            None
+        }
+    }
+}
+
+/// Network difficulty and expected blocks since start, as the running mode
+/// tracks them share by share. Without a mode there's nothing since start
+/// to report, only the node's difficulty.
+async fn running_block_odds(state: &AppState) -> (Option<f64>, Option<f64>) {
+    let Some(handler) = &state.mode_handler else {
+        return (fetch_network_difficulty(state).await, None);
+    };
+    match handler.get_statistics().await {
+        Ok(stats) => (stats.network_difficulty, stats.expected_blocks),
+        Err(e) => {
+            tracing::debug!("Expected blocks unavailable: {}", e);
+            (None, None)
+        }
+    }
+}
+
 /// Disconnect a connection by ID
+#[utoipa::path(
+    delete,
+    path = "/api/v1/connections/{id}",
+    tag = "connections",
+    params(
+        ("id" = Uuid, Path, description = "Connection ID"),
+    ),
+    responses(
+        (status = 200, description = "Disconnect requested", body = Object),
+        (status = 404, description = "Connection not found", body = ApiError),
+        (status = 500, description = "Internal error", body = ApiError),
+    )
+)]
 pub async fn disconnect_connection(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -531,12 +1166,44 @@ pub async fn disconnect_connection(
 }
 
 /// Health check endpoint
-pub async fn health_check() -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+///
+/// Beside a daemon this reflects sv2d's component health: 503 listing the
+/// unhealthy components when one is Critical or sv2d doesn't answer, and
+/// `degraded` while a component warns or bitcoind is still syncing.
+#[utoipa::path(
+    get,
+    path = "/api/v1/health",
+    tag = "status",
+    responses(
+        (status = 200, description = "Service healthy or degraded", body = Object),
+        (status = 503, description = "A component is critical or sv2d is unreachable", body = Object),
+    )
+)]
+pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let Some(rpc_url) = &state.daemon_rpc_url else {
+        let health = serde_json::json!({
+            "status": "healthy",
+            "timestamp": chrono::Utc::now(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime": 3600 // Mock uptime in seconds
+        });
+        return (StatusCode::OK, Json(health));
+    };
+
+    let daemon = daemon_health::check(rpc_url).await;
+    let status = if daemon.status == Health::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
     let health = serde_json::json!({
-        "status": "healthy",
+        "status": daemon.status,
         "timestamp": chrono::Utc::now(),
         "version": env!("CARGO_PKG_VERSION"),
-        "uptime": 3600 // Mock uptime in seconds
+        "uptime": daemon.daemon_uptime_seconds,
+        "bitcoin_synced": daemon.bitcoin_synced,
+        "unhealthy_components": daemon.unhealthy,
+        "degraded_components": daemon.degraded,
     });
-    Ok(Json(health))
+    (status, Json(health))
 }
\ No newline at end of file
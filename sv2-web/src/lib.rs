@@ -1,7 +1,14 @@
 pub mod auth_middleware;
 pub mod validation_middleware;
+pub mod daemon_health;
 pub mod handlers;
+pub mod metrics_exposition;
 pub mod websocket;
+pub mod openapi;
+pub mod routes;
+pub mod server;
+#[cfg(feature = "embed-assets")]
+pub mod assets;
 
 pub use auth_middleware::*;
 pub use validation_middleware::*;
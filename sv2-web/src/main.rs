@@ -1,7 +1,6 @@
 use axum::{
-    routing::{get, post, put, delete},
+    routing::get,
     Router,
-    http::StatusCode,
     response::Redirect,
     middleware,
 };
@@ -11,19 +10,28 @@ use tower_http::{
     trace::TraceLayer,
 };
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 use sv2_core::{
     database::{DatabasePool, DatabaseOps},
     config::DaemonConfig,
     auth::{AuthSystem, AuthConfig},
     connection_auth::ConnectionAuthManager,
+    worker_bans::WorkerBans,
 };
 
 pub mod auth_middleware;
 pub mod validation_middleware;
+pub mod daemon_health;
 pub mod handlers;
 pub mod websocket;
+pub mod openapi;
+pub mod routes;
+pub mod server;
+#[cfg(feature = "embed-assets")]
+pub mod assets;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -35,7 +43,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|_| "sqlite://sv2d.db".to_string());
     
     info!("Connecting to database: {}", database_url);
-    let database = DatabasePool::new(&database_url, 10).await?;
+    let database = Arc::new(DatabasePool::new(&database_url, 10).await?);
     database.migrate().await?;
     info!("Database initialized successfully");
     
@@ -50,10 +58,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let auth_system = Arc::new(tokio::sync::RwLock::new(AuthSystem::new(auth_config)));
     let connection_auth = Arc::new(ConnectionAuthManager::new(auth_system.clone()));
     
+    // Web server settings
+    let server_config = server::WebServerConfig::from_env();
+    info!("Response compression: {}", if server_config.compression { "enabled" } else { "disabled" });
+    info!("Request timeout: {}s", server_config.request_timeout.as_secs());
+
+    // Cancelled on SIGINT/SIGTERM to begin graceful shutdown
+    let shutdown = CancellationToken::new();
+
     // Create application state
     let app_state = handlers::AppState {
-        database: Arc::new(database) as Arc<dyn DatabaseOps>,
+        database: database.clone() as Arc<dyn DatabaseOps>,
         config,
+        shutdown: shutdown.clone(),
+        // Standalone, so there's no mode handler to ask about workers
+        mode_handler: None,
+        websocket: websocket::create_global_broadcaster(),
+        daemon_rpc_url: server_config.daemon_rpc_url.clone(),
+        worker_bans: Arc::new(WorkerBans::load(database.as_ref()).await?),
     };
     
     // Create authentication middleware state
@@ -66,55 +88,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let validation_middleware_state = validation_middleware::ValidationMiddlewareState::new()
         .map_err(|e| anyhow::anyhow!("Failed to create validation middleware: {}", e))?;
 
-    // Determine static files directory
-    let static_dir = determine_static_dir();
-    info!("Serving static files from: {}", static_dir);
+    // Determine where the dashboard assets come from
+    let static_source = determine_static_source();
+    match &static_source {
+        StaticSource::Directory(dir) => info!("Serving static files from: {} (found)", dir.display()),
+        #[cfg(feature = "embed-assets")]
+        StaticSource::Embedded => info!("Serving static files embedded in the binary"),
+        StaticSource::Missing => warn!(
+            "Static directory not found (looked for ./sv2-web/static and ./static); \
+             static routes are disabled and / serves the built-in dashboard page. The API is unaffected."
+        ),
+    }
 
     // Build the router with all API endpoints
     let app = Router::new()
-        // Root redirect to static index.html
-        .route("/", get(|| async { Redirect::permanent("/static/index.html") }))
-        
         // Dashboard route (alternative access)
         .route("/dashboard", get(handlers::index))
         
-        // API v1 routes
-        .route("/api/v1/status", get(handlers::get_status))
-        .route("/api/v1/health", get(handlers::health_check))
-        
-        // Connection management
-        .route("/api/v1/connections", get(handlers::get_connections))
-        .route("/api/v1/connections/:id", get(handlers::get_connection))
-        .route("/api/v1/connections/:id", delete(handlers::disconnect_connection))
-        
-        // Share management
-        .route("/api/v1/shares", get(handlers::get_shares))
-        .route("/api/v1/shares/stats", get(handlers::get_share_stats))
-        
-        // Metrics and monitoring
-        .route("/api/v1/metrics", get(handlers::get_metrics))
-        .route("/api/v1/mining/stats", get(handlers::get_mining_stats))
-        
-        // Work template management
-        .route("/api/v1/templates", get(handlers::get_templates))
-        .route("/api/v1/templates/:id", get(handlers::get_template))
-        .route("/api/v1/templates/custom", post(handlers::submit_custom_template))
-        
-        // Alert management
-        .route("/api/v1/alerts", get(handlers::get_alerts))
-        
-        // Configuration management
-        .route("/api/v1/config", get(handlers::get_config))
-        .route("/api/v1/config", put(handlers::update_config))
-        
+        // REST API
+        .merge(routes::api_routes());
+
+    let app = match &static_source {
+        StaticSource::Directory(dir) => app
+            // Root redirect to static index.html
+            .route("/", get(|| async { Redirect::temporary("/static/index.html") }))
+            // Static file serving
+            .nest_service("/static", ServeDir::new(dir)),
+        #[cfg(feature = "embed-assets")]
+        StaticSource::Embedded => app.merge(assets::router()),
+        // Without a static directory the redirects would loop to a missing
+        // index.html, so serve the embedded dashboard page instead
+        StaticSource::Missing => app.route("/", get(handlers::index)),
+    };
+
+    // Timeout and compression only wrap the routes above; the websocket is
+    // added after so the long-lived session and its upgrade are never touched
+    let app = server::with_request_timeout(app, server_config.request_timeout);
+    let app = if server_config.compression {
+        app.layer(server::compression_layer())
+    } else {
+        app
+    };
+
+    let app = app
         // WebSocket for real-time updates
         .route("/ws", get(websocket::websocket_handler))
         
-        // Static file serving with proper fallback
-        .nest_service("/static", ServeDir::new(&static_dir))
-        
-        // Fallback handler for SPA routing
-        .fallback(static_file_fallback)
+        // Fallback handler for SPA routing and unknown API paths
+        .fallback(handlers::static_file_fallback)
         
         // Add application state
         .with_state(app_state)
@@ -149,40 +170,113 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("🚀 sv2-web dashboard starting on http://{}", addr);
     info!("📊 Dashboard available at http://{}/", addr);
     info!("🔌 WebSocket endpoint at ws://{}/ws", addr);
-    info!("📡 API documentation at http://{}/api/v1/", addr);
+    info!("📡 API documentation at http://{}/api/v1/openapi.json", addr);
 
     // Start the server
+    let listener = std::net::TcpListener::bind(addr)?;
     info!("Server listening on {}", addr);
-    
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await?;
+
+    let signal_token = shutdown.clone();
+    tokio::spawn(async move {
+        server::shutdown_signal().await;
+        info!("Shutdown signal received, draining in-flight requests");
+        signal_token.cancel();
+    });
+
+    server::serve(listener, app, shutdown, server_config.shutdown_timeout).await?;
+
+    info!("Closing database pool");
+    database.close().await;
+    info!("sv2-web stopped");
 
     Ok(())
 }
 
-/// Determine the static files directory based on the current working directory
-fn determine_static_dir() -> String {
-    let current_dir = std::env::current_dir().unwrap_or_default();
-    
-    // Check if we're in the sv2-web directory
-    let sv2_web_static = current_dir.join("sv2-web").join("static");
-    if sv2_web_static.exists() {
-        return sv2_web_static.to_string_lossy().to_string();
+/// Where the dashboard's static assets are served from
+enum StaticSource {
+    /// Files on disk
+    Directory(PathBuf),
+    /// Files compiled into the binary
+    #[cfg(feature = "embed-assets")]
+    Embedded,
+    /// No assets available
+    #[cfg_attr(feature = "embed-assets", allow(dead_code))]
+    Missing,
+}
+
+/// Determine the static asset source. `SV2_WEB_STATIC_DIR` always wins so
+/// assets can be edited in place during development; otherwise embedded
+/// assets are used when compiled in, and the directory is looked up relative
+/// to the current working directory when they are not.
+fn determine_static_source() -> StaticSource {
+    if let Some(dir) = std::env::var_os("SV2_WEB_STATIC_DIR").map(PathBuf::from) {
+        if dir.is_dir() {
+            return StaticSource::Directory(dir);
+        }
+        warn!("SV2_WEB_STATIC_DIR={} is not a directory, ignoring it", dir.display());
     }
-    
+
+    #[cfg(feature = "embed-assets")]
+    {
+        StaticSource::Embedded
+    }
+
+    #[cfg(not(feature = "embed-assets"))]
+    {
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        match resolve_static_dir(&current_dir) {
+            Some(dir) => StaticSource::Directory(dir),
+            None => StaticSource::Missing,
+        }
+    }
+}
+
+/// Resolve the static files directory relative to `base`, returning `None`
+/// if no candidate directory exists
+#[cfg_attr(feature = "embed-assets", allow(dead_code))]
+fn resolve_static_dir(base: &Path) -> Option<PathBuf> {
+    // Check if we're in the workspace root
+    let sv2_web_static = base.join("sv2-web").join("static");
+    if sv2_web_static.is_dir() {
+        return Some(sv2_web_static);
+    }
+
     // Check if we're already in sv2-web and static exists
-    let local_static = current_dir.join("static");
-    if local_static.exists() {
-        return local_static.to_string_lossy().to_string();
+    let local_static = base.join("static");
+    if local_static.is_dir() {
+        return Some(local_static);
     }
-    
-    // Default to relative static directory
-    "static".to_string()
+
+    None
 }
 
-/// Fallback handler for serving static files (SPA support)
-async fn static_file_fallback() -> Result<Redirect, StatusCode> {
-    // For any unmatched routes, redirect to the main dashboard
-    Ok(Redirect::permanent("/static/index.html"))
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_static_dir_missing() {
+        let base = tempfile::tempdir().unwrap();
+        assert_eq!(resolve_static_dir(base.path()), None);
+    }
+
+    #[test]
+    fn test_resolve_static_dir_prefers_workspace_layout() {
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(base.path().join("sv2-web").join("static")).unwrap();
+        std::fs::create_dir_all(base.path().join("static")).unwrap();
+
+        assert_eq!(
+            resolve_static_dir(base.path()),
+            Some(base.path().join("sv2-web").join("static"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_static_dir_local() {
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(base.path().join("static")).unwrap();
+
+        assert_eq!(resolve_static_dir(base.path()), Some(base.path().join("static")));
+    }
+}
@@ -0,0 +1,147 @@
+//! `GET /metrics` in the Prometheus text exposition format.
+//!
+//! Values come from the running mode's `get_statistics`, so a scrape sees
+//! the same numbers as `/api/v1/mining/stats` does. Every series carries a
+//! single `mode` label; nothing is labelled per connection or per worker,
+//! which keeps the number of series fixed however many miners connect.
+
+use std::fmt::Write;
+use sv2_core::MiningStats;
+
+/// `Content-Type` Prometheus expects for the text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+enum Kind {
+    Gauge,
+    Counter,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Gauge => "gauge",
+            Kind::Counter => "counter",
+        }
+    }
+}
+
+/// Exposition for `stats` from a mode running as `mode`, with `connections`
+/// miners connected
+pub fn render(mode: &str, stats: &MiningStats, connections: usize) -> String {
+    let series = [
+        ("sv2_hashrate", Kind::Gauge, "Estimated hashrate in hashes per second", stats.hashrate),
+        ("sv2_shares_accepted_total", Kind::Counter, "Shares accepted since start", stats.shares_accepted as f64),
+        ("sv2_shares_rejected_total", Kind::Counter, "Shares rejected since start", stats.shares_rejected as f64),
+        ("sv2_connections", Kind::Gauge, "Miner connections", connections as f64),
+        ("sv2_acceptance_rate", Kind::Gauge, "Percentage of submitted shares accepted", stats.acceptance_rate),
+    ];
+
+    let mode = escape_label(mode);
+    let mut output = String::new();
+    for (name, kind, help, value) in series {
+        // Writing to a String can't fail
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind.as_str());
+        let _ = writeln!(output, "{}{{mode=\"{}\"}} {}", name, mode, format_value(value));
+    }
+    output
+}
+
+/// Label values escape backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn stats() -> MiningStats {
+        MiningStats {
+            hashrate: 1.2e12,
+            shares_per_minute: 4.0,
+            acceptance_rate: 97.5,
+            efficiency: 97.5,
+            uptime: Duration::from_secs(3600),
+            shares_accepted: 390,
+            shares_rejected: 10,
+            blocks_found: 0,
+            blocks_orphaned: 0,
+            expected_blocks: None,
+            coinbase_tag: None,
+            network_difficulty: None,
+        }
+    }
+
+    /// Minimal text-format parser: every sample must follow its own `# TYPE`
+    /// line, have well-formed labels and a value that parses as a float
+    fn parse(exposition: &str) -> HashMap<String, (String, Vec<(String, String)>, f64)> {
+        let mut types = HashMap::new();
+        let mut samples = HashMap::new();
+        for line in exposition.lines() {
+            if let Some(help) = line.strip_prefix("# HELP ") {
+                assert!(help.split_once(' ').is_some(), "HELP without text: {}", line);
+            } else if let Some(declaration) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = declaration.split_once(' ').expect("TYPE without a kind");
+                assert!(["gauge", "counter"].contains(&kind), "{}", line);
+                assert!(types.insert(name.to_string(), kind.to_string()).is_none(), "{} declared twice", name);
+            } else {
+                let (series, value) = line.rsplit_once(' ').expect("sample without a value");
+                let (name, labels) = series.split_once('{').expect("sample without labels");
+                let labels: Vec<(String, String)> = labels
+                    .strip_suffix('}')
+                    .expect("unterminated labels")
+                    .split(',')
+                    .map(|pair| {
+                        let (key, value) = pair.split_once('=').expect("label without a value");
+                        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).expect("unquoted label");
+                        (key.to_string(), value.to_string())
+                    })
+                    .collect();
+                let kind = types.get(name).unwrap_or_else(|| panic!("{} has no TYPE", name)).clone();
+                let value: f64 = value.parse().unwrap_or_else(|_| panic!("bad value in {}", line));
+                samples.insert(name.to_string(), (kind, labels, value));
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_exposition_parses() {
+        let samples = parse(&render("solo", &stats(), 3));
+        assert_eq!(samples.len(), 5);
+        for (_, labels, _) in samples.values() {
+            assert_eq!(labels, &[("mode".to_string(), "solo".to_string())]);
+        }
+
+        assert_eq!(samples["sv2_hashrate"].0, "gauge");
+        assert_eq!(samples["sv2_hashrate"].2, 1.2e12);
+        assert_eq!(samples["sv2_shares_accepted_total"].0, "counter");
+        assert_eq!(samples["sv2_shares_accepted_total"].2, 390.0);
+        assert_eq!(samples["sv2_shares_rejected_total"].2, 10.0);
+        assert_eq!(samples["sv2_connections"].2, 3.0);
+        assert_eq!(samples["sv2_acceptance_rate"].2, 97.5);
+    }
+
+    #[test]
+    fn test_special_values_and_labels_are_escaped() {
+        let mut stats = stats();
+        stats.hashrate = f64::NAN;
+        let output = render("so\"lo", &stats, 0);
+        assert!(output.contains("sv2_hashrate{mode=\"so\\\"lo\"} NaN\n"), "{}", output);
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_value(390.0), "390");
+    }
+}
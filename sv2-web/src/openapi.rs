@@ -0,0 +1,109 @@
+//! OpenAPI 3 description of the REST API.
+//!
+//! The document is assembled from the `#[utoipa::path]` annotations on the
+//! handlers, so it is regenerated whenever a handler signature changes.
+
+use axum::Json;
+use sv2_core::{
+    Alert, AlertSeverity, ConnectionInfo, ConnectionState, DaemonStatus, MiningStats,
+    PerformanceMetrics, Protocol, Share, ShareBucket, ShareStats, WorkTemplate,
+};
+use sv2_core::blocks::{BlockRecord, BlockStatus};
+use sv2_core::connection_quality::ConnectionQuality;
+use sv2_core::metrics_history::{MetricsHistorySnapshot, MetricsSample};
+use sv2_core::worker_bans::WorkerBan;
+use utoipa::OpenApi;
+
+use crate::handlers::{
+    self, ApiError, BanWorkerRequest, ConfigUpdateRequest, ConfigUpdateResponse, CustomTemplateRequest,
+    VersionInfo, WorkerStats,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "sv2-web API",
+        description = "REST API for monitoring and configuring the sv2d Stratum V2 daemon"
+    ),
+    paths(
+        handlers::get_status,
+        handlers::health_check,
+        handlers::get_version,
+        handlers::get_connections,
+        handlers::get_connection,
+        handlers::disconnect_connection,
+        handlers::get_shares,
+        handlers::get_share_stats,
+        handlers::get_share_timeseries,
+        handlers::get_metrics,
+        handlers::get_metrics_history,
+        handlers::get_mining_stats,
+        handlers::get_prometheus_metrics,
+        handlers::get_templates,
+        handlers::get_template,
+        handlers::submit_custom_template,
+        handlers::get_alerts,
+        handlers::get_blocks,
+        handlers::get_workers,
+        handlers::get_worker,
+        handlers::get_worker_bans,
+        handlers::ban_worker,
+        handlers::unban_worker,
+        handlers::get_config,
+        handlers::update_config,
+        openapi_json,
+    ),
+    components(schemas(
+        ApiError,
+        ConfigUpdateRequest,
+        ConfigUpdateResponse,
+        CustomTemplateRequest,
+        VersionInfo,
+        WorkerStats,
+        BanWorkerRequest,
+        WorkerBan,
+        DaemonStatus,
+        MiningStats,
+        ConnectionInfo,
+        ConnectionQuality,
+        ConnectionState,
+        Protocol,
+        Share,
+        ShareStats,
+        ShareBucket,
+        PerformanceMetrics,
+        MetricsHistorySnapshot,
+        MetricsSample,
+        WorkTemplate,
+        Alert,
+        AlertSeverity,
+        BlockRecord,
+        BlockStatus,
+    )),
+    tags(
+        (name = "status", description = "Daemon status and health"),
+        (name = "connections", description = "Miner connection management"),
+        (name = "shares", description = "Submitted shares and statistics"),
+        (name = "metrics", description = "Performance metrics"),
+        (name = "templates", description = "Work template management"),
+        (name = "alerts", description = "System alerts"),
+        (name = "blocks", description = "Found blocks and their confirmations"),
+        (name = "workers", description = "Per-worker statistics and bans"),
+        (name = "config", description = "Daemon configuration"),
+        (name = "meta", description = "API description"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Serve the OpenAPI document
+#[utoipa::path(
+    get,
+    path = "/api/v1/openapi.json",
+    tag = "meta",
+    responses(
+        (status = 200, description = "OpenAPI 3 document for this API", body = Object),
+    )
+)]
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
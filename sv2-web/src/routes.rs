@@ -0,0 +1,68 @@
+//! REST API routes.
+//!
+//! Every route registered here must have a matching `#[utoipa::path]`
+//! annotation and be listed in [`crate::openapi::ApiDoc`].
+
+use axum::{
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::handlers::{self, AppState};
+use crate::openapi;
+
+/// Build the `/api/v1` router
+pub fn api_routes() -> Router<AppState> {
+    Router::new()
+        // API v1 routes
+        .route("/api/v1/status", get(handlers::get_status))
+        .route("/api/v1/health", get(handlers::health_check))
+        .route("/api/v1/version", get(handlers::get_version))
+        
+        // Connection management
+        .route("/api/v1/connections", get(handlers::get_connections))
+        .route("/api/v1/connections/:id", get(handlers::get_connection))
+        .route("/api/v1/connections/:id", delete(handlers::disconnect_connection))
+        
+        // Share management
+        .route("/api/v1/shares", get(handlers::get_shares))
+        .route("/api/v1/shares/stats", get(handlers::get_share_stats))
+        .route("/api/v1/shares/timeseries", get(handlers::get_share_timeseries))
+        
+        // Metrics and monitoring
+        .route("/api/v1/metrics", get(handlers::get_metrics))
+        .route("/api/v1/metrics/history", get(handlers::get_metrics_history))
+        .route("/api/v1/mining/stats", get(handlers::get_mining_stats))
+        .route("/metrics", get(handlers::get_prometheus_metrics))
+        
+        // Work template management
+        .route("/api/v1/templates", get(handlers::get_templates))
+        .route("/api/v1/templates/:id", get(handlers::get_template))
+        .route("/api/v1/templates/custom", post(handlers::submit_custom_template))
+        
+        // Alert management
+        .route("/api/v1/alerts", get(handlers::get_alerts))
+        
+        // Found blocks
+        .route("/api/v1/blocks", get(handlers::get_blocks))
+        
+        // Per-worker statistics from the running mode
+        .route("/api/v1/workers", get(handlers::get_workers))
+        .route("/api/v1/workers/:id", get(handlers::get_worker))
+        
+        // Worker bans; the name shares the `:id` segment with the route above
+        .route("/api/v1/workers/bans", get(handlers::get_worker_bans))
+        .route("/api/v1/workers/:id/ban", post(handlers::ban_worker))
+        .route("/api/v1/workers/:id/ban", delete(handlers::unban_worker))
+        
+        // Configuration management
+        .route("/api/v1/config", get(handlers::get_config))
+        .route("/api/v1/config", put(handlers::update_config))
+        
+        // API description
+        .route("/api/v1/openapi.json", get(openapi::openapi_json))
+        
+        // Version header on every API response
+        .layer(middleware::from_fn(handlers::version_header_middleware))
+}
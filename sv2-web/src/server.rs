@@ -0,0 +1,195 @@
+//! HTTP server settings, the middleware built from them, and the serve loop.
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    http::StatusCode,
+    BoxError, Json, Router,
+};
+use std::net::TcpListener;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceBuilder;
+
+use crate::handlers::ApiError;
+use tower_http::compression::{
+    predicate::{DefaultPredicate, NotForContentType, Predicate},
+    CompressionLayer,
+};
+
+/// Web server settings, read from `SV2_WEB_*` environment variables
+#[derive(Debug, Clone)]
+pub struct WebServerConfig {
+    /// Compress responses for clients that accept gzip or brotli.
+    /// Disable on CPU-constrained hosts (`SV2_WEB_COMPRESSION=false`).
+    pub compression: bool,
+    /// How long in-flight requests get to finish once shutdown starts
+    /// (`SV2_WEB_SHUTDOWN_TIMEOUT_SECS`)
+    pub shutdown_timeout: Duration,
+    /// Maximum time a request may take before it is answered with 503
+    /// (`SV2_WEB_REQUEST_TIMEOUT_SECS`)
+    pub request_timeout: Duration,
+    /// sv2d's JSON-RPC endpoint, asked for component health by
+    /// `/api/v1/health` (`SV2_WEB_DAEMON_RPC_URL`, empty to not ask)
+    pub daemon_rpc_url: Option<String>,
+}
+
+impl Default for WebServerConfig {
+    fn default() -> Self {
+        Self {
+            compression: true,
+            shutdown_timeout: Duration::from_secs(30),
+            request_timeout: Duration::from_secs(30),
+            daemon_rpc_url: Some("http://127.0.0.1:8333".to_string()),
+        }
+    }
+}
+
+impl WebServerConfig {
+    /// Load settings from the environment, falling back to defaults for
+    /// unset or unparseable values
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            compression: env_flag("SV2_WEB_COMPRESSION").unwrap_or(defaults.compression),
+            shutdown_timeout: env_secs("SV2_WEB_SHUTDOWN_TIMEOUT_SECS")
+                .unwrap_or(defaults.shutdown_timeout),
+            request_timeout: env_secs("SV2_WEB_REQUEST_TIMEOUT_SECS")
+                .unwrap_or(defaults.request_timeout),
+            daemon_rpc_url: match std::env::var("SV2_WEB_DAEMON_RPC_URL") {
+                Ok(url) if url.trim().is_empty() => None,
+                Ok(url) => Some(url.trim().to_string()),
+                Err(_) => defaults.daemon_rpc_url,
+            },
+        }
+    }
+}
+
+fn env_secs(name: &str) -> Option<Duration> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid value {}={:?}, expected whole seconds", name, value);
+            None
+        }
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => {
+            tracing::warn!("Ignoring invalid value {}={:?}, expected true or false", name, value);
+            None
+        }
+    }
+}
+
+/// Gzip/brotli compression for API and dashboard responses.
+///
+/// Skips small bodies, images, server-sent events and formats that are
+/// already compressed, where recompressing only burns CPU.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = DefaultPredicate::new()
+        .and(NotForContentType::const_new("font/woff"))
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/zip"));
+
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(predicate)
+}
+
+/// Fail requests on `router` that take longer than `timeout` with a JSON 503,
+/// so a stuck handler (e.g. a blocked database call) can't hold connections
+/// open indefinitely. Only routes already added to `router` are affected.
+pub fn with_request_timeout<S>(router: Router<S>, timeout: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(handle_timeout_error))
+            .timeout(timeout),
+    )
+}
+
+async fn handle_timeout_error(err: BoxError) -> (StatusCode, Json<ApiError>) {
+    if err.is::<tower::timeout::error::Elapsed>() {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new(503, "Request timed out")),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(500, &format!("Unhandled internal error: {}", err))),
+        )
+    }
+}
+
+/// Resolve once SIGINT (Ctrl+C) or, on Unix, SIGTERM is received
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Serve `app` on `listener` until `shutdown` is cancelled.
+///
+/// Once cancelled the server stops accepting connections and waits up to
+/// `grace` for in-flight requests to finish before returning. Websocket
+/// sessions watch the same token and close themselves.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: CancellationToken,
+    grace: Duration,
+) -> Result<(), hyper::Error> {
+    let server = axum::Server::from_tcp(listener)?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+
+    let deadline = async {
+        shutdown.cancelled().await;
+        tokio::time::sleep(grace).await;
+    };
+
+    tokio::select! {
+        result = server => result,
+        _ = deadline => {
+            tracing::warn!(
+                "In-flight requests did not finish within {}s, shutting down anyway",
+                grace.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
@@ -258,10 +258,16 @@ fn validate_config_json(json: &serde_json::Value, validator: &InputValidator) ->
             }
             Some("Client") => {
                 if let Some(client_config) = mode.get("config") {
-                    if let Some(upstream_pool) = client_config.get("upstream_pool") {
-                        if let Some(url) = upstream_pool.get("url").and_then(|v| v.as_str()) {
+                    // `upstream_pools`, or a single `upstream_pool`
+                    let upstream_pools = match client_config.get("upstream_pools").or_else(|| client_config.get("upstream_pool")) {
+                        Some(serde_json::Value::Array(pools)) => pools.iter().collect(),
+                        Some(pool) => vec![pool],
+                        None => vec![],
+                    };
+                    for (i, pool) in upstream_pools.into_iter().enumerate() {
+                        if let Some(url) = pool.get("url").and_then(|v| v.as_str()) {
                             validator.validate_url(url)
-                                .map_err(|e| format!("Invalid client upstream URL: {}", e))?;
+                                .map_err(|e| format!("Invalid client upstream pool {} URL: {}", i, e))?;
                         }
                     }
                 }
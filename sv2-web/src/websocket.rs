@@ -2,11 +2,12 @@ use axum::{
     extract::{WebSocketUpgrade, State},
     response::Response,
 };
-use axum::extract::ws::{WebSocket, Message};
+use axum::extract::ws::{close_code, CloseFrame, WebSocket, Message};
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::{interval, Duration};
 use tracing::{info, warn, error};
 use uuid::Uuid;
@@ -46,13 +47,82 @@ pub enum WebSocketMessage {
     Subscribed { subscriptions: Vec<String> },
 }
 
+impl WebSocketMessage {
+    /// Channel the message is sent on; `None` for replies and keepalives,
+    /// which every client gets
+    pub fn channel(&self) -> Option<WebSocketChannel> {
+        match self {
+            WebSocketMessage::Status(_)
+            | WebSocketMessage::MetricsUpdate(_)
+            | WebSocketMessage::MiningStatsUpdate(_) => Some(WebSocketChannel::Metrics),
+            WebSocketMessage::ConnectionAdded(_)
+            | WebSocketMessage::ConnectionUpdated(_)
+            | WebSocketMessage::ConnectionRemoved { .. } => Some(WebSocketChannel::Connections),
+            WebSocketMessage::ShareSubmitted(_) => Some(WebSocketChannel::Shares),
+            WebSocketMessage::AlertCreated(_) | WebSocketMessage::AlertResolved { .. } => {
+                Some(WebSocketChannel::Alerts)
+            }
+            WebSocketMessage::Heartbeat { .. }
+            | WebSocketMessage::Error { .. }
+            | WebSocketMessage::Subscribed { .. } => None,
+        }
+    }
+}
+
+/// Group of events a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketChannel {
+    Shares,
+    Connections,
+    Metrics,
+    Alerts,
+}
+
+impl WebSocketChannel {
+    pub const ALL: [WebSocketChannel; 4] = [
+        WebSocketChannel::Shares,
+        WebSocketChannel::Connections,
+        WebSocketChannel::Metrics,
+        WebSocketChannel::Alerts,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebSocketChannel::Shares => "shares",
+            WebSocketChannel::Connections => "connections",
+            WebSocketChannel::Metrics => "metrics",
+            WebSocketChannel::Alerts => "alerts",
+        }
+    }
+
+    /// Channels named in a request, where `*` names all of them
+    pub fn parse_all(names: &[String]) -> Result<Vec<WebSocketChannel>, String> {
+        let mut channels = Vec::new();
+        for name in names {
+            if name == "*" {
+                channels.extend(Self::ALL);
+                continue;
+            }
+            match Self::ALL.iter().find(|channel| channel.as_str() == name) {
+                Some(channel) => channels.push(*channel),
+                None => {
+                    let valid: Vec<&str> = Self::ALL.iter().map(|c| c.as_str()).collect();
+                    return Err(format!("Unknown channel '{}', expected one of {} or *", name, valid.join(", ")));
+                }
+            }
+        }
+        Ok(channels)
+    }
+}
+
 /// WebSocket subscription request
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "action")]
 pub enum WebSocketRequest {
-    /// Subscribe to specific event types
+    /// Subscribe to channels: shares, connections, metrics, alerts or *
     Subscribe { events: Vec<String> },
-    /// Unsubscribe from event types
+    /// Unsubscribe from channels
     Unsubscribe { events: Vec<String> },
     /// Get current status
     GetStatus,
@@ -64,9 +134,12 @@ pub enum WebSocketRequest {
 #[derive(Debug, Clone)]
 pub struct WebSocketSession {
     pub id: Uuid,
-    pub subscriptions: std::collections::HashSet<String>,
+    pub subscriptions: HashSet<WebSocketChannel>,
     pub connected_at: chrono::DateTime<chrono::Utc>,
     pub last_activity: chrono::DateTime<chrono::Utc>,
+    /// Whether the client has chosen its channels; until it does it gets
+    /// all of them, as clients from before subscriptions expect
+    explicit: bool,
 }
 
 impl WebSocketSession {
@@ -74,14 +147,44 @@ impl WebSocketSession {
         let now = chrono::Utc::now();
         Self {
             id: Uuid::new_v4(),
-            subscriptions: std::collections::HashSet::new(),
+            subscriptions: WebSocketChannel::ALL.into_iter().collect(),
             connected_at: now,
             last_activity: now,
+            explicit: false,
+        }
+    }
+
+    /// Add `channels`; the first subscribe replaces the default of all
+    /// channels rather than adding to it
+    pub fn subscribe(&mut self, channels: &[WebSocketChannel]) {
+        if !self.explicit {
+            self.subscriptions.clear();
+            self.explicit = true;
         }
+        self.subscriptions.extend(channels.iter().copied());
+    }
+
+    pub fn unsubscribe(&mut self, channels: &[WebSocketChannel]) {
+        self.explicit = true;
+        for channel in channels {
+            self.subscriptions.remove(channel);
+        }
+    }
+
+    pub fn is_subscribed(&self, channel: WebSocketChannel) -> bool {
+        self.subscriptions.contains(&channel)
+    }
+
+    /// Whether `message` should be sent to this client
+    pub fn wants(&self, message: &WebSocketMessage) -> bool {
+        message.channel().map_or(true, |channel| self.is_subscribed(channel))
     }
 
-    pub fn is_subscribed(&self, event_type: &str) -> bool {
-        self.subscriptions.contains(event_type) || self.subscriptions.contains("*")
+    /// Subscribed channel names, sorted, for the `Subscribed` reply
+    pub fn subscription_names(&self) -> Vec<String> {
+        let mut channels: Vec<WebSocketChannel> = self.subscriptions.iter().copied().collect();
+        channels.sort();
+        channels.into_iter().map(|channel| channel.as_str().to_string()).collect()
     }
 
     pub fn update_activity(&mut self) {
@@ -94,6 +197,12 @@ pub struct WebSocketBroadcaster {
     sender: broadcast::Sender<WebSocketMessage>,
 }
 
+impl Default for WebSocketBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WebSocketBroadcaster {
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(1000);
@@ -160,45 +269,48 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     let session_id = session.read().await.id;
     info!("WebSocket client connected: {}", session_id);
 
-    // Create a broadcaster for this connection (in a real implementation, this would be shared)
-    let broadcaster = Arc::new(WebSocketBroadcaster::new());
-    let mut receiver = broadcaster.subscribe();
+    // Events shared by every client, and replies meant for this one only
+    let mut receiver = state.websocket.subscribe();
+    let (direct, mut direct_receiver) = mpsc::unbounded_channel();
 
     // Split the socket into sender and receiver
     let (sender, mut receiver_ws) = socket.split();
     let sender = Arc::new(tokio::sync::Mutex::new(sender));
 
     // Start periodic status updates
-    let broadcaster_clone = broadcaster.clone();
+    let direct_clone = direct.clone();
     let state_clone = state.clone();
-    let status_task = tokio::spawn(async move {
+    let mut status_task = tokio::spawn(async move {
         let mut interval = interval(Duration::from_secs(5));
         loop {
             interval.tick().await;
             
-            // Get current status and broadcast it
-            match get_current_status(&state_clone).await {
-                Ok(status) => broadcaster_clone.broadcast_status(status),
+            // Get current status and send it
+            let message = match get_current_status(&state_clone).await {
+                Ok(status) => WebSocketMessage::Status(status),
                 Err(e) => {
-                    error!("Failed to get status for WebSocket broadcast: {}", e);
-                    broadcaster_clone.broadcast(WebSocketMessage::Error {
+                    error!("Failed to get status for WebSocket client: {}", e);
+                    WebSocketMessage::Error {
                         message: format!("Failed to get status: {}", e),
-                    });
+                    }
                 }
-            }
+            };
 
             // Send heartbeat
-            broadcaster_clone.broadcast(WebSocketMessage::Heartbeat {
+            let heartbeat = WebSocketMessage::Heartbeat {
                 timestamp: chrono::Utc::now(),
-            });
+            };
+            if direct_clone.send(message).is_err() || direct_clone.send(heartbeat).is_err() {
+                break;
+            }
         }
     });
 
     // Handle incoming messages from client
-    let broadcaster_clone = broadcaster.clone();
+    let direct_clone = direct.clone();
     let session_clone = session.clone();
     let sender_clone = sender.clone();
-    let incoming_task = tokio::spawn(async move {
+    let mut incoming_task = tokio::spawn(async move {
         while let Some(msg) = receiver_ws.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
@@ -210,11 +322,11 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                     match serde_json::from_str::<WebSocketRequest>(&text) {
                         Ok(request) => {
                             let mut session_guard = session_clone.write().await;
-                            handle_websocket_request(request, &mut session_guard, &broadcaster_clone).await;
+                            handle_websocket_request(request, &mut session_guard, &direct_clone);
                         }
                         Err(e) => {
                             warn!("Invalid WebSocket request: {}", e);
-                            broadcaster_clone.broadcast(WebSocketMessage::Error {
+                            let _ = direct_clone.send(WebSocketMessage::Error {
                                 message: format!("Invalid request format: {}", e),
                             });
                         }
@@ -222,7 +334,7 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                 }
                 Ok(Message::Binary(_)) => {
                     // Binary messages not supported for now
-                    broadcaster_clone.broadcast(WebSocketMessage::Error {
+                    let _ = direct_clone.send(WebSocketMessage::Error {
                         message: "Binary messages not supported".to_string(),
                     });
                 }
@@ -254,81 +366,97 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Handle outgoing messages to client
     let session_clone = session.clone();
     let sender_clone = sender.clone();
-    let outgoing_task = tokio::spawn(async move {
-        while let Ok(message) = receiver.recv().await {
-            // Check if client is subscribed to this message type
-            let message_type = match &message {
-                WebSocketMessage::Status(_) => "status",
-                WebSocketMessage::ConnectionAdded(_) => "connection",
-                WebSocketMessage::ConnectionUpdated(_) => "connection",
-                WebSocketMessage::ConnectionRemoved { .. } => "connection",
-                WebSocketMessage::ShareSubmitted(_) => "share",
-                WebSocketMessage::MetricsUpdate(_) => "metrics",
-                WebSocketMessage::MiningStatsUpdate(_) => "mining_stats",
-                WebSocketMessage::AlertCreated(_) => "alert",
-                WebSocketMessage::AlertResolved { .. } => "alert",
-                WebSocketMessage::Heartbeat { .. } => "heartbeat",
-                WebSocketMessage::Error { .. } => "error",
-                WebSocketMessage::Subscribed { .. } => "system",
-            };
-
-            let is_subscribed = {
-                let session_guard = session_clone.read().await;
-                session_guard.is_subscribed(message_type)
-            };
-
-            if is_subscribed {
-                let json = match serde_json::to_string(&message) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        error!("Failed to serialize WebSocket message: {}", e);
+    let mut outgoing_task = tokio::spawn(async move {
+        loop {
+            let message = tokio::select! {
+                received = receiver.recv() => match received {
+                    Ok(message) => message,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client {} fell behind and missed {} events", session_id, skipped);
                         continue;
                     }
-                };
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(message) = direct_receiver.recv() => message,
+            };
 
-                let mut sender_guard = sender_clone.lock().await;
-                if let Err(e) = sender_guard.send(Message::Text(json)).await {
-                    error!("Failed to send WebSocket message: {}", e);
-                    break;
+            // Only send channels the client is subscribed to
+            if !session_clone.read().await.wants(&message) {
+                continue;
+            }
+
+            let json = match serde_json::to_string(&message) {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Failed to serialize WebSocket message: {}", e);
+                    continue;
                 }
+            };
+
+            let mut sender_guard = sender_clone.lock().await;
+            if let Err(e) = sender_guard.send(Message::Text(json)).await {
+                error!("Failed to send WebSocket message: {}", e);
+                break;
             }
         }
     });
 
     // Wait for any task to complete (indicating connection closed or error)
+    // or for the server to start shutting down
     tokio::select! {
-        _ = status_task => {},
-        _ = incoming_task => {},
-        _ = outgoing_task => {},
+        _ = &mut status_task => {},
+        _ = &mut incoming_task => {},
+        _ = &mut outgoing_task => {},
+        _ = state.shutdown.cancelled() => {
+            let close = Message::Close(Some(CloseFrame {
+                code: close_code::AWAY,
+                reason: "Server shutting down".into(),
+            }));
+            if let Err(e) = sender.lock().await.send(close).await {
+                warn!("Failed to send close frame to {}: {}", session_id, e);
+            }
+        }
     }
 
+    status_task.abort();
+    incoming_task.abort();
+    outgoing_task.abort();
+
     info!("WebSocket connection closed: {}", session_id);
 }
 
-async fn handle_websocket_request(
+/// Handle a client request; replies go to `direct`, which only this client
+/// reads. A send only fails once the client has gone, so those are ignored.
+fn handle_websocket_request(
     request: WebSocketRequest,
     session: &mut WebSocketSession,
-    broadcaster: &WebSocketBroadcaster,
+    direct: &mpsc::UnboundedSender<WebSocketMessage>,
 ) {
     match request {
-        WebSocketRequest::Subscribe { events } => {
-            for event in &events {
-                session.subscriptions.insert(event.clone());
+        WebSocketRequest::Subscribe { events } => match WebSocketChannel::parse_all(&events) {
+            Ok(channels) => {
+                session.subscribe(&channels);
+                let _ = direct.send(WebSocketMessage::Subscribed {
+                    subscriptions: session.subscription_names(),
+                });
+                info!("Client {} subscribed to channels: {:?}", session.id, events);
             }
-            broadcaster.broadcast(WebSocketMessage::Subscribed {
-                subscriptions: session.subscriptions.iter().cloned().collect(),
-            });
-            info!("Client {} subscribed to events: {:?}", session.id, events);
-        }
-        WebSocketRequest::Unsubscribe { events } => {
-            for event in &events {
-                session.subscriptions.remove(event);
+            Err(message) => {
+                let _ = direct.send(WebSocketMessage::Error { message });
             }
-            broadcaster.broadcast(WebSocketMessage::Subscribed {
-                subscriptions: session.subscriptions.iter().cloned().collect(),
-            });
-            info!("Client {} unsubscribed from events: {:?}", session.id, events);
-        }
+        },
+        WebSocketRequest::Unsubscribe { events } => match WebSocketChannel::parse_all(&events) {
+            Ok(channels) => {
+                session.unsubscribe(&channels);
+                let _ = direct.send(WebSocketMessage::Subscribed {
+                    subscriptions: session.subscription_names(),
+                });
+                info!("Client {} unsubscribed from channels: {:?}", session.id, events);
+            }
+            Err(message) => {
+                let _ = direct.send(WebSocketMessage::Error { message });
+            }
+        },
         WebSocketRequest::GetStatus => {
             // This would typically get status from the daemon
             // For now, we'll send a mock status
@@ -344,11 +472,12 @@ async fn handle_websocket_request(
                 blocks_found: 0,
                 current_difficulty: 1.0,
                 hashrate: 0.0,
+                mode_health: None,
             };
-            broadcaster.broadcast_status(status);
+            let _ = direct.send(WebSocketMessage::Status(status));
         }
         WebSocketRequest::Ping => {
-            broadcaster.broadcast(WebSocketMessage::Heartbeat {
+            let _ = direct.send(WebSocketMessage::Heartbeat {
                 timestamp: chrono::Utc::now(),
             });
         }
@@ -371,6 +500,7 @@ async fn get_current_status(state: &AppState) -> Result<DaemonStatus, Box<dyn st
         blocks_found: share_stats.blocks_found,
         current_difficulty: 1.0,
         hashrate: share_stats.total_shares as f64 * 1e9, // Mock calculation
+        mode_health: None,
     })
 }
 
@@ -429,26 +559,44 @@ mod tests {
     #[test]
     fn test_websocket_session_creation() {
         let session = WebSocketSession::new();
-        assert!(session.subscriptions.is_empty());
+        assert_eq!(session.subscriptions.len(), WebSocketChannel::ALL.len());
         assert!(session.connected_at <= chrono::Utc::now());
     }
 
     #[test]
     fn test_websocket_session_subscriptions() {
         let mut session = WebSocketSession::new();
-        
-        // Initially not subscribed to anything
-        assert!(!session.is_subscribed("status"));
-        
-        // Subscribe to specific event
-        session.subscriptions.insert("status".to_string());
-        assert!(session.is_subscribed("status"));
-        assert!(!session.is_subscribed("connection"));
-        
-        // Subscribe to all events
-        session.subscriptions.insert("*".to_string());
-        assert!(session.is_subscribed("connection"));
-        assert!(session.is_subscribed("share"));
+        let alert = WebSocketMessage::AlertResolved { id: Uuid::new_v4() };
+        let removed = WebSocketMessage::ConnectionRemoved { id: Uuid::new_v4() };
+        let heartbeat = WebSocketMessage::Heartbeat { timestamp: chrono::Utc::now() };
+
+        // Everything until the client picks channels
+        assert!(session.wants(&alert));
+        assert!(session.wants(&removed));
+
+        // The first subscribe narrows the default, later ones add to it
+        session.subscribe(&[WebSocketChannel::Alerts]);
+        assert!(session.wants(&alert));
+        assert!(!session.wants(&removed));
+        assert!(session.wants(&heartbeat));
+        session.subscribe(&[WebSocketChannel::Connections]);
+        assert_eq!(session.subscription_names(), ["connections", "alerts"]);
+
+        session.unsubscribe(&[WebSocketChannel::Alerts]);
+        assert!(!session.wants(&alert));
+        assert!(session.wants(&removed));
+    }
+
+    #[test]
+    fn test_channel_names() {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            WebSocketChannel::parse_all(&names(&["shares", "metrics"])).unwrap(),
+            [WebSocketChannel::Shares, WebSocketChannel::Metrics]
+        );
+        assert_eq!(WebSocketChannel::parse_all(&names(&["*"])).unwrap(), WebSocketChannel::ALL);
+        let error = WebSocketChannel::parse_all(&names(&["shares", "share"])).unwrap_err();
+        assert!(error.contains("Unknown channel 'share'"), "{}", error);
     }
 
     #[tokio::test]
@@ -469,6 +617,7 @@ mod tests {
             blocks_found: 1,
             current_difficulty: 1.0,
             hashrate: 1e12,
+            mode_health: None,
         };
         
         broadcaster.broadcast_status(test_status.clone());
@@ -498,6 +647,7 @@ mod tests {
             blocks_found: 1,
             current_difficulty: 1.0,
             hashrate: 1e12,
+            mode_health: None,
         };
         
         let message = WebSocketMessage::Status(status);
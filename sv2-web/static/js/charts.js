@@ -480,7 +480,7 @@ class ShareManager {
                 <tr>
                     <td>${time}</td>
                     <td>${share.connection_id.substring(0, 8)}...</td>
-                    <td>${share.difficulty.toFixed(2)}</td>
+                    <td>${formatDifficulty(share.difficulty)}</td>
                     <td>${validBadge}</td>
                     <td>${blockBadge}</td>
                 </tr>
@@ -97,7 +97,8 @@ class DashboardManager {
             // Load current configuration
             await this.loadConfiguration();
             
-            // Load initial status
+            // Load initial status; mining stats first for the network difficulty
+            await this.loadMiningStats();
             await this.loadStatus();
             
             // Load connections
@@ -120,6 +121,7 @@ class DashboardManager {
             const response = await fetch('/api/v1/config');
             if (response.ok) {
                 this.currentConfig = await response.json();
+                window.difficultyDisplay.unit = this.currentConfig.monitoring.difficulty_unit || 'diff1';
             }
         } catch (error) {
             console.error('Failed to load configuration:', error);
@@ -138,6 +140,22 @@ class DashboardManager {
         }
     }
 
+    async loadMiningStats() {
+        try {
+            const response = await fetch('/api/v1/mining/stats');
+            if (response.ok) {
+                const stats = await response.json();
+                window.difficultyDisplay.networkDifficulty = stats.network_difficulty;
+                // Cumulative block probability of accepted shares since start
+                // null when the node couldn't be reached, which isn't the same as 0
+                this.updateElement('expected-blocks', stats.expected_blocks == null
+                    ? 'unavailable' : stats.expected_blocks.toFixed(6));
+            }
+        } catch (error) {
+            console.error('Failed to load mining stats:', error);
+        }
+    }
+
     async loadConnections() {
         try {
             const response = await fetch('/api/v1/connections');
@@ -190,7 +208,7 @@ class DashboardManager {
         // Update status cards
         this.updateElement('uptime', this.formatDuration(status.uptime));
         this.updateElement('connections', status.connections);
-        this.updateElement('difficulty', status.current_difficulty.toFixed(2));
+        this.updateElement('difficulty', formatDifficulty(status.current_difficulty));
         this.updateElement('blocks-found', status.blocks_found);
         
         // Update metrics
@@ -212,6 +230,7 @@ class DashboardManager {
             if (!window.wsManager || !window.wsManager.isConnected()) {
                 await this.loadStatus();
             }
+            await this.loadMiningStats();
         }, 30000);
     }
 
@@ -304,6 +323,7 @@ class DashboardManager {
             
             if (result.success) {
                 this.currentConfig = config;
+                window.difficultyDisplay.unit = config.monitoring.difficulty_unit || 'diff1';
                 this.showConfigSuccess('Configuration saved successfully');
                 
                 // Close modal after a short delay
@@ -1,5 +1,22 @@
 // WebSocket connection management for real-time updates
 
+// Difficulty display unit (monitoring.difficulty_unit) and the last network
+// difficulty the daemon reported; set by the dashboard and stats updates
+window.difficultyDisplay = { unit: 'diff1', networkDifficulty: null };
+
+// Mirrors sv2_core::difficulty_unit::DifficultyUnit::format
+function formatDifficulty(difficulty) {
+    const { unit, networkDifficulty } = window.difficultyDisplay;
+    if (unit === 'network' && networkDifficulty > 0) {
+        const percent = difficulty / networkDifficulty * 100;
+        const decimals = percent === 0 ? 0 : Math.max(0, 2 - Math.floor(Math.log10(Math.abs(percent))));
+        return `${percent.toFixed(decimals)}% of network`;
+    }
+    const suffixes = [[1e15, 'P'], [1e12, 'T'], [1e9, 'G'], [1e6, 'M'], [1e3, 'K']];
+    const match = suffixes.find(([scale]) => difficulty >= scale);
+    return match ? `${(difficulty / match[0]).toFixed(2)}${match[1]}` : difficulty.toFixed(2);
+}
+
 class WebSocketManager {
     constructor(url = 'ws://localhost:8080/ws') {
         this.url = url;
@@ -180,7 +197,7 @@ class WebSocketManager {
         // Update status display
         this.updateElement('uptime', this.formatDuration(status.uptime));
         this.updateElement('connections', status.connections);
-        this.updateElement('difficulty', status.current_difficulty.toFixed(2));
+        this.updateElement('difficulty', formatDifficulty(status.current_difficulty));
         this.updateElement('blocks-found', status.blocks_found);
         this.updateElement('hashrate', this.formatHashrate(status.hashrate));
         this.updateElement('total-shares', status.total_shares);
@@ -237,6 +254,9 @@ class WebSocketManager {
 
     handleMiningStatsUpdate(stats) {
         console.log('Mining stats updated:', stats);
+        if (stats.network_difficulty) {
+            window.difficultyDisplay.networkDifficulty = stats.network_difficulty;
+        }
         this.updateElement('efficiency', `${(stats.efficiency * 100).toFixed(1)}%`);
         this.updateElement('shares-per-minute', stats.shares_per_minute.toFixed(1));
     }
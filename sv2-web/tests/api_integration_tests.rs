@@ -14,6 +14,7 @@ use sv2_core::{
     types::{ConnectionInfo, Share, WorkTemplate, Alert, AlertLevel, Protocol, ConnectionState},
 };
 use sv2_web::handlers::{AppState, ApiError};
+use tokio_util::sync::CancellationToken;
 
 async fn setup_test_app() -> (Router, Arc<dyn DatabaseOps>) {
     // Use in-memory SQLite for testing
@@ -25,6 +26,11 @@ async fn setup_test_app() -> (Router, Arc<dyn DatabaseOps>) {
     let app_state = AppState {
         database: Arc::new(database.clone()) as Arc<dyn DatabaseOps>,
         config,
+        shutdown: CancellationToken::new(),
+        mode_handler: None,
+        websocket: sv2_web::websocket::create_global_broadcaster(),
+        daemon_rpc_url: None,
+        worker_bans: Default::default(),
     };
 
     let app = Router::new()
@@ -34,7 +40,9 @@ async fn setup_test_app() -> (Router, Arc<dyn DatabaseOps>) {
         .route("/api/v1/connections/:id", axum::routing::get(sv2_web::handlers::get_connection))
         .route("/api/v1/shares", axum::routing::get(sv2_web::handlers::get_shares))
         .route("/api/v1/shares/stats", axum::routing::get(sv2_web::handlers::get_share_stats))
+        .route("/api/v1/shares/timeseries", axum::routing::get(sv2_web::handlers::get_share_timeseries))
         .route("/api/v1/metrics", axum::routing::get(sv2_web::handlers::get_metrics))
+        .route("/api/v1/metrics/history", axum::routing::get(sv2_web::handlers::get_metrics_history))
         .route("/api/v1/mining/stats", axum::routing::get(sv2_web::handlers::get_mining_stats))
         .route("/api/v1/templates", axum::routing::get(sv2_web::handlers::get_templates))
         .route("/api/v1/templates/:id", axum::routing::get(sv2_web::handlers::get_template))
@@ -71,6 +79,94 @@ async fn test_health_check_endpoint() {
     assert!(health["version"].is_string());
 }
 
+/// Serve sv2d's `status` RPC on a local port, answering with `status`
+fn mock_daemon(status: Value) -> String {
+    let daemon = Router::new().route(
+        "/",
+        axum::routing::post(move || {
+            let status = status.clone();
+            async move { axum::Json(json!({ "result": status })) }
+        }),
+    );
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(daemon.into_make_service()));
+    url
+}
+
+fn daemon_status(pool_health: &str, bitcoin_synced: bool) -> Value {
+    json!({
+        "running": true,
+        "uptime_seconds": 600,
+        "components": {
+            "bitcoin": { "running": true, "health_status": "Healthy", "last_error": null },
+            "pool": { "running": pool_health != "Critical", "health_status": pool_health, "last_error": null },
+            "translator": { "running": true, "health_status": "Healthy", "last_error": null },
+        },
+        "system_info": { "bitcoin_synced": bitcoin_synced },
+    })
+}
+
+/// `GET /api/v1/health` against a daemon at `daemon_rpc_url`
+async fn daemon_health(daemon_rpc_url: String) -> (StatusCode, Value) {
+    let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+    let app_state = AppState {
+        database: Arc::new(database) as Arc<dyn DatabaseOps>,
+        config: Arc::new(tokio::sync::RwLock::new(DaemonConfig::default())),
+        shutdown: CancellationToken::new(),
+        mode_handler: None,
+        websocket: sv2_web::websocket::create_global_broadcaster(),
+        daemon_rpc_url: Some(daemon_rpc_url),
+        worker_bans: Default::default(),
+    };
+    let app = Router::new()
+        .route("/api/v1/health", axum::routing::get(sv2_web::handlers::health_check))
+        .with_state(app_state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/api/v1/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_health_reflects_daemon_components() {
+    let (status, health) = daemon_health(mock_daemon(daemon_status("Healthy", true))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health["status"], "healthy");
+    assert_eq!(health["uptime"], 600);
+    assert_eq!(health["unhealthy_components"], json!([]));
+
+    let (status, health) = daemon_health(mock_daemon(daemon_status("Critical", true))).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(health["status"], "unhealthy");
+    assert_eq!(health["unhealthy_components"].as_array().unwrap().len(), 1);
+    assert_eq!(health["unhealthy_components"][0]["component"], "pool");
+    assert_eq!(health["unhealthy_components"][0]["health_status"], "Critical");
+
+    // Still serving while bitcoind catches up, but not healthy
+    let (status, health) = daemon_health(mock_daemon(daemon_status("Healthy", false))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health["status"], "degraded");
+    assert_eq!(health["bitcoin_synced"], false);
+}
+
+#[tokio::test]
+async fn test_health_with_daemon_unreachable() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let (status, health) = daemon_health(url).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(health["status"], "unhealthy");
+    assert_eq!(health["unhealthy_components"][0]["component"], "sv2d");
+    assert!(health["unhealthy_components"][0]["last_error"].as_str().unwrap().contains("not reachable"));
+}
+
 #[tokio::test]
 async fn test_status_endpoint() {
     let (app, _) = setup_test_app().await;
@@ -108,6 +204,7 @@ async fn test_connections_endpoint() {
         last_activity: chrono::Utc::now(),
         user_agent: Some("test-miner".to_string()),
         version: Some("1.0.0".to_string()),
+        flags: None,
         subscribed_difficulty: Some(1.0),
         extranonce1: Some("abcd".to_string()),
         extranonce2_size: Some(4),
@@ -116,6 +213,7 @@ async fn test_connections_endpoint() {
         valid_shares: 95,
         invalid_shares: 5,
         blocks_found: 1,
+        quality: None,
     };
 
     database.create_connection(&connection).await.unwrap();
@@ -154,6 +252,7 @@ async fn test_connection_by_id_endpoint() {
         last_activity: chrono::Utc::now(),
         user_agent: None,
         version: None,
+        flags: None,
         subscribed_difficulty: None,
         extranonce1: None,
         extranonce2_size: None,
@@ -162,6 +261,7 @@ async fn test_connection_by_id_endpoint() {
         valid_shares: 0,
         invalid_shares: 0,
         blocks_found: 0,
+        quality: None,
     };
 
     database.create_connection(&connection).await.unwrap();
@@ -280,6 +380,96 @@ async fn test_share_stats_endpoint() {
     assert!((stats.acceptance_rate - 90.0).abs() < 0.1);
 }
 
+#[tokio::test]
+async fn test_share_timeseries_endpoint() {
+    let (app, database) = setup_test_app().await;
+
+    let connection_id = Uuid::new_v4();
+    for (nonce, minutes_ago) in [(1, 3), (2, 3), (3, 90)] {
+        let mut share = Share::new(connection_id, nonce, 1_700_000_000, 1.0);
+        share.submitted_at = chrono::Utc::now() - chrono::Duration::minutes(minutes_ago);
+        share.is_valid = true;
+        database.create_share(&share).await.unwrap();
+    }
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/shares/timeseries?bucket=600&window=3600")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let buckets: Vec<sv2_core::ShareBucket> = serde_json::from_slice(&body).unwrap();
+    // Empty buckets are included and the share from 90 minutes ago is not
+    assert_eq!(buckets.len(), 6);
+    assert_eq!(buckets.iter().map(|b| b.accepted).sum::<u64>(), 2);
+    assert!(buckets.windows(2).all(|pair| pair[1].start - pair[0].start == chrono::Duration::minutes(10)));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/shares/timeseries?bucket=0")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_metrics_history_endpoint() {
+    let (app, database) = setup_test_app().await;
+
+    let connection_id = Uuid::new_v4();
+    for (nonce, minutes_ago, valid) in [(1, 0, true), (2, 0, false), (3, 90, true)] {
+        let mut share = Share::new(connection_id, nonce, 1_700_000_000, 1.0);
+        share.submitted_at = chrono::Utc::now() - chrono::Duration::minutes(minutes_ago);
+        share.is_valid = valid;
+        database.create_share(&share).await.unwrap();
+    }
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/metrics/history")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let history: sv2_core::metrics_history::MetricsHistorySnapshot = serde_json::from_slice(&body).unwrap();
+    // The default history is 360 samples at 10s, so the share from 90 minutes ago is outside it
+    assert_eq!(history.resolution_secs, 10);
+    assert_eq!(history.samples.len(), 360);
+    assert!(history.samples.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+    assert_eq!(history.samples.iter().map(|s| s.shares_accepted).sum::<u64>(), 1);
+    assert_eq!(history.samples.iter().map(|s| s.shares_rejected).sum::<u64>(), 1);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/metrics/history?limit=5")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let history: sv2_core::metrics_history::MetricsHistorySnapshot = serde_json::from_slice(&body).unwrap();
+    assert_eq!(history.samples.len(), 5);
+}
+
 #[tokio::test]
 async fn test_config_endpoints() {
     let (app, _) = setup_test_app().await;
@@ -540,6 +730,10 @@ async fn test_mining_stats_endpoint() {
     assert!(stats.hashrate > 0.0);
     assert!((stats.acceptance_rate - 90.0).abs() < 0.1);
     assert!(stats.shares_per_minute > 0.0);
+
+    // No node to ask for the network difficulty: unavailable, not a misleading 0
+    assert_eq!(stats.network_difficulty, None);
+    assert_eq!(stats.expected_blocks, None);
 }
 
 #[tokio::test]
@@ -0,0 +1,45 @@
+#![cfg(feature = "embed-assets")]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use hyper::Method;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_embedded_index_served_at_root() {
+    let app: axum::Router = sv2_web::assets::router();
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(&body[..], include_bytes!("../static/index.html"));
+}
+
+#[tokio::test]
+async fn test_embedded_missing_asset_returns_not_found() {
+    let app: axum::Router = sv2_web::assets::router();
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/static/does-not-exist.js")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,105 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use serde_json::Value;
+use std::sync::Arc;
+use sv2_core::{
+    config::DaemonConfig,
+    database::{DatabasePool, DatabaseOps},
+};
+use sv2_web::handlers::AppState;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+
+/// Helper function to create test app state
+async fn create_test_app_state() -> AppState {
+    let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+    database.migrate().await.unwrap();
+
+    let config = Arc::new(tokio::sync::RwLock::new(DaemonConfig::default()));
+
+    AppState {
+        database: Arc::new(database) as Arc<dyn DatabaseOps>,
+        config,
+        shutdown: CancellationToken::new(),
+        mode_handler: None,
+        websocket: sv2_web::websocket::create_global_broadcaster(),
+        daemon_rpc_url: None,
+        worker_bans: Default::default(),
+    }
+}
+
+/// Fetch the spec through the router, the same way a client would
+async fn fetch_spec() -> Value {
+    let app = sv2_web::routes::api_routes().with_state(create_test_app_state().await);
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/api/v1/openapi.json")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+/// Extract `(method, path)` pairs from the `.route(...)` calls in routes.rs,
+/// converting axum `:param` segments to OpenAPI `{param}` syntax
+fn registered_routes() -> Vec<(String, String)> {
+    let source = include_str!("../src/routes.rs");
+    let mut routes = Vec::new();
+
+    for line in source.lines() {
+        let Some(rest) = line.trim().strip_prefix(".route(\"") else {
+            continue;
+        };
+        let (path, rest) = rest.split_once('"').unwrap();
+        let method = rest.trim_start_matches(',').trim().split('(').next().unwrap();
+
+        let path = path
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(param) => format!("{{{}}}", param),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        routes.push((method.to_string(), path));
+    }
+
+    routes
+}
+
+#[tokio::test]
+async fn test_spec_lists_every_registered_route() {
+    let spec = fetch_spec().await;
+    let routes = registered_routes();
+
+    assert!(!routes.is_empty());
+    for (method, path) in routes {
+        assert!(
+            spec["paths"][&path][&method].is_object(),
+            "{} {} is registered but missing from the OpenAPI spec",
+            method.to_uppercase(),
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_spec_includes_response_schemas() {
+    let spec = fetch_spec().await;
+
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+    let schemas = &spec["components"]["schemas"];
+    for name in ["DaemonStatus", "ConnectionInfo", "Share", "ShareStats", "PerformanceMetrics", "Alert", "ApiError"] {
+        assert!(schemas[name].is_object(), "schema {} missing", name);
+    }
+}
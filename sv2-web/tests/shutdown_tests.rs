@@ -0,0 +1,72 @@
+use axum::{routing::get, Router};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Router with a single handler that takes `delay` to respond
+fn slow_router(delay: Duration) -> Router {
+    Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    )
+}
+
+fn bind_local() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/slow", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+#[tokio::test]
+async fn test_in_flight_request_completes_during_shutdown() {
+    let (listener, url) = bind_local();
+    let shutdown = CancellationToken::new();
+
+    let server = tokio::spawn(sv2_web::server::serve(
+        listener,
+        slow_router(Duration::from_millis(300)),
+        shutdown.clone(),
+        Duration::from_secs(5),
+    ));
+
+    let request = tokio::spawn(async move { reqwest::get(url).await });
+
+    // Begin shutdown while the request is still being handled
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.cancel();
+
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "done");
+
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_gives_up_after_grace_period() {
+    let (listener, url) = bind_local();
+    let shutdown = CancellationToken::new();
+
+    let server = tokio::spawn(sv2_web::server::serve(
+        listener,
+        slow_router(Duration::from_secs(60)),
+        shutdown.clone(),
+        Duration::from_millis(200),
+    ));
+
+    let _request = tokio::spawn(async move { reqwest::get(url).await });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let started = Instant::now();
+    shutdown.cancel();
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server should stop once the grace period expires")
+        .unwrap()
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -1,6 +1,6 @@
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{header, Request, StatusCode},
 };
 use hyper::Method;
 use serde_json::Value;
@@ -10,6 +10,7 @@ use sv2_core::{
     database::{DatabasePool, DatabaseOps},
 };
 use sv2_web::handlers::AppState;
+use tokio_util::sync::CancellationToken;
 use tower::ServiceExt;
 
 /// Helper function to create test app state
@@ -22,6 +23,11 @@ async fn create_test_app_state() -> AppState {
     AppState {
         database: Arc::new(database) as Arc<dyn DatabaseOps>,
         config,
+        shutdown: CancellationToken::new(),
+        mode_handler: None,
+        websocket: sv2_web::websocket::create_global_broadcaster(),
+        daemon_rpc_url: None,
+        worker_bans: Default::default(),
     }
 }
 
@@ -39,6 +45,7 @@ async fn create_test_router() -> axum::Router {
         .route("/api/v1/shares", get(handlers::get_shares))
         .route("/api/v1/config", get(handlers::get_config))
         .route("/ws", get(websocket::websocket_handler))
+        .fallback(handlers::static_file_fallback)
         .with_state(app_state)
 }
 
@@ -65,6 +72,144 @@ async fn test_health_check_endpoint() {
     assert!(health["uptime"].is_number());
 }
 
+#[tokio::test]
+async fn test_unknown_api_path_returns_json_404() {
+    let app = create_test_router().await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/api/v1/does-not-exist")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(error["code"], 404);
+    assert!(error["error"].as_str().unwrap().contains("/api/v1/does-not-exist"));
+}
+
+#[tokio::test]
+async fn test_unknown_spa_path_redirects_temporarily() {
+    let app = create_test_router().await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/workers/overview")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/");
+}
+
+#[tokio::test]
+async fn test_response_gzipped_when_accepted() {
+    let app = create_test_router()
+        .await
+        .layer(sv2_web::server::compression_layer());
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/api/v1/health")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(&body[..2], &[0x1f, 0x8b]);
+}
+
+#[tokio::test]
+async fn test_response_not_compressed_without_accept_encoding() {
+    let app = create_test_router()
+        .await
+        .layer(sv2_web::server::compression_layer());
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/api/v1/health")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+}
+
+#[tokio::test]
+async fn test_slow_request_times_out_with_503() {
+    use axum::routing::get;
+
+    let app = axum::Router::new().route(
+        "/slow",
+        get(|| async {
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+            "done"
+        }),
+    );
+    let app = sv2_web::server::with_request_timeout(app, std::time::Duration::from_millis(100));
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/slow")
+        .body(Body::empty())
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["code"], 503);
+}
+
+#[tokio::test]
+async fn test_version_endpoint_returns_compiled_version() {
+    let app = sv2_web::routes::api_routes().with_state(create_test_app_state().await);
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/api/v1/version")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(sv2_web::handlers::VERSION_HEADER).unwrap(),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let version: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert!(version["git_commit"].is_string());
+    assert!(version["build_time"].is_string());
+    assert!(version["sv2_implementation"].as_str().unwrap().starts_with("SRI "));
+}
+
 #[tokio::test]
 async fn test_status_endpoint() {
     let app = create_test_router().await;
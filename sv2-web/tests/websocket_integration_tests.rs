@@ -33,6 +33,7 @@ async fn test_websocket_broadcaster() {
         blocks_found: 1,
         current_difficulty: 1.0,
         hashrate: 1e12,
+        mode_health: None,
     };
 
     broadcaster.broadcast_status(status.clone());
@@ -67,6 +68,7 @@ async fn test_websocket_connection_events() {
         last_activity: chrono::Utc::now(),
         user_agent: Some("test-miner".to_string()),
         version: Some("1.0.0".to_string()),
+        flags: None,
         subscribed_difficulty: Some(1.0),
         extranonce1: Some("abcd".to_string()),
         extranonce2_size: Some(4),
@@ -75,6 +77,7 @@ async fn test_websocket_connection_events() {
         valid_shares: 0,
         invalid_shares: 0,
         blocks_found: 0,
+        quality: None,
     };
 
     broadcaster.notify_connection_added(connection.clone());
@@ -252,6 +255,7 @@ async fn test_websocket_message_serialization() {
             blocks_found: 1,
             current_difficulty: 1.0,
             hashrate: 1e12,
+            mode_health: None,
         }),
         WebSocketMessage::ConnectionAdded(ConnectionInfo {
             id: Uuid::new_v4(),
@@ -262,6 +266,7 @@ async fn test_websocket_message_serialization() {
             last_activity: chrono::Utc::now(),
             user_agent: None,
             version: None,
+            flags: None,
             subscribed_difficulty: None,
             extranonce1: None,
             extranonce2_size: None,
@@ -270,6 +275,7 @@ async fn test_websocket_message_serialization() {
             valid_shares: 0,
             invalid_shares: 0,
             blocks_found: 0,
+            quality: None,
         }),
         WebSocketMessage::ConnectionRemoved { id: Uuid::new_v4() },
         WebSocketMessage::ShareSubmitted(Share {
@@ -339,30 +345,28 @@ async fn test_websocket_request_serialization() {
 
 #[tokio::test]
 async fn test_websocket_session_subscriptions() {
-    use sv2_web::websocket::WebSocketSession;
+    use sv2_web::websocket::{WebSocketChannel, WebSocketSession};
 
     let mut session = WebSocketSession::new();
     
-    // Initially not subscribed to anything
-    assert!(!session.is_subscribed("status"));
-    assert!(!session.is_subscribed("connection"));
+    // Subscribed to everything until the client says otherwise
+    for channel in WebSocketChannel::ALL {
+        assert!(session.is_subscribed(channel));
+    }
     
-    // Subscribe to specific events
-    session.subscriptions.insert("status".to_string());
-    session.subscriptions.insert("connection".to_string());
+    // Subscribe to specific channels
+    session.subscribe(&[WebSocketChannel::Metrics, WebSocketChannel::Connections]);
     
-    assert!(session.is_subscribed("status"));
-    assert!(session.is_subscribed("connection"));
-    assert!(!session.is_subscribed("share"));
+    assert!(session.is_subscribed(WebSocketChannel::Metrics));
+    assert!(session.is_subscribed(WebSocketChannel::Connections));
+    assert!(!session.is_subscribed(WebSocketChannel::Shares));
     
-    // Subscribe to all events
-    session.subscriptions.clear();
-    session.subscriptions.insert("*".to_string());
+    // Subscribe to all channels
+    session.subscribe(&WebSocketChannel::ALL);
     
-    assert!(session.is_subscribed("status"));
-    assert!(session.is_subscribed("connection"));
-    assert!(session.is_subscribed("share"));
-    assert!(session.is_subscribed("alert"));
+    for channel in WebSocketChannel::ALL {
+        assert!(session.is_subscribed(channel));
+    }
 }
 
 #[tokio::test]
@@ -398,6 +402,7 @@ async fn test_multiple_websocket_subscribers() {
         blocks_found: 0,
         current_difficulty: 1.0,
         hashrate: 5e11,
+        mode_health: None,
     };
     
     broadcaster.broadcast_status(status.clone());
@@ -466,39 +471,112 @@ async fn test_websocket_heartbeat() {
     }
 }
 
-// Integration test that would require a running server
-// This is commented out as it requires more complex setup
-/*
+/// Serve `/ws` on a local port, returning its URL and the broadcaster its
+/// clients share
+async fn start_websocket_server() -> (String, Arc<WebSocketBroadcaster>) {
+    use axum::{routing::get, Router};
+    use sv2_web::handlers::AppState;
+
+    let websocket = sv2_web::websocket::create_global_broadcaster();
+    let state = AppState {
+        database: setup_test_database().await,
+        config: Arc::new(tokio::sync::RwLock::new(DaemonConfig::default())),
+        shutdown: tokio_util::sync::CancellationToken::new(),
+        mode_handler: None,
+        websocket: websocket.clone(),
+        daemon_rpc_url: None,
+        worker_bans: Default::default(),
+    };
+    let app = Router::new()
+        .route("/ws", get(sv2_web::websocket::websocket_handler))
+        .with_state(state);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let server = axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service());
+    tokio::spawn(server);
+    (url, websocket)
+}
+
+/// Next message of type `wanted`, failing on any other message type apart
+/// from heartbeats
+async fn expect_message<S>(read: &mut S, wanted: &str) -> serde_json::Value
+where
+    S: futures::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        let message = timeout(Duration::from_secs(2), read.next())
+            .await
+            .unwrap_or_else(|_| panic!("no {} message within timeout", wanted))
+            .expect("connection closed")
+            .unwrap();
+        let Message::Text(text) = message else { continue };
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        match value["type"].as_str().unwrap() {
+            "Heartbeat" => continue,
+            found => {
+                assert_eq!(found, wanted, "unexpected message {}", text);
+                return value;
+            }
+        }
+    }
+}
+
 #[tokio::test]
-async fn test_websocket_end_to_end() {
-    // This test would require starting the actual sv2-web server
-    // and connecting to it via WebSocket client
-    
-    // Start server in background
-    // let server_handle = tokio::spawn(async {
-    //     sv2_web::main().await
-    // });
-    
-    // Connect WebSocket client
-    // let (ws_stream, _) = connect_async("ws://127.0.0.1:8080/ws").await.unwrap();
-    // let (mut write, mut read) = ws_stream.split();
-    
-    // Send subscription request
-    // let subscribe_request = json!({
-    //     "action": "Subscribe",
-    //     "events": ["status", "connection"]
-    // });
-    // write.send(Message::Text(subscribe_request.to_string())).await.unwrap();
-    
-    // Receive subscription confirmation
-    // let message = read.next().await.unwrap().unwrap();
-    // ... verify subscription confirmation
-    
-    // Receive periodic status updates
-    // let status_message = read.next().await.unwrap().unwrap();
-    // ... verify status message format
-    
-    // Clean up
-    // server_handle.abort();
+async fn test_clients_only_receive_subscribed_channels() {
+    let (url, broadcaster) = start_websocket_server().await;
+
+    let mut clients = Vec::new();
+    for channel in ["shares", "alerts"] {
+        let (stream, _) = connect_async(&url).await.unwrap();
+        let (mut write, mut read) = stream.split();
+        let subscribe = json!({ "action": "Subscribe", "events": [channel] });
+        write.send(Message::Text(subscribe.to_string())).await.unwrap();
+
+        // Anything sent before the subscription took effect is skipped
+        loop {
+            let message = timeout(Duration::from_secs(2), read.next()).await.unwrap().unwrap().unwrap();
+            let Message::Text(text) = message else { continue };
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            if value["type"] == "Subscribed" {
+                assert_eq!(value["data"]["subscriptions"], json!([channel]));
+                break;
+            }
+        }
+        clients.push((write, read));
+    }
+
+    let share = Share {
+        connection_id: Uuid::new_v4(),
+        nonce: 12345,
+        timestamp: chrono::Utc::now().timestamp() as u32,
+        difficulty: 1.0,
+        is_valid: true,
+        block_hash: None,
+        submitted_at: chrono::Utc::now(),
+    };
+    let alert = Alert::new(
+        AlertLevel::Warning,
+        "Low hashrate".to_string(),
+        "Hashrate dropped".to_string(),
+        "test".to_string(),
+    );
+    broadcaster.notify_share_submitted(share.clone());
+    broadcaster.notify_alert_created(alert.clone());
+    broadcaster.notify_share_submitted(share);
+
+    let (_, shares_read) = &mut clients[0];
+    for _ in 0..2 {
+        let message = expect_message(shares_read, "ShareSubmitted").await;
+        assert_eq!(message["data"]["nonce"], 12345);
+    }
+
+    let (_, alerts_read) = &mut clients[1];
+    let message = expect_message(alerts_read, "AlertCreated").await;
+    assert_eq!(message["data"]["id"], alert.id.to_string());
+
+    // The alert between the two shares never reached the shares client, and
+    // the share after it is skipped before the alerts client's next event
+    broadcaster.notify_alert_resolved(alert.id);
+    expect_message(alerts_read, "AlertResolved").await;
 }
-*/
\ No newline at end of file
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use std::sync::Arc;
+use sv2_core::{
+    config::DaemonConfig,
+    database::{DatabaseOps, DatabasePool},
+    health::{HealthCheck, HealthStatus},
+    mode::ModeHandler,
+    types::Worker,
+    worker_bans::WorkerBan,
+    Connection, HandshakeDetails, MiningStats, Result, Share, ShareResult, WorkTemplate,
+};
+use sv2_web::handlers::{AppState, WorkerStats};
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Mode handler with a fixed set of workers, and statistics if given
+struct WorkersModeHandler {
+    workers: Vec<Worker>,
+    stats: Option<MiningStats>,
+}
+
+#[async_trait]
+impl ModeHandler for WorkersModeHandler {
+    async fn start(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn handle_connection(&self, _conn: Connection) -> Result<()> {
+        Ok(())
+    }
+
+    async fn handle_handshake(&self, _connection_id: sv2_core::ConnectionId, _details: HandshakeDetails) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_share(&self, _share: Share) -> Result<ShareResult> {
+        Ok(ShareResult::Valid)
+    }
+
+    async fn get_work_template(&self) -> Result<WorkTemplate> {
+        Err(sv2_core::Error::Template("No templates in this test".to_string()))
+    }
+
+    async fn handle_disconnection(&self, _connection_id: sv2_core::ConnectionId) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_statistics(&self) -> Result<MiningStats> {
+        self.stats
+            .clone()
+            .ok_or_else(|| sv2_core::Error::System("No statistics in this test".to_string()))
+    }
+
+    async fn health(&self) -> HealthCheck {
+        HealthCheck::new("workers_mode", HealthStatus::Healthy, "Healthy")
+    }
+
+    async fn workers(&self) -> Vec<Worker> {
+        self.workers.clone()
+    }
+
+    fn validate_config(&self, _config: &DaemonConfig) -> Result<()> {
+        Ok(())
+    }
+}
+
+async fn create_test_router(mode_handler: Option<Arc<dyn ModeHandler>>) -> Router {
+    let database = DatabasePool::new("sqlite::memory:", 1).await.unwrap();
+    database.migrate().await.unwrap();
+
+    let app_state = AppState {
+        database: Arc::new(database) as Arc<dyn DatabaseOps>,
+        config: Arc::new(tokio::sync::RwLock::new(DaemonConfig::default())),
+        shutdown: CancellationToken::new(),
+        mode_handler,
+        websocket: sv2_web::websocket::create_global_broadcaster(),
+        daemon_rpc_url: None,
+        worker_bans: Default::default(),
+    };
+    sv2_web::routes::api_routes().with_state(app_state)
+}
+
+async fn get(app: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_worker_statistics_endpoints() {
+    let mut worker = Worker::new(Uuid::new_v4(), "bc1qminer.rig1".to_string(), 512.0);
+    worker.hashrate = 1.2e12;
+    for accepted in [true, true, true, false] {
+        worker.add_share(accepted);
+    }
+    let worker_id = worker.id.clone();
+    let handler = Arc::new(WorkersModeHandler { workers: vec![worker], stats: None }) as Arc<dyn ModeHandler>;
+    let app = create_test_router(Some(handler)).await;
+
+    let (status, body) = get(app.clone(), "/api/v1/workers").await;
+    assert_eq!(status, StatusCode::OK);
+    let workers: Vec<WorkerStats> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(workers.len(), 1);
+    assert_eq!(workers[0].username, "bc1qminer.rig1");
+
+    let (status, body) = get(app.clone(), &format!("/api/v1/workers/{}", worker_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: WorkerStats = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stats.hashrate, 1.2e12);
+    assert_eq!(stats.difficulty, 512.0);
+    assert_eq!(stats.valid_shares, 3);
+    assert_eq!(stats.invalid_shares, 1);
+
+    let (status, _) = get(app, "/api/v1/workers/no-such-worker").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_worker_ban_endpoints() {
+    let app = create_test_router(None).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/workers/rig2/ban")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"reason":"stale flood"}"#))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, body) = get(app.clone(), "/api/v1/workers/bans").await;
+    assert_eq!(status, StatusCode::OK);
+    let bans: Vec<WorkerBan> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(bans.len(), 1);
+    assert_eq!(bans[0].worker_name, "rig2");
+    assert_eq!(bans[0].reason.as_deref(), Some("stale flood"));
+
+    let unban = || Request::builder().method("DELETE").uri("/api/v1/workers/rig2/ban").body(Body::empty()).unwrap();
+    assert_eq!(app.clone().oneshot(unban()).await.unwrap().status(), StatusCode::NO_CONTENT);
+    assert_eq!(app.oneshot(unban()).await.unwrap().status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_workers_unavailable_without_a_mode() {
+    let app = create_test_router(None).await;
+    let (status, _) = get(app, "/api/v1/workers").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_prometheus_metrics_need_mode_statistics() {
+    let (status, _) = get(create_test_router(None).await, "/metrics").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    // The mock can't report statistics
+    let handler = Arc::new(WorkersModeHandler { workers: Vec::new(), stats: None }) as Arc<dyn ModeHandler>;
+    let (status, _) = get(create_test_router(Some(handler)).await, "/metrics").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_mining_stats_report_the_running_modes_expected_blocks() {
+    let stats = MiningStats {
+        expected_blocks: Some(0.25),
+        network_difficulty: Some(1_000_000.0),
+        ..Default::default()
+    };
+    let handler = Arc::new(WorkersModeHandler { workers: Vec::new(), stats: Some(stats) }) as Arc<dyn ModeHandler>;
+
+    let (status, body) = get(create_test_router(Some(handler)).await, "/api/v1/mining/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: MiningStats = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stats.expected_blocks, Some(0.25));
+    assert_eq!(stats.network_difficulty, Some(1_000_000.0));
+}
@@ -0,0 +1,131 @@
+//! Checks that the translator trusts the authority key the pool is actually
+//! using. A mismatch lets miners connect to the translator while its upstream
+//! Noise handshake to the pool fails, so shares silently go nowhere.
+
+use anyhow::{bail, Result};
+
+const POOL_KEY_MARKERS: &[&str] = &["authority_public_key", "authority public key", "authority pubkey"];
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Authority public key the pool printed in its log, newest line first
+pub fn from_pool_log(log: &str) -> Option<String> {
+    log.lines().rev().find_map(|line| {
+        let lower = line.to_lowercase();
+        if !POOL_KEY_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            return None;
+        }
+        line.split(|c: char| !BASE58_ALPHABET.contains(c))
+            .filter(|token| token.len() >= 40)
+            .last()
+            .map(str::to_string)
+    })
+}
+
+/// `authority_public_key` from a pool config
+pub fn from_pool_config(config: &str) -> Option<String> {
+    let value: toml::Value = toml::from_str(config).ok()?;
+    value.get("authority_public_key")?.as_str().map(str::to_string)
+}
+
+/// `authority_pubkey` of every `[[upstreams]]` entry in a translator config
+pub fn from_translator_config(config: &str) -> Result<Vec<String>> {
+    let value: toml::Value = toml::from_str(config)?;
+    let keys = value
+        .get("upstreams")
+        .and_then(|upstreams| upstreams.as_array())
+        .map(|upstreams| {
+            upstreams
+                .iter()
+                .filter_map(|upstream| upstream.get("authority_pubkey")?.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(keys)
+}
+
+/// Fail unless the translator expects `pool_key` from at least one upstream
+pub fn verify_translator_key(pool_key: &str, translator_keys: &[String], translator_config_path: &str) -> Result<()> {
+    if translator_keys.is_empty() {
+        bail!(
+            "Translator config {} has no [[upstreams]] authority_pubkey; \
+             it cannot authenticate the pool",
+            translator_config_path
+        );
+    }
+
+    if !translator_keys.iter().any(|key| key == pool_key) {
+        bail!(
+            "Authority key mismatch: the pool is using {} but translator config {} expects {}. \
+             Miners would connect to the translator but never reach the pool. \
+             Set authority_pubkey under [[upstreams]] to the pool's key.",
+            pool_key,
+            translator_config_path,
+            translator_keys.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: &str = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72";
+    const OTHER: &str = "9cEoWDHp2KtT3pUYaAsjS6yzquNv8QXx3qvCmu8iz8WJ1EB3jUj";
+
+    const TRANSLATOR_CONFIG: &str = r#"
+downstream_address = "0.0.0.0"
+downstream_port = 3333
+
+[[upstreams]]
+address = "127.0.0.1"
+port = 34254
+authority_pubkey = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
+"#;
+
+    fn pool_log(key: &str) -> String {
+        format!(
+            "2025-01-10T12:00:00Z  INFO pool_sv2: Pool starting\n\
+             2025-01-10T12:00:00Z  INFO pool_sv2: authority_public_key: {}\n\
+             2025-01-10T12:00:01Z  INFO pool_sv2: Listening for encrypted connection on: 0.0.0.0:34254\n",
+            key
+        )
+    }
+
+    #[test]
+    fn test_key_from_pool_log() {
+        assert_eq!(from_pool_log(&pool_log(EXPECTED)).as_deref(), Some(EXPECTED));
+
+        // A restart appended to the same log wins over the earlier run
+        let restarted = format!("{}{}", pool_log(OTHER), pool_log(EXPECTED));
+        assert_eq!(from_pool_log(&restarted).as_deref(), Some(EXPECTED));
+
+        assert_eq!(from_pool_log("INFO pool_sv2: Pool starting\n"), None);
+    }
+
+    #[test]
+    fn test_key_from_configs() {
+        let pool_config = format!("authority_public_key = \"{}\"\ncert_validity_sec = 3600\n", OTHER);
+        assert_eq!(from_pool_config(&pool_config).as_deref(), Some(OTHER));
+        assert_eq!(from_translator_config(TRANSLATOR_CONFIG).unwrap(), vec![EXPECTED.to_string()]);
+    }
+
+    #[test]
+    fn test_mismatch_detected() {
+        let translator_keys = from_translator_config(TRANSLATOR_CONFIG).unwrap();
+
+        let pool_key = from_pool_log(&pool_log(EXPECTED)).unwrap();
+        assert!(verify_translator_key(&pool_key, &translator_keys, "translator.toml").is_ok());
+
+        let pool_key = from_pool_log(&pool_log(OTHER)).unwrap();
+        let err = verify_translator_key(&pool_key, &translator_keys, "translator.toml").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("mismatch"));
+        assert!(message.contains(OTHER));
+        assert!(message.contains(EXPECTED));
+
+        assert!(verify_translator_key(EXPECTED, &[], "translator.toml").is_err());
+    }
+}
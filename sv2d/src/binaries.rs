@@ -0,0 +1,214 @@
+//! Records the version and SHA-256 of each external binary sv2d launches, so
+//! an accidental binary swap between restarts shows up in the logs and in
+//! `sv2-cli status`.
+//!
+//! Hashing is lazy: a binary is only hashed the first time it is launched, or
+//! again when its size or modification time no longer match the cached record.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+use tokio::process::Command as TokioCommand;
+use tracing::{info, warn};
+
+/// Cheap file metadata used to decide whether a cached hash is still valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Fingerprint {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BinaryRecord {
+    pub path: PathBuf,
+    pub sha256: String,
+    pub version: Option<String>,
+    #[serde(skip)]
+    fingerprint: Fingerprint,
+}
+
+impl BinaryRecord {
+    pub fn new(path: PathBuf, sha256: String, version: Option<String>, fingerprint: Fingerprint) -> Self {
+        Self { path, sha256, version, fingerprint }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryCheck {
+    /// First time this binary was seen
+    Recorded,
+    /// Same content as the last launch
+    Unchanged,
+    /// Content differs from the last launch
+    Changed { previous_sha256: String },
+}
+
+#[derive(Debug, Default)]
+pub struct BinaryRegistry {
+    records: HashMap<String, BinaryRecord>,
+}
+
+impl BinaryRegistry {
+    /// Whether the cached record for `name` still describes the file at `path`,
+    /// in which case there is no need to hash it again
+    pub fn is_current(&self, name: &str, path: &Path, fingerprint: &Fingerprint) -> bool {
+        self.records
+            .get(name)
+            .is_some_and(|record| record.path == path && &record.fingerprint == fingerprint)
+    }
+
+    /// Store a freshly hashed record, warning if the content changed since the
+    /// previous launch
+    pub fn record(&mut self, name: &str, record: BinaryRecord) -> BinaryCheck {
+        let check = match self.records.get(name) {
+            None => {
+                info!(
+                    "Recorded {} binary {} (version: {}, sha256: {})",
+                    name,
+                    record.path.display(),
+                    record.version.as_deref().unwrap_or("unknown"),
+                    record.sha256
+                );
+                BinaryCheck::Recorded
+            }
+            Some(previous) if previous.sha256 == record.sha256 => BinaryCheck::Unchanged,
+            Some(previous) => {
+                warn!(
+                    "⚠️ {} binary changed on disk since it was last started: {} (version: {}, sha256: {}) -> {} (version: {}, sha256: {})",
+                    name,
+                    previous.path.display(),
+                    previous.version.as_deref().unwrap_or("unknown"),
+                    previous.sha256,
+                    record.path.display(),
+                    record.version.as_deref().unwrap_or("unknown"),
+                    record.sha256
+                );
+                BinaryCheck::Changed {
+                    previous_sha256: previous.sha256.clone(),
+                }
+            }
+        };
+
+        self.records.insert(name.to_string(), record);
+        check
+    }
+
+    pub fn records(&self) -> &HashMap<String, BinaryRecord> {
+        &self.records
+    }
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// First line of `<binary> --version`, if the binary answers in time
+pub async fn probe_version(path: &Path) -> Option<String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(5),
+        TokioCommand::new(path)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(path: &Path) -> BinaryRecord {
+        BinaryRecord::new(
+            path.to_path_buf(),
+            hash_file(path).unwrap(),
+            None,
+            Fingerprint::of(path).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("sv2d-hash-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_changed_binary_triggers_mismatch() {
+        let path = std::env::temp_dir().join(format!("sv2d-binary-{}", std::process::id()));
+        std::fs::write(&path, b"pool_sv2 v1").unwrap();
+
+        let mut registry = BinaryRegistry::default();
+        assert_eq!(registry.record("pool", snapshot(&path)), BinaryCheck::Recorded);
+
+        // Unchanged file: the cached hash is reused
+        assert!(registry.is_current("pool", &path, &Fingerprint::of(&path).unwrap()));
+        assert_eq!(registry.record("pool", snapshot(&path)), BinaryCheck::Unchanged);
+
+        let original = registry.records()["pool"].sha256.clone();
+        std::fs::write(&path, b"pool_sv2 v2, a different build").unwrap();
+        assert!(!registry.is_current("pool", &path, &Fingerprint::of(&path).unwrap()));
+        assert_eq!(
+            registry.record("pool", snapshot(&path)),
+            BinaryCheck::Changed { previous_sha256: original }
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_moved_binary_is_not_current() {
+        let path = std::env::temp_dir().join(format!("sv2d-binary-moved-{}", std::process::id()));
+        std::fs::write(&path, b"translator").unwrap();
+
+        let mut registry = BinaryRegistry::default();
+        registry.record("translator", snapshot(&path));
+        let fingerprint = Fingerprint::of(&path).unwrap();
+        assert!(!registry.is_current("translator", Path::new("/usr/local/bin/translator_sv2"), &fingerprint));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -13,6 +13,7 @@ use tracing::info;
 pub enum Network {
     Regtest,
     Signet,
+    Testnet4,
     Mainnet,
 }
 
@@ -21,6 +22,7 @@ impl Network {
         match self {
             Network::Regtest => 18443,
             Network::Signet => 38332,
+            Network::Testnet4 => 48332,
             Network::Mainnet => 8332,
         }
     }
@@ -29,6 +31,7 @@ impl Network {
         match self {
             Network::Regtest => "regtest",
             Network::Signet => "signet",
+            Network::Testnet4 => "testnet4",
             Network::Mainnet => "mainnet",
         }
     }
@@ -41,7 +44,9 @@ impl FromStr for Network {
         match s {
             "regtest" => Ok(Network::Regtest),
             "signet" => Ok(Network::Signet),
-            "main" => Ok(Network::Mainnet),
+            "testnet4" => Ok(Network::Testnet4),
+            // `main` is what getblockchaininfo reports, `mainnet` what configs say
+            "main" | "mainnet" => Ok(Network::Mainnet),
             _ => Err(anyhow::anyhow!("Unknown network: {}", s)),
         }
     }
@@ -96,12 +101,49 @@ pub async fn ensure_bitcoin_running(network: Network) -> Result<BitcoinConnectio
     start_bitcoin_core(network).await
 }
 
+/// Check a Bitcoin Core that sv2d doesn't manage. Only its RPC is queried;
+/// nothing is ever started.
+pub async fn verify_external_bitcoin(
+    rpc_url: &str,
+    rpc_user: &str,
+    rpc_password: &str,
+    network: Network,
+) -> Result<BitcoinConnection> {
+    let port = reqwest::Url::parse(rpc_url)
+        .with_context(|| format!("Invalid bitcoin rpc_url '{}'", rpc_url))?
+        .port_or_known_default()
+        .unwrap_or_else(|| network.rpc_port());
+
+    let connection = query_blockchain_info(rpc_url, rpc_user, rpc_password, port, true)
+        .await
+        .with_context(|| format!("Bitcoin Core RPC at {} is not reachable", rpc_url))?;
+
+    if connection.network != network {
+        return Err(anyhow::anyhow!(
+            "Bitcoin Core at {} is running on {} but config expects {}",
+            rpc_url, connection.network, network
+        ));
+    }
+
+    Ok(connection)
+}
+
 async fn test_connection(port: u16, is_existing: bool) -> Result<BitcoinConnection> {
+    query_blockchain_info(&format!("http://127.0.0.1:{}", port), "test", "test", port, is_existing).await
+}
+
+async fn query_blockchain_info(
+    url: &str,
+    user: &str,
+    password: &str,
+    port: u16,
+    is_existing: bool,
+) -> Result<BitcoinConnection> {
     let client = Client::new();
     
     let response = client
-        .post(format!("http://127.0.0.1:{}", port))
-        .basic_auth("test", Some("test"))
+        .post(url)
+        .basic_auth(user, Some(password))
         .json(&json!({
             "jsonrpc": "1.0",
             "id": "test",
@@ -159,6 +201,10 @@ async fn start_bitcoin_core(network: Network) -> Result<BitcoinConnection> {
             "rpcuser=test\nrpcpassword=test\nfallbackfee=0.0002\n\n[signet]\nrpcport={}\n",
             network.rpc_port()
         ),
+        Network::Testnet4 => format!(
+            "rpcuser=test\nrpcpassword=test\nfallbackfee=0.0002\n\n[testnet4]\nrpcport={}\n",
+            network.rpc_port()
+        ),
         Network::Mainnet => format!(
             "rpcuser=test\nrpcpassword=test\nfallbackfee=0.0002\nrpcport={}\n",
             network.rpc_port()
@@ -250,7 +296,9 @@ mod tests {
     fn test_network_from_str() {
         assert_eq!(Network::from_str("regtest").unwrap(), Network::Regtest);
         assert_eq!(Network::from_str("signet").unwrap(), Network::Signet);
+        assert_eq!(Network::from_str("testnet4").unwrap(), Network::Testnet4);
         assert_eq!(Network::from_str("main").unwrap(), Network::Mainnet);
+        assert_eq!(Network::from_str("mainnet").unwrap(), Network::Mainnet);
         assert!(Network::from_str("invalid").is_err());
     }
     
@@ -258,6 +306,7 @@ mod tests {
     fn test_network_rpc_port() {
         assert_eq!(Network::Regtest.rpc_port(), 18443);
         assert_eq!(Network::Signet.rpc_port(), 38332);
+        assert_eq!(Network::Testnet4.rpc_port(), 48332);
         assert_eq!(Network::Mainnet.rpc_port(), 8332);
     }
 }
\ No newline at end of file
@@ -0,0 +1,249 @@
+//! Per-component restart circuit breaker.
+//!
+//! A component that keeps crashing shortly after each restart trips the
+//! breaker. While open, restarts are suppressed for a cool-down period;
+//! afterwards a single probe restart is allowed (half-open), and the breaker
+//! only closes again once the component has stayed up for a sustained period.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BreakerState {
+    /// Restarts allowed
+    Closed,
+    /// Too many rapid failures, restarts suppressed until the cool-down ends
+    Open,
+    /// Cool-down over, one probe restart allowed
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    /// Failures within `failure_window` that trip the breaker
+    pub failure_threshold: usize,
+    pub failure_window: Duration,
+    /// How long restarts stay suppressed once tripped
+    pub cooldown: Duration,
+    /// How long a probed component must stay up before the breaker closes
+    pub recovery_period: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            failure_window: Duration::from_secs(120),
+            cooldown: Duration::from_secs(300),
+            recovery_period: Duration::from_secs(120),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    state: BreakerState,
+    recent_failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    healthy_since: Option<Instant>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(BreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            state: BreakerState::Closed,
+            recent_failures: VecDeque::new(),
+            opened_at: None,
+            healthy_since: None,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Record a component exit or failed restart. Returns true if this
+    /// failure opened the breaker.
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.healthy_since = None;
+
+        match self.state {
+            BreakerState::Closed => {
+                self.recent_failures.push_back(now);
+                while let Some(&oldest) = self.recent_failures.front() {
+                    if now.duration_since(oldest) > self.config.failure_window {
+                        self.recent_failures.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                if self.recent_failures.len() >= self.config.failure_threshold {
+                    self.open(now);
+                    return true;
+                }
+                false
+            }
+            // The probe failed, or a restart failed while open: start a new cool-down
+            BreakerState::HalfOpen | BreakerState::Open => {
+                let was_open = self.state == BreakerState::Open;
+                self.open(now);
+                !was_open
+            }
+        }
+    }
+
+    /// Whether a restart may be attempted now. Moves an open breaker whose
+    /// cool-down has elapsed to half-open and allows exactly one probe.
+    pub fn allow_restart(&mut self, now: Instant) -> bool {
+        match self.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => {
+                let cooled_down = self
+                    .opened_at
+                    .map_or(true, |opened| now.duration_since(opened) >= self.config.cooldown);
+                if cooled_down {
+                    self.state = BreakerState::HalfOpen;
+                }
+                cooled_down
+            }
+            BreakerState::HalfOpen => false,
+        }
+    }
+
+    /// Record that the component is running. Closes a half-open breaker once
+    /// the component has been up for the recovery period.
+    pub fn record_healthy(&mut self, now: Instant) {
+        let healthy_since = *self.healthy_since.get_or_insert(now);
+
+        if self.state == BreakerState::HalfOpen
+            && now.duration_since(healthy_since) >= self.config.recovery_period
+        {
+            self.state = BreakerState::Closed;
+            self.recent_failures.clear();
+            self.opened_at = None;
+        }
+    }
+
+    /// Time left before a probe restart is allowed, if the breaker is open
+    pub fn remaining_cooldown(&self, now: Instant) -> Option<Duration> {
+        match (self.state, self.opened_at) {
+            (BreakerState::Open, Some(opened)) => {
+                Some(self.config.cooldown.saturating_sub(now.duration_since(opened)))
+            }
+            _ => None,
+        }
+    }
+
+    fn open(&mut self, now: Instant) {
+        self.state = BreakerState::Open;
+        self.opened_at = Some(now);
+        self.recent_failures.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(BreakerConfig {
+            failure_threshold: 3,
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(300),
+            recovery_period: Duration::from_secs(120),
+        })
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn test_rapid_failures_open_breaker() {
+        let start = Instant::now();
+        let mut b = breaker();
+
+        assert!(!b.record_failure(start));
+        assert!(!b.record_failure(start + secs(10)));
+        assert_eq!(b.state(), BreakerState::Closed);
+        assert!(b.allow_restart(start + secs(10)));
+
+        assert!(b.record_failure(start + secs(20)));
+        assert_eq!(b.state(), BreakerState::Open);
+        assert!(!b.allow_restart(start + secs(30)));
+        assert_eq!(b.remaining_cooldown(start + secs(120)), Some(secs(200)));
+    }
+
+    #[test]
+    fn test_slow_failures_stay_closed() {
+        let start = Instant::now();
+        let mut b = breaker();
+
+        for i in 0..5 {
+            b.record_failure(start + secs(i * 100));
+        }
+        assert_eq!(b.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_cooldown_allows_single_probe() {
+        let start = Instant::now();
+        let mut b = breaker();
+        for i in 0..3 {
+            b.record_failure(start + secs(i));
+        }
+
+        let after_cooldown = start + secs(2) + secs(300);
+        assert!(b.allow_restart(after_cooldown));
+        assert_eq!(b.state(), BreakerState::HalfOpen);
+        assert!(!b.allow_restart(after_cooldown + secs(1)));
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let start = Instant::now();
+        let mut b = breaker();
+        for i in 0..3 {
+            b.record_failure(start + secs(i));
+        }
+        let probe = start + secs(400);
+        assert!(b.allow_restart(probe));
+
+        assert!(b.record_failure(probe + secs(5)));
+        assert_eq!(b.state(), BreakerState::Open);
+        assert!(!b.allow_restart(probe + secs(100)));
+    }
+
+    #[test]
+    fn test_sustained_health_closes_breaker() {
+        let start = Instant::now();
+        let mut b = breaker();
+        for i in 0..3 {
+            b.record_failure(start + secs(i));
+        }
+        let probe = start + secs(400);
+        assert!(b.allow_restart(probe));
+
+        b.record_healthy(probe + secs(10));
+        b.record_healthy(probe + secs(60));
+        assert_eq!(b.state(), BreakerState::HalfOpen);
+
+        b.record_healthy(probe + secs(130));
+        assert_eq!(b.state(), BreakerState::Closed);
+        assert!(b.allow_restart(probe + secs(131)));
+
+        // Failure history was cleared, so a single new failure doesn't re-trip it
+        assert!(!b.record_failure(probe + secs(140)));
+        assert_eq!(b.state(), BreakerState::Closed);
+    }
+}
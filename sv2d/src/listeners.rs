@@ -0,0 +1,226 @@
+//! Extra stratum listen addresses. translator_sv2 binds a single downstream
+//! address, so for every `[translator] bind_address` after the first sv2d
+//! binds its own listener and relays each connection to the translator, which
+//! lets miners reach the same translator over a LAN and a VPN interface, or
+//! on a legacy port.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Accept `bind_address = "host:port"` as well as a list of addresses
+pub fn one_or_many<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(address) => vec![address],
+        OneOrMany::Many(addresses) => addresses,
+    })
+}
+
+/// Every address must be a distinct `ip:port`, and there must be at least one
+pub fn validate_addresses(addresses: &[String]) -> Result<()> {
+    if addresses.is_empty() {
+        return Err(anyhow!("[translator] bind_address needs at least one address"));
+    }
+    let mut seen = HashSet::new();
+    for address in addresses {
+        let parsed: SocketAddr = address.parse()
+            .map_err(|_| anyhow!("[translator] bind_address '{}' must be ip:port", address))?;
+        if !seen.insert(parsed) {
+            return Err(anyhow!("[translator] bind_address '{}' is listed twice", address));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListenerStatus {
+    pub address: String,
+    /// False for the translator's own listener, true for sv2d relays
+    pub relayed: bool,
+    pub active_connections: u64,
+    /// Connections accepted since startup, tracked for relays only
+    pub total_connections: Option<u64>,
+}
+
+/// Connection counts for one relay listener
+#[derive(Debug)]
+pub struct ListenerStats {
+    address: SocketAddr,
+    active: AtomicU64,
+    accepted: AtomicU64,
+}
+
+impl ListenerStats {
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn status(&self) -> ListenerStatus {
+        ListenerStatus {
+            address: self.address.to_string(),
+            relayed: true,
+            active_connections: self.active.load(Ordering::Relaxed),
+            total_connections: Some(self.accepted.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Bound relay listeners, ready to serve
+pub struct RelayListeners {
+    listeners: Vec<(TcpListener, Arc<ListenerStats>)>,
+}
+
+impl RelayListeners {
+    /// Bind every address, failing on the first one that can't be bound
+    pub async fn bind(addresses: &[String]) -> Result<Self> {
+        let mut listeners = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let listener = TcpListener::bind(address)
+                .await
+                .with_context(|| format!("Failed to bind stratum listener on {}", address))?;
+            let stats = Arc::new(ListenerStats {
+                address: listener.local_addr()?,
+                active: AtomicU64::new(0),
+                accepted: AtomicU64::new(0),
+            });
+            listeners.push((listener, stats));
+        }
+        Ok(Self { listeners })
+    }
+
+    pub fn stats(&self) -> Vec<Arc<ListenerStats>> {
+        self.listeners.iter().map(|(_, stats)| Arc::clone(stats)).collect()
+    }
+
+    /// Relay every accepted connection to `upstream` until `cancel` fires
+    pub fn serve(self, upstream: String, cancel: CancellationToken) {
+        for (listener, stats) in self.listeners {
+            info!("🔀 Relaying stratum connections on {} to {}", stats.address, upstream);
+            tokio::spawn(accept_loop(listener, stats, upstream.clone(), cancel.clone()));
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    stats: Arc<ListenerStats>,
+    upstream: String,
+    cancel: CancellationToken,
+) {
+    loop {
+        let (miner, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept on {}: {}", stats.address, e);
+                    continue;
+                }
+            },
+            _ = cancel.cancelled() => break,
+        };
+
+        stats.accepted.fetch_add(1, Ordering::Relaxed);
+        stats.active.fetch_add(1, Ordering::Relaxed);
+        let stats = Arc::clone(&stats);
+        let upstream = upstream.clone();
+        tokio::spawn(async move {
+            if let Err(e) = relay(miner, &upstream).await {
+                debug!("Relay for {} via {} ended: {:#}", peer, stats.address, e);
+            }
+            stats.active.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+async fn relay(mut miner: TcpStream, upstream: &str) -> Result<()> {
+    let mut translator = TcpStream::connect(upstream)
+        .await
+        .with_context(|| format!("translator at {} is not accepting connections", upstream))?;
+    tokio::io::copy_bidirectional(&mut miner, &mut translator).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_bind_address_accepts_one_or_many() {
+        #[derive(Deserialize)]
+        struct Config {
+            #[serde(deserialize_with = "one_or_many")]
+            bind_address: Vec<String>,
+        }
+
+        let one: Config = toml::from_str("bind_address = \"0.0.0.0:3333\"").unwrap();
+        assert_eq!(one.bind_address, ["0.0.0.0:3333"]);
+
+        let many: Config = toml::from_str("bind_address = [\"0.0.0.0:3333\", \"10.8.0.1:3334\"]").unwrap();
+        assert_eq!(many.bind_address.len(), 2);
+        assert!(validate_addresses(&many.bind_address).is_ok());
+
+        assert!(validate_addresses(&[]).is_err());
+        assert!(validate_addresses(&["localhost".to_string()]).is_err());
+        assert!(validate_addresses(&["0.0.0.0:3333".to_string(), "0.0.0.0:3333".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_relays_accept_on_every_address() {
+        // Stand-in translator that echoes what miners send
+        let translator = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream = translator.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = translator.accept().await {
+                tokio::spawn(async move {
+                    let (mut reader, mut writer) = stream.split();
+                    let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                });
+            }
+        });
+
+        let relays = RelayListeners::bind(&["127.0.0.1:0".to_string(), "127.0.0.1:0".to_string()])
+            .await
+            .unwrap();
+        let stats = relays.stats();
+        let cancel = CancellationToken::new();
+        relays.serve(upstream, cancel.clone());
+
+        let mut miners = Vec::new();
+        for listener in &stats {
+            let mut miner = TcpStream::connect(listener.address()).await.unwrap();
+            miner.write_all(b"mining.subscribe\n").await.unwrap();
+            let mut reply = [0u8; 17];
+            miner.read_exact(&mut reply).await.unwrap();
+            assert_eq!(&reply, b"mining.subscribe\n");
+            miners.push(miner);
+        }
+
+        for listener in &stats {
+            let status = listener.status();
+            assert_eq!(status.active_connections, 1);
+            assert_eq!(status.total_connections, Some(1));
+        }
+
+        // A taken address fails fast instead of being skipped
+        let taken = stats[0].address().to_string();
+        assert!(RelayListeners::bind(&[taken]).await.is_err());
+        cancel.cancel();
+    }
+}
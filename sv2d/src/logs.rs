@@ -0,0 +1,166 @@
+//! Component logs over JSON-RPC. Every component sv2d starts writes its own
+//! file; `logs` returns the end of one, and `logs_follow` answers with a
+//! chunked body that keeps carrying whatever is appended to it. A log that
+//! doesn't exist yet reads as empty rather than as an error, since a
+//! component that hasn't started has simply logged nothing.
+
+use hyper::body::{Body, Bytes};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How often a followed log is checked for new output
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum LogComponent {
+    #[serde(rename = "sv2d")]
+    Sv2d,
+    #[serde(rename = "sv2-tp")]
+    Sv2Tp,
+    #[serde(rename = "pool")]
+    Pool,
+    #[serde(rename = "translator")]
+    Translator,
+}
+
+impl LogComponent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogComponent::Sv2d => "sv2d",
+            LogComponent::Sv2Tp => "sv2-tp",
+            LogComponent::Pool => "pool",
+            LogComponent::Translator => "translator",
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        match self {
+            // Where `sv2-cli start` sends sv2d's own output
+            LogComponent::Sv2d => {
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                PathBuf::from(home).join(".sv2d").join("sv2d.log")
+            }
+            LogComponent::Sv2Tp => PathBuf::from("/tmp/sv2d-sv2-tp.log"),
+            LogComponent::Pool => PathBuf::from("/tmp/sv2d-pool.log"),
+            LogComponent::Translator => PathBuf::from("/tmp/sv2d-translator.log"),
+        }
+    }
+}
+
+/// Last `lines` lines of `path`, none if it doesn't exist yet
+pub fn tail(path: &Path, lines: usize) -> Vec<String> {
+    let contents = std::fs::read(path).unwrap_or_default();
+    last_lines(&contents, lines)
+}
+
+fn last_lines(contents: &[u8], lines: usize) -> Vec<String> {
+    let text = String::from_utf8_lossy(contents);
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].iter().map(|line| line.to_string()).collect()
+}
+
+/// Body that starts with the last `lines` lines of `path` and then streams
+/// each complete line appended to it, until the client goes away or sv2d
+/// shuts down
+pub fn follow(path: PathBuf, lines: usize, cancel: CancellationToken) -> Body {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        // One read for both, so nothing appended in between is sent twice
+        let contents = std::fs::read(&path).unwrap_or_default();
+        let mut offset = contents.len() as u64;
+        let backlog = last_lines(&contents, lines);
+        if !backlog.is_empty() {
+            let chunk = format!("{}\n", backlog.join("\n"));
+            if sender.send_data(Bytes::from(chunk)).await.is_err() {
+                return;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(FOLLOW_POLL_INTERVAL) => {}
+            }
+            let (appended, next) = read_appended(&path, offset);
+            offset = next;
+            if !appended.is_empty() && sender.send_data(Bytes::from(appended)).await.is_err() {
+                return;
+            }
+        }
+    });
+    body
+}
+
+/// Complete lines written to `path` after `offset`, and the offset just past
+/// them. A file shorter than `offset` was truncated or replaced, so it's read
+/// from the start.
+fn read_appended(path: &Path, offset: u64) -> (Vec<u8>, u64) {
+    let contents = std::fs::read(path).unwrap_or_default();
+    let start = if (contents.len() as u64) < offset { 0 } else { offset as usize };
+    // A partial last line waits for the rest of it
+    match contents[start..].iter().rposition(|&b| b == b'\n') {
+        Some(end) => (contents[start..start + end + 1].to_vec(), (start + end + 1) as u64),
+        None => (Vec::new(), start as u64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_missing_log_is_empty() {
+        assert!(tail(Path::new("/nonexistent/sv2d-test.log"), 20).is_empty());
+        assert_eq!(read_appended(Path::new("/nonexistent/sv2d-test.log"), 0), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn test_tail_and_appended_lines() {
+        let path = std::env::temp_dir().join(format!("sv2d-logs-test-{}.log", std::process::id()));
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        assert_eq!(tail(&path, 2), ["two", "three"]);
+        assert_eq!(tail(&path, 10).len(), 3);
+
+        let offset = std::fs::metadata(&path).unwrap().len();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"four\nfi").unwrap();
+        let (appended, offset) = read_appended(&path, offset);
+        assert_eq!(appended, b"four\n");
+        file.write_all(b"ve\n").unwrap();
+        assert_eq!(read_appended(&path, offset).0, b"five\n");
+
+        // Truncated, as when a component is restarted with a fresh log
+        std::fs::write(&path, "six\n").unwrap();
+        assert_eq!(read_appended(&path, offset), (b"six\n".to_vec(), 4));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_follow_streams_appended_lines() {
+        let path = std::env::temp_dir().join(format!("sv2d-logs-follow-{}.log", std::process::id()));
+        std::fs::write(&path, "old\nrecent\n").unwrap();
+        let cancel = CancellationToken::new();
+        let mut body = follow(path.clone(), 1, cancel.clone());
+
+        let first = hyper::body::HttpBody::data(&mut body).await.unwrap().unwrap();
+        assert_eq!(&first[..], b"recent\n");
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"new\n").unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(5), hyper::body::HttpBody::data(&mut body))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(&next[..], b"new\n");
+
+        // Ends once sv2d shuts down
+        cancel.cancel();
+        let end = tokio::time::timeout(Duration::from_secs(5), hyper::body::HttpBody::data(&mut body)).await.unwrap();
+        assert!(end.is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
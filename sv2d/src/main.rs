@@ -4,18 +4,57 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Stdio;
+use std::process::{ExitCode, Stdio};
 use tokio::process::{Child, Command as TokioCommand};
 use tokio::signal;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration, interval};
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use std::sync::Arc;
 use std::str::FromStr;
 
 mod bitcoin;
-use bitcoin::{Network, ensure_bitcoin_running};
+use self::bitcoin::{Network, ensure_bitcoin_running, verify_external_bitcoin};
+
+mod startup;
+use startup::{StartupProgress, Step};
+
+mod breaker;
+use breaker::{BreakerState, CircuitBreaker};
+
+mod maintenance;
+use maintenance::MaintenanceMode;
+
+mod binaries;
+use binaries::{BinaryRecord, BinaryRegistry, Fingerprint};
+
+mod tp_log;
+use tp_log::Sv2TpLogState;
+
+mod shutdown;
+use shutdown::ShutdownReason;
+
+mod authority;
+
+mod profile;
+
+mod share_probe;
+use share_probe::{ShareProbeStatus, ShareProbeTracker};
+
+mod listeners;
+use listeners::{ListenerStats, ListenerStatus, RelayListeners};
+
+mod rpc;
+use rpc::{Component, RpcCall, RpcError};
+
+mod logs;
+
+mod miner_stats;
+use miner_stats::MinerSamples;
+
+mod vardiff;
+use vardiff::FleetDifficulty;
 
 /// Find a binary by searching common locations
 fn find_binary(name: &str) -> Result<PathBuf> {
@@ -72,16 +111,35 @@ fn find_binary(name: &str) -> Result<PathBuf> {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DaemonConfig {
+    /// Built-in profile the rest of the config was layered on
+    #[serde(default)]
+    pub profile: Option<String>,
     pub daemon: DaemonSettings,
     pub bitcoin: BitcoinConfig,
     pub pool: PoolConfig,
     pub translator: TranslatorConfig,
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    #[serde(default)]
+    pub startup: StartupConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub sv2_tp: Sv2TpConfig,
+    #[serde(default)]
+    pub share_probe: ShareProbeConfig,
+    #[serde(default)]
+    pub ports: PortsConfig,
+    #[serde(default)]
+    pub miner_stats: MinerStatsConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DaemonSettings {
     pub mode: String, // "proxy" for now
-    pub network: String, // "signet", "regtest", "mainnet"
+    pub network: String, // "signet", "regtest", "testnet4", "mainnet"
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -89,6 +147,75 @@ pub struct BitcoinConfig {
     pub rpc_url: String,
     pub rpc_user: String,
     pub rpc_password: String,
+    /// When false, Bitcoin Core is an external dependency: sv2d only checks
+    /// its RPC and never starts or restarts it
+    #[serde(default = "default_bitcoin_managed")]
+    pub managed: bool,
+}
+
+fn default_bitcoin_managed() -> bool {
+    true
+}
+
+impl DaemonConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.runtime.validate()?;
+        self.startup.validate()?;
+        self.maintenance.validate()?;
+        self.shutdown.validate()?;
+        self.sv2_tp.validate()?;
+        self.translator.validate()?;
+        self.share_probe.validate()?;
+        self.miner_stats.validate()?;
+        // An address from another network would make block rewards unspendable
+        sv2_core::address::check_coinbase_address(&self.pool.coinbase_address, &self.daemon.network)
+            .map_err(|e| anyhow::anyhow!("[pool] coinbase_address: {}", e))?;
+        Ok(())
+    }
+
+    /// Fold `[ports] tp_port` into `[sv2_tp]` and check that no two
+    /// components were given the same port
+    pub fn resolve_ports(&mut self) -> Result<()> {
+        if let Some(tp_port) = self.ports.tp_port {
+            match self.sv2_tp.port {
+                Some(port) if port != tp_port => {
+                    return Err(anyhow::anyhow!(
+                        "[ports] tp_port = {} conflicts with [sv2_tp] port = {}; set only one of them",
+                        tp_port, port
+                    ));
+                }
+                _ => self.sv2_tp.port = Some(tp_port),
+            }
+        }
+
+        // Malformed addresses are left for TranslatorConfig::validate to report
+        let translator = self.translator.bind_address.first();
+        if let Some(address) = translator.and_then(|a| a.parse::<std::net::SocketAddr>().ok()) {
+            if address.port() != self.ports.translator_downstream {
+                return Err(anyhow::anyhow!(
+                    "[translator] bind_address {} doesn't match [ports] translator_downstream = {}",
+                    address, self.ports.translator_downstream
+                ));
+            }
+        }
+
+        let mut ports = vec![
+            ("pool_listen", self.ports.pool_listen),
+            ("pool_plain", self.ports.pool_plain),
+            ("translator_downstream", self.ports.translator_downstream),
+            ("json_rpc", self.ports.json_rpc),
+        ];
+        // An external sv2-tp listens on its own host
+        if !self.sv2_tp.external {
+            ports.push(("tp_port", self.sv2_tp.port(&self.daemon.network)));
+        }
+        for (i, (name, port)) in ports.iter().enumerate() {
+            if let Some((other, _)) = ports[..i].iter().find(|(_, other)| other == port) {
+                return Err(anyhow::anyhow!("[ports] {} and {} are both {}", other, name, port));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -99,8 +226,476 @@ pub struct PoolConfig {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TranslatorConfig {
-    pub bind_address: String,
+    /// Stratum address miners connect to, or a list of them. The translator
+    /// binds the first; sv2d relays connections on the others to it.
+    #[serde(deserialize_with = "listeners::one_or_many")]
+    pub bind_address: Vec<String>,
     pub min_extranonce2_size: u32,
+    /// Vardiff target share rate per miner, until miners report hashrates
+    /// and it's derived from the fleet
+    #[serde(default = "default_shares_per_minute")]
+    pub shares_per_minute: f64,
+    /// Hashrate vardiff assumes for a newly connected miner, in H/s, until
+    /// miners report hashrates and the slowest one is used
+    #[serde(default = "default_min_individual_miner_hashrate")]
+    pub min_individual_miner_hashrate: f64,
+}
+
+fn default_shares_per_minute() -> f64 {
+    5.0
+}
+
+fn default_min_individual_miner_hashrate() -> f64 {
+    500_000_000_000.0 // 0.5 TH/s, a Bitaxe
+}
+
+impl TranslatorConfig {
+    pub fn validate(&self) -> Result<()> {
+        listeners::validate_addresses(&self.bind_address)?;
+        if !self.shares_per_minute.is_finite() || self.shares_per_minute <= 0.0 {
+            return Err(anyhow::anyhow!("[translator] shares_per_minute must be greater than 0"));
+        }
+        if !self.min_individual_miner_hashrate.is_finite() || self.min_individual_miner_hashrate <= 0.0 {
+            return Err(anyhow::anyhow!("[translator] min_individual_miner_hashrate must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    /// The address translator_sv2 itself listens on
+    pub fn primary_address(&self) -> &str {
+        &self.bind_address[0]
+    }
+
+    /// Addresses sv2d binds and relays to the translator
+    pub fn relay_addresses(&self) -> &[String] {
+        &self.bind_address[1..]
+    }
+}
+
+/// Where the Template Provider comes from. By default sv2d spawns and
+/// supervises its own sv2-tp; with `external = true` it connects to one
+/// managed elsewhere and only health-checks it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Sv2TpConfig {
+    #[serde(default)]
+    pub external: bool,
+    #[serde(default = "default_sv2_tp_host")]
+    pub host: String,
+    /// Defaults to the standard sv2-tp port for the configured network
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Required for an external sv2-tp, since its log isn't available to read
+    #[serde(default)]
+    pub authority_key: Option<String>,
+}
+
+fn default_sv2_tp_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl Default for Sv2TpConfig {
+    fn default() -> Self {
+        Self {
+            external: false,
+            host: default_sv2_tp_host(),
+            port: None,
+            authority_key: None,
+        }
+    }
+}
+
+impl Sv2TpConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.host.trim().is_empty() {
+            return Err(anyhow::anyhow!("[sv2_tp] host must not be empty"));
+        }
+        if self.external && self.authority_key.as_deref().map_or(true, |k| k.trim().is_empty()) {
+            return Err(anyhow::anyhow!(
+                "[sv2_tp] external = true requires authority_key. \
+                 Copy the 'Template Provider authority key' line from your sv2-tp log."
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn port(&self, network: &str) -> u16 {
+        self.port.unwrap_or_else(|| default_sv2_tp_port(network))
+    }
+
+    pub fn address(&self, network: &str) -> String {
+        format!("{}:{}", self.host, self.port(network))
+    }
+}
+
+fn default_sv2_tp_port(network: &str) -> u16 {
+    match network {
+        "regtest" => 18447,
+        "signet" => 38336,
+        "testnet4" => 48336,
+        "mainnet" => 8336,
+        _ => 38336, // default to signet port
+    }
+}
+
+/// Ports sv2d and the components it starts listen on. The defaults are the
+/// ports sv2d has always used; a second instance on the same host needs its
+/// own value for each.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PortsConfig {
+    /// Encrypted port of pool_sv2, which the translator connects to
+    #[serde(default = "default_pool_listen_port")]
+    pub pool_listen: u16,
+    /// Unencrypted port pool_sv2 opens alongside `pool_listen`
+    #[serde(default = "default_pool_plain_port")]
+    pub pool_plain: u16,
+    /// Port of translator_sv2 that miners connect to; must match
+    /// `[translator] bind_address`
+    #[serde(default = "default_translator_downstream_port")]
+    pub translator_downstream: u16,
+    /// Overrides the network's standard sv2-tp port, like `[sv2_tp] port`
+    #[serde(default)]
+    pub tp_port: Option<u16>,
+    /// sv2d's JSON-RPC port, which sv2-cli talks to
+    #[serde(default = "default_json_rpc_port")]
+    pub json_rpc: u16,
+}
+
+fn default_pool_listen_port() -> u16 {
+    34254
+}
+
+fn default_pool_plain_port() -> u16 {
+    34250
+}
+
+fn default_translator_downstream_port() -> u16 {
+    3333
+}
+
+fn default_json_rpc_port() -> u16 {
+    8333
+}
+
+impl Default for PortsConfig {
+    fn default() -> Self {
+        Self {
+            pool_listen: default_pool_listen_port(),
+            pool_plain: default_pool_plain_port(),
+            translator_downstream: default_translator_downstream_port(),
+            tp_port: None,
+            json_rpc: default_json_rpc_port(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuntimeConfig {
+    /// Number of tokio worker threads (defaults to the number of CPUs)
+    #[serde(default = "default_worker_threads")]
+    pub worker_threads: usize,
+}
+
+fn default_worker_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: default_worker_threads(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.worker_threads == 0 {
+            return Err(anyhow::anyhow!(
+                "[runtime] worker_threads must be at least 1. \
+                 More threads let sv2d serve RPC and supervise components in parallel on busy hosts; \
+                 fewer threads reduce memory and context switching on small machines."
+            ));
+        }
+
+        let cpus = default_worker_threads();
+        if self.worker_threads > cpus * 4 {
+            warn!(
+                "[runtime] worker_threads = {} is far above the {} available CPUs; \
+                 extra threads add scheduling overhead without adding throughput",
+                self.worker_threads, cpus
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// How long to wait for each managed component to start listening
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StartupConfig {
+    #[serde(default = "ReadinessConfig::sv2_tp")]
+    pub sv2_tp: ReadinessConfig,
+    #[serde(default = "ReadinessConfig::pool")]
+    pub pool: ReadinessConfig,
+    #[serde(default = "ReadinessConfig::translator")]
+    pub translator: ReadinessConfig,
+    /// Extra seconds to wait for sv2-tp when its log shows it is still
+    /// waiting for Bitcoin Core IPC rather than failing
+    #[serde(default = "default_sv2_tp_ipc_grace_secs")]
+    pub sv2_tp_ipc_grace_secs: u64,
+}
+
+fn default_sv2_tp_ipc_grace_secs() -> u64 {
+    120
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            sv2_tp: ReadinessConfig::sv2_tp(),
+            pool: ReadinessConfig::pool(),
+            translator: ReadinessConfig::translator(),
+            sv2_tp_ipc_grace_secs: default_sv2_tp_ipc_grace_secs(),
+        }
+    }
+}
+
+impl StartupConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.sv2_tp.validate("sv2_tp")?;
+        self.pool.validate("pool")?;
+        self.translator.validate("translator")?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReadinessConfig {
+    /// Give up if the component isn't ready after this many seconds
+    pub ready_timeout_secs: u64,
+    /// Seconds between readiness checks
+    pub poll_interval_secs: u64,
+}
+
+impl ReadinessConfig {
+    /// sv2-tp has to connect to Bitcoin Core IPC first, which can take 30-60 seconds
+    fn sv2_tp() -> Self {
+        Self { ready_timeout_secs: 60, poll_interval_secs: 2 }
+    }
+
+    fn pool() -> Self {
+        Self { ready_timeout_secs: 30, poll_interval_secs: 2 }
+    }
+
+    fn translator() -> Self {
+        Self { ready_timeout_secs: 30, poll_interval_secs: 2 }
+    }
+
+    fn validate(&self, component: &str) -> Result<()> {
+        if self.ready_timeout_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "[startup.{}] ready_timeout_secs must be greater than 0", component
+            ));
+        }
+        if self.poll_interval_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "[startup.{}] poll_interval_secs must be greater than 0", component
+            ));
+        }
+        if self.poll_interval_secs > self.ready_timeout_secs {
+            return Err(anyhow::anyhow!(
+                "[startup.{}] poll_interval_secs ({}) must not exceed ready_timeout_secs ({})",
+                component, self.poll_interval_secs, self.ready_timeout_secs
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of readiness checks that fit in the timeout
+    fn attempts(&self) -> u64 {
+        (self.ready_timeout_secs / self.poll_interval_secs).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaintenanceConfig {
+    /// Maintenance mode switches itself off after this many seconds
+    #[serde(default = "default_maintenance_max_duration_secs")]
+    pub max_duration_secs: u64,
+}
+
+fn default_maintenance_max_duration_secs() -> u64 {
+    3600
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: default_maintenance_max_duration_secs(),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_duration_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "[maintenance] max_duration_secs must be greater than 0"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn max_duration(&self) -> Duration {
+        Duration::from_secs(self.max_duration_secs)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShutdownConfig {
+    /// How long components get to exit after SIGTERM, all together, before
+    /// the ones still running are killed
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_shutdown_timeout_secs(),
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_secs == 0 {
+            return Err(anyhow::anyhow!("[shutdown] timeout_secs must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+/// Periodically push a synthetic share through the translator and mark the
+/// pipeline Critical when it stops being answered. Off by default.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShareProbeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_share_probe_interval_secs")]
+    pub interval_secs: u64,
+    /// How long to wait for the translator to answer the probe share
+    #[serde(default = "default_share_probe_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Consecutive failed probes before the pipeline is Critical
+    #[serde(default = "default_share_probe_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Difficulty the probe suggests for its share
+    #[serde(default = "default_share_probe_difficulty")]
+    pub difficulty: f64,
+}
+
+fn default_share_probe_interval_secs() -> u64 {
+    300
+}
+
+fn default_share_probe_timeout_secs() -> u64 {
+    15
+}
+
+fn default_share_probe_failure_threshold() -> u32 {
+    3
+}
+
+fn default_share_probe_difficulty() -> f64 {
+    0.001
+}
+
+impl Default for ShareProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_share_probe_interval_secs(),
+            timeout_secs: default_share_probe_timeout_secs(),
+            failure_threshold: default_share_probe_failure_threshold(),
+            difficulty: default_share_probe_difficulty(),
+        }
+    }
+}
+
+impl ShareProbeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.interval_secs == 0 || self.timeout_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "[share_probe] interval_secs and timeout_secs must be greater than 0"
+            ));
+        }
+        if self.timeout_secs > self.interval_secs {
+            return Err(anyhow::anyhow!(
+                "[share_probe] timeout_secs ({}) must not exceed interval_secs ({})",
+                self.timeout_secs, self.interval_secs
+            ));
+        }
+        if self.failure_threshold == 0 {
+            return Err(anyhow::anyhow!("[share_probe] failure_threshold must be at least 1"));
+        }
+        if !self.difficulty.is_finite() || self.difficulty <= 0.0 {
+            return Err(anyhow::anyhow!("[share_probe] difficulty must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+/// Poll connected miners' own APIs for hashrate and accepted shares
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MinerStatsConfig {
+    #[serde(default = "default_miner_stats_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_miner_stats_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_miner_stats_enabled() -> bool {
+    true
+}
+
+fn default_miner_stats_poll_interval_secs() -> u64 {
+    60
+}
+
+impl Default for MinerStatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_miner_stats_enabled(),
+            poll_interval_secs: default_miner_stats_poll_interval_secs(),
+        }
+    }
+}
+
+impl MinerStatsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && self.poll_interval_secs == 0 {
+            return Err(anyhow::anyhow!("[miner_stats] poll_interval_secs must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -129,21 +724,64 @@ pub struct DaemonState {
     pub processes: RwLock<HashMap<String, Child>>,
     pub start_time: std::time::Instant,
     pub connected_miners: RwLock<HashMap<String, MinerInfo>>,
+    pub miner_samples: RwLock<MinerSamples>, // What connected miners' APIs last reported
     pub cancellation_token: CancellationToken,
+    pub shutdown_reason: RwLock<Option<ShutdownReason>>, // Set by whoever triggers shutdown
     pub authority_key: RwLock<Option<String>>, // Cache authority key for restarts
+    pub pool_authority_key: RwLock<Option<String>>, // Key the running pool actually uses
+    pub sv2_version: String, // Detected once at startup
+    pub breakers: RwLock<HashMap<String, CircuitBreaker>>, // Restart circuit breakers per component
+    pub maintenance: RwLock<MaintenanceMode>, // Pauses automatic restarts
+    pub binaries: RwLock<BinaryRegistry>, // Version and hash of each launched binary
+    pub share_probe: RwLock<ShareProbeTracker>, // End-to-end share flow results
+    pub relay_listeners: RwLock<Vec<Arc<ListenerStats>>>, // Extra translator bind addresses
+    pub startup: RwLock<StartupProgress>, // Outcome of each startup step
 }
 
 impl DaemonState {
-    pub fn new(config: DaemonConfig) -> Self {
+    pub fn new(config: DaemonConfig, sv2_version: String) -> Self {
         Self {
-            config,
+            sv2_version,
             components: RwLock::new(HashMap::new()),
             processes: RwLock::new(HashMap::new()),
             start_time: std::time::Instant::now(),
             connected_miners: RwLock::new(HashMap::new()),
+            miner_samples: RwLock::new(MinerSamples::default()),
             cancellation_token: CancellationToken::new(),
+            shutdown_reason: RwLock::new(None),
             authority_key: RwLock::new(None),
+            pool_authority_key: RwLock::new(None),
+            breakers: RwLock::new(HashMap::new()),
+            maintenance: RwLock::new(MaintenanceMode::default()),
+            binaries: RwLock::new(BinaryRegistry::default()),
+            share_probe: RwLock::new(ShareProbeTracker::new(config.share_probe.failure_threshold)),
+            relay_listeners: RwLock::new(Vec::new()),
+            startup: RwLock::new(StartupProgress::new(STARTUP_STEPS)),
+            config,
+        }
+    }
+
+    /// Begin daemon shutdown. The first reason recorded wins, so a fatal
+    /// failure isn't masked by the shutdown it triggers.
+    pub async fn request_shutdown(&self, reason: ShutdownReason) {
+        let mut current = self.shutdown_reason.write().await;
+        if current.is_none() {
+            *current = Some(reason);
         }
+        self.cancellation_token.cancel();
+    }
+
+    /// Whether maintenance mode is on, logging once when it expires
+    pub async fn in_maintenance(&self) -> bool {
+        let mut maintenance = self.maintenance.write().await;
+        let now = std::time::Instant::now();
+        let elapsed = maintenance.elapsed(now);
+        let active = maintenance.is_active(now);
+        if !active && elapsed.is_some() {
+            warn!("Maintenance mode expired after {}s, automatic restarts resumed",
+                  self.config.maintenance.max_duration_secs);
+        }
+        active
     }
 
     pub async fn update_component_status(&self, name: &str, running: bool, pid: Option<u32>) {
@@ -199,6 +837,10 @@ pub struct StatusResponse {
     pub miners: ConnectedMinersInfo,
     pub system_info: SystemInfo,
     pub uptime_seconds: u64,
+    pub maintenance_expires_in_seconds: Option<u64>, // Set while maintenance mode is on
+    pub share_probe: Option<ShareProbeStatus>, // Set when the share probe is enabled
+    pub listeners: Vec<ListenerStatus>, // Translator listener first, then relays
+    pub startup: StartupProgress,
 }
 
 #[derive(Debug, Serialize)]
@@ -209,6 +851,7 @@ pub struct ComponentStatusInfo {
     pub restart_count: u32,
     pub health_status: HealthStatus,
     pub last_error: Option<String>,
+    pub breaker_state: BreakerState,
 }
 
 #[derive(Debug, Serialize)]
@@ -234,29 +877,72 @@ pub struct SystemInfo {
     pub bitcoin_synced: Option<bool>,
     pub sv2_version: String,
     pub daemon_version: String,
+    pub binaries: HashMap<String, BinaryRecord>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
+    /// Echoed back in the response; clients that send none get `null`
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
     pub method: String,
+    #[serde(default)]
     pub params: serde_json::Value,
 }
 
+impl JsonRpcRequest {
+    /// Parse a request body, or the error response for one that isn't a
+    /// request, keeping its id when it has one
+    pub fn parse(body: &[u8]) -> Result<Self, JsonRpcResponse> {
+        let value: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| JsonRpcResponse::error(RpcError::parse_error(e)))?;
+        let id = value.get("id").cloned().unwrap_or_default();
+        serde_json::from_value(value)
+            .map_err(|e| JsonRpcResponse::error(RpcError::invalid_request(e)).with_id(id))
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct JsonRpcResponse {
-    pub result: serde_json::Value,
+    pub id: serde_json::Value,
+    /// Exactly one of `result` and `error` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl JsonRpcResponse {
+    pub fn success(result: serde_json::Value) -> Self {
+        Self { id: serde_json::Value::Null, result: Some(result), error: None }
+    }
+
+    pub fn error(error: RpcError) -> Self {
+        Self { id: serde_json::Value::Null, result: None, error: Some(error) }
+    }
+
+    pub fn with_id(mut self, id: serde_json::Value) -> Self {
+        self.id = id;
+        self
+    }
 }
 
 async fn start_bitcoin_core(state: Arc<DaemonState>) -> Result<()> {
-    info!("🟡 Starting Bitcoin Core with smart detection...");
-    
     // Parse network from config
     let network = Network::from_str(&state.config.daemon.network)
         .context("Invalid network in config")?;
     
-    // Use smart Bitcoin detection/startup
-    let bitcoin_connection = ensure_bitcoin_running(network).await
-        .context("Failed to ensure Bitcoin Core is running")?;
+    let bitcoin_connection = if state.config.bitcoin.managed {
+        info!("🟡 Starting Bitcoin Core with smart detection...");
+        // Use smart Bitcoin detection/startup
+        ensure_bitcoin_running(network).await
+            .context("Failed to ensure Bitcoin Core is running")?
+    } else {
+        info!("🟡 Checking external Bitcoin Core at {}...", state.config.bitcoin.rpc_url);
+        let bitcoin = &state.config.bitcoin;
+        verify_external_bitcoin(&bitcoin.rpc_url, &bitcoin.rpc_user, &bitcoin.rpc_password, network).await
+            .context("External Bitcoin Core is not available ([bitcoin] managed = false, so sv2d will not start it)")?
+    };
     
     info!("✅ Bitcoin Core ready:");
     info!("   Network: {}", bitcoin_connection.network);
@@ -305,18 +991,15 @@ async fn extract_authority_key_from_logs() -> Result<String> {
 }
 
 async fn start_sv2_tp(state: Arc<DaemonState>) -> Result<String> {
+    if state.config.sv2_tp.external {
+        return connect_external_sv2_tp(state).await;
+    }
+
     info!("🟡 Starting sv2-tp...");
 
     let network = &state.config.daemon.network;
     let datadir = format!("/tmp/bitcoin_{}", network);
-    
-    // Determine correct sv2-tp port based on network
-    let sv2_port = match network.as_str() {
-        "regtest" => 18447,
-        "signet" => 38336, 
-        "mainnet" => 8336,
-        _ => 38336, // default to signet port
-    };
+    let sv2_port = state.config.sv2_tp.port(network);
     
     // Open log files
     let log_file = std::fs::OpenOptions::new()
@@ -324,9 +1007,12 @@ async fn start_sv2_tp(state: Arc<DaemonState>) -> Result<String> {
         .append(true)
         .open("/tmp/sv2d-sv2-tp.log")
         .context("Failed to open sv2-tp log file")?;
+    // Only this run's output matters when diagnosing a slow start
+    let log_offset = log_file.metadata().map(|m| m.len()).unwrap_or(0);
 
     let sv2_tp_path = find_binary("sv2-tp")?;
-    let child = TokioCommand::new(&sv2_tp_path)
+    record_binary(&state, "sv2-tp", &sv2_tp_path).await;
+    let mut child = TokioCommand::new(&sv2_tp_path)
         .arg(format!("-chain={}", network))
         .arg(format!("-datadir={}", datadir))
         .arg(format!("-sv2port={}", sv2_port))
@@ -341,49 +1027,195 @@ async fn start_sv2_tp(state: Arc<DaemonState>) -> Result<String> {
     info!("Started sv2-tp with PID: {:?} on port {}", pid, sv2_port);
     
     // Wait for it to be ready (check for listening port)
-    // sv2-tp needs to connect to Bitcoin IPC first, which can take 30-60 seconds
-    for i in 0..30 {
-        sleep(Duration::from_secs(2)).await;
-        if test_tcp_port(sv2_port).await {
-            info!("✅ sv2-tp ready on port {}", sv2_port);
-            state.update_component_status("sv2-tp", true, pid).await;
+    wait_for_sv2_tp(&mut child, sv2_port, &state.config.startup, "/tmp/sv2d-sv2-tp.log", log_offset).await?;
 
-            let mut processes = state.processes.write().await;
-            processes.insert("sv2-tp".to_string(), child);
+    info!("✅ sv2-tp ready on port {}", sv2_port);
+    state.update_component_status("sv2-tp", true, pid).await;
 
-            // Extract authority key from logs
-            let authority_key = extract_authority_key_from_logs().await
-                .context("Failed to extract authority key from sv2-tp logs")?;
+    let mut processes = state.processes.write().await;
+    processes.insert("sv2-tp".to_string(), child);
 
-            // Cache the authority key for future restarts
-            let mut cached_key = state.authority_key.write().await;
-            *cached_key = Some(authority_key.clone());
+    // Extract authority key from logs
+    let authority_key = extract_authority_key_from_logs().await
+        .context("Failed to extract authority key from sv2-tp logs")?;
 
-            return Ok(authority_key);
-        }
-        if i % 5 == 0 {
-            info!("Waiting for sv2-tp (connecting to Bitcoin IPC)... ({}/30)", i + 1);
-        }
-    }
+    // Cache the authority key for future restarts
+    let mut cached_key = state.authority_key.write().await;
+    *cached_key = Some(authority_key.clone());
 
-    Err(anyhow::anyhow!(
-        "Template Provider (sv2-tp) failed to start within 60 seconds. \
-         Bitcoin Core IPC socket may not be ready or accessible. \
-         Verify Bitcoin Core is running with '-ipcbind=unix' flag and the IPC socket exists."
-    ))
+    Ok(authority_key)
 }
 
-async fn start_pool(state: Arc<DaemonState>, authority_key: &str) -> Result<()> {
-    info!("🟡 Starting SRI Pool...");
+/// Use a Template Provider that sv2d doesn't manage: wait for it to accept
+/// connections and take the authority key from config instead of its log.
+async fn connect_external_sv2_tp(state: Arc<DaemonState>) -> Result<String> {
+    let address = state.config.sv2_tp.address(&state.config.daemon.network);
+    let authority_key = state.config.sv2_tp.authority_key.clone()
+        .context("[sv2_tp] external = true requires authority_key")?;
+    info!("🟡 Connecting to external sv2-tp at {}...", address);
+
+    let readiness = &state.config.startup.sv2_tp;
+    let attempts = readiness.attempts();
+    let mut reachable = test_tcp_addr(&address).await;
+    for i in 0..attempts {
+        if reachable {
+            break;
+        }
+        info!("Waiting for external sv2-tp at {}... ({}/{})", address, i + 1, attempts);
+        sleep(Duration::from_secs(readiness.poll_interval_secs)).await;
+        reachable = test_tcp_addr(&address).await;
+    }
+    if !reachable {
+        return Err(anyhow::anyhow!(
+            "External sv2-tp at {} was not reachable within {}s. \
+             sv2d does not start an external Template Provider; make sure it is running, \
+             or set [sv2_tp] external = false to let sv2d manage it.",
+            address, readiness.ready_timeout_secs
+        ));
+    }
+
+    info!("✅ External sv2-tp reachable at {}", address);
+    state.update_component_status("sv2-tp", true, None).await;
+    *state.authority_key.write().await = Some(authority_key.clone());
+
+    Ok(authority_key)
+}
+
+/// Poll `port` until the component is listening or its readiness timeout
+/// expires. On timeout the error includes `hint` and the tail of the
+/// component's log.
+async fn wait_until_ready(
+    component: &str,
+    port: u16,
+    readiness: &ReadinessConfig,
+    log_path: &str,
+    hint: &str,
+) -> Result<()> {
+    let attempts = readiness.attempts();
+    for i in 0..attempts {
+        sleep(Duration::from_secs(readiness.poll_interval_secs)).await;
+        if test_tcp_port(port).await {
+            return Ok(());
+        }
+        info!("Waiting for {} on port {}... ({}/{})", component, port, i + 1, attempts);
+    }
+
+    Err(anyhow::anyhow!(
+        "Component {} failed to become ready within {}s. {}\nLast lines of {}:\n{}",
+        component,
+        readiness.ready_timeout_secs,
+        hint,
+        log_path,
+        log_tail(log_path, 20)
+    ))
+}
+
+const SV2_TP_IPC_HINT: &str = "Verify Bitcoin Core is running with '-ipcbind=unix' and that its IPC socket \
+    (node.sock in the Bitcoin data directory) exists and is readable by sv2d.";
+
+/// Wait for sv2-tp to listen on `port`. Unlike other components, sv2-tp can
+/// legitimately sit for a while waiting on Bitcoin Core IPC, so when its log
+/// shows that, the wait is extended by `sv2_tp_ipc_grace_secs` with progress
+/// reports. If sv2-tp exits instead, fail straight away.
+async fn wait_for_sv2_tp(
+    child: &mut Child,
+    port: u16,
+    startup: &StartupConfig,
+    log_path: &str,
+    log_offset: u64,
+) -> Result<()> {
+    let readiness = &startup.sv2_tp;
+    let poll_interval = readiness.poll_interval_secs;
+    let base_attempts = readiness.attempts();
+    let grace_attempts = startup.sv2_tp_ipc_grace_secs / poll_interval;
+    let mut log_state = Sv2TpLogState::Unknown;
+    let mut attempt = 0;
+
+    loop {
+        sleep(Duration::from_secs(poll_interval)).await;
+        attempt += 1;
+        if test_tcp_port(port).await {
+            return Ok(());
+        }
+
+        let previous_state = log_state;
+        log_state = tp_log::classify(&tp_log::read_since(log_path, log_offset));
+        let waited = attempt * poll_interval;
+
+        if let Ok(Some(status)) = child.try_wait() {
+            let cause = match &log_state {
+                Sv2TpLogState::WaitingForIpc => format!(
+                    "It exited while still waiting for Bitcoin Core IPC. {}", SV2_TP_IPC_HINT
+                ),
+                Sv2TpLogState::Failed(line) => format!("It reported: {}", line),
+                Sv2TpLogState::Unknown => "Check the log below for the cause.".to_string(),
+            };
+            return Err(anyhow::anyhow!(
+                "sv2-tp crashed during startup (exited with {} after {}s). {}\nLast lines of {}:\n{}",
+                status, waited, cause, log_path, log_tail(log_path, 20)
+            ));
+        }
+
+        if log_state == Sv2TpLogState::WaitingForIpc && previous_state != Sv2TpLogState::WaitingForIpc {
+            info!("sv2-tp is waiting for Bitcoin Core IPC; this can take a while after Bitcoin Core starts");
+        }
+
+        if attempt < base_attempts {
+            info!("Waiting for sv2-tp on port {}... ({}/{})", port, attempt, base_attempts);
+            continue;
+        }
+
+        if log_state == Sv2TpLogState::WaitingForIpc && attempt < base_attempts + grace_attempts {
+            info!(
+                "Still waiting for Bitcoin Core IPC, extending sv2-tp startup wait ({}s elapsed, up to {}s)",
+                waited,
+                readiness.ready_timeout_secs + startup.sv2_tp_ipc_grace_secs
+            );
+            continue;
+        }
+
+        let reason = match &log_state {
+            Sv2TpLogState::WaitingForIpc => format!(
+                "sv2-tp is still waiting for Bitcoin Core IPC after {}s. {}", waited, SV2_TP_IPC_HINT
+            ),
+            Sv2TpLogState::Failed(line) => format!(
+                "sv2-tp is running but reported an error: {}", line
+            ),
+            Sv2TpLogState::Unknown => format!(
+                "sv2-tp is running but not listening, and its log doesn't show it waiting for Bitcoin Core IPC. {}",
+                SV2_TP_IPC_HINT
+            ),
+        };
+        return Err(anyhow::anyhow!(
+            "Component sv2-tp failed to become ready within {}s. {}\nLast lines of {}:\n{}",
+            waited, reason, log_path, log_tail(log_path, 20)
+        ));
+    }
+}
+
+/// Last `lines` lines of a log file, or a note if it can't be read
+fn log_tail(path: &str, lines: usize) -> String {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            let all: Vec<&str> = contents.lines().collect();
+            let tail = &all[all.len().saturating_sub(lines)..];
+            if tail.is_empty() {
+                "  (log is empty)".to_string()
+            } else {
+                tail.iter().map(|line| format!("  {}", line)).collect::<Vec<_>>().join("\n")
+            }
+        }
+        Err(e) => format!("  (could not read log: {})", e),
+    }
+}
+
+async fn start_pool(state: Arc<DaemonState>, authority_key: &str) -> Result<()> {
+    info!("🟡 Starting SRI Pool...");
 
     // Generate pool config
     let network = &state.config.daemon.network;
-    let tp_port = match network.as_str() {
-        "regtest" => 18447,
-        "signet" => 38336,
-        "mainnet" => 8336,
-        _ => 38336, // default to signet port
-    };
+    let tp_address = state.config.sv2_tp.address(network);
+    let ports = &state.config.ports;
 
     info!("📝 Generating pool config with authority key: {}", authority_key);
 
@@ -392,8 +1224,8 @@ async fn start_pool(state: Arc<DaemonState>, authority_key: &str) -> Result<()>
 authority_public_key = "{}"
 authority_secret_key = "mkDLTBBRxdBv998612qipDYoTK3YUrqLe8uWw7gu3iXbSrn2n"
 cert_validity_sec = 3600
-test_only_listen_adress_plain = "0.0.0.0:34250"
-listen_address = "0.0.0.0:34254"
+test_only_listen_adress_plain = "0.0.0.0:{}"
+listen_address = "0.0.0.0:{}"
 
 # Mining address
 coinbase_reward_script = "addr({})"
@@ -405,11 +1237,17 @@ server_id = 1
 pool_signature = "{}"
 
 # Template Provider config
-tp_address = "127.0.0.1:{}"
+tp_address = "{}"
 shares_per_minute = 1.0
 share_batch_size = 10
 "#,
-        network, authority_key, state.config.pool.coinbase_address, state.config.pool.signature, tp_port
+        network,
+        authority_key,
+        ports.pool_plain,
+        ports.pool_listen,
+        state.config.pool.coinbase_address,
+        state.config.pool.signature,
+        tp_address
     );
 
     let config_path = format!("/tmp/pool_{}.toml", network);
@@ -422,8 +1260,10 @@ share_batch_size = 10
         .append(true)
         .open("/tmp/sv2d-pool.log")
         .context("Failed to open pool log file")?;
+    let log_offset = log_file.metadata().map(|m| m.len()).unwrap_or(0);
 
     let pool_path = find_binary("pool_sv2")?;
+    record_binary(&state, "pool_sv2", &pool_path).await;
     let child = TokioCommand::new(&pool_path)
         .arg("--config")
         .arg(&config_path)  // Use dynamically generated config
@@ -435,42 +1275,49 @@ share_batch_size = 10
     let pid = child.id();
     info!("Started SRI Pool with PID: {:?}", pid);
     
-    // Wait for it to be ready with improved timing
-    for i in 0..15 {
-        sleep(Duration::from_secs(2)).await;
-        if test_tcp_port(34254).await {
-            info!("✅ SRI Pool ready on port 34254");
-            state.update_component_status("pool", true, pid).await;
-            
-            let mut processes = state.processes.write().await;
-            processes.insert("pool".to_string(), child);
-            
-            return Ok(());
-        }
-        if i % 3 == 0 {
-            info!("Waiting for SRI Pool to start... ({}/15) - checking port 34254", i + 1);
-        }
-    }
-    
-    Err(anyhow::anyhow!(
-        "SRI Pool failed to start within 30 seconds. \
-         The pool may be unable to bind to its port or configuration is invalid. \
-         Check that port 34254 is available and pool configuration is correct."
-    ))
+    // Wait for it to be ready
+    wait_until_ready(
+        "pool",
+        ports.pool_listen,
+        &state.config.startup.pool,
+        "/tmp/sv2d-pool.log",
+        &format!(
+            "The pool may be unable to bind to its port or configuration is invalid. \
+             Check that port {} is available and pool configuration is correct.",
+            ports.pool_listen
+        ),
+    )
+    .await?;
+
+    info!("✅ SRI Pool ready on port {}", ports.pool_listen);
+    state.update_component_status("pool", true, pid).await;
+
+    // Prefer the key the pool printed over the one we asked it to use
+    let pool_key = authority::from_pool_log(&tp_log::read_since("/tmp/sv2d-pool.log", log_offset))
+        .or_else(|| authority::from_pool_config(&fs::read_to_string(&config_path).ok()?));
+    *state.pool_authority_key.write().await = pool_key;
+
+    let mut processes = state.processes.write().await;
+    processes.insert("pool".to_string(), child);
+
+    Ok(())
 }
 
-async fn start_translator(state: Arc<DaemonState>) -> Result<()> {
-    info!("🟡 Starting SRI Translator...");
+/// Vardiff settings for the miners seen so far, or the configured ones
+/// before any has reported a hashrate
+async fn fleet_difficulty(state: &DaemonState) -> FleetDifficulty {
+    let hashrates = state.miner_samples.read().await.known_hashrates();
+    FleetDifficulty::from_hashrates(&hashrates, &state.config.translator)
+}
 
-    // Give pool extra time to be fully ready for connections
-    info!("Waiting 10 seconds for pool to be fully ready to accept connections...");
-    sleep(Duration::from_secs(10)).await;
+async fn write_translator_config(state: Arc<DaemonState>) -> Result<()> {
+    let difficulty = fleet_difficulty(&state).await;
 
     // Generate translator config based on our working config
     let translator_config = format!(
         r#"# SRI Translator Configuration for Multi-miner Support
 downstream_address = "0.0.0.0"
-downstream_port = 3333
+downstream_port = {}
 
 # Version support
 max_supported_version = 2
@@ -487,21 +1334,77 @@ aggregate_channels = true
 
 # Difficulty params optimized for multi-miner
 [downstream_difficulty_config]
-min_individual_miner_hashrate = 500000000000.0  # 0.5 TH/s - proper for Bitaxe
-shares_per_minute = 5.0  # Optimal feedback frequency for small ASIC miners
+min_individual_miner_hashrate = {:.1}
+shares_per_minute = {:.1}
 enable_vardiff = true  # Enable for proper difficulty adjustment
 
 # Upstream pool connection
 [[upstreams]]
 address = "127.0.0.1"
-port = 34254
+port = {}
 authority_pubkey = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
 "#,
-        state.config.translator.min_extranonce2_size
+        state.config.ports.translator_downstream,
+        state.config.translator.min_extranonce2_size,
+        difficulty.min_individual_miner_hashrate,
+        difficulty.shares_per_minute,
+        state.config.ports.pool_listen
     );
     
     let config_path = "/tmp/translator_sv2d.toml";
     fs::write(config_path, translator_config)?;
+    info!("📝 Wrote translator config to: {}", config_path);
+
+    Ok(())
+}
+
+/// Translator config passed to translator_sv2
+const TRANSLATOR_CONFIG_PATH: &str = "./config/translator_config.WORKING.toml";
+
+/// Make sure the translator will accept the pool's authority key before
+/// starting it, rather than letting miners connect to a dead end
+async fn verify_translator_authority(state: &DaemonState, translator_config_path: &str) -> Result<()> {
+    let Some(pool_key) = state.pool_authority_key.read().await.clone() else {
+        warn!("⚠️  Could not determine the pool's authority key; skipping translator key check");
+        return Ok(());
+    };
+
+    let translator_config = fs::read_to_string(translator_config_path)
+        .with_context(|| format!("Failed to read translator config {}", translator_config_path))?;
+    let translator_keys = authority::from_translator_config(&translator_config)
+        .with_context(|| format!("Failed to parse translator config {}", translator_config_path))?;
+
+    authority::verify_translator_key(&pool_key, &translator_keys, translator_config_path)
+}
+
+/// Point the translator's vardiff at the fleet, so a restart after miners
+/// have connected picks up their hashrates
+async fn update_translator_difficulty(state: &DaemonState, translator_config_path: &str) -> Result<()> {
+    let difficulty = fleet_difficulty(state).await;
+    let translator_config = fs::read_to_string(translator_config_path)
+        .with_context(|| format!("Failed to read translator config {}", translator_config_path))?;
+    let updated = difficulty.apply(&translator_config)
+        .with_context(|| format!("Failed to update translator config {}", translator_config_path))?;
+    if updated != translator_config {
+        fs::write(translator_config_path, updated)
+            .with_context(|| format!("Failed to write translator config {}", translator_config_path))?;
+        info!(
+            "📝 Translator vardiff set to {:.1} H/s minimum, {:.1} shares/min",
+            difficulty.min_individual_miner_hashrate, difficulty.shares_per_minute
+        );
+    }
+    Ok(())
+}
+
+async fn start_translator(state: Arc<DaemonState>) -> Result<()> {
+    info!("🟡 Starting SRI Translator...");
+
+    verify_translator_authority(&state, TRANSLATOR_CONFIG_PATH).await?;
+    update_translator_difficulty(&state, TRANSLATOR_CONFIG_PATH).await?;
+
+    // Give pool extra time to be fully ready for connections
+    info!("Waiting 10 seconds for pool to be fully ready to accept connections...");
+    sleep(Duration::from_secs(10)).await;
 
     // Open log files
     let log_file = std::fs::OpenOptions::new()
@@ -511,9 +1414,10 @@ authority_pubkey = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
         .context("Failed to open translator log file")?;
 
     let translator_path = find_binary("translator_sv2")?;
+    record_binary(&state, "translator_sv2", &translator_path).await;
     let child = TokioCommand::new(&translator_path)
         .arg("--config")
-        .arg("./config/translator_config.WORKING.toml")
+        .arg(TRANSLATOR_CONFIG_PATH)
         .stdout(Stdio::from(log_file.try_clone()?))
         .stderr(Stdio::from(log_file))
         .spawn()
@@ -523,27 +1427,27 @@ authority_pubkey = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
     info!("Started SRI Translator with PID: {:?}", pid);
     
     // Wait for it to be ready
-    for i in 0..15 {
-        sleep(Duration::from_secs(2)).await;
-        if test_tcp_port(3333).await {
-            info!("✅ SRI Translator ready");
-            state.update_component_status("translator", true, pid).await;
-            
-            let mut processes = state.processes.write().await;
-            processes.insert("translator".to_string(), child);
-            
-            return Ok(());
-        }
-        if i % 3 == 0 {
-            info!("Waiting for SRI Translator to start... ({}/15)", i + 1);
-        }
-    }
-    
-    Err(anyhow::anyhow!(
-        "SRI Translator failed to start within 30 seconds. \
-         It may be unable to connect to the pool or bind to the stratum port. \
-         Verify the pool is running and port 34255 is available."
-    ))
+    let port = state.config.ports.translator_downstream;
+    wait_until_ready(
+        "translator",
+        port,
+        &state.config.startup.translator,
+        "/tmp/sv2d-translator.log",
+        &format!(
+            "The translator may be unable to connect to the pool or bind to the stratum port. \
+             Verify the pool is running and port {} is available.",
+            port
+        ),
+    )
+    .await?;
+
+    info!("✅ SRI Translator ready");
+    state.update_component_status("translator", true, pid).await;
+
+    let mut processes = state.processes.write().await;
+    processes.insert("translator".to_string(), child);
+
+    Ok(())
 }
 
 async fn test_bitcoin_rpc(rpc_url: &str) -> Result<()> {
@@ -567,18 +1471,22 @@ async fn test_bitcoin_rpc(rpc_url: &str) -> Result<()> {
 }
 
 async fn test_tcp_port(port: u16) -> bool {
+    test_tcp_addr(&format!("127.0.0.1:{}", port)).await
+}
+
+async fn test_tcp_addr(address: &str) -> bool {
     // Try to connect to the port rather than bind to it
     // This is more reliable than trying to bind
-    match tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port)).await {
-        Ok(_) => true,  // Connection successful - service is running
-        Err(_) => false, // Connection failed - service not ready
+    match tokio::time::timeout(Duration::from_secs(5), tokio::net::TcpStream::connect(address)).await {
+        Ok(Ok(_)) => true,  // Connection successful - service is running
+        _ => false, // Connection failed or timed out - service not ready
     }
 }
 
 async fn detect_connected_miners(state: Arc<DaemonState>) -> Result<()> {
-    // Use netstat/lsof to detect active connections to port 3333
+    // Use netstat/lsof to detect active connections to the translator
     let output = TokioCommand::new("lsof")
-        .args(&["-i", ":3333", "-n"])
+        .args(["-i", format!(":{}", state.config.ports.translator_downstream).as_str(), "-n"])
         .output()
         .await?;
     
@@ -597,7 +1505,7 @@ async fn detect_connected_miners(state: Arc<DaemonState>) -> Result<()> {
                             .unwrap_or_default()
                             .as_secs()
                             .to_string(),
-                        hashrate: None, // We can't detect hashrate from network connections alone
+                        hashrate: None, // Filled in from the miner's API when it answers
                         shares_submitted: 0,
                         last_activity: "Active".to_string(),
                     };
@@ -607,6 +1515,14 @@ async fn detect_connected_miners(state: Arc<DaemonState>) -> Result<()> {
         }
     }
     
+    // Fill in what the miners' own APIs last reported
+    let samples = state.miner_samples.read().await;
+    let now = std::time::Instant::now();
+    for miner in current_miners.values_mut() {
+        samples.apply(miner, now, state.config.miner_stats.poll_interval());
+    }
+    drop(samples);
+    
     // Update the connected miners
     let mut miners = state.connected_miners.write().await;
     *miners = current_miners;
@@ -620,12 +1536,14 @@ async fn generate_enhanced_status(state: Arc<DaemonState>) -> Result<StatusRespo
     
     let components = state.components.read().await;
     let miners = state.connected_miners.read().await;
+    let breakers = state.breakers.read().await;
     let now = std::time::Instant::now();
     
     // Build component status info
     let mut component_info = HashMap::new();
     for (name, status) in components.iter() {
         let uptime_seconds = status.start_time.map(|start| now.duration_since(start).as_secs());
+        let breaker_state = breakers.get(name).map_or(BreakerState::Closed, |b| b.state());
         
         component_info.insert(name.clone(), ComponentStatusInfo {
             running: status.running,
@@ -634,17 +1552,19 @@ async fn generate_enhanced_status(state: Arc<DaemonState>) -> Result<StatusRespo
             restart_count: status.restart_count,
             health_status: status.health_status.clone(),
             last_error: status.last_error.clone(),
+            breaker_state,
         });
     }
     
-    // Check if all components are running
-    let running = components.values().all(|c| c.running);
+    // A startup that stopped partway leaves later components never launched
+    let startup = state.startup.read().await.clone();
+    let running = startup.is_complete() && components.values().all(|c| c.running);
     
     // Build miners info
     let active_miners: Vec<_> = miners.values().cloned().collect();
     let miners_info = ConnectedMinersInfo {
         total_count: active_miners.len() as u32,
-        active_count: active_miners.iter().filter(|m| m.hashrate.is_some()).count() as u32,
+        active_count: active_miners.iter().filter(|m| m.hashrate.is_some_and(|h| h > 0.0)).count() as u32,
         miners: active_miners,
     };
     
@@ -654,12 +1574,40 @@ async fn generate_enhanced_status(state: Arc<DaemonState>) -> Result<StatusRespo
     // Calculate daemon uptime
     let uptime_seconds = now.duration_since(state.start_time).as_secs();
     
+    let maintenance_expires_in_seconds = if state.in_maintenance().await {
+        state.maintenance.read().await.remaining(now).map(|d| d.as_secs())
+    } else {
+        None
+    };
+    
+    let share_probe = if state.config.share_probe.enabled {
+        Some(state.share_probe.read().await.status().clone())
+    } else {
+        None
+    };
+    
+    // The translator's own connections are only visible through lsof, where
+    // relayed miners show up as loopback peers
+    let mut listeners = vec![ListenerStatus {
+        address: state.config.translator.primary_address().to_string(),
+        relayed: false,
+        active_connections: miners.keys()
+            .filter(|ip| !ip.parse::<std::net::IpAddr>().map_or(false, |ip| ip.is_loopback()))
+            .count() as u64,
+        total_connections: None,
+    }];
+    listeners.extend(state.relay_listeners.read().await.iter().map(|stats| stats.status()));
+    
     Ok(StatusResponse {
         running,
         components: component_info,
         miners: miners_info,
         system_info,
         uptime_seconds,
+        maintenance_expires_in_seconds,
+        share_probe,
+        listeners,
+        startup,
     })
 }
 
@@ -696,8 +1644,95 @@ async fn get_system_info(state: Arc<DaemonState>) -> SystemInfo {
         bitcoin_network: state.config.daemon.network.clone(),
         bitcoin_blocks,
         bitcoin_synced,
-        sv2_version: "SRI v1.0.2".to_string(),
-        daemon_version: "sv2d v0.1.0".to_string(),
+        sv2_version: state.sv2_version.clone(),
+        daemon_version: daemon_version(),
+        binaries: state.binaries.read().await.records().clone(),
+    }
+}
+
+fn daemon_version() -> String {
+    format!("sv2d v{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Record the version and hash of a binary about to be launched, warning if
+/// it changed since the last launch. The hash is only recomputed when the
+/// file's size or modification time changed.
+async fn record_binary(state: &DaemonState, name: &str, path: &std::path::Path) {
+    let fingerprint = match Fingerprint::of(path) {
+        Ok(fingerprint) => fingerprint,
+        Err(e) => {
+            warn!("Could not stat {} binary {}: {}", name, path.display(), e);
+            return;
+        }
+    };
+
+    if state.binaries.read().await.is_current(name, path, &fingerprint) {
+        return;
+    }
+
+    let hash_path = path.to_path_buf();
+    let sha256 = match tokio::task::spawn_blocking(move || binaries::hash_file(&hash_path)).await {
+        Ok(Ok(sha256)) => sha256,
+        Ok(Err(e)) => {
+            warn!("Could not hash {} binary {}: {}", name, path.display(), e);
+            return;
+        }
+        Err(e) => {
+            warn!("Hashing {} binary {} panicked: {}", name, path.display(), e);
+            return;
+        }
+    };
+    let version = binaries::probe_version(path).await;
+
+    state.binaries.write().await
+        .record(name, BinaryRecord::new(path.to_path_buf(), sha256, version, fingerprint));
+}
+
+/// Ask the installed SRI pool binary for its version, falling back to
+/// "unknown" rather than reporting a version we can't confirm
+async fn detect_sri_version() -> String {
+    let pool_path = match find_binary("pool_sv2") {
+        Ok(path) => path,
+        Err(_) => return "unknown".to_string(),
+    };
+
+    let output = tokio::time::timeout(
+        Duration::from_secs(5),
+        TokioCommand::new(&pool_path)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output(),
+    )
+    .await;
+
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            parse_sri_version(&String::from_utf8_lossy(&output.stdout))
+                .unwrap_or_else(|| "unknown".to_string())
+        }
+        Ok(Ok(output)) => {
+            warn!("{} --version exited with {}", pool_path.display(), output.status);
+            "unknown".to_string()
+        }
+        Ok(Err(e)) => {
+            warn!("Failed to run {} --version: {}", pool_path.display(), e);
+            "unknown".to_string()
+        }
+        Err(_) => {
+            warn!("{} --version timed out", pool_path.display());
+            "unknown".to_string()
+        }
+    }
+}
+
+/// Turn `pool_sv2 --version` output (e.g. "pool_sv2 0.1.3") into "SRI v0.1.3"
+fn parse_sri_version(output: &str) -> Option<String> {
+    let version = output.lines().next()?.split_whitespace().last()?;
+    let version = version.trim_start_matches('v');
+    if version.chars().next()?.is_ascii_digit() {
+        Some(format!("SRI v{}", version))
+    } else {
+        None
     }
 }
 
@@ -726,6 +1761,29 @@ async fn check_and_restart_components(
     let components_to_check = vec!["bitcoin", "sv2-tp", "pool", "translator"];
 
     for component_name in components_to_check {
+        // External dependencies are health-checked, never restarted
+        match component_name {
+            "sv2-tp" if state.config.sv2_tp.external => {
+                let address = state.config.sv2_tp.address(&state.config.daemon.network);
+                let reachable = test_tcp_addr(&address).await;
+                report_external_health(state, component_name, &address, reachable).await;
+                continue;
+            }
+            "bitcoin" if !state.config.bitcoin.managed => {
+                let bitcoin = &state.config.bitcoin;
+                let reachable = match Network::from_str(&state.config.daemon.network) {
+                    Ok(network) => verify_external_bitcoin(
+                        &bitcoin.rpc_url, &bitcoin.rpc_user, &bitcoin.rpc_password, network,
+                    ).await.is_ok(),
+                    Err(_) => false,
+                };
+                report_external_health(state, component_name, &bitcoin.rpc_url, reachable).await;
+                continue;
+            }
+            _ => {}
+        }
+
+        let now = std::time::Instant::now();
         let mut processes = state.processes.write().await;
 
         if let Some(child) = processes.get_mut(component_name) {
@@ -735,88 +1793,253 @@ async fn check_and_restart_components(
                     // Process exited
                     warn!("{} process exited with status: {}", component_name, status);
 
-                    // Update status
-                    state.update_component_status(component_name, false, None).await;
-
-                    // Remove dead process
-                    drop(processes); // Release lock before restarting
+                    // Remove dead process and release lock before restarting
+                    processes.remove(component_name);
+                    drop(processes);
 
-                    // Track failures
-                    let failures = failure_counts.entry(component_name.to_string()).or_insert(0);
-                    *failures += 1;
+                    state.update_component_status(component_name, false, None).await;
+                    *failure_counts.entry(component_name.to_string()).or_insert(0) += 1;
 
-                    if *failures > 10 {
-                        error!("{} has failed {} times consecutively, giving up", component_name, failures);
-                        state.set_component_error(
-                            component_name,
-                            format!("Component failed {} times and will not be restarted automatically", failures)
-                        ).await;
+                    // Exits during maintenance are expected and don't count against the breaker
+                    if state.in_maintenance().await {
+                        info!("{} exited during maintenance, not restarting until maintenance ends", component_name);
                         continue;
                     }
-
-                    // Exponential backoff: 1s, 2s, 4s, 8s, 16s, 32s, 64s (max 60s)
-                    let backoff = std::cmp::min(2u64.pow(*failures - 1), 60);
-                    warn!("{} restarting after {} second backoff (failure {}/10)",
-                          component_name, backoff, failures);
-                    sleep(Duration::from_secs(backoff)).await;
-
-                    // Attempt restart
-                    let restart_result: Result<()> = match component_name {
-                        "bitcoin" => start_bitcoin_core(Arc::clone(state)).await,
-                        "sv2-tp" => {
-                            // sv2-tp returns authority key which gets cached automatically
-                            start_sv2_tp(Arc::clone(state)).await.map(|_| ())
-                        },
-                        "pool" => {
-                            // Use cached authority key for pool restart
-                            let auth_key = state.authority_key.read().await;
-                            if let Some(key) = auth_key.as_ref() {
-                                start_pool(Arc::clone(state), key).await
-                            } else {
-                                Err(anyhow::anyhow!(
-                                    "Cannot restart pool: authority key not cached. \
-                                     The Template Provider must be running first to generate the authority key. \
-                                     Start sv2-tp before attempting pool restart."
-                                ))
-                            }
-                        },
-                        "translator" => start_translator(Arc::clone(state)).await,
-                        _ => continue,
-                    };
-
-                    match restart_result {
-                        Ok(_) => {
-                            info!("✅ Successfully restarted {}", component_name);
-                            *failures = 0; // Reset failure count on success
-                        }
-                        Err(e) => {
-                            error!("Failed to restart {}: {}", component_name, e);
-                        }
-                    }
+                    record_breaker_failure(state, component_name, now).await;
                 }
                 Ok(None) => {
                     // Process is still running, all good
+                    drop(processes);
+                    state.breakers.write().await
+                        .entry(component_name.to_string())
+                        .or_default()
+                        .record_healthy(now);
+                    continue;
                 }
                 Err(e) => {
                     error!("Error checking {} status: {}", component_name, e);
+                    continue;
+                }
+            }
+        } else {
+            drop(processes);
+        }
+
+        // Only components that failed and haven't been brought back are pending a restart
+        let failures = match failure_counts.get(component_name) {
+            Some(&failures) if failures > 0 => failures,
+            _ => continue,
+        };
+
+        if state.in_maintenance().await {
+            continue;
+        }
+
+        if failures > 10 {
+            error!("{} has failed {} times consecutively, giving up", component_name, failures);
+            let detail = format!("Component failed {} times and will not be restarted automatically", failures);
+            state.set_component_error(component_name, detail.clone()).await;
+            failure_counts.remove(component_name);
+            state.request_shutdown(ShutdownReason::ComponentFailed {
+                component: component_name.to_string(),
+                detail,
+            }).await;
+            return;
+        }
+
+        // Crash-looping components wait out the breaker cool-down before a probe restart
+        {
+            let mut breakers = state.breakers.write().await;
+            let breaker = breakers.entry(component_name.to_string()).or_default();
+            if !breaker.allow_restart(now) {
+                continue;
+            }
+            if breaker.state() == BreakerState::HalfOpen {
+                info!("{} circuit breaker half-open, attempting probe restart", component_name);
+            }
+        }
+
+        // Exponential backoff: 1s, 2s, 4s, 8s, 16s, 32s, 64s (max 60s)
+        let backoff = std::cmp::min(2u64.pow(failures - 1), 60);
+        warn!("{} restarting after {} second backoff (failure {}/10)",
+              component_name, backoff, failures);
+        sleep(Duration::from_secs(backoff)).await;
+
+        // Already brought back during the backoff, e.g. by a `restart` request
+        if state.processes.read().await.contains_key(component_name) {
+            failure_counts.insert(component_name.to_string(), 0);
+            continue;
+        }
+
+        // Attempt restart
+        let restart_result = start_component(state, component_name).await;
+
+        match restart_result {
+            Ok(_) => {
+                info!("✅ Successfully restarted {}", component_name);
+                failure_counts.insert(component_name.to_string(), 0); // Reset failure count on success
+            }
+            Err(e) => {
+                error!("Failed to restart {}: {}", component_name, e);
+                *failure_counts.entry(component_name.to_string()).or_insert(0) += 1;
+                record_breaker_failure(state, component_name, std::time::Instant::now()).await;
+            }
+        }
+    }
+}
+
+async fn miner_stats_loop(state: Arc<DaemonState>) {
+    let config = &state.config.miner_stats;
+    info!("📈 Polling connected miners' APIs every {}s", config.poll_interval_secs);
+    let scanner = sv2_core::scanner::NetworkScanner::new();
+    let mut poll_interval = interval(config.poll_interval());
+
+    loop {
+        tokio::select! {
+            _ = poll_interval.tick() => {
+                if let Err(e) = detect_connected_miners(Arc::clone(&state)).await {
+                    debug!("Failed to list connected miners: {:#}", e);
+                    continue;
+                }
+                let ips: Vec<String> = state.connected_miners.read().await.keys().cloned().collect();
+                state.miner_samples.write().await.retain_connected(&ips);
+                for ip in &ips {
+                    miner_stats::poll_miner(&scanner, &state.miner_samples, ip).await;
                 }
             }
+            _ = state.cancellation_token.cancelled() => {
+                info!("Miner stats polling shutting down");
+                break;
+            }
         }
     }
 }
 
-async fn start_all_components(state: Arc<DaemonState>) -> Result<()> {
-    info!("🚀 Starting all components...");
+async fn share_probe_loop(state: Arc<DaemonState>) {
+    let config = &state.config.share_probe;
+    info!("🔍 Starting share probe every {}s", config.interval_secs);
+    let mut probe_interval = interval(Duration::from_secs(config.interval_secs));
+
+    loop {
+        tokio::select! {
+            _ = probe_interval.tick() => {
+                run_share_probe(&state).await;
+            }
+            _ = state.cancellation_token.cancelled() => {
+                info!("Share probe shutting down");
+                break;
+            }
+        }
+    }
+}
+
+/// Push one share through the translator and record the outcome. Skipped
+/// while the translator is down or in maintenance, where the component
+/// checks already report the problem.
+async fn run_share_probe(state: &Arc<DaemonState>) {
+    let translator_running = state.components.read().await
+        .get("translator")
+        .map_or(false, |status| status.running);
+    if !translator_running || state.in_maintenance().await {
+        return;
+    }
+
+    let config = &state.config.share_probe;
+    let address = share_probe::probe_address(state.config.translator.primary_address());
+    let result = share_probe::probe_once(&address, config.difficulty, Duration::from_secs(config.timeout_secs)).await;
+
+    let mut tracker = state.share_probe.write().await;
+    match result {
+        Ok(round_trip) => {
+            if !matches!(tracker.status().health_status, HealthStatus::Healthy | HealthStatus::Unknown) {
+                info!("✅ Share probe answered again after {} failures", tracker.status().consecutive_failures);
+            }
+            tracker.record_success(round_trip);
+        }
+        Err(e) => {
+            warn!("Share probe via {} failed: {:#}", address, e);
+            if tracker.record_failure(format!("{:#}", e)) {
+                error!("Share pipeline is Critical: {} consecutive probe shares got no answer",
+                       tracker.status().consecutive_failures);
+            }
+        }
+    }
+}
 
-    // Start in order: Bitcoin -> sv2-tp -> Pool -> Translator
-    start_bitcoin_core(Arc::clone(&state)).await?;
+/// Record the health of a component sv2d doesn't manage, alerting when it
+/// becomes unreachable. Outages are reported, never restarted.
+async fn report_external_health(state: &Arc<DaemonState>, component_name: &str, address: &str, reachable: bool) {
+    let was_running = state.components.read().await
+        .get(component_name)
+        .map_or(false, |status| status.running);
 
-    // Start sv2-tp and get the authority key it generates
-    let authority_key = start_sv2_tp(Arc::clone(&state)).await?;
+    if reachable {
+        if !was_running {
+            info!("✅ External {} at {} is reachable again", component_name, address);
+        }
+        state.update_component_status(component_name, true, None).await;
+    } else {
+        if was_running {
+            error!("External {} at {} is unreachable; sv2d does not manage it and will not restart it",
+                   component_name, address);
+        }
+        state.update_component_status(component_name, false, None).await;
+        state.set_component_error(
+            component_name,
+            format!("External {} at {} is unreachable", component_name, address),
+        ).await;
+    }
+}
 
-    // Pass authority key to pool and translator
-    start_pool(Arc::clone(&state), &authority_key).await?;
-    start_translator(Arc::clone(&state)).await?;
+async fn record_breaker_failure(state: &Arc<DaemonState>, component_name: &str, now: std::time::Instant) {
+    let mut breakers = state.breakers.write().await;
+    let breaker = breakers.entry(component_name.to_string()).or_default();
+    if breaker.record_failure(now) {
+        let cooldown = breaker.remaining_cooldown(now).unwrap_or_default();
+        warn!("{} is crash-looping, circuit breaker open; next restart attempt in {}s",
+              component_name, cooldown.as_secs());
+    }
+}
+
+/// Component startup dependencies. The pool needs the authority key that
+/// sv2-tp generates, so that edge must stay; steps with no edge between them
+/// run concurrently.
+const STARTUP_STEPS: &[Step] = &[
+    Step { name: "bitcoin", depends_on: &[] },
+    Step { name: "sv2-tp", depends_on: &["bitcoin"] },
+    Step { name: "pool", depends_on: &["sv2-tp"] },
+    Step { name: "translator-config", depends_on: &[] },
+    Step { name: "translator", depends_on: &["pool", "translator-config"] },
+];
+
+/// Start every component, or after a failed startup, resume from the step
+/// that failed
+async fn start_all_components(state: Arc<DaemonState>) -> Result<()> {
+    if state.startup.read().await.is_complete() {
+        info!("All components already started");
+        return Ok(());
+    }
+    info!("🚀 Starting all components...");
+
+    startup::run_tracked(STARTUP_STEPS, &state.startup, |step| {
+        let state = Arc::clone(&state);
+        async move {
+            match step {
+                "bitcoin" => start_bitcoin_core(state).await,
+                // sv2-tp caches the authority key it generates for the pool
+                "sv2-tp" => start_sv2_tp(state).await.map(|_| ()),
+                "pool" => {
+                    let authority_key = state.authority_key.read().await.clone()
+                        .context("sv2-tp finished without providing an authority key")?;
+                    start_pool(state, &authority_key).await
+                }
+                "translator-config" => write_translator_config(state).await,
+                "translator" => start_translator(state).await,
+                other => Err(anyhow::anyhow!("Unknown startup step '{}'", other)),
+            }
+        }
+    })
+    .await?;
 
     info!("✅ All components started successfully!");
     Ok(())
@@ -825,52 +2048,223 @@ async fn start_all_components(state: Arc<DaemonState>) -> Result<()> {
 async fn stop_all_components(state: &Arc<DaemonState>) -> Result<()> {
     info!("🛑 Stopping all components...");
 
+    // Reverse start order, then anything else that was started
+    let mut components: Vec<String> = ["translator", "pool", "sv2-tp", "bitcoin"].map(String::from).to_vec();
+    let others: Vec<String> = state.processes.read().await.keys()
+        .filter(|component| !components.contains(component))
+        .cloned()
+        .collect();
+    components.extend(others);
+
+    stop_components(state, &components).await;
+    info!("✅ All components stopped");
+    Ok(())
+}
+
+/// Stop the named components that are running, in order, within the
+/// configured shutdown timeout
+async fn stop_components(state: &Arc<DaemonState>, components: &[String]) {
+    // Held until every child is reaped so the monitor can't restart one meanwhile
     let mut processes = state.processes.write().await;
-    
-    // Stop in reverse order
-    for component in ["translator", "pool", "sv2-tp", "bitcoin"] {
-        if let Some(mut child) = processes.remove(component) {
-            info!("Stopping {}...", component);
-            if let Err(e) = child.kill().await {
-                warn!("Failed to stop {}: {}", component, e);
+    let children: Vec<(String, Child)> = components
+        .iter()
+        .filter_map(|component| processes.remove(component).map(|child| (component.clone(), child)))
+        .collect();
+
+    let timeout = state.config.shutdown.timeout();
+    let stopping = children.len();
+    let killed = stop_children(children, timeout).await;
+    for component in components {
+        state.update_component_status(component, false, None).await;
+    }
+    if stopping > 0 {
+        info!("{} of {} components exited on SIGTERM, {} killed", stopping - killed.len(), stopping, killed.len());
+    }
+
+    if !killed.is_empty() {
+        warn!(
+            "⚠️  Killed {} after they ignored SIGTERM for {}s",
+            killed.join(", "),
+            timeout.as_secs()
+        );
+    }
+}
+
+/// Stop `component` and start it again, after it the components that depend
+/// on it when `with_dependents` is set. Returns the components restarted.
+async fn restart_component(
+    state: &Arc<DaemonState>,
+    component: Component,
+    with_dependents: bool,
+) -> Result<Vec<&'static str>> {
+    match component {
+        Component::Bitcoin if !state.config.bitcoin.managed => {
+            return Err(anyhow::anyhow!("Bitcoin Core is external ([bitcoin] managed = false); sv2d can't restart it"));
+        }
+        Component::Sv2Tp if state.config.sv2_tp.external => {
+            return Err(anyhow::anyhow!("sv2-tp is external ([sv2_tp] external = true); sv2d can't restart it"));
+        }
+        _ => {}
+    }
+
+    let mut chain = vec![component];
+    if with_dependents {
+        chain.extend(component.dependents());
+    }
+    let names: Vec<&'static str> = chain.iter().map(|c| c.name()).collect();
+    info!("🔄 Restarting {}...", names.join(", "));
+
+    // Dependents stop first and start last
+    let stop_order: Vec<String> = names.iter().rev().map(|name| name.to_string()).collect();
+    stop_components(state, &stop_order).await;
+    for name in &names {
+        start_component(state, name).await
+            .with_context(|| format!("Failed to start {} during restart", name))?;
+    }
+
+    info!("✅ Restarted {}", names.join(", "));
+    Ok(names)
+}
+
+/// Start one component on its own; the pool uses the cached authority key
+async fn start_component(state: &Arc<DaemonState>, component: &str) -> Result<()> {
+    match component {
+        "bitcoin" => start_bitcoin_core(Arc::clone(state)).await,
+        // sv2-tp caches the authority key it generates for the pool
+        "sv2-tp" => start_sv2_tp(Arc::clone(state)).await.map(|_| ()),
+        "pool" => {
+            let auth_key = state.authority_key.read().await.clone();
+            if let Some(key) = auth_key {
+                start_pool(Arc::clone(state), &key).await
+            } else {
+                Err(anyhow::anyhow!(
+                    "Cannot restart pool: authority key not cached. \
+                     The Template Provider must be running first to generate the authority key. \
+                     Start sv2-tp before attempting pool restart."
+                ))
             }
-            state.update_component_status(component, false, None).await;
         }
+        "translator" => start_translator(Arc::clone(state)).await,
+        other => Err(anyhow::anyhow!("Unknown component '{}'", other)),
     }
-    
-    info!("✅ All components stopped");
-    Ok(())
 }
 
-async fn handle_json_rpc(
-    request: JsonRpcRequest,
-    state: Arc<DaemonState>,
-) -> Result<JsonRpcResponse> {
-    match request.method.as_str() {
-        "start" => {
-            start_all_components(state).await?;
-            Ok(JsonRpcResponse {
-                result: serde_json::json!({
-                    "status": "started",
-                    "components": ["bitcoin", "sv2-tp", "pool", "translator"]
-                }),
-            })
-        }
-        "stop" => {
+/// Send each child SIGTERM, wait up to `timeout` for all of them together,
+/// then kill the rest. Every child has been reaped by the time this returns,
+/// so none is left a zombie or holding its ports. Returns the killed ones.
+async fn stop_children(children: Vec<(String, Child)>, timeout: Duration) -> Vec<String> {
+    for (component, child) in &children {
+        // None once the child has already been reaped
+        let Some(pid) = child.id() else { continue };
+        info!("Stopping {}...", component);
+        let sent = TokioCommand::new("kill")
+            .args(["-TERM", &pid.to_string()])
+            .status()
+            .await;
+        if !matches!(sent, Ok(status) if status.success()) {
+            warn!("Failed to send SIGTERM to {} (pid {})", component, pid);
+        }
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut killed = Vec::new();
+    for (component, mut child) in children {
+        match tokio::time::timeout_at(deadline, child.wait()).await {
+            Ok(Ok(status)) => info!("{} exited ({})", component, status),
+            Ok(Err(e)) => warn!("Failed to wait for {}: {}", component, e),
+            Err(_) => {
+                warn!("{} still running after {}s, killing it", component, timeout.as_secs());
+                // kill() also waits, reaping it
+                if let Err(e) = child.kill().await {
+                    warn!("Failed to kill {}: {}", component, e);
+                }
+                killed.push(component);
+            }
+        }
+    }
+    killed
+}
+
+/// Answer `request`, echoing its id; a method that fails while running is
+/// answered with an internal error (-32603)
+async fn handle_json_rpc(request: JsonRpcRequest, state: Arc<DaemonState>) -> JsonRpcResponse {
+    let response = match RpcCall::parse(&request.method, &request.params) {
+        Ok(call) => run_rpc_call(call, state).await.unwrap_or_else(|e| {
+            error!("RPC '{}' failed: {:#}", request.method, e);
+            JsonRpcResponse::error(RpcError::internal(&e))
+        }),
+        Err(error) => {
+            warn!("Rejected RPC request: {}", error.message);
+            JsonRpcResponse::error(error)
+        }
+    };
+    response.with_id(request.id.unwrap_or_default())
+}
+
+async fn run_rpc_call(call: RpcCall, state: Arc<DaemonState>) -> Result<JsonRpcResponse> {
+    match call {
+        RpcCall::Start => {
+            // Startup runs in the background; one that failed resumes from the failed step
+            let startup = state.startup.read().await.clone();
+            let status = if startup.is_complete() {
+                "started"
+            } else if startup.in_progress() {
+                "starting"
+            } else {
+                let retry_state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = start_all_components(retry_state).await {
+                        error!("Startup retry failed: {:#}", e);
+                    }
+                });
+                "retrying"
+            };
+            Ok(JsonRpcResponse::success(serde_json::json!({
+                "status": status,
+                "failed_step": startup.failure().map(|step| step.name),
+                "components": ["bitcoin", "sv2-tp", "pool", "translator"]
+            })))
+        }
+        RpcCall::Stop => {
             stop_all_components(&state).await?;
             // Trigger graceful daemon shutdown
-            state.cancellation_token.cancel();
-            Ok(JsonRpcResponse {
-                result: serde_json::json!({"status": "stopped", "daemon": "shutting down"}),
-            })
+            state.request_shutdown(ShutdownReason::RpcStop).await;
+            Ok(JsonRpcResponse::success(serde_json::json!({"status": "stopped", "daemon": "shutting down"})))
         }
-        "status" => {
+        RpcCall::Status => {
             let status_response = generate_enhanced_status(state).await?;
-            Ok(JsonRpcResponse {
-                result: serde_json::json!(status_response),
-            })
+            Ok(JsonRpcResponse::success(serde_json::json!(status_response)))
+        }
+        RpcCall::Restart(params) => {
+            let restarted = restart_component(&state, params.component, params.with_dependents).await?;
+            Ok(JsonRpcResponse::success(serde_json::json!({"status": "restarted", "components": restarted})))
+        }
+        RpcCall::Logs(params) | RpcCall::LogsFollow(params) => {
+            let path = params.component.path();
+            Ok(JsonRpcResponse::success(serde_json::json!({
+                "component": params.component.name(),
+                "path": path.display().to_string(),
+                "lines": logs::tail(&path, params.lines),
+            })))
+        }
+        RpcCall::Maintenance(params) => {
+            let enabled = params.enabled;
+            let now = std::time::Instant::now();
+            let mut maintenance = state.maintenance.write().await;
+
+            if enabled {
+                maintenance.enable(now, state.config.maintenance.max_duration());
+                warn!("🔧 Maintenance mode on: automatic restarts paused for up to {}s",
+                      state.config.maintenance.max_duration_secs);
+            } else if maintenance.disable(now) {
+                info!("🔧 Maintenance mode off: automatic restarts resumed");
+            }
+
+            Ok(JsonRpcResponse::success(serde_json::json!({
+                "maintenance": maintenance.is_active(now),
+                "expires_in_seconds": maintenance.remaining(now).map(|d| d.as_secs()),
+            })))
         }
-        _ => Err(anyhow::anyhow!("Unknown method: {}", request.method)),
     }
 }
 
@@ -889,12 +2283,24 @@ async fn run_json_rpc_server(state: Arc<DaemonState>) -> Result<()> {
                 let state = Arc::clone(&state);
                 async move {
                     let body_bytes = hyper::body::to_bytes(req.into_body()).await?;
-                    let request: JsonRpcRequest = serde_json::from_slice(&body_bytes)?;
+                    let request = match JsonRpcRequest::parse(&body_bytes) {
+                        Ok(request) => request,
+                        Err(response) => {
+                            let response_json = serde_json::to_string(&response)?;
+                            return Ok::<_, anyhow::Error>(Response::new(Body::from(response_json)));
+                        }
+                    };
+
+                    // Streamed as plain text until the client hangs up
+                    if let Ok(RpcCall::LogsFollow(params)) = RpcCall::parse(&request.method, &request.params) {
+                        let body = logs::follow(params.component.path(), params.lines, state.cancellation_token.clone());
+                        let response = Response::builder()
+                            .header(hyper::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                            .body(body)?;
+                        return Ok::<_, anyhow::Error>(response);
+                    }
 
-                    let response = handle_json_rpc(request, state).await
-                        .unwrap_or_else(|e| JsonRpcResponse {
-                            result: serde_json::json!({"error": e.to_string()}),
-                        });
+                    let response = handle_json_rpc(request, state).await;
 
                     let response_json = serde_json::to_string(&response)?;
 
@@ -904,10 +2310,10 @@ async fn run_json_rpc_server(state: Arc<DaemonState>) -> Result<()> {
         }
     });
 
-    let addr = ([127, 0, 0, 1], 8333).into();
+    let addr: std::net::SocketAddr = ([127, 0, 0, 1], shutdown_state.config.ports.json_rpc).into();
     let server = Server::bind(&addr).serve(make_svc);
 
-    info!("JSON-RPC server listening on http://127.0.0.1:8333");
+    info!("JSON-RPC server listening on http://{}", addr);
 
     // Make server gracefully shutdownable
     let graceful = server.with_graceful_shutdown(async move {
@@ -915,9 +2321,7 @@ async fn run_json_rpc_server(state: Arc<DaemonState>) -> Result<()> {
         info!("RPC server shutting down gracefully");
     });
 
-    if let Err(e) = graceful.await {
-        error!("Server error: {}", e);
-    }
+    graceful.await.context("JSON-RPC server error")?;
 
     Ok(())
 }
@@ -937,14 +2341,17 @@ fn load_config() -> Result<DaemonConfig> {
     let config_content = fs::read_to_string(&config_path)
         .context("Failed to read config file")?;
     
-    let config: DaemonConfig = toml::from_str(&config_content)
+    let raw: toml::Value = toml::from_str(&config_content)
+        .context("Failed to parse config file")?;
+    let mut config: DaemonConfig = profile::apply(raw)?
+        .try_into()
         .context("Failed to parse config file")?;
+    config.resolve_ports()?;
     
     Ok(config)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     tracing_subscriber::fmt::init();
     
     let _matches = Command::new("sv2d")
@@ -961,16 +2368,60 @@ async fn main() -> Result<()> {
     
     // Load configuration
     let config = load_config()?;
-    info!("Loaded config for network: {}", config.daemon.network);
+    match &config.profile {
+        Some(profile) => info!("Loaded config for network: {} (profile {})", config.daemon.network, profile),
+        None => info!("Loaded config for network: {}", config.daemon.network),
+    }
+
+    config.validate()?;
+    info!("Starting async runtime with {} worker threads", config.runtime.worker_threads);
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config.runtime.worker_threads)
+        .enable_all()
+        .build()
+        .context("Failed to build async runtime")?;
+
+    let reason = runtime.block_on(run(config))?;
+    if reason.is_fatal() {
+        error!("sv2d exiting with code {}: {}", reason.exit_code(), reason);
+    } else {
+        info!("sv2d exited cleanly: {}", reason);
+    }
+
+    Ok(ExitCode::from(&reason))
+}
+
+async fn run(config: DaemonConfig) -> Result<ShutdownReason> {
+    let sv2_version = detect_sri_version().await;
+    info!("SV2 implementation: {}", sv2_version);
+
+    // Bind extra stratum addresses up front so a taken port stops startup
+    let relays = RelayListeners::bind(config.translator.relay_addresses()).await?;
 
     // Create daemon state
-    let state = Arc::new(DaemonState::new(config));
+    let state = Arc::new(DaemonState::new(config, sv2_version));
+
+    *state.relay_listeners.write().await = relays.stats();
+    relays.serve(
+        share_probe::probe_address(state.config.translator.primary_address()),
+        state.cancellation_token.clone(),
+    );
+
+    if state.config.share_probe.enabled {
+        tokio::spawn(share_probe_loop(Arc::clone(&state)));
+    }
+
+    if state.config.miner_stats.enabled {
+        tokio::spawn(miner_stats_loop(Arc::clone(&state)));
+    }
 
     // Start component initialization in background
     let init_state = Arc::clone(&state);
     tokio::spawn(async move {
         if let Err(e) = start_all_components(init_state).await {
-            error!("Failed to start components: {}", e);
+            error!("Failed to start components: {:#}", e);
+            error!("Fix the problem and run 'sv2-cli start' to retry from the failed step");
         }
     });
 
@@ -980,22 +2431,483 @@ async fn main() -> Result<()> {
             info!("Monitor loop ended");
         }
         result = run_json_rpc_server(Arc::clone(&state)) => {
-            if let Err(e) = result {
-                error!("JSON-RPC server error: {}", e);
-            }
+            let detail = match result {
+                Ok(()) => "server exited".to_string(),
+                Err(e) => format!("{:#}", e),
+            };
+            state.request_shutdown(ShutdownReason::RpcServerFailed(detail)).await;
         }
         _ = signal::ctrl_c() => {
             info!("Received shutdown signal (Ctrl+C)");
+            state.request_shutdown(ShutdownReason::Signal("SIGINT")).await;
         }
-        _ = state.cancellation_token.cancelled() => {
-            info!("Received shutdown request via RPC");
-        }
+        _ = state.cancellation_token.cancelled() => {}
     }
 
     // Graceful shutdown
-    info!("Shutting down sv2d...");
+    let reason = state.shutdown_reason.read().await.clone()
+        .unwrap_or(ShutdownReason::RpcStop);
+    info!("Shutting down sv2d ({})...", reason);
     state.cancellation_token.cancel();
     stop_all_components(&state).await?;
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daemon_version_matches_crate_version() {
+        assert_eq!(daemon_version(), format!("sv2d v{}", env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_startup_config_defaults_match_previous_waits() {
+        let startup = StartupConfig::default();
+        assert_eq!(startup.sv2_tp.ready_timeout_secs, 60);
+        assert_eq!(startup.pool.ready_timeout_secs, 30);
+        assert_eq!(startup.translator.ready_timeout_secs, 30);
+        assert_eq!(startup.sv2_tp.attempts(), 30);
+        assert!(startup.validate().is_ok());
+    }
+
+    #[test]
+    fn test_startup_config_rejects_non_positive_values() {
+        let mut startup = StartupConfig::default();
+        startup.pool.ready_timeout_secs = 0;
+        assert!(startup.validate().unwrap_err().to_string().contains("[startup.pool]"));
+
+        let mut startup = StartupConfig::default();
+        startup.translator.poll_interval_secs = 0;
+        assert!(startup.validate().is_err());
+
+        let mut startup = StartupConfig::default();
+        startup.sv2_tp.poll_interval_secs = 120;
+        assert!(startup.validate().is_err());
+    }
+
+    #[test]
+    fn test_startup_config_partial_override() {
+        let startup: StartupConfig = toml::from_str(
+            "[pool]\nready_timeout_secs = 120\npoll_interval_secs = 5\n",
+        )
+        .unwrap();
+        assert_eq!(startup.pool.ready_timeout_secs, 120);
+        assert_eq!(startup.sv2_tp.ready_timeout_secs, 60);
+    }
+
+    #[test]
+    fn test_log_tail() {
+        let path = std::env::temp_dir().join(format!("sv2d-log-tail-{}.log", std::process::id()));
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let tail = log_tail(path.to_str().unwrap(), 2);
+        assert_eq!(tail, "  two\n  three");
+
+        fs::remove_file(&path).unwrap();
+        assert!(log_tail(path.to_str().unwrap(), 2).contains("could not read log"));
+    }
+
+    #[tokio::test]
+    async fn test_translator_rejects_mismatched_pool_key() {
+        let path = std::env::temp_dir().join(format!("sv2d-translator-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "[[upstreams]]\naddress = \"127.0.0.1\"\nport = 34254\n\
+             authority_pubkey = \"9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72\"\n",
+        )
+        .unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let state = test_daemon_state();
+        // Pool key unknown: nothing to compare against
+        assert!(verify_translator_authority(&state, path_str).await.is_ok());
+
+        *state.pool_authority_key.write().await =
+            Some("9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72".to_string());
+        assert!(verify_translator_authority(&state, path_str).await.is_ok());
+
+        *state.pool_authority_key.write().await =
+            Some("9cEoWDHp2KtT3pUYaAsjS6yzquNv8QXx3qvCmu8iz8WJ1EB3jUj".to_string());
+        let err = verify_translator_authority(&state, path_str).await.unwrap_err();
+        assert!(err.to_string().contains("Authority key mismatch"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_sri_version() {
+        assert_eq!(parse_sri_version("pool_sv2 0.1.3\n"), Some("SRI v0.1.3".to_string()));
+        assert_eq!(parse_sri_version("pool_sv2 v1.2.0"), Some("SRI v1.2.0".to_string()));
+        assert_eq!(parse_sri_version(""), None);
+        assert_eq!(parse_sri_version("error: unexpected argument"), None);
+    }
+
+    #[tokio::test]
+    async fn test_partial_startup_reports_failed_step() {
+        let state = test_daemon_state();
+
+        let result = startup::run_tracked(STARTUP_STEPS, &state.startup, |step| async move {
+            match step {
+                "pool" => Err(anyhow::anyhow!("pool_sv2 exited: address already in use")),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.unwrap_err().to_string().contains("'pool'"));
+
+        let status = serde_json::to_value(&*state.startup.read().await).unwrap();
+        let step = |name: &str| {
+            status["steps"].as_array().unwrap().iter()
+                .find(|step| step["name"] == name)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(step("sv2-tp")["state"], "completed");
+        assert_eq!(step("pool")["state"], "failed");
+        assert_eq!(step("pool")["error"], "pool_sv2 exited: address already in use");
+        assert_eq!(step("translator")["state"], "pending");
+
+        // Retrying resumes at the pool instead of relaunching bitcoin and sv2-tp
+        startup::run_tracked(STARTUP_STEPS, &state.startup, |step| async move {
+            assert!(!matches!(step, "bitcoin" | "sv2-tp"), "{} started twice", step);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert!(state.startup.read().await.is_complete());
+    }
+
+    fn test_daemon_state() -> Arc<DaemonState> {
+        Arc::new(DaemonState::new(test_config(), "unknown".to_string()))
+    }
+
+    fn test_config() -> DaemonConfig {
+        toml::from_str(
+            r#"
+            [daemon]
+            mode = "proxy"
+            network = "regtest"
+
+            [bitcoin]
+            rpc_url = "http://127.0.0.1:18443"
+            rpc_user = "user"
+            rpc_password = "pass"
+
+            [pool]
+            signature = "test"
+            coinbase_address = "bcrt1qtest"
+
+            [translator]
+            bind_address = "127.0.0.1:3333"
+            min_extranonce2_size = 8
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_exit_during_maintenance_is_not_restarted() {
+        let state = test_daemon_state();
+        state.maintenance.write().await
+            .enable(std::time::Instant::now(), state.config.maintenance.max_duration());
+
+        let mut child = TokioCommand::new("true").spawn().unwrap();
+        child.wait().await.unwrap();
+        state.processes.write().await.insert("translator".to_string(), child);
+        state.update_component_status("translator", true, None).await;
+
+        let mut failure_counts = HashMap::new();
+        check_and_restart_components(&state, &mut failure_counts).await;
+        // A second tick must not pick up the pending restart either
+        check_and_restart_components(&state, &mut failure_counts).await;
+
+        assert!(!state.processes.read().await.contains_key("translator"));
+        assert!(!state.components.read().await["translator"].running);
+        assert_eq!(state.components.read().await["translator"].restart_count, 1);
+        assert_eq!(failure_counts.get("translator"), Some(&1));
+        assert_eq!(state.breakers.read().await.get("translator").map(|b| b.state()), None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_rpc_params_are_rejected_up_front() {
+        let state = test_daemon_state();
+        let request = JsonRpcRequest {
+            id: Some(serde_json::json!(7)),
+            method: "maintenance".to_string(),
+            params: serde_json::json!({"enabled": "yes"}),
+        };
+        let response = handle_json_rpc(request, Arc::clone(&state)).await;
+        assert_eq!(response.id, serde_json::json!(7));
+        assert_eq!(response.result, None);
+        assert_eq!(response.error.map(|e| e.code), Some(rpc::INVALID_PARAMS));
+        assert!(!state.maintenance.write().await.is_active(std::time::Instant::now()));
+
+        // A request without params reads as empty params
+        let request: JsonRpcRequest = serde_json::from_str(r#"{"method": "status"}"#).unwrap();
+        assert_eq!(request.params, serde_json::Value::Null);
+        assert!(RpcCall::parse(&request.method, &request.params).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_responses_follow_json_rpc() {
+        let state = test_daemon_state();
+
+        // The id is echoed whatever its type, and a request without one still works
+        let request = JsonRpcRequest::parse(br#"{"id": "abc", "method": "logs", "params": {"component": "pool"}}"#).unwrap();
+        let response = serde_json::to_value(handle_json_rpc(request, Arc::clone(&state)).await).unwrap();
+        assert_eq!(response["id"], "abc");
+        assert_eq!(response["result"]["component"], "pool");
+        assert!(response.get("error").is_none());
+
+        let request = JsonRpcRequest::parse(br#"{"method": "reboot"}"#).unwrap();
+        let response = serde_json::to_value(handle_json_rpc(request, Arc::clone(&state)).await).unwrap();
+        assert_eq!(response["id"], serde_json::Value::Null);
+        assert_eq!(response["error"]["code"], rpc::METHOD_NOT_FOUND);
+        assert!(response.get("result").is_none());
+
+        let error = JsonRpcRequest::parse(b"{not json").unwrap_err();
+        assert_eq!(error.error.map(|e| e.code), Some(rpc::PARSE_ERROR));
+        let error = JsonRpcRequest::parse(br#"{"id": 3, "params": {}}"#).unwrap_err();
+        assert_eq!(error.id, serde_json::json!(3));
+        assert_eq!(error.error.map(|e| e.code), Some(rpc::INVALID_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_child_draining_on_sigterm_is_not_killed() {
+        // Like the translator finishing its miners' shares, then exiting
+        let draining = TokioCommand::new("sh")
+            .args(["-c", "trap 'sleep 0.3; kill $!; exit 0' TERM; sleep 30 & wait"])
+            .spawn()
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        let started = std::time::Instant::now();
+        let killed = stop_children(vec![("translator".to_string(), draining)], Duration::from_secs(5)).await;
+
+        assert!(killed.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_child_ignoring_sigterm_is_killed_after_timeout() {
+        // Ignored signals stay ignored across exec
+        let stubborn = TokioCommand::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        let polite = TokioCommand::new("sleep").arg("30").spawn().unwrap();
+        let stubborn_pid = stubborn.id().unwrap();
+        // Let the shell install its trap before it's signalled
+        sleep(Duration::from_millis(200)).await;
+
+        let started = std::time::Instant::now();
+        let killed = stop_children(
+            vec![("pool".to_string(), stubborn), ("translator".to_string(), polite)],
+            Duration::from_secs(1),
+        )
+        .await;
+
+        assert_eq!(killed, ["pool"]);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // Reaped, so not even a zombie is left under its pid
+        let alive = std::process::Command::new("kill")
+            .args(["-0", &stubborn_pid.to_string()])
+            .status()
+            .unwrap();
+        assert!(!alive.success());
+    }
+
+    #[test]
+    fn test_maintenance_config_defaults_and_validation() {
+        let state = test_daemon_state();
+        assert_eq!(state.config.maintenance.max_duration_secs, 3600);
+        assert!(state.config.maintenance.validate().is_ok());
+        assert!(MaintenanceConfig { max_duration_secs: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_share_probe_is_off_by_default() {
+        let config = test_config();
+        assert!(!config.share_probe.enabled);
+        assert!(config.share_probe.validate().is_ok());
+
+        let probe = ShareProbeConfig { enabled: true, timeout_secs: 600, ..config.share_probe.clone() };
+        assert!(probe.validate().unwrap_err().to_string().contains("timeout_secs"));
+        let probe = ShareProbeConfig { enabled: true, failure_threshold: 0, ..config.share_probe };
+        assert!(probe.validate().is_err());
+    }
+
+    #[test]
+    fn test_sv2_tp_defaults_to_managed() {
+        let config = test_config();
+        assert!(!config.sv2_tp.external);
+        assert_eq!(config.sv2_tp.address("regtest"), "127.0.0.1:18447");
+        assert_eq!(config.sv2_tp.address("mainnet"), "127.0.0.1:8336");
+        assert!(config.sv2_tp.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ports_default_to_fixed_ports_and_must_differ() {
+        let mut config = test_config();
+        config.resolve_ports().unwrap();
+        assert_eq!(config.ports.pool_listen, 34254);
+        assert_eq!(config.ports.translator_downstream, 3333);
+        assert_eq!(config.ports.json_rpc, 8333);
+        assert_eq!(config.sv2_tp.address("regtest"), "127.0.0.1:18447");
+
+        let mut config = test_config();
+        config.ports = toml::from_str("pool_listen = 44254\ntp_port = 48447\njson_rpc = 9333\n").unwrap();
+        config.resolve_ports().unwrap();
+        assert_eq!(config.sv2_tp.address("regtest"), "127.0.0.1:48447");
+
+        let mut config = test_config();
+        config.ports.json_rpc = 34254;
+        let error = config.resolve_ports().unwrap_err().to_string();
+        assert!(error.contains("pool_listen and json_rpc are both 34254"), "{}", error);
+
+        let mut config = test_config();
+        config.ports.tp_port = Some(18447);
+        config.sv2_tp.port = Some(18448);
+        assert!(config.resolve_ports().unwrap_err().to_string().contains("set only one"));
+
+        let mut config = test_config();
+        config.ports.translator_downstream = 3334;
+        assert!(config.resolve_ports().unwrap_err().to_string().contains("bind_address"));
+    }
+
+    #[test]
+    fn test_external_sv2_tp_requires_authority_key() {
+        let mut sv2_tp: Sv2TpConfig =
+            toml::from_str("external = true\nhost = \"10.0.0.5\"\nport = 8442\n").unwrap();
+        assert_eq!(sv2_tp.address("signet"), "10.0.0.5:8442");
+        assert!(sv2_tp.validate().unwrap_err().to_string().contains("authority_key"));
+
+        sv2_tp.authority_key = Some("9cEoWDHp2KtT3pUYaAsjS6yzquNv8QXx3qvCmu8iz8WJ1EB3jUj".to_string());
+        assert!(sv2_tp.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_external_sv2_tp_is_not_spawned() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = test_config();
+        config.sv2_tp.external = true;
+        config.sv2_tp.port = Some(listener.local_addr().unwrap().port());
+        config.sv2_tp.authority_key = Some("external-key".to_string());
+        let state = Arc::new(DaemonState::new(config, "unknown".to_string()));
+
+        let key = start_sv2_tp(Arc::clone(&state)).await.unwrap();
+        assert_eq!(key, "external-key");
+        assert_eq!(state.authority_key.read().await.as_deref(), Some("external-key"));
+        assert!(state.processes.read().await.is_empty());
+        assert!(state.components.read().await["sv2-tp"].running);
+
+        // Once it goes away the monitor reports it instead of restarting it
+        drop(listener);
+        let mut failure_counts = HashMap::new();
+        check_and_restart_components(&state, &mut failure_counts).await;
+        let components = state.components.read().await;
+        assert!(!components["sv2-tp"].running);
+        assert!(components["sv2-tp"].last_error.as_deref().unwrap().contains("unreachable"));
+        assert!(state.processes.read().await.is_empty());
+        assert!(failure_counts.is_empty());
+    }
+
+    /// Minimal JSON-RPC endpoint answering every request with a regtest
+    /// `getblockchaininfo` result
+    async fn mock_bitcoin_rpc() -> (String, tokio::task::JoinHandle<()>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                // Read headers and the (small) body before answering
+                while let Ok(n) = socket.read(&mut buf).await {
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let content_length = text[..header_end]
+                            .lines()
+                            .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if request.len() >= header_end + 4 + content_length {
+                            break;
+                        }
+                    }
+                }
+                let body = r#"{"result":{"chain":"regtest","blocks":101,"initialblockdownload":false},"error":null,"id":"test"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_bitcoin_managed_by_default() {
+        assert!(test_config().bitcoin.managed);
+    }
+
+    #[tokio::test]
+    async fn test_unmanaged_bitcoin_is_only_checked() {
+        let (rpc_url, server) = mock_bitcoin_rpc().await;
+        let mut config = test_config();
+        config.bitcoin.managed = false;
+        config.bitcoin.rpc_url = rpc_url;
+        let state = Arc::new(DaemonState::new(config, "unknown".to_string()));
+
+        // The external node answers over RPC, so nothing is spawned
+        start_bitcoin_core(Arc::clone(&state)).await.unwrap();
+        assert!(state.components.read().await["bitcoin"].running);
+        assert!(state.processes.read().await.is_empty());
+
+        // When it goes away the monitor alerts instead of starting a node
+        server.abort();
+        let _ = server.await;
+        let mut failure_counts = HashMap::new();
+        check_and_restart_components(&state, &mut failure_counts).await;
+        let components = state.components.read().await;
+        assert!(!components["bitcoin"].running);
+        assert!(components["bitcoin"].last_error.as_deref().unwrap().contains("unreachable"));
+        assert!(state.processes.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_terminal_component_failure_exits_non_zero() {
+        let state = test_daemon_state();
+
+        let mut child = TokioCommand::new("true").spawn().unwrap();
+        child.wait().await.unwrap();
+        state.processes.write().await.insert("pool".to_string(), child);
+        state.update_component_status("pool", true, None).await;
+
+        // The pool already used up its restarts; this exit is the last straw
+        let mut failure_counts = HashMap::from([("pool".to_string(), 10)]);
+        check_and_restart_components(&state, &mut failure_counts).await;
+
+        assert!(state.cancellation_token.is_cancelled());
+        let reason = state.shutdown_reason.read().await.clone().unwrap();
+        assert!(matches!(&reason, ShutdownReason::ComponentFailed { component, .. } if component == "pool"));
+        assert_ne!(reason.exit_code(), 0);
+
+        // A later clean stop doesn't overwrite the fatal reason
+        state.request_shutdown(ShutdownReason::RpcStop).await;
+        assert_eq!(state.shutdown_reason.read().await.as_ref(), Some(&reason));
+    }
+}
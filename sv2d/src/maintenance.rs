@@ -0,0 +1,82 @@
+//! Maintenance mode: pauses automatic component restarts while an operator
+//! works on the stack by hand. It always expires after a maximum duration so
+//! a forgotten `maintenance on` can't leave components down indefinitely.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceMode {
+    /// When maintenance was switched on and when it expires
+    window: Option<(Instant, Instant)>,
+}
+
+impl MaintenanceMode {
+    /// Switch maintenance on. Re-enabling restarts the expiry window.
+    pub fn enable(&mut self, now: Instant, max_duration: Duration) {
+        self.window = Some((now, now + max_duration));
+    }
+
+    /// Switch maintenance off. Returns true if it was active.
+    pub fn disable(&mut self, now: Instant) -> bool {
+        let was_active = self.is_active(now);
+        self.window = None;
+        was_active
+    }
+
+    /// Whether maintenance is on. An expired window is cleared.
+    pub fn is_active(&mut self, now: Instant) -> bool {
+        match self.window {
+            Some((_, expires_at)) if now < expires_at => true,
+            Some(_) => {
+                self.window = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Time left before maintenance expires on its own
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.window
+            .map(|(_, expires_at)| expires_at.saturating_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// How long maintenance has been on
+    pub fn elapsed(&self, now: Instant) -> Option<Duration> {
+        self.window.map(|(started_at, _)| now.saturating_duration_since(started_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enable_and_disable() {
+        let now = Instant::now();
+        let mut maintenance = MaintenanceMode::default();
+        assert!(!maintenance.is_active(now));
+
+        maintenance.enable(now, Duration::from_secs(600));
+        assert!(maintenance.is_active(now + Duration::from_secs(10)));
+        assert_eq!(maintenance.remaining(now + Duration::from_secs(10)), Some(Duration::from_secs(590)));
+        assert_eq!(maintenance.elapsed(now + Duration::from_secs(10)), Some(Duration::from_secs(10)));
+
+        assert!(maintenance.disable(now + Duration::from_secs(20)));
+        assert!(!maintenance.is_active(now + Duration::from_secs(20)));
+        assert!(!maintenance.disable(now + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_auto_expires() {
+        let now = Instant::now();
+        let mut maintenance = MaintenanceMode::default();
+        maintenance.enable(now, Duration::from_secs(60));
+
+        assert!(!maintenance.is_active(now + Duration::from_secs(60)));
+        assert_eq!(maintenance.remaining(now + Duration::from_secs(60)), None);
+        // Expiry clears the window rather than only reporting it inactive
+        assert_eq!(maintenance.elapsed(now), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! Miner API polling. `lsof` only shows which IPs are connected to the
+//! translator; this asks each of them for its own view through the API the
+//! scanner knows how to read, so `status` can report hashrate and accepted
+//! shares and count only miners that are actually hashing. Each IP is probed
+//! once when it connects; one that doesn't answer as a miner is left alone
+//! until it reconnects.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant, SystemTime};
+use sv2_core::scanner::{DetectedMiner, MinerDetails, NetworkScanner};
+use tracing::debug;
+
+use crate::MinerInfo;
+
+/// One answer from a miner's API
+#[derive(Debug, Clone)]
+struct MinerSample {
+    hashrate: Option<f64>,
+    shares_accepted: Option<u64>,
+    sampled_at: Instant,
+    /// Last time the miner reported a nonzero hashrate or new shares
+    last_activity: Option<SystemTime>,
+}
+
+/// What sv2d has learned from connected miners' APIs, by IP
+#[derive(Debug, Default)]
+pub struct MinerSamples {
+    /// Probe result per connected IP; `None` for IPs that didn't answer as a miner
+    probes: HashMap<String, Option<DetectedMiner>>,
+    samples: HashMap<String, MinerSample>,
+    /// Last nonzero hashrate each miner reported. Kept across disconnects,
+    /// since a translator restart drops every connection but not the fleet.
+    fleet: HashMap<String, f64>,
+}
+
+impl MinerSamples {
+    /// Forget IPs that are no longer connected, so they're probed afresh if
+    /// they come back
+    pub fn retain_connected(&mut self, connected: &[String]) {
+        self.probes.retain(|ip, _| connected.contains(ip));
+        self.samples.retain(|ip, _| connected.contains(ip));
+    }
+
+    pub fn needs_probe(&self, ip: &str) -> bool {
+        !self.probes.contains_key(ip)
+    }
+
+    pub fn record_probe(&mut self, ip: &str, miner: Option<DetectedMiner>) {
+        self.probes.insert(ip.to_string(), miner);
+    }
+
+    /// The miner behind `ip`, if the probe found one worth querying
+    pub fn miner(&self, ip: &str) -> Option<&DetectedMiner> {
+        self.probes.get(ip).and_then(Option::as_ref)
+    }
+
+    pub fn record(&mut self, ip: &str, details: &MinerDetails, now: Instant) {
+        let previous = self.samples.get(ip);
+        let hashing = details.hashrate.is_some_and(|hashrate| hashrate > 0.0);
+        let new_shares = match (previous.and_then(|p| p.shares_accepted), details.shares_accepted) {
+            (Some(before), Some(after)) => after > before,
+            (None, Some(after)) => after > 0,
+            _ => false,
+        };
+        if let Some(hashrate) = details.hashrate.filter(|_| hashing) {
+            self.fleet.insert(ip.to_string(), hashrate);
+        }
+        let last_activity = if hashing || new_shares {
+            Some(SystemTime::now())
+        } else {
+            previous.and_then(|p| p.last_activity)
+        };
+        self.samples.insert(ip.to_string(), MinerSample {
+            hashrate: details.hashrate,
+            shares_accepted: details.shares_accepted,
+            sampled_at: now,
+            last_activity,
+        });
+    }
+
+    /// Hashrates of every miner that has reported one, connected or not
+    pub fn known_hashrates(&self) -> Vec<f64> {
+        self.fleet.values().copied().collect()
+    }
+
+    /// Fill `miner` in from its latest sample. A sample older than `interval`
+    /// (allowing for a poll that is still running) says nothing about now, so
+    /// it leaves the hashrate unset.
+    pub fn apply(&self, miner: &mut MinerInfo, now: Instant, interval: Duration) {
+        let Some(sample) = self.samples.get(&miner.ip) else {
+            return;
+        };
+        if now.duration_since(sample.sampled_at) <= interval * 2 {
+            miner.hashrate = sample.hashrate;
+        }
+        miner.shares_submitted = sample.shares_accepted.map_or(0, |shares| shares.min(u32::MAX as u64) as u32);
+        if let Some(at) = sample.last_activity {
+            let secs = at.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+            miner.last_activity = secs.to_string();
+        }
+    }
+}
+
+/// Probe `ip` if it hasn't been yet, then query it if it's a miner. Network
+/// calls happen without holding `samples`.
+pub async fn poll_miner(
+    scanner: &NetworkScanner,
+    samples: &tokio::sync::RwLock<MinerSamples>,
+    ip: &str,
+) {
+    let Ok(address) = ip.parse::<IpAddr>() else {
+        return;
+    };
+    if samples.read().await.needs_probe(ip) {
+        let miner = scanner.probe_host(address).await.ok().flatten();
+        if miner.is_none() {
+            debug!("{} didn't answer as a miner; not polling its API", ip);
+        }
+        samples.write().await.record_probe(ip, miner);
+    }
+
+    let Some(miner) = samples.read().await.miner(ip).cloned() else {
+        return;
+    };
+    match scanner.refresh_details(&miner).await {
+        Ok(details) => samples.write().await.record(ip, &details, Instant::now()),
+        Err(e) => debug!("Miner API at {} failed: {:#}", ip, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sv2_core::scanner::MinerType;
+
+    fn miner_info(ip: &str) -> MinerInfo {
+        MinerInfo {
+            ip: ip.to_string(),
+            connected_at: "0".to_string(),
+            hashrate: None,
+            shares_submitted: 0,
+            last_activity: "Active".to_string(),
+        }
+    }
+
+    fn details(hashrate: f64, shares_accepted: u64) -> MinerDetails {
+        MinerDetails { hashrate: Some(hashrate), shares_accepted: Some(shares_accepted), ..Default::default() }
+    }
+
+    #[test]
+    fn test_samples_fill_miner_info_until_stale() {
+        let interval = Duration::from_secs(30);
+        let start = Instant::now();
+        let mut samples = MinerSamples::default();
+        samples.record("10.0.0.5", &details(500e9, 12), start);
+        samples.record("10.0.0.6", &details(0.0, 0), start);
+
+        let mut hashing = miner_info("10.0.0.5");
+        samples.apply(&mut hashing, start + interval, interval);
+        assert_eq!(hashing.hashrate, Some(500e9));
+        assert_eq!(hashing.shares_submitted, 12);
+        assert_ne!(hashing.last_activity, "Active");
+
+        // Connected but reporting nothing, so it never counts as active
+        let mut idle = miner_info("10.0.0.6");
+        samples.apply(&mut idle, start, interval);
+        assert_eq!(idle.hashrate, Some(0.0));
+        assert_eq!(idle.last_activity, "Active");
+
+        let mut stale = miner_info("10.0.0.5");
+        samples.apply(&mut stale, start + interval * 3, interval);
+        assert_eq!(stale.hashrate, None);
+        assert_eq!(stale.shares_submitted, 12);
+    }
+
+    #[test]
+    fn test_unresponsive_ip_is_not_queried_until_it_reconnects() {
+        let mut samples = MinerSamples::default();
+        assert!(samples.needs_probe("10.0.0.7"));
+        samples.record_probe("10.0.0.7", None);
+        assert!(!samples.needs_probe("10.0.0.7"));
+        assert!(samples.miner("10.0.0.7").is_none());
+
+        samples.record_probe("10.0.0.5", Some(DetectedMiner {
+            ip: "10.0.0.5".parse().unwrap(),
+            miner_type: MinerType::Bitaxe,
+            api_port: Some(80),
+            response_time_ms: 4,
+            last_seen: Instant::now(),
+            details: MinerDetails::default(),
+        }));
+        samples.record("10.0.0.5", &details(500e9, 1), Instant::now());
+        assert!(samples.miner("10.0.0.5").is_some());
+
+        samples.retain_connected(&["10.0.0.5".to_string()]);
+        assert!(samples.needs_probe("10.0.0.7"));
+        samples.retain_connected(&[]);
+        assert!(samples.needs_probe("10.0.0.5"));
+        assert!(samples.samples.is_empty());
+        // Still known when the translator comes back
+        assert_eq!(samples.known_hashrates(), vec![500e9]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Named config profiles. A config with `profile = "<name>"` starts from the
+//! profile's settings; anything the config sets itself takes precedence.
+
+use anyhow::{anyhow, Context, Result};
+use toml::Value;
+
+/// Profile name and its TOML, shared with `sv2-cli setup --profile`
+pub const PROFILES: &[(&str, &str)] = &[
+    ("regtest-fast", include_str!("../../config/profiles/regtest-fast.toml")),
+    ("signet-bitaxe", include_str!("../../config/profiles/signet-bitaxe.toml")),
+    ("mainnet-apollo", include_str!("../../config/profiles/mainnet-apollo.toml")),
+];
+
+pub fn profile_names() -> Vec<&'static str> {
+    PROFILES.iter().map(|(name, _)| *name).collect()
+}
+
+fn profile(name: &str) -> Result<Value> {
+    let (_, source) = PROFILES
+        .iter()
+        .find(|(profile_name, _)| *profile_name == name)
+        .ok_or_else(|| anyhow!(
+            "Unknown config profile '{}'. Available profiles: {}",
+            name,
+            profile_names().join(", ")
+        ))?;
+    toml::from_str(source).with_context(|| format!("Built-in profile '{}' is invalid", name))
+}
+
+/// Seed `config` with its profile's defaults, if it names one
+pub fn apply(config: Value) -> Result<Value> {
+    let Some(name) = config.get("profile").and_then(Value::as_str) else {
+        return Ok(config);
+    };
+
+    let mut merged = profile(name)?;
+    merge(&mut merged, config);
+    Ok(merged)
+}
+
+/// Recursively overlay `overrides` onto `base`; tables merge, everything else replaces
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Table(base), Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaemonConfig;
+
+    fn coinbase_address(network: &str) -> &'static str {
+        match network {
+            "mainnet" => "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "regtest" => "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+            _ => "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+        }
+    }
+
+    fn config_for(name: &str) -> Result<DaemonConfig> {
+        let network = profile(name)?["daemon"]["network"].as_str().unwrap().to_string();
+        let user: Value = toml::from_str(&format!(
+            "profile = \"{}\"\n\n[bitcoin]\nrpc_user = \"alice\"\nrpc_password = \"secret\"\n\n\
+             [pool]\ncoinbase_address = \"{}\"\n",
+            name,
+            coinbase_address(&network)
+        ))?;
+        Ok(apply(user)?.try_into()?)
+    }
+
+    #[test]
+    fn test_every_profile_produces_valid_config() {
+        for name in profile_names() {
+            let config = config_for(name).unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+            config.validate().unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+            assert_eq!(config.profile.as_deref(), Some(name));
+        }
+    }
+
+    #[test]
+    fn test_config_overrides_profile() {
+        let user: Value = toml::from_str(
+            r#"
+            profile = "signet-bitaxe"
+
+            [pool]
+            coinbase_address = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+
+            [translator]
+            min_extranonce2_size = 8
+
+            [startup.sv2_tp]
+            ready_timeout_secs = 120
+            "#,
+        )
+        .unwrap();
+        let config: DaemonConfig = apply(user).unwrap().try_into().unwrap();
+
+        assert_eq!(config.daemon.network, "signet");
+        assert_eq!(config.translator.min_extranonce2_size, 8);
+        assert_eq!(config.translator.shares_per_minute, 5.0);
+        assert_eq!(config.startup.sv2_tp.ready_timeout_secs, 120);
+        assert_eq!(config.startup.sv2_tp.poll_interval_secs, 2);
+    }
+
+    #[test]
+    fn test_unknown_profile_is_rejected() {
+        let user: Value = toml::from_str("profile = \"testnet-s21\"\n").unwrap();
+        let err = apply(user).unwrap_err().to_string();
+        assert!(err.contains("testnet-s21"));
+        assert!(err.contains("regtest-fast"));
+    }
+
+    #[test]
+    fn test_config_without_profile_is_unchanged() {
+        let user: Value = toml::from_str("[daemon]\nnetwork = \"regtest\"\n").unwrap();
+        assert_eq!(apply(user.clone()).unwrap(), user);
+    }
+}
@@ -0,0 +1,246 @@
+//! Typed parameters for the daemon's JSON-RPC methods.
+//!
+//! Each method's params are deserialized into its own struct before the
+//! method runs, so a malformed request is answered with a JSON-RPC
+//! invalid-params error (-32602) naming the problem instead of failing
+//! halfway through. Methods without parameters accept `null`, `{}` or `[]`
+//! and reject anything else.
+
+use crate::logs::LogComponent;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    /// The request body isn't JSON
+    pub fn parse_error(reason: impl std::fmt::Display) -> Self {
+        Self { code: PARSE_ERROR, message: format!("Parse error: {}", reason) }
+    }
+
+    /// The body is JSON but not a request, e.g. it has no method
+    pub fn invalid_request(reason: impl std::fmt::Display) -> Self {
+        Self { code: INVALID_REQUEST, message: format!("Invalid request: {}", reason) }
+    }
+
+    /// The method was valid but failed while running
+    pub fn internal(error: &anyhow::Error) -> Self {
+        Self { code: INTERNAL_ERROR, message: format!("{:#}", error) }
+    }
+
+    fn invalid_params(method: &str, reason: impl std::fmt::Display) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: format!("Invalid params for '{}': {}", method, reason),
+        }
+    }
+}
+
+/// A request whose params matched its method
+#[derive(Debug, PartialEq)]
+pub enum RpcCall {
+    Start,
+    Stop,
+    Status,
+    Maintenance(MaintenanceParams),
+    Restart(RestartParams),
+    Logs(LogsParams),
+    /// Streamed by the HTTP server; anywhere else it's answered like `logs`
+    LogsFollow(LogsParams),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceParams {
+    pub enabled: bool,
+}
+
+/// A component sv2d starts and supervises
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Component {
+    #[serde(rename = "bitcoin")]
+    Bitcoin,
+    #[serde(rename = "sv2-tp")]
+    Sv2Tp,
+    #[serde(rename = "pool")]
+    Pool,
+    #[serde(rename = "translator")]
+    Translator,
+}
+
+impl Component {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Component::Bitcoin => "bitcoin",
+            Component::Sv2Tp => "sv2-tp",
+            Component::Pool => "pool",
+            Component::Translator => "translator",
+        }
+    }
+
+    /// Components started after this one that rely on it, in start order
+    pub fn dependents(&self) -> &'static [Component] {
+        match self {
+            Component::Bitcoin => &[Component::Sv2Tp, Component::Pool, Component::Translator],
+            Component::Sv2Tp => &[Component::Pool, Component::Translator],
+            Component::Pool => &[Component::Translator],
+            Component::Translator => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestartParams {
+    pub component: Component,
+    /// Also restart the components that depend on it
+    #[serde(default)]
+    pub with_dependents: bool,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogsParams {
+    pub component: LogComponent,
+    /// How many of the most recent lines to return
+    #[serde(default = "default_log_lines")]
+    pub lines: usize,
+}
+
+fn default_log_lines() -> usize {
+    50
+}
+
+impl RpcCall {
+    pub fn parse(method: &str, params: &Value) -> Result<Self, RpcError> {
+        match method {
+            "start" => parse_params::<NoParams>(method, params).map(|_| RpcCall::Start),
+            "stop" => parse_params::<NoParams>(method, params).map(|_| RpcCall::Stop),
+            "status" => parse_params::<NoParams>(method, params).map(|_| RpcCall::Status),
+            "maintenance" => parse_params(method, params).map(RpcCall::Maintenance),
+            "restart" => {
+                let params: RestartParams = parse_params(method, params)?;
+                // A restarted sv2-tp has a new authority key that only a restarted pool picks up
+                if params.component == Component::Sv2Tp && !params.with_dependents {
+                    return Err(RpcError::invalid_params(
+                        method,
+                        "restarting sv2-tp changes its authority key, so pool and translator \
+                         must restart with it; set with_dependents",
+                    ));
+                }
+                Ok(RpcCall::Restart(params))
+            }
+            "logs" => parse_params(method, params).map(RpcCall::Logs),
+            "logs_follow" => parse_params(method, params).map(RpcCall::LogsFollow),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method: {}", method),
+            }),
+        }
+    }
+}
+
+/// Params are an object; a missing or empty params value reads as `{}`
+fn parse_params<T: DeserializeOwned>(method: &str, params: &Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => Value::Object(Default::default()),
+        Value::Array(items) if items.is_empty() => Value::Object(Default::default()),
+        Value::Object(_) => params.clone(),
+        other => return Err(RpcError::invalid_params(method, format!("expected an object, got {}", other))),
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(method, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invalid(method: &str, params: Value) -> String {
+        let error = RpcCall::parse(method, &params).unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS, "{}", error.message);
+        error.message
+    }
+
+    #[test]
+    fn test_methods_without_params() {
+        for (method, call) in [("start", RpcCall::Start), ("stop", RpcCall::Stop), ("status", RpcCall::Status)] {
+            // Missing or empty
+            assert_eq!(RpcCall::parse(method, &Value::Null).unwrap(), call);
+            assert_eq!(RpcCall::parse(method, &json!({})).unwrap(), call);
+            assert_eq!(RpcCall::parse(method, &json!([])).unwrap(), call);
+            // Extra
+            assert!(invalid(method, json!({"force": true})).contains("unknown field `force`"));
+            // Wrong type
+            invalid(method, json!("now"));
+            invalid(method, json!([1]));
+        }
+    }
+
+    #[test]
+    fn test_maintenance_params() {
+        assert_eq!(
+            RpcCall::parse("maintenance", &json!({"enabled": true})).unwrap(),
+            RpcCall::Maintenance(MaintenanceParams { enabled: true })
+        );
+        // Missing
+        assert!(invalid("maintenance", Value::Null).contains("missing field `enabled`"));
+        assert!(invalid("maintenance", json!({})).contains("missing field `enabled`"));
+        // Extra
+        assert!(invalid("maintenance", json!({"enabled": true, "minutes": 5})).contains("unknown field `minutes`"));
+        // Wrong type
+        assert!(invalid("maintenance", json!({"enabled": "yes"})).contains("invalid type"));
+        invalid("maintenance", json!(true));
+    }
+
+    #[test]
+    fn test_logs_params() {
+        assert_eq!(
+            RpcCall::parse("logs", &json!({"component": "sv2-tp", "lines": 10})).unwrap(),
+            RpcCall::Logs(LogsParams { component: LogComponent::Sv2Tp, lines: 10 })
+        );
+        assert_eq!(
+            RpcCall::parse("logs_follow", &json!({"component": "pool"})).unwrap(),
+            RpcCall::LogsFollow(LogsParams { component: LogComponent::Pool, lines: 50 })
+        );
+        assert!(invalid("logs", json!({})).contains("missing field `component`"));
+        assert!(invalid("logs", json!({"component": "bitcoind"})).contains("unknown variant `bitcoind`"));
+        assert!(invalid("logs_follow", json!({"component": "pool", "lines": -1})).contains("invalid value"));
+    }
+
+    #[test]
+    fn test_restart_params() {
+        assert_eq!(
+            RpcCall::parse("restart", &json!({"component": "pool"})).unwrap(),
+            RpcCall::Restart(RestartParams { component: Component::Pool, with_dependents: false })
+        );
+        assert_eq!(
+            RpcCall::parse("restart", &json!({"component": "sv2-tp", "with_dependents": true})).unwrap(),
+            RpcCall::Restart(RestartParams { component: Component::Sv2Tp, with_dependents: true })
+        );
+        assert!(invalid("restart", json!({"component": "sv2-tp"})).contains("authority key"));
+        assert!(invalid("restart", json!({"component": "miner"})).contains("unknown variant `miner`"));
+        assert!(invalid("restart", Value::Null).contains("missing field `component`"));
+    }
+
+    #[test]
+    fn test_unknown_method() {
+        let error = RpcCall::parse("reload", &Value::Null).unwrap_err();
+        assert_eq!(error.code, METHOD_NOT_FOUND);
+    }
+}
@@ -0,0 +1,233 @@
+//! Synthetic share probe. Process checks only show that each component is
+//! alive; this pushes one low-difficulty share through the translator like a
+//! miner would and checks that it gets an answer, so a pipeline that is up but
+//! not moving shares is reported too.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::HealthStatus;
+
+/// Worker name the probe authorizes as
+pub const PROBE_WORKER: &str = "sv2d-probe";
+
+/// Request id of the probe's `mining.submit`
+const SUBMIT_ID: u64 = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareProbeStatus {
+    pub health_status: HealthStatus,
+    pub consecutive_failures: u32,
+    pub probes_run: u64,
+    pub last_round_trip_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Turns probe results into a pipeline health status
+#[derive(Debug, Clone)]
+pub struct ShareProbeTracker {
+    /// Consecutive failures before the pipeline is `Critical`
+    failure_threshold: u32,
+    status: ShareProbeStatus,
+}
+
+impl ShareProbeTracker {
+    pub fn new(failure_threshold: u32) -> Self {
+        Self {
+            failure_threshold,
+            status: ShareProbeStatus {
+                health_status: HealthStatus::Unknown,
+                consecutive_failures: 0,
+                probes_run: 0,
+                last_round_trip_ms: None,
+                last_error: None,
+            },
+        }
+    }
+
+    pub fn record_success(&mut self, round_trip: Duration) {
+        self.status.probes_run += 1;
+        self.status.consecutive_failures = 0;
+        self.status.last_round_trip_ms = Some(round_trip.as_millis() as u64);
+        self.status.last_error = None;
+        self.status.health_status = HealthStatus::Healthy;
+    }
+
+    /// Returns true when this failure is the one that makes the pipeline `Critical`
+    pub fn record_failure(&mut self, error: String) -> bool {
+        self.status.probes_run += 1;
+        self.status.consecutive_failures += 1;
+        self.status.last_error = Some(error);
+
+        let was_critical = matches!(self.status.health_status, HealthStatus::Critical);
+        self.status.health_status = if self.status.consecutive_failures >= self.failure_threshold {
+            HealthStatus::Critical
+        } else {
+            HealthStatus::Warning
+        };
+        !was_critical && matches!(self.status.health_status, HealthStatus::Critical)
+    }
+
+    pub fn status(&self) -> &ShareProbeStatus {
+        &self.status
+    }
+}
+
+/// Where to reach a listener bound to `bind_address` from this host
+pub fn probe_address(bind_address: &str) -> String {
+    match bind_address.rsplit_once(':') {
+        Some(("0.0.0.0", port)) | Some(("", port)) => format!("127.0.0.1:{}", port),
+        Some(("[::]", port)) => format!("[::1]:{}", port),
+        _ => bind_address.to_string(),
+    }
+}
+
+/// Submit one share at `address` and wait for the translator to answer it.
+/// A rejection still proves shares are flowing; silence or a dropped
+/// connection does not.
+pub async fn probe_once(address: &str, difficulty: f64, timeout: Duration) -> Result<Duration> {
+    let started = Instant::now();
+    tokio::time::timeout(timeout, submit_probe_share(address, difficulty))
+        .await
+        .map_err(|_| anyhow!("no answer to the probe share within {}s", timeout.as_secs()))??;
+    Ok(started.elapsed())
+}
+
+async fn submit_probe_share(address: &str, difficulty: f64) -> Result<()> {
+    let stream = TcpStream::connect(address)
+        .await
+        .with_context(|| format!("Failed to connect to translator at {}", address))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let encode = |message: Value| format!("{}\n", message);
+    let handshake = [
+        encode(json!({"id": 1, "method": "mining.subscribe", "params": ["sv2d-probe/0.1"]})),
+        encode(json!({"id": 2, "method": "mining.authorize", "params": [PROBE_WORKER, "x"]})),
+        encode(json!({"id": 3, "method": "mining.suggest_difficulty", "params": [difficulty]})),
+    ];
+    for message in handshake {
+        writer.write_all(message.as_bytes()).await?;
+    }
+
+    let mut extranonce2_size = 4;
+    loop {
+        let line = lines.next_line().await?
+            .ok_or_else(|| anyhow!("translator closed the connection"))?;
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        if message.get("id").and_then(Value::as_u64) == Some(1) {
+            if let Some(size) = message.pointer("/result/2").and_then(Value::as_u64) {
+                extranonce2_size = size as usize;
+            }
+            continue;
+        }
+
+        if message.get("id").and_then(Value::as_u64) == Some(SUBMIT_ID) {
+            return Ok(());
+        }
+
+        if message.get("method").and_then(Value::as_str) == Some("mining.notify") {
+            let job_id = message.pointer("/params/0").and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("mining.notify without a job id"))?;
+            let ntime = message.pointer("/params/7").and_then(Value::as_str).unwrap_or("00000000");
+            let submit = json!({
+                "id": SUBMIT_ID,
+                "method": "mining.submit",
+                "params": [PROBE_WORKER, job_id, "00".repeat(extranonce2_size), ntime, "00000000"],
+            });
+            writer.write_all(encode(submit).as_bytes()).await?;
+        }
+
+        if message.get("id").and_then(Value::as_u64) == Some(2)
+            && message.get("result").and_then(Value::as_bool) == Some(false)
+        {
+            bail!("translator refused to authorize the probe worker");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Answers the handshake, sends one job and optionally answers submits
+    async fn fake_translator(answer_submits: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let reply = match request["method"].as_str() {
+                    Some("mining.subscribe") => json!({"id": 1, "result": [[], "f000000f", 4], "error": null}),
+                    Some("mining.authorize") => json!({"id": 2, "result": true, "error": null}),
+                    Some("mining.suggest_difficulty") => json!({
+                        "id": null,
+                        "method": "mining.notify",
+                        "params": ["1", "00", "", "", [], "20000000", "1d00ffff", "65a0c000", true],
+                    }),
+                    Some("mining.submit") if answer_submits => {
+                        json!({"id": request["id"], "result": false, "error": [23, "Low difficulty share", null]})
+                    }
+                    _ => continue,
+                };
+                writer.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn test_tracker_goes_critical_after_consecutive_failures() {
+        let mut tracker = ShareProbeTracker::new(3);
+        assert!(matches!(tracker.status().health_status, HealthStatus::Unknown));
+
+        tracker.record_success(Duration::from_millis(42));
+        assert!(matches!(tracker.status().health_status, HealthStatus::Healthy));
+        assert_eq!(tracker.status().last_round_trip_ms, Some(42));
+
+        assert!(!tracker.record_failure("timeout".to_string()));
+        assert!(!tracker.record_failure("timeout".to_string()));
+        assert!(matches!(tracker.status().health_status, HealthStatus::Warning));
+
+        // Only the transition into Critical is reported
+        assert!(tracker.record_failure("timeout".to_string()));
+        assert!(!tracker.record_failure("timeout".to_string()));
+        assert!(matches!(tracker.status().health_status, HealthStatus::Critical));
+        assert_eq!(tracker.status().consecutive_failures, 4);
+
+        // One good round trip clears it
+        tracker.record_success(Duration::from_millis(10));
+        assert!(matches!(tracker.status().health_status, HealthStatus::Healthy));
+        assert_eq!(tracker.status().consecutive_failures, 0);
+        assert_eq!(tracker.status().last_error, None);
+        assert_eq!(tracker.status().probes_run, 6);
+    }
+
+    #[test]
+    fn test_probe_address() {
+        assert_eq!(probe_address("0.0.0.0:3333"), "127.0.0.1:3333");
+        assert_eq!(probe_address("[::]:3333"), "[::1]:3333");
+        assert_eq!(probe_address("10.0.0.3:3333"), "10.0.0.3:3333");
+    }
+
+    #[tokio::test]
+    async fn test_probe_round_trip() {
+        let address = fake_translator(true).await;
+        assert!(probe_once(&address, 0.001, Duration::from_secs(5)).await.is_ok());
+
+        let address = fake_translator(false).await;
+        let err = probe_once(&address, 0.001, Duration::from_millis(300)).await.unwrap_err();
+        assert!(err.to_string().contains("no answer"));
+    }
+}
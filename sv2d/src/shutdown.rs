@@ -0,0 +1,74 @@
+//! Why the daemon is exiting, and the process exit code that goes with it, so
+//! orchestrators can tell a requested stop from a fatal failure.
+
+use std::fmt;
+use std::process::ExitCode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// `stop` requested over JSON-RPC
+    RpcStop,
+    /// Interrupted by the named signal
+    Signal(&'static str),
+    /// A component exhausted its restarts (exit code 2)
+    ComponentFailed { component: String, detail: String },
+    /// The JSON-RPC server stopped unexpectedly (exit code 3)
+    RpcServerFailed(String),
+}
+
+impl ShutdownReason {
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, ShutdownReason::RpcStop | ShutdownReason::Signal(_))
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ShutdownReason::RpcStop | ShutdownReason::Signal(_) => 0,
+            ShutdownReason::ComponentFailed { .. } => 2,
+            ShutdownReason::RpcServerFailed(_) => 3,
+        }
+    }
+}
+
+impl From<&ShutdownReason> for ExitCode {
+    fn from(reason: &ShutdownReason) -> Self {
+        ExitCode::from(reason.exit_code())
+    }
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownReason::RpcStop => write!(f, "stop requested via RPC"),
+            ShutdownReason::Signal(signal) => write!(f, "received {}", signal),
+            ShutdownReason::ComponentFailed { component, detail } => {
+                write!(f, "{} failed permanently: {}", component, detail)
+            }
+            ShutdownReason::RpcServerFailed(e) => write!(f, "JSON-RPC server failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_stops_exit_zero() {
+        assert_eq!(ShutdownReason::RpcStop.exit_code(), 0);
+        assert_eq!(ShutdownReason::Signal("SIGINT").exit_code(), 0);
+        assert!(!ShutdownReason::Signal("SIGINT").is_fatal());
+    }
+
+    #[test]
+    fn test_failures_exit_non_zero() {
+        let component = ShutdownReason::ComponentFailed {
+            component: "pool".to_string(),
+            detail: "exceeded restart limit".to_string(),
+        };
+        assert!(component.is_fatal());
+        assert_eq!(component.exit_code(), 2);
+        assert_eq!(component.to_string(), "pool failed permanently: exceeded restart limit");
+        assert_eq!(ShutdownReason::RpcServerFailed("bind".to_string()).exit_code(), 3);
+    }
+}
@@ -0,0 +1,347 @@
+//! Dependency-ordered component startup.
+//!
+//! Each step starts as soon as everything it depends on has finished, so
+//! independent work (like writing configs) overlaps with slow readiness
+//! waits instead of queueing behind them. The outcome of every step is
+//! kept in [`StartupProgress`], so a failed startup can be inspected and
+//! resumed from the step that failed.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tracing::info;
+
+/// A startup step and the steps that must complete before it begins
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub name: &'static str,
+    pub depends_on: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepState {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Stopped because another step failed while this one was running
+    Aborted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepStatus {
+    pub name: &'static str,
+    pub state: StepState,
+    pub error: Option<String>,
+}
+
+/// Outcome of each startup step across startup attempts
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupProgress {
+    pub attempts: u32,
+    pub steps: Vec<StepStatus>,
+}
+
+impl StartupProgress {
+    pub fn new(steps: &[Step]) -> Self {
+        Self {
+            attempts: 0,
+            steps: steps
+                .iter()
+                .map(|step| StepStatus { name: step.name, state: StepState::Pending, error: None })
+                .collect(),
+        }
+    }
+
+    pub fn state(&self, name: &str) -> StepState {
+        self.steps
+            .iter()
+            .find(|step| step.name == name)
+            .map_or(StepState::Pending, |step| step.state)
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.steps.iter().any(|step| step.state == StepState::Running)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.steps.iter().all(|step| step.state == StepState::Completed)
+    }
+
+    /// The step that stopped the last attempt and its error
+    pub fn failure(&self) -> Option<&StepStatus> {
+        self.steps.iter().find(|step| step.state == StepState::Failed)
+    }
+
+    fn set(&mut self, name: &'static str, state: StepState, error: Option<String>) {
+        match self.steps.iter_mut().find(|step| step.name == name) {
+            Some(step) => {
+                step.state = state;
+                step.error = error;
+            }
+            None => self.steps.push(StepStatus { name, state, error }),
+        }
+    }
+
+    fn abort_running(&mut self) {
+        for step in &mut self.steps {
+            if step.state == StepState::Running {
+                step.state = StepState::Aborted;
+            }
+        }
+    }
+}
+
+/// Run `steps`, calling `start` for each once its dependencies have completed.
+///
+/// Returns step names in completion order. The first failure aborts any steps
+/// still running and is returned with the step name attached.
+pub async fn run<F, Fut>(steps: &[Step], start: F) -> Result<Vec<&'static str>>
+where
+    F: Fn(&'static str) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    run_tracked(steps, &RwLock::new(StartupProgress::new(steps)), start).await
+}
+
+/// Like [`run`], recording each step's outcome in `progress`. Steps that
+/// completed in an earlier attempt are skipped, so calling this again after a
+/// failure retries from the failed step.
+pub async fn run_tracked<F, Fut>(
+    steps: &[Step],
+    progress: &RwLock<StartupProgress>,
+    start: F,
+) -> Result<Vec<&'static str>>
+where
+    F: Fn(&'static str) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    for step in steps {
+        for dep in step.depends_on {
+            if !steps.iter().any(|s| s.name == *dep) {
+                return Err(anyhow!("Step '{}' depends on unknown step '{}'", step.name, dep));
+            }
+        }
+    }
+
+    let mut completed: Vec<&'static str> = {
+        let mut progress = progress.write().await;
+        if progress.in_progress() {
+            return Err(anyhow!("Startup is already in progress"));
+        }
+        progress.attempts += 1;
+        steps
+            .iter()
+            .map(|step| step.name)
+            .filter(|name| progress.state(name) == StepState::Completed)
+            .collect()
+    };
+    let mut started: HashSet<&'static str> = completed.iter().copied().collect();
+    let mut running = JoinSet::new();
+
+    loop {
+        for step in steps {
+            let ready = !started.contains(step.name)
+                && step.depends_on.iter().all(|dep| completed.contains(dep));
+            if ready {
+                started.insert(step.name);
+                let name = step.name;
+                progress.write().await.set(name, StepState::Running, None);
+                let future = start(name);
+                running.spawn(async move { (name, future.await) });
+            }
+        }
+
+        match running.join_next().await {
+            Some(Ok((name, Ok(())))) => {
+                info!("Startup step '{}' complete", name);
+                progress.write().await.set(name, StepState::Completed, None);
+                completed.push(name);
+            }
+            Some(Ok((name, Err(e)))) => {
+                running.abort_all();
+                let mut progress = progress.write().await;
+                progress.set(name, StepState::Failed, Some(format!("{:#}", e)));
+                progress.abort_running();
+                return Err(e.context(format!("Startup step '{}' failed", name)));
+            }
+            Some(Err(e)) => {
+                running.abort_all();
+                progress.write().await.abort_running();
+                return Err(anyhow!("Startup step panicked: {}", e));
+            }
+            None => break,
+        }
+    }
+
+    if completed.len() != steps.len() {
+        let blocked: Vec<_> = steps
+            .iter()
+            .filter(|s| !started.contains(s.name))
+            .map(|s| s.name)
+            .collect();
+        return Err(anyhow!("Startup steps {:?} have cyclic dependencies", blocked));
+    }
+
+    Ok(completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Start(&'static str),
+        Finish(&'static str),
+    }
+
+    /// Run `steps` where each step sleeps for its given delay, recording events
+    async fn run_recorded(
+        steps: &[Step],
+        delays: &'static [(&'static str, u64)],
+    ) -> (Result<Vec<&'static str>>, Vec<Event>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&events);
+
+        let result = run(steps, move |name| {
+            let events = Arc::clone(&recorder);
+            let delay = delays.iter().find(|(n, _)| *n == name).map_or(0, |(_, d)| *d);
+            async move {
+                events.lock().unwrap().push(Event::Start(name));
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                events.lock().unwrap().push(Event::Finish(name));
+                Ok(())
+            }
+        })
+        .await;
+
+        let events = std::mem::take(&mut *events.lock().unwrap());
+        (result, events)
+    }
+
+    fn position(events: &[Event], event: Event) -> usize {
+        events.iter().position(|e| *e == event).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_start_order_respects_dependencies() {
+        let steps = [
+            Step { name: "bitcoin", depends_on: &[] },
+            Step { name: "sv2-tp", depends_on: &["bitcoin"] },
+            Step { name: "pool", depends_on: &["sv2-tp"] },
+            Step { name: "translator-config", depends_on: &[] },
+            Step { name: "translator", depends_on: &["pool", "translator-config"] },
+        ];
+
+        let (result, events) = run_recorded(
+            &steps,
+            &[("bitcoin", 30), ("sv2-tp", 30), ("pool", 10), ("translator-config", 5)],
+        )
+        .await;
+
+        assert_eq!(result.unwrap().len(), steps.len());
+        for step in &steps {
+            let start = position(&events, Event::Start(step.name));
+            for dep in step.depends_on {
+                assert!(
+                    position(&events, Event::Finish(dep)) < start,
+                    "{} started before {} finished",
+                    step.name,
+                    dep
+                );
+            }
+        }
+
+        // The independent config step ran while bitcoin was still starting
+        assert!(
+            position(&events, Event::Finish("translator-config"))
+                < position(&events, Event::Finish("bitcoin"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failure_stops_dependents() {
+        let steps = [
+            Step { name: "a", depends_on: &[] },
+            Step { name: "b", depends_on: &["a"] },
+        ];
+
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&started);
+        let result = run(&steps, move |name| {
+            recorder.lock().unwrap().push(name);
+            async move { Err(anyhow!("boom")) }
+        })
+        .await;
+
+        assert!(result.unwrap_err().to_string().contains("'a'"));
+        assert_eq!(*started.lock().unwrap(), vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_resumes_from_failed_step() {
+        let steps = [
+            Step { name: "a", depends_on: &[] },
+            Step { name: "b", depends_on: &["a"] },
+            Step { name: "c", depends_on: &["b"] },
+        ];
+        let progress = RwLock::new(StartupProgress::new(&steps));
+
+        let result = run_tracked(&steps, &progress, |name| async move {
+            match name {
+                "b" => Err(anyhow!("port in use")),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.is_err());
+
+        {
+            let progress = progress.read().await;
+            assert_eq!(progress.state("a"), StepState::Completed);
+            assert_eq!(progress.state("c"), StepState::Pending);
+            let failure = progress.failure().unwrap();
+            assert_eq!(failure.name, "b");
+            assert_eq!(failure.error.as_deref(), Some("port in use"));
+            assert!(!progress.is_complete());
+        }
+
+        // The retry starts only the failed step and what depends on it
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&started);
+        run_tracked(&steps, &progress, move |name| {
+            recorder.lock().unwrap().push(name);
+            async move { Ok(()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*started.lock().unwrap(), vec!["b", "c"]);
+        let progress = progress.read().await;
+        assert!(progress.is_complete());
+        assert!(progress.failure().is_none());
+        assert_eq!(progress.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cycle_and_unknown_dependency_rejected() {
+        let cyclic = [
+            Step { name: "a", depends_on: &["b"] },
+            Step { name: "b", depends_on: &["a"] },
+        ];
+        let (result, events) = run_recorded(&cyclic, &[]).await;
+        assert!(result.is_err());
+        assert!(events.is_empty());
+
+        let unknown = [Step { name: "a", depends_on: &["missing"] }];
+        let (result, _) = run_recorded(&unknown, &[]).await;
+        assert!(result.unwrap_err().to_string().contains("missing"));
+    }
+}
@@ -0,0 +1,119 @@
+//! Reads sv2-tp's log to tell why it isn't listening yet: still waiting for
+//! Bitcoin Core's IPC socket (worth waiting longer), or failed outright.
+
+/// What the most recent relevant sv2-tp log line says about startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sv2TpLogState {
+    /// sv2-tp is up but can't reach Bitcoin Core over IPC yet
+    WaitingForIpc,
+    /// sv2-tp logged a fatal error; carries the offending line
+    Failed(String),
+    /// Nothing conclusive in the log
+    Unknown,
+}
+
+const IPC_MARKERS: &[&str] = &["ipc", "node.sock", "bitcoin-node"];
+const IPC_CONNECTED_MARKERS: &[&str] = &["connected to bitcoin", "ipc connected", "connection established"];
+const FATAL_MARKERS: &[&str] = &[
+    "error:",
+    "exception",
+    "terminate called",
+    "panicked",
+    "segmentation fault",
+    "aborted",
+    "fatal",
+];
+
+/// Classify sv2-tp log output, giving precedence to the newest relevant line
+pub fn classify(log: &str) -> Sv2TpLogState {
+    for line in log.lines().rev() {
+        let lower = line.to_lowercase();
+
+        if IPC_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            if IPC_CONNECTED_MARKERS.iter().any(|marker| lower.contains(marker)) {
+                // IPC is fine, so whatever is slow it isn't the node connection
+                return Sv2TpLogState::Unknown;
+            }
+            return Sv2TpLogState::WaitingForIpc;
+        }
+
+        if FATAL_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            return Sv2TpLogState::Failed(line.trim().to_string());
+        }
+    }
+
+    Sv2TpLogState::Unknown
+}
+
+/// Log content written after `offset`, so earlier runs appended to the same
+/// file don't affect classification
+pub fn read_since(path: &str, offset: u64) -> String {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            let start = usize::try_from(offset).unwrap_or(usize::MAX).min(bytes.len());
+            String::from_utf8_lossy(&bytes[start..]).into_owned()
+        }
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAITING_FOR_IPC: &str = "\
+2025-01-10T12:00:00Z Sv2 Template Provider starting
+2025-01-10T12:00:00Z Using data directory /tmp/bitcoin_signet
+2025-01-10T12:00:01Z Connecting to bitcoin-node via IPC socket /tmp/bitcoin_signet/signet/node.sock
+2025-01-10T12:00:01Z Error: Unable to connect to IPC socket: No such file or directory, retrying in 5s
+2025-01-10T12:00:06Z Waiting for bitcoin-node IPC...
+";
+
+    const CRASHED: &str = "\
+2025-01-10T12:00:00Z Sv2 Template Provider starting
+2025-01-10T12:00:00Z Using data directory /tmp/bitcoin_signet
+2025-01-10T12:00:00Z Error: Unable to bind to 0.0.0.0:38336 on this computer. Address already in use
+";
+
+    const IPC_UP_THEN_CRASHED: &str = "\
+2025-01-10T12:00:01Z Waiting for bitcoin-node IPC...
+2025-01-10T12:00:09Z Connected to bitcoin-node via IPC
+2025-01-10T12:00:10Z terminate called after throwing an instance of 'std::runtime_error'
+";
+
+    #[test]
+    fn test_detects_waiting_for_ipc() {
+        assert_eq!(classify(WAITING_FOR_IPC), Sv2TpLogState::WaitingForIpc);
+    }
+
+    #[test]
+    fn test_detects_crash() {
+        match classify(CRASHED) {
+            Sv2TpLogState::Failed(line) => assert!(line.contains("Address already in use")),
+            other => panic!("expected failure, got {:?}", other),
+        }
+        assert!(matches!(classify(IPC_UP_THEN_CRASHED), Sv2TpLogState::Failed(_)));
+    }
+
+    #[test]
+    fn test_ipc_connected_is_not_waiting() {
+        let log = "Waiting for bitcoin-node IPC...\nConnected to bitcoin-node via IPC\n";
+        assert_eq!(classify(log), Sv2TpLogState::Unknown);
+        assert_eq!(classify(""), Sv2TpLogState::Unknown);
+    }
+
+    #[test]
+    fn test_read_since_skips_earlier_runs() {
+        let path = std::env::temp_dir().join(format!("sv2d-tp-log-{}.log", std::process::id()));
+        std::fs::write(&path, CRASHED).unwrap();
+        let offset = CRASHED.len() as u64;
+        std::fs::write(&path, format!("{}{}", CRASHED, WAITING_FOR_IPC)).unwrap();
+
+        let path = path.to_str().unwrap();
+        assert_eq!(classify(&read_since(path, offset)), Sv2TpLogState::WaitingForIpc);
+        assert_eq!(read_since(path, u64::MAX), "");
+
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(read_since(path, 0), "");
+    }
+}
@@ -0,0 +1,109 @@
+//! Translator vardiff settings from the miners sv2d has actually seen. The
+//! translator assumes `min_individual_miner_hashrate` for every new
+//! connection, so a figure far from the fleet's means a long ramp before
+//! shares arrive at `shares_per_minute`. Until any miner has reported a
+//! hashrate, the `[translator]` settings are used as they are.
+
+use anyhow::{Context, Result};
+use sv2_core::scanner::recommended_shares_per_minute;
+use toml_edit::DocumentMut;
+
+use crate::TranslatorConfig;
+
+/// `[downstream_difficulty_config]` values written for translator_sv2
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FleetDifficulty {
+    pub min_individual_miner_hashrate: f64,
+    pub shares_per_minute: f64,
+}
+
+impl FleetDifficulty {
+    /// Start vardiff at the slowest miner's hashrate, so none of them begins
+    /// above what it can find, with the share rate the scanner recommends
+    /// for the fleet's total
+    pub fn from_hashrates(hashrates: &[f64], configured: &TranslatorConfig) -> Self {
+        let hashrates: Vec<f64> = hashrates.iter().copied().filter(|h| h.is_finite() && *h > 0.0).collect();
+        match hashrates.iter().copied().reduce(f64::min) {
+            Some(slowest) => Self {
+                min_individual_miner_hashrate: slowest,
+                shares_per_minute: recommended_shares_per_minute(hashrates.iter().sum()),
+            },
+            None => Self {
+                min_individual_miner_hashrate: configured.min_individual_miner_hashrate,
+                shares_per_minute: configured.shares_per_minute,
+            },
+        }
+    }
+
+    /// `translator_config` with its `[downstream_difficulty_config]` set to
+    /// these values, leaving everything else as written
+    pub fn apply(&self, translator_config: &str) -> Result<String> {
+        let mut document: DocumentMut = translator_config.parse().context("Invalid translator config")?;
+        let section = document["downstream_difficulty_config"]
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .context("downstream_difficulty_config is not a table")?;
+        section.insert("min_individual_miner_hashrate", toml_edit::value(self.min_individual_miner_hashrate));
+        section.insert("shares_per_minute", toml_edit::value(self.shares_per_minute));
+        Ok(document.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured() -> TranslatorConfig {
+        TranslatorConfig {
+            bind_address: vec!["0.0.0.0:34255".to_string()],
+            min_extranonce2_size: 8,
+            shares_per_minute: 5.0,
+            min_individual_miner_hashrate: 500e9,
+        }
+    }
+
+    #[test]
+    fn test_no_known_miners_keeps_configured_values() {
+        let difficulty = FleetDifficulty::from_hashrates(&[0.0], &configured());
+        assert_eq!(difficulty, FleetDifficulty { min_individual_miner_hashrate: 500e9, shares_per_minute: 5.0 });
+    }
+
+    #[test]
+    fn test_fleet_sets_slowest_hashrate_and_share_rate() {
+        let difficulty = FleetDifficulty::from_hashrates(&[1.2e12, 4.8e12, 650e9], &configured());
+        assert_eq!(difficulty.min_individual_miner_hashrate, 650e9);
+        assert_eq!(difficulty.shares_per_minute, 5.0);
+
+        let small = FleetDifficulty::from_hashrates(&[400e9], &configured());
+        assert_eq!(small.shares_per_minute, 3.0);
+    }
+
+    #[test]
+    fn test_apply_rewrites_only_the_difficulty_section() {
+        let config = r#"# Translator
+downstream_port = 34255
+
+[downstream_difficulty_config]
+min_individual_miner_hashrate = 500_000_000_000.0
+shares_per_minute = 6.0
+enable_vardiff = true
+
+[[upstreams]]
+address = "127.0.0.1"
+authority_pubkey = "9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72"
+"#;
+        let difficulty = FleetDifficulty { min_individual_miner_hashrate: 650e9, shares_per_minute: 3.0 };
+        let updated = difficulty.apply(config).unwrap();
+        assert!(updated.starts_with("# Translator\n"));
+
+        let value: toml::Value = toml::from_str(&updated).unwrap();
+        let section = &value["downstream_difficulty_config"];
+        assert_eq!(section["min_individual_miner_hashrate"].as_float(), Some(650e9));
+        assert_eq!(section["shares_per_minute"].as_float(), Some(3.0));
+        assert_eq!(section["enable_vardiff"].as_bool(), Some(true));
+        assert_eq!(
+            value["upstreams"][0]["authority_pubkey"].as_str(),
+            Some("9auqWEzQDVyd2oe1JVGFLMLHZtCo2FFqZwtKA5gd9xbuEu7PH72")
+        );
+    }
+}